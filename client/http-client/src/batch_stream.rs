@@ -0,0 +1,293 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any
+// person obtaining a copy of this software and associated
+// documentation files (the "Software"), to deal in the
+// Software without restriction, including without
+// limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software
+// is furnished to do so, subject to the following
+// conditions:
+//
+// The above copyright notice and this permission notice
+// shall be included in all copies or substantial portions
+// of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF
+// ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED
+// TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A
+// PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT
+// SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY
+// CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+// IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Reads the responses of a batch request as they are received.
+
+use std::ops::Range;
+use std::sync::Arc;
+
+use hyper::body::Bytes;
+use jsonrpsee_core::client::{BatchEntry, Error, RequestIdManager};
+use jsonrpsee_core::{BoxError, JsonRawValue};
+use jsonrpsee_types::{InvalidRequestId, Response, ResponseSuccess};
+use serde::de::DeserializeOwned;
+use tokio::sync::OwnedSemaphorePermit;
+
+use crate::transport::ResponseBody;
+
+/// The responses to the method calls of a batch request which are read from the response body.
+#[derive(Debug)]
+pub(crate) struct BatchStream<B> {
+	pub(crate) body: ResponseBody<B>,
+	pub(crate) splitter: JsonArraySplitter,
+	pub(crate) id_manager: Arc<RequestIdManager>,
+	pub(crate) id_range: Range<u64>,
+	/// The index in the batch of each method call, in the order of their ids.
+	pub(crate) calls: Vec<usize>,
+	/// Whether the method calls have been answered, in the order of their ids.
+	pub(crate) answered: Vec<bool>,
+	pub(crate) _permit: Option<OwnedSemaphorePermit>,
+}
+
+impl<B> BatchStream<B>
+where
+	B: http_body::Body<Data = Bytes> + Unpin,
+	B::Error: Into<BoxError>,
+{
+	/// Read the next response, `None` once the response array has been read in full.
+	pub(crate) async fn next_entry<R: DeserializeOwned>(
+		&mut self,
+	) -> Result<Option<(usize, BatchEntry<'static, R>)>, Error> {
+		loop {
+			if let Some(rp) = self.splitter.next_element().map_err(Error::ParseError)? {
+				return self.decode(&rp).map(Some);
+			}
+
+			if self.splitter.is_closed() {
+				return Ok(None);
+			}
+
+			match self.body.next_chunk().await.map_err(|e| Error::Transport(e.into()))? {
+				Some(chunk) => self.splitter.push(&chunk),
+				None => {
+					let eof = std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "The batch response ended early");
+					return Err(Error::ParseError(serde_json::Error::io(eof)));
+				}
+			}
+		}
+	}
+
+	fn decode<R: DeserializeOwned>(&mut self, rp: &[u8]) -> Result<(usize, BatchEntry<'static, R>), Error> {
+		let rp: Response<&JsonRawValue> = serde_json::from_slice(rp).map_err(Error::ParseError)?;
+		let id = self.id_manager.as_id_generator().parse_id(&rp.id)?;
+
+		let pos = id
+			.checked_sub(self.id_range.start)
+			.and_then(|p| usize::try_from(p).ok())
+			.filter(|p| self.answered.get(*p) == Some(&false))
+			.ok_or_else(|| InvalidRequestId::NotPendingRequest(id.to_string()))?;
+		self.answered[pos] = true;
+
+		let entry = match ResponseSuccess::try_from(rp) {
+			Ok(r) => Ok(serde_json::from_str(r.result.get()).map_err(Error::ParseError)?),
+			Err(err) => Err(err),
+		};
+
+		Ok((self.calls[pos], entry))
+	}
+}
+
+/// Splits a JSON array which is received in chunks into its elements.
+///
+/// The elements are only delimited, they are validated once they are parsed.
+#[derive(Debug, Default)]
+pub(crate) struct JsonArraySplitter {
+	buf: Vec<u8>,
+	/// Position in `buf` up to which it has been scanned.
+	pos: usize,
+	/// Position in `buf` before which the elements have been taken.
+	consumed: usize,
+	state: SplitState,
+	/// Nesting depth of the objects and arrays in the current element.
+	depth: usize,
+	in_string: bool,
+	escaped: bool,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum SplitState {
+	/// Before the opening bracket of the array.
+	#[default]
+	Start,
+	/// Between two elements of the array.
+	Between,
+	/// In the element which starts at the given position.
+	Element(usize),
+	/// After the closing bracket of the array.
+	Closed,
+}
+
+impl JsonArraySplitter {
+	/// Append the next chunk of the array.
+	pub(crate) fn push(&mut self, chunk: &[u8]) {
+		// The elements which have been taken are only removed once they make up half of the buffer,
+		// such that the buffer isn't shifted for every element.
+		if self.consumed > self.buf.len() / 2 {
+			self.buf.drain(..self.consumed);
+			self.pos -= self.consumed;
+			if let SplitState::Element(start) = &mut self.state {
+				*start -= self.consumed;
+			}
+			self.consumed = 0;
+		}
+
+		self.buf.extend_from_slice(chunk);
+	}
+
+	/// Returns whether the closing bracket of the array has been read.
+	pub(crate) fn is_closed(&self) -> bool {
+		self.state == SplitState::Closed
+	}
+
+	/// Take the next element which has been received in full.
+	pub(crate) fn next_element(&mut self) -> Result<Option<Vec<u8>>, serde_json::Error> {
+		while self.pos < self.buf.len() {
+			let byte = self.buf[self.pos];
+
+			match self.state {
+				SplitState::Start => match byte {
+					b'[' => self.state = SplitState::Between,
+					b if b.is_ascii_whitespace() => (),
+					_ => return Err(serde::de::Error::custom("The batch response is not a JSON array")),
+				},
+				SplitState::Between => match byte {
+					b']' => self.state = SplitState::Closed,
+					b',' => (),
+					b if b.is_ascii_whitespace() => (),
+					_ => {
+						self.state = SplitState::Element(self.pos);
+						continue;
+					}
+				},
+				SplitState::Element(start) => {
+					if let Some(end) = self.element_end(byte) {
+						let element = self.buf[start..end].to_vec();
+						self.consumed = end;
+						self.pos = end;
+						self.state = SplitState::Between;
+						return Ok(Some(element));
+					}
+				}
+				SplitState::Closed => return Ok(None),
+			}
+
+			self.pos += 1;
+		}
+
+		Ok(None)
+	}
+
+	/// Scan the next byte of an element, returns the end of the element if it's complete.
+	fn element_end(&mut self, byte: u8) -> Option<usize> {
+		if self.in_string {
+			if self.escaped {
+				self.escaped = false;
+			} else if byte == b'\\' {
+				self.escaped = true;
+			} else if byte == b'"' {
+				self.in_string = false;
+				if self.depth == 0 {
+					return Some(self.pos + 1);
+				}
+			}
+			return None;
+		}
+
+		match byte {
+			b'"' => self.in_string = true,
+			b'{' | b'[' => self.depth += 1,
+			b'}' | b']' if self.depth > 0 => {
+				self.depth -= 1;
+				if self.depth == 0 {
+					return Some(self.pos + 1);
+				}
+			}
+			// The end of a number, `true`, `false` or `null`.
+			b',' | b']' if self.depth == 0 => return Some(self.pos),
+			b if b.is_ascii_whitespace() && self.depth == 0 => return Some(self.pos),
+			_ => (),
+		}
+
+		None
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::JsonArraySplitter;
+
+	fn split(chunks: &[&str]) -> Vec<String> {
+		let mut splitter = JsonArraySplitter::default();
+		let mut elements = Vec::new();
+
+		for chunk in chunks {
+			splitter.push(chunk.as_bytes());
+			while let Some(element) = splitter.next_element().unwrap() {
+				elements.push(String::from_utf8(element).unwrap());
+			}
+		}
+
+		assert!(splitter.is_closed());
+		elements
+	}
+
+	#[test]
+	fn splits_array_in_chunks() {
+		let array = r#" [{"id":1,"result":"a]\"}"}, {"id":2,"result":[1,{"b":[]}]} ,3,"x,y" , null]"#;
+		let expected = [r#"{"id":1,"result":"a]\"}"}"#, r#"{"id":2,"result":[1,{"b":[]}]}"#, "3", r#""x,y""#, "null"];
+
+		assert_eq!(split(&[array]), expected);
+
+		// Every possible split into two chunks.
+		for i in 0..array.len() {
+			assert_eq!(split(&[&array[..i], &array[i..]]), expected, "split at {i}");
+		}
+
+		assert_eq!(split(&["[", "]"]), Vec::<String>::new());
+	}
+
+	#[test]
+	fn element_is_returned_once_complete() {
+		let mut splitter = JsonArraySplitter::default();
+		splitter.push(br#"[{"id":1}"#);
+		assert_eq!(splitter.next_element().unwrap().unwrap(), br#"{"id":1}"#);
+		assert_eq!(splitter.next_element().unwrap(), None);
+		assert!(!splitter.is_closed());
+	}
+
+	#[test]
+	fn taken_elements_are_removed_from_the_buffer() {
+		let mut splitter = JsonArraySplitter::default();
+		splitter.push(b"[");
+
+		for i in 0..1000 {
+			splitter.push(format!(r#"{{"id":{i}}},"#).as_bytes());
+			assert_eq!(splitter.next_element().unwrap().unwrap(), format!(r#"{{"id":{i}}}"#).as_bytes());
+			assert!(splitter.buf.len() < 32);
+		}
+
+		splitter.push(b"]");
+		assert_eq!(splitter.next_element().unwrap(), None);
+		assert!(splitter.is_closed());
+	}
+
+	#[test]
+	fn rejects_other_values() {
+		let mut splitter = JsonArraySplitter::default();
+		splitter.push(br#"{"jsonrpc":"2.0","error":{"code":-32600,"message":"Invalid request"},"id":null}"#);
+		assert!(splitter.next_element().is_err());
+	}
+}
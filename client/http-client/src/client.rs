@@ -24,24 +24,26 @@
 // IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
 // DEALINGS IN THE SOFTWARE.
 
-use std::borrow::Cow as StdCow;
 use std::fmt;
 use std::sync::Arc;
 use std::time::Duration;
 
+use crate::batch_stream::{BatchStream, JsonArraySplitter};
 use crate::transport::{self, Error as TransportError, HttpBackend, HttpTransportClient, HttpTransportClientBuilder};
-use crate::types::{NotificationSer, RequestSer, Response};
+use crate::types::Response;
 use crate::{HttpRequest, HttpResponse};
-use async_trait::async_trait;
 use hyper::body::Bytes;
 use hyper::http::HeaderMap;
 use jsonrpsee_core::client::{
-	generate_batch_id_range, BatchResponse, ClientT, Error, IdKind, RequestIdManager, Subscription, SubscriptionClientT,
+	generate_batch_id_range_with, serialize_batch_request_with, serialize_notification, serialize_request,
+	BatchResponse, BatchResponseStream, ClientT, Error, IdKind, RequestIdGenerator, RequestIdManager, Subscription,
+	SubscriptionClientT,
 };
+use jsonrpsee_core::codec::Codec;
 use jsonrpsee_core::params::BatchRequestBuilder;
 use jsonrpsee_core::traits::ToRpcParams;
-use jsonrpsee_core::{BoxError, JsonRawValue, TEN_MB_SIZE_BYTES};
-use jsonrpsee_types::{ErrorObject, InvalidRequestId, ResponseSuccess, TwoPointZero};
+use jsonrpsee_core::{BoxError, JsonRawValue, Limits, TEN_MB_SIZE_BYTES};
+use jsonrpsee_types::{ErrorObject, InvalidRequestId, ResponseSuccess};
 use serde::de::DeserializeOwned;
 use tokio::sync::Semaphore;
 use tower::layer::util::Identity;
@@ -81,15 +83,39 @@ pub struct HttpClientBuilder<L = Identity> {
 	request_timeout: Duration,
 	#[cfg(feature = "tls")]
 	certificate_store: CertificateStore,
-	id_kind: IdKind,
+	id_generator: Arc<dyn RequestIdGenerator>,
 	max_log_length: u32,
 	headers: HeaderMap,
 	service_builder: tower::ServiceBuilder<L>,
 	tcp_no_delay: bool,
+	propagate_deadline: bool,
 	max_concurrent_requests: Option<usize>,
+	codec: Option<&'static dyn Codec>,
+	json_rpc_v1: bool,
 }
 
 impl<L> HttpClientBuilder<L> {
+	/// Apply a profile of [`Limits`] at once.
+	///
+	/// It sets the max request and response size, the max log length and uses [`Limits::timeout`]
+	/// as request timeout. The max batch length isn't used by the client.
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use jsonrpsee_core::Limits;
+	/// use jsonrpsee_http_client::HttpClientBuilder;
+	///
+	/// let client = HttpClientBuilder::default().with_limits(Limits::strict()).build("http://localhost:9944");
+	/// ```
+	pub fn with_limits(mut self, limits: Limits) -> Self {
+		self.max_request_size = limits.max_request_size;
+		self.max_response_size = limits.max_response_size;
+		self.max_log_length = limits.max_log_length;
+		self.request_timeout = limits.timeout;
+		self
+	}
+
 	/// Set the maximum size of a request body in bytes. Default is 10 MiB.
 	pub fn max_request_size(mut self, size: u32) -> Self {
 		self.max_request_size = size;
@@ -187,7 +213,14 @@ impl<L> HttpClientBuilder<L> {
 
 	/// Configure the data type of the request object ID (default is number).
 	pub fn id_format(mut self, id_kind: IdKind) -> Self {
-		self.id_kind = id_kind;
+		self.id_generator = Arc::new(id_kind);
+		self
+	}
+
+	/// Configure a custom generator of the request object IDs, which replaces
+	/// the format set by [`HttpClientBuilder::id_format`].
+	pub fn id_generator<G: RequestIdGenerator + 'static>(mut self, id_generator: G) -> Self {
+		self.id_generator = Arc::new(id_generator);
 		self
 	}
 
@@ -215,12 +248,43 @@ impl<L> HttpClientBuilder<L> {
 		self
 	}
 
+	/// Send the request timeout to the server in the
+	/// [`DEADLINE_HEADER`](jsonrpsee_core::http_helpers::DEADLINE_HEADER), such that the methods
+	/// can stop working on calls which the client has already given up on.
+	///
+	/// Calls made with [`ClientT::request_with_timeout`] send their own timeout instead.
+	///
+	/// Default is `false`.
+	pub fn set_propagate_deadline(mut self, enabled: bool) -> Self {
+		self.propagate_deadline = enabled;
+		self
+	}
+
+	/// Encode requests and responses with the `codec` instead of JSON, see [`jsonrpsee_core::codec`] for details.
+	///
+	/// The server must support the codec, otherwise the requests are rejected.
+	///
+	/// Default is JSON.
+	pub fn set_codec(mut self, codec: &'static dyn Codec) -> Self {
+		self.codec = Some(codec);
+		self
+	}
+
+	/// Send [JSON-RPC 1.0](https://www.jsonrpc.org/specification_v1) requests and accept 1.0 responses,
+	/// for instance to call legacy daemons such as bitcoind, see [`jsonrpsee_types::v1`] for details.
+	///
+	/// Default is `false`.
+	pub fn set_json_rpc_v1(mut self, enabled: bool) -> Self {
+		self.json_rpc_v1 = enabled;
+		self
+	}
+
 	/// Set custom tower middleware.
 	pub fn set_http_middleware<T>(self, service_builder: tower::ServiceBuilder<T>) -> HttpClientBuilder<T> {
 		HttpClientBuilder {
 			#[cfg(feature = "tls")]
 			certificate_store: self.certificate_store,
-			id_kind: self.id_kind,
+			id_generator: self.id_generator,
 			headers: self.headers,
 			max_log_length: self.max_log_length,
 			max_request_size: self.max_request_size,
@@ -228,7 +292,10 @@ impl<L> HttpClientBuilder<L> {
 			service_builder,
 			request_timeout: self.request_timeout,
 			tcp_no_delay: self.tcp_no_delay,
+			propagate_deadline: self.propagate_deadline,
 			max_concurrent_requests: self.max_concurrent_requests,
+			codec: self.codec,
+			json_rpc_v1: self.json_rpc_v1,
 		}
 	}
 }
@@ -249,11 +316,14 @@ where
 			request_timeout,
 			#[cfg(feature = "tls")]
 			certificate_store,
-			id_kind,
+			id_generator,
 			headers,
 			max_log_length,
 			service_builder,
 			tcp_no_delay,
+			propagate_deadline,
+			codec,
+			json_rpc_v1,
 			..
 		} = self;
 
@@ -266,6 +336,8 @@ where
 			service_builder,
 			#[cfg(feature = "tls")]
 			certificate_store,
+			codec,
+			json_rpc_v1,
 		}
 		.build(target)
 		.map_err(|e| Error::Transport(e.into()))?;
@@ -276,9 +348,10 @@ where
 
 		Ok(HttpClient {
 			transport,
-			id_manager: Arc::new(RequestIdManager::new(id_kind)),
+			id_manager: Arc::new(RequestIdManager::with_generator(id_generator)),
 			request_timeout,
 			request_guard,
+			propagate_deadline,
 		})
 	}
 }
@@ -291,12 +364,15 @@ impl Default for HttpClientBuilder<Identity> {
 			request_timeout: Duration::from_secs(60),
 			#[cfg(feature = "tls")]
 			certificate_store: CertificateStore::Native,
-			id_kind: IdKind::Number,
+			id_generator: Arc::new(IdKind::Number),
 			max_log_length: 4096,
 			headers: HeaderMap::new(),
 			service_builder: tower::ServiceBuilder::new(),
 			tcp_no_delay: true,
+			propagate_deadline: false,
 			max_concurrent_requests: None,
+			codec: None,
+			json_rpc_v1: false,
 		}
 	}
 }
@@ -319,6 +395,8 @@ pub struct HttpClient<S = HttpBackend> {
 	id_manager: Arc<RequestIdManager>,
 	/// Concurrent requests limit guard.
 	request_guard: Option<Arc<Semaphore>>,
+	/// Whether the timeout of the requests is sent in the deadline header.
+	propagate_deadline: bool,
 }
 
 impl HttpClient<HttpBackend> {
@@ -328,8 +406,7 @@ impl HttpClient<HttpBackend> {
 	}
 }
 
-#[async_trait]
-impl<B, S> ClientT for HttpClient<S>
+impl<B, S> HttpClient<S>
 where
 	S: Service<HttpRequest, Response = HttpResponse<B>, Error = TransportError> + Send + Sync + Clone,
 	<S as Service<HttpRequest>>::Future: Send,
@@ -337,30 +414,13 @@ where
 	B::Error: Into<BoxError>,
 	B::Data: Send,
 {
-	#[instrument(name = "notification", skip(self, params), level = "trace")]
-	async fn notification<Params>(&self, method: &str, params: Params) -> Result<(), Error>
-	where
-		Params: ToRpcParams + Send,
-	{
-		let _permit = match self.request_guard.as_ref() {
-			Some(permit) => permit.acquire().await.ok(),
-			None => None,
-		};
-		let params = params.to_rpc_params()?;
-		let notif =
-			serde_json::to_string(&NotificationSer::borrowed(&method, params.as_deref())).map_err(Error::ParseError)?;
-
-		let fut = self.transport.send(notif);
-
-		match tokio::time::timeout(self.request_timeout, fut).await {
-			Ok(Ok(ok)) => Ok(ok),
-			Err(_) => Err(Error::RequestTimeout),
-			Ok(Err(e)) => Err(Error::Transport(e.into())),
-		}
+	/// The value of the deadline header for a request which times out after `timeout`, if any.
+	fn deadline(&self, timeout: Duration) -> Option<Duration> {
+		self.propagate_deadline.then_some(timeout)
 	}
 
-	#[instrument(name = "method_call", skip(self, params), level = "trace")]
-	async fn request<R, Params>(&self, method: &str, params: Params) -> Result<R, Error>
+	/// Send a method call which times out after `timeout`.
+	async fn send_request<R, Params>(&self, method: &str, params: Params, timeout: Duration) -> Result<R, Error>
 	where
 		R: DeserializeOwned,
 		Params: ToRpcParams + Send,
@@ -372,11 +432,10 @@ where
 		let id = self.id_manager.next_request_id();
 		let params = params.to_rpc_params()?;
 
-		let request = RequestSer::borrowed(&id, &method, params.as_deref());
-		let raw = serde_json::to_string(&request).map_err(Error::ParseError)?;
+		let raw = serialize_request(&id, method, params.as_deref())?;
 
-		let fut = self.transport.send_and_read_body(raw);
-		let body = match tokio::time::timeout(self.request_timeout, fut).await {
+		let fut = self.transport.send_and_read_body(raw, self.deadline(timeout));
+		let body = match tokio::time::timeout(timeout, fut).await {
 			Ok(Ok(body)) => body,
 			Err(_e) => {
 				return Err(Error::RequestTimeout);
@@ -398,6 +457,54 @@ where
 			Err(InvalidRequestId::NotPendingRequest(response.id.to_string()).into())
 		}
 	}
+}
+
+impl<B, S> ClientT for HttpClient<S>
+where
+	S: Service<HttpRequest, Response = HttpResponse<B>, Error = TransportError> + Send + Sync + Clone,
+	<S as Service<HttpRequest>>::Future: Send,
+	B: http_body::Body<Data = Bytes> + Send + Unpin + 'static,
+	B::Error: Into<BoxError>,
+	B::Data: Send,
+{
+	#[instrument(name = "notification", skip(self, params), level = "trace")]
+	async fn notification<Params>(&self, method: &str, params: Params) -> Result<(), Error>
+	where
+		Params: ToRpcParams + Send,
+	{
+		let _permit = match self.request_guard.as_ref() {
+			Some(permit) => permit.acquire().await.ok(),
+			None => None,
+		};
+		let params = params.to_rpc_params()?;
+		let notif = serialize_notification(method, params.as_deref())?;
+
+		let fut = self.transport.send(notif, self.deadline(self.request_timeout));
+
+		match tokio::time::timeout(self.request_timeout, fut).await {
+			Ok(Ok(ok)) => Ok(ok),
+			Err(_) => Err(Error::RequestTimeout),
+			Ok(Err(e)) => Err(Error::Transport(e.into())),
+		}
+	}
+
+	#[instrument(name = "method_call", skip(self, params), level = "trace")]
+	async fn request<R, Params>(&self, method: &str, params: Params) -> Result<R, Error>
+	where
+		R: DeserializeOwned,
+		Params: ToRpcParams + Send,
+	{
+		self.send_request(method, params, self.request_timeout).await
+	}
+
+	#[instrument(name = "method_call", skip(self, params), level = "trace")]
+	async fn request_with_timeout<R, Params>(&self, method: &str, params: Params, timeout: Duration) -> Result<R, Error>
+	where
+		R: DeserializeOwned,
+		Params: ToRpcParams + Send,
+	{
+		self.send_request(method, params, timeout.min(self.request_timeout)).await
+	}
 
 	#[instrument(name = "batch", skip(self, batch), level = "trace")]
 	async fn batch_request<'a, R>(&self, batch: BatchRequestBuilder<'a>) -> Result<BatchResponse<'a, R>, Error>
@@ -408,22 +515,25 @@ where
 			Some(permit) => permit.acquire().await.ok(),
 			None => None,
 		};
+		let calls: Vec<_> = batch.calls().collect();
+		let num_calls = calls.len();
 		let batch = batch.build()?;
 		let id = self.id_manager.next_request_id();
-		let id_range = generate_batch_id_range(id, batch.len() as u64)?;
-
-		let mut batch_request = Vec::with_capacity(batch.len());
-		for ((method, params), id) in batch.into_iter().zip(id_range.clone()) {
-			let id = self.id_manager.as_id_kind().into_id(id);
-			batch_request.push(RequestSer {
-				jsonrpc: TwoPointZero,
-				id,
-				method: method.into(),
-				params: params.map(StdCow::Owned),
-			});
+		let id_generator = self.id_manager.as_id_generator();
+		let id_range = generate_batch_id_range_with(id_generator, id, num_calls as u64)?;
+		let batch_request = serialize_batch_request_with(batch, id_generator, id_range.clone())?;
+
+		// A batch of notifications is not answered.
+		if num_calls == 0 {
+			let fut = self.transport.send(batch_request, self.deadline(self.request_timeout));
+			return match tokio::time::timeout(self.request_timeout, fut).await {
+				Ok(Ok(())) => Ok(BatchResponse::new(0, Vec::new(), 0, calls)),
+				Err(_) => Err(Error::RequestTimeout),
+				Ok(Err(e)) => Err(Error::Transport(e.into())),
+			};
 		}
 
-		let fut = self.transport.send_and_read_body(serde_json::to_string(&batch_request).map_err(Error::ParseError)?);
+		let fut = self.transport.send_and_read_body(batch_request, self.deadline(self.request_timeout));
 
 		let body = match tokio::time::timeout(self.request_timeout, fut).await {
 			Ok(Ok(body)) => body,
@@ -433,16 +543,16 @@ where
 
 		let json_rps: Vec<Response<&JsonRawValue>> = serde_json::from_slice(&body).map_err(Error::ParseError)?;
 
-		let mut responses = Vec::with_capacity(json_rps.len());
+		let mut responses = Vec::with_capacity(num_calls);
 		let mut successful_calls = 0;
 		let mut failed_calls = 0;
 
-		for _ in 0..json_rps.len() {
+		for _ in 0..num_calls {
 			responses.push(Err(ErrorObject::borrowed(0, "", None)));
 		}
 
 		for rp in json_rps {
-			let id = rp.id.try_parse_inner_as_number()?;
+			let id = id_generator.parse_id(&rp.id)?;
 
 			let res = match ResponseSuccess::try_from(rp) {
 				Ok(r) => {
@@ -468,11 +578,74 @@ where
 			}
 		}
 
-		Ok(BatchResponse::new(successful_calls, responses, failed_calls))
+		Ok(BatchResponse::new(successful_calls, responses, failed_calls, calls))
+	}
+
+	/// Send a batch request and yield the responses as soon as they are read from the response body,
+	/// which the server writes as the method calls complete if it streams the batch responses.
+	///
+	/// The request timeout applies to the whole batch response.
+	#[instrument(name = "batch_stream", skip(self, batch), level = "trace")]
+	async fn batch_request_stream<'a, R>(
+		&self,
+		batch: BatchRequestBuilder<'a>,
+	) -> Result<BatchResponseStream<'a, R>, Error>
+	where
+		R: DeserializeOwned + fmt::Debug + Send + 'a,
+	{
+		let permit = match self.request_guard.clone() {
+			Some(permit) => permit.acquire_owned().await.ok(),
+			None => None,
+		};
+		let calls: Vec<_> = batch.calls().map(|(idx, _)| idx).collect();
+		let batch = batch.build()?;
+		let id = self.id_manager.next_request_id();
+		let id_generator = self.id_manager.as_id_generator();
+		let id_range = generate_batch_id_range_with(id_generator, id, calls.len() as u64)?;
+		let batch_request = serialize_batch_request_with(batch, id_generator, id_range.clone())?;
+		let deadline = tokio::time::Instant::now() + self.request_timeout;
+
+		// A batch of notifications is not answered.
+		if calls.is_empty() {
+			let fut = self.transport.send(batch_request, self.deadline(self.request_timeout));
+			return match tokio::time::timeout_at(deadline, fut).await {
+				Ok(Ok(())) => Ok(BatchResponseStream::new(futures_util::stream::empty())),
+				Err(_) => Err(Error::RequestTimeout),
+				Ok(Err(e)) => Err(Error::Transport(e.into())),
+			};
+		}
+
+		let fut = self.transport.send_and_stream_body(batch_request, self.deadline(self.request_timeout));
+		let body = match tokio::time::timeout_at(deadline, fut).await {
+			Ok(Ok(body)) => body,
+			Err(_) => return Err(Error::RequestTimeout),
+			Ok(Err(e)) => return Err(Error::Transport(e.into())),
+		};
+
+		let batch = BatchStream {
+			body,
+			splitter: JsonArraySplitter::default(),
+			id_manager: self.id_manager.clone(),
+			id_range,
+			answered: vec![false; calls.len()],
+			calls,
+			_permit: permit,
+		};
+
+		// The stream ends after the first error because the rest of the body can't be read reliably.
+		let responses = futures_util::stream::unfold(Some(batch), move |batch| async move {
+			let mut batch = batch?;
+			match tokio::time::timeout_at(deadline, batch.next_entry()).await {
+				Ok(Ok(rp)) => rp.map(|rp| (Ok(rp), Some(batch))),
+				Ok(Err(e)) => Some((Err(e), None)),
+				Err(_) => Some((Err(Error::RequestTimeout), None)),
+			}
+		});
+
+		Ok(BatchResponseStream::new(responses))
 	}
 }
 
-#[async_trait]
 impl<B, S> SubscriptionClientT for HttpClient<S>
 where
 	S: Service<HttpRequest, Response = HttpResponse<B>, Error = TransportError> + Send + Sync + Clone,
@@ -499,7 +672,7 @@ where
 
 	/// Subscribe to a specific method. Not implemented for HTTP; will always return [`Error::HttpNotImplemented`].
 	#[instrument(name = "subscribe_method", fields(method = _method), skip(self, _method), level = "trace")]
-	async fn subscribe_to_method<'a, N>(&self, _method: &'a str) -> Result<Subscription<N>, Error>
+	async fn subscribe_to_method<N>(&self, _method: &str) -> Result<Subscription<N>, Error>
 	where
 		N: DeserializeOwned,
 	{
@@ -36,6 +36,7 @@
 #![cfg_attr(not(test), warn(unused_crate_dependencies))]
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
+mod batch_stream;
 mod client;
 
 /// HTTP transport.
@@ -26,7 +26,7 @@
 
 use crate::types::error::{ErrorCode, ErrorObject};
 use crate::HttpClientBuilder;
-use jsonrpsee_core::client::{BatchResponse, ClientT, IdKind};
+use jsonrpsee_core::client::{BatchResponse, ClientT, IdKind, RequestIdGenerator};
 use jsonrpsee_core::params::BatchRequestBuilder;
 use jsonrpsee_core::ClientError;
 use jsonrpsee_core::{rpc_params, DeserializeOwned};
@@ -34,6 +34,7 @@ use jsonrpsee_test_utils::helpers::*;
 use jsonrpsee_test_utils::mocks::Id;
 use jsonrpsee_test_utils::TimeoutFutureExt;
 use jsonrpsee_types::error::ErrorObjectOwned;
+use jsonrpsee_types::InvalidRequestId;
 
 fn init_logger() {
 	let _ = tracing_subscriber::FmtSubscriber::builder()
@@ -75,6 +76,38 @@ async fn method_call_with_id_str() {
 	assert_eq!(&response, exp);
 }
 
+#[tokio::test]
+async fn batch_request_with_custom_id_generator() {
+	#[derive(Debug)]
+	struct ShardIds;
+
+	impl RequestIdGenerator for ShardIds {
+		fn to_id(&self, n: u64) -> jsonrpsee_types::Id<'static> {
+			jsonrpsee_types::Id::Str(format!("shard-7-{n}").into())
+		}
+
+		fn parse_id(&self, id: &jsonrpsee_types::Id) -> Result<u64, InvalidRequestId> {
+			id.as_str()
+				.and_then(|id| id.strip_prefix("shard-7-"))
+				.and_then(|n| n.parse().ok())
+				.ok_or_else(|| InvalidRequestId::Invalid(id.to_string()))
+		}
+	}
+
+	let server_response = r#"[{"jsonrpc":"2.0","result":"goodbye","id":"shard-7-1"},{"jsonrpc":"2.0","result":"hello","id":"shard-7-0"}]"#;
+	let server_addr =
+		http_server_with_hardcoded_response(server_response.to_owned()).with_default_timeout().await.unwrap();
+	let uri = format!("http://{server_addr}");
+	let client = HttpClientBuilder::default().id_generator(ShardIds).build(&uri).unwrap();
+
+	let mut batch_request = BatchRequestBuilder::new();
+	batch_request.insert("say_hello", rpc_params![]).unwrap();
+	batch_request.insert("say_goodbye", rpc_params![]).unwrap();
+	let res: BatchResponse<String> = client.batch_request(batch_request).with_default_timeout().await.unwrap().unwrap();
+	let results: Vec<String> = res.into_ok().unwrap().collect();
+	assert_eq!(results, vec!["hello".to_string(), "goodbye".to_string()]);
+}
+
 #[tokio::test]
 async fn notification_works() {
 	let server_addr = http_server_with_hardcoded_response(String::new()).with_default_timeout().await.unwrap();
@@ -237,6 +270,26 @@ async fn batch_request_with_untagged_enum_works() {
 	assert_eq!(response, vec![Custom::Text("hello".to_string()), Custom::Number(13)]);
 }
 
+#[tokio::test]
+async fn batch_request_with_notifications_works() {
+	let mut batch_request = BatchRequestBuilder::new();
+	batch_request.insert_notification("log", rpc_params!["start"]).unwrap();
+	batch_request.insert("say_hello", rpc_params![]).unwrap();
+	batch_request.insert_notification("log", rpc_params!["middle"]).unwrap();
+	batch_request.insert("get_swag", rpc_params![]).unwrap();
+	let server_response =
+		r#"[{"jsonrpc":"2.0","result":"hello","id":0}, {"jsonrpc":"2.0","result":"here's your swag","id":1}]"#
+			.to_string();
+	let batch_response = run_batch_request_with_response::<String>(batch_request, server_response)
+		.with_default_timeout()
+		.await
+		.unwrap()
+		.unwrap();
+	assert_eq!(batch_response.len(), 2);
+	let results: Vec<String> = batch_response.into_ok().unwrap().collect();
+	assert_eq!(results, vec!["hello".to_string(), "here's your swag".to_string()]);
+}
+
 #[tokio::test]
 async fn batch_request_out_of_order_response() {
 	let mut batch_request = BatchRequestBuilder::new();
@@ -257,6 +310,21 @@ async fn batch_request_out_of_order_response() {
 	assert_eq!(response, vec!["hello".to_string(), "goodbye".to_string(), "here's your swag".to_string()]);
 }
 
+#[tokio::test]
+async fn connection_refused_keeps_io_error() {
+	let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+	let addr = listener.local_addr().unwrap();
+	drop(listener);
+
+	let client = HttpClientBuilder::default().build(format!("http://{addr}")).unwrap();
+	let err =
+		client.request::<String, _>("say_hello", rpc_params![]).with_default_timeout().await.unwrap().unwrap_err();
+
+	assert!(err.as_transport().is_some());
+	let io_err = err.downcast_transport::<std::io::Error>().expect("io error is a source of the transport error");
+	assert_eq!(io_err.kind(), std::io::ErrorKind::ConnectionRefused);
+}
+
 async fn run_batch_request_with_response<T: Send + DeserializeOwned + std::fmt::Debug + Clone + 'static>(
 	batch: BatchRequestBuilder<'_>,
 	response: String,
@@ -12,15 +12,18 @@ use hyper::http::{HeaderMap, HeaderValue};
 use hyper_util::client::legacy::connect::HttpConnector;
 use hyper_util::client::legacy::Client;
 use hyper_util::rt::TokioExecutor;
+use jsonrpsee_core::codec::{Codec, CodecError};
 use jsonrpsee_core::tracing::client::{rx_log_from_bytes, tx_log_from_str};
 use jsonrpsee_core::BoxError;
 use jsonrpsee_core::{
-	http_helpers::{self, HttpError},
+	http_helpers::{self, HttpError, DEADLINE_HEADER},
 	TEN_MB_SIZE_BYTES,
 };
+use jsonrpsee_types::v1;
 use std::future::Future;
 use std::pin::Pin;
 use std::task::{Context, Poll};
+use std::time::Duration;
 use thiserror::Error;
 use tower::layer::util::Identity;
 use tower::{Layer, Service, ServiceExt};
@@ -103,6 +106,10 @@ pub struct HttpTransportClientBuilder<L> {
 	pub(crate) service_builder: tower::ServiceBuilder<L>,
 	/// TCP_NODELAY
 	pub(crate) tcp_no_delay: bool,
+	/// Codec of requests and responses, JSON if `None`.
+	pub(crate) codec: Option<&'static dyn Codec>,
+	/// Whether JSON-RPC 1.0 requests are sent.
+	pub(crate) json_rpc_v1: bool,
 }
 
 impl Default for HttpTransportClientBuilder<Identity> {
@@ -123,6 +130,8 @@ impl HttpTransportClientBuilder<Identity> {
 			headers: HeaderMap::new(),
 			service_builder: tower::ServiceBuilder::new(),
 			tcp_no_delay: true,
+			codec: None,
+			json_rpc_v1: false,
 		}
 	}
 }
@@ -171,6 +180,18 @@ impl<L> HttpTransportClientBuilder<L> {
 		self
 	}
 
+	/// See docs [`crate::HttpClientBuilder::set_codec`] for more information.
+	pub fn set_codec(mut self, codec: &'static dyn Codec) -> Self {
+		self.codec = Some(codec);
+		self
+	}
+
+	/// See docs [`crate::HttpClientBuilder::set_json_rpc_v1`] for more information.
+	pub fn set_json_rpc_v1(mut self, enabled: bool) -> Self {
+		self.json_rpc_v1 = enabled;
+		self
+	}
+
 	/// Configure a tower service.
 	pub fn set_service<T>(self, service: tower::ServiceBuilder<T>) -> HttpTransportClientBuilder<T> {
 		HttpTransportClientBuilder {
@@ -182,6 +203,8 @@ impl<L> HttpTransportClientBuilder<L> {
 			max_response_size: self.max_response_size,
 			service_builder: service,
 			tcp_no_delay: self.tcp_no_delay,
+			codec: self.codec,
+			json_rpc_v1: self.json_rpc_v1,
 		}
 	}
 
@@ -203,6 +226,8 @@ impl<L> HttpTransportClientBuilder<L> {
 			headers,
 			service_builder,
 			tcp_no_delay,
+			codec,
+			json_rpc_v1,
 		} = self;
 		let mut url = Url::parse(target.as_ref()).map_err(|e| Error::Url(format!("Invalid URL: {e}")))?;
 
@@ -261,9 +286,11 @@ impl<L> HttpTransportClientBuilder<L> {
 		// Cache request headers: 2 default headers, followed by user custom headers.
 		// Maintain order for headers in case of duplicate keys:
 		// https://datatracker.ietf.org/doc/html/rfc7230#section-3.2.2
+		let content_type = codec.map_or(CONTENT_TYPE_JSON, |codec| codec.content_type());
+
 		let mut cached_headers = HeaderMap::with_capacity(2 + headers.len());
-		cached_headers.insert(hyper::header::CONTENT_TYPE, HeaderValue::from_static(CONTENT_TYPE_JSON));
-		cached_headers.insert(hyper::header::ACCEPT, HeaderValue::from_static(CONTENT_TYPE_JSON));
+		cached_headers.insert(hyper::header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+		cached_headers.insert(hyper::header::ACCEPT, HeaderValue::from_static(content_type));
 		for (key, value) in headers.into_iter() {
 			if let Some(key) = key {
 				cached_headers.insert(key, value);
@@ -288,6 +315,8 @@ impl<L> HttpTransportClientBuilder<L> {
 			max_response_size,
 			max_log_length,
 			headers: cached_headers,
+			codec,
+			json_rpc_v1,
 		})
 	}
 }
@@ -309,6 +338,10 @@ pub struct HttpTransportClient<S> {
 	max_log_length: u32,
 	/// Custom headers to pass with every request.
 	headers: HeaderMap,
+	/// Codec of requests and responses, JSON if `None`.
+	codec: Option<&'static dyn Codec>,
+	/// Whether JSON-RPC 1.0 requests are sent.
+	json_rpc_v1: bool,
 }
 
 impl<B, S> HttpTransportClient<S>
@@ -318,7 +351,13 @@ where
 	B::Data: Send,
 	B::Error: Into<BoxError>,
 {
-	async fn inner_send(&self, body: String) -> Result<HttpResponse<B>, Error> {
+	async fn inner_send(&self, body: String, deadline: Option<Duration>) -> Result<HttpResponse<B>, Error> {
+		let body = if self.json_rpc_v1 { v1::request_to_v1(&body) } else { body };
+		let body = match self.codec {
+			Some(codec) => codec.encode(body.as_bytes())?,
+			None => body.into_bytes(),
+		};
+
 		if body.len() > self.max_request_size as usize {
 			return Err(Error::RequestTooLarge);
 		}
@@ -326,6 +365,9 @@ where
 		let mut req = HttpRequest::post(&self.target);
 		if let Some(headers) = req.headers_mut() {
 			*headers = self.headers.clone();
+			if let Some(deadline) = deadline {
+				headers.insert(DEADLINE_HEADER, http_helpers::deadline_header_value(deadline));
+			}
 		}
 
 		let req = req.body(body.into()).expect("URI and request headers are valid; qed");
@@ -339,27 +381,103 @@ where
 	}
 
 	/// Send serialized message and wait until all bytes from the HTTP message body have been read.
-	pub(crate) async fn send_and_read_body(&self, body: String) -> Result<Vec<u8>, Error> {
+	///
+	/// The `deadline` is sent in the [`DEADLINE_HEADER`] if it's set.
+	pub(crate) async fn send_and_read_body(&self, body: String, deadline: Option<Duration>) -> Result<Vec<u8>, Error> {
 		tx_log_from_str(&body, self.max_log_length);
 
-		let response = self.inner_send(body).await?;
+		let response = self.inner_send(body, deadline).await?;
 		let (parts, body) = response.into_parts();
 
-		let (body, _is_single) = http_helpers::read_body(&parts.headers, body, self.max_response_size).await?;
+		let (mut body, _is_single) = match self.codec {
+			Some(codec) => http_helpers::read_encoded_body(&parts.headers, body, self.max_response_size, codec).await?,
+			None => http_helpers::read_body(&parts.headers, body, self.max_response_size).await?,
+		};
+
+		if self.json_rpc_v1 {
+			if let Ok(rp) = std::str::from_utf8(&body) {
+				body = v1::response_to_v2(rp).into_bytes();
+			}
+		}
 
 		rx_log_from_bytes(&body, self.max_log_length);
 
 		Ok(body)
 	}
 
+	/// Send serialized message and read the HTTP message body as it's received.
+	///
+	/// The body is read in full if it's decoded by a codec or converted from JSON-RPC 1.0.
+	pub(crate) async fn send_and_stream_body(
+		&self,
+		body: String,
+		deadline: Option<Duration>,
+	) -> Result<ResponseBody<B>, Error> {
+		if self.codec.is_some() || self.json_rpc_v1 {
+			return self.send_and_read_body(body, deadline).await.map(ResponseBody::Read);
+		}
+
+		tx_log_from_str(&body, self.max_log_length);
+
+		let response = self.inner_send(body, deadline).await?;
+
+		Ok(ResponseBody::Streamed {
+			body: response.into_body(),
+			max_size: self.max_response_size as usize,
+			read: 0,
+			max_log_length: self.max_log_length,
+		})
+	}
+
 	/// Send serialized message without reading the HTTP message body.
-	pub(crate) async fn send(&self, body: String) -> Result<(), Error> {
-		let _ = self.inner_send(body).await?;
+	pub(crate) async fn send(&self, body: String, deadline: Option<Duration>) -> Result<(), Error> {
+		let _ = self.inner_send(body, deadline).await?;
 
 		Ok(())
 	}
 }
 
+/// Body of a response which is read as it's received, see [`HttpTransportClient::send_and_stream_body`].
+#[derive(Debug)]
+pub(crate) enum ResponseBody<B> {
+	/// The body has already been read in full.
+	Read(Vec<u8>),
+	/// The body is read in chunks of at most `max_size` bytes in total.
+	Streamed { body: B, max_size: usize, read: usize, max_log_length: u32 },
+}
+
+impl<B> ResponseBody<B>
+where
+	B: http_body::Body<Data = Bytes> + Unpin,
+	B::Error: Into<BoxError>,
+{
+	/// Read the next chunk of the body, `None` once it has been read in full.
+	pub(crate) async fn next_chunk(&mut self) -> Result<Option<Bytes>, Error> {
+		let (body, max_size, read, max_log_length) = match self {
+			Self::Read(body) if body.is_empty() => return Ok(None),
+			Self::Read(body) => return Ok(Some(std::mem::take(body).into())),
+			Self::Streamed { body, max_size, read, max_log_length } => (body, *max_size, read, *max_log_length),
+		};
+
+		loop {
+			let Some(frame) = std::future::poll_fn(|cx| Pin::new(&mut *body).poll_frame(cx)).await else {
+				return Ok(None);
+			};
+
+			// The trailers are ignored.
+			if let Ok(data) = frame.map_err(|e| HttpError::Stream(e.into()))?.into_data() {
+				*read += data.len();
+				if *read > max_size {
+					return Err(HttpError::TooLarge.into());
+				}
+
+				rx_log_from_bytes(&data, max_log_length);
+				return Ok(Some(data));
+			}
+		}
+	}
+}
+
 /// Error that can happen during a request.
 #[derive(Debug, Error)]
 pub enum Error {
@@ -385,6 +503,10 @@ pub enum Error {
 	/// Invalid certificate store.
 	#[error("Invalid certificate store")]
 	InvalidCertficateStore,
+
+	/// The request could not be encoded by the codec.
+	#[error(transparent)]
+	Codec(#[from] CodecError),
 }
 
 #[cfg(test)]
@@ -473,7 +595,7 @@ mod tests {
 
 		let body = "a".repeat(81);
 		assert_eq!(body.len(), 81);
-		let response = client.send(body).await.unwrap_err();
+		let response = client.send(body, None).await.unwrap_err();
 		assert!(matches!(response, Error::RequestTooLarge));
 	}
 }
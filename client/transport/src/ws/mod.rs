@@ -33,8 +33,10 @@ use std::time::Duration;
 use base64::Engine;
 use futures_util::io::{BufReader, BufWriter};
 use jsonrpsee_core::client::{MaybeSend, ReceivedMessage, TransportReceiverT, TransportSenderT};
+use jsonrpsee_core::codec::{Codec, CodecError};
 use jsonrpsee_core::TEN_MB_SIZE_BYTES;
 use jsonrpsee_core::{async_trait, Cow};
+use jsonrpsee_types::v1;
 use soketto::connection::Error::Utf8;
 use soketto::data::ByteSlice125;
 use soketto::handshake::client::{Client as WsHandshakeClient, ServerResponse};
@@ -72,12 +74,16 @@ pub enum CertificateStore {
 pub struct Sender<T> {
 	inner: connection::Sender<BufReader<BufWriter<T>>>,
 	max_request_size: u32,
+	codec: Option<&'static dyn Codec>,
+	json_rpc_v1: bool,
 }
 
 /// Receiving end of WebSocket transport.
 #[derive(Debug)]
 pub struct Receiver<T> {
 	inner: connection::Receiver<BufReader<BufWriter<T>>>,
+	codec: Option<&'static dyn Codec>,
+	json_rpc_v1: bool,
 }
 
 /// Builder for a WebSocket transport [`Sender`] and [`Receiver`] pair.
@@ -98,6 +104,10 @@ pub struct WsTransportClientBuilder {
 	pub max_redirections: usize,
 	/// TCP no delay.
 	pub tcp_no_delay: bool,
+	/// Codec to offer to the server.
+	pub codec: Option<&'static dyn Codec>,
+	/// Send JSON-RPC 1.0 requests.
+	pub json_rpc_v1: bool,
 }
 
 impl Default for WsTransportClientBuilder {
@@ -111,6 +121,8 @@ impl Default for WsTransportClientBuilder {
 			headers: http::HeaderMap::new(),
 			max_redirections: 5,
 			tcp_no_delay: true,
+			codec: None,
+			json_rpc_v1: false,
 		}
 	}
 }
@@ -159,6 +171,26 @@ impl WsTransportClientBuilder {
 		self.max_redirections = redirect;
 		self
 	}
+
+	/// Offer to encode the messages with the `codec` instead of JSON, see [`jsonrpsee_core::codec`] for details.
+	///
+	/// The offer is made with the [`Codec::ws_subprotocol`] and if the server doesn't select it,
+	/// the messages are encoded as JSON.
+	///
+	/// Default is JSON.
+	pub fn set_codec(mut self, codec: &'static dyn Codec) -> Self {
+		self.codec = Some(codec);
+		self
+	}
+
+	/// Send [JSON-RPC 1.0](https://www.jsonrpc.org/specification_v1) requests and accept 1.0 responses,
+	/// see [`jsonrpsee_types::v1`] for details.
+	///
+	/// Default is `false`.
+	pub fn set_json_rpc_v1(mut self, enabled: bool) -> Self {
+		self.json_rpc_v1 = enabled;
+		self
+	}
 }
 
 /// Stream mode, either plain TCP or TLS.
@@ -178,7 +210,7 @@ pub enum Mode {
 pub enum WsHandshakeError {
 	/// Failed to load system certs
 	#[error("Failed to load system certs: {0}")]
-	CertificateStore(io::Error),
+	CertificateStore(#[source] io::Error),
 
 	/// Invalid URL.
 	#[error("Invalid URL: {0}")]
@@ -186,7 +218,7 @@ pub enum WsHandshakeError {
 
 	/// Error when opening the TCP socket.
 	#[error("Error when opening the TCP socket: {0}")]
-	Io(io::Error),
+	Io(#[source] io::Error),
 
 	/// Error in the transport layer.
 	#[error("{0}")]
@@ -214,7 +246,7 @@ pub enum WsHandshakeError {
 
 	/// Failed to resolve IP addresses for this hostname.
 	#[error("Failed to resolve IP addresses for this hostname: {0}")]
-	ResolutionFailed(io::Error),
+	ResolutionFailed(#[source] io::Error),
 
 	/// Couldn't find any IP address for this hostname.
 	#[error("No IP address found for this hostname: {0}")]
@@ -230,6 +262,9 @@ pub enum WsError {
 	/// Message was too large.
 	#[error("The message was too large")]
 	MessageTooLarge,
+	/// Message could not be encoded or decoded by the codec.
+	#[error(transparent)]
+	Codec(#[from] CodecError),
 }
 
 #[async_trait]
@@ -242,6 +277,19 @@ where
 	/// Sends out a request. Returns a `Future` that finishes when the request has been
 	/// successfully sent.
 	async fn send(&mut self, body: String) -> Result<(), Self::Error> {
+		let body = if self.json_rpc_v1 { v1::request_to_v1(&body) } else { body };
+
+		if let Some(codec) = self.codec {
+			let body = codec.encode(body.as_bytes())?;
+			if body.len() > self.max_request_size as usize {
+				return Err(WsError::MessageTooLarge);
+			}
+
+			self.inner.send_binary_mut(body).await?;
+			self.inner.flush().await?;
+			return Ok(());
+		}
+
 		if body.len() > self.max_request_size as usize {
 			return Err(WsError::MessageTooLarge);
 		}
@@ -287,9 +335,19 @@ where
 			match recv {
 				Incoming::Data(Data::Text(_)) => {
 					let s = String::from_utf8(message).map_err(|err| WsError::Connection(Utf8(err.utf8_error())))?;
+					let s = if self.json_rpc_v1 { v1::response_to_v2(&s) } else { s };
 					break Ok(ReceivedMessage::Text(s));
 				}
-				Incoming::Data(Data::Binary(_)) => break Ok(ReceivedMessage::Bytes(message)),
+				Incoming::Data(Data::Binary(_)) => {
+					let message = match self.codec {
+						Some(codec) => codec.decode(&message)?,
+						None => message,
+					};
+					match std::str::from_utf8(&message) {
+						Ok(s) if self.json_rpc_v1 => break Ok(ReceivedMessage::Text(v1::response_to_v2(s))),
+						_ => break Ok(ReceivedMessage::Bytes(message)),
+					}
+				}
 				Incoming::Pong(_) => break Ok(ReceivedMessage::Pong),
 				_ => continue,
 			}
@@ -485,15 +543,23 @@ impl WsTransportClientBuilder {
 
 		client.set_headers(&headers);
 
+		if let Some(codec) = self.codec {
+			client.add_protocol(codec.ws_subprotocol());
+		}
+
 		// Perform the initial handshake.
 		match client.handshake().await {
-			Ok(ServerResponse::Accepted { .. }) => {
+			Ok(ServerResponse::Accepted { protocol }) => {
 				tracing::debug!(target: LOG_TARGET, "Connection established to target: {:?}", target);
+				let codec = self.codec.filter(|codec| protocol.as_deref() == Some(codec.ws_subprotocol()));
+
 				let mut builder = client.into_builder();
 				builder.set_max_frame_size(usize::MAX);
 				builder.set_max_message_size(self.max_response_size as usize);
 				let (sender, receiver) = builder.finish();
-				Ok((Sender { inner: sender, max_request_size: self.max_request_size }, Receiver { inner: receiver }))
+				let json_rpc_v1 = self.json_rpc_v1;
+				let sender = Sender { inner: sender, max_request_size: self.max_request_size, codec, json_rpc_v1 };
+				Ok((sender, Receiver { inner: receiver, codec, json_rpc_v1 }))
 			}
 
 			Ok(ServerResponse::Rejected { status_code }) => {
@@ -38,6 +38,7 @@ use std::time::Duration;
 
 use jsonrpsee_client_transport::web;
 use jsonrpsee_core::client::{ClientBuilder, Error, IdKind};
+use jsonrpsee_core::Limits;
 
 /// Builder for [`Client`].
 ///
@@ -86,6 +87,13 @@ impl WasmClientBuilder {
 		WasmClientBuilder::default()
 	}
 
+	/// See documentation [`ClientBuilder::with_limits`].
+	pub fn with_limits(mut self, limits: Limits) -> Self {
+		self.request_timeout = limits.timeout;
+		self.max_log_length = limits.max_log_length;
+		self
+	}
+
 	/// See documentation [`ClientBuilder::request_timeout`] (default is 60 seconds).
 	pub fn request_timeout(mut self, timeout: Duration) -> Self {
 		self.request_timeout = timeout;
@@ -45,8 +45,13 @@ pub use jsonrpsee_core::client::Client as WsClient;
 pub use jsonrpsee_types as types;
 
 use jsonrpsee_client_transport::ws::{AsyncRead, AsyncWrite, WsTransportClientBuilder};
-use jsonrpsee_core::client::{ClientBuilder, Error, IdKind, MaybeSend, TransportReceiverT, TransportSenderT};
-use jsonrpsee_core::TEN_MB_SIZE_BYTES;
+use jsonrpsee_core::client::{
+	ClientBuilder, Error, IdKind, MaybeSend, RequestIdGenerator, TransportReceiverT, TransportSenderT,
+};
+use jsonrpsee_core::codec::Codec;
+use jsonrpsee_core::traits::SubscriptionIdParser;
+use jsonrpsee_core::{Limits, TEN_MB_SIZE_BYTES};
+use std::sync::Arc;
 use std::time::Duration;
 use url::Url;
 
@@ -94,9 +99,12 @@ pub struct WsClientBuilder {
 	max_concurrent_requests: usize,
 	max_buffer_capacity_per_subscription: usize,
 	max_redirections: usize,
-	id_kind: IdKind,
+	id_generator: Arc<dyn RequestIdGenerator>,
 	max_log_length: u32,
 	tcp_no_delay: bool,
+	codec: Option<&'static dyn Codec>,
+	json_rpc_v1: bool,
+	subscription_id_parser: Option<Arc<dyn SubscriptionIdParser>>,
 }
 
 impl Default for WsClientBuilder {
@@ -113,9 +121,12 @@ impl Default for WsClientBuilder {
 			max_concurrent_requests: 256,
 			max_buffer_capacity_per_subscription: 1024,
 			max_redirections: 5,
-			id_kind: IdKind::Number,
+			id_generator: Arc::new(IdKind::Number),
 			max_log_length: 4096,
 			tcp_no_delay: true,
+			codec: None,
+			json_rpc_v1: false,
+			subscription_id_parser: None,
 		}
 	}
 }
@@ -194,6 +205,18 @@ impl WsClientBuilder {
 		self
 	}
 
+	/// Apply a profile of [`Limits`] at once.
+	///
+	/// It sets the max request and response size, the max log length and uses [`Limits::timeout`]
+	/// as request timeout. The max batch length isn't used by the client.
+	pub fn with_limits(mut self, limits: Limits) -> Self {
+		self.max_request_size = limits.max_request_size;
+		self.max_response_size = limits.max_response_size;
+		self.max_log_length = limits.max_log_length;
+		self.request_timeout = limits.timeout;
+		self
+	}
+
 	/// See documentation [`WsTransportClientBuilder::max_request_size`] (default is 10 MB).
 	pub fn max_request_size(mut self, size: u32) -> Self {
 		self.max_request_size = size;
@@ -256,7 +279,13 @@ impl WsClientBuilder {
 
 	/// See documentation for [`ClientBuilder::id_format`] (default is Number).
 	pub fn id_format(mut self, kind: IdKind) -> Self {
-		self.id_kind = kind;
+		self.id_generator = Arc::new(kind);
+		self
+	}
+
+	/// See documentation for [`ClientBuilder::id_generator`].
+	pub fn id_generator<G: RequestIdGenerator + 'static>(mut self, id_generator: G) -> Self {
+		self.id_generator = Arc::new(id_generator);
 		self
 	}
 
@@ -274,6 +303,24 @@ impl WsClientBuilder {
 		self
 	}
 
+	/// See documentation [`ClientBuilder::set_subscription_id_parser`] (default is a string or a number).
+	pub fn set_subscription_id_parser<P: SubscriptionIdParser + 'static>(mut self, parser: P) -> Self {
+		self.subscription_id_parser = Some(Arc::new(parser));
+		self
+	}
+
+	/// See documentation [`WsTransportClientBuilder::set_codec`] (default is JSON).
+	pub fn set_codec(mut self, codec: &'static dyn Codec) -> Self {
+		self.codec = Some(codec);
+		self
+	}
+
+	/// See documentation [`WsTransportClientBuilder::set_json_rpc_v1`] (default is false).
+	pub fn set_json_rpc_v1(mut self, enabled: bool) -> Self {
+		self.json_rpc_v1 = enabled;
+		self
+	}
+
 	/// Build the [`WsClient`] with specified [`TransportSenderT`] [`TransportReceiverT`] parameters
 	///
 	/// ## Panics
@@ -289,9 +336,10 @@ impl WsClientBuilder {
 			request_timeout,
 			ping_config,
 			max_buffer_capacity_per_subscription,
-			id_kind,
+			id_generator,
 			max_log_length,
 			tcp_no_delay,
+			subscription_id_parser,
 			..
 		} = self;
 
@@ -299,7 +347,7 @@ impl WsClientBuilder {
 			.max_buffer_capacity_per_subscription(max_buffer_capacity_per_subscription)
 			.request_timeout(request_timeout)
 			.max_concurrent_requests(max_concurrent_requests)
-			.id_format(id_kind)
+			.id_generator(id_generator)
 			.set_max_logging_length(max_log_length)
 			.set_tcp_no_delay(tcp_no_delay);
 
@@ -307,6 +355,10 @@ impl WsClientBuilder {
 			client = client.enable_ws_ping(cfg);
 		}
 
+		if let Some(parser) = subscription_id_parser {
+			client = client.set_subscription_id_parser(parser);
+		}
+
 		client.build_with_tokio(sender, receiver)
 	}
 
@@ -328,6 +380,8 @@ impl WsClientBuilder {
 			max_response_size: self.max_response_size,
 			max_redirections: self.max_redirections,
 			tcp_no_delay: self.tcp_no_delay,
+			codec: self.codec,
+			json_rpc_v1: self.json_rpc_v1,
 		};
 
 		let uri = Url::parse(url.as_ref()).map_err(|e| Error::Transport(e.into()))?;
@@ -354,6 +408,8 @@ impl WsClientBuilder {
 			max_response_size: self.max_response_size,
 			max_redirections: self.max_redirections,
 			tcp_no_delay: self.tcp_no_delay,
+			codec: self.codec,
+			json_rpc_v1: self.json_rpc_v1,
 		};
 
 		let uri = Url::parse(url.as_ref()).map_err(|e| Error::Transport(e.into()))?;
@@ -28,15 +28,17 @@ use crate::types::error::{ErrorCode, ErrorObject};
 use crate::WsClientBuilder;
 
 use jsonrpsee_core::client::{
-	BatchResponse, ClientT, Error, IdKind, Subscription, SubscriptionClientT, SubscriptionCloseReason,
+	BatchResponse, ClientT, Error, IdKind, RequestIdGenerator, Subscription, SubscriptionClientT,
+	SubscriptionCloseReason, SubscriptionKind,
 };
 use jsonrpsee_core::params::BatchRequestBuilder;
+use jsonrpsee_core::traits::SubscriptionIdParser;
 use jsonrpsee_core::{rpc_params, DeserializeOwned};
 use jsonrpsee_test_utils::helpers::*;
 use jsonrpsee_test_utils::mocks::{Id, WebSocketTestServer};
 use jsonrpsee_test_utils::TimeoutFutureExt;
 use jsonrpsee_types::error::ErrorObjectOwned;
-use jsonrpsee_types::{Notification, SubscriptionId, SubscriptionPayload, SubscriptionResponse};
+use jsonrpsee_types::{InvalidRequestId, Notification, SubscriptionId, SubscriptionPayload, SubscriptionResponse};
 use serde_json::Value as JsonValue;
 
 fn init_logger() {
@@ -90,6 +92,38 @@ async fn method_call_with_id_str() {
 	assert_eq!(&response, exp);
 }
 
+#[tokio::test]
+async fn method_call_with_custom_id_generator() {
+	#[derive(Debug)]
+	struct ShardIds;
+
+	impl RequestIdGenerator for ShardIds {
+		fn to_id(&self, n: u64) -> jsonrpsee_types::Id<'static> {
+			jsonrpsee_types::Id::Str(format!("shard-7-{n}").into())
+		}
+
+		fn parse_id(&self, id: &jsonrpsee_types::Id) -> Result<u64, InvalidRequestId> {
+			id.as_str()
+				.and_then(|id| id.strip_prefix("shard-7-"))
+				.and_then(|n| n.parse().ok())
+				.ok_or_else(|| InvalidRequestId::Invalid(id.to_string()))
+		}
+	}
+
+	let server = WebSocketTestServer::with_hardcoded_response(
+		"127.0.0.1:0".parse().unwrap(),
+		ok_response("hello".into(), Id::Str("shard-7-0".into())),
+	)
+	.with_default_timeout()
+	.await
+	.unwrap();
+	let uri = format!("ws://{}", server.local_addr());
+	let client =
+		WsClientBuilder::default().id_generator(ShardIds).build(&uri).with_default_timeout().await.unwrap().unwrap();
+	let response: String = client.request("o", rpc_params![]).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(&response, "hello");
+}
+
 #[tokio::test]
 async fn notif_works() {
 	// this empty string shouldn't be read because the server shouldn't respond to notifications.
@@ -102,6 +136,27 @@ async fn notif_works() {
 	assert!(client.notification("notif", rpc_params![]).with_default_timeout().await.unwrap().is_ok());
 }
 
+#[tokio::test]
+async fn request_with_timeout_works() {
+	// The server never answers the call.
+	let notif = server_notification("unknown", serde_json::json!([]));
+	let server = WebSocketTestServer::with_hardcoded_response("127.0.0.1:0".parse().unwrap(), notif)
+		.with_default_timeout()
+		.await
+		.unwrap();
+	let uri = to_ws_uri_string(server.local_addr());
+	let client = WsClientBuilder::default().build(&uri).with_default_timeout().await.unwrap().unwrap();
+
+	let timeout = std::time::Duration::from_millis(50);
+	let err = client
+		.request_with_timeout::<String, _>("say_hello", rpc_params![], timeout)
+		.with_default_timeout()
+		.await
+		.unwrap()
+		.unwrap_err();
+	assert!(matches!(err, Error::RequestTimeout));
+}
+
 #[tokio::test]
 async fn response_with_wrong_id() {
 	let err = run_request_with_response(ok_response("hello".into(), Id::Num(99)))
@@ -170,6 +225,68 @@ async fn subscription_works() {
 	}
 }
 
+#[tokio::test]
+async fn subscription_id_parser_works() {
+	init_logger();
+
+	/// Subscription IDs given as any JSON value, which is kept as a string.
+	#[derive(Debug)]
+	struct AnyJsonId;
+
+	impl SubscriptionIdParser for AnyJsonId {
+		fn parse(&self, id: &JsonValue) -> Option<SubscriptionId<'static>> {
+			Some(SubscriptionId::Str(id.to_string().into()))
+		}
+
+		fn to_json(&self, id: &SubscriptionId) -> JsonValue {
+			match id {
+				SubscriptionId::Str(s) => serde_json::from_str(s).unwrap(),
+				SubscriptionId::Num(n) => (*n).into(),
+			}
+		}
+	}
+
+	let sub_id = r#"{"id":"abc","shard":1}"#;
+	let server = WebSocketTestServer::with_hardcoded_subscription(
+		"127.0.0.1:0".parse().unwrap(),
+		format!(r#"{{"jsonrpc":"2.0","id":0,"result":{sub_id}}}"#),
+		format!(
+			r#"{{"jsonrpc":"2.0","method":"subscribe_hello","params":{{"subscription":{sub_id},"result":"hello"}}}}"#
+		),
+	)
+	.with_default_timeout()
+	.await
+	.unwrap();
+	let uri = to_ws_uri_string(server.local_addr());
+
+	// The subscription ID is neither a string nor a number.
+	let client = WsClientBuilder::default().build(&uri).with_default_timeout().await.unwrap().unwrap();
+	let err = client
+		.subscribe::<String, _>("subscribe_hello", rpc_params![], "unsubscribe_hello")
+		.with_default_timeout()
+		.await
+		.unwrap()
+		.unwrap_err();
+	assert!(matches!(err, Error::InvalidSubscriptionId));
+
+	let client = WsClientBuilder::default()
+		.set_subscription_id_parser(AnyJsonId)
+		.build(&uri)
+		.with_default_timeout()
+		.await
+		.unwrap()
+		.unwrap();
+	let mut sub: Subscription<String> = client
+		.subscribe("subscribe_hello", rpc_params![], "unsubscribe_hello")
+		.with_default_timeout()
+		.await
+		.unwrap()
+		.unwrap();
+	assert!(matches!(sub.kind(), SubscriptionKind::Subscription(SubscriptionId::Str(id)) if id == sub_id));
+	let response: String = sub.next().with_default_timeout().await.unwrap().unwrap().unwrap();
+	assert_eq!("hello", response);
+}
+
 #[tokio::test]
 async fn notification_handler_works() {
 	let server = WebSocketTestServer::with_hardcoded_notification(
@@ -322,6 +439,26 @@ async fn batch_request_works() {
 	assert_eq!(results, vec!["hello".to_string(), "goodbye".to_string(), "here's your swag".to_string()]);
 }
 
+#[tokio::test]
+async fn batch_request_with_notifications_works() {
+	let mut batch_request = BatchRequestBuilder::new();
+	batch_request.insert_notification("log", rpc_params!["start"]).unwrap();
+	batch_request.insert("say_hello", rpc_params![]).unwrap();
+	batch_request.insert_notification("log", rpc_params!["middle"]).unwrap();
+	batch_request.insert("get_swag", rpc_params![]).unwrap();
+	let server_response =
+		r#"[{"jsonrpc":"2.0","result":"hello","id":0}, {"jsonrpc":"2.0","result":"here's your swag","id":1}]"#
+			.to_string();
+	let batch_response = run_batch_request_with_response::<String>(batch_request, server_response)
+		.with_default_timeout()
+		.await
+		.unwrap()
+		.unwrap();
+	assert_eq!(batch_response.len(), 2);
+	let results: Vec<String> = batch_response.into_ok().unwrap().collect();
+	assert_eq!(results, vec!["hello".to_string(), "here's your swag".to_string()]);
+}
+
 #[tokio::test]
 async fn batch_request_out_of_order_response() {
 	let mut batch_request = BatchRequestBuilder::new();
@@ -34,9 +34,12 @@ use futures_timer::Delay;
 use futures_util::future::{self, Either};
 use tokio::sync::oneshot;
 
-use jsonrpsee_types::response::SubscriptionError;
+use crate::traits::SubscriptionIdParser;
+use crate::JsonRawValue;
+use jsonrpsee_types::response::{SubscriptionError, SubscriptionPayloadError};
 use jsonrpsee_types::{
-	ErrorObject, Id, InvalidRequestId, RequestSer, Response, ResponseSuccess, SubscriptionId, SubscriptionResponse,
+	ErrorObject, Id, InvalidRequestId, RequestSer, Response, ResponseSuccess, SubscriptionId, SubscriptionPayload,
+	SubscriptionResponse,
 };
 use serde_json::Value as JsonValue;
 use std::ops::Range;
@@ -89,13 +92,16 @@ pub(crate) fn process_batch_response(
 
 /// Attempts to process a subscription response.
 ///
+/// The response is borrowed from the received message and the `result` is only copied
+/// once, into the buffer handed over to the subscription.
+///
 /// Returns `Some(sub_id)` if the subscription should be closed otherwise
 /// `None` is returned.
 pub(crate) fn process_subscription_response(
 	manager: &mut RequestManager,
-	response: SubscriptionResponse<JsonValue>,
+	response: SubscriptionResponse<&JsonRawValue>,
 ) -> Option<SubscriptionId<'static>> {
-	let sub_id = response.params.subscription.into_owned();
+	let sub_id = response.params.subscription;
 	let request_id = match manager.get_request_id_by_subscription_id(&sub_id) {
 		Some(request_id) => request_id,
 		None => {
@@ -105,12 +111,12 @@ pub(crate) fn process_subscription_response(
 	};
 
 	match manager.as_subscription_mut(&request_id) {
-		Some(send_back_sink) => match send_back_sink.send(response.params.result) {
+		Some(send_back_sink) => match send_back_sink.send(response.params.result.to_owned()) {
 			Ok(_) => None,
-			Err(TrySubscriptionSendError::Closed) => Some(sub_id),
+			Err(TrySubscriptionSendError::Closed) => Some(sub_id.into_owned()),
 			Err(TrySubscriptionSendError::TooSlow(m)) => {
 				tracing::debug!(target: LOG_TARGET, "Subscription {{method={}, sub_id={:?}}} couldn't keep up with server; failed to send {m}", response.method, sub_id);
-				Some(sub_id)
+				Some(sub_id.into_owned())
 			}
 		},
 		None => {
@@ -120,16 +126,75 @@ pub(crate) fn process_subscription_response(
 	}
 }
 
+/// Subscription response or error whose subscription ID has been parsed by a [`SubscriptionIdParser`].
+pub(crate) enum ParsedSubscriptionNotif {
+	Response(SubscriptionResponse<'static, JsonValue>),
+	Error(SubscriptionError<'static, JsonValue>),
+}
+
+/// Attempts to parse a subscription response or error, where the subscription ID is parsed by `parser`.
+pub(crate) fn parse_subscription_notif(
+	raw: &[u8],
+	parser: &dyn SubscriptionIdParser,
+) -> Option<ParsedSubscriptionNotif> {
+	let notif: jsonrpsee_types::Notification<serde_json::Map<String, JsonValue>> = serde_json::from_slice(raw).ok()?;
+	let mut params = notif.params;
+	let subscription = parser.parse(params.get("subscription")?)?;
+	let method = notif.method.into_owned().into();
+
+	if let Some(result) = params.remove("result") {
+		let payload = SubscriptionPayload { subscription, result };
+		Some(ParsedSubscriptionNotif::Response(SubscriptionResponse::new(method, payload)))
+	} else {
+		let payload = SubscriptionPayloadError { subscription, error: params.remove("error")? };
+		Some(ParsedSubscriptionNotif::Error(SubscriptionError::new(method, payload)))
+	}
+}
+
+/// Attempts to process a subscription response or error whose subscription ID has been parsed by a
+/// [`SubscriptionIdParser`].
+///
+/// Returns `Some(sub_id)` if the subscription should be closed otherwise
+/// `None` is returned.
+pub(crate) fn process_parsed_subscription_notif(
+	manager: &mut RequestManager,
+	notif: ParsedSubscriptionNotif,
+) -> Option<SubscriptionId<'static>> {
+	match notif {
+		ParsedSubscriptionNotif::Response(response) => {
+			let SubscriptionResponse { method, params: SubscriptionPayload { subscription, result }, .. } = response;
+			let result = match serde_json::value::to_raw_value(&result) {
+				Ok(result) => result,
+				Err(e) => {
+					tracing::debug!(target: LOG_TARGET, "Subscription {:?} sent an invalid result: {e}", subscription);
+					return None;
+				}
+			};
+			let payload = SubscriptionPayload { subscription, result: &*result };
+			process_subscription_response(manager, SubscriptionResponse::new(method, payload))
+		}
+		ParsedSubscriptionNotif::Error(response) => {
+			process_subscription_close_response(manager, response);
+			None
+		}
+	}
+}
+
+/// Parse the subscription ID sent by the server, by the [`SubscriptionIdParser`] of the client if any.
+fn parse_subscription_id(parser: Option<&dyn SubscriptionIdParser>, id: JsonValue) -> Option<SubscriptionId<'static>> {
+	match parser {
+		Some(parser) => parser.parse(&id),
+		None => SubscriptionId::try_from(id).ok(),
+	}
+}
+
 /// Attempts to close a subscription when a [`SubscriptionError`] is received.
 ///
 /// If the notification is not found it's just logged as a warning and the connection
 /// will continue.
 ///
 /// It's possible that the user closed down the subscription before the actual close response is received
-pub(crate) fn process_subscription_close_response(
-	manager: &mut RequestManager,
-	response: SubscriptionError<JsonValue>,
-) {
+pub(crate) fn process_subscription_close_response<T>(manager: &mut RequestManager, response: SubscriptionError<T>) {
 	let sub_id = response.params.subscription.into_owned();
 	match manager.get_request_id_by_subscription_id(&sub_id) {
 		Some(request_id) => {
@@ -149,8 +214,8 @@ pub(crate) fn process_subscription_close_response(
 /// It's possible that user close down the subscription before this notification is received.
 pub(crate) fn process_notification(manager: &mut RequestManager, notif: Notification) {
 	match manager.as_notification_handler_mut(notif.method.to_string()) {
-		// If the notification doesn't have params, we just send an empty JSON object to indicate that to the user.
-		Some(send_back_sink) => match send_back_sink.send(notif.params.unwrap_or_default()) {
+		// If the notification doesn't have params, we just send `null` to indicate that to the user.
+		Some(send_back_sink) => match send_back_sink.send(notif.params.unwrap_or(JsonRawValue::NULL).to_owned()) {
 			Ok(()) => (),
 			Err(TrySubscriptionSendError::Closed) => {
 				let _ = manager.remove_notification_handler(&notif.method);
@@ -175,6 +240,7 @@ pub(crate) fn process_single_response(
 	manager: &mut RequestManager,
 	response: Response<JsonValue>,
 	max_capacity_per_subscription: usize,
+	sub_id_parser: Option<&dyn SubscriptionIdParser>,
 ) -> Result<Option<RequestMessage>, InvalidRequestId> {
 	let response_id = response.id.clone().into_owned();
 	let result = ResponseSuccess::try_from(response).map(|s| s.result).map_err(Error::Call);
@@ -195,7 +261,7 @@ pub(crate) fn process_single_response(
 				.complete_pending_subscription(response_id.clone())
 				.ok_or(InvalidRequestId::NotPendingRequest(response_id.to_string()))?;
 
-			let sub_id = result.map(|r| SubscriptionId::try_from(r).ok());
+			let sub_id = result.map(|r| parse_subscription_id(sub_id_parser, r));
 
 			let sub_id = match sub_id {
 				Ok(Some(sub_id)) => sub_id,
@@ -216,7 +282,7 @@ pub(crate) fn process_single_response(
 			{
 				match send_back_oneshot.send(Ok((subscribe_rx, sub_id.clone()))) {
 					Ok(_) => Ok(None),
-					Err(_) => Ok(build_unsubscribe_message(manager, response_id, sub_id)),
+					Err(_) => Ok(build_unsubscribe_message(manager, response_id, sub_id, sub_id_parser)),
 				}
 			} else {
 				let _ = send_back_oneshot.send(Err(Error::InvalidSubscriptionId));
@@ -247,11 +313,15 @@ pub(crate) fn build_unsubscribe_message(
 	manager: &mut RequestManager,
 	sub_req_id: Id<'static>,
 	sub_id: SubscriptionId<'static>,
+	sub_id_parser: Option<&dyn SubscriptionIdParser>,
 ) -> Option<RequestMessage> {
 	let (unsub_req_id, _, unsub, sub_id) = manager.unsubscribe(sub_req_id, sub_id)?;
 
 	let mut params = ArrayParams::new();
-	params.insert(sub_id).ok()?;
+	match sub_id_parser {
+		Some(parser) => params.insert(parser.to_json(&sub_id)).ok()?,
+		None => params.insert(sub_id).ok()?,
+	}
 	let params = params.to_rpc_params().ok()?;
 
 	let raw = serde_json::to_string(&RequestSer::owned(unsub_req_id.clone(), unsub, params)).ok()?;
@@ -250,6 +250,19 @@ impl RequestManager {
 		}
 	}
 
+	/// Drop the channels of the pending method calls whose caller has gone away.
+	///
+	/// The calls stay pending such that their responses are still recognized when they arrive.
+	pub(crate) fn release_cancelled_calls(&mut self) {
+		for kind in self.requests.values_mut() {
+			if let Kind::PendingMethodCall(send_back) = kind {
+				if send_back.as_ref().is_some_and(|s| s.is_closed()) {
+					*send_back = None;
+				}
+			}
+		}
+	}
+
 	/// Removes the subscription without waiting for the unsubscribe call.
 	///
 	/// Returns `Some` if the subscription was removed.
@@ -355,6 +368,23 @@ mod tests {
 		assert!(manager.complete_pending_call(Id::Number(0)).is_some());
 	}
 
+	#[test]
+	fn release_cancelled_calls_works() {
+		let (cancelled_tx, cancelled_rx) = oneshot::channel::<Result<JsonValue, Error>>();
+		let (request_tx, _request_rx) = oneshot::channel::<Result<JsonValue, Error>>();
+
+		let mut manager = RequestManager::new();
+		assert!(manager.insert_pending_call(Id::Number(0), Some(cancelled_tx)).is_ok());
+		assert!(manager.insert_pending_call(Id::Number(1), Some(request_tx)).is_ok());
+
+		drop(cancelled_rx);
+		manager.release_cancelled_calls();
+
+		// The cancelled call is still pending such that its response is recognized.
+		assert!(matches!(manager.complete_pending_call(Id::Number(0)), Some(None)));
+		assert!(matches!(manager.complete_pending_call(Id::Number(1)), Some(Some(_))));
+	}
+
 	#[test]
 	fn insert_remove_subscription_works() {
 		let (pending_sub_tx, _) = oneshot::channel();
@@ -28,8 +28,11 @@
 
 mod helpers;
 mod manager;
+mod scope;
 mod utils;
 
+pub use scope::{ClientScope, ClientScopeHandle};
+
 use crate::client::async_client::helpers::{process_subscription_close_response, InnerBatchResponse};
 use crate::client::async_client::utils::MaybePendingFutures;
 use crate::client::{
@@ -39,34 +42,38 @@ use crate::client::{
 use crate::error::RegisterMethodError;
 use crate::params::{BatchRequestBuilder, EmptyBatchRequest};
 use crate::tracing::client::{rx_log_from_json, tx_log_from_str};
-use crate::traits::ToRpcParams;
-use crate::JsonRawValue;
-use std::borrow::Cow as StdCow;
+use crate::traits::{SubscriptionIdParser, ToRpcParams};
+use crate::{JsonRawValue, Limits};
 
 use core::time::Duration;
 use helpers::{
-	build_unsubscribe_message, call_with_timeout, process_batch_response, process_notification,
-	process_single_response, process_subscription_response, stop_subscription,
+	build_unsubscribe_message, call_with_timeout, parse_subscription_notif, process_batch_response,
+	process_notification, process_parsed_subscription_notif, process_single_response, process_subscription_response,
+	stop_subscription,
 };
-use jsonrpsee_types::{InvalidRequestId, ResponseSuccess, TwoPointZero};
+use jsonrpsee_types::{InvalidRequestId, ResponseSuccess};
 use manager::RequestManager;
+use std::fmt;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 
-use async_trait::async_trait;
 use futures_timer::Delay;
 use futures_util::future::{self, Either};
 use futures_util::stream::StreamExt;
 use futures_util::Stream;
 use jsonrpsee_types::response::{ResponsePayload, SubscriptionError};
-use jsonrpsee_types::{NotificationSer, RequestSer, Response, SubscriptionResponse};
+use jsonrpsee_types::{Response, SubscriptionResponse};
 use serde::de::DeserializeOwned;
 use tokio::sync::{mpsc, oneshot};
 use tracing::instrument;
 
 use self::utils::{InactivityCheck, IntervalStream};
-use super::{generate_batch_id_range, subscription_channel, FrontToBack, IdKind, RequestIdManager};
+use super::{
+	generate_batch_id_range_with, serialize_batch_request_with, serialize_notification, serialize_request,
+	subscription_channel, FrontToBack, IdKind, RequestIdGenerator, RequestIdManager,
+};
 
-pub(crate) type Notification<'a> = jsonrpsee_types::Notification<'a, Option<serde_json::Value>>;
+pub(crate) type Notification<'a> = jsonrpsee_types::Notification<'a, Option<&'a JsonRawValue>>;
 
 const LOG_TARGET: &str = "jsonrpsee-client";
 const NOT_POISONED: &str = "Not poisoned; qed";
@@ -177,15 +184,16 @@ impl ErrorFromBack {
 }
 
 /// Builder for [`Client`].
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Clone)]
 pub struct ClientBuilder {
 	request_timeout: Duration,
 	max_concurrent_requests: usize,
 	max_buffer_capacity_per_subscription: usize,
-	id_kind: IdKind,
+	id_generator: Arc<dyn RequestIdGenerator>,
 	max_log_length: u32,
 	ping_config: Option<PingConfig>,
 	tcp_no_delay: bool,
+	subscription_id_parser: Option<Arc<dyn SubscriptionIdParser>>,
 }
 
 impl Default for ClientBuilder {
@@ -194,10 +202,11 @@ impl Default for ClientBuilder {
 			request_timeout: Duration::from_secs(60),
 			max_concurrent_requests: 256,
 			max_buffer_capacity_per_subscription: 1024,
-			id_kind: IdKind::Number,
+			id_generator: Arc::new(IdKind::Number),
 			max_log_length: 4096,
 			ping_config: None,
 			tcp_no_delay: true,
+			subscription_id_parser: None,
 		}
 	}
 }
@@ -208,6 +217,14 @@ impl ClientBuilder {
 		ClientBuilder::default()
 	}
 
+	/// Apply the max log length and use [`Limits::timeout`] as request timeout, the other
+	/// limits are applied by the transport.
+	pub fn with_limits(mut self, limits: Limits) -> Self {
+		self.request_timeout = limits.timeout;
+		self.max_log_length = limits.max_log_length;
+		self
+	}
+
 	/// Set request timeout (default is 60 seconds).
 	pub fn request_timeout(mut self, timeout: Duration) -> Self {
 		self.request_timeout = timeout;
@@ -239,7 +256,14 @@ impl ClientBuilder {
 
 	/// Configure the data type of the request object ID (default is number).
 	pub fn id_format(mut self, id_kind: IdKind) -> Self {
-		self.id_kind = id_kind;
+		self.id_generator = Arc::new(id_kind);
+		self
+	}
+
+	/// Configure a custom generator of the request object IDs, which replaces
+	/// the format set by [`ClientBuilder::id_format`].
+	pub fn id_generator<G: RequestIdGenerator + 'static>(mut self, id_generator: G) -> Self {
+		self.id_generator = Arc::new(id_generator);
 		self
 	}
 
@@ -279,6 +303,15 @@ impl ClientBuilder {
 		self
 	}
 
+	/// Set the parser of the subscription IDs sent by the server, which allows for
+	/// subscription IDs that are neither a string nor a number.
+	///
+	/// Default: the subscription IDs must be a string or a number.
+	pub fn set_subscription_id_parser<P: SubscriptionIdParser + 'static>(mut self, parser: P) -> Self {
+		self.subscription_id_parser = Some(Arc::new(parser));
+		self
+	}
+
 	/// Build the client with given transport.
 	///
 	/// ## Panics
@@ -292,6 +325,7 @@ impl ClientBuilder {
 		R: TransportReceiverT + Send,
 	{
 		let (to_back, from_front) = mpsc::channel(self.max_concurrent_requests);
+		let (cancellations, from_scopes) = mpsc::unbounded_channel();
 		let disconnect_reason = SharedDisconnectReason::default();
 		let max_buffer_capacity_per_subscription = self.max_buffer_capacity_per_subscription;
 		let (client_dropped_tx, client_dropped_rx) = oneshot::channel();
@@ -324,9 +358,11 @@ impl ClientBuilder {
 		tokio::spawn(send_task(SendTaskParams {
 			sender,
 			from_frontend: from_front,
+			from_scopes,
 			close_tx: send_receive_task_sync_tx.clone(),
 			manager: manager.clone(),
 			max_buffer_capacity_per_subscription,
+			subscription_id_parser: self.subscription_id_parser.clone(),
 			ping_interval,
 		}));
 
@@ -336,6 +372,8 @@ impl ClientBuilder {
 			to_send_task: to_back.clone(),
 			manager,
 			max_buffer_capacity_per_subscription: self.max_buffer_capacity_per_subscription,
+			subscription_id_parser: self.subscription_id_parser,
+			id_generator: self.id_generator.clone(),
 			inactivity_check,
 			inactivity_stream,
 		}));
@@ -344,9 +382,10 @@ impl ClientBuilder {
 
 		Client {
 			to_back: to_back.clone(),
+			cancellations,
 			request_timeout: self.request_timeout,
 			error: ErrorFromBack::new(to_back, disconnect_reason),
-			id_manager: RequestIdManager::new(self.id_kind),
+			id_manager: RequestIdManager::with_generator(self.id_generator),
 			max_log_length: self.max_log_length,
 			on_exit: Some(client_dropped_tx),
 		}
@@ -365,6 +404,7 @@ impl ClientBuilder {
 		type PendingIntervalStream = IntervalStream<Pending<()>>;
 
 		let (to_back, from_front) = mpsc::channel(self.max_concurrent_requests);
+		let (cancellations, from_scopes) = mpsc::unbounded_channel();
 		let disconnect_reason = SharedDisconnectReason::default();
 		let max_buffer_capacity_per_subscription = self.max_buffer_capacity_per_subscription;
 		let (client_dropped_tx, client_dropped_rx) = oneshot::channel();
@@ -378,9 +418,11 @@ impl ClientBuilder {
 		wasm_bindgen_futures::spawn_local(send_task(SendTaskParams {
 			sender,
 			from_frontend: from_front,
+			from_scopes,
 			close_tx: send_receive_task_sync_tx.clone(),
 			manager: manager.clone(),
 			max_buffer_capacity_per_subscription,
+			subscription_id_parser: self.subscription_id_parser.clone(),
 			ping_interval,
 		}));
 
@@ -390,6 +432,8 @@ impl ClientBuilder {
 			to_send_task: to_back.clone(),
 			manager,
 			max_buffer_capacity_per_subscription: self.max_buffer_capacity_per_subscription,
+			subscription_id_parser: self.subscription_id_parser,
+			id_generator: self.id_generator.clone(),
 			inactivity_check,
 			inactivity_stream,
 		}));
@@ -402,9 +446,10 @@ impl ClientBuilder {
 
 		Client {
 			to_back: to_back.clone(),
+			cancellations,
 			request_timeout: self.request_timeout,
 			error: ErrorFromBack::new(to_back, disconnect_reason),
-			id_manager: RequestIdManager::new(self.id_kind),
+			id_manager: RequestIdManager::with_generator(self.id_generator),
 			max_log_length: self.max_log_length,
 			on_exit: Some(client_dropped_tx),
 		}
@@ -416,6 +461,10 @@ impl ClientBuilder {
 pub struct Client {
 	/// Channel to send requests to the background task.
 	to_back: mpsc::Sender<FrontToBack>,
+	/// Channel to send the messages of cancelled scopes to the background task.
+	///
+	/// It's unbounded such that the messages are never dropped, even if the channel of the requests is full.
+	cancellations: mpsc::UnboundedSender<FrontToBack>,
 	error: ErrorFromBack,
 	/// Request timeout. Defaults to 60sec.
 	request_timeout: Duration,
@@ -462,6 +511,46 @@ impl Client {
 	pub async fn on_disconnect(&self) {
 		self.to_back.closed().await;
 	}
+
+	/// Create a scope of calls and subscriptions made through this client, which are
+	/// aborted when the scope is dropped or cancelled, see [`ClientScope`] for details.
+	pub fn scope(&self) -> ClientScope<'_> {
+		ClientScope::new(self)
+	}
+
+	/// Send a method call which times out after `timeout`.
+	async fn send_request<R, Params>(&self, method: &str, params: Params, timeout: Duration) -> Result<R, Error>
+	where
+		R: DeserializeOwned,
+		Params: ToRpcParams + Send,
+	{
+		let (send_back_tx, send_back_rx) = oneshot::channel();
+		let id = self.id_manager.next_request_id();
+
+		let params = params.to_rpc_params()?;
+		let raw = serialize_request(&id, method, params.as_deref())?;
+		tx_log_from_str(&raw, self.max_log_length);
+
+		if self
+			.to_back
+			.clone()
+			.send(FrontToBack::Request(RequestMessage { raw, id: id.clone(), send_back: Some(send_back_tx) }))
+			.await
+			.is_err()
+		{
+			return Err(self.disconnect_reason().await);
+		}
+
+		let json_value = match call_with_timeout(timeout, send_back_rx).await {
+			Ok(Ok(v)) => v,
+			Ok(Err(err)) => return Err(err),
+			Err(_) => return Err(self.disconnect_reason().await),
+		};
+
+		rx_log_from_json(&Response::new(ResponsePayload::success_borrowed(&json_value), id), self.max_log_length);
+
+		serde_json::from_value(json_value).map_err(Error::ParseError)
+	}
 }
 
 impl Drop for Client {
@@ -472,7 +561,6 @@ impl Drop for Client {
 	}
 }
 
-#[async_trait]
 impl ClientT for Client {
 	#[instrument(name = "notification", skip(self, params), level = "trace")]
 	async fn notification<Params>(&self, method: &str, params: Params) -> Result<(), Error>
@@ -482,9 +570,7 @@ impl ClientT for Client {
 		// NOTE: we use this to guard against max number of concurrent requests.
 		let _req_id = self.id_manager.next_request_id();
 		let params = params.to_rpc_params()?;
-		let notif = NotificationSer::borrowed(&method, params.as_deref());
-
-		let raw = serde_json::to_string(&notif).map_err(Error::ParseError)?;
+		let raw = serialize_notification(method, params.as_deref())?;
 		tx_log_from_str(&raw, self.max_log_length);
 
 		let sender = self.to_back.clone();
@@ -505,60 +591,50 @@ impl ClientT for Client {
 		R: DeserializeOwned,
 		Params: ToRpcParams + Send,
 	{
-		let (send_back_tx, send_back_rx) = oneshot::channel();
-		let id = self.id_manager.next_request_id();
-
-		let params = params.to_rpc_params()?;
-		let raw =
-			serde_json::to_string(&RequestSer::borrowed(&id, &method, params.as_deref())).map_err(Error::ParseError)?;
-		tx_log_from_str(&raw, self.max_log_length);
-
-		if self
-			.to_back
-			.clone()
-			.send(FrontToBack::Request(RequestMessage { raw, id: id.clone(), send_back: Some(send_back_tx) }))
-			.await
-			.is_err()
-		{
-			return Err(self.disconnect_reason().await);
-		}
-
-		let json_value = match call_with_timeout(self.request_timeout, send_back_rx).await {
-			Ok(Ok(v)) => v,
-			Ok(Err(err)) => return Err(err),
-			Err(_) => return Err(self.disconnect_reason().await),
-		};
-
-		rx_log_from_json(&Response::new(ResponsePayload::success_borrowed(&json_value), id), self.max_log_length);
+		self.send_request(method, params, self.request_timeout).await
+	}
 
-		serde_json::from_value(json_value).map_err(Error::ParseError)
+	#[instrument(name = "method_call", skip(self, params), level = "trace")]
+	async fn request_with_timeout<R, Params>(&self, method: &str, params: Params, timeout: Duration) -> Result<R, Error>
+	where
+		R: DeserializeOwned,
+		Params: ToRpcParams + Send,
+	{
+		self.send_request(method, params, timeout.min(self.request_timeout)).await
 	}
 
 	#[instrument(name = "batch", skip(self, batch), level = "trace")]
 	async fn batch_request<'a, R>(&self, batch: BatchRequestBuilder<'a>) -> Result<BatchResponse<'a, R>, Error>
 	where
-		R: DeserializeOwned,
+		R: DeserializeOwned + fmt::Debug + 'a,
 	{
+		let calls: Vec<_> = batch.calls().collect();
+		let num_calls = calls.len();
 		let batch = batch.build()?;
 		let id = self.id_manager.next_request_id();
-		let id_range = generate_batch_id_range(id, batch.len() as u64)?;
-
-		let mut batches = Vec::with_capacity(batch.len());
-		for ((method, params), id) in batch.into_iter().zip(id_range.clone()) {
-			let id = self.id_manager.as_id_kind().into_id(id);
-			batches.push(RequestSer {
-				jsonrpc: TwoPointZero,
-				id,
-				method: method.into(),
-				params: params.map(StdCow::Owned),
-			});
-		}
+		let id_generator = self.id_manager.as_id_generator();
+		let id_range = generate_batch_id_range_with(id_generator, id, num_calls as u64)?;
+		let raw = serialize_batch_request_with(batch, id_generator, id_range.clone())?;
 
-		let (send_back_tx, send_back_rx) = oneshot::channel();
+		tx_log_from_str(&raw, self.max_log_length);
 
-		let raw = serde_json::to_string(&batches).map_err(Error::ParseError)?;
+		// A batch of notifications is not answered.
+		if num_calls == 0 {
+			let sender = self.to_back.clone();
+			let fut = sender.send(FrontToBack::Notification(raw));
 
-		tx_log_from_str(&raw, self.max_log_length);
+			tokio::pin!(fut);
+
+			return match future::select(fut, Delay::new(self.request_timeout)).await {
+				Either::Left((Ok(()), _)) => {
+					Ok(BatchResponse { successful_calls: 0, failed_calls: 0, responses: Vec::new(), calls })
+				}
+				Either::Left((Err(_), _)) => Err(self.disconnect_reason().await),
+				Either::Right((_, _)) => Err(Error::RequestTimeout),
+			};
+		}
+
+		let (send_back_tx, send_back_rx) = oneshot::channel();
 
 		if self
 			.to_back
@@ -596,11 +672,10 @@ impl ClientT for Client {
 				}
 			}
 		}
-		Ok(BatchResponse { successful_calls, failed_calls, responses })
+		Ok(BatchResponse { successful_calls, failed_calls, responses, calls })
 	}
 }
 
-#[async_trait]
 impl SubscriptionClientT for Client {
 	/// Send a subscription request to the server.
 	///
@@ -625,8 +700,7 @@ impl SubscriptionClientT for Client {
 		let id_unsub = self.id_manager.next_request_id();
 		let params = params.to_rpc_params()?;
 
-		let raw = serde_json::to_string(&RequestSer::borrowed(&id_sub, &subscribe_method, params.as_deref()))
-			.map_err(Error::ParseError)?;
+		let raw = serialize_request(&id_sub, subscribe_method, params.as_deref())?;
 
 		tx_log_from_str(&raw, self.max_log_length);
 
@@ -660,7 +734,7 @@ impl SubscriptionClientT for Client {
 
 	/// Subscribe to a specific method.
 	#[instrument(name = "subscribe_method", skip(self), level = "trace")]
-	async fn subscribe_to_method<'a, N>(&self, method: &'a str) -> Result<Subscription<N>, Error>
+	async fn subscribe_to_method<N>(&self, method: &str) -> Result<Subscription<N>, Error>
 	where
 		N: DeserializeOwned,
 	{
@@ -697,12 +771,16 @@ fn handle_backend_messages<R: TransportReceiverT>(
 	message: Option<Result<ReceivedMessage, R::Error>>,
 	manager: &ThreadSafeRequestManager,
 	max_buffer_capacity_per_subscription: usize,
+	sub_id_parser: Option<&dyn SubscriptionIdParser>,
+	id_generator: &dyn RequestIdGenerator,
 ) -> Result<Vec<FrontToBack>, Error> {
 	// Handle raw messages of form `ReceivedMessage::Bytes` (Vec<u8>) or ReceivedMessage::Data` (String).
 	fn handle_recv_message(
 		raw: &[u8],
 		manager: &ThreadSafeRequestManager,
 		max_buffer_capacity_per_subscription: usize,
+		sub_id_parser: Option<&dyn SubscriptionIdParser>,
+		id_generator: &dyn RequestIdGenerator,
 	) -> Result<Vec<FrontToBack>, Error> {
 		let first_non_whitespace = raw.iter().find(|byte| !byte.is_ascii_whitespace());
 		let mut messages = Vec::new();
@@ -711,13 +789,23 @@ fn handle_backend_messages<R: TransportReceiverT>(
 			Some(b'{') => {
 				// Single response to a request.
 				if let Ok(single) = serde_json::from_slice::<Response<_>>(raw) {
-					let maybe_unsub =
-						process_single_response(&mut manager.lock(), single, max_buffer_capacity_per_subscription)?;
+					let maybe_unsub = process_single_response(
+						&mut manager.lock(),
+						single,
+						max_buffer_capacity_per_subscription,
+						sub_id_parser,
+					)?;
 
 					if let Some(unsub) = maybe_unsub {
 						return Ok(vec![FrontToBack::Request(unsub)]);
 					}
 				}
+				// Subscription response or error whose subscription ID is parsed by the user.
+				else if let Some(notif) = sub_id_parser.and_then(|parser| parse_subscription_notif(raw, parser)) {
+					if let Some(sub_id) = process_parsed_subscription_notif(&mut manager.lock(), notif) {
+						return Ok(vec![FrontToBack::SubscriptionClosed(sub_id)]);
+					}
+				}
 				// Subscription response.
 				else if let Ok(response) = serde_json::from_slice::<SubscriptionResponse<_>>(raw) {
 					if let Some(sub_id) = process_subscription_response(&mut manager.lock(), response) {
@@ -725,7 +813,7 @@ fn handle_backend_messages<R: TransportReceiverT>(
 					}
 				}
 				// Subscription error response.
-				else if let Ok(response) = serde_json::from_slice::<SubscriptionError<_>>(raw) {
+				else if let Ok(response) = serde_json::from_slice::<SubscriptionError<&JsonRawValue>>(raw) {
 					process_subscription_close_response(&mut manager.lock(), response);
 				}
 				// Incoming Notification
@@ -745,7 +833,7 @@ fn handle_backend_messages<R: TransportReceiverT>(
 
 					for r in raw_responses {
 						if let Ok(response) = serde_json::from_str::<Response<_>>(r.get()) {
-							let id = response.id.try_parse_inner_as_number()?;
+							let id = id_generator.parse_id(&response.id)?;
 							let result = ResponseSuccess::try_from(response).map(|s| s.result);
 							batch.push(InnerBatchResponse { id, result });
 
@@ -758,12 +846,19 @@ fn handle_backend_messages<R: TransportReceiverT>(
 							if id > r.end {
 								r.end = id;
 							}
+						} else if let Some(notif) =
+							sub_id_parser.and_then(|parser| parse_subscription_notif(r.get().as_bytes(), parser))
+						{
+							got_notif = true;
+							if let Some(sub_id) = process_parsed_subscription_notif(&mut manager.lock(), notif) {
+								messages.push(FrontToBack::SubscriptionClosed(sub_id));
+							}
 						} else if let Ok(response) = serde_json::from_str::<SubscriptionResponse<_>>(r.get()) {
 							got_notif = true;
 							if let Some(sub_id) = process_subscription_response(&mut manager.lock(), response) {
 								messages.push(FrontToBack::SubscriptionClosed(sub_id));
 							}
-						} else if let Ok(response) = serde_json::from_slice::<SubscriptionError<_>>(raw) {
+						} else if let Ok(response) = serde_json::from_slice::<SubscriptionError<&JsonRawValue>>(raw) {
 							got_notif = true;
 							process_subscription_close_response(&mut manager.lock(), response);
 						} else if let Ok(notif) = serde_json::from_str::<Notification>(r.get()) {
@@ -798,12 +893,20 @@ fn handle_backend_messages<R: TransportReceiverT>(
 			tracing::debug!(target: LOG_TARGET, "Received pong");
 			Ok(vec![])
 		}
-		Some(Ok(ReceivedMessage::Bytes(raw))) => {
-			handle_recv_message(raw.as_ref(), manager, max_buffer_capacity_per_subscription)
-		}
-		Some(Ok(ReceivedMessage::Text(raw))) => {
-			handle_recv_message(raw.as_ref(), manager, max_buffer_capacity_per_subscription)
-		}
+		Some(Ok(ReceivedMessage::Bytes(raw))) => handle_recv_message(
+			raw.as_ref(),
+			manager,
+			max_buffer_capacity_per_subscription,
+			sub_id_parser,
+			id_generator,
+		),
+		Some(Ok(ReceivedMessage::Text(raw))) => handle_recv_message(
+			raw.as_ref(),
+			manager,
+			max_buffer_capacity_per_subscription,
+			sub_id_parser,
+			id_generator,
+		),
 		Some(Err(e)) => Err(Error::Transport(e.into())),
 		None => Err(Error::Custom("TransportReceiver dropped".into())),
 	}
@@ -815,6 +918,7 @@ async fn handle_frontend_messages<S: TransportSenderT>(
 	manager: &ThreadSafeRequestManager,
 	sender: &mut S,
 	max_buffer_capacity_per_subscription: usize,
+	sub_id_parser: Option<&dyn SubscriptionIdParser>,
 ) -> Result<(), S::Error> {
 	match message {
 		FrontToBack::Batch(batch) => {
@@ -832,6 +936,11 @@ async fn handle_frontend_messages<S: TransportSenderT>(
 		}
 		// User called `request` on the front-end
 		FrontToBack::Request(request) => {
+			// The call was cancelled or timed out before it was sent.
+			if request.send_back.as_ref().is_some_and(|s| s.is_closed()) {
+				return Ok(());
+			}
+
 			if let Err(send_back) = manager.lock().insert_pending_call(request.id.clone(), request.send_back) {
 				tracing::debug!(target: LOG_TARGET, "Denied duplicate method call");
 
@@ -873,7 +982,7 @@ async fn handle_frontend_messages<S: TransportSenderT>(
 				let m = &mut *manager.lock();
 
 				m.get_request_id_by_subscription_id(&sub_id)
-					.and_then(|req_id| build_unsubscribe_message(m, req_id, sub_id))
+					.and_then(|req_id| build_unsubscribe_message(m, req_id, sub_id, sub_id_parser))
 			};
 
 			if let Some(unsub) = maybe_unsub {
@@ -894,6 +1003,11 @@ async fn handle_frontend_messages<S: TransportSenderT>(
 		FrontToBack::UnregisterNotification(method) => {
 			let _ = manager.lock().remove_notification_handler(&method);
 		}
+		// User cancelled calls of a scope.
+		FrontToBack::ReleaseCancelledCalls(scheduled) => {
+			scheduled.store(false, Ordering::Release);
+			manager.lock().release_cancelled_calls();
+		}
 	};
 
 	Ok(())
@@ -913,9 +1027,11 @@ fn unparse_error(raw: &[u8]) -> Error {
 struct SendTaskParams<T: TransportSenderT, S> {
 	sender: T,
 	from_frontend: mpsc::Receiver<FrontToBack>,
+	from_scopes: mpsc::UnboundedReceiver<FrontToBack>,
 	close_tx: mpsc::Sender<Result<(), Error>>,
 	manager: ThreadSafeRequestManager,
 	max_buffer_capacity_per_subscription: usize,
+	subscription_id_parser: Option<Arc<dyn SubscriptionIdParser>>,
 	ping_interval: IntervalStream<S>,
 }
 
@@ -927,36 +1043,48 @@ where
 	let SendTaskParams {
 		mut sender,
 		mut from_frontend,
+		mut from_scopes,
 		close_tx,
 		manager,
 		max_buffer_capacity_per_subscription,
+		subscription_id_parser,
 		mut ping_interval,
 	} = params;
 
 	// This is safe because `tokio::time::Interval`, `tokio::mpsc::Sender` and `tokio::mpsc::Receiver`
 	// are cancel-safe.
 	let res = loop {
-		tokio::select! {
+		let msg = tokio::select! {
 			biased;
 			_ = close_tx.closed() => break Ok(()),
+			// The channel is closed once the client and its scopes have been dropped.
+			Some(msg) = from_scopes.recv() => msg,
 			maybe_msg = from_frontend.recv() => {
 				let Some(msg) = maybe_msg else {
 					break Ok(());
 				};
-
-				if let Err(e) =
-					handle_frontend_messages(msg, &manager, &mut sender, max_buffer_capacity_per_subscription).await
-				{
-					tracing::debug!(target: LOG_TARGET, "ws send failed: {e}");
-					break Err(Error::Transport(e.into()));
-				}
+				msg
 			}
 			_ = ping_interval.next() => {
 				if let Err(err) = sender.send_ping().await {
 					tracing::debug!(target: LOG_TARGET, "Send ws ping failed: {err}");
 					break Err(Error::Transport(err.into()));
 				}
+				continue;
 			}
+		};
+
+		if let Err(e) = handle_frontend_messages(
+			msg,
+			&manager,
+			&mut sender,
+			max_buffer_capacity_per_subscription,
+			subscription_id_parser.as_deref(),
+		)
+		.await
+		{
+			tracing::debug!(target: LOG_TARGET, "ws send failed: {e}");
+			break Err(Error::Transport(e.into()));
 		}
 	};
 
@@ -971,6 +1099,8 @@ struct ReadTaskParams<R: TransportReceiverT, S> {
 	to_send_task: mpsc::Sender<FrontToBack>,
 	manager: ThreadSafeRequestManager,
 	max_buffer_capacity_per_subscription: usize,
+	subscription_id_parser: Option<Arc<dyn SubscriptionIdParser>>,
+	id_generator: Arc<dyn RequestIdGenerator>,
 	inactivity_check: InactivityCheck,
 	inactivity_stream: IntervalStream<S>,
 }
@@ -986,6 +1116,8 @@ where
 		to_send_task,
 		manager,
 		max_buffer_capacity_per_subscription,
+		subscription_id_parser,
+		id_generator,
 		mut inactivity_check,
 		mut inactivity_stream,
 	} = params;
@@ -1017,7 +1149,13 @@ where
 				inactivity_check.mark_as_active();
 				let Some(msg) = maybe_msg else { break Ok(()) };
 
-				match handle_backend_messages::<R>(Some(msg), &manager, max_buffer_capacity_per_subscription) {
+				match handle_backend_messages::<R>(
+					Some(msg),
+					&manager,
+					max_buffer_capacity_per_subscription,
+					subscription_id_parser.as_deref(),
+					&*id_generator,
+				) {
 					Ok(messages) => {
 						for msg in messages {
 							pending_unsubscribes.push(to_send_task.send(msg));
@@ -0,0 +1,263 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any
+// person obtaining a copy of this software and associated
+// documentation files (the "Software"), to deal in the
+// Software without restriction, including without
+// limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software
+// is furnished to do so, subject to the following
+// conditions:
+//
+// The above copyright notice and this permission notice
+// shall be included in all copies or substantial portions
+// of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF
+// ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED
+// TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A
+// PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT
+// SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY
+// CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+// IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Scopes of calls made through a [`Client`].
+
+use std::fmt;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures_util::future::{self, Either};
+use serde::de::DeserializeOwned;
+use tokio::sync::{mpsc, watch};
+
+use super::Client;
+use crate::client::{BatchResponse, ClientT, Error, FrontToBack, Subscription, SubscriptionClientT, SubscriptionKind};
+use crate::params::BatchRequestBuilder;
+use crate::traits::ToRpcParams;
+
+/// Scope of the calls and subscriptions made through a [`Client`], see [`Client::scope`].
+///
+/// The scope is cancelled when it's dropped, for instance because the task which owns it
+/// was aborted, or by calling [`ClientScope::cancel`] or [`ClientScopeHandle::cancel`]. Then:
+///
+/// - The calls in progress which were made through the scope fail with [`Error::Cancelled`],
+///   and so do the calls made through the scope afterwards.
+/// - The subscriptions made through the scope are closed and unsubscribed from the server.
+///
+/// The unsubscribe calls are sent even if the client is busy. The cancelled calls are not answered
+/// anymore and the calls which haven't been sent yet are dropped, the server may still answer the
+/// calls that were already sent.
+///
+/// # Examples
+///
+/// ```no_run
+/// use jsonrpsee_core::client::{Client, ClientT};
+/// use jsonrpsee_core::rpc_params;
+///
+/// async fn call_both(client: &Client) {
+///     let scope = client.scope();
+///     let handle = scope.handle();
+///
+///     // Cancel the calls from elsewhere, dropping `scope` has the same effect.
+///     tokio::spawn(async move { handle.cancel() });
+///
+///     let _ = scope.request::<String, _>("say_hello", rpc_params![]).await;
+/// }
+/// ```
+#[derive(Debug)]
+pub struct ClientScope<'a> {
+	client: &'a Client,
+	inner: Arc<ScopeInner>,
+}
+
+impl<'a> ClientScope<'a> {
+	pub(crate) fn new(client: &'a Client) -> Self {
+		let (cancelled, _) = watch::channel(false);
+		let inner = ScopeInner {
+			cancelled,
+			cancellations: client.cancellations.clone(),
+			release_scheduled: Arc::new(AtomicBool::new(false)),
+			subscriptions: Mutex::new(Vec::new()),
+		};
+		Self { client, inner: Arc::new(inner) }
+	}
+
+	/// Get a handle to cancel the scope from elsewhere, such as another task.
+	pub fn handle(&self) -> ClientScopeHandle {
+		ClientScopeHandle(self.inner.clone())
+	}
+
+	/// Cancel the calls and subscriptions of the scope.
+	pub fn cancel(&self) {
+		self.inner.cancel();
+	}
+
+	/// Returns whether the scope has been cancelled.
+	pub fn is_cancelled(&self) -> bool {
+		*self.inner.cancelled.borrow()
+	}
+
+	/// Run `fut` until it completes or until the scope is cancelled.
+	async fn run<T>(&self, fut: impl Future<Output = Result<T, Error>>) -> Result<T, Error> {
+		let mut cancelled = self.inner.cancelled.subscribe();
+		let cancelled = async move {
+			while !*cancelled.borrow_and_update() {
+				// The sender lives as long as the scope.
+				if cancelled.changed().await.is_err() {
+					break;
+				}
+			}
+		};
+
+		let res = {
+			tokio::pin!(fut, cancelled);
+
+			match future::select(fut, cancelled).await {
+				Either::Left((res, _)) => Some(res),
+				Either::Right(_) => None,
+			}
+		};
+
+		// The call is cancelled by dropping it above, then its pending entry can be released.
+		res.unwrap_or_else(|| {
+			self.inner.release_cancelled_calls();
+			Err(Error::Cancelled)
+		})
+	}
+
+	/// Keep track of a subscription made through the scope, or close it if the scope has been cancelled.
+	fn track<Notif>(&self, sub: Subscription<Notif>) -> Result<Subscription<Notif>, Error> {
+		let mut subscriptions = self.inner.subscriptions.lock().expect("lock poisoned; qed");
+
+		if self.is_cancelled() {
+			drop(subscriptions);
+			// Dropping the subscription closes it.
+			drop(sub);
+			return Err(Error::Cancelled);
+		}
+
+		subscriptions.push(sub.kind().clone());
+		Ok(sub)
+	}
+}
+
+impl Drop for ClientScope<'_> {
+	fn drop(&mut self) {
+		self.inner.cancel();
+	}
+}
+
+/// Handle to cancel a [`ClientScope`].
+#[derive(Debug, Clone)]
+pub struct ClientScopeHandle(Arc<ScopeInner>);
+
+impl ClientScopeHandle {
+	/// Cancel the calls and subscriptions of the scope.
+	pub fn cancel(&self) {
+		self.0.cancel();
+	}
+
+	/// Returns whether the scope has been cancelled.
+	pub fn is_cancelled(&self) -> bool {
+		*self.0.cancelled.borrow()
+	}
+}
+
+#[derive(Debug)]
+struct ScopeInner {
+	cancelled: watch::Sender<bool>,
+	/// Channel to send the messages of cancelled scopes to the background task.
+	cancellations: mpsc::UnboundedSender<FrontToBack>,
+	/// Whether the background task has been asked to release the cancelled calls and hasn't done it yet.
+	release_scheduled: Arc<AtomicBool>,
+	/// The subscriptions made through the scope, which may have been closed already.
+	subscriptions: Mutex<Vec<SubscriptionKind>>,
+}
+
+impl ScopeInner {
+	fn cancel(&self) {
+		if self.cancelled.send_replace(true) {
+			return;
+		}
+
+		let subscriptions = std::mem::take(&mut *self.subscriptions.lock().expect("lock poisoned; qed"));
+
+		// Same as dropping the subscriptions, it's fine if they have already been closed.
+		for kind in subscriptions {
+			let msg = match kind {
+				SubscriptionKind::Method(notif) => FrontToBack::UnregisterNotification(notif),
+				SubscriptionKind::Subscription(sub_id) => FrontToBack::SubscriptionClosed(sub_id),
+			};
+			let _ = self.cancellations.send(msg);
+		}
+	}
+
+	fn release_cancelled_calls(&self) {
+		// The calls which are cancelled until the background task handles the message are released as well.
+		if !self.release_scheduled.swap(true, Ordering::AcqRel) {
+			let _ = self.cancellations.send(FrontToBack::ReleaseCancelledCalls(self.release_scheduled.clone()));
+		}
+	}
+}
+
+impl ClientT for ClientScope<'_> {
+	async fn notification<Params>(&self, method: &str, params: Params) -> Result<(), Error>
+	where
+		Params: ToRpcParams + Send,
+	{
+		self.run(self.client.notification(method, params)).await
+	}
+
+	async fn request<R, Params>(&self, method: &str, params: Params) -> Result<R, Error>
+	where
+		R: DeserializeOwned,
+		Params: ToRpcParams + Send,
+	{
+		self.run(self.client.request(method, params)).await
+	}
+
+	async fn request_with_timeout<R, Params>(&self, method: &str, params: Params, timeout: Duration) -> Result<R, Error>
+	where
+		R: DeserializeOwned,
+		Params: ToRpcParams + Send,
+	{
+		self.run(self.client.request_with_timeout(method, params, timeout)).await
+	}
+
+	async fn batch_request<'a, R>(&self, batch: BatchRequestBuilder<'a>) -> Result<BatchResponse<'a, R>, Error>
+	where
+		R: DeserializeOwned + fmt::Debug + 'a,
+	{
+		self.run(self.client.batch_request(batch)).await
+	}
+}
+
+impl SubscriptionClientT for ClientScope<'_> {
+	async fn subscribe<'a, Notif, Params>(
+		&self,
+		subscribe_method: &'a str,
+		params: Params,
+		unsubscribe_method: &'a str,
+	) -> Result<Subscription<Notif>, Error>
+	where
+		Params: ToRpcParams + Send,
+		Notif: DeserializeOwned,
+	{
+		let sub = self.run(self.client.subscribe(subscribe_method, params, unsubscribe_method)).await?;
+		self.track(sub)
+	}
+
+	async fn subscribe_to_method<Notif>(&self, method: &str) -> Result<Subscription<Notif>, Error>
+	where
+		Notif: DeserializeOwned,
+	{
+		let sub = self.run(self.client.subscribe_to_method(method)).await?;
+		self.track(sub)
+	}
+}
@@ -0,0 +1,261 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any
+// person obtaining a copy of this software and associated
+// documentation files (the "Software"), to deal in the
+// Software without restriction, including without
+// limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software
+// is furnished to do so, subject to the following
+// conditions:
+//
+// The above copyright notice and this permission notice
+// shall be included in all copies or substantial portions
+// of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF
+// ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED
+// TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A
+// PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT
+// SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY
+// CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+// IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Client traits whose methods return boxed futures, for the implementations which are written
+//! with [`async_trait`](crate::async_trait).
+//!
+//! Each type which implements [`ClientT`] or [`SubscriptionClientT`] of this module implements the
+//! trait of the same name in [`crate::client`], such that an implementation only has to change the
+//! path of the trait it implements:
+//!
+//! ```rust
+//! use jsonrpsee_core::async_trait;
+//! use jsonrpsee_core::client::compat::ClientT;
+//! use jsonrpsee_core::client::{BatchResponse, Error};
+//! use jsonrpsee_core::params::BatchRequestBuilder;
+//! use jsonrpsee_core::traits::ToRpcParams;
+//! use jsonrpsee_core::DeserializeOwned;
+//!
+//! struct Offline;
+//!
+//! #[async_trait]
+//! impl ClientT for Offline {
+//!     async fn notification<Params>(&self, _method: &str, _params: Params) -> Result<(), Error>
+//!     where
+//!         Params: ToRpcParams + Send,
+//!     {
+//!         Err(Error::Custom("offline".into()))
+//!     }
+//!
+//!     async fn request<R, Params>(&self, _method: &str, _params: Params) -> Result<R, Error>
+//!     where
+//!         R: DeserializeOwned,
+//!         Params: ToRpcParams + Send,
+//!     {
+//!         Err(Error::Custom("offline".into()))
+//!     }
+//!
+//!     async fn batch_request<'a, R>(&self, _batch: BatchRequestBuilder<'a>) -> Result<BatchResponse<'a, R>, Error>
+//!     where
+//!         R: DeserializeOwned + std::fmt::Debug + 'a,
+//!     {
+//!         Err(Error::Custom("offline".into()))
+//!     }
+//! }
+//!
+//! # async fn call() {
+//! use jsonrpsee_core::rpc_params;
+//!
+//! // `Offline` implements `jsonrpsee_core::client::ClientT`.
+//! let err = jsonrpsee_core::client::ClientT::request::<String, _>(&Offline, "say_hello", rpc_params![])
+//!     .await
+//!     .unwrap_err();
+//! assert_eq!(err.to_string(), "Custom error: offline");
+//! # }
+//! ```
+
+use std::fmt;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+
+use super::{BatchResponse, BatchResponseStream, Error, Subscription};
+use crate::params::BatchRequestBuilder;
+use crate::traits::ToRpcParams;
+
+/// Same as [`super::ClientT`], with boxed futures.
+#[async_trait]
+pub trait ClientT {
+	/// See [`super::ClientT::notification`].
+	async fn notification<Params>(&self, method: &str, params: Params) -> Result<(), Error>
+	where
+		Params: ToRpcParams + Send;
+
+	/// See [`super::ClientT::request`].
+	async fn request<R, Params>(&self, method: &str, params: Params) -> Result<R, Error>
+	where
+		R: DeserializeOwned,
+		Params: ToRpcParams + Send;
+
+	/// See [`super::ClientT::request_with_timeout`].
+	async fn request_with_timeout<R, Params>(&self, method: &str, params: Params, timeout: Duration) -> Result<R, Error>
+	where
+		R: DeserializeOwned,
+		Params: ToRpcParams + Send,
+	{
+		super::with_timeout(self.request(method, params), timeout).await
+	}
+
+	/// See [`super::ClientT::batch_request`].
+	async fn batch_request<'a, R>(&self, batch: BatchRequestBuilder<'a>) -> Result<BatchResponse<'a, R>, Error>
+	where
+		R: DeserializeOwned + fmt::Debug + 'a;
+
+	/// See [`super::ClientT::batch_request_stream`].
+	async fn batch_request_stream<'a, R>(
+		&self,
+		batch: BatchRequestBuilder<'a>,
+	) -> Result<BatchResponseStream<'a, R>, Error>
+	where
+		R: DeserializeOwned + fmt::Debug + Send + 'a,
+	{
+		self.batch_request::<R>(batch).await.map(BatchResponseStream::from_response)
+	}
+}
+
+/// Same as [`super::SubscriptionClientT`], with boxed futures.
+#[async_trait]
+pub trait SubscriptionClientT: ClientT {
+	/// See [`super::SubscriptionClientT::subscribe`].
+	async fn subscribe<'a, Notif, Params>(
+		&self,
+		subscribe_method: &'a str,
+		params: Params,
+		unsubscribe_method: &'a str,
+	) -> Result<Subscription<Notif>, Error>
+	where
+		Params: ToRpcParams + Send,
+		Notif: DeserializeOwned;
+
+	/// See [`super::SubscriptionClientT::subscribe_to_method`].
+	async fn subscribe_to_method<Notif>(&self, method: &str) -> Result<Subscription<Notif>, Error>
+	where
+		Notif: DeserializeOwned;
+}
+
+impl<T: ClientT + Sync + ?Sized> super::ClientT for T {
+	async fn notification<Params>(&self, method: &str, params: Params) -> Result<(), Error>
+	where
+		Params: ToRpcParams + Send,
+	{
+		ClientT::notification(self, method, params).await
+	}
+
+	async fn request<R, Params>(&self, method: &str, params: Params) -> Result<R, Error>
+	where
+		R: DeserializeOwned,
+		Params: ToRpcParams + Send,
+	{
+		ClientT::request(self, method, params).await
+	}
+
+	async fn request_with_timeout<R, Params>(&self, method: &str, params: Params, timeout: Duration) -> Result<R, Error>
+	where
+		R: DeserializeOwned,
+		Params: ToRpcParams + Send,
+	{
+		ClientT::request_with_timeout(self, method, params, timeout).await
+	}
+
+	async fn batch_request<'a, R>(&self, batch: BatchRequestBuilder<'a>) -> Result<BatchResponse<'a, R>, Error>
+	where
+		R: DeserializeOwned + fmt::Debug + 'a,
+	{
+		ClientT::batch_request(self, batch).await
+	}
+
+	async fn batch_request_stream<'a, R>(
+		&self,
+		batch: BatchRequestBuilder<'a>,
+	) -> Result<BatchResponseStream<'a, R>, Error>
+	where
+		R: DeserializeOwned + fmt::Debug + Send + 'a,
+		Self: Sync,
+	{
+		ClientT::batch_request_stream(self, batch).await
+	}
+}
+
+impl<T: SubscriptionClientT + Sync + ?Sized> super::SubscriptionClientT for T {
+	async fn subscribe<'a, Notif, Params>(
+		&self,
+		subscribe_method: &'a str,
+		params: Params,
+		unsubscribe_method: &'a str,
+	) -> Result<Subscription<Notif>, Error>
+	where
+		Params: ToRpcParams + Send,
+		Notif: DeserializeOwned,
+	{
+		SubscriptionClientT::subscribe(self, subscribe_method, params, unsubscribe_method).await
+	}
+
+	async fn subscribe_to_method<Notif>(&self, method: &str) -> Result<Subscription<Notif>, Error>
+	where
+		Notif: DeserializeOwned,
+	{
+		SubscriptionClientT::subscribe_to_method(self, method).await
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::params::ArrayParams;
+
+	struct Unanswered;
+
+	#[async_trait]
+	impl ClientT for Unanswered {
+		async fn notification<Params>(&self, _method: &str, _params: Params) -> Result<(), Error>
+		where
+			Params: ToRpcParams + Send,
+		{
+			Ok(())
+		}
+
+		async fn request<R, Params>(&self, _method: &str, _params: Params) -> Result<R, Error>
+		where
+			R: DeserializeOwned,
+			Params: ToRpcParams + Send,
+		{
+			futures_util::future::pending::<()>().await;
+			unreachable!()
+		}
+
+		async fn batch_request<'a, R>(&self, _batch: BatchRequestBuilder<'a>) -> Result<BatchResponse<'a, R>, Error>
+		where
+			R: DeserializeOwned + fmt::Debug + 'a,
+		{
+			futures_util::future::pending::<()>().await;
+			unreachable!()
+		}
+	}
+
+	#[tokio::test]
+	async fn default_request_with_timeout_times_out() {
+		let err = super::super::ClientT::request_with_timeout::<String, _>(
+			&Unanswered,
+			"say_hello",
+			ArrayParams::new(),
+			Duration::from_millis(10),
+		)
+		.await
+		.unwrap_err();
+		assert!(matches!(err, Error::RequestTimeout));
+	}
+}
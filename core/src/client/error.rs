@@ -28,6 +28,7 @@
 
 use crate::{params::EmptyBatchRequest, BoxError, RegisterMethodError};
 use jsonrpsee_types::{ErrorObjectOwned, InvalidRequestId};
+use std::error::Error as StdError;
 use std::sync::Arc;
 
 /// Error type.
@@ -37,11 +38,14 @@ pub enum Error {
 	#[error("{0}")]
 	Call(#[from] ErrorObjectOwned),
 	/// Networking error or error on the low-level protocol layer.
-	#[error(transparent)]
-	Transport(BoxError),
+	///
+	/// The transport error is the [`source`](std::error::Error::source) of this error, see
+	/// [`Error::downcast_transport`] to find the root cause of the error.
+	#[error("{0}")]
+	Transport(#[source] BoxError),
 	/// The background task has been terminated.
 	#[error("The background task closed {0}; restart required")]
-	RestartNeeded(Arc<Error>),
+	RestartNeeded(#[source] Arc<Error>),
 	/// Failed to parse the data.
 	#[error("Parse error: {0}")]
 	ParseError(#[from] serde_json::Error),
@@ -54,6 +58,9 @@ pub enum Error {
 	/// Request timeout
 	#[error("Request timeout")]
 	RequestTimeout,
+	/// The call was cancelled, for instance because the scope it was made in was dropped.
+	#[error("The call was cancelled")]
+	Cancelled,
 	/// Custom error.
 	#[error("Custom error: {0}")]
 	Custom(String),
@@ -67,3 +74,80 @@ pub enum Error {
 	#[error(transparent)]
 	RegisterMethod(#[from] RegisterMethodError),
 }
+
+impl Error {
+	/// Returns the error object of the response if the call failed.
+	pub fn as_call(&self) -> Option<&ErrorObjectOwned> {
+		match self {
+			Self::Call(err) => Some(err),
+			_ => None,
+		}
+	}
+
+	/// Returns the transport error, also if the background task was closed because of it.
+	pub fn as_transport(&self) -> Option<&(dyn StdError + Send + Sync + 'static)> {
+		match self {
+			Self::Transport(err) => Some(err.as_ref()),
+			Self::RestartNeeded(err) => err.as_transport(),
+			_ => None,
+		}
+	}
+
+	/// Returns the first error of type `E` in the source chain of the transport error, starting with
+	/// the transport error itself.
+	///
+	/// This is how the root cause of a transport error is classified, such as an [`std::io::Error`]
+	/// when the connection was refused or a `rustls::Error` when the TLS handshake failed.
+	pub fn downcast_transport<E: StdError + 'static>(&self) -> Option<&E> {
+		let mut next: Option<&(dyn StdError + 'static)> = Some(self.as_transport()?);
+
+		while let Some(err) = next {
+			if let Some(err) = err.downcast_ref::<E>() {
+				return Some(err);
+			}
+			next = err.source();
+		}
+
+		None
+	}
+
+	/// Returns whether the request timed out.
+	pub fn is_timeout(&self) -> bool {
+		matches!(self, Self::RequestTimeout)
+	}
+
+	/// Returns whether the background task was closed and the client must be restarted.
+	pub fn is_restart_needed(&self) -> bool {
+		matches!(self, Self::RestartNeeded(_))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::Error;
+	use std::error::Error as StdError;
+	use std::io;
+	use std::sync::Arc;
+
+	#[derive(Debug, thiserror::Error)]
+	#[error("connection failed")]
+	struct ConnectError(#[source] io::Error);
+
+	#[test]
+	fn transport_source_chain_is_preserved() {
+		let io_err = io::Error::new(io::ErrorKind::ConnectionRefused, "refused");
+		let err = Error::RestartNeeded(Arc::new(Error::Transport(Box::new(ConnectError(io_err)))));
+
+		assert!(err.is_restart_needed());
+		assert_eq!(err.as_transport().unwrap().to_string(), "connection failed");
+		assert!(err.downcast_transport::<ConnectError>().is_some());
+		assert_eq!(err.downcast_transport::<io::Error>().unwrap().kind(), io::ErrorKind::ConnectionRefused);
+		assert!(err.downcast_transport::<std::fmt::Error>().is_none());
+
+		let transport = err.source().unwrap();
+		assert_eq!(transport.source().unwrap().to_string(), "connection failed");
+		assert_eq!(transport.source().unwrap().source().unwrap().to_string(), "refused");
+
+		assert!(Error::RequestTimeout.as_transport().is_none());
+	}
+}
@@ -0,0 +1,284 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any
+// person obtaining a copy of this software and associated
+// documentation files (the "Software"), to deal in the
+// Software without restriction, including without
+// limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software
+// is furnished to do so, subject to the following
+// conditions:
+//
+// The above copyright notice and this permission notice
+// shall be included in all copies or substantial portions
+// of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF
+// ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED
+// TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A
+// PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT
+// SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY
+// CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+// IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Client which answers with canned responses instead of connecting to a server, for unit tests.
+//!
+//! ```rust
+//! use jsonrpsee_core::client::{ClientT, Error, MockClient};
+//! use jsonrpsee_core::rpc_params;
+//! use jsonrpsee_types::{ErrorCode, ErrorObject};
+//!
+//! # async fn call() {
+//! let client = MockClient::new();
+//! client.expect_response("say_hello", "hello").expect_error("say_bye", ErrorCode::InternalError);
+//!
+//! let res: String = client.request("say_hello", rpc_params![1]).await.unwrap();
+//! assert_eq!(res, "hello");
+//!
+//! let err = client.request::<String, _>("say_bye", rpc_params![]).await.unwrap_err();
+//! assert!(matches!(err, Error::Call(e) if e == ErrorObject::from(ErrorCode::InternalError)));
+//!
+//! assert_eq!(client.calls()[0].method, "say_hello");
+//! assert_eq!(client.calls()[0].params, Some(serde_json::json!([1])));
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use super::{subscription_channel, BatchResponse, ClientT, Error, Subscription, SubscriptionClientT, SubscriptionKind};
+use crate::params::BatchRequestBuilder;
+use crate::traits::ToRpcParams;
+use crate::JsonRawValue;
+use jsonrpsee_types::{ErrorCode, ErrorObjectOwned, SubscriptionId};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use tokio::sync::mpsc;
+
+/// Call or notification which was made with a [`MockClient`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockCall {
+	/// Name of the method.
+	pub method: String,
+	/// Parameters of the call, if any.
+	pub params: Option<JsonValue>,
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+	responses: HashMap<String, Result<JsonValue, ErrorObjectOwned>>,
+	subscriptions: HashMap<String, Vec<JsonValue>>,
+	calls: Vec<MockCall>,
+}
+
+/// JSON-RPC client which records the calls and answers them with the responses which were set
+/// with [`MockClient::expect_response`], [`MockClient::expect_error`] and [`MockClient::expect_subscription`],
+/// instead of sending them to a server.
+///
+/// Calls to methods without a response fail with a "Method not found" error like a server would.
+/// The clones of a `MockClient` share the responses and the recorded calls.
+#[derive(Debug, Clone, Default)]
+pub struct MockClient {
+	state: Arc<Mutex<MockState>>,
+}
+
+impl MockClient {
+	/// Create a client without responses.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Answer the calls to `method` with `result`.
+	///
+	/// # Panics
+	///
+	/// Panics if `result` can't be serialized to JSON.
+	pub fn expect_response(&self, method: impl Into<String>, result: impl Serialize) -> &Self {
+		let result = serde_json::to_value(result).expect("Response must be serializable to JSON");
+		self.state().responses.insert(method.into(), Ok(result));
+		self
+	}
+
+	/// Answer the calls to `method` with `error`.
+	pub fn expect_error(&self, method: impl Into<String>, error: impl Into<ErrorObjectOwned>) -> &Self {
+		self.state().responses.insert(method.into(), Err(error.into()));
+		self
+	}
+
+	/// Accept the subscriptions to `method`, they receive `items` and are closed afterwards.
+	///
+	/// # Panics
+	///
+	/// Panics if an item can't be serialized to JSON.
+	pub fn expect_subscription<T: Serialize>(
+		&self,
+		method: impl Into<String>,
+		items: impl IntoIterator<Item = T>,
+	) -> &Self {
+		let items = items
+			.into_iter()
+			.map(|item| serde_json::to_value(item).expect("Subscription item must be serializable to JSON"))
+			.collect();
+		self.state().subscriptions.insert(method.into(), items);
+		self
+	}
+
+	/// The calls and notifications which were made in order, including the subscriptions.
+	pub fn calls(&self) -> Vec<MockCall> {
+		self.state().calls.clone()
+	}
+
+	fn state(&self) -> std::sync::MutexGuard<'_, MockState> {
+		self.state.lock().expect("Mutex not poisoned; qed")
+	}
+
+	fn record(&self, method: &str, params: Option<Box<JsonRawValue>>) {
+		let params = params.map(|params| serde_json::from_str(params.get()).expect("Params are valid JSON; qed"));
+		self.state().calls.push(MockCall { method: method.to_owned(), params });
+	}
+
+	fn response(&self, method: &str) -> Result<JsonValue, ErrorObjectOwned> {
+		self.state().responses.get(method).cloned().unwrap_or_else(|| Err(ErrorCode::MethodNotFound.into()))
+	}
+
+	fn subscription<Notif>(&self, method: &str, kind: SubscriptionKind) -> Result<Subscription<Notif>, Error> {
+		let Some(items) = self.state().subscriptions.get(method).cloned() else {
+			return Err(Error::Call(ErrorCode::MethodNotFound.into()));
+		};
+
+		// The subscription ends when the sender is dropped after the items are sent.
+		let (tx, rx) = subscription_channel(items.len().max(1));
+		for item in items {
+			let item = serde_json::value::to_raw_value(&item).expect("Valid JSON; qed");
+			let _ = tx.send(item);
+		}
+
+		// Nothing handles the unsubscribe messages.
+		let (to_back, _) = mpsc::channel(1);
+
+		Ok(Subscription::new(to_back, rx, kind))
+	}
+}
+
+impl ClientT for MockClient {
+	async fn notification<Params>(&self, method: &str, params: Params) -> Result<(), Error>
+	where
+		Params: ToRpcParams + Send,
+	{
+		self.record(method, params.to_rpc_params()?);
+		Ok(())
+	}
+
+	async fn request<R, Params>(&self, method: &str, params: Params) -> Result<R, Error>
+	where
+		R: DeserializeOwned,
+		Params: ToRpcParams + Send,
+	{
+		self.record(method, params.to_rpc_params()?);
+		let result = self.response(method)?;
+		serde_json::from_value(result).map_err(Error::ParseError)
+	}
+
+	async fn batch_request<'a, R>(&self, batch: BatchRequestBuilder<'a>) -> Result<BatchResponse<'a, R>, Error>
+	where
+		R: DeserializeOwned + fmt::Debug + 'a,
+	{
+		let calls: Vec<_> = batch.calls().collect();
+		let mut responses = Vec::with_capacity(calls.len());
+		let mut failed_calls = 0;
+
+		for entry in batch.build()? {
+			self.record(entry.method, entry.params);
+			if entry.is_notification {
+				continue;
+			}
+
+			match self.response(entry.method) {
+				Ok(result) => responses.push(Ok(serde_json::from_value(result)?)),
+				Err(err) => {
+					failed_calls += 1;
+					responses.push(Err(err));
+				}
+			}
+		}
+
+		Ok(BatchResponse::new(calls.len() - failed_calls, responses, failed_calls, calls))
+	}
+}
+
+impl SubscriptionClientT for MockClient {
+	async fn subscribe<'a, Notif, Params>(
+		&self,
+		subscribe_method: &'a str,
+		params: Params,
+		_unsubscribe_method: &'a str,
+	) -> Result<Subscription<Notif>, Error>
+	where
+		Params: ToRpcParams + Send,
+		Notif: DeserializeOwned,
+	{
+		self.record(subscribe_method, params.to_rpc_params()?);
+		let id = self.state().calls.len() as u64;
+		self.subscription(subscribe_method, SubscriptionKind::Subscription(SubscriptionId::Num(id)))
+	}
+
+	async fn subscribe_to_method<Notif>(&self, method: &str) -> Result<Subscription<Notif>, Error>
+	where
+		Notif: DeserializeOwned,
+	{
+		self.subscription(method, SubscriptionKind::Method(method.to_owned()))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::params::ArrayParams;
+	use serde_json::json;
+
+	#[tokio::test]
+	async fn subscription_receives_items() {
+		let client = MockClient::new();
+		client.expect_subscription("sub", [1, 2]);
+
+		let mut params = ArrayParams::new();
+		params.insert("a").unwrap();
+
+		let mut sub = client.subscribe::<u32, _>("sub", params, "unsub").await.unwrap();
+		assert_eq!(sub.next().await.unwrap().unwrap(), 1);
+		assert_eq!(sub.next().await.unwrap().unwrap(), 2);
+		assert!(sub.next().await.is_none());
+
+		let err = client.subscribe::<u32, _>("unknown", ArrayParams::new(), "unsub").await.unwrap_err();
+		assert!(matches!(err, Error::Call(e) if e.code() == ErrorCode::MethodNotFound.code()));
+
+		assert_eq!(
+			client.calls(),
+			vec![
+				MockCall { method: "sub".into(), params: Some(json!(["a"])) },
+				MockCall { method: "unknown".into(), params: None },
+			]
+		);
+	}
+
+	#[tokio::test]
+	async fn batch_request_works() {
+		let client = MockClient::new();
+		client.expect_response("a", 1).expect_error("b", ErrorCode::InternalError);
+
+		let mut batch = BatchRequestBuilder::new();
+		batch.insert("a", ArrayParams::new()).unwrap();
+		batch.insert_notification("n", ArrayParams::new()).unwrap();
+		batch.insert("b", ArrayParams::new()).unwrap();
+
+		let res = client.batch_request::<u32>(batch).await.unwrap();
+		assert_eq!(res.num_successful_calls(), 1);
+		assert_eq!(res.num_failed_calls(), 1);
+		assert_eq!(client.calls().len(), 3);
+	}
+}
@@ -28,28 +28,38 @@
 
 cfg_async_client! {
 	pub mod async_client;
-	pub use async_client::{Client, ClientBuilder};
+	pub use async_client::{Client, ClientBuilder, ClientScope, ClientScopeHandle};
 }
 
+cfg_mock! {
+	pub mod mock;
+	pub use mock::{MockCall, MockClient};
+}
+
+pub mod compat;
 pub mod error;
 pub use error::Error;
 
 use std::fmt;
+use std::future::Future;
 use std::ops::Range;
 use std::pin::Pin;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Arc, RwLock};
 use std::task::{self, Poll};
+use std::time::Duration;
 use tokio::sync::mpsc::error::TrySendError;
 
-use crate::params::BatchRequestBuilder;
+use crate::params::{BatchRequestBuilder, BatchRequestEntry};
 use crate::traits::ToRpcParams;
+use crate::JsonRawValue;
 use async_trait::async_trait;
 use core::marker::PhantomData;
 use futures_util::stream::{Stream, StreamExt};
-use jsonrpsee_types::{ErrorObject, Id, SubscriptionId};
+use jsonrpsee_types::{ErrorObject, Id, InvalidRequestId, NotificationSer, RequestSer, SubscriptionId, TwoPointZero};
 use serde::de::DeserializeOwned;
 use serde_json::Value as JsonValue;
+use std::borrow::Cow as StdCow;
 use tokio::sync::{mpsc, oneshot};
 
 /// Shared state whether a subscription has lagged or not.
@@ -83,19 +93,73 @@ pub mod __reexports {
 }
 
 /// [JSON-RPC](https://www.jsonrpc.org/specification) client interface that can make requests and notifications.
-#[async_trait]
+///
+/// The methods return `Send` futures which aren't boxed, they are implemented with `async fn`:
+///
+/// ```rust
+/// use jsonrpsee_core::client::{BatchResponse, ClientT, Error};
+/// use jsonrpsee_core::params::BatchRequestBuilder;
+/// use jsonrpsee_core::traits::ToRpcParams;
+/// use jsonrpsee_core::DeserializeOwned;
+///
+/// struct Offline;
+///
+/// impl ClientT for Offline {
+///     async fn notification<Params>(&self, _method: &str, _params: Params) -> Result<(), Error>
+///     where
+///         Params: ToRpcParams + Send,
+///     {
+///         Err(Error::Custom("offline".into()))
+///     }
+///
+///     async fn request<R, Params>(&self, _method: &str, _params: Params) -> Result<R, Error>
+///     where
+///         R: DeserializeOwned,
+///         Params: ToRpcParams + Send,
+///     {
+///         Err(Error::Custom("offline".into()))
+///     }
+///
+///     async fn batch_request<'a, R>(&self, _batch: BatchRequestBuilder<'a>) -> Result<BatchResponse<'a, R>, Error>
+///     where
+///         R: DeserializeOwned + std::fmt::Debug + 'a,
+///     {
+///         Err(Error::Custom("offline".into()))
+///     }
+/// }
+/// ```
+///
+/// Implementations which are written with `#[async_trait]` may implement [`compat::ClientT`] instead.
 pub trait ClientT {
 	/// Send a [notification request](https://www.jsonrpc.org/specification#notification)
-	async fn notification<Params>(&self, method: &str, params: Params) -> Result<(), Error>
+	fn notification<Params>(&self, method: &str, params: Params) -> impl Future<Output = Result<(), Error>> + Send
 	where
 		Params: ToRpcParams + Send;
 
 	/// Send a [method call request](https://www.jsonrpc.org/specification#request_object).
-	async fn request<R, Params>(&self, method: &str, params: Params) -> Result<R, Error>
+	fn request<R, Params>(&self, method: &str, params: Params) -> impl Future<Output = Result<R, Error>> + Send
 	where
 		R: DeserializeOwned,
 		Params: ToRpcParams + Send;
 
+	/// Send a [method call request](https://www.jsonrpc.org/specification#request_object) which fails with
+	/// [`Error::RequestTimeout`] if it isn't answered within `timeout`, or the request timeout of the client
+	/// if that's shorter.
+	///
+	/// The default implementation stops waiting for the response of [`ClientT::request`] once `timeout` has elapsed.
+	fn request_with_timeout<R, Params>(
+		&self,
+		method: &str,
+		params: Params,
+		timeout: Duration,
+	) -> impl Future<Output = Result<R, Error>> + Send
+	where
+		R: DeserializeOwned,
+		Params: ToRpcParams + Send,
+	{
+		with_timeout(self.request(method, params), timeout)
+	}
+
 	/// Send a [batch request](https://www.jsonrpc.org/specification#batch).
 	///
 	/// The response to batch are returned in the same order as it was inserted in the batch.
@@ -103,13 +167,36 @@ pub trait ClientT {
 	///
 	/// Returns `Ok` if all requests in the batch were answered.
 	/// Returns `Error` if the network failed or any of the responses could be parsed a valid JSON-RPC response.
-	async fn batch_request<'a, R>(&self, batch: BatchRequestBuilder<'a>) -> Result<BatchResponse<'a, R>, Error>
+	fn batch_request<'a, R>(
+		&self,
+		batch: BatchRequestBuilder<'a>,
+	) -> impl Future<Output = Result<BatchResponse<'a, R>, Error>> + Send
 	where
 		R: DeserializeOwned + fmt::Debug + 'a;
+
+	/// Send a [batch request](https://www.jsonrpc.org/specification#batch) and get a stream of the responses
+	/// in the order in which they are received, with the index of their entry in the [`BatchRequestBuilder`].
+	///
+	/// The default implementation waits for the whole batch response, whereas the HTTP client yields each
+	/// response as soon as it has been read from a streamed batch response.
+	///
+	/// Returns `Error` if the batch request couldn't be sent, later failures end the stream early,
+	/// see [`BatchResponseStream::error`].
+	fn batch_request_stream<'a, R>(
+		&self,
+		batch: BatchRequestBuilder<'a>,
+	) -> impl Future<Output = Result<BatchResponseStream<'a, R>, Error>> + Send
+	where
+		R: DeserializeOwned + fmt::Debug + Send + 'a,
+		Self: Sync,
+	{
+		async move { self.batch_request::<R>(batch).await.map(BatchResponseStream::from_response) }
+	}
 }
 
 /// [JSON-RPC](https://www.jsonrpc.org/specification) client interface that can make requests, notifications and subscriptions.
-#[async_trait]
+///
+/// Implementations which are written with `#[async_trait]` may implement [`compat::SubscriptionClientT`] instead.
 pub trait SubscriptionClientT: ClientT {
 	/// Initiate a subscription by performing a JSON-RPC method call where the server responds with
 	/// a `Subscription ID` that is used to fetch messages on that subscription,
@@ -123,12 +210,12 @@ pub trait SubscriptionClientT: ClientT {
 	///
 	/// The `Notif` param is a generic type to receive generic subscriptions, see [`Subscription`] for further
 	/// documentation.
-	async fn subscribe<'a, Notif, Params>(
+	fn subscribe<'a, Notif, Params>(
 		&self,
 		subscribe_method: &'a str,
 		params: Params,
 		unsubscribe_method: &'a str,
-	) -> Result<Subscription<Notif>, Error>
+	) -> impl Future<Output = Result<Subscription<Notif>, Error>> + Send
 	where
 		Params: ToRpcParams + Send,
 		Notif: DeserializeOwned;
@@ -137,7 +224,10 @@ pub trait SubscriptionClientT: ClientT {
 	///
 	/// The `Notif` param is a generic type to receive generic subscriptions, see [`Subscription`] for further
 	/// documentation.
-	async fn subscribe_to_method<'a, Notif>(&self, method: &'a str) -> Result<Subscription<Notif>, Error>
+	fn subscribe_to_method<Notif>(
+		&self,
+		method: &str,
+	) -> impl Future<Output = Result<Subscription<Notif>, Error>> + Send
 	where
 		Notif: DeserializeOwned;
 }
@@ -157,13 +247,30 @@ impl<T: Send> MaybeSend for T {}
 impl<T> MaybeSend for T {}
 
 /// Transport interface to send data asynchronous.
+///
+/// Together with [`TransportReceiverT`] this is the extension point to run the async `Client`
+/// over any transport, such as QUIC or in-process channels, by passing both halves of the
+/// transport to `ClientBuilder::build_with_tokio`.
+///
+/// # Framing
+///
+/// The client takes care of the JSON-RPC protocol and the transport only has to move
+/// whole messages:
+///
+/// - Each call to [`TransportSenderT::send`] gets exactly one serialized JSON-RPC request,
+///   notification or batch which must be delivered as a single message.
+/// - Messages are sent one at a time from a single background task, in the order they
+///   were issued by the client.
+/// - If sending fails the client is terminated and all pending calls fail with the error.
+///
+/// See `examples/core_client_custom_transport.rs` for a complete example.
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
 pub trait TransportSenderT: MaybeSend + 'static {
 	/// Error that may occur during sending a message.
 	type Error: std::error::Error + Send + Sync;
 
-	/// Send.
+	/// Send one serialized JSON-RPC message.
 	async fn send(&mut self, msg: String) -> Result<(), Self::Error>;
 
 	/// This is optional because it's most likely relevant for WebSocket transports only.
@@ -196,13 +303,26 @@ pub enum ReceivedMessage {
 }
 
 /// Transport interface to receive data asynchronous.
+///
+/// # Framing
+///
+/// - Each call to [`TransportReceiverT::receive`] must return exactly one JSON-RPC response,
+///   batch response or notification, as [`ReceivedMessage::Text`] or [`ReceivedMessage::Bytes`].
+///   A message must not be split up and several messages must not be concatenated.
+/// - [`ReceivedMessage::Pong`] is only relevant for transports which send pings,
+///   see [`TransportSenderT::send_ping`].
+/// - `receive` is called in a loop from a single background task and its future is never
+///   dropped before it completes, so it doesn't have to be cancel-safe.
+/// - Returning an error terminates the client and all pending calls fail with the error.
+///
+/// See [`TransportSenderT`] for how to use a custom transport with the client.
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
 pub trait TransportReceiverT: 'static {
 	/// Error that may occur during receiving a message.
 	type Error: std::error::Error + Send + Sync;
 
-	/// Receive.
+	/// Receive one JSON-RPC message.
 	async fn receive(&mut self) -> Result<ReceivedMessage, Self::Error>;
 }
 
@@ -269,7 +389,7 @@ pub struct Subscription<Notif> {
 	is_closed: bool,
 	/// Channel to send requests to the background task.
 	to_back: mpsc::Sender<FrontToBack>,
-	/// Channel from which we receive notifications from the server, as encoded `JsonValue`s.
+	/// Channel from which we receive notifications from the server, as raw JSON values.
 	rx: SubscriptionReceiver,
 	/// Callback kind.
 	kind: Option<SubscriptionKind>,
@@ -399,6 +519,10 @@ enum FrontToBack {
 	// Such operations will be blocked until a response is received or the background
 	// thread has been terminated.
 	SubscriptionClosed(SubscriptionId<'static>),
+	/// Calls of a scope have been cancelled, their pending entries don't need to be answered anymore.
+	///
+	/// The flag is cleared before the pending entries are released.
+	ReleaseCancelledCalls(Arc<AtomicBool>),
 }
 
 impl<Notif> Subscription<Notif>
@@ -425,7 +549,7 @@ where
 	type Item = Result<Notif, serde_json::Error>;
 	fn poll_next(mut self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> task::Poll<Option<Self::Item>> {
 		let res = match futures_util::ready!(self.rx.poll_next_unpin(cx)) {
-			Some(v) => Some(serde_json::from_value::<Notif>(v).map_err(Into::into)),
+			Some(v) => Some(serde_json::from_str::<Notif>(v.get())),
 			None => {
 				self.is_closed = true;
 				None
@@ -457,34 +581,136 @@ impl<Notif> Drop for Subscription<Notif> {
 pub struct RequestIdManager {
 	/// Get the next request ID.
 	current_id: CurrentId,
-	/// Request ID type.
+	/// Generator of the request IDs.
+	id_generator: Arc<dyn RequestIdGenerator>,
+	/// Request ID type, only used by the deprecated [`RequestIdManager::as_id_kind`].
 	id_kind: IdKind,
 }
 
 impl RequestIdManager {
-	/// Create a new `RequestIdGuard` with the provided concurrency limit.
+	/// Create a new `RequestIdManager` which generates IDs of the given kind.
 	pub fn new(id_kind: IdKind) -> Self {
-		Self { current_id: CurrentId::new(), id_kind }
+		Self { current_id: CurrentId::new(), id_generator: Arc::new(id_kind), id_kind }
+	}
+
+	/// Create a new `RequestIdManager` which generates IDs with `id_generator`.
+	pub fn with_generator(id_generator: Arc<dyn RequestIdGenerator>) -> Self {
+		Self { current_id: CurrentId::new(), id_generator, id_kind: IdKind::Number }
 	}
 
 	/// Attempts to get the next request ID.
 	pub fn next_request_id(&self) -> Id<'static> {
-		self.id_kind.into_id(self.current_id.next())
+		self.id_generator.to_id(self.current_id.next())
+	}
+
+	/// Get a handle to the [`RequestIdGenerator`].
+	pub fn as_id_generator(&self) -> &dyn RequestIdGenerator {
+		&*self.id_generator
 	}
 
 	/// Get a handle to the `IdKind`.
+	///
+	/// This is [`IdKind::Number`] if the manager was created with [`RequestIdManager::with_generator`].
+	#[deprecated(since = "0.25.0", note = "use `RequestIdManager::as_id_generator` instead")]
 	pub fn as_id_kind(&self) -> IdKind {
 		self.id_kind
 	}
 }
 
+/// Generator of the IDs of the requests made by a client, see [`IdKind`] for the built-in formats.
+///
+/// Each request is given the next number of a sequence which the generator turns into an ID,
+/// for instance to embed a shard number or a trace ID in the IDs or to make them deterministic
+/// when recording and replaying calls in tests. The number is parsed back from the ID of the
+/// response to find the call it belongs to, which is why the generator must be able to invert it.
+///
+/// ```
+/// use jsonrpsee_core::client::RequestIdGenerator;
+/// use jsonrpsee_types::{Id, InvalidRequestId};
+///
+/// #[derive(Debug)]
+/// struct ShardIds(u16);
+///
+/// impl RequestIdGenerator for ShardIds {
+///     fn to_id(&self, n: u64) -> Id<'static> {
+///         Id::Str(format!("{}-{n}", self.0).into())
+///     }
+///
+///     fn parse_id(&self, id: &Id) -> Result<u64, InvalidRequestId> {
+///         id.as_str()
+///             .and_then(|id| id.strip_prefix(&format!("{}-", self.0)))
+///             .and_then(|n| n.parse().ok())
+///             .ok_or_else(|| InvalidRequestId::Invalid(id.to_string()))
+///     }
+/// }
+///
+/// let ids = ShardIds(7);
+/// assert_eq!(ids.parse_id(&ids.to_id(42)).unwrap(), 42);
+/// ```
+pub trait RequestIdGenerator: Send + Sync + std::fmt::Debug {
+	/// Generate the ID of the request with the number `n`.
+	///
+	/// Distinct numbers must result in distinct IDs.
+	fn to_id(&self, n: u64) -> Id<'static>;
+
+	/// Parse the number of a request from its ID, which is the inverse of [`RequestIdGenerator::to_id`].
+	fn parse_id(&self, id: &Id) -> Result<u64, InvalidRequestId>;
+}
+
+impl RequestIdGenerator for IdKind {
+	fn to_id(&self, n: u64) -> Id<'static> {
+		self.into_id(n)
+	}
+
+	fn parse_id(&self, id: &Id) -> Result<u64, InvalidRequestId> {
+		// Only the ids of the configured kind are decoded, such that a server can't pass off
+		// an id of another kind as the id of a pending call.
+		let number = match (self, id) {
+			(IdKind::Uuid, Id::Str(s)) => uuid::decode(s),
+			#[cfg(not(target_arch = "wasm32"))]
+			(IdKind::Timestamp, Id::Str(s)) => timestamp::decode(s),
+			(IdKind::String | IdKind::Number, _) => return id.try_parse_inner_as_number(),
+			_ => None,
+		};
+
+		number.ok_or_else(|| InvalidRequestId::Invalid(id.to_string()))
+	}
+}
+
+impl<T: RequestIdGenerator + ?Sized> RequestIdGenerator for Arc<T> {
+	fn to_id(&self, n: u64) -> Id<'static> {
+		(**self).to_id(n)
+	}
+
+	fn parse_id(&self, id: &Id) -> Result<u64, InvalidRequestId> {
+		(**self).parse_id(id)
+	}
+}
+
 /// JSON-RPC request object id data type.
 #[derive(Debug, Copy, Clone)]
+#[non_exhaustive]
 pub enum IdKind {
 	/// String.
 	String,
 	/// Number.
 	Number,
+	/// UUID string which is unique across processes, for instance to correlate the logs of
+	/// distributed clients and servers.
+	///
+	/// The ids are [version 8 UUIDs](https://www.rfc-editor.org/rfc/rfc9562#name-uuid-version-8) made of
+	/// random bits which are generated once per process and the number of the request.
+	Uuid,
+	/// String which starts with the time at which the request was made, such that the calls of
+	/// several clients and reconnected clients can be ordered by sorting their ids.
+	///
+	/// The ids consist of the milliseconds since the UNIX epoch and the number of the request in
+	/// fixed-width hex, for instance `"0192a6e9c4f0-000000000000002a"`. The timestamps never decrease
+	/// within a process, even if the system clock is set back.
+	///
+	/// Not available on `wasm32` which has no system clock.
+	#[cfg(not(target_arch = "wasm32"))]
+	Timestamp,
 }
 
 impl IdKind {
@@ -493,6 +719,154 @@ impl IdKind {
 		match self {
 			IdKind::Number => Id::Number(id),
 			IdKind::String => Id::Str(format!("{id}").into()),
+			IdKind::Uuid => Id::Str(uuid::encode(id).into()),
+			#[cfg(not(target_arch = "wasm32"))]
+			IdKind::Timestamp => Id::Str(timestamp::encode(id).into()),
+		}
+	}
+}
+
+/// The ids of [`IdKind::Timestamp`].
+#[cfg(not(target_arch = "wasm32"))]
+mod timestamp {
+	use std::sync::atomic::{AtomicU64, Ordering};
+	use std::time::{SystemTime, UNIX_EPOCH};
+
+	/// Encode the number of a request with the current time.
+	pub(super) fn encode(number: u64) -> String {
+		static LAST_MILLIS: AtomicU64 = AtomicU64::new(0);
+
+		let now = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_millis() as u64);
+		let millis = LAST_MILLIS.fetch_max(now, Ordering::Relaxed).max(now);
+
+		format!("{millis:012x}-{number:016x}")
+	}
+
+	/// Decode the number of a request from an id created by [`encode`].
+	pub(super) fn decode(id: &str) -> Option<u64> {
+		let (millis, number) = id.split_once('-')?;
+		let is_hex = |s: &str, len| s.len() == len && s.bytes().all(|b| b.is_ascii_hexdigit());
+
+		if !is_hex(millis, 12) || !is_hex(number, 16) {
+			return None;
+		}
+
+		u64::from_str_radix(number, 16).ok()
+	}
+
+	#[cfg(test)]
+	mod tests {
+		use super::{decode, encode};
+
+		#[test]
+		fn roundtrip_works() {
+			for number in [0, 1, 1337, u64::MAX] {
+				let id = encode(number);
+				assert_eq!(id.len(), 29);
+				assert_eq!(decode(&id), Some(number));
+			}
+		}
+
+		#[test]
+		fn ids_are_ordered() {
+			let ids: Vec<_> = (0..100).map(encode).collect();
+			let mut sorted = ids.clone();
+			sorted.sort();
+			assert_eq!(ids, sorted);
+		}
+
+		#[test]
+		fn invalid_ids_are_rejected() {
+			assert_eq!(decode("1"), None);
+			assert_eq!(decode("0192a6e9c4f0000000000000002a"), None);
+			assert_eq!(decode("0192a6e9c4f-0000000000000002a"), None);
+			assert_eq!(decode("0192a6e9c4f0-+00000000000002a"), None);
+		}
+
+		#[test]
+		fn only_ids_of_the_configured_kind_are_decoded() {
+			use crate::client::{IdKind, RequestIdGenerator};
+			use jsonrpsee_types::Id;
+
+			let id = Id::Str(encode(42).into());
+			assert_eq!(IdKind::Timestamp.parse_id(&id).unwrap(), 42);
+			assert!(IdKind::String.parse_id(&id).is_err());
+			assert!(IdKind::Uuid.parse_id(&id).is_err());
+			assert_eq!(IdKind::String.parse_id(&Id::Str("42".into())).unwrap(), 42);
+		}
+	}
+}
+
+/// The UUIDs of [`IdKind::Uuid`].
+mod uuid {
+	use std::sync::OnceLock;
+
+	const VERSION: u128 = 0x8 << 76;
+	const VERSION_MASK: u128 = 0xf << 76;
+	const VARIANT: u128 = 0b10 << 62;
+	const VARIANT_MASK: u128 = 0b11 << 62;
+	const NUMBER_MASK: u64 = (1 << 62) - 1;
+
+	/// Encode the number of a request as a UUID.
+	///
+	/// The UUID consists of 48 random bits, the version, 12 random bits, the variant and 62 bits of the number.
+	pub(super) fn encode(number: u64) -> String {
+		static RANDOM: OnceLock<u64> = OnceLock::new();
+		let random = *RANDOM.get_or_init(rand::random);
+
+		let random_bits = (u128::from(random >> 16) << 80) | (u128::from(random & 0xfff) << 64);
+		let uuid = random_bits | VERSION | VARIANT | u128::from(number & NUMBER_MASK);
+
+		format!(
+			"{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+			uuid >> 96,
+			(uuid >> 80) & 0xffff,
+			(uuid >> 64) & 0xffff,
+			(uuid >> 48) & 0xffff,
+			uuid & 0xffff_ffff_ffff
+		)
+	}
+
+	/// Decode the number of a request from a UUID created by [`encode`].
+	pub(super) fn decode(uuid: &str) -> Option<u64> {
+		let bytes = uuid.as_bytes();
+		if bytes.len() != 36 || [8, 13, 18, 23].iter().any(|&i| bytes[i] != b'-') {
+			return None;
+		}
+
+		let hex: String = uuid.chars().filter(|&c| c != '-').collect();
+		if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+			return None;
+		}
+
+		let uuid = u128::from_str_radix(&hex, 16).ok()?;
+		(uuid & VERSION_MASK == VERSION && uuid & VARIANT_MASK == VARIANT).then_some(uuid as u64 & NUMBER_MASK)
+	}
+
+	#[cfg(test)]
+	mod tests {
+		use super::{decode, encode};
+
+		#[test]
+		fn roundtrip_works() {
+			for number in [0, 1, 1337, (1 << 62) - 1] {
+				let uuid = encode(number);
+				assert_eq!(uuid.len(), 36);
+				assert_eq!(&uuid[14..15], "8");
+				assert_eq!(decode(&uuid), Some(number));
+			}
+
+			// The random bits are the same within a process.
+			assert_eq!(encode(1)[..18], encode(2)[..18]);
+		}
+
+		#[test]
+		fn invalid_uuids_are_rejected() {
+			assert_eq!(decode("1"), None);
+			// Version 4
+			assert_eq!(decode("f47ac10b-58cc-4372-a567-0e02b2c3d479"), None);
+			assert_eq!(decode("f47ac10b58cc8372-a567-0e02b2c3d4791"), None);
+			assert_eq!(decode("+47ac10b-58cc-8372-a567-0e02b2c3d479"), None);
 		}
 	}
 }
@@ -514,8 +888,19 @@ impl CurrentId {
 }
 
 /// Generate a range of IDs to be used in a batch request.
+#[deprecated(since = "0.25.0", note = "use `generate_batch_id_range_with` instead")]
 pub fn generate_batch_id_range(id: Id, len: u64) -> Result<Range<u64>, Error> {
-	let id_start = id.try_parse_inner_as_number()?;
+	generate_batch_id_range_with(&IdKind::Number, id, len)
+}
+
+/// Generate a range of IDs to be used in a batch request, starting at `id` which was
+/// generated by `id_generator`.
+pub fn generate_batch_id_range_with(
+	id_generator: &dyn RequestIdGenerator,
+	id: Id,
+	len: u64,
+) -> Result<Range<u64>, Error> {
+	let id_start = id_generator.parse_id(&id)?;
 	let id_end = id_start
 		.checked_add(len)
 		.ok_or_else(|| Error::Custom("BatchID range wrapped; restart the client or try again later".to_string()))?;
@@ -523,21 +908,105 @@ pub fn generate_batch_id_range(id: Id, len: u64) -> Result<Range<u64>, Error> {
 	Ok(id_start..id_end)
 }
 
+/// Serialize a batch request where the method calls get the ids generated by `id_generator`
+/// from `ids` in order.
+pub fn serialize_batch_request_with(
+	batch: Vec<BatchRequestEntry>,
+	id_generator: &dyn RequestIdGenerator,
+	mut ids: Range<u64>,
+) -> Result<String, Error> {
+	// The calls are serialized one after the other into a single buffer.
+	let capacity: usize = batch.iter().map(|entry| envelope_capacity(entry.method, entry.params.as_deref())).sum();
+	let mut buf = Vec::with_capacity(capacity + 2);
+	buf.push(b'[');
+
+	for (i, entry) in batch.into_iter().enumerate() {
+		if i > 0 {
+			buf.push(b',');
+		}
+
+		let method = entry.method.into();
+		let params = entry.params.map(StdCow::Owned);
+
+		let res = if entry.is_notification {
+			serde_json::to_writer(&mut buf, &NotificationSer { jsonrpc: TwoPointZero, method, params })
+		} else {
+			let id = id_generator.to_id(ids.next().expect("The range has an id for each call; qed"));
+			serde_json::to_writer(&mut buf, &RequestSer { jsonrpc: TwoPointZero, id, method, params })
+		};
+
+		res.map_err(Error::ParseError)?;
+	}
+
+	buf.push(b']');
+
+	// Safety: serde_json does not emit invalid UTF-8.
+	Ok(unsafe { String::from_utf8_unchecked(buf) })
+}
+
+/// Serialize a method call into a buffer which is allocated once, with room for the
+/// already serialized `params` and the envelope of the call.
+pub fn serialize_request(id: &Id, method: &str, params: Option<&JsonRawValue>) -> Result<String, Error> {
+	let mut buf = Vec::with_capacity(envelope_capacity(method, params));
+	serde_json::to_writer(&mut buf, &RequestSer::borrowed(id, &method, params)).map_err(Error::ParseError)?;
+
+	// Safety: serde_json does not emit invalid UTF-8.
+	Ok(unsafe { String::from_utf8_unchecked(buf) })
+}
+
+/// Serialize a notification into a buffer which is allocated once, with room for the
+/// already serialized `params` and the envelope of the notification.
+pub fn serialize_notification(method: &str, params: Option<&JsonRawValue>) -> Result<String, Error> {
+	let mut buf = Vec::with_capacity(envelope_capacity(method, params));
+	serde_json::to_writer(&mut buf, &NotificationSer::borrowed(&method, params)).map_err(Error::ParseError)?;
+
+	// Safety: serde_json does not emit invalid UTF-8.
+	Ok(unsafe { String::from_utf8_unchecked(buf) })
+}
+
+/// Number of bytes of a serialized call, with about 64 bytes for the `jsonrpc` and `id` fields.
+fn envelope_capacity(method: &str, params: Option<&JsonRawValue>) -> usize {
+	method.len() + params.map_or(0, |p| p.get().len()) + 64
+}
+
+/// Fails with [`Error::RequestTimeout`] if `fut` isn't completed within `timeout`.
+async fn with_timeout<T>(fut: impl Future<Output = Result<T, Error>>, timeout: Duration) -> Result<T, Error> {
+	let fut = std::pin::pin!(fut);
+
+	match futures_util::future::select(fut, futures_timer::Delay::new(timeout)).await {
+		futures_util::future::Either::Left((res, _)) => res,
+		futures_util::future::Either::Right(_) => Err(Error::RequestTimeout),
+	}
+}
+
 /// Represent a single entry in a batch response.
 pub type BatchEntry<'a, R> = Result<R, ErrorObject<'a>>;
 
 /// Batch response.
+///
+/// The responses are in the order of the method calls in the [`BatchRequestBuilder`], which
+/// excludes the notifications.
 #[derive(Debug, Clone)]
 pub struct BatchResponse<'a, R> {
 	successful_calls: usize,
 	failed_calls: usize,
 	responses: Vec<BatchEntry<'a, R>>,
+	calls: Vec<(usize, &'a str)>,
 }
 
 impl<'a, R: fmt::Debug + 'a> BatchResponse<'a, R> {
 	/// Create a new [`BatchResponse`].
-	pub fn new(successful_calls: usize, responses: Vec<BatchEntry<'a, R>>, failed_calls: usize) -> Self {
-		Self { successful_calls, responses, failed_calls }
+	///
+	/// `calls` are the insertion index and method of the method call of each response,
+	/// see [`BatchRequestBuilder::calls`].
+	pub fn new(
+		successful_calls: usize,
+		responses: Vec<BatchEntry<'a, R>>,
+		failed_calls: usize,
+		calls: Vec<(usize, &'a str)>,
+	) -> Self {
+		debug_assert_eq!(responses.len(), calls.len());
+		Self { successful_calls, responses, failed_calls, calls }
 	}
 
 	/// Get the length of the batch response.
@@ -591,6 +1060,34 @@ impl<'a, R: fmt::Debug + 'a> BatchResponse<'a, R> {
 	pub fn iter(&self) -> impl Iterator<Item = &BatchEntry<'_, R>> {
 		self.responses.iter()
 	}
+
+	/// Get the response of the entry at `index` of the [`BatchRequestBuilder`].
+	///
+	/// Returns `None` if the entry is a notification or if the index is out of bounds.
+	pub fn get(&self, index: usize) -> Option<&BatchEntry<'a, R>> {
+		let pos = self.calls.binary_search_by_key(&index, |(idx, _)| *idx).ok()?;
+		self.responses.get(pos)
+	}
+
+	/// Returns the responses with the index of their entry in the [`BatchRequestBuilder`].
+	pub fn into_ordered_results(self) -> Vec<(usize, BatchEntry<'a, R>)> {
+		self.calls.into_iter().map(|(idx, _)| idx).zip(self.responses).collect()
+	}
+
+	/// Returns an iterator over all responses with the method of their call.
+	pub fn iter_with_methods(&self) -> impl Iterator<Item = (&'a str, &BatchEntry<'a, R>)> {
+		self.calls.iter().map(|(_, method)| *method).zip(self.responses.iter())
+	}
+
+	/// Similar to [`BatchResponse::iter_with_methods`] but takes the responses by value instead.
+	pub fn into_iter_with_methods(self) -> impl Iterator<Item = (&'a str, BatchEntry<'a, R>)> {
+		self.calls.into_iter().map(|(_, method)| method).zip(self.responses)
+	}
+
+	/// Returns a stream of all responses, for instance to process them with the combinators of [`StreamExt`].
+	pub fn into_stream(self) -> impl Stream<Item = BatchEntry<'a, R>> {
+		futures_util::stream::iter(self.responses)
+	}
 }
 
 impl<'a, R> IntoIterator for BatchResponse<'a, R> {
@@ -602,22 +1099,87 @@ impl<'a, R> IntoIterator for BatchResponse<'a, R> {
 	}
 }
 
+/// Stream of the responses to a batch request in the order in which they are received,
+/// see [`ClientT::batch_request_stream`].
+///
+/// Each item is the index of the entry in the [`BatchRequestBuilder`] with its response. The stream
+/// ends once all method calls have been answered, or early if the responses couldn't be read in which
+/// case [`BatchResponseStream::error`] returns why.
+pub struct BatchResponseStream<'a, R> {
+	inner: Option<BoxedBatchStream<'a, R>>,
+	error: Option<Error>,
+}
+
+type BoxedBatchStream<'a, R> = Pin<Box<dyn Stream<Item = Result<(usize, BatchEntry<'static, R>), Error>> + Send + 'a>>;
+
+impl<'a, R> BatchResponseStream<'a, R> {
+	/// Create a new [`BatchResponseStream`] from a stream of the responses, which ends at the first error.
+	pub fn new(responses: impl Stream<Item = Result<(usize, BatchEntry<'static, R>), Error>> + Send + 'a) -> Self {
+		Self { inner: Some(Box::pin(responses)), error: None }
+	}
+
+	/// Get the error which ended the stream before all method calls were answered, if any.
+	pub fn error(&self) -> Option<&Error> {
+		self.error.as_ref()
+	}
+
+	/// Stream the responses of a batch response which has been received in full.
+	pub(crate) fn from_response(responses: BatchResponse<'a, R>) -> Self
+	where
+		R: fmt::Debug + Send + 'a,
+	{
+		let responses: Vec<_> = responses
+			.into_ordered_results()
+			.into_iter()
+			.map(|(idx, rp)| Ok((idx, rp.map_err(ErrorObject::into_owned))))
+			.collect();
+
+		Self::new(futures_util::stream::iter(responses))
+	}
+}
+
+impl<R> Stream for BatchResponseStream<'_, R> {
+	type Item = (usize, BatchEntry<'static, R>);
+
+	fn poll_next(mut self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Option<Self::Item>> {
+		let Some(inner) = self.inner.as_mut() else {
+			return Poll::Ready(None);
+		};
+
+		let error = match task::ready!(inner.as_mut().poll_next(cx)) {
+			Some(Ok(rp)) => return Poll::Ready(Some(rp)),
+			Some(Err(e)) => Some(e),
+			None => None,
+		};
+
+		self.inner = None;
+		self.error = error;
+		Poll::Ready(None)
+	}
+}
+
+impl<R> fmt::Debug for BatchResponseStream<'_, R> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("BatchResponseStream").field("error", &self.error).finish_non_exhaustive()
+	}
+}
+
 #[derive(thiserror::Error, Debug)]
 enum TrySubscriptionSendError {
 	#[error("The subscription is closed")]
 	Closed,
 	#[error("A subscription message was dropped")]
-	TooSlow(JsonValue),
+	TooSlow(Box<JsonRawValue>),
 }
 
 #[derive(Debug)]
 pub(crate) struct SubscriptionSender {
-	inner: mpsc::Sender<JsonValue>,
+	inner: mpsc::Sender<Box<JsonRawValue>>,
 	lagged: SubscriptionLagged,
 }
 
 impl SubscriptionSender {
-	fn send(&self, msg: JsonValue) -> Result<(), TrySubscriptionSendError> {
+	fn send(&self, msg: Box<JsonRawValue>) -> Result<(), TrySubscriptionSendError> {
 		match self.inner.try_send(msg) {
 			Ok(_) => Ok(()),
 			Err(TrySendError::Closed(_)) => Err(TrySubscriptionSendError::Closed),
@@ -631,12 +1193,12 @@ impl SubscriptionSender {
 
 #[derive(Debug)]
 pub(crate) struct SubscriptionReceiver {
-	inner: mpsc::Receiver<JsonValue>,
+	inner: mpsc::Receiver<Box<JsonRawValue>>,
 	lagged: SubscriptionLagged,
 }
 
 impl Stream for SubscriptionReceiver {
-	type Item = JsonValue;
+	type Item = Box<JsonRawValue>;
 
 	fn poll_next(mut self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> task::Poll<Option<Self::Item>> {
 		self.inner.poll_recv(cx)
@@ -650,3 +1212,46 @@ fn subscription_channel(max_buf_size: usize) -> (SubscriptionSender, Subscriptio
 
 	(SubscriptionSender { inner: tx, lagged: lagged_tx }, SubscriptionReceiver { inner: rx, lagged: lagged_rx })
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::params::ArrayParams;
+
+	struct Unanswered;
+
+	impl ClientT for Unanswered {
+		async fn notification<Params>(&self, _method: &str, _params: Params) -> Result<(), Error>
+		where
+			Params: ToRpcParams + Send,
+		{
+			Ok(())
+		}
+
+		async fn request<R, Params>(&self, _method: &str, _params: Params) -> Result<R, Error>
+		where
+			R: DeserializeOwned,
+			Params: ToRpcParams + Send,
+		{
+			futures_util::future::pending::<()>().await;
+			unreachable!()
+		}
+
+		async fn batch_request<'a, R>(&self, _batch: BatchRequestBuilder<'a>) -> Result<BatchResponse<'a, R>, Error>
+		where
+			R: DeserializeOwned + fmt::Debug + 'a,
+		{
+			futures_util::future::pending::<()>().await;
+			unreachable!()
+		}
+	}
+
+	#[tokio::test]
+	async fn default_request_with_timeout_times_out() {
+		let err = Unanswered
+			.request_with_timeout::<String, _>("say_hello", ArrayParams::new(), Duration::from_millis(10))
+			.await
+			.unwrap_err();
+		assert!(matches!(err, Error::RequestTimeout));
+	}
+}
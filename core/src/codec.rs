@@ -0,0 +1,175 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any
+// person obtaining a copy of this software and associated
+// documentation files (the "Software"), to deal in the
+// Software without restriction, including without
+// limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software
+// is furnished to do so, subject to the following
+// conditions:
+//
+// The above copyright notice and this permission notice
+// shall be included in all copies or substantial portions
+// of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF
+// ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED
+// TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A
+// PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT
+// SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY
+// CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+// IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Pluggable encodings of JSON-RPC messages.
+//!
+//! JSON is the default encoding of JSON-RPC messages, a [`Codec`] transcodes them from and to
+//! an alternative wire format while keeping their structure intact.
+//!
+//! The transports negotiate the codec of HTTP requests with the `Content-Type` header and of
+//! WebSocket connections with the `Sec-WebSocket-Protocol` header, see [`Codec::content_type`] and
+//! [`Codec::ws_subprotocol`]. The codecs which are enabled by crate features are returned by [`builtin`],
+//! other codecs are implemented by users and negotiated from a set of [`Codecs`].
+//!
+//! # Limitations
+//!
+//! A codec only changes the wire format. The clients and servers still build and parse the messages
+//! with `serde_json`, whose types such as `RawValue` are part of the public API of the requests,
+//! responses and params, and transcode them with the codec. Hence a codec can't replace the JSON
+//! parser or serializer itself, for instance to parse with `simd-json` or to preserve the order of
+//! the keys of objects, and it adds the cost of transcoding to the cost of JSON.
+
+use std::fmt;
+use std::sync::Arc;
+
+/// An alternative wire encoding of JSON-RPC messages.
+pub trait Codec: fmt::Debug + Send + Sync + 'static {
+	/// The `Content-Type` of HTTP requests and responses encoded by this codec.
+	fn content_type(&self) -> &'static str;
+
+	/// The WebSocket subprotocol of connections with messages encoded by this codec,
+	/// which are sent in binary frames.
+	fn ws_subprotocol(&self) -> &'static str;
+
+	/// Encode a JSON message.
+	fn encode(&self, json: &[u8]) -> Result<Vec<u8>, CodecError>;
+
+	/// Decode a message into JSON.
+	fn decode(&self, data: &[u8]) -> Result<Vec<u8>, CodecError>;
+}
+
+/// Error that occurs if a message can't be transcoded.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("Invalid {content_type} message: {reason}")]
+pub struct CodecError {
+	content_type: &'static str,
+	reason: String,
+}
+
+impl CodecError {
+	/// Create a new error for the codec with the given `Content-Type`.
+	pub fn new(content_type: &'static str, reason: impl ToString) -> Self {
+		Self { content_type, reason: reason.to_string() }
+	}
+}
+
+/// The codecs which are enabled by the crate features.
+pub fn builtin() -> &'static [&'static dyn Codec] {
+	&[
+		#[cfg(feature = "cbor")]
+		&crate::cbor::Cbor,
+		#[cfg(feature = "msgpack")]
+		&crate::msgpack::MessagePack,
+	]
+}
+
+/// Find the builtin codec of the given `Content-Type`, ignoring its parameters.
+pub fn from_content_type(content_type: &str) -> Option<&'static dyn Codec> {
+	find_content_type(builtin(), content_type)
+}
+
+/// Find the first builtin codec of the given WebSocket subprotocols.
+pub fn from_ws_subprotocols<'a>(protocols: impl IntoIterator<Item = &'a str>) -> Option<&'static dyn Codec> {
+	find_ws_subprotocol(builtin(), protocols)
+}
+
+/// The codecs which the encoding of the messages is negotiated from, the [`builtin`] codecs by default.
+///
+/// # Examples
+///
+/// ```
+/// use jsonrpsee_core::codec::{Codec, CodecError, Codecs};
+///
+/// // JSON in a custom `Content-Type`.
+/// #[derive(Debug)]
+/// struct Tagged;
+///
+/// impl Codec for Tagged {
+///     fn content_type(&self) -> &'static str {
+///         "application/x-tagged"
+///     }
+///
+///     fn ws_subprotocol(&self) -> &'static str {
+///         "tagged"
+///     }
+///
+///     fn encode(&self, json: &[u8]) -> Result<Vec<u8>, CodecError> {
+///         Ok(json.to_vec())
+///     }
+///
+///     fn decode(&self, data: &[u8]) -> Result<Vec<u8>, CodecError> {
+///         Ok(data.to_vec())
+///     }
+/// }
+///
+/// let codecs = Codecs::new([&Tagged as &dyn Codec]);
+/// assert!(codecs.from_content_type("application/x-tagged; charset=utf-8").is_some());
+/// assert!(codecs.from_ws_subprotocols(["json", "tagged"]).is_some());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Codecs(Arc<[&'static dyn Codec]>);
+
+impl Codecs {
+	/// Create a set of codecs, in the order of preference.
+	pub fn new(codecs: impl IntoIterator<Item = &'static dyn Codec>) -> Self {
+		Self(codecs.into_iter().collect())
+	}
+
+	/// Get the codecs in the order of preference.
+	pub fn iter(&self) -> impl Iterator<Item = &'static dyn Codec> + '_ {
+		self.0.iter().copied()
+	}
+
+	/// Find the codec of the given `Content-Type`, ignoring its parameters.
+	pub fn from_content_type(&self, content_type: &str) -> Option<&'static dyn Codec> {
+		find_content_type(&self.0, content_type)
+	}
+
+	/// Find the first codec of the given WebSocket subprotocols.
+	pub fn from_ws_subprotocols<'a>(&self, protocols: impl IntoIterator<Item = &'a str>) -> Option<&'static dyn Codec> {
+		find_ws_subprotocol(&self.0, protocols)
+	}
+}
+
+impl Default for Codecs {
+	fn default() -> Self {
+		Self::new(builtin().iter().copied())
+	}
+}
+
+fn find_content_type(codecs: &[&'static dyn Codec], content_type: &str) -> Option<&'static dyn Codec> {
+	let mime = content_type.split(';').next().unwrap_or_default().trim();
+	codecs.iter().copied().find(|codec| mime.eq_ignore_ascii_case(codec.content_type()))
+}
+
+fn find_ws_subprotocol<'a>(
+	codecs: &[&'static dyn Codec],
+	protocols: impl IntoIterator<Item = &'a str>,
+) -> Option<&'static dyn Codec> {
+	protocols
+		.into_iter()
+		.find_map(|protocol| codecs.iter().copied().find(|codec| protocol.trim() == codec.ws_subprotocol()))
+}
@@ -26,6 +26,7 @@
 
 //! Shared HTTP utilities.
 
+use crate::codec::Codec;
 use crate::BoxError;
 use bytes::{Buf, Bytes};
 use http_body::Frame;
@@ -33,8 +34,20 @@ use http_body_util::{BodyExt, Limited};
 use std::{
 	pin::Pin,
 	task::{Context, Poll},
+	time::Duration,
 };
 
+/// Header with the number of milliseconds that the caller waits for the response, after
+/// which it gives up on the call, similar to `grpc-timeout`.
+///
+/// The duration is relative to when the request is received, which doesn't depend on
+/// the clocks of the client and the server being in sync.
+///
+/// The deadlines are only propagated over HTTP: the WebSocket client doesn't send them and the
+/// WebSocket server doesn't insert a `Deadline` into the extensions of the calls, which are only
+/// bounded by the method timeouts of the server instead.
+pub const DEADLINE_HEADER: &str = "x-jsonrpc-deadline";
+
 /// HTTP request type.
 pub type Request<T = Body> = http::Request<T>;
 /// HTTP response type.
@@ -189,6 +202,53 @@ where
 	}
 }
 
+/// Read the body of a request or response which is encoded by `codec` and transcode it into JSON.
+///
+/// Returns the JSON and whether it's a single call, like [`read_body`]. Both the encoded body and
+/// the JSON it's decoded into must not exceed `max_body_size`.
+pub async fn read_encoded_body<B>(
+	headers: &http::HeaderMap,
+	body: B,
+	max_body_size: u32,
+	codec: &dyn Codec,
+) -> Result<(Vec<u8>, bool), HttpError>
+where
+	B: http_body::Body<Data = Bytes> + Send + 'static,
+	B::Data: Send,
+	B::Error: Into<BoxError>,
+{
+	let body_size = read_header_content_length(headers).unwrap_or(0);
+
+	if body_size > max_body_size {
+		return Err(HttpError::TooLarge);
+	}
+
+	futures_util::pin_mut!(body);
+
+	let mut received_data = Vec::with_capacity(std::cmp::min(body_size as usize, 16 * 1024));
+	let mut limited_body = Limited::new(body, max_body_size as usize);
+
+	while let Some(frame_or_err) = limited_body.frame().await {
+		let frame = frame_or_err.map_err(HttpError::Stream)?;
+		if let Some(data) = frame.data_ref() {
+			received_data.extend_from_slice(data.chunk());
+		}
+	}
+
+	let json = codec.decode(&received_data).map_err(|_| HttpError::Malformed)?;
+
+	// The JSON may be much larger than the compact encoding.
+	if json.len() > max_body_size as usize {
+		return Err(HttpError::TooLarge);
+	}
+
+	match json.first() {
+		Some(b'{') => Ok((json, true)),
+		Some(b'[') => Ok((json, false)),
+		_ => Err(HttpError::Malformed),
+	}
+}
+
 /// Read the `Content-Length` HTTP Header. Must fit into a `u32`; returns `None` otherwise.
 ///
 /// NOTE: There's no specific hard limit on `Content_length` in HTTP specification.
@@ -210,6 +270,18 @@ pub fn read_header_value(headers: &http::header::HeaderMap, header_name: http::h
 	}
 }
 
+/// Returns the duration of the [`DEADLINE_HEADER`] when there is exactly one valid value.
+pub fn read_deadline_header(headers: &http::header::HeaderMap) -> Option<Duration> {
+	let millis = read_header_value(headers, http::header::HeaderName::from_static(DEADLINE_HEADER))?;
+	millis.parse::<u64>().ok().map(Duration::from_millis)
+}
+
+/// Returns the value of the [`DEADLINE_HEADER`] for `timeout`.
+pub fn deadline_header_value(timeout: Duration) -> http::header::HeaderValue {
+	let millis = u64::try_from(timeout.as_millis()).unwrap_or(u64::MAX);
+	millis.into()
+}
+
 /// Returns an iterator of all values for a given a header name
 pub fn read_header_values<'a>(
 	headers: &'a http::header::HeaderMap,
@@ -220,7 +292,10 @@ pub fn read_header_values<'a>(
 
 #[cfg(test)]
 mod tests {
-	use super::{read_body, read_header_content_length, HttpError};
+	use super::{
+		deadline_header_value, read_body, read_deadline_header, read_encoded_body, read_header_content_length, HttpError,
+	};
+	use crate::codec::{Codec, CodecError};
 	use http_body_util::BodyExt;
 
 	type Body = http_body_util::Full<bytes::Bytes>;
@@ -233,6 +308,38 @@ mod tests {
 		assert!(read_body(&headers, body, 127).await.is_err());
 	}
 
+	#[tokio::test]
+	async fn decoded_body_size_limit_works() {
+		/// Codec whose messages are the number of elements of a JSON array of zeros.
+		#[derive(Debug)]
+		struct Zeros;
+
+		impl Codec for Zeros {
+			fn content_type(&self) -> &'static str {
+				"application/x-zeros"
+			}
+
+			fn ws_subprotocol(&self) -> &'static str {
+				"zeros"
+			}
+
+			fn encode(&self, _json: &[u8]) -> Result<Vec<u8>, CodecError> {
+				unimplemented!()
+			}
+
+			fn decode(&self, data: &[u8]) -> Result<Vec<u8>, CodecError> {
+				let len: usize = std::str::from_utf8(data).unwrap().parse().unwrap();
+				Ok(serde_json::to_vec(&vec![0; len]).unwrap())
+			}
+		}
+
+		let headers = http::header::HeaderMap::new();
+		let body = |len: &str| Body::from(len.to_owned()).map_err(|e| HttpError::Stream(e.into()));
+
+		assert_eq!(read_encoded_body(&headers, body("3"), 127, &Zeros).await.unwrap(), (b"[0,0,0]".to_vec(), false));
+		assert!(matches!(read_encoded_body(&headers, body("100"), 127, &Zeros).await, Err(HttpError::TooLarge)));
+	}
+
 	#[test]
 	fn read_content_length_works() {
 		let mut headers = http::header::HeaderMap::new();
@@ -243,6 +350,19 @@ mod tests {
 		assert_eq!(read_header_content_length(&headers), None);
 	}
 
+	#[test]
+	fn deadline_header_round_trip() {
+		let mut headers = http::header::HeaderMap::new();
+		assert_eq!(read_deadline_header(&headers), None);
+
+		headers.insert(super::DEADLINE_HEADER, deadline_header_value(std::time::Duration::from_millis(1500)));
+		assert_eq!(headers.get(super::DEADLINE_HEADER).unwrap(), "1500");
+		assert_eq!(read_deadline_header(&headers), Some(std::time::Duration::from_millis(1500)));
+
+		headers.insert(super::DEADLINE_HEADER, "soon".parse().unwrap());
+		assert_eq!(read_deadline_header(&headers), None);
+	}
+
 	#[test]
 	fn read_content_length_too_big_value() {
 		let mut headers = http::header::HeaderMap::new();
@@ -43,10 +43,22 @@ pub mod traits;
 /// RPC Parameters.
 pub mod params;
 
+pub mod codec;
+
+pub mod limits;
+
 cfg_http_helpers! {
 	pub mod http_helpers;
 }
 
+cfg_cbor! {
+	pub mod cbor;
+}
+
+cfg_msgpack! {
+	pub mod msgpack;
+}
+
 cfg_server! {
 	pub mod id_providers;
 	pub mod server;
@@ -61,6 +73,7 @@ cfg_client! {
 pub mod tracing;
 pub use async_trait::async_trait;
 pub use error::{RegisterMethodError, StringError};
+pub use limits::Limits;
 
 /// JSON-RPC result.
 pub type RpcResult<T> = std::result::Result<T, jsonrpsee_types::ErrorObjectOwned>;
@@ -84,6 +97,11 @@ pub mod __reexports {
 		pub use tokio;
 	}
 
+	// Needed for the OpenRPC generation in the proc macro API.
+	cfg_schemars! {
+		pub use schemars;
+	}
+
 	pub use super::proc_macros_support::*;
 }
 
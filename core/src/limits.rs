@@ -0,0 +1,114 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any
+// person obtaining a copy of this software and associated
+// documentation files (the "Software"), to deal in the
+// Software without restriction, including without
+// limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software
+// is furnished to do so, subject to the following
+// conditions:
+//
+// The above copyright notice and this permission notice
+// shall be included in all copies or substantial portions
+// of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF
+// ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED
+// TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A
+// PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT
+// SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY
+// CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+// IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Limits shared by the client and server builders.
+
+use std::time::Duration;
+
+use crate::TEN_MB_SIZE_BYTES;
+
+/// Limits of the requests and responses which are applied by the client and server builders at once,
+/// for instance `HttpClientBuilder::with_limits` and `ServerBuilder::with_limits`.
+///
+/// Each builder applies the limits it supports:
+///
+/// - The clients apply the size limits, the max log length and use the timeout as request timeout.
+/// - The server applies the size limits, the max batch length and uses the timeout as method timeout.
+///
+/// The limits may be tuned starting from one of the presets:
+///
+/// ```rust
+/// use jsonrpsee_core::Limits;
+///
+/// let mut limits = Limits::public();
+/// limits.max_batch_len = Some(50);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Limits {
+	/// Max size of a request in bytes.
+	pub max_request_size: u32,
+	/// Max size of a response in bytes.
+	pub max_response_size: u32,
+	/// Max number of calls in a batch request, `None` for no limit.
+	pub max_batch_len: Option<u32>,
+	/// Max length of the logged requests and responses in number of characters.
+	pub max_log_length: u32,
+	/// Max duration of a call.
+	pub timeout: Duration,
+}
+
+impl Default for Limits {
+	/// The defaults of the clients: 10 MiB requests and responses, batches of any length,
+	/// logs of up to 4096 characters and a timeout of 60 seconds.
+	fn default() -> Self {
+		Self {
+			max_request_size: TEN_MB_SIZE_BYTES,
+			max_response_size: TEN_MB_SIZE_BYTES,
+			max_batch_len: None,
+			max_log_length: 4096,
+			timeout: Duration::from_secs(60),
+		}
+	}
+}
+
+impl Limits {
+	/// Limits for services with small messages which must fail fast: 128 KiB requests, 1 MiB responses,
+	/// batches of up to 10 calls, logs of up to 256 characters and a timeout of 10 seconds.
+	pub const fn strict() -> Self {
+		Self {
+			max_request_size: 128 * 1024,
+			max_response_size: 1024 * 1024,
+			max_batch_len: Some(10),
+			max_log_length: 256,
+			timeout: Duration::from_secs(10),
+		}
+	}
+
+	/// Limits for services exposed to the internet: 1 MiB requests, 10 MiB responses, batches of up to
+	/// 100 calls, logs of up to 1024 characters and a timeout of 30 seconds.
+	pub const fn public() -> Self {
+		Self {
+			max_request_size: 1024 * 1024,
+			max_response_size: TEN_MB_SIZE_BYTES,
+			max_batch_len: Some(100),
+			max_log_length: 1024,
+			timeout: Duration::from_secs(30),
+		}
+	}
+
+	/// Limits for trusted services on an internal network: 100 MiB requests and responses, batches of
+	/// any length, logs of up to 4096 characters and a timeout of 2 minutes.
+	pub const fn internal() -> Self {
+		Self {
+			max_request_size: 10 * TEN_MB_SIZE_BYTES,
+			max_response_size: 10 * TEN_MB_SIZE_BYTES,
+			max_batch_len: None,
+			max_log_length: 4096,
+			timeout: Duration::from_secs(120),
+		}
+	}
+}
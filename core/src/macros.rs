@@ -29,6 +29,30 @@ macro_rules! cfg_client_or_server {
 	}
 }
 
+macro_rules! cfg_cbor {
+	($($item:item)*) => {
+		cfg_feature!("cbor", $($item)*);
+	};
+}
+
+macro_rules! cfg_msgpack {
+	($($item:item)*) => {
+		cfg_feature!("msgpack", $($item)*);
+	};
+}
+
+macro_rules! cfg_schemars {
+	($($item:item)*) => {
+		cfg_feature!("schemars", $($item)*);
+	};
+}
+
+macro_rules! cfg_mock {
+	($($item:item)*) => {
+		cfg_feature!("mock", $($item)*);
+	};
+}
+
 macro_rules! cfg_http_helpers {
  ($($item:item)*) => {
 		cfg_feature!("http-helpers", $($item)*);
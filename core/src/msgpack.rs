@@ -0,0 +1,83 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any
+// person obtaining a copy of this software and associated
+// documentation files (the "Software"), to deal in the
+// Software without restriction, including without
+// limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software
+// is furnished to do so, subject to the following
+// conditions:
+//
+// The above copyright notice and this permission notice
+// shall be included in all copies or substantial portions
+// of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF
+// ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED
+// TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A
+// PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT
+// SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY
+// CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+// IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! MessagePack encoding of JSON-RPC messages.
+//!
+//! The messages keep the structure of JSON-RPC, i.e. the request and response objects and their
+//! params, results and errors are encoded as MessagePack maps and arrays instead of JSON objects and arrays.
+
+use crate::codec::{Codec, CodecError};
+
+/// The `Content-Type` of MessagePack encoded HTTP requests and responses.
+pub const CONTENT_TYPE: &str = "application/msgpack";
+
+/// The WebSocket subprotocol of connections with MessagePack encoded messages.
+pub const WS_SUBPROTOCOL: &str = "jsonrpc.msgpack";
+
+/// The MessagePack [`Codec`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MessagePack;
+
+impl Codec for MessagePack {
+	fn content_type(&self) -> &'static str {
+		CONTENT_TYPE
+	}
+
+	fn ws_subprotocol(&self) -> &'static str {
+		WS_SUBPROTOCOL
+	}
+
+	fn encode(&self, json: &[u8]) -> Result<Vec<u8>, CodecError> {
+		let value: serde_json::Value = serde_json::from_slice(json).map_err(|e| CodecError::new(CONTENT_TYPE, e))?;
+		rmp_serde::to_vec_named(&value).map_err(|e| CodecError::new(CONTENT_TYPE, e))
+	}
+
+	fn decode(&self, data: &[u8]) -> Result<Vec<u8>, CodecError> {
+		let value: serde_json::Value = rmp_serde::from_slice(data).map_err(|e| CodecError::new(CONTENT_TYPE, e))?;
+		serde_json::to_vec(&value).map_err(|e| CodecError::new(CONTENT_TYPE, e))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::MessagePack;
+	use crate::codec::Codec;
+
+	#[test]
+	fn roundtrip_works() {
+		let json = r#"{"jsonrpc":"2.0","id":1,"method":"say_hello","params":[1,-2.5,"a",null,true,{"b":[]}]}"#;
+		let msgpack = MessagePack.encode(json.as_bytes()).unwrap();
+		assert!(msgpack.len() < json.len());
+		let decoded: serde_json::Value = serde_json::from_slice(&MessagePack.decode(&msgpack).unwrap()).unwrap();
+		assert_eq!(decoded, serde_json::from_str::<serde_json::Value>(json).unwrap());
+	}
+
+	#[test]
+	fn invalid_msgpack_is_rejected() {
+		assert!(MessagePack.decode(&[0xc1]).is_err());
+		assert!(MessagePack.encode(b"{").is_err());
+	}
+}
@@ -55,22 +55,24 @@ mod params_builder {
 	impl ParamsBuilder {
 		/// Construct a new [`ParamsBuilder`] with custom start and end tokens.
 		/// The inserted values are wrapped by the _start_ and _end_ tokens.
-		fn new(start: char, end: char) -> Self {
-			ParamsBuilder { bytes: Vec::new(), start, end }
+		///
+		/// Nothing is allocated until the first value is inserted unless `capacity` is non-zero.
+		fn new(start: char, end: char, capacity: usize) -> Self {
+			ParamsBuilder { bytes: Vec::with_capacity(capacity), start, end }
 		}
 
 		/// Construct a new [`ParamsBuilder`] for positional parameters equivalent to a JSON array object.
-		pub(crate) fn positional() -> Self {
-			Self::new('[', ']')
+		pub(crate) fn positional(capacity: usize) -> Self {
+			Self::new('[', ']', capacity)
 		}
 
 		/// Construct a new [`ParamsBuilder`] for named parameters equivalent to a JSON map object.
-		pub(crate) fn named() -> Self {
-			Self::new('{', '}')
+		pub(crate) fn named(capacity: usize) -> Self {
+			Self::new('{', '}', capacity)
 		}
 
 		/// Initialize the internal vector if it is empty:
-		///  - allocate [`PARAM_BYTES_CAPACITY`] to avoid resizing
+		///  - allocate [`PARAM_BYTES_CAPACITY`] to avoid resizing, unless a capacity was provided
 		///  - add the `start` character.
 		///
 		/// # Note
@@ -78,7 +80,9 @@ mod params_builder {
 		/// Initialization is needed prior to inserting elements.
 		fn maybe_initialize(&mut self) {
 			if self.bytes.is_empty() {
-				self.bytes.reserve(PARAM_BYTES_CAPACITY);
+				if self.bytes.capacity() == 0 {
+					self.bytes.reserve(PARAM_BYTES_CAPACITY);
+				}
 				self.bytes.push(self.start as u8);
 			}
 		}
@@ -149,6 +153,15 @@ impl ObjectParams {
 		Self::default()
 	}
 
+	/// Construct a new [`ObjectParams`] with room for `capacity` bytes of serialized parameters,
+	/// including the surrounding braces and separators.
+	///
+	/// The parameters are serialized into a single buffer, which isn't reallocated
+	/// while building if the capacity is large enough.
+	pub fn with_capacity(capacity: usize) -> Self {
+		Self(params_builder::ParamsBuilder::named(capacity))
+	}
+
 	/// Insert a named value (key, value) pair into the builder.
 	/// The _name_ and _value_ are delimited by the `:` token.
 	pub fn insert<P: Serialize>(&mut self, name: &str, value: P) -> Result<(), serde_json::Error> {
@@ -158,7 +171,7 @@ impl ObjectParams {
 
 impl Default for ObjectParams {
 	fn default() -> Self {
-		Self(params_builder::ParamsBuilder::named())
+		Self::with_capacity(0)
 	}
 }
 
@@ -196,6 +209,26 @@ impl ArrayParams {
 		Self::default()
 	}
 
+	/// Construct a new [`ArrayParams`] with room for `capacity` bytes of serialized parameters,
+	/// including the surrounding brackets and separators.
+	///
+	/// The parameters are serialized into a single buffer, which isn't reallocated
+	/// while building if the capacity is large enough.
+	///
+	/// ```rust
+	/// use jsonrpsee_core::params::ArrayParams;
+	/// use jsonrpsee_core::traits::ToRpcParams;
+	///
+	/// let mut builder = ArrayParams::with_capacity(16);
+	/// builder.insert(1).unwrap();
+	/// builder.insert("abc").unwrap();
+	///
+	/// assert_eq!(builder.to_rpc_params().unwrap().unwrap().get(), r#"[1,"abc"]"#);
+	/// ```
+	pub fn with_capacity(capacity: usize) -> Self {
+		Self(params_builder::ParamsBuilder::positional(capacity))
+	}
+
 	/// Insert a plain value into the builder.
 	pub fn insert<P: Serialize>(&mut self, value: P) -> Result<(), serde_json::Error> {
 		self.0.insert(value)
@@ -204,7 +237,7 @@ impl ArrayParams {
 
 impl Default for ArrayParams {
 	fn default() -> Self {
-		Self(params_builder::ParamsBuilder::positional())
+		Self::with_capacity(0)
 	}
 }
 
@@ -226,10 +259,24 @@ const BATCH_PARAMS_NUM_CAPACITY: usize = 4;
 #[error("Empty batch request is not allowed")]
 pub struct EmptyBatchRequest;
 
+/// Entry of a [`BatchRequestBuilder`].
+#[derive(Clone, Debug)]
+pub struct BatchRequestEntry<'a> {
+	/// Name of the method.
+	pub method: &'a str,
+	/// Parameters of the method.
+	pub params: Option<Box<RawValue>>,
+	/// Whether the entry is a notification, which has no id and isn't answered by the server.
+	pub is_notification: bool,
+}
+
 /// Request builder that serializes RPC parameters to construct a valid batch parameter.
 /// This is the equivalent of chaining multiple RPC requests.
+///
+/// The batch may contain notifications, which are not answered by the server and don't have
+/// an entry in the batch response.
 #[derive(Clone, Debug, Default)]
-pub struct BatchRequestBuilder<'a>(Vec<(&'a str, Option<Box<RawValue>>)>);
+pub struct BatchRequestBuilder<'a>(Vec<BatchRequestEntry<'a>>);
 
 impl<'a> BatchRequestBuilder<'a> {
 	/// Construct a new [`BatchRequestBuilder`].
@@ -239,13 +286,22 @@ impl<'a> BatchRequestBuilder<'a> {
 
 	/// Inserts the RPC method with provided parameters into the builder.
 	pub fn insert<Params: ToRpcParams>(&mut self, method: &'a str, value: Params) -> Result<(), serde_json::Error> {
-		self.0.push((method, value.to_rpc_params()?));
+		self.0.push(BatchRequestEntry { method, params: value.to_rpc_params()?, is_notification: false });
+		Ok(())
+	}
+
+	/// Inserts the RPC notification with provided parameters into the builder.
+	pub fn insert_notification<Params: ToRpcParams>(
+		&mut self,
+		method: &'a str,
+		value: Params,
+	) -> Result<(), serde_json::Error> {
+		self.0.push(BatchRequestEntry { method, params: value.to_rpc_params()?, is_notification: true });
 		Ok(())
 	}
 
 	/// Finish the building process and return a valid batch parameter.
-	#[allow(clippy::type_complexity)]
-	pub fn build(self) -> Result<Vec<(&'a str, Option<Box<RawValue>>)>, EmptyBatchRequest> {
+	pub fn build(self) -> Result<Vec<BatchRequestEntry<'a>>, EmptyBatchRequest> {
 		if self.0.is_empty() {
 			Err(EmptyBatchRequest)
 		} else {
@@ -254,13 +310,24 @@ impl<'a> BatchRequestBuilder<'a> {
 	}
 
 	/// Get an iterator over the batch request.
-	pub fn iter(&self) -> impl Iterator<Item = (&'a str, Option<&RawValue>)> {
-		self.0.iter().map(|(method, params)| (*method, params.as_deref()))
+	pub fn iter(&self) -> impl Iterator<Item = &BatchRequestEntry<'a>> {
+		self.0.iter()
+	}
+
+	/// Get the number of method calls in the batch request, which excludes the notifications.
+	pub fn num_calls(&self) -> usize {
+		self.calls().count()
+	}
+
+	/// Get an iterator over the insertion index and method of the method calls in the batch request,
+	/// which excludes the notifications.
+	pub fn calls(&self) -> impl Iterator<Item = (usize, &'a str)> + '_ {
+		self.0.iter().enumerate().filter(|(_, entry)| !entry.is_notification).map(|(idx, entry)| (idx, entry.method))
 	}
 }
 
 impl<'a> IntoIterator for BatchRequestBuilder<'a> {
-	type Item = (&'a str, Option<Box<RawValue>>);
+	type Item = BatchRequestEntry<'a>;
 	type IntoIter = std::vec::IntoIter<Self::Item>;
 
 	fn into_iter(self) -> Self::IntoIter {
@@ -0,0 +1,82 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any
+// person obtaining a copy of this software and associated
+// documentation files (the "Software"), to deal in the
+// Software without restriction, including without
+// limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software
+// is furnished to do so, subject to the following
+// conditions:
+//
+// The above copyright notice and this permission notice
+// shall be included in all copies or substantial portions
+// of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF
+// ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED
+// TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A
+// PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT
+// SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY
+// CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+// IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+
+/// Context of an [`RpcModule`](crate::server::RpcModule) which can be replaced while the server is running,
+/// for instance to reload the configuration or to fail over to another backend without re-registering
+/// the methods.
+///
+/// Cloning the handle shares the context, thus the handle returned by
+/// [`RpcModule::context_handle`](crate::server::RpcModule::context_handle) replaces the context that
+/// the methods of the module see. The calls in progress keep the context that they have loaded.
+///
+/// # Examples
+///
+/// ```
+/// use jsonrpsee_core::server::{RpcModule, SwappableContext};
+///
+/// let mut module = RpcModule::new(SwappableContext::new("primary"));
+/// module.register_method("backend", |_, ctx, _| *ctx.load()).unwrap();
+///
+/// // Fail over to another backend.
+/// module.context_handle().store("secondary");
+/// ```
+#[derive(Debug)]
+pub struct SwappableContext<T>(Arc<RwLock<Arc<T>>>);
+
+impl<T> Clone for SwappableContext<T> {
+	fn clone(&self) -> Self {
+		Self(self.0.clone())
+	}
+}
+
+impl<T> SwappableContext<T> {
+	/// Create a new context.
+	pub fn new(ctx: T) -> Self {
+		Self(Arc::new(RwLock::new(Arc::new(ctx))))
+	}
+
+	/// Get the current context.
+	pub fn load(&self) -> Arc<T> {
+		self.0.read().clone()
+	}
+
+	/// Replace the context and return the previous context.
+	pub fn store(&self, ctx: T) -> Arc<T> {
+		std::mem::replace(&mut *self.0.write(), Arc::new(ctx))
+	}
+
+	/// Replace the context with the context computed by `f` from the current context.
+	///
+	/// Concurrent updates are applied one after another, such that no update is lost.
+	pub fn update(&self, f: impl FnOnce(&T) -> T) {
+		let mut ctx = self.0.write();
+		*ctx = Arc::new(f(&ctx));
+	}
+}
@@ -0,0 +1,105 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any
+// person obtaining a copy of this software and associated
+// documentation files (the "Software"), to deal in the
+// Software without restriction, including without
+// limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software
+// is furnished to do so, subject to the following
+// conditions:
+//
+// The above copyright notice and this permission notice
+// shall be included in all copies or substantial portions
+// of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF
+// ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED
+// TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A
+// PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT
+// SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY
+// CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+// IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Thread pool for CPU-bound methods.
+
+use std::panic::AssertUnwindSafe;
+use std::sync::{mpsc, Arc};
+use std::thread;
+
+use parking_lot::Mutex;
+use tokio::sync::{oneshot, Semaphore};
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// Pool of threads that execute the calls of the CPU-bound methods registered with
+/// [`RpcModule::register_cpu_method`](crate::server::RpcModule::register_cpu_method),
+/// which keeps expensive computations off the threads of the async runtime.
+///
+/// The calls wait in a bounded queue until a thread is available and are rejected with a
+/// [`ErrorCode::ServerIsBusy`](jsonrpsee_types::ErrorCode::ServerIsBusy) error if the queue is full.
+///
+/// The pool can be shared by several methods and its threads are stopped once the pool
+/// and all the methods that use it have been dropped.
+#[derive(Debug, Clone)]
+pub struct CpuPool {
+	jobs: mpsc::Sender<Job>,
+	/// Permits for the calls that are executed or queued.
+	slots: Arc<Semaphore>,
+}
+
+impl CpuPool {
+	/// Create a new pool with `threads` threads and a queue of at most `queue_capacity` calls
+	/// that wait for a thread.
+	///
+	/// # Panics
+	///
+	/// Panics if `threads` is 0 or if a thread couldn't be spawned.
+	pub fn new(threads: usize, queue_capacity: usize) -> Self {
+		assert!(threads > 0, "The CPU pool must have at least one thread");
+
+		let (tx, rx) = mpsc::channel::<Job>();
+		let rx = Arc::new(Mutex::new(rx));
+
+		for i in 0..threads {
+			let rx = rx.clone();
+			thread::Builder::new()
+				.name(format!("jsonrpsee-cpu-{i}"))
+				.spawn(move || loop {
+					let Ok(job) = rx.lock().recv() else {
+						break;
+					};
+					job();
+				})
+				.expect("Failed to spawn CPU pool thread");
+		}
+
+		Self { jobs: tx, slots: Arc::new(Semaphore::new(threads + queue_capacity)) }
+	}
+
+	/// Queue `f` to be executed on the pool and return a receiver for its result, which is
+	/// the payload of the panic if `f` panics.
+	///
+	/// Returns `None` if the queue is full.
+	pub(crate) fn spawn<R>(
+		&self,
+		f: impl FnOnce() -> R + Send + 'static,
+	) -> Option<oneshot::Receiver<thread::Result<R>>>
+	where
+		R: Send + 'static,
+	{
+		let slot = self.slots.clone().try_acquire_owned().ok()?;
+		let (tx, rx) = oneshot::channel();
+		let job = Box::new(move || {
+			let rp = std::panic::catch_unwind(AssertUnwindSafe(f));
+			// The slot is released before the result is sent such that the caller can queue another call.
+			drop(slot);
+			_ = tx.send(rp);
+		});
+
+		self.jobs.send(job).ok().map(|()| rx)
+	}
+}
@@ -0,0 +1,81 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any
+// person obtaining a copy of this software and associated
+// documentation files (the "Software"), to deal in the
+// Software without restriction, including without
+// limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software
+// is furnished to do so, subject to the following
+// conditions:
+//
+// The above copyright notice and this permission notice
+// shall be included in all copies or substantial portions
+// of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF
+// ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED
+// TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A
+// PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT
+// SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY
+// CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+// IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::time::{Duration, Instant};
+
+use http::Extensions;
+
+/// Point in time after which the caller of a method has given up waiting for the response,
+/// such that the method may stop working on it.
+///
+/// The HTTP server inserts it in the extensions of the calls of requests with the
+/// [`DEADLINE_HEADER`](crate::http_helpers::DEADLINE_HEADER), which is filled in by clients
+/// from their request timeout.
+///
+/// # Examples
+///
+/// ```
+/// use jsonrpsee_core::server::{Deadline, RpcModule};
+///
+/// let mut module = RpcModule::new(());
+/// module
+///     .register_method("work", |_, _, ext| match Deadline::from_extensions(ext) {
+///         Some(deadline) if deadline.is_expired() => "too late",
+///         _ => "done",
+///     })
+///     .unwrap();
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Deadline(Instant);
+
+impl Deadline {
+	/// Create a deadline which expires `timeout` from now.
+	///
+	/// Returns `None` if the deadline is too far in the future to be represented.
+	pub fn after(timeout: Duration) -> Option<Self> {
+		Instant::now().checked_add(timeout).map(Self)
+	}
+
+	/// Get the deadline of a call from its extensions, if the caller sent one.
+	pub fn from_extensions(extensions: &Extensions) -> Option<Self> {
+		extensions.get::<Self>().copied()
+	}
+
+	/// Get the point in time at which the deadline expires.
+	pub fn instant(&self) -> Instant {
+		self.0
+	}
+
+	/// Get the time left until the deadline expires, which is zero once it has expired.
+	pub fn remaining(&self) -> Duration {
+		self.0.saturating_duration_since(Instant::now())
+	}
+
+	/// Returns whether the deadline has expired.
+	pub fn is_expired(&self) -> bool {
+		Instant::now() >= self.0
+	}
+}
@@ -24,7 +24,7 @@
 // IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
 // DEALINGS IN THE SOFTWARE.
 
-use crate::server::SubscriptionMessage;
+use crate::server::{SinkMessage, SubscriptionMessage};
 use tokio::sync::mpsc;
 
 /// Error that may occur during [`crate::server::MethodSink::try_send`] or [`crate::server::SubscriptionSink::try_send`].
@@ -60,26 +60,34 @@ pub enum SendTimeoutError {
 #[error("The remote peer closed the connection")]
 pub struct PendingSubscriptionAcceptError;
 
-impl From<mpsc::error::SendError<String>> for DisconnectError {
-	fn from(e: mpsc::error::SendError<String>) -> Self {
-		DisconnectError(SubscriptionMessage::from_complete_message(e.0))
+impl From<mpsc::error::SendError<SinkMessage>> for DisconnectError {
+	fn from(e: mpsc::error::SendError<SinkMessage>) -> Self {
+		DisconnectError(SubscriptionMessage::from_complete_message(e.0.into_string()))
 	}
 }
 
-impl From<mpsc::error::TrySendError<String>> for TrySendError {
-	fn from(e: mpsc::error::TrySendError<String>) -> Self {
+impl From<mpsc::error::TrySendError<SinkMessage>> for TrySendError {
+	fn from(e: mpsc::error::TrySendError<SinkMessage>) -> Self {
 		match e {
-			mpsc::error::TrySendError::Closed(m) => Self::Closed(SubscriptionMessage::from_complete_message(m)),
-			mpsc::error::TrySendError::Full(m) => Self::Full(SubscriptionMessage::from_complete_message(m)),
+			mpsc::error::TrySendError::Closed(m) => {
+				Self::Closed(SubscriptionMessage::from_complete_message(m.into_string()))
+			}
+			mpsc::error::TrySendError::Full(m) => {
+				Self::Full(SubscriptionMessage::from_complete_message(m.into_string()))
+			}
 		}
 	}
 }
 
-impl From<mpsc::error::SendTimeoutError<String>> for SendTimeoutError {
-	fn from(e: mpsc::error::SendTimeoutError<String>) -> Self {
+impl From<mpsc::error::SendTimeoutError<SinkMessage>> for SendTimeoutError {
+	fn from(e: mpsc::error::SendTimeoutError<SinkMessage>) -> Self {
 		match e {
-			mpsc::error::SendTimeoutError::Closed(m) => Self::Closed(SubscriptionMessage::from_complete_message(m)),
-			mpsc::error::SendTimeoutError::Timeout(m) => Self::Timeout(SubscriptionMessage::from_complete_message(m)),
+			mpsc::error::SendTimeoutError::Closed(m) => {
+				Self::Closed(SubscriptionMessage::from_complete_message(m.into_string()))
+			}
+			mpsc::error::SendTimeoutError::Timeout(m) => {
+				Self::Timeout(SubscriptionMessage::from_complete_message(m.into_string()))
+			}
 		}
 	}
 }
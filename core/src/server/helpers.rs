@@ -25,10 +25,12 @@
 // DEALINGS IN THE SOFTWARE.
 
 use std::io;
+use std::sync::Arc;
 use std::time::Duration;
 
 use jsonrpsee_types::{ErrorCode, ErrorObject, Id, InvalidRequest, Response, ResponsePayload};
-use tokio::sync::mpsc;
+use parking_lot::{Mutex, RwLock};
+use tokio::sync::{mpsc, watch};
 
 use super::{DisconnectError, SendTimeoutError, SubscriptionMessage, TrySendError};
 
@@ -55,6 +57,12 @@ impl BoundedWriter {
 		Self { max_len, buf: Vec::with_capacity(128) }
 	}
 
+	/// Create a new bounded writer that writes to `buf`, such as a buffer of a [`BufferPool`].
+	pub fn with_buffer(max_len: usize, mut buf: Vec<u8>) -> Self {
+		buf.clear();
+		Self { max_len, buf }
+	}
+
 	/// Consume the writer and extract the written bytes.
 	pub fn into_bytes(self) -> Vec<u8> {
 		self.buf
@@ -77,24 +85,148 @@ impl<'a> io::Write for &'a mut BoundedWriter {
 	}
 }
 
+/// Pool of the buffers that the messages of a connection are serialized into,
+/// which avoids allocating a new buffer for every message.
+///
+/// The buffer of a message is returned to the pool once the message has been sent
+/// but buffers that have grown beyond 64 KiB are dropped to bound the memory of the pool.
+#[derive(Clone, Debug)]
+pub struct BufferPool {
+	buffers: Arc<Mutex<Vec<Vec<u8>>>>,
+	max_buffers: usize,
+}
+
+impl BufferPool {
+	/// Max capacity of a buffer which is returned to the pool.
+	const MAX_BUFFER_CAPACITY: usize = 64 * 1024;
+
+	/// Create a new pool that keeps at most `max_buffers` buffers.
+	pub fn new(max_buffers: usize) -> Self {
+		Self { buffers: Arc::new(Mutex::new(Vec::with_capacity(max_buffers))), max_buffers }
+	}
+
+	/// Take an empty buffer from the pool or allocate a new one if the pool is empty.
+	pub fn get(&self) -> Vec<u8> {
+		self.buffers.lock().pop().unwrap_or_else(|| Vec::with_capacity(128))
+	}
+
+	/// Return the buffer of a message that has been sent to the pool.
+	pub fn recycle(&self, msg: String) {
+		let mut buf = msg.into_bytes();
+
+		if buf.capacity() > Self::MAX_BUFFER_CAPACITY {
+			return;
+		}
+		buf.clear();
+
+		let mut buffers = self.buffers.lock();
+		if buffers.len() < self.max_buffers {
+			buffers.push(buf);
+		}
+	}
+}
+
+/// Message that is sent to the connection by a [`MethodSink`].
+///
+/// The result of a notification is shared by reference instead of being copied for each
+/// subscription that it's sent to, and the notification is only written out as one JSON
+/// message once it's sent on the connection.
+#[derive(Debug, Clone)]
+pub enum SinkMessage {
+	/// Complete JSON message.
+	Json(String),
+	/// Notification which consists of the JSON up to its `result` and the `result` itself.
+	Notification {
+		/// The JSON of the notification up to its `result`.
+		head: String,
+		/// The JSON of the result which may be shared with other notifications.
+		result: Arc<str>,
+	},
+}
+
+impl SinkMessage {
+	/// The JSON that follows the `result` of a notification.
+	const NOTIFICATION_TAIL: &'static str = "}}";
+
+	/// Get the length of the message in bytes.
+	pub fn len(&self) -> usize {
+		match self {
+			Self::Json(json) => json.len(),
+			Self::Notification { head, result } => head.len() + result.len() + Self::NOTIFICATION_TAIL.len(),
+		}
+	}
+
+	/// Returns whether the message is empty.
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// Convert the message into a JSON string.
+	pub fn into_string(self) -> String {
+		match self {
+			Self::Json(json) => json,
+			Self::Notification { .. } => self.write_notification(String::with_capacity(self.len())),
+		}
+	}
+
+	/// Convert the message into a JSON string which is written into a buffer of `pool` if it's a notification.
+	pub fn into_pooled_string(self, pool: &BufferPool) -> String {
+		match self {
+			Self::Json(json) => json,
+			Self::Notification { .. } => {
+				let buf = String::from_utf8(pool.get()).expect("the buffers of the pool are empty; qed");
+				self.write_notification(buf)
+			}
+		}
+	}
+
+	fn write_notification(&self, mut buf: String) -> String {
+		if let Self::Notification { head, result } = self {
+			buf.push_str(head);
+			buf.push_str(result);
+			buf.push_str(Self::NOTIFICATION_TAIL);
+		}
+		buf
+	}
+}
+
+impl From<String> for SinkMessage {
+	fn from(json: String) -> Self {
+		Self::Json(json)
+	}
+}
+
 /// Sink that is used to send back the result to the server for a specific method.
 #[derive(Clone, Debug)]
 pub struct MethodSink {
 	/// Channel sender.
-	tx: mpsc::Sender<String>,
+	tx: mpsc::Sender<SinkMessage>,
 	/// Max response size in bytes for a executed call.
 	max_response_size: u32,
+	/// Pool of the buffers that the batches of notifications are serialized into.
+	buffer_pool: Option<BufferPool>,
 }
 
 impl MethodSink {
 	/// Create a new `MethodSink` with unlimited response size.
-	pub fn new(tx: mpsc::Sender<String>) -> Self {
-		MethodSink { tx, max_response_size: u32::MAX }
+	pub fn new(tx: mpsc::Sender<SinkMessage>) -> Self {
+		MethodSink { tx, max_response_size: u32::MAX, buffer_pool: None }
 	}
 
 	/// Create a new `MethodSink` with a limited response size.
-	pub fn new_with_limit(tx: mpsc::Sender<String>, max_response_size: u32) -> Self {
-		MethodSink { tx, max_response_size }
+	pub fn new_with_limit(tx: mpsc::Sender<SinkMessage>, max_response_size: u32) -> Self {
+		MethodSink { tx, max_response_size, buffer_pool: None }
+	}
+
+	/// Serialize the batches of notifications into the buffers of `pool`.
+	pub fn with_buffer_pool(mut self, pool: BufferPool) -> Self {
+		self.buffer_pool = Some(pool);
+		self
+	}
+
+	/// Get the pool of the buffers that the batches of notifications are serialized into.
+	pub fn buffer_pool(&self) -> Option<&BufferPool> {
+		self.buffer_pool.as_ref()
 	}
 
 	/// Returns whether this channel is closed without needing a context.
@@ -120,13 +252,13 @@ impl MethodSink {
 	/// connection has been closed or if the message buffer is full.
 	///
 	/// Returns the message if the send fails such that either can be thrown away or re-sent later.
-	pub fn try_send(&mut self, msg: String) -> Result<(), TrySendError> {
-		self.tx.try_send(msg).map_err(Into::into)
+	pub fn try_send(&mut self, msg: impl Into<SinkMessage>) -> Result<(), TrySendError> {
+		self.tx.try_send(msg.into()).map_err(Into::into)
 	}
 
 	/// Async send which will wait until there is space in channel buffer or that the subscription is disconnected.
-	pub async fn send(&self, msg: String) -> Result<(), DisconnectError> {
-		self.tx.send(msg).await.map_err(Into::into)
+	pub async fn send(&self, msg: impl Into<SinkMessage>) -> Result<(), DisconnectError> {
+		self.tx.send(msg.into()).await.map_err(Into::into)
 	}
 
 	/// Send a JSON-RPC error to the client
@@ -138,8 +270,8 @@ impl MethodSink {
 	}
 
 	/// Similar to `MethodSink::send` but only waits for a limited time.
-	pub async fn send_timeout(&self, msg: String, timeout: Duration) -> Result<(), SendTimeoutError> {
-		self.tx.send_timeout(msg, timeout).await.map_err(Into::into)
+	pub async fn send_timeout(&self, msg: impl Into<SinkMessage>, timeout: Duration) -> Result<(), SendTimeoutError> {
+		self.tx.send_timeout(msg.into(), timeout).await.map_err(Into::into)
 	}
 
 	/// Get the capacity of the channel.
@@ -163,6 +295,110 @@ impl MethodSink {
 	}
 }
 
+/// Token that is cancelled once the connection that made a call is closed.
+///
+/// The server inserts it in the [`Extensions`](http::Extensions) of every call such that
+/// long-running handlers can stop computing results that nobody will receive.
+///
+/// For the WebSocket transport the token is cancelled once the connection is closed and
+/// for the HTTP transport once the request has been answered or aborted by the client.
+#[derive(Debug, Clone)]
+pub struct CancellationToken(watch::Receiver<()>);
+
+impl CancellationToken {
+	/// Returns whether the token has been cancelled.
+	pub fn is_cancelled(&self) -> bool {
+		self.0.has_changed().is_err()
+	}
+
+	/// Completes once the token has been cancelled.
+	///
+	/// # Cancel safety
+	/// This method is cancel safe.
+	pub async fn cancelled(&self) {
+		let mut rx = self.0.clone();
+		// Nothing is ever sent on the channel, it fails once the guard is dropped.
+		while rx.changed().await.is_ok() {}
+	}
+}
+
+/// Guard that cancels all its [`CancellationToken`]s when dropped.
+#[derive(Debug)]
+pub struct CancellationGuard(watch::Sender<()>);
+
+impl CancellationGuard {
+	/// Create a new guard.
+	pub fn new() -> Self {
+		Self(watch::channel(()).0)
+	}
+
+	/// Create a new token which is cancelled once the guard is dropped.
+	pub fn token(&self) -> CancellationToken {
+		CancellationToken(self.0.subscribe())
+	}
+}
+
+impl Default for CancellationGuard {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// Typed storage that is shared by all calls on a connection.
+///
+/// The server creates it when a connection is established and inserts it in the
+/// [`Extensions`](http::Extensions) of every call and subscription on that connection such that
+/// handlers can keep session data around, for example authorization scopes or caches.
+///
+/// Unlike the [`Extensions`](http::Extensions) of a call, which are cloned for every call,
+/// changes to the storage are visible to the subsequent calls on the connection.
+///
+/// # Examples
+///
+/// ```
+/// use jsonrpsee_core::server::{ConnectionExtensions, RpcModule};
+///
+/// #[derive(Clone)]
+/// struct Calls(usize);
+///
+/// let mut module = RpcModule::new(());
+/// module.register_method("count", |_, _, ext| {
+///     let conn = ext.get::<ConnectionExtensions>().unwrap();
+///     let calls = conn.get::<Calls>().map_or(1, |c| c.0 + 1);
+///     conn.insert(Calls(calls));
+///     calls
+/// }).unwrap();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ConnectionExtensions(Arc<RwLock<http::Extensions>>);
+
+impl ConnectionExtensions {
+	/// Create a new empty storage.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Insert a value into the storage and return the previous value of that type, if any.
+	pub fn insert<T: Clone + Send + Sync + 'static>(&self, val: T) -> Option<T> {
+		self.0.write().insert(val)
+	}
+
+	/// Get a copy of the value of that type, if any.
+	pub fn get<T: Clone + Send + Sync + 'static>(&self) -> Option<T> {
+		self.0.read().get::<T>().cloned()
+	}
+
+	/// Get a copy of the value of that type or insert the value returned by `f`.
+	pub fn get_or_insert_with<T: Clone + Send + Sync + 'static>(&self, f: impl FnOnce() -> T) -> T {
+		self.0.write().get_or_insert_with(f).clone()
+	}
+
+	/// Remove the value of that type from the storage and return it, if any.
+	pub fn remove<T: Clone + Send + Sync + 'static>(&self) -> Option<T> {
+		self.0.write().remove::<T>()
+	}
+}
+
 /// Figure out if this is a sufficiently complete request that we can extract an [`Id`] out of, or just plain
 /// unparseable garbage.
 pub fn prepare_error(data: &[u8]) -> (Id<'_>, ErrorCode) {
@@ -174,7 +410,7 @@ pub fn prepare_error(data: &[u8]) -> (Id<'_>, ErrorCode) {
 
 #[cfg(test)]
 mod tests {
-	use crate::server::BoundedWriter;
+	use crate::server::{BoundedWriter, BufferPool, CancellationGuard};
 	use jsonrpsee_types::{Id, Response, ResponsePayload};
 
 	#[test]
@@ -193,4 +429,40 @@ mod tests {
 		// NOTE: `"` is part of the serialization so 101 characters.
 		assert!(serde_json::to_writer(&mut writer, &"x".repeat(99)).is_err());
 	}
+
+	#[test]
+	fn buffer_pool_works() {
+		let pool = BufferPool::new(1);
+
+		let mut buf = pool.get();
+		buf.extend_from_slice(b"hello");
+		let ptr = buf.as_ptr();
+		pool.recycle(String::from_utf8(buf).unwrap());
+		// The pool is full.
+		pool.recycle(String::from("world"));
+
+		let buf = pool.get();
+		assert!(buf.is_empty());
+		assert_eq!(buf.as_ptr(), ptr);
+
+		// Buffers that have grown too big aren't kept.
+		pool.recycle("x".repeat(BufferPool::MAX_BUFFER_CAPACITY + 1));
+		assert!(pool.buffers.lock().is_empty());
+	}
+
+	#[tokio::test]
+	async fn cancellation_token_works() {
+		let guard = CancellationGuard::new();
+		let token = guard.token();
+		assert!(!token.is_cancelled());
+
+		let waiter = tokio::spawn({
+			let token = token.clone();
+			async move { token.cancelled().await }
+		});
+
+		drop(guard);
+		waiter.await.unwrap();
+		assert!(token.is_cancelled());
+	}
 }
@@ -25,15 +25,21 @@
 // DEALINGS IN THE SOFTWARE.
 
 use crate::server::{BoundedWriter, LOG_TARGET};
+use crate::BoxError;
+use std::pin::Pin;
+use std::sync::Arc;
 use std::task::Poll;
 
+use futures_util::future;
+use futures_util::stream::{self, Stream, StreamExt};
 use futures_util::{Future, FutureExt};
 use http::Extensions;
 use jsonrpsee_types::error::{
 	reject_too_big_batch_response, ErrorCode, ErrorObject, OVERSIZED_RESPONSE_CODE, OVERSIZED_RESPONSE_MSG,
 };
 use jsonrpsee_types::{ErrorObjectOwned, Id, Response, ResponsePayload as InnerResponsePayload};
-use serde::Serialize;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
 use serde_json::value::to_raw_value;
 
 #[derive(Debug, Clone)]
@@ -117,6 +123,71 @@ impl MethodResponse {
 		&self.result
 	}
 
+	/// Deserialize the error object of the response.
+	///
+	/// Returns `None` if the call was successful.
+	pub fn error_object(&self) -> Option<ErrorObjectOwned> {
+		self.deserialize_error().map(|(_, err)| err)
+	}
+
+	/// Replace the error object of the response with the one returned by `f`, for instance
+	/// to attach data to the error or to remove internal details from it.
+	///
+	/// The response is serialized again and is left as is if the call was successful.
+	pub fn map_error(mut self, f: impl FnOnce(ErrorObjectOwned) -> ErrorObjectOwned) -> Self {
+		let Some((id, err)) = self.deserialize_error() else {
+			return self;
+		};
+
+		let err = f(err);
+		let err_code = err.code();
+		let payload = InnerResponsePayload::<()>::error(err);
+		self.result = serde_json::to_string(&Response::new(payload, id)).expect("JSON serialization infallible; qed");
+		self.success_or_error = MethodResponseResult::Failed(err_code);
+		self
+	}
+
+	/// Replace the `id` of the response with the JSON `raw`, for instance to echo the id of the call
+	/// exactly as it was sent.
+	///
+	/// The response is left as is if it's a batch response or if its id isn't `id`.
+	pub fn with_raw_id(mut self, id: &Id, raw: &str) -> Self {
+		let id = serde_json::to_string(id).expect("JSON serialization infallible; qed");
+
+		if !self.is_batch() {
+			replace_id(&mut self.result, &id, raw);
+		}
+
+		self
+	}
+
+	/// Convert the response into a [JSON-RPC 1.0](jsonrpsee_types::v1) response.
+	pub fn into_json_rpc_v1(mut self) -> Self {
+		self.result = jsonrpsee_types::v1::response_to_v1(&self.result);
+		self
+	}
+
+	fn deserialize_error(&self) -> Option<(Id<'static>, ErrorObjectOwned)> {
+		#[derive(Deserialize)]
+		struct ErrorResponse<'a> {
+			#[serde(borrow)]
+			id: Id<'a>,
+			error: ErrorObjectOwned,
+		}
+
+		if !self.is_error() {
+			return None;
+		}
+
+		match serde_json::from_str::<ErrorResponse>(&self.result) {
+			Ok(rp) => Some((rp.id.into_owned(), rp.error)),
+			Err(e) => {
+				tracing::warn!(target: LOG_TARGET, "Could not deserialize error response: {:?}", e);
+				None
+			}
+		}
+	}
+
 	/// Create a method response from [`BatchResponse`].
 	pub fn from_batch(batch: BatchResponse) -> Self {
 		Self {
@@ -147,7 +218,16 @@ impl MethodResponse {
 	where
 		T: Serialize + Clone,
 	{
-		let mut writer = BoundedWriter::new(max_response_size);
+		Self::response_with_buffer(id, rp, max_response_size, Vec::with_capacity(128))
+	}
+
+	/// Similar to [`MethodResponse::response`] but serializes the response into `buf`,
+	/// such as a buffer of a [`BufferPool`](crate::server::BufferPool).
+	pub fn response_with_buffer<T>(id: Id, rp: ResponsePayload<T>, max_response_size: usize, buf: Vec<u8>) -> Self
+	where
+		T: Serialize + Clone,
+	{
+		let mut writer = BoundedWriter::with_buffer(max_response_size, buf);
 
 		let success_or_error = if let InnerResponsePayload::Error(ref e) = rp.inner {
 			MethodResponseResult::Failed(e.code())
@@ -227,6 +307,9 @@ impl MethodResponse {
 	}
 
 	/// Returns a reference to the associated extensions.
+	///
+	/// The response of an accepted subscription contains the
+	/// [`SubscriptionId`](jsonrpsee_types::SubscriptionId) of the subscription.
 	pub fn extensions(&self) -> &Extensions {
 		&self.extensions
 	}
@@ -242,6 +325,137 @@ impl MethodResponse {
 	}
 }
 
+/// Replace the serialized `id` at the start of the serialized `response` with `raw`.
+fn replace_id(response: &mut String, id: &str, raw: &str) {
+	const HEAD: &str = r#"{"jsonrpc":"2.0","id":"#;
+
+	let rest = response.strip_prefix(HEAD).and_then(|rest| rest.strip_prefix(id));
+
+	if rest.is_some_and(|rest| rest.starts_with([',', '}'])) {
+		response.replace_range(HEAD.len()..HEAD.len() + id.len(), raw);
+	}
+}
+
+/// The serialized chunks of a streamed response, see [`StreamedResponseSupport`].
+pub struct ResponseStream(Pin<Box<dyn Stream<Item = Result<String, BoxError>> + Send>>);
+
+impl std::fmt::Debug for ResponseStream {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("ResponseStream").finish_non_exhaustive()
+	}
+}
+
+impl Stream for ResponseStream {
+	type Item = Result<String, BoxError>;
+
+	fn poll_next(mut self: Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> Poll<Option<Self::Item>> {
+		self.0.as_mut().poll_next(cx)
+	}
+}
+
+/// Handle in the extensions of a call which indicates that the transport is able to send
+/// a streamed response, such as the result of a method registered with
+/// [`RpcModule::register_streaming_method`](crate::server::RpcModule::register_streaming_method).
+///
+/// The method handler puts the chunks of the response into the handle and answers the call with a
+/// placeholder [`MethodResponse`], which the transport replaces with the chunks if it's successful.
+/// Only the HTTP transport inserts the handle for single calls, a middleware which needs the buffered
+/// response has to remove it from the extensions of the request.
+#[derive(Debug, Clone, Default)]
+pub struct StreamedResponseSupport(Arc<Mutex<Option<ResponseStream>>>);
+
+impl StreamedResponseSupport {
+	/// Create a new handle without a streamed response.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Set the streamed response to the call `id`, where the serialized `result` is produced by
+	/// the stream of chunks that are wrapped in the JSON-RPC response object.
+	///
+	/// The chunks must together form valid JSON. If the stream yields an error the response is aborted.
+	pub(crate) fn set<S>(&self, id: &Id, result: S)
+	where
+		S: Stream<Item = Result<String, BoxError>> + Send + 'static,
+	{
+		let id = serde_json::to_string(id).expect("JSON serialization infallible; qed");
+		let head = format!(r#"{{"jsonrpc":"2.0","id":{id},"result":"#);
+		let tail = "}".to_owned();
+		let chunks = stream::once(future::ready(Ok(head))).chain(result).chain(stream::once(future::ready(Ok(tail))));
+
+		*self.0.lock() = Some(ResponseStream(Box::pin(chunks)));
+	}
+
+	/// Take the streamed response.
+	///
+	/// Returns `None` if the call wasn't answered with a streamed response or if it has already been taken.
+	pub fn take(&self) -> Option<ResponseStream> {
+		self.0.lock().take()
+	}
+}
+
+/// Extensions of the response to a call which a method handler or middleware may attach typed values to,
+/// such as cache hints or the identity of the caller.
+///
+/// The server inserts it in the [`Extensions`] of every call, unless a middleware has already done so,
+/// and passes the same handle on to the [`MethodResponse::extensions`] of the call. That is, a middleware
+/// inserts values which the method handler reads into the extensions of the request and a method handler
+/// attaches values which the middleware observes to the [`ResponseExtensions`] of the request.
+///
+/// Over HTTP the values of a single call, but not of a batch, are moved into the extensions of the HTTP
+/// response once the call has been answered and a [`http::HeaderMap`] among them is appended to the
+/// headers of the HTTP response.
+///
+/// ```
+/// use jsonrpsee_core::server::{Extensions, ResponseExtensions};
+///
+/// #[derive(Debug, Clone, PartialEq)]
+/// struct CacheHint(u64);
+///
+/// // In the method handler.
+/// fn handler(extensions: &Extensions) {
+///     if let Some(rp_ext) = extensions.get::<ResponseExtensions>() {
+///         rp_ext.insert(CacheHint(60));
+///     }
+/// }
+///
+/// let rp_ext = ResponseExtensions::new();
+/// let mut extensions = Extensions::new();
+/// extensions.insert(rp_ext.clone());
+///
+/// handler(&extensions);
+/// assert_eq!(rp_ext.get::<CacheHint>(), Some(CacheHint(60)));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ResponseExtensions(Arc<Mutex<Extensions>>);
+
+impl ResponseExtensions {
+	/// Create new empty response extensions.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Insert a value, which replaces and returns the previous value of the same type.
+	pub fn insert<T: Clone + Send + Sync + 'static>(&self, val: T) -> Option<T> {
+		self.0.lock().insert(val)
+	}
+
+	/// Get a clone of the value of type `T`.
+	pub fn get<T: Clone + Send + Sync + 'static>(&self) -> Option<T> {
+		self.0.lock().get::<T>().cloned()
+	}
+
+	/// Remove the value of type `T`.
+	pub fn remove<T: Clone + Send + Sync + 'static>(&self) -> Option<T> {
+		self.0.lock().remove::<T>()
+	}
+
+	/// Take all values, which leaves the response extensions empty.
+	pub fn take(&self) -> Extensions {
+		std::mem::take(&mut *self.0.lock())
+	}
+}
+
 /// Represent the outcome of a method call success or failed.
 #[derive(Debug, Copy, Clone)]
 enum MethodResponseResult {
@@ -513,4 +727,41 @@ mod tests {
 		let exp_err = r#"{"jsonrpc":"2.0","id":null,"error":{"code":-32011,"message":"The batch response was too large","data":"Exceeded max limit of 63"}}"#;
 		assert_eq!(batch.result, exp_err);
 	}
+
+	#[test]
+	fn map_error_works() {
+		use jsonrpsee_types::{ErrorObject, ErrorObjectOwned};
+
+		let success = MethodResponse::response(Id::Number(1), ResponsePayload::success_borrowed(&"a"), usize::MAX);
+		assert!(success.error_object().is_none());
+		let success = success.map_error(|_| unreachable!());
+		assert_eq!(success.result, r#"{"jsonrpc":"2.0","id":1,"result":"a"}"#);
+
+		let failed = MethodResponse::error(Id::Str("x".into()), ErrorObject::owned(-32000, "boom", Some("secret")));
+		assert_eq!(failed.error_object(), Some(ErrorObject::owned(-32000, "boom", Some("secret"))));
+
+		let failed = failed.map_error(|err| ErrorObjectOwned::owned(-32001, err.message().to_owned(), None::<()>));
+		assert_eq!(failed.as_error_code(), Some(-32001));
+		assert_eq!(failed.result, r#"{"jsonrpc":"2.0","id":"x","error":{"code":-32001,"message":"boom"}}"#);
+	}
+
+	#[test]
+	fn into_json_rpc_v1_works() {
+		let rp = MethodResponse::response(Id::Number(1), ResponsePayload::success("a"), usize::MAX).into_json_rpc_v1();
+		let expected = serde_json::json!({"result": "a", "error": null, "id": 1});
+		assert_eq!(serde_json::from_str::<serde_json::Value>(&rp.result).unwrap(), expected);
+		assert!(rp.is_success());
+	}
+
+	#[test]
+	fn with_raw_id_works() {
+		let rp = MethodResponse::response(Id::Number(1), ResponsePayload::success("a"), usize::MAX);
+		let rp = rp.with_raw_id(&Id::Number(1), "1.0");
+		assert_eq!(rp.result, r#"{"jsonrpc":"2.0","id":1.0,"result":"a"}"#);
+
+		// The id is only replaced if it matches.
+		let rp = MethodResponse::response(Id::Number(12), ResponsePayload::success("a"), usize::MAX);
+		let rp = rp.with_raw_id(&Id::Number(1), "1.0");
+		assert_eq!(rp.result, r#"{"jsonrpc":"2.0","id":12,"result":"a"}"#);
+	}
 }
@@ -26,25 +26,52 @@
 
 //! Shared modules for the JSON-RPC servers.
 
+/// Context of modules which can be replaced at runtime.
+mod context;
+/// Thread pool for CPU-bound methods.
+mod cpu_pool;
+/// Deadline of calls.
+mod deadline;
 /// Error types.
 mod error;
 /// Helpers.
 pub mod helpers;
 /// Method response related types.
 mod method_response;
+/// OpenRPC document generation.
+mod openrpc;
+/// Panic isolation of method handlers.
+mod panic;
+/// Validation of the params of calls against a JSON Schema.
+mod params_schema;
 /// JSON-RPC "modules" group sets of methods that belong together and handles method/subscription registration.
 mod rpc_module;
 /// Subscription related types.
 mod subscription;
-
+/// Subscription statistics.
+mod subscription_stats;
+/// Deserialization of the params of typed methods.
+mod typed_params;
+
+pub use context::*;
+pub use cpu_pool::*;
+pub use deadline::*;
 pub use error::*;
 pub use helpers::*;
 pub use http::Extensions;
 pub use method_response::*;
+pub use openrpc::*;
+pub use panic::*;
+pub use params_schema::{validate_params, ParamsSchemaError, SchemaViolation};
 pub use rpc_module::*;
 pub use subscription::*;
+pub use subscription_stats::*;
+
+use std::borrow::Cow;
+use std::sync::Arc;
 
 use jsonrpsee_types::ErrorObjectOwned;
+use serde_json::value::RawValue;
 
 const LOG_TARGET: &str = "jsonrpsee-server";
 
@@ -137,6 +164,53 @@ impl IntoResponse for ErrorObjectOwned {
 	}
 }
 
+/// Pre-serialized JSON which is embedded as-is in the response.
+///
+/// The JSON is validated once when the `RawJson` is created and is then
+/// written to the response without being parsed or serialized again,
+/// which is useful for proxies and caches that already hold serialized JSON.
+///
+/// Cloning a `RawJson` is cheap because the JSON is reference-counted.
+#[derive(Debug, Clone)]
+pub struct RawJson(Arc<RawValue>);
+
+impl RawJson {
+	/// Create a new `RawJson` from a string which must be valid JSON.
+	pub fn new(json: impl Into<String>) -> Result<Self, serde_json::Error> {
+		RawValue::from_string(json.into()).map(Into::into)
+	}
+
+	/// Get the JSON as a [`RawValue`].
+	pub fn as_raw_value(&self) -> &RawValue {
+		&self.0
+	}
+
+	/// Get the JSON as a string.
+	pub fn get(&self) -> &str {
+		self.0.get()
+	}
+}
+
+impl From<Box<RawValue>> for RawJson {
+	fn from(json: Box<RawValue>) -> Self {
+		Self(json.into())
+	}
+}
+
+impl From<Arc<RawValue>> for RawJson {
+	fn from(json: Arc<RawValue>) -> Self {
+		Self(json)
+	}
+}
+
+impl serde::Serialize for RawJson {
+	fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		self.0.serialize(serializer)
+	}
+}
+
+// Static and shared results such as `Cow<'static, RawValue>` or `Arc<str>` are serialized
+// straight into the response, without being cloned into an owned value first.
 macro_rules! impl_into_response {
 	($($n:ty),*) => {
 		$(
@@ -166,7 +240,13 @@ impl_into_response!(
 	isize,
 	String,
 	&'static str,
+	Cow<'static, str>,
+	Arc<str>,
 	bool,
 	serde_json::Value,
+	Box<RawValue>,
+	Cow<'static, RawValue>,
+	Arc<RawValue>,
+	RawJson,
 	()
 );
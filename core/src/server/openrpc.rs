@@ -0,0 +1,527 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any
+// person obtaining a copy of this software and associated
+// documentation files (the "Software"), to deal in the
+// Software without restriction, including without
+// limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software
+// is furnished to do so, subject to the following
+// conditions:
+//
+// The above copyright notice and this permission notice
+// shall be included in all copies or substantial portions
+// of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF
+// ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED
+// TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A
+// PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT
+// SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY
+// CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+// IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::sync::Arc;
+
+use serde_json::{json, Map, Value};
+
+use crate::server::{MethodCallback, Methods};
+
+/// The version of the OpenRPC specification that the generated documents conform to.
+pub const OPENRPC_VERSION: &str = "1.2.6";
+
+/// Describes a parameter of a method.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParamDescriptor {
+	name: String,
+	schema: Value,
+	required: bool,
+}
+
+impl ParamDescriptor {
+	/// Create a new required parameter with the given JSON Schema.
+	pub fn new(name: impl Into<String>, schema: Value) -> Self {
+		Self { name: name.into(), schema, required: true }
+	}
+
+	/// Create a new required parameter which accepts any value, for instance because its schema is unknown.
+	pub fn any(name: impl Into<String>) -> Self {
+		Self::new(name, json!({}))
+	}
+
+	/// Mark the parameter as optional.
+	pub fn optional(mut self) -> Self {
+		self.required = false;
+		self
+	}
+
+	/// Get the name of the parameter.
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	/// Get the JSON Schema of the parameter.
+	pub fn schema(&self) -> &Value {
+		&self.schema
+	}
+
+	/// Returns whether the parameter is required.
+	pub fn is_required(&self) -> bool {
+		self.required
+	}
+
+	fn to_json(&self) -> Value {
+		json!({ "name": self.name, "schema": self.schema, "required": self.required })
+	}
+}
+
+cfg_schemars! {
+	impl ParamDescriptor {
+		/// Create a new required parameter whose JSON Schema is the schema of `T` derived with [`schemars`].
+		///
+		/// The schema is self-contained, the definitions of the types that it references are included
+		/// in its `$defs`.
+		///
+		/// # Examples
+		///
+		/// ```
+		/// use jsonrpsee_core::server::ParamDescriptor;
+		///
+		/// let param = ParamDescriptor::of::<Vec<u64>>("values");
+		/// assert_eq!(param.schema()["type"], "array");
+		/// ```
+		pub fn of<T: schemars::JsonSchema + ?Sized>(name: impl Into<String>) -> Self {
+			let settings = schemars::generate::SchemaSettings::draft2020_12().with(|s| s.meta_schema = None);
+			Self::new(name, settings.into_generator().into_root_schema_for::<T>().to_value())
+		}
+	}
+}
+
+/// Subscription related metadata of a subscription method.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SubscriptionMetadata {
+	notification: &'static str,
+	unsubscribe: &'static str,
+}
+
+impl SubscriptionMetadata {
+	pub(crate) fn new(notification: &'static str, unsubscribe: &'static str) -> Self {
+		Self { notification, unsubscribe }
+	}
+
+	/// Get the name of the method in the notifications of the subscription.
+	pub fn notification(&self) -> &'static str {
+		self.notification
+	}
+
+	/// Get the name of the method to unsubscribe.
+	pub fn unsubscribe(&self) -> &'static str {
+		self.unsubscribe
+	}
+}
+
+/// Metadata of a registered method which is used to describe it, for instance in an OpenRPC document.
+#[derive(Debug, Clone, Default)]
+pub struct MethodMetadata {
+	pub(crate) description: Option<String>,
+	pub(crate) params: Option<Vec<ParamDescriptor>>,
+	pub(crate) params_schema: Option<Arc<Value>>,
+	pub(crate) result_schema: Option<Value>,
+	pub(crate) subscription: Option<SubscriptionMetadata>,
+	pub(crate) hidden: bool,
+	pub(crate) alias_of: Option<&'static str>,
+	pub(crate) deprecated: Option<String>,
+	pub(crate) auth: Option<String>,
+}
+
+impl MethodMetadata {
+	/// Get the description of the method, if configured.
+	pub fn description(&self) -> Option<&str> {
+		self.description.as_deref()
+	}
+
+	/// Get the parameters of the method, if configured.
+	pub fn params(&self) -> Option<&[ParamDescriptor]> {
+		self.params.as_deref()
+	}
+
+	/// Get the JSON Schema of the params of the method, if configured.
+	pub fn params_schema(&self) -> Option<&Value> {
+		self.params_schema.as_deref()
+	}
+
+	/// Get the JSON Schema of the result of the method, if configured.
+	pub fn result_schema(&self) -> Option<&Value> {
+		self.result_schema.as_ref()
+	}
+
+	/// Get the subscription metadata if the method is a subscription.
+	pub fn subscription(&self) -> Option<SubscriptionMetadata> {
+		self.subscription
+	}
+
+	/// Returns whether the method is hidden from the generated OpenRPC document.
+	pub fn is_hidden(&self) -> bool {
+		self.hidden
+	}
+
+	/// Get the name of the method that this method is an alias of, if it's an alias.
+	pub fn alias_of(&self) -> Option<&'static str> {
+		self.alias_of
+	}
+
+	/// Get the deprecation note of the method, if it's deprecated.
+	pub fn deprecation(&self) -> Option<&str> {
+		self.deprecated.as_deref()
+	}
+
+	/// Get the authorization that is required to call the method, if any.
+	pub fn auth(&self) -> Option<&str> {
+		self.auth.as_deref()
+	}
+}
+
+/// Information about the API that is included in the generated OpenRPC document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpenRpcInfo {
+	/// The title of the API.
+	pub title: String,
+	/// The version of the API.
+	pub version: String,
+}
+
+impl OpenRpcInfo {
+	/// Create a new [`OpenRpcInfo`].
+	pub fn new(title: impl Into<String>, version: impl Into<String>) -> Self {
+		Self { title: title.into(), version: version.into() }
+	}
+}
+
+/// Describes a method in an OpenRPC document.
+///
+/// # Examples
+///
+/// ```
+/// use jsonrpsee_core::server::{openrpc_document, MethodDescriptor, OpenRpcInfo, ParamDescriptor};
+/// use serde_json::json;
+///
+/// let method = MethodDescriptor::new("say_hello")
+///     .description("Greets the caller")
+///     .param(ParamDescriptor::new("name", json!({ "type": "string" })))
+///     .result(json!({ "type": "string" }));
+///
+/// let doc = openrpc_document(&OpenRpcInfo::new("hello", "1.0.0"), [method]);
+/// assert_eq!(doc["methods"][0]["params"][0]["name"], "name");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct MethodDescriptor {
+	name: String,
+	description: Option<String>,
+	deprecated: bool,
+	params: Vec<ParamDescriptor>,
+	param_structure: Option<&'static str>,
+	result: Value,
+	subscription: Option<(SubscriptionMetadata, Option<Value>)>,
+}
+
+impl MethodDescriptor {
+	/// Create a new method without parameters whose result can be any value.
+	pub fn new(name: impl Into<String>) -> Self {
+		Self {
+			name: name.into(),
+			description: None,
+			deprecated: false,
+			params: Vec::new(),
+			param_structure: None,
+			result: json!({}),
+			subscription: None,
+		}
+	}
+
+	/// Create a new method to unsubscribe, which takes the subscription ID and returns a boolean.
+	pub fn unsubscription(name: impl Into<String>) -> Self {
+		let mut method = Self::new(name)
+			.param(ParamDescriptor::new("subscription", subscription_id_schema()))
+			.result(json!({ "type": "boolean" }));
+		method.param_structure = Some("by-position");
+		method
+	}
+
+	/// Create a descriptor of an alias of this method.
+	pub fn alias(&self, name: impl Into<String>) -> Self {
+		Self { name: name.into(), ..self.clone() }
+	}
+
+	/// Set the description of the method.
+	pub fn description(mut self, description: impl Into<String>) -> Self {
+		self.description = Some(description.into());
+		self
+	}
+
+	/// Mark the method as deprecated.
+	pub fn deprecated(mut self) -> Self {
+		self.deprecated = true;
+		self
+	}
+
+	/// Append a parameter to the method.
+	pub fn param(mut self, param: ParamDescriptor) -> Self {
+		self.params.push(param);
+		self
+	}
+
+	/// Set the JSON Schema of the result of the method.
+	pub fn result(mut self, schema: Value) -> Self {
+		self.result = schema;
+		self
+	}
+
+	/// Mark the method as a subscription, which returns the subscription ID and whose notifications
+	/// contain items with the given JSON Schema.
+	pub fn subscription(
+		mut self,
+		notification: &'static str,
+		unsubscribe: &'static str,
+		item_schema: Option<Value>,
+	) -> Self {
+		self.subscription = Some((SubscriptionMetadata::new(notification, unsubscribe), item_schema));
+		self.result = subscription_id_schema();
+		self
+	}
+
+	/// Get the name of the method.
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	fn to_json(&self) -> Value {
+		let mut method = Map::new();
+
+		method.insert("name".into(), self.name.clone().into());
+
+		if let Some(description) = &self.description {
+			method.insert("description".into(), description.clone().into());
+		}
+
+		if self.deprecated {
+			method.insert("deprecated".into(), true.into());
+		}
+
+		method.insert("params".into(), self.params.iter().map(ParamDescriptor::to_json).collect());
+
+		if let Some(structure) = self.param_structure {
+			method.insert("paramStructure".into(), structure.into());
+		}
+
+		method.insert("result".into(), json!({ "name": "result", "schema": self.result }));
+
+		if let Some((sub, item_schema)) = &self.subscription {
+			let mut subscription = json!({ "notification": sub.notification, "unsubscribe": sub.unsubscribe });
+			if let Some(schema) = item_schema {
+				subscription["schema"] = schema.clone();
+			}
+			method.insert("x-subscription".into(), subscription);
+		}
+
+		Value::Object(method)
+	}
+}
+
+/// Generate an [OpenRPC](https://spec.open-rpc.org) document that describes the given methods.
+///
+/// The methods are sorted by name.
+pub fn openrpc_document(info: &OpenRpcInfo, methods: impl IntoIterator<Item = MethodDescriptor>) -> Value {
+	let mut methods: Vec<_> = methods.into_iter().collect();
+	methods.sort_by(|a, b| a.name.cmp(&b.name));
+
+	json!({
+		"openrpc": OPENRPC_VERSION,
+		"info": { "title": info.title, "version": info.version },
+		"methods": methods.iter().map(MethodDescriptor::to_json).collect::<Vec<_>>(),
+	})
+}
+
+cfg_schemars! {
+	/// Generates an OpenRPC document where the JSON Schemas of the params and results are derived
+	/// with [`schemars`].
+	///
+	/// The schemas of named types are defined once in `components.schemas` of the document and
+	/// referenced by the methods.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use jsonrpsee_core::server::{MethodDescriptor, OpenRpcGenerator, OpenRpcInfo};
+	///
+	/// let mut generator = OpenRpcGenerator::new();
+	/// let method = MethodDescriptor::new("add")
+	///     .param(generator.param::<Vec<u64>>("values"))
+	///     .result(generator.schema::<u64>());
+	///
+	/// let doc = generator.document(&OpenRpcInfo::new("math", "1.0.0"), [method]);
+	/// assert_eq!(doc["methods"][0]["params"][0]["schema"]["type"], "array");
+	/// ```
+	#[derive(Debug, Clone)]
+	pub struct OpenRpcGenerator {
+		schemas: schemars::SchemaGenerator,
+	}
+
+	impl OpenRpcGenerator {
+		/// Create a new generator.
+		pub fn new() -> Self {
+			let settings = schemars::generate::SchemaSettings::draft2020_12()
+				.with(|s| s.definitions_path = "/components/schemas".into());
+			Self { schemas: settings.into_generator() }
+		}
+
+		/// Get the JSON Schema of `T`.
+		pub fn schema<T: schemars::JsonSchema + ?Sized>(&mut self) -> Value {
+			self.schemas.subschema_for::<T>().to_value()
+		}
+
+		/// Create a new required parameter whose JSON Schema is the schema of `T`.
+		pub fn param<T: schemars::JsonSchema + ?Sized>(&mut self, name: impl Into<String>) -> ParamDescriptor {
+			ParamDescriptor::new(name, self.schema::<T>())
+		}
+
+		/// Generate the OpenRPC document that describes the given methods.
+		pub fn document(mut self, info: &OpenRpcInfo, methods: impl IntoIterator<Item = MethodDescriptor>) -> Value {
+			let mut doc = openrpc_document(info, methods);
+			let schemas = self.schemas.take_definitions(true);
+
+			if !schemas.is_empty() {
+				doc["components"] = json!({ "schemas": schemas });
+			}
+
+			doc
+		}
+	}
+
+	impl Default for OpenRpcGenerator {
+		fn default() -> Self {
+			Self::new()
+		}
+	}
+}
+
+impl Methods {
+	/// Generate an [OpenRPC](https://spec.open-rpc.org) document that describes the registered methods.
+	///
+	/// The parameters of a method are taken from [`Methods::set_method_params`] or otherwise derived from
+	/// the schema attached with [`Methods::set_method_params_schema`]. Subscriptions are described by the
+	/// `x-subscription` extension which contains the names of the notification and unsubscribe methods.
+	/// Methods hidden by [`Methods::hide_method`] are not included.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use jsonrpsee_core::server::{OpenRpcInfo, RpcModule};
+	///
+	/// let mut module = RpcModule::new(());
+	/// module.register_method("say_hello", |_, _, _| "lo").unwrap();
+	///
+	/// let doc = module.openrpc(&OpenRpcInfo::new("hello", "1.0.0"));
+	/// assert_eq!(doc["methods"][0]["name"], "say_hello");
+	/// ```
+	pub fn openrpc(&self, info: &OpenRpcInfo) -> Value {
+		let methods = self
+			.method_names()
+			.filter(|name| !self.method_metadata(name).is_some_and(|m| m.is_hidden()))
+			.map(|name| self.openrpc_method(name));
+
+		openrpc_document(info, methods)
+	}
+
+	fn openrpc_method(&self, name: &'static str) -> MethodDescriptor {
+		let metadata = self.method_metadata(name).cloned().unwrap_or_default();
+
+		let mut method = match self.method(name) {
+			Some(MethodCallback::Unsubscription(_)) => MethodDescriptor::unsubscription(name),
+			Some(MethodCallback::Subscription(_)) => MethodDescriptor::new(name).result(subscription_id_schema()),
+			_ => MethodDescriptor::new(name),
+		};
+
+		if method.param_structure.is_none() {
+			let (params, structure) = if let Some(params) = metadata.params {
+				(params, None)
+			} else if let Some(schema) = metadata.params_schema {
+				params_from_schema(&schema)
+			} else {
+				(Vec::new(), None)
+			};
+			method.params = params;
+			method.param_structure = structure;
+		}
+
+		if let Some(sub) = metadata.subscription {
+			method = method.subscription(sub.notification, sub.unsubscribe, None);
+		}
+
+		if let Some(schema) = metadata.result_schema {
+			method = method.result(schema);
+		}
+
+		if let Some(description) = metadata.description {
+			method = method.description(description);
+		}
+
+		if metadata.deprecated.is_some() {
+			method = method.deprecated();
+		}
+
+		method
+	}
+}
+
+/// The JSON Schema of a subscription ID.
+fn subscription_id_schema() -> Value {
+	json!({ "type": ["string", "integer"] })
+}
+
+/// Derive the parameters from the schema of the params.
+fn params_from_schema(schema: &Value) -> (Vec<ParamDescriptor>, Option<&'static str>) {
+	if let Some(properties) = schema.get("properties").and_then(Value::as_object) {
+		let required: Vec<_> =
+			schema.get("required").and_then(Value::as_array).into_iter().flatten().filter_map(Value::as_str).collect();
+
+		let params = properties
+			.iter()
+			.map(|(name, schema)| {
+				let param = ParamDescriptor::new(name.clone(), schema.clone());
+				if required.contains(&name.as_str()) {
+					param
+				} else {
+					param.optional()
+				}
+			})
+			.collect();
+
+		return (params, Some("by-name"));
+	}
+
+	let items = schema.get("prefixItems").or_else(|| schema.get("items")).and_then(Value::as_array);
+
+	if let Some(items) = items {
+		let min_items = schema.get("minItems").and_then(Value::as_u64).unwrap_or(items.len() as u64);
+
+		let params = items
+			.iter()
+			.enumerate()
+			.map(|(idx, schema)| {
+				let param = ParamDescriptor::new(format!("param{idx}"), schema.clone());
+				if (idx as u64) < min_items {
+					param
+				} else {
+					param.optional()
+				}
+			})
+			.collect();
+
+		return (params, Some("by-position"));
+	}
+
+	(Vec::new(), None)
+}
@@ -0,0 +1,103 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any
+// person obtaining a copy of this software and associated
+// documentation files (the "Software"), to deal in the
+// Software without restriction, including without
+// limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software
+// is furnished to do so, subject to the following
+// conditions:
+//
+// The above copyright notice and this permission notice
+// shall be included in all copies or substantial portions
+// of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF
+// ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED
+// TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A
+// PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT
+// SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY
+// CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+// IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::any::Any;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use jsonrpsee_types::error::{INTERNAL_ERROR_CODE, INTERNAL_ERROR_MSG};
+use jsonrpsee_types::{ErrorObject, ErrorObjectOwned};
+
+use crate::server::{ConnectionId, LOG_TARGET};
+
+/// A panic of a method handler or a subscription task.
+#[derive(Debug, Clone)]
+pub struct MethodPanic {
+	/// The connection ID.
+	pub conn_id: ConnectionId,
+	/// The name of the method.
+	pub method: String,
+	/// The ID which is included in the error response to correlate it with the panic.
+	pub correlation_id: u64,
+	/// The message of the panic, if the payload is a string.
+	pub message: Option<String>,
+}
+
+/// Hook invoked when a method handler or a subscription task panics.
+///
+/// The server inserts it into the extensions of the calls, such that it is invoked
+/// for the panics that are caught while the call is processed.
+#[derive(Clone)]
+pub struct PanicHook(Arc<dyn Fn(&MethodPanic) + Send + Sync>);
+
+impl PanicHook {
+	/// Create a new hook.
+	pub fn new(f: impl Fn(&MethodPanic) + Send + Sync + 'static) -> Self {
+		Self(Arc::new(f))
+	}
+}
+
+impl fmt::Debug for PanicHook {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str("PanicHook")
+	}
+}
+
+/// Report a caught panic to the `hook`, if any, and return the `Internal error` which
+/// contains the correlation ID of the panic as `{"correlationId": u64}`.
+pub fn report_panic(
+	conn_id: ConnectionId,
+	method: &str,
+	payload: Box<dyn Any + Send>,
+	hook: Option<&PanicHook>,
+) -> ErrorObjectOwned {
+	static NEXT_CORRELATION_ID: AtomicU64 = AtomicU64::new(1);
+
+	let correlation_id = NEXT_CORRELATION_ID.fetch_add(1, Ordering::Relaxed);
+	let message = match payload.downcast::<String>() {
+		Ok(msg) => Some(*msg),
+		Err(payload) => payload.downcast::<&'static str>().ok().map(|msg| (*msg).to_owned()),
+	};
+
+	tracing::error!(
+		target: LOG_TARGET,
+		"Method `{}` panicked (correlation id {}): {}",
+		method,
+		correlation_id,
+		message.as_deref().unwrap_or("Box<dyn Any>")
+	);
+
+	if let Some(hook) = hook {
+		(hook.0)(&MethodPanic { conn_id, method: method.to_owned(), correlation_id, message });
+	}
+
+	ErrorObject::owned(
+		INTERNAL_ERROR_CODE,
+		INTERNAL_ERROR_MSG,
+		Some(serde_json::json!({ "correlationId": correlation_id })),
+	)
+}
@@ -0,0 +1,517 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any
+// person obtaining a copy of this software and associated
+// documentation files (the "Software"), to deal in the
+// Software without restriction, including without
+// limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software
+// is furnished to do so, subject to the following
+// conditions:
+//
+// The above copyright notice and this permission notice
+// shall be included in all copies or substantial portions
+// of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF
+// ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED
+// TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A
+// PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT
+// SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY
+// CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+// IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Validation of the params of calls against the JSON Schema of a method.
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::RegisterMethodError;
+
+/// Keywords of JSON Schema which are validated.
+const ASSERTIONS: &[&str] = &[
+	"$ref",
+	"type",
+	"enum",
+	"const",
+	"properties",
+	"required",
+	"additionalProperties",
+	"items",
+	"prefixItems",
+	"additionalItems",
+	"minItems",
+	"maxItems",
+	"uniqueItems",
+	"minimum",
+	"maximum",
+	"exclusiveMinimum",
+	"exclusiveMaximum",
+	"minLength",
+	"maxLength",
+	"allOf",
+	"anyOf",
+	"oneOf",
+	"not",
+];
+
+/// Keywords of JSON Schema which only annotate or hold subschemas for `$ref`, they don't restrict the values.
+/// Note that `format` is an annotation, as by default in JSON Schema 2019-09 and later.
+const ANNOTATIONS: &[&str] = &[
+	"$schema",
+	"$id",
+	"$comment",
+	"$defs",
+	"definitions",
+	"title",
+	"description",
+	"default",
+	"examples",
+	"deprecated",
+	"readOnly",
+	"writeOnly",
+	"format",
+];
+
+/// Max number of `$ref`s followed to validate a value, which prevents that recursive references loop forever.
+const MAX_REF_DEPTH: usize = 64;
+
+/// A violation of the JSON Schema of the params.
+///
+/// A list of these is included as `data` of the `Invalid params` error.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SchemaViolation {
+	/// JSON pointer to the offending value in the params, for instance `/0/amount`.
+	pub path: String,
+	/// Description of the violation.
+	pub message: String,
+}
+
+/// Error when the JSON Schema of the params of a method couldn't be attached.
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum ParamsSchemaError {
+	/// The method couldn't be found.
+	#[error(transparent)]
+	Register(#[from] RegisterMethodError),
+	/// The schema uses a keyword which isn't supported, which would otherwise be ignored by the validation.
+	#[error("Unsupported JSON Schema keyword `{keyword}` at `{path}`")]
+	UnsupportedKeyword {
+		/// The keyword.
+		keyword: String,
+		/// JSON pointer to the keyword in the schema.
+		path: String,
+	},
+	/// The schema has a `$ref` which doesn't point to a subschema of the schema itself.
+	#[error("Unresolved JSON Schema reference `{reference}` at `{path}`")]
+	UnresolvedReference {
+		/// The reference.
+		reference: String,
+		/// JSON pointer to the reference in the schema.
+		path: String,
+	},
+}
+
+/// Checks that the validation supports every keyword of `schema`, such that no value is accepted
+/// because a keyword has been ignored.
+pub(crate) fn check_schema(schema: &Value) -> Result<(), ParamsSchemaError> {
+	check_at(schema, schema, "")
+}
+
+fn check_at(root: &Value, schema: &Value, path: &str) -> Result<(), ParamsSchemaError> {
+	let Value::Object(schema) = schema else { return Ok(()) };
+
+	for (keyword, value) in schema {
+		let keyword_path = format!("{path}/{}", escape(keyword));
+
+		if !ASSERTIONS.contains(&keyword.as_str()) && !ANNOTATIONS.contains(&keyword.as_str()) {
+			return Err(ParamsSchemaError::UnsupportedKeyword { keyword: keyword.clone(), path: keyword_path });
+		}
+
+		match (keyword.as_str(), value) {
+			("$ref", Value::String(reference)) if resolve(root, reference).is_none() => {
+				return Err(ParamsSchemaError::UnresolvedReference {
+					reference: reference.clone(),
+					path: keyword_path,
+				});
+			}
+			("properties" | "$defs" | "definitions", Value::Object(subschemas)) => {
+				for (key, subschema) in subschemas {
+					check_at(root, subschema, &format!("{keyword_path}/{}", escape(key)))?;
+				}
+			}
+			("items" | "prefixItems" | "allOf" | "anyOf" | "oneOf", Value::Array(subschemas)) => {
+				for (idx, subschema) in subschemas.iter().enumerate() {
+					check_at(root, subschema, &format!("{keyword_path}/{idx}"))?;
+				}
+			}
+			("items" | "additionalItems" | "additionalProperties" | "not", subschema) => {
+				check_at(root, subschema, &keyword_path)?;
+			}
+			_ => (),
+		}
+	}
+
+	Ok(())
+}
+
+/// Resolves a reference to a subschema of `root`, for instance `#/$defs/Transfer`.
+fn resolve<'a>(root: &'a Value, reference: &str) -> Option<&'a Value> {
+	root.pointer(reference.strip_prefix('#')?)
+}
+
+fn escape(key: &str) -> String {
+	key.replace('~', "~0").replace('/', "~1")
+}
+
+/// Validate `value` against the JSON Schema `schema`, which has been accepted by
+/// [`Methods::set_method_params_schema`](crate::server::Methods::set_method_params_schema).
+///
+/// Returns an empty list if `value` conforms to the schema.
+pub fn validate_params(schema: &Value, value: &Value) -> Vec<SchemaViolation> {
+	let mut violations = Vec::new();
+	Validator { root: schema, depth: 0 }.validate_at(schema, value, "", &mut violations);
+	violations
+}
+
+#[derive(Clone, Copy)]
+struct Validator<'a> {
+	root: &'a Value,
+	depth: usize,
+}
+
+impl<'a> Validator<'a> {
+	fn matches(self, schema: &'a Value, value: &Value) -> bool {
+		let mut violations = Vec::new();
+		self.validate_at(schema, value, "", &mut violations);
+		violations.is_empty()
+	}
+
+	fn validate_at(self, schema: &'a Value, value: &Value, path: &str, violations: &mut Vec<SchemaViolation>) {
+		let violation = |message: String| SchemaViolation { path: path.to_owned(), message };
+
+		let schema = match schema {
+			Value::Bool(true) => return,
+			Value::Bool(false) => return violations.push(violation("no value is allowed".to_owned())),
+			Value::Object(schema) => schema,
+			_ => return,
+		};
+
+		if let Some(Value::String(reference)) = schema.get("$ref") {
+			match resolve(self.root, reference) {
+				Some(_) if self.depth >= MAX_REF_DEPTH => {
+					return violations.push(violation("too deeply nested references".to_owned()));
+				}
+				Some(target) => {
+					Validator { root: self.root, depth: self.depth + 1 }.validate_at(target, value, path, violations)
+				}
+				None => return violations.push(violation(format!("unresolved reference `{reference}`"))),
+			}
+		}
+
+		let mut violation = |message: String| violations.push(SchemaViolation { path: path.to_owned(), message });
+
+		if let Some(ty) = schema.get("type") {
+			let allowed: Vec<&str> = match ty {
+				Value::String(ty) => vec![ty.as_str()],
+				Value::Array(tys) => tys.iter().filter_map(Value::as_str).collect(),
+				_ => Vec::new(),
+			};
+
+			if !allowed.is_empty() && !allowed.iter().any(|ty| is_type(value, ty)) {
+				return violation(format!("expected {}, got {}", allowed.join(" or "), type_name(value)));
+			}
+		}
+
+		if let Some(Value::Array(variants)) = schema.get("enum") {
+			if !variants.contains(value) {
+				violation(format!("expected one of {}", Value::Array(variants.clone())));
+			}
+		}
+
+		if let Some(expected) = schema.get("const") {
+			if expected != value {
+				violation(format!("expected {expected}"));
+			}
+		}
+
+		match value {
+			Value::Number(n) => {
+				let n = n.as_f64().unwrap_or_default();
+				let bound = |key: &str| schema.get(key).and_then(Value::as_f64);
+
+				if let Some(min) = bound("minimum").filter(|min| n < *min) {
+					violation(format!("expected a number >= {min}"));
+				}
+				if let Some(max) = bound("maximum").filter(|max| n > *max) {
+					violation(format!("expected a number <= {max}"));
+				}
+				if let Some(min) = bound("exclusiveMinimum").filter(|min| n <= *min) {
+					violation(format!("expected a number > {min}"));
+				}
+				if let Some(max) = bound("exclusiveMaximum").filter(|max| n >= *max) {
+					violation(format!("expected a number < {max}"));
+				}
+			}
+			Value::String(s) => {
+				let len = s.chars().count() as u64;
+
+				if let Some(min) = schema.get("minLength").and_then(Value::as_u64).filter(|min| len < *min) {
+					violation(format!("expected at least {min} characters"));
+				}
+				if let Some(max) = schema.get("maxLength").and_then(Value::as_u64).filter(|max| len > *max) {
+					violation(format!("expected at most {max} characters"));
+				}
+			}
+			Value::Array(items) => self.validate_array(schema, items, path, violations),
+			Value::Object(object) => self.validate_object(schema, object, path, violations),
+			_ => (),
+		}
+
+		self.validate_combinators(schema, value, path, violations);
+	}
+
+	fn validate_array(
+		self,
+		schema: &'a Map<String, Value>,
+		items: &[Value],
+		path: &str,
+		violations: &mut Vec<SchemaViolation>,
+	) {
+		let len = items.len() as u64;
+		let mut violation = |message: String| violations.push(SchemaViolation { path: path.to_owned(), message });
+
+		if let Some(min) = schema.get("minItems").and_then(Value::as_u64).filter(|min| len < *min) {
+			violation(format!("expected at least {min} items"));
+		}
+		if let Some(max) = schema.get("maxItems").and_then(Value::as_u64).filter(|max| len > *max) {
+			violation(format!("expected at most {max} items"));
+		}
+		if schema.get("uniqueItems") == Some(&Value::Bool(true))
+			&& items.iter().enumerate().any(|(idx, item)| items[..idx].contains(item))
+		{
+			violation("expected unique items".to_owned());
+		}
+
+		// `items` as an array is the tuple form of older drafts, `prefixItems` is the one of draft 2020-12.
+		let (prefix, rest) = match (schema.get("prefixItems"), schema.get("items")) {
+			(Some(Value::Array(prefix)), rest) => (prefix.as_slice(), rest),
+			(None, Some(Value::Array(prefix))) => (prefix.as_slice(), schema.get("additionalItems")),
+			(_, rest) => (&[][..], rest),
+		};
+
+		for (idx, item) in items.iter().enumerate() {
+			let item_path = format!("{path}/{idx}");
+
+			match prefix.get(idx) {
+				Some(item_schema) => self.validate_at(item_schema, item, &item_path, violations),
+				None => {
+					if let Some(item_schema) = rest {
+						self.validate_at(item_schema, item, &item_path, violations);
+					}
+				}
+			}
+		}
+	}
+
+	fn validate_object(
+		self,
+		schema: &'a Map<String, Value>,
+		object: &Map<String, Value>,
+		path: &str,
+		violations: &mut Vec<SchemaViolation>,
+	) {
+		if let Some(Value::Array(required)) = schema.get("required") {
+			for key in required.iter().filter_map(Value::as_str) {
+				if !object.contains_key(key) {
+					violations.push(SchemaViolation {
+						path: path.to_owned(),
+						message: format!("missing required property `{key}`"),
+					});
+				}
+			}
+		}
+
+		let properties = schema.get("properties").and_then(Value::as_object);
+
+		for (key, value) in object {
+			let prop_path = format!("{path}/{}", escape(key));
+
+			match properties.and_then(|p| p.get(key)) {
+				Some(prop_schema) => self.validate_at(prop_schema, value, &prop_path, violations),
+				None => match schema.get("additionalProperties") {
+					Some(Value::Bool(false)) => violations
+						.push(SchemaViolation { path: prop_path, message: format!("unexpected property `{key}`") }),
+					Some(additional) => self.validate_at(additional, value, &prop_path, violations),
+					None => (),
+				},
+			}
+		}
+	}
+
+	fn validate_combinators(
+		self,
+		schema: &'a Map<String, Value>,
+		value: &Value,
+		path: &str,
+		violations: &mut Vec<SchemaViolation>,
+	) {
+		let subschemas = |key: &str| schema.get(key).and_then(Value::as_array).map(Vec::as_slice);
+
+		if let Some(all) = subschemas("allOf") {
+			for s in all {
+				self.validate_at(s, value, path, violations);
+			}
+		}
+
+		if let Some(any) = subschemas("anyOf") {
+			if !any.iter().any(|s| self.matches(s, value)) {
+				violations.push(SchemaViolation {
+					path: path.to_owned(),
+					message: "expected to match any of the schemas in `anyOf`".to_owned(),
+				});
+			}
+		}
+
+		if let Some(one) = subschemas("oneOf") {
+			let n = one.iter().filter(|s| self.matches(s, value)).count();
+			if n != 1 {
+				violations.push(SchemaViolation {
+					path: path.to_owned(),
+					message: format!("expected to match exactly one of the schemas in `oneOf`, matched {n}"),
+				});
+			}
+		}
+
+		if let Some(not) = schema.get("not") {
+			if self.matches(not, value) {
+				violations.push(SchemaViolation {
+					path: path.to_owned(),
+					message: "expected to not match the schema in `not`".to_owned(),
+				});
+			}
+		}
+	}
+}
+
+fn is_type(value: &Value, ty: &str) -> bool {
+	match ty {
+		"null" => value.is_null(),
+		"boolean" => value.is_boolean(),
+		"object" => value.is_object(),
+		"array" => value.is_array(),
+		"string" => value.is_string(),
+		"number" => value.is_number(),
+		"integer" => value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|n| n.fract() == 0.0),
+		_ => false,
+	}
+}
+
+fn type_name(value: &Value) -> &'static str {
+	match value {
+		Value::Null => "null",
+		Value::Bool(_) => "boolean",
+		Value::Number(_) => "number",
+		Value::String(_) => "string",
+		Value::Array(_) => "array",
+		Value::Object(_) => "object",
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{check_schema, validate_params as validate, ParamsSchemaError, SchemaViolation};
+	use serde_json::json;
+
+	fn paths(violations: Vec<SchemaViolation>) -> Vec<String> {
+		violations.into_iter().map(|v| v.path).collect()
+	}
+
+	#[test]
+	fn should_accept_conforming_values() {
+		let schema = json!({
+			"type": "array",
+			"prefixItems": [
+				{ "type": "string", "minLength": 1 },
+				{ "type": "object", "properties": { "amount": { "type": "integer", "minimum": 0 } }, "required": ["amount"] }
+			],
+			"maxItems": 2
+		});
+		assert!(validate(&schema, &json!(["alice", { "amount": 10 }])).is_empty());
+		assert!(validate(&json!(true), &json!(null)).is_empty());
+	}
+
+	#[test]
+	fn should_report_the_path_of_violations() {
+		let schema = json!({
+			"type": "array",
+			"items": [
+				{ "type": "string" },
+				{
+					"type": "object",
+					"properties": { "amount": { "type": "integer", "minimum": 0 } },
+					"required": ["to"],
+					"additionalProperties": false
+				}
+			]
+		});
+		let violations = validate(&schema, &json!([1, { "amount": -1, "memo": "x" }]));
+		assert_eq!(paths(violations), vec!["/0", "/1", "/1/amount", "/1/memo"]);
+	}
+
+	#[test]
+	fn should_validate_combinators() {
+		let schema = json!({ "oneOf": [{ "type": "integer" }, { "type": "number" }] });
+		assert!(validate(&schema, &json!(1.5)).is_empty());
+		assert_eq!(validate(&schema, &json!(1)).len(), 1);
+
+		let schema = json!({ "anyOf": [{ "enum": ["a", "b"] }, { "const": 1 }], "not": { "const": "b" } });
+		assert!(validate(&schema, &json!("a")).is_empty());
+		assert!(validate(&schema, &json!(1)).is_empty());
+		assert_eq!(validate(&schema, &json!("b")).len(), 1);
+		assert_eq!(validate(&schema, &json!("c")).len(), 1);
+	}
+
+	#[test]
+	fn should_follow_references() {
+		// As generated by `schemars` for `(Transfer,)` with a recursive `memo`.
+		let schema = json!({
+			"$schema": "https://json-schema.org/draft/2020-12/schema",
+			"type": "array",
+			"prefixItems": [{ "$ref": "#/$defs/Transfer" }],
+			"minItems": 1,
+			"maxItems": 1,
+			"$defs": {
+				"Transfer": {
+					"type": "object",
+					"properties": {
+						"amount": { "type": "integer", "format": "uint64", "minimum": 0 },
+						"next": { "anyOf": [{ "$ref": "#/$defs/Transfer" }, { "type": "null" }] }
+					},
+					"required": ["amount"]
+				}
+			}
+		});
+		check_schema(&schema).unwrap();
+
+		assert!(validate(&schema, &json!([{ "amount": 1, "next": { "amount": 2 } }])).is_empty());
+		assert_eq!(paths(validate(&schema, &json!([{ "amount": -1 }]))), vec!["/0/amount"]);
+		assert_eq!(paths(validate(&schema, &json!([{ "amount": 1, "next": {} }]))), vec!["/0/next"]);
+	}
+
+	#[test]
+	fn should_reject_unsupported_keywords() {
+		let err =
+			check_schema(&json!({ "properties": { "name": { "type": "string", "pattern": "^a" } } })).unwrap_err();
+		assert!(
+			matches!(err, ParamsSchemaError::UnsupportedKeyword { keyword, path } if keyword == "pattern" && path == "/properties/name/pattern")
+		);
+
+		let err = check_schema(&json!({ "items": { "$ref": "#/components/schemas/Transfer" } })).unwrap_err();
+		assert!(matches!(err, ParamsSchemaError::UnresolvedReference { path, .. } if path == "/items/$ref"));
+
+		check_schema(&json!({ "type": "array", "items": { "type": "integer" }, "uniqueItems": true })).unwrap();
+	}
+}
@@ -29,26 +29,37 @@ use std::fmt::{self, Debug};
 use std::future::Future;
 use std::ops::{Deref, DerefMut};
 use std::sync::Arc;
+use std::time::Duration;
 
 use crate::error::RegisterMethodError;
 use crate::id_providers::RandomIntegerIdProvider;
-use crate::server::helpers::MethodSink;
-use crate::server::method_response::MethodResponse;
+use crate::server::context::SwappableContext;
+use crate::server::cpu_pool::CpuPool;
+use crate::server::helpers::{BufferPool, ConnectionExtensions, MethodSink, SinkMessage};
+use crate::server::method_response::{MethodResponse, StreamedResponseSupport};
+use crate::server::openrpc::{MethodMetadata, ParamDescriptor, SubscriptionMetadata};
+use crate::server::panic::{report_panic, PanicHook};
+use crate::server::params_schema::{check_schema, ParamsSchemaError};
 use crate::server::subscription::{
-	sub_message_to_json, BoundedSubscriptions, IntoSubscriptionCloseResponse, PendingSubscriptionSink,
-	SubNotifResultOrError, Subscribers, Subscription, SubscriptionCloseResponse, SubscriptionKey, SubscriptionPermit,
-	SubscriptionState,
+	sub_message_to_json, BackpressurePolicy, BoundedSubscriptions, IntoSubscriptionCloseResponse,
+	PendingSubscriptionSink, SubNotifResultOrError, Subscribers, Subscription, SubscriptionCloseResponse,
+	SubscriptionKey, SubscriptionMessage, SubscriptionPermit, SubscriptionState,
 };
-use crate::server::{ResponsePayload, LOG_TARGET};
+use crate::server::{ResponsePayload, SubscriptionStats, LOG_TARGET};
 use crate::traits::ToRpcParams;
-use futures_util::{future::BoxFuture, FutureExt};
+use crate::{BoxError, JsonRawValue, SubscriptionResult};
+use futures_util::{
+	future::{self, BoxFuture},
+	FutureExt, Stream, StreamExt,
+};
 use http::Extensions;
-use jsonrpsee_types::error::{ErrorCode, ErrorObject};
+use jsonrpsee_types::error::{reject_method_timeout, ErrorCode, ErrorObject};
 use jsonrpsee_types::{
 	ErrorObjectOwned, Id, Params, Request, Response, ResponseSuccess, SubscriptionId as RpcSubscriptionId,
 };
 use rustc_hash::FxHashMap;
 use serde::de::DeserializeOwned;
+use serde::Serialize;
 use tokio::sync::{mpsc, oneshot};
 
 use super::IntoResponse;
@@ -94,8 +105,8 @@ pub type MaxResponseSize = usize;
 /// Raw response from an RPC
 /// A tuple containing:
 ///   - Call result as a `String`,
-///   - a [`mpsc::UnboundedReceiver<String>`] to receive future subscription results
-pub type RawRpcResponse = (String, mpsc::Receiver<String>);
+///   - a [`mpsc::Receiver<SinkMessage>`] to receive future subscription results
+pub type RawRpcResponse = (String, mpsc::Receiver<SinkMessage>);
 
 /// The error that can occur when [`Methods::call`] or [`Methods::subscribe`] is invoked.
 #[derive(thiserror::Error, Debug)]
@@ -209,10 +220,93 @@ impl Debug for MethodCallback {
 	}
 }
 
+/// Configuration of a registered method which overrides the server defaults.
+#[derive(Default, Debug, Copy, Clone)]
+pub struct MethodConfig {
+	timeout: Option<Duration>,
+	max_request_size: Option<u32>,
+	max_response_size: Option<u32>,
+	backpressure: Option<BackpressurePolicy>,
+	coalescing_window: Option<Duration>,
+	heartbeat_interval: Option<Duration>,
+	priority: Option<ResponsePriority>,
+	max_subscriptions_per_connection: Option<u32>,
+}
+
+impl MethodConfig {
+	/// Get the max execution time of the method, if configured.
+	///
+	/// It's ignored by synchronous methods, which never time out.
+	pub fn timeout(&self) -> Option<Duration> {
+		self.timeout
+	}
+
+	/// Get the max request size in bytes of the method, if configured.
+	pub fn max_request_size(&self) -> Option<u32> {
+		self.max_request_size
+	}
+
+	/// Get the max response size in bytes of the method, if configured.
+	pub fn max_response_size(&self) -> Option<u32> {
+		self.max_response_size
+	}
+
+	/// Get the backpressure policy of the subscription, if configured.
+	pub fn backpressure(&self) -> Option<BackpressurePolicy> {
+		self.backpressure
+	}
+
+	/// Get the coalescing window of the subscription, if configured.
+	pub fn coalescing_window(&self) -> Option<Duration> {
+		self.coalescing_window
+	}
+
+	/// Get the heartbeat interval of the subscription, if configured.
+	pub fn heartbeat_interval(&self) -> Option<Duration> {
+		self.heartbeat_interval
+	}
+
+	/// Get the priority of the responses of the method, if configured.
+	pub fn response_priority(&self) -> Option<ResponsePriority> {
+		self.priority
+	}
+
+	/// Get the max number of subscriptions of the subscribe method per connection, if configured.
+	pub fn max_subscriptions_per_connection(&self) -> Option<u32> {
+		self.max_subscriptions_per_connection
+	}
+}
+
+/// Priority class of the responses of a method which decides the order in which the
+/// responses of a connection are sent once its send buffer is full.
+///
+/// The responses of the methods with a higher priority are sent before the responses
+/// of the methods with a lower priority, such that for instance health checks and
+/// unsubscribes are still answered quickly when a connection is busy with bulk queries.
+///
+/// By itself this only orders the send queue of the connection and the calls are dispatched
+/// in the order that they're received. The server's `PriorityAdmissionLayer` middleware
+/// also admits the calls in the order of their priority once the server is saturated.
+///
+/// Unsubscribe methods have [`ResponsePriority::High`] and all other methods have
+/// [`ResponsePriority::Normal`] unless configured otherwise.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ResponsePriority {
+	/// Bulk queries whose responses may be delayed.
+	Low,
+	/// The default priority.
+	#[default]
+	Normal,
+	/// Methods that should be answered even if the connection is busy.
+	High,
+}
+
 /// Reference-counted, clone-on-write collection of synchronous and asynchronous methods.
 #[derive(Default, Debug, Clone)]
 pub struct Methods {
 	callbacks: Arc<FxHashMap<&'static str, MethodCallback>>,
+	configs: Arc<FxHashMap<&'static str, MethodConfig>>,
+	metadata: Arc<FxHashMap<&'static str, MethodMetadata>>,
 	extensions: Extensions,
 }
 
@@ -249,6 +343,25 @@ impl Methods {
 		Arc::make_mut(&mut self.callbacks)
 	}
 
+	/// Helper for obtaining a mut ref to the config of a registered method.
+	fn mut_config(&mut self, method_name: &str) -> Result<&mut MethodConfig, RegisterMethodError> {
+		let Some((name, _)) = self.callbacks.get_key_value(method_name) else {
+			return Err(RegisterMethodError::MethodNotFound(method_name.into()));
+		};
+		let name = *name;
+
+		Ok(Arc::make_mut(&mut self.configs).entry(name).or_default())
+	}
+
+	fn mut_metadata(&mut self, method_name: &str) -> Result<&mut MethodMetadata, RegisterMethodError> {
+		let Some((name, _)) = self.callbacks.get_key_value(method_name) else {
+			return Err(RegisterMethodError::MethodNotFound(method_name.into()));
+		};
+		let name = *name;
+
+		Ok(Arc::make_mut(&mut self.metadata).entry(name).or_default())
+	}
+
 	/// Merge two [`Methods`]'s by adding all [`MethodCallback`]s from `other` into `self`.
 	/// Fails if any of the methods in `other` is present already.
 	pub fn merge(&mut self, other: impl Into<Methods>) -> Result<(), RegisterMethodError> {
@@ -264,6 +377,18 @@ impl Methods {
 			callbacks.insert(name, callback);
 		}
 
+		let configs = Arc::make_mut(&mut self.configs);
+
+		for (name, config) in Arc::make_mut(&mut other.configs).drain() {
+			configs.insert(name, config);
+		}
+
+		let metadata = Arc::make_mut(&mut self.metadata);
+
+		for (name, meta) in Arc::make_mut(&mut other.metadata).drain() {
+			metadata.insert(name, meta);
+		}
+
 		Ok(())
 	}
 
@@ -278,6 +403,329 @@ impl Methods {
 		self.callbacks.get_key_value(method_name).map(|(k, v)| (*k, v))
 	}
 
+	/// Returns the configuration of the method, if anything has been configured for it.
+	pub fn method_config(&self, method_name: &str) -> Option<&MethodConfig> {
+		self.configs.get(method_name)
+	}
+
+	/// Returns an `Iterator` with all methods that have a custom configuration.
+	pub fn method_configs(&self) -> impl Iterator<Item = (&'static str, &MethodConfig)> + '_ {
+		self.configs.iter().map(|(k, v)| (*k, v))
+	}
+
+	/// Returns the [`ResponsePriority`] of the method which is the configured priority
+	/// or else the default priority of the method.
+	pub fn response_priority(&self, method_name: &str) -> ResponsePriority {
+		match self.method_config(method_name).and_then(|c| c.response_priority()) {
+			Some(priority) => priority,
+			None if matches!(self.method(method_name), Some(MethodCallback::Unsubscription(_))) => {
+				ResponsePriority::High
+			}
+			None => ResponsePriority::Normal,
+		}
+	}
+
+	/// Configure the max execution time of a registered method which overrides
+	/// the default timeout of the server.
+	///
+	/// When the timeout expires the future of the method call is dropped and the
+	/// call is answered with a [`reject_method_timeout`] error.
+	///
+	/// This only applies to asynchronous methods (including blocking methods) because
+	/// synchronous methods are executed to completion, subscriptions are not affected either.
+	/// Note that a blocking method keeps running on its thread after the timeout has expired,
+	/// only the response is discarded.
+	///
+	/// Fails if the method has not been registered.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use std::time::Duration;
+	/// use jsonrpsee_core::server::RpcModule;
+	///
+	/// let mut module = RpcModule::new(());
+	/// module.register_async_method("slow", |_, _, _| async { "done" }).unwrap();
+	/// module.set_method_timeout("slow", Duration::from_secs(10)).unwrap();
+	/// ```
+	pub fn set_method_timeout(&mut self, method_name: &str, timeout: Duration) -> Result<(), RegisterMethodError> {
+		self.mut_config(method_name)?.timeout = Some(timeout);
+		Ok(())
+	}
+
+	/// Configure the max request size in bytes of a registered method which overrides
+	/// the `max_request_body_size` of the server.
+	///
+	/// This makes it possible to accept big requests for a few methods without raising
+	/// the limit for everything else. The limit applies to single calls and notifications
+	/// because batch requests are always subject to the `max_request_body_size` of the server.
+	///
+	/// The transport still reads messages up to the biggest limit of the methods, but a message
+	/// which exceeds the limit of the server is rejected before it's parsed unless it's a call
+	/// of a method with a bigger limit, of which only the method and the id are read.
+	///
+	/// Fails if the method has not been registered.
+	pub fn set_method_max_request_size(&mut self, method_name: &str, size: u32) -> Result<(), RegisterMethodError> {
+		self.mut_config(method_name)?.max_request_size = Some(size);
+		Ok(())
+	}
+
+	/// Configure the max response size in bytes of a registered method which overrides
+	/// the `max_response_body_size` of the server.
+	///
+	/// The limit may be higher than the limit of the server, but it never exceeds the limit that a
+	/// WebSocket client advertised for its connection.
+	///
+	/// If the response of a batch entry exceeds the limit of the whole batch
+	/// the batch is still rejected.
+	///
+	/// Fails if the method has not been registered.
+	pub fn set_method_max_response_size(&mut self, method_name: &str, size: u32) -> Result<(), RegisterMethodError> {
+		self.mut_config(method_name)?.max_response_size = Some(size);
+		Ok(())
+	}
+
+	/// Configure the [`ResponsePriority`] of a registered method, which orders its responses
+	/// in the send queue of the connection and, with the server's `PriorityAdmissionLayer`,
+	/// the order in which the waiting calls are admitted.
+	///
+	/// Fails if the method has not been registered.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use jsonrpsee_core::server::{ResponsePriority, RpcModule};
+	///
+	/// let mut module = RpcModule::new(());
+	/// module.register_method("health", |_, _, _| "ok").unwrap();
+	/// module.set_response_priority("health", ResponsePriority::High).unwrap();
+	/// ```
+	pub fn set_response_priority(
+		&mut self,
+		method_name: &str,
+		priority: ResponsePriority,
+	) -> Result<(), RegisterMethodError> {
+		self.mut_config(method_name)?.priority = Some(priority);
+		Ok(())
+	}
+
+	/// Configure what happens when the send buffer of the connection is full and
+	/// a notification is sent on a subscription, see [`BackpressurePolicy`] for the options.
+	///
+	/// The policy is configured on the subscribe method and has no effect on other methods.
+	///
+	/// Fails if the method has not been registered.
+	pub fn set_subscription_backpressure(
+		&mut self,
+		subscribe_method_name: &str,
+		policy: BackpressurePolicy,
+	) -> Result<(), RegisterMethodError> {
+		self.mut_config(subscribe_method_name)?.backpressure = Some(policy);
+		Ok(())
+	}
+
+	/// Configure a window during which the notifications of a subscription are merged into one message
+	/// which is a JSON array of notifications, see [`SubscriptionSink::send`](crate::server::SubscriptionSink::send).
+	///
+	/// This reduces the overhead of subscriptions that send out many small notifications
+	/// at the cost of delaying the notifications for at most `window`. The notifications are sent out
+	/// before the window has elapsed once [`MAX_COALESCED_NOTIFICATIONS`](crate::server::MAX_COALESCED_NOTIFICATIONS)
+	/// notifications are buffered.
+	///
+	/// The window is configured on the subscribe method and has no effect on other methods.
+	///
+	/// Fails if the method has not been registered.
+	pub fn set_subscription_coalescing_window(
+		&mut self,
+		subscribe_method_name: &str,
+		window: Duration,
+	) -> Result<(), RegisterMethodError> {
+		self.mut_config(subscribe_method_name)?.coalescing_window = Some(window);
+		Ok(())
+	}
+
+	/// Configure a heartbeat for a subscription which is a notification that is sent out if no
+	/// other notification has been sent on the subscription during the last `interval`.
+	///
+	/// This lets the clients distinguish a quiet subscription from a broken connection.
+	/// The heartbeat notification has the following format:
+	///
+	/// ```json
+	/// {"jsonrpc":"2.0","method":"<method>","params":{"subscription":"<subscriptionID>","heartbeat":true}}
+	/// ```
+	///
+	/// The heartbeat is configured on the subscribe method and has no effect on other methods.
+	///
+	/// Fails if the method has not been registered.
+	pub fn set_subscription_heartbeat(
+		&mut self,
+		subscribe_method_name: &str,
+		interval: Duration,
+	) -> Result<(), RegisterMethodError> {
+		self.mut_config(subscribe_method_name)?.heartbeat_interval = Some(interval);
+		Ok(())
+	}
+
+	/// Limit the number of subscriptions of a subscribe method per connection.
+	///
+	/// The limit is enforced by the server in addition to the max subscriptions per connection
+	/// of the server before the subscription callback is invoked, and the subscribe call is
+	/// rejected with a "too many subscriptions" error once the limit has been reached.
+	///
+	/// Fails if the method has not been registered.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use jsonrpsee_core::server::{RpcModule, SubscriptionMessage};
+	///
+	/// let mut module = RpcModule::new(());
+	/// module
+	///     .register_subscription("subscribe_blocks", "blocks", "unsubscribe_blocks", |_, pending, _, _| async move {
+	///         let sink = pending.accept().await?;
+	///         sink.send(SubscriptionMessage::from_json(&1)?).await?;
+	///         Ok(())
+	///     })
+	///     .unwrap();
+	/// module.set_max_subscriptions_per_connection("subscribe_blocks", 2).unwrap();
+	/// ```
+	pub fn set_max_subscriptions_per_connection(
+		&mut self,
+		subscribe_method_name: &str,
+		max: u32,
+	) -> Result<(), RegisterMethodError> {
+		self.mut_config(subscribe_method_name)?.max_subscriptions_per_connection = Some(max);
+		Ok(())
+	}
+
+	/// Attach a [JSON Schema](https://json-schema.org) that the params of a registered method must conform to.
+	///
+	/// The schema is not enforced by the method itself, it is used by middleware such as
+	/// `ParamsValidationLayer` in `jsonrpsee-server` that validates the params before the call is dispatched.
+	///
+	/// Only the keywords which are validated by [`validate_params`](crate::server::validate_params) and
+	/// annotations are accepted, and `$ref`s must point to subschemas of the schema itself such as `#/$defs/Foo`,
+	/// as generated by `schemars`. This ensures that no value is accepted because a keyword would be ignored.
+	///
+	/// Fails if the method has not been registered or if the schema is not supported.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use jsonrpsee_core::server::RpcModule;
+	///
+	/// let mut module = RpcModule::new(());
+	/// module.register_method("add", |params, _, _| {
+	///     let (a, b): (u64, u64) = params.parse().unwrap();
+	///     a + b
+	/// }).unwrap();
+	/// module.set_method_params_schema("add", serde_json::json!({
+	///     "type": "array",
+	///     "items": { "type": "integer", "minimum": 0 },
+	///     "minItems": 2,
+	///     "maxItems": 2,
+	/// })).unwrap();
+	/// ```
+	pub fn set_method_params_schema(
+		&mut self,
+		method_name: &str,
+		schema: serde_json::Value,
+	) -> Result<(), ParamsSchemaError> {
+		check_schema(&schema)?;
+		self.mut_metadata(method_name)?.params_schema = Some(Arc::new(schema));
+		Ok(())
+	}
+
+	/// Returns the JSON Schema of the params of the method, if one has been attached.
+	pub fn method_params_schema(&self, method_name: &str) -> Option<&serde_json::Value> {
+		self.metadata.get(method_name)?.params_schema()
+	}
+
+	/// Returns an `Iterator` with all methods that have a params schema attached.
+	pub fn method_params_schemas(&self) -> impl Iterator<Item = (&'static str, &serde_json::Value)> + '_ {
+		self.metadata.iter().filter_map(|(name, metadata)| Some((*name, metadata.params_schema()?)))
+	}
+
+	/// Describe the parameters of a registered method, which is used in the generated OpenRPC document.
+	///
+	/// The `rpc` macro describes the parameters of the methods it generates by their names, and by the
+	/// JSON Schemas of their types derived with `schemars` if the trait has the `openrpc` attribute.
+	///
+	/// Fails if the method has not been registered.
+	pub fn set_method_params(
+		&mut self,
+		method_name: &str,
+		params: Vec<ParamDescriptor>,
+	) -> Result<(), RegisterMethodError> {
+		self.mut_metadata(method_name)?.params = Some(params);
+		Ok(())
+	}
+
+	/// Attach a JSON Schema of the result of a registered method, which is used in the generated OpenRPC document.
+	///
+	/// Fails if the method has not been registered.
+	pub fn set_method_result_schema(
+		&mut self,
+		method_name: &str,
+		schema: serde_json::Value,
+	) -> Result<(), RegisterMethodError> {
+		self.mut_metadata(method_name)?.result_schema = Some(schema);
+		Ok(())
+	}
+
+	/// Set the description of a registered method, which is used in the generated OpenRPC document.
+	///
+	/// Fails if the method has not been registered.
+	pub fn set_method_description(
+		&mut self,
+		method_name: &str,
+		description: impl Into<String>,
+	) -> Result<(), RegisterMethodError> {
+		self.mut_metadata(method_name)?.description = Some(description.into());
+		Ok(())
+	}
+
+	/// Hide a registered method from the generated OpenRPC document, for instance
+	/// because it's only meant for internal use.
+	///
+	/// The method can still be called.
+	///
+	/// Fails if the method has not been registered.
+	pub fn hide_method(&mut self, method_name: &str) -> Result<(), RegisterMethodError> {
+		self.mut_metadata(method_name)?.hidden = true;
+		Ok(())
+	}
+
+	/// Mark a registered method as deprecated with a note for its callers, for instance which method
+	/// replaces it.
+	///
+	/// The method can still be called and is marked as deprecated in the generated OpenRPC document.
+	///
+	/// Fails if the method has not been registered.
+	pub fn deprecate_method(&mut self, method_name: &str, note: impl Into<String>) -> Result<(), RegisterMethodError> {
+		self.mut_metadata(method_name)?.deprecated = Some(note.into());
+		Ok(())
+	}
+
+	/// Describe the authorization that is required to call a registered method, for instance a role or a scope.
+	///
+	/// This is only informational and it's up to a middleware to enforce it.
+	///
+	/// Fails if the method has not been registered.
+	pub fn require_auth(
+		&mut self,
+		method_name: &str,
+		requirement: impl Into<String>,
+	) -> Result<(), RegisterMethodError> {
+		self.mut_metadata(method_name)?.auth = Some(requirement.into());
+		Ok(())
+	}
+
+	/// Returns the metadata of the method, if anything has been attached to it.
+	pub fn method_metadata(&self, method_name: &str) -> Option<&MethodMetadata> {
+		self.metadata.get(method_name)
+	}
+
 	/// Helper to call a method on the `RPC module` without having to spin up a server.
 	///
 	/// The params must be serializable as JSON array, see [`ToRpcParams`] for further documentation.
@@ -340,7 +788,7 @@ impl Methods {
 	///     let (resp, mut stream) = module.raw_json_request(r#"{"jsonrpc":"2.0","method":"hi","id":0}"#, 1).await.unwrap();
 	///     // If the response is an error converting it to `Success` will fail.
 	///     let resp: Success<u64> = serde_json::from_str::<Response<u64>>(&resp).unwrap().try_into().unwrap();
-	///     let sub_resp = stream.recv().await.unwrap();
+	///     let sub_resp = stream.recv().await.unwrap().into_string();
 	///     assert_eq!(
 	///         format!(r#"{{"jsonrpc":"2.0","method":"hi","params":{{"subscription":{},"result":"one answer"}}}}"#, resp.result),
 	///         sub_resp
@@ -351,7 +799,7 @@ impl Methods {
 		&self,
 		request: &str,
 		buf_size: usize,
-	) -> Result<(String, mpsc::Receiver<String>), serde_json::Error> {
+	) -> Result<(String, mpsc::Receiver<SinkMessage>), serde_json::Error> {
 		tracing::trace!("[Methods::raw_json_request] Request: {:?}", request);
 		let req: Request = serde_json::from_str(request)?;
 		let (resp, rx) = self.inner_call(req, buf_size, mock_subscription_permit()).await;
@@ -371,20 +819,33 @@ impl Methods {
 		// request doesn't have any extensions.
 		let Request { id, method, params, .. } = req;
 		let params = Params::new(params.as_ref().map(|params| params.as_ref().get()));
-		let max_response_size = usize::MAX;
+		let max_response_size =
+			self.method_config(&method).and_then(|c| c.max_response_size()).map_or(usize::MAX, |s| s as usize);
 		let conn_id = ConnectionId(0);
 		let mut ext = self.extensions.clone();
 		ext.insert(conn_id);
+		if ext.get::<ConnectionExtensions>().is_none() {
+			ext.insert(ConnectionExtensions::new());
+		}
 
 		let response = match self.method(&method) {
 			None => MethodResponse::error(id, ErrorObject::from(ErrorCode::MethodNotFound)),
 			Some(MethodCallback::Sync(cb)) => (cb)(id, params, max_response_size, ext),
 			Some(MethodCallback::Async(cb)) => {
-				(cb)(id.into_owned(), params.into_owned(), conn_id, max_response_size, ext).await
+				let id = id.into_owned();
+				let fut = (cb)(id.clone(), params.into_owned(), conn_id, max_response_size, ext);
+
+				match self.method_config(&method).and_then(|c| c.timeout()) {
+					Some(timeout) => match tokio::time::timeout(timeout, fut).await {
+						Ok(rp) => rp,
+						Err(_) => MethodResponse::error(id, reject_method_timeout(timeout)),
+					},
+					None => fut.await,
+				}
 			}
 			Some(MethodCallback::Subscription(cb)) => {
-				let conn_state =
-					SubscriptionState { conn_id, id_provider: &RandomIntegerIdProvider, subscription_permit };
+				let config = self.method_config(&method).copied().unwrap_or_default();
+				let conn_state = SubscriptionState::new(conn_id, &RandomIntegerIdProvider, subscription_permit, config);
 				let res = (cb)(id, params, MethodSink::new(tx.clone()), conn_state, ext).await;
 
 				// This message is not used because it's used for metrics so we discard in other to
@@ -469,6 +930,15 @@ impl Methods {
 		Ok(Subscription { sub_id, rx })
 	}
 
+	/// Returns whether both are clones of the same methods which haven't been modified since.
+	///
+	/// The extensions are not compared.
+	pub fn ptr_eq(&self, other: &Methods) -> bool {
+		Arc::ptr_eq(&self.callbacks, &other.callbacks)
+			&& Arc::ptr_eq(&self.configs, &other.configs)
+			&& Arc::ptr_eq(&self.metadata, &other.metadata)
+	}
+
 	/// Returns an `Iterator` with all the method names registered on this server.
 	pub fn method_names(&self) -> impl Iterator<Item = &'static str> + '_ {
 		self.callbacks.keys().copied()
@@ -556,6 +1026,14 @@ impl<Context> RpcModule<Context> {
 	}
 }
 
+impl<T> RpcModule<SwappableContext<T>> {
+	/// Get a handle to replace the context of the module, which is also seen by
+	/// the methods that are already registered.
+	pub fn context_handle(&self) -> SwappableContext<T> {
+		(*self.ctx).clone()
+	}
+}
+
 impl<Context> From<RpcModule<Context>> for Methods {
 	fn from(module: RpcModule<Context>) -> Methods {
 		module.methods
@@ -588,16 +1066,66 @@ impl<Context: Send + Sync + 'static> RpcModule<Context> {
 			method_name,
 			MethodCallback::Sync(Arc::new(move |id, params, max_response_size, extensions| {
 				let rp = callback(params, &*ctx, &extensions).into_response();
-				MethodResponse::response(id, rp, max_response_size).with_extensions(extensions)
+				pooled_response(id, rp, max_response_size, &extensions).with_extensions(extensions)
 			})),
 		)
 	}
 
+	/// Register a new synchronous RPC method whose callback receives the params already deserialized
+	/// into `P`, instead of [`Params`].
+	///
+	/// The params may be given either positionally as an array or by name as an object, which both
+	/// map onto the fields of a struct. Positional params which are left out at the end are regarded
+	/// as `null`, such that trailing `Option` fields may be omitted. A call without params is regarded
+	/// as an empty object.
+	/// If the params can't be deserialized into `P` the call fails with an `Invalid params` error
+	/// that names the offending field.
+	///
+	/// ## Examples
+	///
+	/// ```
+	/// use jsonrpsee_core::server::RpcModule;
+	/// use serde::Deserialize;
+	///
+	/// #[derive(Deserialize)]
+	/// struct Greeting {
+	///     name: String,
+	///     excited: Option<bool>,
+	/// }
+	///
+	/// let mut module = RpcModule::new(());
+	/// module.register_typed_method("greet", |greeting: Greeting, _ctx, _| {
+	///     let end = if greeting.excited.unwrap_or(false) { "!" } else { "." };
+	///     format!("Hello, {}{end}", greeting.name)
+	/// }).unwrap();
+	/// ```
+	pub fn register_typed_method<P, R, F>(
+		&mut self,
+		method_name: &'static str,
+		callback: F,
+	) -> Result<&mut MethodCallback, RegisterMethodError>
+	where
+		Context: Send + Sync + 'static,
+		P: DeserializeOwned,
+		R: IntoResponse + 'static,
+		F: Fn(P, &Context, &Extensions) -> R + Send + Sync + 'static,
+	{
+		self.register_method(
+			method_name,
+			move |params, ctx, extensions| match super::typed_params::parse_typed_params::<P>(&params) {
+				Ok(params) => callback(params, ctx, extensions).into_response(),
+				Err(e) => ResponsePayload::error(e),
+			},
+		)
+	}
+
 	/// Removes the method if it exists.
 	///
 	/// Be aware that a subscription consist of two methods, `subscribe` and `unsubscribe` and
 	/// it's the caller responsibility to remove both `subscribe` and `unsubscribe` methods for subscriptions.
 	pub fn remove_method(&mut self, method_name: &'static str) -> Option<MethodCallback> {
+		Arc::make_mut(&mut self.methods.configs).remove(method_name);
+		Arc::make_mut(&mut self.methods.metadata).remove(method_name);
 		self.methods.mut_callbacks().remove(method_name)
 	}
 
@@ -634,7 +1162,171 @@ impl<Context: Send + Sync + 'static> RpcModule<Context> {
 				// it's safe to clone it.
 				let future = async move {
 					let rp = callback(params, ctx, extensions.clone()).await.into_response();
-					MethodResponse::response(id, rp, max_response_size).with_extensions(extensions)
+					pooled_response(id, rp, max_response_size, &extensions).with_extensions(extensions)
+				};
+				future.boxed()
+			})),
+		)
+	}
+
+	/// Register a new asynchronous RPC method whose callback receives the params already deserialized
+	/// into `P`, instead of [`Params`].
+	///
+	/// The params are deserialized as for [`RpcModule::register_typed_method`], before the callback is invoked.
+	///
+	/// ## Examples
+	///
+	/// ```
+	/// use jsonrpsee_core::server::RpcModule;
+	/// use serde::Deserialize;
+	///
+	/// #[derive(Deserialize)]
+	/// struct Greeting {
+	///     name: String,
+	/// }
+	///
+	/// let mut module = RpcModule::new(());
+	/// module.register_async_typed_method("greet", |greeting: Greeting, _ctx, _| async move {
+	///     format!("Hello, {}", greeting.name)
+	/// }).unwrap();
+	/// ```
+	pub fn register_async_typed_method<P, R, Fun, Fut>(
+		&mut self,
+		method_name: &'static str,
+		callback: Fun,
+	) -> Result<&mut MethodCallback, RegisterMethodError>
+	where
+		P: DeserializeOwned + Send + 'static,
+		R: IntoResponse + 'static,
+		Fut: Future<Output = R> + Send,
+		Fun: (Fn(P, Arc<Context>, Extensions) -> Fut) + Clone + Send + Sync + 'static,
+	{
+		self.register_async_method(method_name, move |params, ctx, extensions| {
+			let params = super::typed_params::parse_typed_params::<P>(&params);
+			let callback = callback.clone();
+
+			async move {
+				match params {
+					Ok(params) => callback(params, ctx, extensions).await.into_response(),
+					Err(e) => ResponsePayload::error(e),
+				}
+			}
+		})
+	}
+
+	/// Register a new asynchronous RPC method whose callback receives the params as raw JSON,
+	/// for use cases such as proxies that forward the params without interpreting them.
+	///
+	/// The params are only scanned to find their boundaries and are `None` if the call has no params.
+	/// The callback is invoked before the returned future is polled, such that the params have to be
+	/// copied, for instance with [`ToOwned::to_owned`], to be used by the future.
+	///
+	/// ## Examples
+	///
+	/// ```
+	/// use jsonrpsee_core::server::RpcModule;
+	///
+	/// let mut module = RpcModule::new(());
+	/// module.register_raw_method("echo", |params, _ctx, _| {
+	///     let params = params.map(ToOwned::to_owned);
+	///     async move { params }
+	/// }).unwrap();
+	/// ```
+	pub fn register_raw_method<R, Fun, Fut>(
+		&mut self,
+		method_name: &'static str,
+		callback: Fun,
+	) -> Result<&mut MethodCallback, RegisterMethodError>
+	where
+		R: IntoResponse + 'static,
+		Fut: Future<Output = R> + Send + 'static,
+		Fun: (Fn(Option<&JsonRawValue>, Arc<Context>, &Extensions) -> Fut) + Send + Sync + 'static,
+	{
+		let ctx = self.ctx.clone();
+		self.methods.verify_and_insert(
+			method_name,
+			MethodCallback::Async(Arc::new(move |id, params, _, max_response_size, extensions| {
+				let raw = match params.as_str().map(serde_json::from_str::<&JsonRawValue>).transpose() {
+					Ok(raw) => raw,
+					Err(_) => {
+						let rp = MethodResponse::error(id, ErrorCode::InvalidParams).with_extensions(extensions);
+						return future::ready(rp).boxed();
+					}
+				};
+
+				let future = callback(raw, ctx.clone(), &extensions);
+
+				async move {
+					let rp = future.await.into_response();
+					pooled_response(id, rp, max_response_size, &extensions).with_extensions(extensions)
+				}
+				.boxed()
+			})),
+		)
+	}
+
+	/// Register a new asynchronous RPC method whose result is a JSON array with the items produced
+	/// by the stream that the callback returns.
+	///
+	/// For a single call over HTTP the response is streamed to the client as the items are produced,
+	/// such that large results don't have to be buffered in memory. The max response size doesn't
+	/// apply to such responses.
+	///
+	/// Otherwise, such as over WebSocket, in a batch or when a middleware has removed the
+	/// [`StreamedResponseSupport`] from the extensions of the call, the items are collected into
+	/// a regular response which is limited by the max response size.
+	///
+	/// The middleware observes a successful response with a `null` result in place of a streamed
+	/// response, see [`StreamedResponseSupport`] for details.
+	///
+	/// If an item fails to serialize a streamed response is aborted and a buffered response
+	/// is replaced by an internal error.
+	///
+	/// ## Examples
+	///
+	/// ```
+	/// use jsonrpsee_core::server::RpcModule;
+	/// use jsonrpsee_types::ErrorObjectOwned;
+	///
+	/// let mut module = RpcModule::new(());
+	/// module.register_streaming_method("numbers", |_params, _ctx, _| async {
+	///     Ok::<_, ErrorObjectOwned>(futures_util::stream::iter(0..1_000_000))
+	/// }).unwrap();
+	/// ```
+	pub fn register_streaming_method<T, St, E, Fun, Fut>(
+		&mut self,
+		method_name: &'static str,
+		callback: Fun,
+	) -> Result<&mut MethodCallback, RegisterMethodError>
+	where
+		T: Serialize + Send + 'static,
+		St: Stream<Item = T> + Send + 'static,
+		E: Into<ErrorObjectOwned> + 'static,
+		Fut: Future<Output = Result<St, E>> + Send,
+		Fun: (Fn(Params<'static>, Arc<Context>, Extensions) -> Fut) + Clone + Send + Sync + 'static,
+	{
+		let ctx = self.ctx.clone();
+		self.methods.verify_and_insert(
+			method_name,
+			MethodCallback::Async(Arc::new(move |id, params, _, max_response_size, extensions| {
+				let ctx = ctx.clone();
+				let callback = callback.clone();
+
+				let future = async move {
+					let items = match callback(params, ctx, extensions.clone()).await {
+						Ok(items) => items,
+						Err(err) => return MethodResponse::error(id, err.into()).with_extensions(extensions),
+					};
+
+					let rp = match extensions.get::<StreamedResponseSupport>() {
+						Some(streamed) => {
+							streamed.set(&id, json_array_chunks(items));
+							MethodResponse::response(id, ResponsePayload::success(()), max_response_size)
+						}
+						None => collect_json_array(id, items, max_response_size).await,
+					};
+
+					rp.with_extensions(extensions)
 				};
 				future.boxed()
 			})),
@@ -657,23 +1349,102 @@ impl<Context: Send + Sync + 'static> RpcModule<Context> {
 		let ctx = self.ctx.clone();
 		let callback = self.methods.verify_and_insert(
 			method_name,
-			MethodCallback::Async(Arc::new(move |id, params, _, max_response_size, extensions| {
+			MethodCallback::Async(Arc::new(move |id, params, conn_id, max_response_size, extensions| {
 				let ctx = ctx.clone();
 				let callback = callback.clone();
 
 				// NOTE: the extensions can't be mutated at this point so
 				// it's safe to clone it.
 				let extensions2 = extensions.clone();
+				let id2 = id.clone();
 
 				tokio::task::spawn_blocking(move || {
 					let rp = callback(params, ctx, extensions2.clone()).into_response();
-					MethodResponse::response(id, rp, max_response_size).with_extensions(extensions2)
+					pooled_response(id2, rp, max_response_size, &extensions2).with_extensions(extensions2)
 				})
-				.map(|result| match result {
+				.map(move |result| match result {
 					Ok(r) => r,
+					Err(err) if err.is_panic() => {
+						let err = report_panic(conn_id, method_name, err.into_panic(), extensions.get::<PanicHook>());
+						MethodResponse::error(id, err).with_extensions(extensions)
+					}
 					Err(err) => {
 						tracing::error!(target: LOG_TARGET, "Join error for blocking RPC method: {:?}", err);
-						MethodResponse::error(Id::Null, ErrorObject::from(ErrorCode::InternalError))
+						MethodResponse::error(id, ErrorObject::from(ErrorCode::InternalError))
+							.with_extensions(extensions)
+					}
+				})
+				.boxed()
+			})),
+		)?;
+
+		Ok(callback)
+	}
+
+	/// Register a new **CPU-bound** synchronous RPC method which is executed on the threads of `pool`,
+	/// such that expensive computations don't block the async runtime.
+	///
+	/// Unlike [`register_blocking_method`](RpcModule::register_blocking_method) the number of
+	/// threads and the number of calls that wait for a thread are bounded by the [`CpuPool`],
+	/// and the calls are rejected with a [`ErrorCode::ServerIsBusy`] error once its queue is full.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use jsonrpsee_core::server::{CpuPool, RpcModule};
+	///
+	/// let pool = CpuPool::new(4, 64);
+	/// let mut module = RpcModule::new(());
+	/// module.register_cpu_method("fib", &pool, |params, _, _| {
+	///     fn fib(n: u64) -> u64 {
+	///         if n < 2 { n } else { fib(n - 1) + fib(n - 2) }
+	///     }
+	///     params.one::<u64>().map(fib)
+	/// }).unwrap();
+	/// ```
+	pub fn register_cpu_method<R, F>(
+		&mut self,
+		method_name: &'static str,
+		pool: &CpuPool,
+		callback: F,
+	) -> Result<&mut MethodCallback, RegisterMethodError>
+	where
+		Context: Send + Sync + 'static,
+		R: IntoResponse + 'static,
+		F: Fn(Params, Arc<Context>, Extensions) -> R + Clone + Send + Sync + 'static,
+	{
+		let ctx = self.ctx.clone();
+		let pool = pool.clone();
+		let callback = self.methods.verify_and_insert(
+			method_name,
+			MethodCallback::Async(Arc::new(move |id, params, conn_id, max_response_size, extensions| {
+				let ctx = ctx.clone();
+				let callback = callback.clone();
+
+				// NOTE: the extensions can't be mutated at this point so
+				// it's safe to clone it.
+				let extensions2 = extensions.clone();
+				let id2 = id.clone();
+
+				let rp = pool.spawn(move || {
+					let rp = callback(params, ctx, extensions2.clone()).into_response();
+					pooled_response(id2, rp, max_response_size, &extensions2).with_extensions(extensions2)
+				});
+
+				let Some(rp) = rp else {
+					let rp = MethodResponse::error(id, ErrorObject::from(ErrorCode::ServerIsBusy));
+					return future::ready(rp.with_extensions(extensions)).boxed();
+				};
+
+				rp.map(move |result| match result {
+					Ok(Ok(r)) => r,
+					Ok(Err(payload)) => {
+						let err = report_panic(conn_id, method_name, payload, extensions.get::<PanicHook>());
+						MethodResponse::error(id, err).with_extensions(extensions)
+					}
+					Err(_) => {
+						tracing::error!(target: LOG_TARGET, "CPU pool was stopped before the call completed");
+						MethodResponse::error(id, ErrorObject::from(ErrorCode::InternalError))
 							.with_extensions(extensions)
 					}
 				})
@@ -795,7 +1566,8 @@ impl<Context: Send + Sync + 'static> RpcModule<Context> {
 		Fut: Future<Output = R> + Send + 'static,
 		R: IntoSubscriptionCloseResponse + Send,
 	{
-		let subscribers = self.verify_and_register_unsubscribe(subscribe_method_name, unsubscribe_method_name)?;
+		let subscribers =
+			self.verify_and_register_unsubscribe(subscribe_method_name, notif_method_name, unsubscribe_method_name)?;
 		let ctx = self.ctx.clone();
 
 		// Subscribe
@@ -803,13 +1575,14 @@ impl<Context: Send + Sync + 'static> RpcModule<Context> {
 			self.methods.verify_and_insert(
 				subscribe_method_name,
 				MethodCallback::Subscription(Arc::new(move |id, params, method_sink, conn, extensions| {
-					let uniq_sub = SubscriptionKey { conn_id: conn.conn_id, sub_id: conn.id_provider.next_id() };
+					let uniq_sub = conn.subscription_key(&subscribers, subscribe_method_name, &params);
 
 					// response to the subscription call.
 					let (tx, rx) = oneshot::channel();
 					let (accepted_tx, accepted_rx) = oneshot::channel();
 
 					let sub_id = uniq_sub.sub_id.clone();
+					let accepted_sub_id = sub_id.clone();
 					let method = notif_method_name;
 
 					let sink = PendingSubscriptionSink {
@@ -820,6 +1593,10 @@ impl<Context: Send + Sync + 'static> RpcModule<Context> {
 						id: id.clone().into_owned(),
 						subscribe: tx,
 						permit: conn.subscription_permit,
+						method_permit: conn.method_permit,
+						config: conn.config,
+						stats: extensions.get::<SubscriptionStats>().cloned(),
+						subscribe_method: subscribe_method_name,
 					};
 
 					// The subscription callback is a future from the subscription
@@ -831,9 +1608,23 @@ impl<Context: Send + Sync + 'static> RpcModule<Context> {
 					// it's safe to clone it.
 					let sub_fut = callback(params.into_owned(), sink, ctx.clone(), extensions.clone());
 
+					// A panic of the subscription task is reported and closes the subscription.
+					let conn_id = conn.conn_id;
+					let panic_hook = extensions.get::<PanicHook>().cloned();
+					let sub_fut = async move {
+						match std::panic::AssertUnwindSafe(sub_fut).catch_unwind().await {
+							Ok(r) => Ok(r),
+							Err(payload) => {
+								report_panic(conn_id, subscribe_method_name, payload, panic_hook.as_ref());
+								Err(())
+							}
+						}
+					};
+
 					tokio::spawn(async move {
+						let accepted = async move { accepted_rx.await.map_err(|_| ()) };
 						// This will wait for the subscription future to be resolved
-						let response = match futures_util::future::try_join(sub_fut.map(|f| Ok(f)), accepted_rx).await {
+						let response = match future::try_join(sub_fut, accepted).await {
 							Ok((r, _)) => r.into_response(),
 							// The accept call failed i.e, the subscription was not accepted.
 							Err(_) => return,
@@ -867,7 +1658,7 @@ impl<Context: Send + Sync + 'static> RpcModule<Context> {
 							Err(_) => MethodResponse::error(id, ErrorCode::InternalError),
 						};
 
-						rp.with_extensions(extensions)
+						with_subscription_id(rp.with_extensions(extensions), accepted_sub_id)
 					})
 				})),
 			)?
@@ -876,6 +1667,78 @@ impl<Context: Send + Sync + 'static> RpcModule<Context> {
 		Ok(callback)
 	}
 
+	/// Similar to [`RpcModule::register_subscription`] but the callback returns a stream
+	/// and every item of the stream is sent out as a notification.
+	///
+	/// The subscription is accepted before the stream is polled and it is closed once the stream
+	/// is exhausted, when the client unsubscribes or when the connection is closed.
+	/// If the client is slow the stream is not polled until there is space in the send buffer of the connection.
+	///
+	/// If an item can't be serialized the subscription is closed with an error notification.
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	///
+	/// use jsonrpsee_core::server::RpcModule;
+	///
+	/// let mut ctx = RpcModule::new(99_usize);
+	/// ctx.register_subscription_from_stream("sub", "notif_name", "unsub", |_params, ctx| {
+	///     futures_util::stream::iter(vec![*ctx, *ctx + 1, *ctx + 2])
+	/// });
+	/// ```
+	pub fn register_subscription_from_stream<F, S, T>(
+		&mut self,
+		subscribe_method_name: &'static str,
+		notif_method_name: &'static str,
+		unsubscribe_method_name: &'static str,
+		callback: F,
+	) -> Result<&mut MethodCallback, RegisterMethodError>
+	where
+		Context: Send + Sync + 'static,
+		F: (Fn(Params<'static>, Arc<Context>) -> S) + Send + Sync + Clone + 'static,
+		S: Stream<Item = T> + Send + 'static,
+		T: Serialize + Send,
+	{
+		self.register_subscription(
+			subscribe_method_name,
+			notif_method_name,
+			unsubscribe_method_name,
+			move |params, pending, ctx, _| {
+				let stream = callback(params, ctx);
+
+				async move {
+					let Ok(sink) = pending.accept().await else {
+						return Ok(());
+					};
+
+					futures_util::pin_mut!(stream);
+
+					loop {
+						let item = tokio::select! {
+							_ = sink.closed() => break,
+							item = stream.next() => match item {
+								Some(item) => item,
+								None => break,
+							},
+						};
+
+						let msg = SubscriptionMessage::from_json(&item)?;
+
+						tokio::select! {
+							_ = sink.closed() => break,
+							res = sink.send(msg) => if res.is_err() {
+								break;
+							},
+						}
+					}
+
+					SubscriptionResult::Ok(())
+				}
+			},
+		)
+	}
+
 	/// Similar to [`RpcModule::register_subscription`] but a little lower-level API
 	/// where handling the subscription is managed the user i.e, polling the subscription
 	/// such as spawning a separate task to do so.
@@ -933,7 +1796,8 @@ impl<Context: Send + Sync + 'static> RpcModule<Context> {
 		F: (Fn(Params, PendingSubscriptionSink, Arc<Context>, &Extensions) -> R) + Send + Sync + Clone + 'static,
 		R: IntoSubscriptionCloseResponse,
 	{
-		let subscribers = self.verify_and_register_unsubscribe(subscribe_method_name, unsubscribe_method_name)?;
+		let subscribers =
+			self.verify_and_register_unsubscribe(subscribe_method_name, notif_method_name, unsubscribe_method_name)?;
 		let ctx = self.ctx.clone();
 
 		// Subscribe
@@ -941,7 +1805,8 @@ impl<Context: Send + Sync + 'static> RpcModule<Context> {
 			self.methods.verify_and_insert(
 				subscribe_method_name,
 				MethodCallback::Subscription(Arc::new(move |id, params, method_sink, conn, extensions| {
-					let uniq_sub = SubscriptionKey { conn_id: conn.conn_id, sub_id: conn.id_provider.next_id() };
+					let uniq_sub = conn.subscription_key(&subscribers, subscribe_method_name, &params);
+					let sub_id = uniq_sub.sub_id.clone();
 
 					// response to the subscription call.
 					let (tx, rx) = oneshot::channel();
@@ -954,6 +1819,10 @@ impl<Context: Send + Sync + 'static> RpcModule<Context> {
 						id: id.clone().into_owned(),
 						subscribe: tx,
 						permit: conn.subscription_permit,
+						method_permit: conn.method_permit,
+						config: conn.config,
+						stats: extensions.get::<SubscriptionStats>().cloned(),
+						subscribe_method: subscribe_method_name,
 					};
 
 					callback(params, sink, ctx.clone(), &extensions);
@@ -966,7 +1835,7 @@ impl<Context: Send + Sync + 'static> RpcModule<Context> {
 							Err(_) => MethodResponse::error(id, ErrorCode::InternalError),
 						};
 
-						rp.with_extensions(extensions)
+						with_subscription_id(rp.with_extensions(extensions), sub_id)
 					})
 				})),
 			)?
@@ -980,6 +1849,7 @@ impl<Context: Send + Sync + 'static> RpcModule<Context> {
 	fn verify_and_register_unsubscribe(
 		&mut self,
 		subscribe_method_name: &'static str,
+		notif_method_name: &'static str,
 		unsubscribe_method_name: &'static str,
 	) -> Result<Subscribers, RegisterMethodError> {
 		if subscribe_method_name == unsubscribe_method_name {
@@ -989,6 +1859,10 @@ impl<Context: Send + Sync + 'static> RpcModule<Context> {
 		self.methods.verify_method_name(subscribe_method_name)?;
 		self.methods.verify_method_name(unsubscribe_method_name)?;
 
+		let subscription = SubscriptionMetadata::new(notif_method_name, unsubscribe_method_name);
+		Arc::make_mut(&mut self.methods.metadata).entry(subscribe_method_name).or_default().subscription =
+			Some(subscription);
+
 		let subscribers = Subscribers::default();
 
 		// Unsubscribe
@@ -1026,6 +1900,7 @@ impl<Context: Send + Sync + 'static> RpcModule<Context> {
 					}
 
 					MethodResponse::response(id, ResponsePayload::success(result), max_response_size)
+						.with_extensions(extensions)
 				})),
 			);
 		}
@@ -1048,10 +1923,86 @@ impl<Context: Send + Sync + 'static> RpcModule<Context> {
 
 		self.methods.mut_callbacks().insert(alias, callback);
 
+		if let Some(config) = self.methods.configs.get(existing_method).copied() {
+			Arc::make_mut(&mut self.methods.configs).insert(alias, config);
+		}
+
+		let mut metadata = self.methods.metadata.get(existing_method).cloned().unwrap_or_default();
+		metadata.alias_of = Some(metadata.alias_of.unwrap_or(existing_method));
+		Arc::make_mut(&mut self.methods.metadata).insert(alias, metadata);
+
 		Ok(())
 	}
 }
 
+/// Serialize the items of a stream as the chunks of a JSON array.
+fn json_array_chunks<T: Serialize>(
+	items: impl Stream<Item = T> + Send + 'static,
+) -> impl Stream<Item = Result<String, BoxError>> + Send + 'static {
+	let mut first = true;
+	let items = items.map(move |item| {
+		let sep = if std::mem::take(&mut first) { "" } else { "," };
+		serde_json::to_string(&item).map(|item| format!("{sep}{item}")).map_err(BoxError::from)
+	});
+
+	futures_util::stream::once(async { Ok("[".to_owned()) })
+		.chain(items)
+		.chain(futures_util::stream::once(async { Ok("]".to_owned()) }))
+}
+
+/// Serialize the response of a method call into a buffer of the [`BufferPool`] of the connection if it has one.
+fn pooled_response<T>(
+	id: Id,
+	rp: ResponsePayload<'static, T>,
+	max_response_size: usize,
+	extensions: &Extensions,
+) -> MethodResponse
+where
+	T: Serialize + Clone,
+{
+	match extensions.get::<BufferPool>() {
+		Some(pool) => MethodResponse::response_with_buffer(id, rp, max_response_size, pool.get()),
+		None => MethodResponse::response(id, rp, max_response_size),
+	}
+}
+
+/// Attach the ID of an accepted subscription to the response of the subscription call,
+/// such that the transport can tell which subscription the call created.
+fn with_subscription_id(mut rp: MethodResponse, sub_id: RpcSubscriptionId<'static>) -> MethodResponse {
+	if rp.is_success() {
+		rp.extensions_mut().insert(sub_id);
+	}
+	rp
+}
+
+/// Collect the items of a stream into a JSON array response.
+///
+/// Stops consuming the stream once the max response size is exceeded.
+async fn collect_json_array<T: Serialize>(
+	id: Id<'_>,
+	items: impl Stream<Item = T>,
+	max_response_size: usize,
+) -> MethodResponse {
+	let mut items = std::pin::pin!(items);
+	let mut array = Vec::new();
+	let mut len = 0;
+
+	while let Some(item) = items.next().await {
+		let Ok(item) = serde_json::value::to_raw_value(&item) else {
+			return MethodResponse::error(id, ErrorCode::InternalError);
+		};
+
+		len += item.get().len() + 1;
+		array.push(item);
+
+		if len > max_response_size {
+			break;
+		}
+	}
+
+	MethodResponse::response(id, ResponsePayload::success(array), max_response_size)
+}
+
 fn mock_subscription_permit() -> SubscriptionPermit {
 	BoundedSubscriptions::new(1).acquire().expect("1 permit should exist; qed")
 }
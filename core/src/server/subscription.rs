@@ -26,17 +26,24 @@
 
 //! Subscription related types and traits for server implementations.
 
-use super::helpers::MethodSink;
+use super::helpers::{BufferPool, MethodSink, SinkMessage};
 use super::{MethodResponse, MethodsError, ResponsePayload};
 use crate::server::error::{DisconnectError, PendingSubscriptionAcceptError, SendTimeoutError, TrySendError};
-use crate::server::rpc_module::ConnectionId;
+use crate::server::rpc_module::{ConnectionId, MethodConfig};
+use crate::server::SubscriptionStats;
 use crate::server::LOG_TARGET;
 use crate::{error::StringError, traits::IdProvider};
+use futures_util::{Stream, StreamExt};
 use jsonrpsee_types::SubscriptionPayload;
-use jsonrpsee_types::{response::SubscriptionError, ErrorObjectOwned, Id, SubscriptionId, SubscriptionResponse};
+use jsonrpsee_types::{
+	response::SubscriptionError, ErrorObjectOwned, Id, Params, SubscriptionId, SubscriptionResponse,
+};
 use parking_lot::Mutex;
 use rustc_hash::FxHashMap;
 use serde::{de::DeserializeOwned, Serialize};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
 use std::{sync::Arc, time::Duration};
 use tokio::sync::{mpsc, oneshot, OwnedSemaphorePermit, Semaphore};
 
@@ -44,6 +51,58 @@ use tokio::sync::{mpsc, oneshot, OwnedSemaphorePermit, Semaphore};
 pub type Subscribers = Arc<Mutex<FxHashMap<SubscriptionKey, (MethodSink, mpsc::Receiver<()>)>>>;
 /// Subscription permit.
 pub type SubscriptionPermit = OwnedSemaphorePermit;
+/// Boxed stream of the items of a subscription, which the subscriptions of the `rpc` macro
+/// that are declared to return `impl Stream<Item = T>` return in the server trait.
+pub type SubscriptionStream<T> = Pin<Box<dyn Stream<Item = T> + Send + 'static>>;
+
+/// Max number of notifications of a subscription with a coalescing window that are merged into one message.
+///
+/// Once that many notifications are buffered they're sent out without waiting for the window to elapse.
+pub const MAX_COALESCED_NOTIFICATIONS: usize = 256;
+
+/// What [`SubscriptionSink::send`] does when the send buffer of the connection is full.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum BackpressurePolicy {
+	/// Wait until there is space in the buffer.
+	#[default]
+	Wait,
+	/// Wait for at most the given duration and drop the notification if there is still no space in the buffer.
+	WaitTimeout(Duration),
+	/// Drop the notification that is sent.
+	DropNewest,
+	/// Drop the older notifications of the subscription that are still waiting
+	/// for space in the buffer such that only the latest one is sent out.
+	///
+	/// Notifications that already made it to the buffer are not dropped.
+	DropOldest,
+	/// Close the subscription.
+	Disconnect,
+}
+
+/// Notification that waits for space in the send buffer with [`BackpressurePolicy::DropOldest`].
+#[derive(Debug, Default)]
+struct PendingNotification {
+	/// The latest notification that hasn't been sent out and when it was sent.
+	msg: Option<(SinkMessage, Instant)>,
+	/// Whether a task is waiting for space in the buffer to send out `msg`.
+	forwarding: bool,
+}
+
+fn batch_json(jsons: Vec<SinkMessage>, pool: Option<&BufferPool>) -> String {
+	let mut batch = match pool {
+		Some(pool) => String::from_utf8(pool.get()).expect("the buffers of the pool are empty; qed"),
+		None => String::new(),
+	};
+	batch.push('[');
+	for (i, json) in jsons.into_iter().enumerate() {
+		if i > 0 {
+			batch.push(',');
+		}
+		batch.push_str(&json.into_string());
+	}
+	batch.push(']');
+	batch
+}
 
 /// Convert something into a subscription close notification
 /// before a subscription is terminated.
@@ -118,7 +177,9 @@ pub enum SubscriptionMessageInner {
 	/// Complete JSON message.
 	Complete(String),
 	/// Need subscription ID and method name.
-	NeedsData(String),
+	///
+	/// The JSON is shared by the notifications that the message is sent as.
+	NeedsData(Arc<str>),
 }
 
 /// Subscription message.
@@ -130,7 +191,7 @@ impl SubscriptionMessage {
 	///
 	/// Fails if the value couldn't be serialized.
 	pub fn from_json(t: &impl Serialize) -> Result<Self, serde_json::Error> {
-		serde_json::to_string(t).map(|json| SubscriptionMessage(SubscriptionMessageInner::NeedsData(json)))
+		serde_json::to_string(t).map(|json| SubscriptionMessage(SubscriptionMessageInner::NeedsData(json.into())))
 	}
 
 	/// Create a subscription message this is more efficient than [`SubscriptionMessage::from_json`]
@@ -169,7 +230,7 @@ where
 			res
 		};
 
-		SubscriptionMessage(SubscriptionMessageInner::NeedsData(json_str))
+		SubscriptionMessage(SubscriptionMessageInner::NeedsData(json_str.into()))
 	}
 }
 
@@ -222,6 +283,14 @@ impl IsUnsubscribed {
 	}
 }
 
+/// Reason why a subscription couldn't be accepted.
+enum AcceptError {
+	/// The connection was closed.
+	Closed,
+	/// The response exceeded the max response size.
+	TooBig,
+}
+
 /// Represents a single subscription that is waiting to be accepted or rejected.
 ///
 /// If this is dropped without calling `PendingSubscription::reject` or `PendingSubscriptionSink::accept`
@@ -246,6 +315,14 @@ pub struct PendingSubscriptionSink {
 	pub(crate) subscribe: oneshot::Sender<MethodResponse>,
 	/// Subscription permit.
 	pub(crate) permit: OwnedSemaphorePermit,
+	/// Subscription permit of the subscribe method, if it's limited per connection.
+	pub(crate) method_permit: Option<OwnedSemaphorePermit>,
+	/// Configuration of the subscription.
+	pub(crate) config: MethodConfig,
+	/// Statistics of the subscriptions on the server.
+	pub(crate) stats: Option<SubscriptionStats>,
+	/// Name of the subscribe method which the statistics are tracked by.
+	pub(crate) subscribe_method: &'static str,
 }
 
 impl PendingSubscriptionSink {
@@ -270,10 +347,68 @@ impl PendingSubscriptionSink {
 	/// Panics if the subscription response exceeded the `max_response_size`.
 	pub async fn accept(self) -> Result<SubscriptionSink, PendingSubscriptionAcceptError> {
 		let response = MethodResponse::subscription_response(
-			self.id,
+			self.id.clone(),
 			ResponsePayload::success_borrowed(&self.uniq_sub.sub_id),
 			self.inner.max_response_size() as usize,
 		);
+
+		match self.accept_with_response(response).await {
+			Err(AcceptError::TooBig) => panic!(
+				"The subscription response was too big; adjust the `max_response_size` or change Subscription ID generation"
+			),
+			res => res.map_err(|_| PendingSubscriptionAcceptError),
+		}
+	}
+
+	/// Similar to [`PendingSubscriptionSink::accept`] but responds the subscription method call
+	/// with a custom `result` instead of the subscription ID.
+	///
+	/// This is useful for protocols where the subscribe call returns an object that contains
+	/// the subscription ID, see [`PendingSubscriptionSink::subscription_id`], and some initial state.
+	///
+	/// Fails if the connection is closed or if the response exceeded the `max_response_size`,
+	/// in which case an error is sent out as response to the subscription call.
+	pub async fn accept_with<T: Serialize + Clone>(
+		self,
+		result: T,
+	) -> Result<SubscriptionSink, PendingSubscriptionAcceptError> {
+		let response = MethodResponse::subscription_response(
+			self.id.clone(),
+			ResponsePayload::success(result),
+			self.inner.max_response_size() as usize,
+		);
+
+		self.accept_with_response(response).await.map_err(|_| PendingSubscriptionAcceptError)
+	}
+
+	/// Accept the subscription and send the items of `stream` as notifications until the stream ends
+	/// or the subscription is closed, for instance because the client unsubscribed.
+	///
+	/// Fails if the subscription couldn't be accepted or an item couldn't be serialized.
+	pub async fn pipe_from_stream<S, T>(self, stream: S) -> Result<(), StringError>
+	where
+		S: Stream<Item = T>,
+		T: Serialize,
+	{
+		let sink = self.accept().await?;
+		let mut stream = std::pin::pin!(stream);
+
+		loop {
+			tokio::select! {
+				_ = sink.closed() => return Ok(()),
+				item = stream.next() => {
+					let Some(item) = item else { return Ok(()) };
+					let msg = SubscriptionMessage::from_json(&item)?;
+
+					if sink.send(msg).await.is_err() {
+						return Ok(());
+					}
+				}
+			}
+		}
+	}
+
+	async fn accept_with_response(self, response: MethodResponse) -> Result<SubscriptionSink, AcceptError> {
 		let success = response.is_success();
 
 		// TODO: #1052
@@ -282,25 +417,62 @@ impl PendingSubscriptionSink {
 		//
 		// The same message is sent twice here because one is sent directly to the transport layer and
 		// the other one is sent internally to accept the subscription.
-		self.inner.send(response.to_result()).await.map_err(|_| PendingSubscriptionAcceptError)?;
-		self.subscribe.send(response).map_err(|_| PendingSubscriptionAcceptError)?;
-
-		if success {
+		//
+		// The subscription is registered before it's answered, such that the ID isn't given to another
+		// subscription of the connection once the client knows about it.
+		let unsubscribe = success.then(|| {
 			let (tx, rx) = mpsc::channel(1);
 			self.subscribers.lock().insert(self.uniq_sub.clone(), (self.inner.clone(), rx));
-			Ok(SubscriptionSink {
+			tx
+		});
+		let sent = match self.inner.send(response.to_result()).await {
+			Ok(()) => self.subscribe.send(response).is_ok(),
+			Err(_) => false,
+		};
+		if !sent {
+			if success {
+				self.subscribers.lock().remove(&self.uniq_sub);
+			}
+			return Err(AcceptError::Closed);
+		}
+
+		if let Some(tx) = unsubscribe {
+			let conn_id = self.uniq_sub.conn_id;
+			let notifier = Notifier {
 				inner: self.inner,
-				method: self.method,
 				subscribers: self.subscribers,
 				uniq_sub: self.uniq_sub,
 				unsubscribe: IsUnsubscribed(tx),
-				_permit: Arc::new(self.permit),
+				backpressure: self.config.backpressure().unwrap_or_default(),
+				pending: Default::default(),
+				sent: Default::default(),
+				subscribe_method: self.subscribe_method,
+				stats: self.stats.clone(),
+			};
+
+			if let Some(interval) = self.config.heartbeat_interval() {
+				tokio::spawn(heartbeat(notifier.clone(), interval, self.method));
+			}
+
+			Ok(SubscriptionSink {
+				notifier,
+				method: self.method,
+				_permit: Arc::new((self.permit, self.method_permit)),
+				coalesced: self.config.coalescing_window().map(|window| (window, Default::default())),
+				_stats: self
+					.stats
+					.map(|stats| Arc::new(ActiveSubscription::new(stats, self.subscribe_method, conn_id))),
 			})
 		} else {
-			panic!("The subscription response was too big; adjust the `max_response_size` or change Subscription ID generation");
+			Err(AcceptError::TooBig)
 		}
 	}
 
+	/// Get the ID of the subscription if it's accepted.
+	pub fn subscription_id(&self) -> SubscriptionId<'static> {
+		self.uniq_sub.sub_id.clone()
+	}
+
 	/// Returns connection identifier, which was used to perform pending subscription request
 	pub fn connection_id(&self) -> ConnectionId {
 		self.uniq_sub.conn_id
@@ -325,24 +497,52 @@ impl PendingSubscriptionSink {
 /// Represents a single subscription that hasn't been processed yet.
 #[derive(Debug, Clone)]
 pub struct SubscriptionSink {
-	/// Sink.
-	inner: MethodSink,
+	/// Sends out the notifications.
+	notifier: Notifier,
 	/// MethodCallback.
 	method: &'static str,
-	/// Shared Mutex of subscriptions for this method.
-	subscribers: Subscribers,
-	/// Unique subscription.
-	uniq_sub: SubscriptionKey,
-	/// A future to that fires once the unsubscribe method has been called.
-	unsubscribe: IsUnsubscribed,
-	/// Subscription permit
-	_permit: Arc<SubscriptionPermit>,
+	/// Subscription permits of the connection and of the subscribe method.
+	_permit: Arc<(SubscriptionPermit, Option<SubscriptionPermit>)>,
+	/// Notifications that are merged into one message if a coalescing window is configured.
+	coalesced: Option<(Duration, Arc<Mutex<Coalesced>>)>,
+	/// Keeps track of the subscription in the statistics.
+	_stats: Option<Arc<ActiveSubscription>>,
+}
+
+/// Notifications of a subscription which are buffered until the coalescing window elapses.
+#[derive(Debug, Default)]
+struct Coalesced {
+	jsons: Vec<SinkMessage>,
+	/// Number of times the buffer has been flushed early because it was full,
+	/// such that the timer of a flushed window doesn't flush the next window.
+	early_flushes: u64,
+}
+
+/// Counts a subscription as active in [`SubscriptionStats`] until dropped.
+#[derive(Debug)]
+struct ActiveSubscription {
+	stats: SubscriptionStats,
+	method: &'static str,
+	conn_id: ConnectionId,
+}
+
+impl ActiveSubscription {
+	fn new(stats: SubscriptionStats, method: &'static str, conn_id: ConnectionId) -> Self {
+		stats.subscribed(method, conn_id);
+		Self { stats, method, conn_id }
+	}
+}
+
+impl Drop for ActiveSubscription {
+	fn drop(&mut self) {
+		self.stats.unsubscribed(self.method, self.conn_id);
+	}
 }
 
 impl SubscriptionSink {
 	/// Get the subscription ID.
 	pub fn subscription_id(&self) -> SubscriptionId<'static> {
-		self.uniq_sub.sub_id.clone()
+		self.notifier.uniq_sub.sub_id.clone()
 	}
 
 	/// Get the method name.
@@ -352,14 +552,19 @@ impl SubscriptionSink {
 
 	/// Get the connection ID.
 	pub fn connection_id(&self) -> ConnectionId {
-		self.uniq_sub.conn_id
+		self.notifier.uniq_sub.conn_id
 	}
 
-	/// Send out a response on the subscription and wait until there is capacity.
+	/// Send out a response on the subscription.
+	///
+	/// If the send buffer of the connection is full it's handled according to
+	/// the [`BackpressurePolicy`] of the subscription which by default waits until there is capacity.
 	///
+	/// If a coalescing window is configured for the subscription the notification is
+	/// sent out together with the other notifications sent within the window.
 	///
 	/// Returns
-	/// - `Ok(())` if the message could be sent.
+	/// - `Ok(())` if the message could be sent or was dropped according to the [`BackpressurePolicy`].
 	/// - `Err(unsent_msg)` if the connection or subscription was closed.
 	///
 	/// # Cancel safety
@@ -371,8 +576,41 @@ impl SubscriptionSink {
 			return Err(DisconnectError(msg));
 		}
 
-		let json = sub_message_to_json(msg, SubNotifResultOrError::Result, &self.uniq_sub.sub_id, self.method);
-		self.inner.send(json).await.map_err(Into::into)
+		let json = self.to_json(msg);
+		self.send_json(json).await
+	}
+
+	async fn send_json(&self, json: SinkMessage) -> Result<(), DisconnectError> {
+		match &self.coalesced {
+			Some((window, coalesced)) => self.coalesce(*window, coalesced, std::iter::once(json)).await,
+			None => self.notifier.send(json).await,
+		}
+	}
+
+	/// Send out several notifications on the subscription as one message which is
+	/// a JSON array of notifications.
+	///
+	/// This is the same as [`SubscriptionSink::send`] except that it has less
+	/// overhead if the notifications are available at the same time.
+	///
+	/// Returns
+	/// - `Ok(())` if the messages could be sent or were dropped according to the [`BackpressurePolicy`].
+	/// - `Err(unsent_msg)` if the connection or subscription was closed.
+	pub async fn send_batch(&self, msgs: impl IntoIterator<Item = SubscriptionMessage>) -> Result<(), DisconnectError> {
+		let jsons: Vec<SinkMessage> = msgs.into_iter().map(|msg| self.to_json(msg)).collect();
+
+		if self.is_closed() {
+			return Err(DisconnectError(SubscriptionMessage::from_complete_message(batch_json(jsons, None))));
+		}
+
+		if jsons.is_empty() {
+			return Ok(());
+		}
+
+		match &self.coalesced {
+			Some((window, coalesced)) => self.coalesce(*window, coalesced, jsons).await,
+			None => self.notifier.send(batch_json(jsons, self.notifier.inner.buffer_pool()).into()).await,
+		}
 	}
 
 	/// Similar to `SubscriptionSink::send` but only waits for a limited time.
@@ -382,8 +620,12 @@ impl SubscriptionSink {
 			return Err(SendTimeoutError::Closed(msg));
 		}
 
-		let json = sub_message_to_json(msg, SubNotifResultOrError::Result, &self.uniq_sub.sub_id, self.method);
-		self.inner.send_timeout(json, timeout).await.map_err(Into::into)
+		let json = self.to_json(msg);
+		let started = Instant::now();
+		self.notifier.sent.store(true, Ordering::Relaxed);
+		self.notifier.inner.send_timeout(json, timeout).await?;
+		self.notifier.on_sent(started);
+		Ok(())
 	}
 
 	/// Attempts to immediately send out the message as JSON string to the subscribers but fails if the
@@ -398,43 +640,343 @@ impl SubscriptionSink {
 			return Err(TrySendError::Closed(msg));
 		}
 
-		let json = sub_message_to_json(msg, SubNotifResultOrError::Result, &self.uniq_sub.sub_id, self.method);
-		self.inner.try_send(json).map_err(Into::into)
+		let json = self.to_json(msg);
+		let started = Instant::now();
+		self.notifier.sent.store(true, Ordering::Relaxed);
+		self.notifier.inner.try_send(json)?;
+		self.notifier.on_sent(started);
+		Ok(())
 	}
 
 	/// Returns whether the subscription is closed.
 	pub fn is_closed(&self) -> bool {
-		self.inner.is_closed() || !self.is_active_subscription()
+		self.notifier.is_closed()
 	}
 
 	/// Completes when the subscription has been closed.
 	pub async fn closed(&self) {
 		// Both are cancel-safe thus ok to use select here.
 		tokio::select! {
-			_ = self.inner.closed() => (),
-			_ = self.unsubscribe.unsubscribed() => (),
+			_ = self.notifier.inner.closed() => (),
+			_ = self.notifier.unsubscribe.unsubscribed() => (),
 		}
 	}
 
 	/// Get the capacity of the subscription.
 	pub fn capacity(&self) -> usize {
-		self.inner.capacity()
+		self.notifier.inner.capacity()
 	}
 
 	/// Get the max capacity of the subscription.
 	pub fn max_capacity(&self) -> usize {
-		self.inner.max_capacity()
+		self.notifier.inner.max_capacity()
+	}
+
+	fn to_json(&self, msg: SubscriptionMessage) -> SinkMessage {
+		sub_message_to_json(msg, SubNotifResultOrError::Result, &self.notifier.uniq_sub.sub_id, self.method)
 	}
 
-	fn is_active_subscription(&self) -> bool {
-		!self.unsubscribe.is_unsubscribed()
+	/// Buffer the notifications and send them out once the window has elapsed
+	/// or once [`MAX_COALESCED_NOTIFICATIONS`] notifications are buffered.
+	async fn coalesce(
+		&self,
+		window: Duration,
+		coalesced: &Arc<Mutex<Coalesced>>,
+		jsons: impl IntoIterator<Item = SinkMessage>,
+	) -> Result<(), DisconnectError> {
+		let full = {
+			let mut buf = coalesced.lock();
+			let first = buf.jsons.is_empty();
+			buf.jsons.extend(jsons);
+
+			if buf.jsons.len() >= MAX_COALESCED_NOTIFICATIONS {
+				buf.early_flushes += 1;
+				Some(std::mem::take(&mut buf.jsons))
+			} else {
+				if first {
+					let notifier = self.notifier.clone();
+					let coalesced = coalesced.clone();
+					let early_flushes = buf.early_flushes;
+
+					tokio::spawn(async move {
+						tokio::time::sleep(window).await;
+						let jsons = {
+							let mut buf = coalesced.lock();
+							if buf.early_flushes != early_flushes {
+								return;
+							}
+							std::mem::take(&mut buf.jsons)
+						};
+
+						if notifier.is_closed() {
+							return;
+						}
+
+						let json = coalesced_json(jsons, &notifier);
+						_ = notifier.send(json).await;
+					});
+				}
+				None
+			}
+		};
+
+		match full {
+			Some(jsons) => self.notifier.send(coalesced_json(jsons, &self.notifier)).await,
+			None => Ok(()),
+		}
+	}
+}
+
+/// Merge the coalesced notifications into one message.
+fn coalesced_json(mut jsons: Vec<SinkMessage>, notifier: &Notifier) -> SinkMessage {
+	if jsons.len() == 1 {
+		jsons.remove(0)
+	} else {
+		batch_json(jsons, notifier.inner.buffer_pool()).into()
 	}
 }
 
 impl Drop for SubscriptionSink {
 	fn drop(&mut self) {
-		if self.is_active_subscription() {
-			self.subscribers.lock().remove(&self.uniq_sub);
+		if !self.notifier.unsubscribe.is_unsubscribed() {
+			self.notifier.subscribers.lock().remove(&self.notifier.uniq_sub);
+		}
+	}
+}
+
+/// Sends out the same notification to many subscriptions.
+///
+/// The notification is serialized once with [`SubscriptionMessage::from_json`] and only the
+/// subscription ID is filled in for each subscription, which is much cheaper than creating a
+/// [`SubscriptionMessage`] for each [`SubscriptionSink::send`] when there are many subscribers.
+///
+/// Subscriptions that have been closed are removed from the broadcast.
+///
+/// # Examples
+///
+/// ```no_run
+/// use jsonrpsee_core::server::{RpcModule, SubscriptionBroadcast, SubscriptionMessage};
+///
+/// let broadcast = SubscriptionBroadcast::new();
+/// let mut module = RpcModule::new(broadcast.clone());
+/// module.register_subscription("subscribe_heads", "heads", "unsubscribe_heads", |_, pending, broadcast, _| async move {
+///     let sink = pending.accept().await?;
+///     broadcast.add(sink);
+///     Ok(())
+/// }).unwrap();
+///
+/// # async fn new_head(broadcast: SubscriptionBroadcast) {
+/// let msg = SubscriptionMessage::from_json(&"0xdeadbeef").unwrap();
+/// broadcast.send(msg).await;
+/// # }
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct SubscriptionBroadcast {
+	sinks: Arc<Mutex<Vec<Arc<SubscriptionSink>>>>,
+}
+
+impl SubscriptionBroadcast {
+	/// Create a new broadcast without subscriptions.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Add a subscription to the broadcast.
+	pub fn add(&self, sink: SubscriptionSink) {
+		self.sinks.lock().push(Arc::new(sink));
+	}
+
+	/// Get the number of subscriptions in the broadcast.
+	///
+	/// This may include subscriptions that have been closed since the last [`SubscriptionBroadcast::send`].
+	pub fn len(&self) -> usize {
+		self.sinks.lock().len()
+	}
+
+	/// Returns whether the broadcast has no subscriptions.
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// Send out the notification to all subscriptions according to the
+	/// [`BackpressurePolicy`] of each subscription.
+	///
+	/// A message created with [`SubscriptionMessage::new`] is sent with the subscription ID
+	/// of each subscription instead of the one that it was created with.
+	///
+	/// Returns the number of subscriptions that the notification was sent to.
+	pub async fn send(&self, msg: SubscriptionMessage) -> usize {
+		let result = match &msg.0 {
+			SubscriptionMessageInner::Complete(json) => {
+				serde_json::from_str::<SubscriptionResponse<&serde_json::value::RawValue>>(json)
+					.ok()
+					.map(|notif| Arc::<str>::from(notif.params.result.get()))
+			}
+			SubscriptionMessageInner::NeedsData(_) => None,
+		};
+		let msg = result.map_or(msg, |result| SubscriptionMessage(SubscriptionMessageInner::NeedsData(result)));
+
+		let sinks = {
+			let mut sinks = self.sinks.lock();
+			sinks.retain(|s| !s.is_closed());
+			sinks.clone()
+		};
+
+		let sends = sinks.iter().map(|sink| {
+			let json = sub_message_to_json_borrowed(
+				&msg,
+				SubNotifResultOrError::Result,
+				&sink.notifier.uniq_sub.sub_id,
+				sink.method,
+			);
+			sink.send_json(json)
+		});
+
+		futures_util::future::join_all(sends).await.into_iter().filter(|r| r.is_ok()).count()
+	}
+}
+
+/// Sends out the notifications of a subscription according to its [`BackpressurePolicy`].
+#[derive(Debug, Clone)]
+struct Notifier {
+	/// Sink.
+	inner: MethodSink,
+	/// Shared Mutex of subscriptions for this method.
+	subscribers: Subscribers,
+	/// Unique subscription.
+	uniq_sub: SubscriptionKey,
+	/// A future to that fires once the unsubscribe method has been called.
+	unsubscribe: IsUnsubscribed,
+	/// What to do when the send buffer is full.
+	backpressure: BackpressurePolicy,
+	/// Notification that waits for space in the buffer with [`BackpressurePolicy::DropOldest`].
+	pending: Arc<Mutex<PendingNotification>>,
+	/// Whether a notification has been sent since the last heartbeat.
+	sent: Arc<AtomicBool>,
+	/// Name of the subscribe method which the statistics are tracked by.
+	subscribe_method: &'static str,
+	/// Statistics of the subscriptions on the server.
+	stats: Option<SubscriptionStats>,
+}
+
+impl Notifier {
+	fn is_closed(&self) -> bool {
+		self.inner.is_closed() || self.unsubscribe.is_unsubscribed()
+	}
+
+	async fn send(&self, json: SinkMessage) -> Result<(), DisconnectError> {
+		self.sent.store(true, Ordering::Relaxed);
+		let started = Instant::now();
+
+		match self.backpressure {
+			BackpressurePolicy::Wait => {
+				self.inner.send(json).await?;
+				self.on_sent(started);
+				Ok(())
+			}
+			BackpressurePolicy::WaitTimeout(timeout) => match self.inner.send_timeout(json, timeout).await {
+				Ok(()) => {
+					self.on_sent(started);
+					Ok(())
+				}
+				Err(SendTimeoutError::Timeout(_)) => {
+					self.on_dropped();
+					Ok(())
+				}
+				Err(SendTimeoutError::Closed(msg)) => Err(DisconnectError(msg)),
+			},
+			BackpressurePolicy::DropNewest => match self.inner.clone().try_send(json) {
+				Ok(()) => {
+					self.on_sent(started);
+					Ok(())
+				}
+				Err(TrySendError::Full(_)) => {
+					self.on_dropped();
+					Ok(())
+				}
+				Err(TrySendError::Closed(msg)) => Err(DisconnectError(msg)),
+			},
+			BackpressurePolicy::Disconnect => match self.inner.clone().try_send(json) {
+				Ok(()) => {
+					self.on_sent(started);
+					Ok(())
+				}
+				Err(TrySendError::Full(msg)) => {
+					tracing::debug!(
+						target: LOG_TARGET,
+						"Send buffer full; closing subscription={:?}",
+						self.uniq_sub.sub_id
+					);
+					self.on_dropped();
+					self.subscribers.lock().remove(&self.uniq_sub);
+					Err(DisconnectError(msg))
+				}
+				Err(TrySendError::Closed(msg)) => Err(DisconnectError(msg)),
+			},
+			BackpressurePolicy::DropOldest => self.send_drop_oldest(json, started),
+		}
+	}
+
+	fn send_drop_oldest(&self, json: SinkMessage, started: Instant) -> Result<(), DisconnectError> {
+		let mut pending = self.pending.lock();
+
+		// The buffer was full before, replace the older notification to preserve the order.
+		if pending.forwarding {
+			if pending.msg.replace((json, started)).is_some() {
+				self.on_dropped();
+			}
+			return Ok(());
+		}
+
+		match self.inner.clone().try_send(json) {
+			Ok(()) => {
+				self.on_sent(started);
+				Ok(())
+			}
+			Err(TrySendError::Closed(msg)) => Err(DisconnectError(msg)),
+			Err(TrySendError::Full(msg)) => {
+				let SubscriptionMessageInner::Complete(json) = msg.0 else {
+					unreachable!("messages that couldn't be sent are complete; qed");
+				};
+				pending.msg = Some((json.into(), started));
+				pending.forwarding = true;
+
+				let this = self.clone();
+
+				tokio::spawn(async move {
+					while this.inner.has_capacity().await.is_ok() {
+						let (msg, started) = {
+							let mut pending = this.pending.lock();
+							match pending.msg.take() {
+								Some(msg) => msg,
+								None => {
+									pending.forwarding = false;
+									return;
+								}
+							}
+						};
+
+						if this.inner.send(msg).await.is_err() {
+							return;
+						}
+						this.on_sent(started);
+					}
+				});
+
+				Ok(())
+			}
+		}
+	}
+
+	fn on_sent(&self, started: Instant) {
+		if let Some(stats) = &self.stats {
+			stats.sent(self.subscribe_method, self.uniq_sub.conn_id, started.elapsed());
+		}
+	}
+
+	fn on_dropped(&self) {
+		if let Some(stats) = &self.stats {
+			stats.dropped(self.subscribe_method, self.uniq_sub.conn_id);
 		}
 	}
 }
@@ -442,7 +984,7 @@ impl Drop for SubscriptionSink {
 /// Wrapper struct that maintains a subscription "mainly" for testing.
 #[derive(Debug)]
 pub struct Subscription {
-	pub(crate) rx: mpsc::Receiver<String>,
+	pub(crate) rx: mpsc::Receiver<SinkMessage>,
 	pub(crate) sub_id: SubscriptionId<'static>,
 }
 
@@ -460,7 +1002,7 @@ impl Subscription {
 
 	/// Receives the next value on the subscription if the value could be decoded as T.
 	pub async fn next<T: DeserializeOwned>(&mut self) -> Option<Result<(T, SubscriptionId<'static>), MethodsError>> {
-		let raw = self.rx.recv().await?;
+		let raw = self.rx.recv().await?.into_string();
 
 		tracing::debug!(target: LOG_TARGET, "[Subscription::next]: rx {}", raw);
 
@@ -488,12 +1030,17 @@ impl Drop for Subscription {
 pub struct BoundedSubscriptions {
 	guard: Arc<Semaphore>,
 	max: u32,
+	per_method: Arc<Mutex<FxHashMap<&'static str, Arc<Semaphore>>>>,
 }
 
 impl BoundedSubscriptions {
 	/// Create a new bounded subscription.
 	pub fn new(max_subscriptions: u32) -> Self {
-		Self { guard: Arc::new(Semaphore::new(max_subscriptions as usize)), max: max_subscriptions }
+		Self {
+			guard: Arc::new(Semaphore::new(max_subscriptions as usize)),
+			max: max_subscriptions,
+			per_method: Default::default(),
+		}
 	}
 
 	/// Attempts to acquire a subscription slot.
@@ -503,6 +1050,16 @@ impl BoundedSubscriptions {
 		Arc::clone(&self.guard).try_acquire_owned().ok()
 	}
 
+	/// Attempts to acquire a subscription slot of the subscribe method `method`
+	/// which is limited to `max` subscriptions.
+	///
+	/// Fails if `max` subscriptions of the method have been exceeded.
+	pub fn acquire_for_method(&self, method: &'static str, max: u32) -> Option<SubscriptionPermit> {
+		let guard =
+			self.per_method.lock().entry(method).or_insert_with(|| Arc::new(Semaphore::new(max as usize))).clone();
+		guard.try_acquire_owned().ok()
+	}
+
 	/// Get the maximum number of permitted subscriptions.
 	pub const fn max(&self) -> u32 {
 		self.max
@@ -518,6 +1075,90 @@ pub struct SubscriptionState<'a> {
 	pub id_provider: &'a dyn IdProvider,
 	/// Subscription limit
 	pub subscription_permit: SubscriptionPermit,
+	/// Configuration of the subscribe method.
+	pub(crate) config: MethodConfig,
+	/// Subscription limit of the subscribe method, if it's limited per connection.
+	pub(crate) method_permit: Option<SubscriptionPermit>,
+}
+
+impl<'a> SubscriptionState<'a> {
+	/// Create a new [`SubscriptionState`].
+	pub fn new(
+		conn_id: ConnectionId,
+		id_provider: &'a dyn IdProvider,
+		subscription_permit: SubscriptionPermit,
+		config: MethodConfig,
+	) -> Self {
+		Self { conn_id, id_provider, subscription_permit, config, method_permit: None }
+	}
+
+	/// Set the subscription limit of the subscribe method, if it's limited per connection.
+	pub fn with_method_permit(mut self, method_permit: Option<SubscriptionPermit>) -> Self {
+		self.method_permit = method_permit;
+		self
+	}
+
+	/// Get the configuration of the subscribe method.
+	pub fn config(&self) -> &MethodConfig {
+		&self.config
+	}
+
+	/// The key of a new subscription to `method` with `params` on the connection.
+	///
+	/// If the ID which is derived from the params is already used by an active subscription
+	/// of the connection, the ID is given by [`IdProvider::next_id`] instead.
+	pub(crate) fn subscription_key(&self, subscribers: &Subscribers, method: &str, params: &Params) -> SubscriptionKey {
+		let key = SubscriptionKey { conn_id: self.conn_id, sub_id: self.id_provider.next_id_for(method, params) };
+
+		if subscribers.lock().contains_key(&key) {
+			SubscriptionKey { conn_id: self.conn_id, sub_id: self.id_provider.next_id() }
+		} else {
+			key
+		}
+	}
+}
+
+/// Sends out a heartbeat notification if no notification has been sent on
+/// the subscription during the last `interval` until the subscription is closed.
+///
+/// The heartbeat has the following format and is dropped if the send buffer is full:
+///
+/// ```json
+/// {"jsonrpc":"2.0","method":"<method>","params":{"subscription":"<subscriptionID>","heartbeat":true}}
+/// ```
+async fn heartbeat(notifier: Notifier, interval: Duration, method: &'static str) {
+	let sub_id = serde_json::to_string(&notifier.uniq_sub.sub_id).expect("valid JSON; qed");
+	let json =
+		format!(r#"{{"jsonrpc":"2.0","method":"{method}","params":{{"subscription":{sub_id},"heartbeat":true}}}}"#);
+	let mut inner = notifier.inner.clone();
+
+	let mut ticks = tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+	ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+	loop {
+		tokio::select! {
+			_ = notifier.inner.closed() => break,
+			_ = notifier.unsubscribe.unsubscribed() => break,
+			_ = ticks.tick() => {
+				if !notifier.sent.swap(false, Ordering::Relaxed) && matches!(inner.try_send(json.clone()), Err(TrySendError::Closed(_))) {
+					break;
+				}
+			}
+		}
+	}
+}
+
+/// Similar to [`sub_message_to_json`] but shares the message such that it can be reused.
+fn sub_message_to_json_borrowed(
+	msg: &SubscriptionMessage,
+	result_or_err: SubNotifResultOrError,
+	sub_id: &SubscriptionId,
+	method: &str,
+) -> SinkMessage {
+	match &msg.0 {
+		SubscriptionMessageInner::Complete(msg) => SinkMessage::Json(msg.clone()),
+		SubscriptionMessageInner::NeedsData(result) => notification(result.clone(), result_or_err, sub_id, method),
+	}
 }
 
 pub(crate) fn sub_message_to_json(
@@ -525,16 +1166,24 @@ pub(crate) fn sub_message_to_json(
 	result_or_err: SubNotifResultOrError,
 	sub_id: &SubscriptionId,
 	method: &str,
-) -> String {
-	let result_or_err = result_or_err.as_str();
-
+) -> SinkMessage {
 	match msg.0 {
-		SubscriptionMessageInner::Complete(msg) => msg,
-		SubscriptionMessageInner::NeedsData(result) => {
-			let sub_id = serde_json::to_string(&sub_id).expect("valid JSON; qed");
-			format!(
-				r#"{{"jsonrpc":"2.0","method":"{method}","params":{{"subscription":{sub_id},"{result_or_err}":{result}}}}}"#,
-			)
-		}
+		SubscriptionMessageInner::Complete(msg) => SinkMessage::Json(msg),
+		SubscriptionMessageInner::NeedsData(result) => notification(result, result_or_err, sub_id, method),
 	}
 }
+
+/// Create a notification whose `result` is shared instead of copied.
+fn notification(
+	result: Arc<str>,
+	result_or_err: SubNotifResultOrError,
+	sub_id: &SubscriptionId,
+	method: &str,
+) -> SinkMessage {
+	let result_or_err = result_or_err.as_str();
+	let sub_id = serde_json::to_string(&sub_id).expect("valid JSON; qed");
+	let head =
+		format!(r#"{{"jsonrpc":"2.0","method":"{method}","params":{{"subscription":{sub_id},"{result_or_err}":"#);
+
+	SinkMessage::Notification { head, result }
+}
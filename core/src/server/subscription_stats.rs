@@ -0,0 +1,166 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any
+// person obtaining a copy of this software and associated
+// documentation files (the "Software"), to deal in the
+// Software without restriction, including without
+// limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software
+// is furnished to do so, subject to the following
+// conditions:
+//
+// The above copyright notice and this permission notice
+// shall be included in all copies or substantial portions
+// of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF
+// ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED
+// TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A
+// PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT
+// SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY
+// CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+// IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Subscription statistics.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use rustc_hash::FxHashMap;
+
+use crate::server::ConnectionId;
+
+/// Counters of one or more subscriptions.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct SubscriptionCounters {
+	/// Number of currently active subscriptions.
+	pub active: usize,
+	/// Total number of notifications sent out.
+	pub notifications_sent: u64,
+	/// Total number of notifications dropped due to the backpressure policy.
+	pub notifications_dropped: u64,
+	/// Total time spent waiting for the notifications to be sent out.
+	pub total_send_latency: Duration,
+}
+
+impl SubscriptionCounters {
+	/// Average time it took to send out a notification to the connection.
+	pub fn average_send_latency(&self) -> Option<Duration> {
+		if self.notifications_sent == 0 {
+			return None;
+		}
+
+		let nanos = self.total_send_latency.as_nanos() / u128::from(self.notifications_sent);
+		Some(Duration::from_nanos(nanos as u64))
+	}
+
+	fn add(&mut self, other: &Self) {
+		self.active += other.active;
+		self.notifications_sent += other.notifications_sent;
+		self.notifications_dropped += other.notifications_dropped;
+		self.total_send_latency += other.total_send_latency;
+	}
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+	/// Counters of the connections that have active subscriptions.
+	live: FxHashMap<(&'static str, ConnectionId), SubscriptionCounters>,
+	/// Counters of subscriptions that have been closed.
+	closed: FxHashMap<&'static str, SubscriptionCounters>,
+}
+
+/// Statistics of the subscriptions on a server.
+///
+/// The server inserts it in the [`Extensions`](http::Extensions) of every call
+/// such that the RPC middleware can feed the statistics to metrics.
+#[derive(Debug, Default, Clone)]
+pub struct SubscriptionStats(Arc<Mutex<Inner>>);
+
+impl SubscriptionStats {
+	/// Create new empty statistics.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Get the counters of each subscription by the name of the subscribe method.
+	pub fn by_method(&self) -> FxHashMap<&'static str, SubscriptionCounters> {
+		let inner = self.0.lock();
+		let mut by_method = inner.closed.clone();
+
+		for ((method, _), counters) in inner.live.iter() {
+			by_method.entry(method).or_default().add(counters);
+		}
+
+		by_method
+	}
+
+	/// Get the counters of each connection that has active subscriptions.
+	pub fn by_connection(&self) -> FxHashMap<ConnectionId, SubscriptionCounters> {
+		let mut by_conn = FxHashMap::<_, SubscriptionCounters>::default();
+
+		for ((_, conn_id), counters) in self.0.lock().live.iter() {
+			by_conn.entry(*conn_id).or_default().add(counters);
+		}
+
+		by_conn
+	}
+
+	pub(crate) fn subscribed(&self, method: &'static str, conn_id: ConnectionId) {
+		self.0.lock().live.entry((method, conn_id)).or_default().active += 1;
+	}
+
+	pub(crate) fn unsubscribed(&self, method: &'static str, conn_id: ConnectionId) {
+		let mut inner = self.0.lock();
+		let Some(counters) = inner.live.get_mut(&(method, conn_id)) else {
+			return;
+		};
+		counters.active -= 1;
+
+		if counters.active == 0 {
+			let counters = inner.live.remove(&(method, conn_id)).expect("exists; qed");
+			inner.closed.entry(method).or_default().add(&counters);
+		}
+	}
+
+	pub(crate) fn sent(&self, method: &'static str, conn_id: ConnectionId, latency: Duration) {
+		if let Some(counters) = self.0.lock().live.get_mut(&(method, conn_id)) {
+			counters.notifications_sent += 1;
+			counters.total_send_latency += latency;
+		}
+	}
+
+	pub(crate) fn dropped(&self, method: &'static str, conn_id: ConnectionId) {
+		if let Some(counters) = self.0.lock().live.get_mut(&(method, conn_id)) {
+			counters.notifications_dropped += 1;
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::SubscriptionCounters;
+	use std::time::Duration;
+
+	#[test]
+	fn average_send_latency_works() {
+		let counters = SubscriptionCounters::default();
+		assert_eq!(counters.average_send_latency(), None);
+
+		let counters =
+			SubscriptionCounters { notifications_sent: 4, total_send_latency: Duration::from_millis(10), ..Default::default() };
+		assert_eq!(counters.average_send_latency(), Some(Duration::from_micros(2500)));
+
+		// More notifications than fit in a `u32`.
+		let counters = SubscriptionCounters {
+			notifications_sent: 1 << 33,
+			total_send_latency: Duration::from_secs(1 << 33),
+			..Default::default()
+		};
+		assert_eq!(counters.average_send_latency(), Some(Duration::from_secs(1)));
+	}
+}
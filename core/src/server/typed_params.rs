@@ -0,0 +1,224 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any
+// person obtaining a copy of this software and associated
+// documentation files (the "Software"), to deal in the
+// Software without restriction, including without
+// limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software
+// is furnished to do so, subject to the following
+// conditions:
+//
+// The above copyright notice and this permission notice
+// shall be included in all copies or substantial portions
+// of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF
+// ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED
+// TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A
+// PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT
+// SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY
+// CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+// IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::cell::RefCell;
+
+use jsonrpsee_types::{ErrorObjectOwned, InvalidParam, Params};
+use serde::de::{self, DeserializeOwned, DeserializeSeed, Visitor};
+use serde::forward_to_deserialize_any;
+use serde_json::{Map, Value};
+
+/// Deserialize the params of a call into `P` for [`crate::server::RpcModule::register_typed_method`].
+///
+/// The params may be given either positionally as an array or by name as an object and the
+/// `Invalid params` error names the field whose value couldn't be deserialized, see [`InvalidParam`].
+/// Positional params which are left out at the end are regarded as `null`, such that trailing
+/// optional fields may be omitted.
+pub(crate) fn parse_typed_params<P: DeserializeOwned>(params: &Params) -> Result<P, ErrorObjectOwned> {
+	let value = match params.as_str() {
+		Some(json) => serde_json::from_str(json).map_err(|e| InvalidParam::new(e).into_error())?,
+		// Calls without params are regarded as an empty object such that
+		// structs with only optional fields can be called without params.
+		None => match P::deserialize(Value::Null) {
+			Ok(params) => return Ok(params),
+			Err(_) => Value::Object(Map::new()),
+		},
+	};
+
+	let field = RefCell::new(Field::default());
+	P::deserialize(TrackedValue { value, fields: &[], len: 0, field: &field })
+		.map_err(|e: serde_json::Error| invalid_params(e, field.into_inner()))
+}
+
+fn invalid_params(e: serde_json::Error, field: Field) -> ErrorObjectOwned {
+	let param = match (field.position, field.name) {
+		(position, Some(name)) => {
+			let param = InvalidParam::new(format!("invalid value for field `{name}`: {e}")).with_name(&name);
+			match position {
+				Some(position) => param.with_position(position),
+				None => param,
+			}
+		}
+		(Some(position), None) => {
+			InvalidParam::new(format!("invalid value for field `{position}`: {e}")).with_position(position)
+		}
+		(None, None) => InvalidParam::new(e),
+	};
+	param.into_error()
+}
+
+/// The top-level param which is being deserialized.
+#[derive(Debug, Default)]
+struct Field {
+	position: Option<usize>,
+	name: Option<String>,
+}
+
+/// Deserializer of the params which keeps track of the field that is being deserialized.
+struct TrackedValue<'a> {
+	value: Value,
+	/// Names of the struct fields, used to name the positional params.
+	fields: &'static [&'static str],
+	/// Number of fields of the struct or tuple, the missing positional params are padded up to it.
+	len: usize,
+	field: &'a RefCell<Field>,
+}
+
+impl<'de, 'a> de::Deserializer<'de> for TrackedValue<'a> {
+	type Error = serde_json::Error;
+
+	fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+		match self.value {
+			Value::Object(map) => {
+				visitor.visit_map(TrackedMap { iter: map.into_iter(), value: None, field: self.field })
+			}
+			Value::Array(seq) => visitor.visit_seq(TrackedSeq {
+				iter: seq.into_iter(),
+				index: 0,
+				fields: self.fields,
+				len: self.len,
+				field: self.field,
+			}),
+			value => value.deserialize_any(visitor),
+		}
+	}
+
+	fn deserialize_struct<V: Visitor<'de>>(
+		self,
+		_name: &'static str,
+		fields: &'static [&'static str],
+		visitor: V,
+	) -> Result<V::Value, Self::Error> {
+		TrackedValue { fields, len: fields.len(), ..self }.deserialize_any(visitor)
+	}
+
+	fn deserialize_tuple<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error> {
+		TrackedValue { len, ..self }.deserialize_any(visitor)
+	}
+
+	fn deserialize_tuple_struct<V: Visitor<'de>>(
+		self,
+		_name: &'static str,
+		len: usize,
+		visitor: V,
+	) -> Result<V::Value, Self::Error> {
+		TrackedValue { len, ..self }.deserialize_any(visitor)
+	}
+
+	fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+		match self.value {
+			Value::Null => visitor.visit_none(),
+			_ => visitor.visit_some(self),
+		}
+	}
+
+	fn deserialize_newtype_struct<V: Visitor<'de>>(
+		self,
+		_name: &'static str,
+		visitor: V,
+	) -> Result<V::Value, Self::Error> {
+		visitor.visit_newtype_struct(self)
+	}
+
+	fn deserialize_enum<V: Visitor<'de>>(
+		self,
+		name: &'static str,
+		variants: &'static [&'static str],
+		visitor: V,
+	) -> Result<V::Value, Self::Error> {
+		self.value.deserialize_enum(name, variants, visitor)
+	}
+
+	forward_to_deserialize_any! {
+		bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+		bytes byte_buf unit unit_struct seq map identifier ignored_any
+	}
+}
+
+/// Positional params, only the top-level params are tracked.
+struct TrackedSeq<'a> {
+	iter: std::vec::IntoIter<Value>,
+	index: usize,
+	fields: &'static [&'static str],
+	len: usize,
+	field: &'a RefCell<Field>,
+}
+
+impl<'de, 'a> de::SeqAccess<'de> for TrackedSeq<'a> {
+	type Error = serde_json::Error;
+
+	fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error> {
+		// The params which are left out at the end are `null` such that optional fields can be omitted.
+		let value = match self.iter.next() {
+			Some(value) => value,
+			None if self.index < self.len => Value::Null,
+			None => {
+				*self.field.borrow_mut() = Field::default();
+				return Ok(None);
+			}
+		};
+
+		let name = self.fields.get(self.index).map(|name| (*name).to_owned());
+		*self.field.borrow_mut() = Field { position: Some(self.index), name };
+		self.index += 1;
+		seed.deserialize(value).map(Some)
+	}
+
+	fn size_hint(&self) -> Option<usize> {
+		Some(self.iter.len().max(self.len.saturating_sub(self.index)))
+	}
+}
+
+/// Params by name, only the top-level params are tracked.
+struct TrackedMap<'a> {
+	iter: serde_json::map::IntoIter,
+	value: Option<Value>,
+	field: &'a RefCell<Field>,
+}
+
+impl<'de, 'a> de::MapAccess<'de> for TrackedMap<'a> {
+	type Error = serde_json::Error;
+
+	fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error> {
+		let Some((key, value)) = self.iter.next() else {
+			*self.field.borrow_mut() = Field::default();
+			return Ok(None);
+		};
+
+		*self.field.borrow_mut() = Field { position: None, name: Some(key.clone()) };
+		self.value = Some(value);
+		seed.deserialize(Value::String(key)).map(Some)
+	}
+
+	fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Self::Error> {
+		let value = self.value.take().ok_or_else(|| de::Error::custom("value is missing"))?;
+		seed.deserialize(value)
+	}
+
+	fn size_hint(&self) -> Option<usize> {
+		Some(self.iter.len())
+	}
+}
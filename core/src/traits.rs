@@ -24,9 +24,10 @@
 // IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
 // DEALINGS IN THE SOFTWARE.
 
-use jsonrpsee_types::SubscriptionId;
+use jsonrpsee_types::{Params, SubscriptionId};
 use serde::Serialize;
 use serde_json::value::RawValue;
+use serde_json::Value as JsonValue;
 
 /// Marker trait for types that can be serialized as JSON compatible strings.
 ///
@@ -138,6 +139,18 @@ tuple_impls! {
 pub trait IdProvider: Send + Sync + std::fmt::Debug {
 	/// Returns the next ID for the subscription.
 	fn next_id(&self) -> SubscriptionId<'static>;
+
+	/// Returns the next ID for a subscription to `method` with `params`, such that the ID
+	/// may be derived from the params of the subscription.
+	///
+	/// If the ID is already used by an active subscription of the connection, the subscription
+	/// gets an ID from [`IdProvider::next_id`] instead.
+	///
+	/// By default the method and params are ignored and the ID is given by [`IdProvider::next_id`].
+	fn next_id_for(&self, method: &str, params: &Params) -> SubscriptionId<'static> {
+		let _ = (method, params);
+		self.next_id()
+	}
 }
 
 // Implement `IdProvider` for `Box<T>`
@@ -149,4 +162,63 @@ impl<T: IdProvider + ?Sized> IdProvider for Box<T> {
 	fn next_id(&self) -> SubscriptionId<'static> {
 		(**self).next_id()
 	}
+
+	fn next_id_for(&self, method: &str, params: &Params) -> SubscriptionId<'static> {
+		(**self).next_id_for(method, params)
+	}
+}
+
+/// Trait to parse the subscription IDs sent by the server, which is used by the client.
+///
+/// The ID in the response to the subscription call, the IDs in the notifications of the subscription
+/// and the ID which is sent to unsubscribe are all handled by the parser, such that a client can
+/// support subscription IDs which are neither a string nor a number.
+///
+/// ```
+/// use jsonrpsee_core::traits::SubscriptionIdParser;
+/// use jsonrpsee_types::SubscriptionId;
+/// use serde_json::{json, Value};
+///
+/// /// Subscription IDs given as any JSON value, which is kept as a string.
+/// #[derive(Debug)]
+/// struct AnyJsonId;
+///
+/// impl SubscriptionIdParser for AnyJsonId {
+///     fn parse(&self, id: &Value) -> Option<SubscriptionId<'static>> {
+///         Some(SubscriptionId::Str(id.to_string().into()))
+///     }
+///
+///     fn to_json(&self, id: &SubscriptionId) -> Value {
+///         match id {
+///             SubscriptionId::Str(s) => serde_json::from_str(s).unwrap_or_else(|_| s.as_ref().into()),
+///             SubscriptionId::Num(n) => (*n).into(),
+///         }
+///     }
+/// }
+///
+/// let id = json!({"shard": 1, "id": 7});
+/// let sub_id = AnyJsonId.parse(&id).unwrap();
+/// assert_eq!(AnyJsonId.to_json(&sub_id), id);
+/// ```
+pub trait SubscriptionIdParser: Send + Sync + std::fmt::Debug {
+	/// Parse the subscription ID sent by the server, returns `None` if the ID is invalid.
+	fn parse(&self, id: &JsonValue) -> Option<SubscriptionId<'static>>;
+
+	/// Convert the subscription ID back into the JSON value sent by the server, which is
+	/// the param of the call to unsubscribe.
+	///
+	/// By default the ID is sent as a string or a number.
+	fn to_json(&self, id: &SubscriptionId) -> JsonValue {
+		id.clone().into()
+	}
+}
+
+impl<T: SubscriptionIdParser + ?Sized> SubscriptionIdParser for std::sync::Arc<T> {
+	fn parse(&self, id: &JsonValue) -> Option<SubscriptionId<'static>> {
+		(**self).parse(id)
+	}
+
+	fn to_json(&self, id: &SubscriptionId) -> JsonValue {
+		(**self).to_json(id)
+	}
 }
@@ -0,0 +1,134 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any
+// person obtaining a copy of this software and associated
+// documentation files (the "Software"), to deal in the
+// Software without restriction, including without
+// limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software
+// is furnished to do so, subject to the following
+// conditions:
+//
+// The above copyright notice and this permission notice
+// shall be included in all copies or substantial portions
+// of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF
+// ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED
+// TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A
+// PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT
+// SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY
+// CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+// IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Example of a custom client transport which connects the core async client to an
+//! in-process [`RpcModule`] over channels, without any networking involved.
+
+use jsonrpsee::core::async_trait;
+use jsonrpsee::core::client::{
+	Client, ClientBuilder, ClientT, ReceivedMessage, SubscriptionClientT, TransportReceiverT, TransportSenderT,
+};
+use jsonrpsee::rpc_params;
+use jsonrpsee::server::{RpcModule, SubscriptionMessage};
+use tokio::sync::mpsc;
+
+#[derive(Debug)]
+struct Closed;
+
+impl std::fmt::Display for Closed {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str("in-process server is closed")
+	}
+}
+
+impl std::error::Error for Closed {}
+
+struct Sender(mpsc::UnboundedSender<String>);
+
+#[async_trait]
+impl TransportSenderT for Sender {
+	type Error = Closed;
+
+	async fn send(&mut self, msg: String) -> Result<(), Self::Error> {
+		self.0.send(msg).map_err(|_| Closed)
+	}
+}
+
+struct Receiver(mpsc::UnboundedReceiver<String>);
+
+#[async_trait]
+impl TransportReceiverT for Receiver {
+	type Error = Closed;
+
+	async fn receive(&mut self) -> Result<ReceivedMessage, Self::Error> {
+		// Each message must be exactly one JSON-RPC response, batch response or notification.
+		self.0.recv().await.map(ReceivedMessage::Text).ok_or(Closed)
+	}
+}
+
+/// Create a transport which sends every request to `module` and sends back its responses
+/// and subscription notifications.
+///
+/// NOTE: batch requests are not supported by this transport.
+fn in_process_transport(module: RpcModule<()>) -> (Sender, Receiver) {
+	let (to_server, mut from_client) = mpsc::unbounded_channel::<String>();
+	let (to_client, from_server) = mpsc::unbounded_channel();
+
+	tokio::spawn(async move {
+		while let Some(request) = from_client.recv().await {
+			let Ok((response, mut notifs)) = module.raw_json_request(&request, 16).await else {
+				tracing::warn!("Unsupported request: {request}");
+				continue;
+			};
+
+			if to_client.send(response).is_err() {
+				break;
+			}
+
+			let to_client = to_client.clone();
+			tokio::spawn(async move {
+				while let Some(notif) = notifs.recv().await {
+					if to_client.send(notif.into_string()).is_err() {
+						break;
+					}
+				}
+			});
+		}
+	});
+
+	(Sender(to_server), Receiver(from_server))
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+	tracing_subscriber::FmtSubscriber::builder()
+		.with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+		.try_init()
+		.expect("setting default subscriber failed");
+
+	let mut module = RpcModule::new(());
+	module.register_method("say_hello", |_, _, _| "lo")?;
+	module.register_subscription("subscribe_hello", "hello", "unsubscribe_hello", |_, pending, _, _| async move {
+		let sink = pending.accept().await?;
+		for i in 0..3 {
+			sink.send(SubscriptionMessage::from_json(&i)?).await?;
+		}
+		Ok(())
+	})?;
+
+	let (tx, rx) = in_process_transport(module);
+	let client: Client = ClientBuilder::default().build_with_tokio(tx, rx);
+
+	let response: String = client.request("say_hello", rpc_params![]).await?;
+	tracing::info!("response: {:?}", response);
+
+	let mut sub = client.subscribe::<usize, _>("subscribe_hello", rpc_params![], "unsubscribe_hello").await?;
+	for _ in 0..3 {
+		tracing::info!("notification: {:?}", sub.next().await);
+	}
+
+	Ok(())
+}
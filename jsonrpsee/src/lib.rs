@@ -48,6 +48,11 @@
 //! - **`client-ws-transport`** - Enables `ws` transport with TLS.
 //! - **`client-ws-transport-no-tls`** - Enables `ws` transport without TLS.
 //! - **`client-web-transport`** - Enables `websys` transport.
+//! - **`arbitrary-precision`** - Enables `serde_json/arbitrary_precision` such that numbers
+//!   in params and results, for instance u256 values passed through `serde_json::Value`,
+//!   are not rounded through `f64`. Not included in `full`.
+//! - **`mock`** - Enables the `MockClient` and the mock clients generated by the `mock` argument of
+//!   the `rpc` macro, for unit tests. Not included in `full`.
 
 #![warn(missing_docs, missing_debug_implementations, missing_copy_implementations, unreachable_pub)]
 #![cfg_attr(not(test), warn(unused_crate_dependencies))]
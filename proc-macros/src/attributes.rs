@@ -41,7 +41,7 @@ pub(crate) struct Argument {
 	pub tokens: TokenStream2,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy)]
 pub enum ParamKind {
 	Array,
 	Map,
@@ -224,13 +224,13 @@ where
 	arg.ok().map(transform).transpose()
 }
 
-pub(crate) fn parse_param_kind(arg: Result<Argument, MissingArgument>) -> syn::Result<ParamKind> {
+pub(crate) fn parse_param_kind(arg: Result<Argument, MissingArgument>) -> syn::Result<Option<ParamKind>> {
 	let kind: Option<syn::Ident> = optional(arg, Argument::value)?;
 
 	match kind {
-		None => Ok(ParamKind::Array),
-		Some(ident) if ident == "array" => Ok(ParamKind::Array),
-		Some(ident) if ident == "map" => Ok(ParamKind::Map),
+		None => Ok(None),
+		Some(ident) if ident == "array" => Ok(Some(ParamKind::Array)),
+		Some(ident) if ident == "map" => Ok(Some(ParamKind::Map)),
 		ident => Err(Error::new(ident.span(), "param_kind must be either `map` or `array`")),
 	}
 }
@@ -30,7 +30,7 @@ use crate::visitor::{FindAllParams, FindSubscriptionParams};
 use proc_macro2::{Span, TokenStream as TokenStream2};
 use proc_macro_crate::{crate_name, FoundCrate};
 use quote::quote;
-use syn::{parse_quote, punctuated::Punctuated, token::Comma, visit::Visit, Token, WherePredicate};
+use syn::{parse::Parser, parse_quote, punctuated::Punctuated, token::Comma, visit::Visit, Token, WherePredicate};
 
 /// Search for client-side `jsonrpsee` in `Cargo.toml`.
 pub(crate) fn find_jsonrpsee_client_crate() -> Result<proc_macro2::TokenStream, syn::Error> {
@@ -124,26 +124,7 @@ pub(crate) fn generate_where_clause(
 		.type_params()
 		.map(|ty| {
 			let ty_path = syn::TypePath { qself: None, path: ty.ident.clone().into() };
-			let mut bounds: Punctuated<syn::TypeParamBound, Token![+]> = parse_quote!(Send + Sync + 'static);
-
-			if is_client {
-				if visitor.input_params.contains(&ty.ident) {
-					bounds.push(parse_quote!(jsonrpsee::core::Serialize))
-				}
-				if visitor.ret_params.contains(&ty.ident) || visitor.sub_params.contains(&ty.ident) {
-					bounds.push(parse_quote!(jsonrpsee::core::DeserializeOwned))
-				}
-			} else {
-				if visitor.input_params.contains(&ty.ident) {
-					bounds.push(parse_quote!(jsonrpsee::core::DeserializeOwned))
-				}
-				if visitor.ret_params.contains(&ty.ident) {
-					bounds.push(parse_quote!(std::clone::Clone))
-				}
-				if visitor.ret_params.contains(&ty.ident) || visitor.sub_params.contains(&ty.ident) {
-					bounds.push(parse_quote!(jsonrpsee::core::Serialize))
-				}
-			}
+			let mut bounds = generate_bounds(&visitor, &ty.ident, is_client);
 
 			// Add the trait bounds specified in the trait.
 			if let Some(where_clause) = &additional_where_clause {
@@ -168,11 +149,76 @@ pub(crate) fn generate_where_clause(
 		.collect()
 }
 
+/// Generates the associated types of the server trait with the bounds they need
+/// depending on how they're used in the trait, see [`generate_where_clause`].
+///
+/// If custom bounds are provided, only the bounds declared on the associated types are kept.
+pub(crate) fn generate_server_assoc_types(
+	item_trait: &syn::ItemTrait,
+	sub_tys: &[syn::Type],
+	assoc_types: &[syn::TraitItemType],
+	bounds: Option<&Punctuated<WherePredicate, Comma>>,
+) -> Vec<syn::TraitItemType> {
+	let visitor = visit_trait(item_trait, sub_tys);
+
+	assoc_types
+		.iter()
+		.map(|ty| {
+			let mut ty = ty.clone();
+
+			if bounds.is_none() {
+				ty.colon_token.get_or_insert_with(Default::default);
+				ty.bounds.extend(generate_bounds(&visitor, &ty.ident, false));
+			}
+
+			ty
+		})
+		.collect()
+}
+
+/// The bounds of a generic parameter or associated type depending on where it's used in the trait.
+fn generate_bounds(
+	visitor: &FindAllParams,
+	ident: &syn::Ident,
+	is_client: bool,
+) -> Punctuated<syn::TypeParamBound, Token![+]> {
+	let mut bounds: Punctuated<syn::TypeParamBound, Token![+]> = parse_quote!(Send + Sync + 'static);
+
+	if is_client {
+		if visitor.input_params.contains(ident) {
+			bounds.push(parse_quote!(jsonrpsee::core::Serialize))
+		}
+		if visitor.ret_params.contains(ident) || visitor.sub_params.contains(ident) {
+			bounds.push(parse_quote!(jsonrpsee::core::DeserializeOwned))
+		}
+	} else {
+		if visitor.input_params.contains(ident) {
+			bounds.push(parse_quote!(jsonrpsee::core::DeserializeOwned))
+		}
+		if visitor.ret_params.contains(ident) {
+			bounds.push(parse_quote!(std::clone::Clone))
+		}
+		if visitor.ret_params.contains(ident) || visitor.sub_params.contains(ident) {
+			bounds.push(parse_quote!(jsonrpsee::core::Serialize))
+		}
+	}
+
+	bounds
+}
+
 /// Traverse the RPC trait by first finding the subscription parameters and then all elements
 /// needed for generating the `client` and `server` traits/implementations.
 fn visit_trait(item_trait: &syn::ItemTrait, sub_tys: &[syn::Type]) -> FindAllParams {
 	let type_params: HashSet<_> = item_trait.generics.type_params().map(|t| t.ident.clone()).collect();
-	let sub_tys = FindSubscriptionParams::new(type_params).visit(sub_tys);
+	let assoc_types: HashSet<_> = item_trait
+		.items
+		.iter()
+		.filter_map(|item| match item {
+			syn::TraitItem::Type(ty) => Some(ty.ident.clone()),
+			_ => None,
+		})
+		.collect();
+	let sub_tys = FindSubscriptionParams::new(type_params, assoc_types).visit(sub_tys);
 	let mut visitor = FindAllParams::new(sub_tys);
 	visitor.visit_item_trait(item_trait);
 	visitor
@@ -193,6 +239,21 @@ pub(crate) fn is_option(ty: &syn::Type) -> bool {
 	false
 }
 
+/// Returns `T` if the type is `Option<T>`.
+pub(crate) fn option_type(ty: &syn::Type) -> Option<&syn::Type> {
+	if !is_option(ty) {
+		return None;
+	}
+
+	let syn::Type::Path(path) = ty else { return None };
+	let syn::PathArguments::AngleBracketed(args) = &path.path.segments.last()?.arguments else { return None };
+
+	args.args.iter().find_map(|arg| match arg {
+		syn::GenericArgument::Type(ty) => Some(ty),
+		_ => None,
+	})
+}
+
 /// Iterates over all Attribute's and parses only the attributes that are doc comments.
 ///
 /// Note that `doc comments` are expanded into `#[doc = "some comment"]`
@@ -208,9 +269,97 @@ pub(crate) fn extract_doc_comments(attrs: &[syn::Attribute]) -> TokenStream2 {
 	quote! ( #(#docs)* )
 }
 
+/// Returns the text of the doc comments, if any.
+pub(crate) fn doc_comments_text(docs: &TokenStream2) -> Option<String> {
+	let attrs = syn::Attribute::parse_outer.parse2(docs.clone()).ok()?;
+	let lines: Vec<_> = attrs
+		.iter()
+		.filter_map(|attr| match &attr.meta {
+			syn::Meta::NameValue(syn::MetaNameValue {
+				value: syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(doc), .. }),
+				..
+			}) => Some(doc.value()),
+			_ => None,
+		})
+		.collect();
+
+	let text = lines.iter().map(|line| line.strip_prefix(' ').unwrap_or(line)).collect::<Vec<_>>().join("\n");
+	let text = text.trim();
+
+	(!text.is_empty()).then(|| text.to_string())
+}
+
+/// Returns the type of the successful result of a method, for instance `T` for `RpcResult<T>`.
+///
+/// Returns `None` if the type is opaque.
+pub(crate) fn result_type(ty: &syn::Type) -> Option<&syn::Type> {
+	match ty {
+		syn::Type::ImplTrait(_) => None,
+		syn::Type::Path(path) => {
+			let Some(seg) = path.path.segments.last() else { return Some(ty) };
+			if !matches!(seg.ident.to_string().as_str(), "Result" | "RpcResult" | "ResponsePayload") {
+				return Some(ty);
+			}
+
+			match &seg.arguments {
+				syn::PathArguments::AngleBracketed(args) => args.args.iter().find_map(|arg| match arg {
+					syn::GenericArgument::Type(ty) => Some(ty),
+					_ => None,
+				}),
+				_ => None,
+			}
+		}
+		_ => Some(ty),
+	}
+}
+
+/// Returns `T` if the type is `jsonrpsee::core::RpcResult<T>`.
+///
+/// The path must be written in full because the macro can't resolve imports and type aliases.
+pub(crate) fn rpc_result_type(ty: &syn::Type) -> Option<&syn::Type> {
+	let syn::Type::Path(path) = ty else { return None };
+	let mut idents = path.path.segments.iter().map(|seg| &seg.ident);
+	let is_rpc_result = path.qself.is_none()
+		&& idents.next()? == "jsonrpsee"
+		&& idents.next()? == "core"
+		&& idents.next()? == "RpcResult"
+		&& idents.next().is_none();
+	if !is_rpc_result {
+		return None;
+	}
+
+	result_type(ty)
+}
+
+/// Returns `true` if the last segment of the path of the type is `RpcResult`.
+pub(crate) fn is_named_rpc_result(ty: &syn::Type) -> bool {
+	matches!(ty, syn::Type::Path(path) if path.path.segments.last().is_some_and(|seg| seg.ident == "RpcResult"))
+}
+
+/// Returns `T` if the type is `impl Stream<Item = T>`.
+pub(crate) fn stream_item(ty: &syn::Type) -> Option<&syn::Type> {
+	let bound = stream_bound(ty)?;
+	let syn::PathArguments::AngleBracketed(args) = &bound.path.segments.last()?.arguments else { return None };
+
+	args.args.iter().find_map(|arg| match arg {
+		syn::GenericArgument::AssocType(assoc) if assoc.ident == "Item" => Some(&assoc.ty),
+		_ => None,
+	})
+}
+
+/// Returns the `Stream<Item = T>` bound of `impl Stream<Item = T>`.
+pub(crate) fn stream_bound(ty: &syn::Type) -> Option<&syn::TraitBound> {
+	let syn::Type::ImplTrait(impl_trait) = ty else { return None };
+
+	impl_trait.bounds.iter().find_map(|bound| match bound {
+		syn::TypeParamBound::Trait(bound) if bound.path.segments.last()?.ident == "Stream" => Some(bound),
+		_ => None,
+	})
+}
+
 #[cfg(test)]
 mod tests {
-	use super::is_option;
+	use super::{doc_comments_text, is_option, option_type, result_type, rpc_result_type, stream_item};
 	use syn::parse_quote;
 
 	#[test]
@@ -220,5 +369,45 @@ mod tests {
 		assert!(is_option(&parse_quote!(Option)));
 		assert!(is_option(&parse_quote!(std::option::Option<R>)));
 		assert!(!is_option(&parse_quote!(foo::bar::Option::Booyah)));
+
+		let ty: syn::Type = parse_quote!(u64);
+		assert_eq!(option_type(&parse_quote!(std::option::Option<u64>)), Some(&ty));
+		assert_eq!(option_type(&parse_quote!(Option)), None);
+		assert_eq!(option_type(&parse_quote!(u64)), None);
+	}
+
+	#[test]
+	fn result_type_works() {
+		let ty: syn::Type = parse_quote!(u64);
+		assert_eq!(result_type(&parse_quote!(RpcResult<u64>)), Some(&ty));
+		assert_eq!(result_type(&parse_quote!(Result<u64, ErrorObjectOwned>)), Some(&ty));
+		assert_eq!(result_type(&parse_quote!(ResponsePayload<'static, u64>)), Some(&ty));
+		assert_eq!(result_type(&parse_quote!(u64)), Some(&ty));
+		assert_eq!(result_type(&parse_quote!(impl IntoResponse)), None);
+
+		assert_eq!(rpc_result_type(&parse_quote!(jsonrpsee::core::RpcResult<u64>)), Some(&ty));
+		assert_eq!(rpc_result_type(&parse_quote!(::jsonrpsee::core::RpcResult<u64>)), Some(&ty));
+		assert_eq!(rpc_result_type(&parse_quote!(RpcResult<u64>)), None);
+		assert_eq!(rpc_result_type(&parse_quote!(Result<u64, ErrorObjectOwned>)), None);
+	}
+
+	#[test]
+	fn stream_item_works() {
+		let ty: syn::Type = parse_quote!(u64);
+		assert_eq!(stream_item(&parse_quote!(impl Stream<Item = u64>)), Some(&ty));
+		assert_eq!(stream_item(&parse_quote!(impl Send + futures::Stream<Item = u64>)), Some(&ty));
+		assert_eq!(stream_item(&parse_quote!(impl Iterator<Item = u64>)), None);
+		assert_eq!(stream_item(&parse_quote!(SubscriptionResult)), None);
+	}
+
+	#[test]
+	fn doc_comments_text_works() {
+		let docs = quote::quote! {
+			#[doc = " Adds the values."]
+			#[doc = ""]
+			#[doc = " Returns the sum."]
+		};
+		assert_eq!(doc_comments_text(&docs).as_deref(), Some("Adds the values.\n\nReturns the sum."));
+		assert_eq!(doc_comments_text(&quote::quote!()), None);
 	}
 }
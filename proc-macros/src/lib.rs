@@ -37,6 +37,7 @@ mod helpers;
 mod render_client;
 mod render_server;
 mod rpc_macro;
+mod rpc_params;
 pub(crate) mod visitor;
 
 /// Main RPC macro.
@@ -147,21 +148,49 @@ pub(crate) mod visitor;
 ///   implementation's methods conveniently.
 /// - `namespace`: add a prefix to all the methods and subscriptions in this RPC. For example, with namespace `foo` and
 ///   method `spam`, the resulting method name will be `foo_spam`.
+/// - `versions`: list of versions of the namespace, for instance `versions("1", "2")`. The methods and subscriptions
+///   are available in all the versions unless they have a `versions` argument, and are named after the namespace of
+///   each version, for instance `foo_v1_spam` and `foo_v2_spam`. The server registers them under the first version
+///   and aliases them in the other ones, so the functions of `RpcModule` which take a method name, such as
+///   `hide_method`, must be given the name of the first version. `<Trait>Client` is replaced by one client per
+///   version which only has the methods and subscriptions of the version, for instance `<Trait>V1Client` and
+///   `<Trait>V2Client`.
+///   Versions must only contain ASCII letters, digits and underscores. Requires `namespace`.
 /// - `server_bounds`: replace *all* auto-generated trait bounds with the user-defined ones for the server
 ///   implementation.
 /// - `client_bounds`: replace *all* auto-generated trait bounds with the user-defined ones for the client
 ///   implementation.
+/// - `error`: error type of the server implementation of the methods which return `RpcResult<T>`, for instance
+///   `error = MyError`. These methods return `Result<T, MyError>` in `<Trait>Server` instead, which requires
+///   `MyError: Into<ErrorObjectOwned>`. Requires `server`. The macro can't resolve imports and type aliases,
+///   so these methods must return `RpcResult` with its full path, for instance `jsonrpsee::core::RpcResult<u64>`.
+/// - `param_kind`: kind of structure to use for parameter passing by the methods and subscriptions of the trait.
+///   Can be "array" or "map", defaults to "array". It's overridden by the `param_kind` of a method or subscription.
+/// - `mock`: add the `Mock<Trait>Client` struct, which implements `<Trait>Client` for unit tests without a server.
+///   It has an `expect_<method>` function for each method and subscription to set its result or items, and
+///   records the calls. Requires `client` and the `mock` feature of `jsonrpsee` must be enabled, which is meant to
+///   be enabled for the dev-dependencies only.
+/// - `openrpc`: add the `<Trait>Server::openrpc` function which generates an [OpenRPC](https://spec.open-rpc.org)
+///   document that describes the methods and subscriptions of the trait. The JSON Schemas of the parameters, results
+///   and subscription items are derived with `schemars`, so their types must implement `schemars::JsonSchema` and
+///   the `schemars` feature of `jsonrpsee` must be enabled. Requires `server`.
 ///
 /// **Trait requirements:**
 ///
 /// A trait wrapped with the `rpc` attribute **must not**:
 ///
-/// - have associated types or constants;
+/// - have associated constants, or associated types with defaults, generics or where clauses;
 /// - have Rust methods not marked with either the `method` or `subscription` attribute;
 /// - be empty.
 ///
 /// At least one of the `server` or `client` flags must be provided, otherwise the compilation will err.
 ///
+/// The trait may have generic type parameters and associated types. The bounds they need for the methods
+/// and subscriptions, such as `Serialize` or `DeserializeOwned`, are added to the generated traits unless
+/// `server_bounds` or `client_bounds` are provided. The associated types are kept in `<Trait>Server`, whereas
+/// `<Trait>Client` takes them as generic type parameters after the ones of the trait, for instance
+/// `trait Chain { type Hash; }` has `ChainClient<Hash>` as client.
+///
 /// ### `method` attribute
 ///
 /// `method` attribute is used to define an RPC method.
@@ -173,7 +202,16 @@ pub(crate) mod visitor;
 ///              Aliases are processed ignoring the namespace, so add the complete name, including the
 ///              namespace.
 /// - `blocking`: when set method execution will always spawn on a dedicated thread. Only usable with non-`async` methods.
-/// - `param_kind`: kind of structure to use for parameter passing. Can be "array" or "map", defaults to "array".
+/// - `versions`: versions of the namespace in which the method is available, for instance `versions("2")`.
+///   Defaults to all the versions of the trait.
+/// - `timeout_ms`: timeout of the method in milliseconds. The server cancels calls which take longer, see
+///   `RpcModule::set_method_timeout`, and the client gives up waiting for the response after the timeout
+///   if it's shorter than the request timeout of the client, see `ClientT::request_with_timeout`.
+///   Only usable with `async` methods.
+/// - `param_kind`: kind of structure to use for parameter passing. Can be "array" or "map", defaults to the
+///   `param_kind` of the trait.
+///   This only concerns the client, the server accepts the params either by position or by name where the names are
+///   the names of the arguments in `snake_case` or `camelCase`.
 ///
 /// **Method requirements:**
 ///
@@ -195,8 +233,14 @@ pub(crate) mod visitor;
 /// - `aliases` (optional): aliases for `name`. Aliases are processed ignoring the namespace,
 ///                         so add the complete name, including the namespace.
 /// - `unsubscribe_aliases` (optional): Similar to `aliases` but for `unsubscribe`.
+/// - `versions` (optional): versions of the namespace in which the subscription is available. Defaults to all the
+///   versions of the trait. The notifications are named after the first version.
 /// - `item` (mandatory): type of items yielded by the subscription. Note that it must be the type, not string.
-/// - `param_kind`: kind of structure to use for parameter passing. Can be "array" or "map", defaults to "array".
+///   Defaults to `T` for the subscriptions which return `impl Stream<Item = T>`.
+/// - `param_kind`: kind of structure to use for parameter passing. Can be "array" or "map", defaults to the
+///   `param_kind` of the trait.
+///   This only concerns the client, the server accepts the params either by position or by name where the names are
+///   the names of the arguments in `snake_case` or `camelCase`.
 ///
 /// **Method requirements:**
 ///
@@ -207,7 +251,13 @@ pub(crate) mod visitor;
 ///
 /// Rust method marked with `subscription` attribute **may**:
 ///
-/// - have input parameters or not.
+/// - have input parameters or not;
+/// - return `impl Stream<Item = T>` or `Result<impl Stream<Item = T>, E>` where `E: Into<ErrorObjectOwned>`,
+///   in which case it may also be synchronous. Such a method doesn't take a `PendingSubscriptionSink` in
+///   `<Trait>Server` and returns a `jsonrpsee::server::SubscriptionStream<T>`, that is
+///   `Pin<Box<dyn Stream<Item = T> + Send>>` with the path of `Stream` as it's written in the trait, instead.
+///   The generated code rejects the subscription with the error, if any, or accepts it and sends the items of
+///   the stream until it ends or the client unsubscribes.
 ///
 /// ### `argument` attribute
 ///
@@ -216,6 +266,15 @@ pub(crate) mod visitor;
 /// **Arguments:**
 ///
 /// - `rename`: rename the generated JSON key.
+/// - `default`: the value of the argument when it's absent or `null` in the request, for instance
+///   `#[argument(default = 10)]`. Can't be used with `Option` arguments which are `None` when absent.
+/// - `context`: the argument is taken from the `Extensions` of the call on the server instead of the RPC params,
+///   and isn't an argument of the client, for instance `#[argument(context)] conn: ConnectionId`. The argument is
+///   either `&Extensions`, the extensions themselves, `Option<T>` which is a clone of `T` if it's in the extensions,
+///   or `T` which is a clone of `T` and fails the call with an internal error if `T` isn't in the extensions.
+///   The server inserts the `ConnectionId` and `PeerInfo` of the connection in the extensions, and middleware may
+///   insert more, for instance the identity of the caller. Can't be combined with `rename`, `default` or the
+///   `with_extensions` argument of the method.
 ///
 ///
 /// ## Full workflow example
@@ -421,6 +480,45 @@ pub fn rpc(attr: TokenStream, item: TokenStream) -> TokenStream {
 	.into()
 }
 
+/// Derives `ToRpcParams` for a struct, such that it can be passed as the parameters of a call.
+///
+/// The fields are serialized as positional parameters in the order of their declaration, or as
+/// named parameters with `#[rpc_params(param_kind = map)]` on the struct, which requires named fields.
+///
+/// The fields support the following attributes:
+/// - `#[rpc_params(rename = "name")]` to use another name than the name of the field for named parameters,
+///   which requires `param_kind = map`.
+/// - `#[rpc_params(skip)]` to leave out the field.
+///
+/// # Examples
+///
+/// ```
+/// use jsonrpsee::core::{client::ClientT, ClientError};
+/// use jsonrpsee::proc_macros::RpcParams;
+///
+/// #[derive(RpcParams)]
+/// #[rpc_params(param_kind = map)]
+/// struct Transfer {
+///     from: String,
+///     #[rpc_params(rename = "toAddress")]
+///     to: String,
+///     amount: u64,
+/// }
+///
+/// async fn transfer(client: impl ClientT) -> Result<String, ClientError> {
+///     let params = Transfer { from: "alice".into(), to: "bob".into(), amount: 10 };
+///     client.request("transfer", params).await
+/// }
+/// ```
+#[proc_macro_derive(RpcParams, attributes(rpc_params))]
+pub fn derive_rpc_params(item: TokenStream) -> TokenStream {
+	match syn::parse(item).and_then(rpc_params::derive) {
+		Ok(tokens) => tokens,
+		Err(err) => err.to_compile_error(),
+	}
+	.into()
+}
+
 /// Convenience form of `rpc` that may use `?` for error handling to avoid boilerplate.
 fn rpc_impl(attr: syn::Attribute, item: TokenStream) -> Result<proc_macro2::TokenStream, syn::Error> {
 	let trait_data: syn::ItemTrait = syn::parse(item)?;
@@ -24,15 +24,36 @@
 // IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
 // DEALINGS IN THE SOFTWARE.
 use crate::attributes::ParamKind;
-use crate::helpers::generate_where_clause;
+use crate::helpers::{generate_where_clause, is_option, result_type};
 use crate::rpc_macro::{RpcDescription, RpcFnArg, RpcMethod, RpcSubscription};
+use crate::visitor::ReplaceAssocTypes;
 use proc_macro2::TokenStream as TokenStream2;
-use quote::{quote, quote_spanned};
+use quote::{quote, quote_spanned, ToTokens};
 use syn::spanned::Spanned;
+use syn::visit_mut::VisitMut;
 use syn::{AngleBracketedGenericArguments, FnArg, Ident, Pat, PatIdent, PatType, PathArguments, TypeParam};
 
 impl RpcDescription {
 	pub(super) fn render_client(&self) -> Result<TokenStream2, syn::Error> {
+		if !self.assoc_types.is_empty() {
+			return self.assoc_types_as_generics().render_client();
+		}
+
+		if self.methods.iter().any(|method| !method.context_params.is_empty())
+			|| self.subscriptions.iter().any(|sub| !sub.context_params.is_empty())
+		{
+			return self.without_context_params().render_client();
+		}
+
+		if !self.versions.is_empty() {
+			let clients = self
+				.versions
+				.iter()
+				.map(|version| self.pinned_to_version(version).render_client())
+				.collect::<Result<Vec<_>, _>>()?;
+			return Ok(quote! { #(#clients)* });
+		}
+
 		let jsonrpsee = self.jsonrpsee_client_path.as_ref().unwrap();
 		let sub_tys: Vec<syn::Type> = self.subscriptions.clone().into_iter().map(|s| s.item).collect();
 
@@ -50,6 +71,7 @@ impl RpcDescription {
 		let method_impls =
 			self.methods.iter().map(|method| self.render_method(method)).collect::<Result<Vec<_>, _>>()?;
 		let sub_impls = self.subscriptions.iter().map(|sub| self.render_sub(sub)).collect::<Result<Vec<_>, _>>()?;
+		let mock_impl = if self.needs_mock { self.render_mock() } else { TokenStream2::new() };
 
 		let async_trait = self.jrps_client_item(quote! { core::__reexports::async_trait });
 
@@ -64,11 +86,245 @@ impl RpcDescription {
 			}
 
 			impl<TypeJsonRpseeInteral #(,#type_idents)*> #trait_name #type_generics for TypeJsonRpseeInteral where TypeJsonRpseeInteral: #super_trait #(,#where_clause)* {}
+
+			#mock_impl
 		};
 
 		Ok(trait_impl)
 	}
 
+	/// The client trait is implemented for all clients, so it can't choose the associated types.
+	/// Instead, they're generic type params after the ones of the trait, for instance
+	/// `trait Chain { type Hash; }` becomes `trait ChainClient<Hash>`.
+	fn assoc_types_as_generics(&self) -> Self {
+		let mut rpc = self.clone();
+		let mut replace = ReplaceAssocTypes::new(rpc.assoc_types.iter().map(|ty| ty.ident.clone()).collect());
+
+		rpc.trait_def.items.retain(|item| !matches!(item, syn::TraitItem::Type(_)));
+		for ty in std::mem::take(&mut rpc.assoc_types) {
+			rpc.trait_def.generics.params.push(syn::GenericParam::Type(TypeParam {
+				attrs: Vec::new(),
+				ident: ty.ident,
+				colon_token: ty.colon_token,
+				bounds: ty.bounds,
+				eq_token: None,
+				default: None,
+			}));
+		}
+		replace.visit_item_trait_mut(&mut rpc.trait_def);
+
+		for method in &mut rpc.methods {
+			replace.visit_trait_item_fn_mut(&mut method.signature);
+			for param in &mut method.params {
+				replace.visit_type_mut(&mut param.ty);
+			}
+			if let Some(returns) = &mut method.returns {
+				replace.visit_type_mut(returns);
+			}
+		}
+
+		for sub in &mut rpc.subscriptions {
+			replace.visit_trait_item_fn_mut(&mut sub.signature);
+			for param in &mut sub.params {
+				replace.visit_type_mut(&mut param.ty);
+			}
+			replace.visit_type_mut(&mut sub.item);
+		}
+
+		rpc
+	}
+
+	/// The `context` arguments are provided by the server, so they aren't arguments of the client.
+	fn without_context_params(&self) -> Self {
+		fn remove(signature: &mut syn::TraitItemFn, context_params: &mut Vec<RpcFnArg>) {
+			let context: Vec<_> = context_params.drain(..).map(|arg| arg.arg_pat.ident).collect();
+			signature.sig.inputs = std::mem::take(&mut signature.sig.inputs)
+				.into_iter()
+				.filter(|arg| match arg {
+					FnArg::Typed(PatType { pat, .. }) => {
+						!matches!(&**pat, Pat::Ident(PatIdent { ident, .. }) if context.contains(ident))
+					}
+					FnArg::Receiver(_) => true,
+				})
+				.collect();
+		}
+
+		let mut rpc = self.clone();
+		for method in &mut rpc.methods {
+			remove(&mut method.signature, &mut method.context_params);
+		}
+		for sub in &mut rpc.subscriptions {
+			remove(&mut sub.signature, &mut sub.context_params);
+		}
+		rpc
+	}
+
+	/// Each version has its own client, for instance `FooV2Client` for `trait Foo` and version `2`,
+	/// with the methods and subscriptions available in the version.
+	fn pinned_to_version(&self, version: &str) -> Self {
+		let mut rpc = self.clone();
+
+		rpc.trait_def.ident = quote::format_ident!("{}V{}", self.trait_def.ident, version);
+		rpc.namespace = Some(self.versioned_namespace(version));
+		rpc.methods.retain(|method| method.versions.iter().any(|v| v == version));
+		rpc.subscriptions.retain(|sub| sub.versions.iter().any(|v| v == version));
+		rpc.versions.clear();
+		rpc
+	}
+
+	/// Renders `Mock<Trait>Client` which answers the calls of the client trait with a `MockClient`.
+	fn render_mock(&self) -> TokenStream2 {
+		let mock_name = quote::format_ident!("Mock{}Client", &self.trait_def.ident);
+		let mock_client = self.jrps_client_item(quote! { core::client::MockClient });
+		let mock_call = self.jrps_client_item(quote! { core::client::MockCall });
+		let client_t = self.jrps_client_item(quote! { core::client::ClientT });
+		let sub_client_t = self.jrps_client_item(quote! { core::client::SubscriptionClientT });
+		let jrps_error = self.jrps_client_item(quote! { core::client::Error });
+		let batch_request = self.jrps_client_item(quote! { core::params::BatchRequestBuilder });
+		let batch_response = self.jrps_client_item(quote! { core::client::BatchResponse });
+		let subscription = self.jrps_client_item(quote! { core::client::Subscription });
+		let to_rpc_params = self.jrps_client_item(quote! { core::traits::ToRpcParams });
+		let serialize = self.jrps_client_item(quote! { core::Serialize });
+		let deserialize = self.jrps_client_item(quote! { core::DeserializeOwned });
+		let error_object = self.jrps_client_item(quote! { types::ErrorObjectOwned });
+
+		let type_idents: Vec<_> = self.trait_def.generics.type_params().map(|param| &param.ident).collect();
+		let generics = if type_idents.is_empty() { quote!() } else { quote!(<#(#type_idents),*>) };
+
+		let expect_methods = self.methods.iter().filter_map(|method| {
+			let ty = result_type(method.returns.as_ref()?)?;
+			let expect = quote::format_ident!("expect_{}", method.signature.sig.ident);
+			let name = self.rpc_identifier(&method.name);
+			let doc = format!("Answer the calls of `{name}` with `result`.");
+
+			Some(quote! {
+				#[doc = #doc]
+				pub fn #expect(&self, result: ::core::result::Result<#ty, #error_object>) -> &Self where #ty: #serialize {
+					match result {
+						Ok(value) => self.client.expect_response(#name, value),
+						Err(err) => self.client.expect_error(#name, err),
+					};
+					self
+				}
+			})
+		});
+
+		let expect_subscriptions = self.subscriptions.iter().map(|sub| {
+			let item = &sub.item;
+			let expect = quote::format_ident!("expect_{}", sub.signature.sig.ident);
+			let name = self.rpc_identifier(&sub.name);
+			let doc = format!("Accept the subscriptions to `{name}`, they receive `items` and are closed afterwards.");
+
+			quote! {
+				#[doc = #doc]
+				pub fn #expect(&self, items: impl IntoIterator<Item = #item>) -> &Self where #item: #serialize {
+					self.client.expect_subscription(#name, items);
+					self
+				}
+			}
+		});
+
+		let doc_comment = format!(
+			"Mock client for the `{0}` RPC API, which implements `{0}Client` with canned responses instead of a server.",
+			&self.trait_def.ident
+		);
+
+		quote! {
+			#[doc = #doc_comment]
+			pub struct #mock_name #generics {
+				client: #mock_client,
+				_marker: ::core::marker::PhantomData<fn() -> (#(#type_idents,)*)>,
+			}
+
+			impl #generics #mock_name #generics {
+				/// Create a mock client without responses.
+				pub fn new() -> Self {
+					Self { client: #mock_client::new(), _marker: ::core::marker::PhantomData }
+				}
+
+				/// The calls and notifications which were made in order, including the subscriptions.
+				pub fn calls(&self) -> Vec<#mock_call> {
+					self.client.calls()
+				}
+
+				/// The underlying mock client.
+				pub fn client(&self) -> &#mock_client {
+					&self.client
+				}
+
+				#(#expect_methods)*
+				#(#expect_subscriptions)*
+			}
+
+			impl #generics ::core::default::Default for #mock_name #generics {
+				fn default() -> Self {
+					Self::new()
+				}
+			}
+
+			impl #generics ::core::clone::Clone for #mock_name #generics {
+				fn clone(&self) -> Self {
+					Self { client: self.client.clone(), _marker: ::core::marker::PhantomData }
+				}
+			}
+
+			impl #generics ::core::fmt::Debug for #mock_name #generics {
+				fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+					f.debug_struct(stringify!(#mock_name)).field("client", &self.client).finish()
+				}
+			}
+
+			impl #generics #client_t for #mock_name #generics {
+				async fn notification<Params>(&self, method: &str, params: Params) -> ::core::result::Result<(), #jrps_error>
+				where
+					Params: #to_rpc_params + Send,
+				{
+					#client_t::notification(&self.client, method, params).await
+				}
+
+				async fn request<R, Params>(&self, method: &str, params: Params) -> ::core::result::Result<R, #jrps_error>
+				where
+					R: #deserialize,
+					Params: #to_rpc_params + Send,
+				{
+					#client_t::request(&self.client, method, params).await
+				}
+
+				async fn batch_request<'a, R>(
+					&self,
+					batch: #batch_request<'a>,
+				) -> ::core::result::Result<#batch_response<'a, R>, #jrps_error>
+				where
+					R: #deserialize + ::core::fmt::Debug + 'a,
+				{
+					#client_t::batch_request(&self.client, batch).await
+				}
+			}
+
+			impl #generics #sub_client_t for #mock_name #generics {
+				async fn subscribe<'a, Notif, Params>(
+					&self,
+					subscribe_method: &'a str,
+					params: Params,
+					unsubscribe_method: &'a str,
+				) -> ::core::result::Result<#subscription<Notif>, #jrps_error>
+				where
+					Params: #to_rpc_params + Send,
+					Notif: #deserialize,
+				{
+					#sub_client_t::subscribe(&self.client, subscribe_method, params, unsubscribe_method).await
+				}
+
+				async fn subscribe_to_method<Notif>(&self, method: &str) -> ::core::result::Result<#subscription<Notif>, #jrps_error>
+				where
+					Notif: #deserialize,
+				{
+					#sub_client_t::subscribe_to_method(&self.client, method).await
+				}
+			}
+		}
+	}
+
 	/// Verify and rewrite the return type (for methods).
 	fn return_result_type(&self, mut ty: syn::Type) -> TokenStream2 {
 		// We expect a valid type path.
@@ -138,19 +394,24 @@ impl RpcDescription {
 		// Name of the RPC method (e.g. `foo_makeSpam`).
 		let rpc_method_name = self.rpc_identifier(&method.name);
 
-		// Called method is either `request` or `notification`.
+		// Called method is either `request`, `request_with_timeout` or `notification`.
 		// `returns` represent the return type of the *rust method* (`Result<T, jsonrpsee::core::ClientError>`).
-		let (called_method, returns) = if let Some(returns) = &method.returns {
-			let called_method = quote::format_ident!("request");
+		let (call, returns) = if let Some(returns) = &method.returns {
+			let call = match method.timeout_ms {
+				Some(ms) => quote! {
+					self.request_with_timeout(#rpc_method_name, params, ::std::time::Duration::from_millis(#ms))
+				},
+				None => quote! { self.request(#rpc_method_name, params) },
+			};
 			let returns = self.return_result_type(returns.clone());
 			let returns = quote! { #returns };
 
-			(called_method, returns)
+			(call, returns)
 		} else {
-			let called_method = quote::format_ident!("notification");
+			let call = quote! { self.notification(#rpc_method_name, params) };
 			let returns = quote! { Result<(), #jrps_error> };
 
-			(called_method, returns)
+			(call, returns)
 		};
 
 		// Encoded parameters for the request.
@@ -167,7 +428,7 @@ impl RpcDescription {
 			#[allow(clippy::used_underscore_binding)]
 			async fn #rust_method_name(#rust_method_params) -> #returns {
 				let params = { #parameter_builder };
-				self.#called_method(#rpc_method_name, params).await
+				#call.await
 			}
 		};
 		Ok(method)
@@ -228,17 +489,11 @@ impl RpcDescription {
 			);
 		}
 
-		match param_kind {
+		// The inserted value of each argument, `name, value` for a map.
+		let values: Vec<_> = match param_kind {
 			ParamKind::Map => {
 				// Extract parameter names.
 				let param_names = extract_param_names(&signature.sig);
-				// Combine parameter names and values to pass them as parameters.
-				let params_insert = params.iter().map(|arg| {
-					// Throw away the type.
-					let value = arg.arg_pat();
-					let name = arg.name();
-					quote!(#name, #value)
-				});
 
 				// It's possible that the user has a parameter named `ILLEGAL_PARAM_NAME` in there API
 				// which would conflict with our internal parameter name
@@ -248,31 +503,59 @@ impl RpcDescription {
 					panic!("Cannot use `{}` as a parameter name", ILLEGAL_PARAM_NAME);
 				}
 
-				quote!({
-					let mut #p = #jsonrpsee::core::params::ObjectParams::new();
-					#(
-						if let Err(err) = #p.insert(#params_insert) {
-							#reexports::panic_fail_serialize(stringify!(#params_insert), err);
-						}
-					)*
-					#p
-				})
+				// Combine parameter names and values to pass them as parameters.
+				params
+					.iter()
+					.map(|arg| {
+						// Throw away the type.
+						let value = arg.arg_pat();
+						let name = arg.name();
+						quote!(#name, #value)
+					})
+					.collect()
 			}
-			ParamKind::Array => {
-				// Throw away the type.
-				let params = params.iter().map(RpcFnArg::arg_pat);
-
-				quote!({
-					let mut #p = #jsonrpsee::core::params::ArrayParams::new();
-					#(
-						if let Err(err) = #p.insert(#params) {
-							#reexports::panic_fail_serialize(stringify!(#params), err);
-						}
-					)*
-					#p
-				})
+			// Throw away the type.
+			ParamKind::Array => params.iter().map(|arg| arg.arg_pat().to_token_stream()).collect(),
+		};
+
+		// Trailing `Option` arguments which are `None` are not sent.
+		let first_option = params.len() - params.iter().rev().take_while(|arg| is_option(arg.ty())).count();
+
+		let inserts = values.iter().enumerate().map(|(idx, value)| {
+			let insert = quote! {
+				if let Err(err) = #p.insert(#value) {
+					#reexports::panic_fail_serialize(stringify!(#value), err);
+				}
+			};
+
+			if idx < first_option {
+				return insert;
 			}
-		}
+
+			// An argument is sent if it's `Some` and by position also if any of the arguments after it is `Some`.
+			let sent = match param_kind {
+				ParamKind::Map => &params[idx..=idx],
+				ParamKind::Array => &params[idx..],
+			};
+			let sent = sent.iter().map(RpcFnArg::arg_pat);
+
+			quote! {
+				if #(#sent.is_some())||* {
+					#insert
+				}
+			}
+		});
+
+		let params_ty = match param_kind {
+			ParamKind::Map => quote!(ObjectParams),
+			ParamKind::Array => quote!(ArrayParams),
+		};
+
+		quote!({
+			let mut #p = #jsonrpsee::core::params::#params_ty::new();
+			#(#inserts)*
+			#p
+		})
 	}
 }
 
@@ -29,7 +29,10 @@ use std::str::FromStr;
 
 use super::RpcDescription;
 use crate::{
-	helpers::{generate_where_clause, is_option},
+	helpers::{
+		doc_comments_text, generate_server_assoc_types, generate_where_clause, option_type, result_type,
+		rpc_result_type,
+	},
 	rpc_macro::RpcFnArg,
 };
 use proc_macro2::{Span, TokenStream as TokenStream2};
@@ -38,12 +41,21 @@ use syn::Attribute;
 
 impl RpcDescription {
 	pub(super) fn render_server(&self) -> Result<TokenStream2, syn::Error> {
+		if !self.versions.is_empty() {
+			return self.versions_as_aliases().render_server();
+		}
+
 		let trait_name = quote::format_ident!("{}Server", &self.trait_def.ident);
 		let generics = self.trait_def.generics.clone();
 		let (impl_generics, _, where_clause) = generics.split_for_impl();
 
+		let sub_tys: Vec<syn::Type> = self.subscriptions.iter().map(|s| s.item.clone()).collect();
+		let assoc_types =
+			generate_server_assoc_types(&self.trait_def, &sub_tys, &self.assoc_types, self.server_bounds.as_ref());
+
 		let method_impls = self.render_methods()?;
 		let into_rpc_impl = self.render_into_rpc()?;
+		let openrpc_impl = if self.needs_openrpc { self.render_openrpc() } else { TokenStream2::new() };
 		let async_trait = self.jrps_server_item(quote! { core::__reexports::async_trait });
 
 		// Doc-comment to be associated with the server.
@@ -53,14 +65,50 @@ impl RpcDescription {
 			#[#async_trait]
 			#[doc = #doc_comment]
 			pub trait #trait_name #impl_generics: Sized + Send + Sync + 'static #where_clause {
+				#(#assoc_types)*
 				#method_impls
 				#into_rpc_impl
+				#openrpc_impl
 			}
 		};
 
 		Ok(trait_impl)
 	}
 
+	/// The server registers the methods and subscriptions under the namespace of the first version
+	/// they're available in, and aliases them in the namespaces of the other versions.
+	/// For instance `foo_v1_spam` and its alias `foo_v2_spam` for namespace `foo` and versions `1` and `2`.
+	fn versions_as_aliases(&self) -> Self {
+		let mut rpc = self.clone();
+		let versioned = |versions: &[String], name: &str| -> Vec<String> {
+			versions.iter().map(|version| format!("{}_{name}", self.versioned_namespace(version))).collect()
+		};
+
+		for method in &mut rpc.methods {
+			let mut names = versioned(&method.versions, &method.name);
+			method.name = names.remove(0);
+			method.aliases.splice(0..0, names);
+		}
+
+		for sub in &mut rpc.subscriptions {
+			let mut names = versioned(&sub.versions, &sub.name);
+			sub.name = names.remove(0);
+			sub.aliases.splice(0..0, names);
+
+			let mut names = versioned(&sub.versions, &sub.unsubscribe);
+			sub.unsubscribe = names.remove(0);
+			sub.unsubscribe_aliases.splice(0..0, names);
+
+			if let Some(notif) = &mut sub.notif_name_override {
+				*notif = versioned(&sub.versions[..1], notif).remove(0);
+			}
+		}
+
+		rpc.namespace = None;
+		rpc.versions.clear();
+		rpc
+	}
+
 	fn render_methods(&self) -> Result<TokenStream2, syn::Error> {
 		let methods = self.methods.iter().map(|method| {
 			let docs = &method.docs;
@@ -73,6 +121,13 @@ impl RpcDescription {
 				method_sig.sig.inputs.insert(1, ext);
 			}
 
+			if let (Some(error), Some(returns)) = (&self.error, &method.returns) {
+				// Replace `RpcResult<T>` with `Result<T, Error>`.
+				if let Some(ty) = rpc_result_type(returns) {
+					method_sig.sig.output = syn::parse_quote!(-> Result<#ty, #error>);
+				}
+			}
+
 			quote! {
 				#docs
 				#method_sig
@@ -81,18 +136,34 @@ impl RpcDescription {
 
 		let subscriptions = self.subscriptions.iter().map(|sub| {
 			let docs = &sub.docs;
-			let subscription_sink_ty = self.jrps_server_item(quote! { PendingSubscriptionSink });
-
-			// Add `SubscriptionSink` as the second input parameter to the signature.
-			let subscription_sink: syn::FnArg = syn::parse_quote!(subscription_sink: #subscription_sink_ty);
 			let mut sub_sig = sub.signature.clone();
-			sub_sig.sig.inputs.insert(1, subscription_sink);
+
+			if let Some(stream) = &sub.stream {
+				// The stream is boxed because `impl Trait` can't be returned by `async_trait` methods,
+				// which is a `SubscriptionStream<T>` with the path of `Stream` as it's written in the trait.
+				let trait_path = &stream.trait_path;
+				let item = &stream.item;
+				let stream_ty: syn::Type = syn::parse_quote! {
+					::core::pin::Pin<::std::boxed::Box<dyn #trait_path<Item = #item> + Send + 'static>>
+				};
+				sub_sig.sig.output = match &stream.error {
+					Some(error) => syn::parse_quote!(-> ::core::result::Result<#stream_ty, #error>),
+					None => syn::parse_quote!(-> #stream_ty),
+				};
+			} else {
+				let subscription_sink_ty = self.jrps_server_item(quote! { PendingSubscriptionSink });
+
+				// Add `SubscriptionSink` as the second input parameter to the signature.
+				let subscription_sink: syn::FnArg = syn::parse_quote!(subscription_sink: #subscription_sink_ty);
+				sub_sig.sig.inputs.insert(1, subscription_sink);
+			}
 
 			if sub.with_extensions {
 				let ext_ty = self.jrps_server_item(quote! { Extensions });
-				// Add `Extension` as the third parameter to the signature.
+				// Add `Extension` as the parameter after the subscription sink, if any.
 				let ext: syn::FnArg = syn::parse_quote!(ext: &#ext_ty);
-				sub_sig.sig.inputs.insert(2, ext);
+				let idx = if sub.stream.is_some() { 1 } else { 2 };
+				sub_sig.sig.inputs.insert(idx, ext);
 			}
 
 			quote! {
@@ -149,47 +220,54 @@ impl RpcDescription {
 				// provided `Params` object.
 				// `params_seq` is the comma-delimited sequence of parameters we're passing to the rust function
 				// called..
-				let (parsing, params_seq) = self.render_params_decoding(&method.params, None);
+				let (parsing, _) = self.render_params_decoding(&method.params, None);
+				let context_params = self.render_context_params(&method.context_params, None);
+				let params_seq = render_call_args(&method.signature);
+				let (ext_param, ext) = if method.with_extensions {
+					(quote!(ext), quote!(&ext,))
+				} else if !method.context_params.is_empty() {
+					(quote!(ext), quote!())
+				} else {
+					(quote!(_), quote!())
+				};
 
 				let into_response = self.jrps_server_item(quote! { IntoResponse });
 
 				check_name(&rpc_method_name, rust_method_name.span());
 
-				if method.signature.sig.asyncness.is_some() {
-					if method.with_extensions {
-						self.handle_register_result(quote! {
-							rpc.register_async_method(#rpc_method_name, |params, context, ext| async move {
-								#parsing
-								#into_response::into_response(context.as_ref().#rust_method_name(&ext, #params_seq).await)
-							})
-						})
-					} else {
-						self.handle_register_result(quote! {
-							rpc.register_async_method(#rpc_method_name, |params, context, _| async move {
-								#parsing
-								#into_response::into_response(context.as_ref().#rust_method_name(#params_seq).await)
-							})
-						})
+				let params_metadata = self.render_params_metadata(&rpc_method_name, &method.params);
+				let timeout = method.timeout_ms.map(|ms| {
+					quote! {
+						rpc.set_method_timeout(#rpc_method_name, ::std::time::Duration::from_millis(#ms))
+							.expect("The method was registered above; qed");
 					}
+				});
+
+				let register = if method.signature.sig.asyncness.is_some() {
+					self.handle_register_result(quote! {
+						rpc.register_async_method(#rpc_method_name, |params, context, #ext_param| async move {
+							#parsing
+							#context_params
+							#into_response::into_response(context.as_ref().#rust_method_name(#ext #params_seq).await)
+						})
+					})
 				} else {
 					let register_kind =
 						if method.blocking { quote!(register_blocking_method) } else { quote!(register_method) };
 
-					if method.with_extensions {
-						self.handle_register_result(quote! {
-							rpc.#register_kind(#rpc_method_name, |params, context, ext| {
-								#parsing
-								#into_response::into_response(context.#rust_method_name(&ext, #params_seq))
-							})
-						})
-					} else {
-						self.handle_register_result(quote! {
-							rpc.#register_kind(#rpc_method_name, |params, context, _| {
-								#parsing
-								#into_response::into_response(context.#rust_method_name(#params_seq))
-							})
+					self.handle_register_result(quote! {
+						rpc.#register_kind(#rpc_method_name, |params, context, #ext_param| {
+							#parsing
+							#context_params
+							#into_response::into_response(context.#rust_method_name(#ext #params_seq))
 						})
-					}
+					})
+				};
+
+				quote! {
+					#register
+					#params_metadata
+					#timeout
 				}
 			})
 			.collect::<Vec<_>>();
@@ -210,7 +288,16 @@ impl RpcDescription {
 				// provided `Params` object.
 				// `params_seq` is the comma-delimited sequence of parameters.
 				let pending = proc_macro2::Ident::new("pending", rust_method_name.span());
-				let (parsing, params_seq) = self.render_params_decoding(&sub.params, Some(pending));
+				let (parsing, _) = self.render_params_decoding(&sub.params, Some(pending.clone()));
+				let context_params = self.render_context_params(&sub.context_params, Some(pending));
+				let params_seq = render_call_args(&sub.signature);
+				let (ext_param, ext) = if sub.with_extensions {
+					(quote!(ext), quote!(&ext,))
+				} else if !sub.context_params.is_empty() {
+					(quote!(ext), quote!())
+				} else {
+					(quote!(_), quote!())
+				};
 				let sub_err = self.jrps_server_item(quote! { SubscriptionCloseResponse });
 				let into_sub_response = self.jrps_server_item(quote! { IntoSubscriptionCloseResponse });
 
@@ -225,38 +312,57 @@ impl RpcDescription {
 					None => rpc_sub_name.clone(),
 				};
 
-				if sub.signature.sig.asyncness.is_some() {
-					if sub.with_extensions {
-						self.handle_register_result(quote! {
-							rpc.register_subscription(#rpc_sub_name, #rpc_notif_name, #rpc_unsub_name, |params, mut pending, context, ext| async move {
-								#parsing
-								#into_sub_response::into_response(context.as_ref().#rust_method_name(pending, &ext, #params_seq).await)
-							})
-						})
+				let params_metadata = self.render_params_metadata(&rpc_sub_name, &sub.params);
+
+				let register = if let Some(stream) = &sub.stream {
+					let call = if sub.signature.sig.asyncness.is_some() {
+						quote! { context.as_ref().#rust_method_name(#ext #params_seq).await }
 					} else {
-						self.handle_register_result(quote! {
-							rpc.register_subscription(#rpc_sub_name, #rpc_notif_name, #rpc_unsub_name, |params, mut pending, context, _| async move {
-								#parsing
-								#into_sub_response::into_response(context.as_ref().#rust_method_name(pending, #params_seq).await)
-							})
+						quote! { context.as_ref().#rust_method_name(#ext #params_seq) }
+					};
+					let stream = match stream.error {
+						Some(_) => quote! {
+							match #call {
+								Ok(stream) => stream,
+								Err(err) => {
+									pending.reject(err).await;
+									return #sub_err::None;
+								}
+							}
+						},
+						None => call,
+					};
+
+					self.handle_register_result(quote! {
+						rpc.register_subscription(#rpc_sub_name, #rpc_notif_name, #rpc_unsub_name, |params, mut pending, context, #ext_param| async move {
+							#parsing
+							#context_params
+							let stream = #stream;
+							#into_sub_response::into_response(pending.pipe_from_stream(stream).await)
 						})
-					}
-				} else if sub.with_extensions {
+					})
+				} else if sub.signature.sig.asyncness.is_some() {
 					self.handle_register_result(quote! {
-						rpc.register_subscription_raw(#rpc_sub_name, #rpc_notif_name, #rpc_unsub_name, |params, mut pending, context, ext| {
+						rpc.register_subscription(#rpc_sub_name, #rpc_notif_name, #rpc_unsub_name, |params, mut pending, context, #ext_param| async move {
 							#parsing
-							let _ = context.as_ref().#rust_method_name(pending, &ext, #params_seq);
-							#sub_err::None
+							#context_params
+							#into_sub_response::into_response(context.as_ref().#rust_method_name(pending, #ext #params_seq).await)
 						})
 					})
 				} else {
 					self.handle_register_result(quote! {
-						rpc.register_subscription_raw(#rpc_sub_name, #rpc_notif_name, #rpc_unsub_name, |params, mut pending, context, _| {
+						rpc.register_subscription_raw(#rpc_sub_name, #rpc_notif_name, #rpc_unsub_name, |params, mut pending, context, #ext_param| {
 							#parsing
-							let _ = context.as_ref().#rust_method_name(pending, #params_seq);
+							#context_params
+							let _ = context.as_ref().#rust_method_name(pending, #ext #params_seq);
 							#sub_err::None
 						})
 					})
+				};
+
+				quote! {
+					#register
+					#params_metadata
 				}
 			})
 			.collect::<Vec<_>>();
@@ -325,11 +431,12 @@ impl RpcDescription {
 
 		let sub_tys: Vec<syn::Type> = self.subscriptions.clone().into_iter().map(|s| s.item).collect();
 		let where_clause = generate_where_clause(&self.trait_def, &sub_tys, false, self.server_bounds.as_ref());
+		let openrpc_bounds = if self.needs_openrpc { self.openrpc_bounds() } else { Vec::new() };
 
 		// NOTE(niklasad1): empty where clause is valid rust syntax.
 		Ok(quote! {
 			#[doc = #doc_comment]
-			fn into_rpc(self) -> #rpc_module<Self> where #(#where_clause,)* {
+			fn into_rpc(self) -> #rpc_module<Self> where #(#where_clause,)* #(#openrpc_bounds,)* {
 				let mut rpc = #rpc_module::new(self);
 
 				#(#errors)*
@@ -343,22 +450,133 @@ impl RpcDescription {
 		})
 	}
 
-	fn render_params_decoding(
-		&self,
-		params: &[RpcFnArg],
-		sub: Option<proc_macro2::Ident>,
-	) -> (TokenStream2, TokenStream2) {
-		if params.is_empty() {
-			return (TokenStream2::default(), TokenStream2::default());
+	fn render_openrpc(&self) -> TokenStream2 {
+		let generator = self.jrps_server_item(quote! { OpenRpcGenerator });
+		let info = self.jrps_server_item(quote! { OpenRpcInfo });
+		let json_value = self.jrps_server_item(quote! { core::JsonValue });
+
+		let methods = self.methods.iter().map(|method| {
+			let name = self.rpc_identifier(&method.name);
+			let descriptor = self.render_method_descriptor(&name, &method.docs, &method.params);
+			let deprecated = (!method.deprecated.is_empty()).then(|| quote! { let method = method.deprecated(); });
+			let result = match &method.returns {
+				Some(ty) => result_type(ty).map(|ty| quote! { generator.schema::<#ty>() }),
+				None => Some(quote! { generator.schema::<()>() }),
+			};
+			let result = result.map(|schema| quote! { let method = method.result(#schema); });
+			let aliases = &method.aliases;
+
+			quote! {{
+				let method = #descriptor;
+				#deprecated
+				#result
+				#(methods.push(method.alias(#aliases));)*
+				methods.push(method);
+			}}
+		});
+
+		let subscriptions = self.subscriptions.iter().map(|sub| {
+			let method_descriptor = self.jrps_server_item(quote! { MethodDescriptor });
+			let name = self.rpc_identifier(&sub.name);
+			let notif_name = match &sub.notif_name_override {
+				Some(notif) => self.rpc_identifier(notif),
+				None => name.clone(),
+			};
+			let unsub_name = self.rpc_identifier(&sub.unsubscribe);
+			let descriptor = self.render_method_descriptor(&name, &sub.docs, &sub.params);
+			let item = &sub.item;
+			let aliases = &sub.aliases;
+			let unsub_aliases = &sub.unsubscribe_aliases;
+
+			quote! {{
+				let method = #descriptor.subscription(#notif_name, #unsub_name, Some(generator.schema::<#item>()));
+				#(methods.push(method.alias(#aliases));)*
+				methods.push(method);
+
+				let method = #method_descriptor::unsubscription(#unsub_name);
+				#(methods.push(method.alias(#unsub_aliases));)*
+				methods.push(method);
+			}}
+		});
+
+		let bounds = self.openrpc_bounds();
+
+		let doc_comment =
+			"Generates an OpenRPC document that describes the methods and subscriptions defined in the trait.";
+
+		quote! {
+			#[doc = #doc_comment]
+			fn openrpc(info: &#info) -> #json_value where #(#bounds,)* {
+				let mut generator = #generator::new();
+				let mut methods = Vec::new();
+
+				#(#methods)*
+				#(#subscriptions)*
+
+				generator.document(info, methods)
+			}
 		}
+	}
 
-		let params_fields_seq = params.iter().map(RpcFnArg::arg_pat);
-		let params_fields = quote! { #(#params_fields_seq),* };
+	/// Describe a method or subscription in the generated OpenRPC document by its name, description and parameters.
+	fn render_method_descriptor(&self, rpc_name: &str, docs: &TokenStream2, params: &[RpcFnArg]) -> TokenStream2 {
+		let method_descriptor = self.jrps_server_item(quote! { MethodDescriptor });
 
-		let reexports = self.jrps_server_item(quote! { core::__reexports });
+		let description = doc_comments_text(docs).map(|text| quote! { .description(#text) });
+		let params = params.iter().map(|arg| {
+			let name = arg.name();
+			let ty = arg.ty();
+			let optional = arg.is_optional().then(|| quote! { .optional() });
+			quote! { .param(generator.param::<#ty>(#name)#optional) }
+		});
+
+		quote! {
+			#method_descriptor::new(#rpc_name)#description #(#params)*
+		}
+	}
+
+	/// Describe the parameters of a registered method by their names, and by the JSON Schemas
+	/// of their types if the trait generates OpenRPC documents.
+	fn render_params_metadata(&self, rpc_method_name: &str, params: &[RpcFnArg]) -> TokenStream2 {
+		let param_descriptor = self.jrps_server_item(quote! { ParamDescriptor });
+
+		let params = params.iter().map(|arg| {
+			let name = arg.name();
+			let ty = arg.ty();
+			let optional = arg.is_optional().then(|| quote! { .optional() });
+			if self.needs_openrpc {
+				quote! { #param_descriptor::of::<#ty>(#name)#optional }
+			} else {
+				quote! { #param_descriptor::any(#name)#optional }
+			}
+		});
+
+		quote! {
+			rpc.set_method_params(#rpc_method_name, vec![#(#params),*])
+				.expect("The method was registered above; qed");
+		}
+	}
+
+	/// The bounds which the generics of the trait must satisfy to derive the JSON Schemas of the OpenRPC document.
+	fn openrpc_bounds(&self) -> Vec<TokenStream2> {
+		let json_schema = self.jrps_server_item(quote! { core::__reexports::schemars::JsonSchema });
+
+		let bounds = self.trait_def.generics.type_params().map(|param| {
+			let ident = &param.ident;
+			quote! { #ident: #json_schema }
+		});
+		let assoc_bounds = self.assoc_types.iter().map(|ty| {
+			let ident = &ty.ident;
+			quote! { Self::#ident: #json_schema }
+		});
+
+		bounds.chain(assoc_bounds).collect()
+	}
 
-		let error_ret = if let Some(pending) = &sub {
-			let tokio = quote! { #reexports::tokio };
+	/// Returns the error `e` from the callback of a method, or rejects the subscription `sub` with it.
+	fn render_error_return(&self, sub: Option<&proc_macro2::Ident>) -> TokenStream2 {
+		if let Some(pending) = sub {
+			let tokio = self.jrps_server_item(quote! { core::__reexports::tokio });
 			let sub_err = self.jrps_server_item(quote! { SubscriptionCloseResponse });
 			quote! {
 				#tokio::spawn(#pending.reject(e));
@@ -369,18 +587,73 @@ impl RpcDescription {
 			quote! {
 				return #response_payload::error(e);
 			}
-		};
+		}
+	}
+
+	/// Binds the `context` arguments from the extensions `ext` of the call. `&Extensions` is the extensions
+	/// themselves, `Option<T>` is `T` if it's in the extensions and `T` is an internal error if it isn't.
+	fn render_context_params(&self, params: &[RpcFnArg], sub: Option<proc_macro2::Ident>) -> TokenStream2 {
+		let error_ret = self.render_error_return(sub.as_ref());
+		let error_object = self.jrps_server_item(quote! { types::ErrorObject });
+		let internal_error = self.jrps_server_item(quote! { types::error::INTERNAL_ERROR_CODE });
+
+		let bindings = params.iter().map(|arg| {
+			let (arg_pat, ty) = (arg.arg_pat(), arg.ty());
+
+			if arg.is_extensions() {
+				quote! { let #arg_pat: #ty = &ext; }
+			} else if let Some(inner) = option_type(ty) {
+				quote! { let #arg_pat: #ty = ext.get::<#inner>().cloned(); }
+			} else {
+				quote! {
+					let #arg_pat: #ty = match ext.get::<#ty>() {
+						Some(v) => ::core::clone::Clone::clone(v),
+						None => {
+							let e = #error_object::owned(
+								#internal_error,
+								::std::format!("Missing `{}` in the extensions of the call", stringify!(#ty)),
+								None::<()>,
+							);
+							#error_ret
+						}
+					};
+				}
+			}
+		});
+
+		quote! { #(#bindings)* }
+	}
+
+	fn render_params_decoding(
+		&self,
+		params: &[RpcFnArg],
+		sub: Option<proc_macro2::Ident>,
+	) -> (TokenStream2, TokenStream2) {
+		if params.is_empty() {
+			return (TokenStream2::default(), TokenStream2::default());
+		}
+
+		let params_fields_seq = params.iter().map(RpcFnArg::arg_pat);
+		let params_fields = quote! { #(#params_fields_seq),* };
+
+		let reexports = self.jrps_server_item(quote! { core::__reexports });
+		let error_ret = self.render_error_return(sub.as_ref());
 
 		// Code to decode sequence of parameters from a JSON array.
 		let decode_array = {
-			let decode_fields = params.iter().map(|RpcFnArg { arg_pat, ty, .. }| {
-				let is_option = is_option(ty);
-				let next_method = if is_option { quote!(optional_next) } else { quote!(next) };
+			let decode_fields = params.iter().map(|arg| {
+				let (arg_pat, ty) = (arg.arg_pat(), arg.ty());
+				let is_optional = arg.is_optional();
+				let (next_method, value) = match arg.default() {
+					Some(default) => (quote!(optional_next::<#ty>), quote!(v.unwrap_or_else(|| #default))),
+					None if is_optional => (quote!(optional_next), quote!(v)),
+					None => (quote!(next), quote!(v)),
+				};
 				quote! {
 					let #arg_pat: #ty = match seq.#next_method() {
-						Ok(v) => v,
+						Ok(v) => #value,
 						Err(e) => {
-							#reexports::log_fail_parse(stringify!(#arg_pat), stringify!(#ty), &e, #is_option);
+							#reexports::log_fail_parse(stringify!(#arg_pat), stringify!(#ty), &e, #is_optional);
 							#error_ret
 						}
 					};
@@ -419,13 +692,21 @@ impl RpcDescription {
 					#[serde(#alias)]
 				};
 
+				let ty = if fn_arg.default().is_some() { quote!(Option<#ty>) } else { quote!(#ty) };
+
 				quote! {
 					#serde_alias
 					#serde_rename
 					#arg_pat: #ty,
 				}
 			});
-			let destruct = params.iter().map(RpcFnArg::arg_pat).map(|a| quote!(parsed.#a));
+			let destruct = params.iter().map(|arg| {
+				let arg_pat = arg.arg_pat();
+				match arg.default() {
+					Some(default) => quote!(parsed.#arg_pat.unwrap_or_else(|| #default)),
+					None => quote!(parsed.#arg_pat),
+				}
+			});
 			let types = params.iter().map(RpcFnArg::ty);
 
 			quote! {
@@ -458,3 +739,16 @@ impl RpcDescription {
 		(parsing, params_fields)
 	}
 }
+
+/// Renders the arguments of the call of a method, in the order of its signature, for instance `a, b, ext`.
+fn render_call_args(method: &syn::TraitItemFn) -> TokenStream2 {
+	let args = method.sig.inputs.iter().filter_map(|arg| match arg {
+		syn::FnArg::Typed(arg) => match &*arg.pat {
+			syn::Pat::Ident(pat) => Some(&pat.ident),
+			_ => None,
+		},
+		syn::FnArg::Receiver(_) => None,
+	});
+
+	quote! { #(#args),* }
+}
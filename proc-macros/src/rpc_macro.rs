@@ -31,11 +31,13 @@ use std::borrow::Cow;
 use crate::attributes::{
 	optional, parse_param_kind, Aliases, Argument, AttributeMeta, MissingArgument, NameMapping, ParamKind,
 };
-use crate::helpers::extract_doc_comments;
+use crate::helpers::{
+	extract_doc_comments, is_named_rpc_result, is_option, rpc_result_type, stream_bound, stream_item,
+};
 use proc_macro2::TokenStream as TokenStream2;
 use quote::quote;
 use syn::spanned::Spanned;
-use syn::{punctuated::Punctuated, Attribute, Token};
+use syn::{punctuated::Punctuated, Attribute, LitStr, Token};
 
 /// Represents a single argument in a RPC call.
 ///
@@ -45,26 +47,62 @@ pub struct RpcFnArg {
 	pub(crate) arg_pat: syn::PatIdent,
 	rename_to: Option<String>,
 	pub(crate) ty: syn::Type,
+	default: Option<syn::Expr>,
+	/// The argument is taken from the extensions of the call instead of the RPC params.
+	pub(crate) context: bool,
 }
 
 impl RpcFnArg {
 	pub fn from_arg_attrs(arg_pat: syn::PatIdent, ty: syn::Type, attrs: &mut Vec<syn::Attribute>) -> syn::Result<Self> {
 		let mut rename_to = None;
+		let mut default = None;
+		let mut context = false;
 
 		if let Some(attr) = find_attr(attrs, "argument") {
-			let [rename] = AttributeMeta::parse(attr.clone())?.retain(["rename"])?;
+			let [context_flag, default_value, rename] =
+				AttributeMeta::parse(attr.clone())?.retain(["context", "default", "rename"])?;
 
+			context = optional(context_flag, Argument::flag)?.is_some();
 			let rename = optional(rename, Argument::string)?;
 
+			if context && (rename.is_some() || default_value.is_ok()) {
+				return Err(syn::Error::new(
+					attr.span(),
+					"`context` arguments aren't RPC params, they can't be combined with `default` or `rename`",
+				));
+			}
+
 			if let Some(rename) = rename {
 				rename_to = Some(rename);
 			}
+
+			default = optional(default_value, Argument::value::<syn::Expr>)?;
+		}
+
+		if let Some(default) = &default {
+			if is_option(&ty) {
+				return Err(syn::Error::new(
+					default.span(),
+					"`default` can't be used with `Option` arguments, they default to `None`",
+				));
+			}
+		}
+
+		if context {
+			if let syn::Type::Reference(reference) = &ty {
+				if !is_extensions(&reference.elem) {
+					return Err(syn::Error::new_spanned(
+						&ty,
+						"`context` arguments must be either `&Extensions` or owned types which are cloned from the extensions",
+					));
+				}
+			}
 		}
 
 		// remove argument attribute after inspection
 		attrs.retain(|attr| !attr.meta.path().is_ident("argument"));
 
-		Ok(Self { arg_pat, rename_to, ty })
+		Ok(Self { arg_pat, rename_to, ty, default, context })
 	}
 
 	/// Return the pattern identifier of the argument.
@@ -79,6 +117,18 @@ impl RpcFnArg {
 	pub fn ty(&self) -> &syn::Type {
 		&self.ty
 	}
+	/// Return the value of the argument when it's absent, if any.
+	pub fn default(&self) -> Option<&syn::Expr> {
+		self.default.as_ref()
+	}
+	/// Return whether the argument is `&Extensions`, the extensions of the call.
+	pub fn is_extensions(&self) -> bool {
+		matches!(&self.ty, syn::Type::Reference(reference) if is_extensions(&reference.elem))
+	}
+	/// Return whether the argument may be absent, either because it's an `Option` or has a default.
+	pub fn is_optional(&self) -> bool {
+		self.default.is_some() || is_option(&self.ty)
+	}
 }
 
 #[derive(Debug, Clone)]
@@ -93,18 +143,31 @@ pub struct RpcMethod {
 	pub signature: syn::TraitItemFn,
 	pub aliases: Vec<String>,
 	pub with_extensions: bool,
+	/// Arguments which are taken from the extensions of the call, they aren't part of `params`.
+	pub context_params: Vec<RpcFnArg>,
+	pub timeout_ms: Option<u64>,
+	/// Versions of the namespace in which the method is available.
+	pub versions: Vec<String>,
 }
 
 impl RpcMethod {
-	pub fn from_item(attr: Attribute, mut method: syn::TraitItemFn) -> syn::Result<Self> {
-		let [aliases, blocking, name, param_kind, with_extensions] =
-			AttributeMeta::parse(attr)?.retain(["aliases", "blocking", "name", "param_kind", "with_extensions"])?;
+	pub fn from_item(
+		attr: Attribute,
+		mut method: syn::TraitItemFn,
+		default_param_kind: ParamKind,
+		trait_versions: &[String],
+	) -> syn::Result<Self> {
+		let [aliases, blocking, name, param_kind, timeout_ms, versions, with_extensions] = AttributeMeta::parse(attr)?
+			.retain(["aliases", "blocking", "name", "param_kind", "timeout_ms", "versions", "with_extensions"])?;
 
 		let aliases = parse_aliases(aliases)?;
 		let blocking = optional(blocking, Argument::flag)?.is_some();
 		let name = name?.string()?;
-		let param_kind = parse_param_kind(param_kind)?;
+		let param_kind = parse_param_kind(param_kind)?.unwrap_or(default_param_kind);
 		let with_extensions = optional(with_extensions, Argument::flag)?.is_some();
+		let timeout_ms =
+			optional(timeout_ms, Argument::value::<syn::LitInt>)?.map(|lit| lit.base10_parse::<u64>()).transpose()?;
+		let versions = parse_versions(versions, trait_versions)?;
 
 		let docs = extract_doc_comments(&method.attrs);
 		let deprecated = match find_attr(&method.attrs, "deprecated") {
@@ -116,6 +179,10 @@ impl RpcMethod {
 			return Err(syn::Error::new(method.sig.span(), "Blocking method must be synchronous"));
 		}
 
+		if timeout_ms.is_some() && method.sig.asyncness.is_none() {
+			return Err(syn::Error::new(method.sig.span(), "Method with a timeout must be asynchronous"));
+		}
+
 		let params: Vec<_> = method
 			.sig
 			.inputs
@@ -138,6 +205,8 @@ impl RpcMethod {
 			})
 			.collect::<Result<_, _>>()?;
 
+		let (context_params, params) = split_context_params(params, with_extensions, &method)?;
+
 		let returns = match method.sig.output.clone() {
 			syn::ReturnType::Default => None,
 			syn::ReturnType::Type(_, output) => Some(*output),
@@ -157,6 +226,9 @@ impl RpcMethod {
 			docs,
 			deprecated,
 			with_extensions,
+			context_params,
+			timeout_ms,
+			versions,
 		})
 	}
 }
@@ -180,11 +252,66 @@ pub struct RpcSubscription {
 	pub aliases: Vec<String>,
 	pub unsubscribe_aliases: Vec<String>,
 	pub with_extensions: bool,
+	/// Arguments which are taken from the extensions of the call, they aren't part of `params`.
+	pub context_params: Vec<RpcFnArg>,
+	pub stream: Option<StreamReturn>,
+	/// Versions of the namespace in which the subscription is available.
+	pub versions: Vec<String>,
+}
+
+/// Return type of a subscription which returns a stream of its items instead of
+/// taking a `PendingSubscriptionSink`.
+#[derive(Debug, Clone)]
+pub struct StreamReturn {
+	/// Type of the items, `T` of `impl Stream<Item = T>`.
+	pub item: syn::Type,
+	/// Error type if the stream is returned as `Result<impl Stream<Item = T>, E>`.
+	pub error: Option<syn::Type>,
+	/// Path of the `Stream` trait as written in the trait, without its generic arguments.
+	pub trait_path: syn::Path,
+}
+
+impl StreamReturn {
+	fn from_return_type(output: &syn::ReturnType) -> Option<Self> {
+		let syn::ReturnType::Type(_, ty) = output else { return None };
+
+		if let Some(item) = stream_item(ty) {
+			return Some(Self { item: item.clone(), error: None, trait_path: Self::trait_path(ty)? });
+		}
+
+		let syn::Type::Path(path) = &**ty else { return None };
+		let seg = path.path.segments.last()?;
+		if seg.ident != "Result" {
+			return None;
+		}
+
+		let syn::PathArguments::AngleBracketed(args) = &seg.arguments else { return None };
+		let mut tys = args.args.iter().filter_map(|arg| match arg {
+			syn::GenericArgument::Type(ty) => Some(ty),
+			_ => None,
+		});
+		let stream = tys.next()?;
+		let item = stream_item(stream)?;
+		let error = tys.next()?;
+
+		Some(Self { item: item.clone(), error: Some(error.clone()), trait_path: Self::trait_path(stream)? })
+	}
+
+	fn trait_path(ty: &syn::Type) -> Option<syn::Path> {
+		let mut path = stream_bound(ty)?.path.clone();
+		path.segments.last_mut()?.arguments = syn::PathArguments::None;
+		Some(path)
+	}
 }
 
 impl RpcSubscription {
-	pub fn from_item(attr: syn::Attribute, mut sub: syn::TraitItemFn) -> syn::Result<Self> {
-		let [aliases, item, name, param_kind, unsubscribe, unsubscribe_aliases, with_extensions] =
+	pub fn from_item(
+		attr: syn::Attribute,
+		mut sub: syn::TraitItemFn,
+		default_param_kind: ParamKind,
+		trait_versions: &[String],
+	) -> syn::Result<Self> {
+		let [aliases, item, name, param_kind, unsubscribe, unsubscribe_aliases, versions, with_extensions] =
 			AttributeMeta::parse(attr)?.retain([
 				"aliases",
 				"item",
@@ -192,6 +319,7 @@ impl RpcSubscription {
 				"param_kind",
 				"unsubscribe",
 				"unsubscribe_aliases",
+				"versions",
 				"with_extensions",
 			])?;
 
@@ -199,10 +327,16 @@ impl RpcSubscription {
 		let map = name?.value::<NameMapping>()?;
 		let name = map.name;
 		let notif_name_override = map.mapped;
-		let item = item?.value()?;
-		let param_kind = parse_param_kind(param_kind)?;
+		let stream = StreamReturn::from_return_type(&sub.sig.output);
+		// The item of a stream subscription defaults to the item of the stream.
+		let item = match &stream {
+			Some(stream) => optional(item, Argument::value::<syn::Type>)?.unwrap_or_else(|| stream.item.clone()),
+			None => item?.value()?,
+		};
+		let param_kind = parse_param_kind(param_kind)?.unwrap_or(default_param_kind);
 		let unsubscribe_aliases = parse_aliases(unsubscribe_aliases)?;
 		let with_extensions = optional(with_extensions, Argument::flag)?.is_some();
+		let versions = parse_versions(versions, trait_versions)?;
 
 		let docs = extract_doc_comments(&sub.attrs);
 		let unsubscribe = match parse_subscribe(unsubscribe)? {
@@ -226,6 +360,7 @@ impl RpcSubscription {
 				},
 			})
 			.collect::<Result<_, _>>()?;
+		let (context_params, params) = split_context_params(params, with_extensions, &sub)?;
 
 		// We've analyzed attributes and don't need them anymore.
 		sub.attrs.clear();
@@ -242,11 +377,14 @@ impl RpcSubscription {
 			aliases,
 			docs,
 			with_extensions,
+			context_params,
+			stream,
+			versions,
 		})
 	}
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct RpcDescription {
 	/// Path to the `jsonrpsee` client types part.
 	pub(crate) jsonrpsee_client_path: Option<TokenStream2>,
@@ -260,30 +398,59 @@ pub struct RpcDescription {
 	/// Assuming that trait to which attribute is applied is named `Foo`, the generated
 	/// client trait will have `FooClient` name.
 	pub(crate) needs_client: bool,
+	/// Switch denoting that the server trait must have a function that generates an OpenRPC document.
+	pub(crate) needs_openrpc: bool,
+	/// Switch denoting that a mock of the client must be generated.
+	/// Assuming that trait to which attribute is applied is named `Foo`, the generated
+	/// mock will have `MockFooClient` name.
+	pub(crate) needs_mock: bool,
 	/// Optional prefix for RPC namespace.
 	pub(crate) namespace: Option<String>,
+	/// Versions of the namespace. Each method and subscription is registered under the first version it's
+	/// available in and aliased in the namespaces of the other versions.
+	pub(crate) versions: Vec<String>,
 	/// Trait definition in which all the attributes were stripped.
 	pub(crate) trait_def: syn::ItemTrait,
 	/// List of RPC methods defined in the trait.
 	pub(crate) methods: Vec<RpcMethod>,
 	/// List of RPC subscriptions defined in the trait.
 	pub(crate) subscriptions: Vec<RpcSubscription>,
+	/// List of associated types defined in the trait.
+	pub(crate) assoc_types: Vec<syn::TraitItemType>,
 	/// Optional user defined trait bounds for the client implementation.
 	pub(crate) client_bounds: Option<Punctuated<syn::WherePredicate, Token![,]>>,
 	/// Optional user defined trait bounds for the server implementation.
 	pub(crate) server_bounds: Option<Punctuated<syn::WherePredicate, Token![,]>>,
+	/// Optional error type of the methods of the server implementation which return `RpcResult`.
+	pub(crate) error: Option<syn::Type>,
 }
 
 impl RpcDescription {
 	pub fn from_item(attr: Attribute, mut item: syn::ItemTrait) -> syn::Result<Self> {
-		let [client, server, namespace, client_bounds, server_bounds] =
-			AttributeMeta::parse(attr)?.retain(["client", "server", "namespace", "client_bounds", "server_bounds"])?;
+		let [client, server, namespace, versions, client_bounds, server_bounds, openrpc, mock, param_kind, error] =
+			AttributeMeta::parse(attr)?.retain([
+				"client",
+				"server",
+				"namespace",
+				"versions",
+				"client_bounds",
+				"server_bounds",
+				"openrpc",
+				"mock",
+				"param_kind",
+				"error",
+			])?;
 
 		let needs_server = optional(server, Argument::flag)?.is_some();
 		let needs_client = optional(client, Argument::flag)?.is_some();
 		let namespace = optional(namespace, Argument::string)?;
+		let versions = optional(versions, Argument::group::<LitStr>)?;
 		let client_bounds = optional(client_bounds, Argument::group)?;
 		let server_bounds = optional(server_bounds, Argument::group)?;
+		let needs_openrpc = optional(openrpc, Argument::flag)?.is_some();
+		let needs_mock = optional(mock, Argument::flag)?.is_some();
+		let param_kind = parse_param_kind(param_kind)?.unwrap_or(ParamKind::Array);
+		let error = optional(error, Argument::value::<syn::Type>)?;
 		if !needs_server && !needs_client {
 			return Err(syn::Error::new_spanned(&item.ident, "Either 'server' or 'client' attribute must be applied"));
 		}
@@ -302,6 +469,36 @@ impl RpcDescription {
 			));
 		}
 
+		if needs_openrpc && !needs_server {
+			return Err(syn::Error::new_spanned(&item.ident, "Attribute 'server' must be specified with 'openrpc'"));
+		}
+
+		if needs_mock && !needs_client {
+			return Err(syn::Error::new_spanned(&item.ident, "Attribute 'client' must be specified with 'mock'"));
+		}
+
+		if error.is_some() && !needs_server {
+			return Err(syn::Error::new_spanned(&item.ident, "Attribute 'server' must be specified with 'error'"));
+		}
+
+		if versions.is_some() && namespace.is_none() {
+			return Err(syn::Error::new_spanned(
+				&item.ident,
+				"Attribute 'namespace' must be specified with 'versions'",
+			));
+		}
+
+		let versions = match versions {
+			Some(versions) if versions.is_empty() => {
+				return Err(syn::Error::new_spanned(
+					&item.ident,
+					"Attribute 'versions' must list at least one version",
+				));
+			}
+			Some(versions) => versions.into_iter().map(parse_version).collect::<syn::Result<Vec<_>>>()?,
+			None => Vec::new(),
+		};
+
 		let jsonrpsee_client_path = crate::helpers::find_jsonrpsee_client_crate().ok();
 		let jsonrpsee_server_path = crate::helpers::find_jsonrpsee_server_crate().ok();
 
@@ -316,11 +513,21 @@ impl RpcDescription {
 
 		let mut methods = Vec::new();
 		let mut subscriptions = Vec::new();
+		let mut assoc_types = Vec::new();
 
-		// Go through all the methods in the trait and collect methods and
-		// subscriptions.
+		// Go through all the methods in the trait and collect methods,
+		// subscriptions and associated types.
 		for entry in item.items.iter() {
-			if let syn::TraitItem::Fn(method) = entry {
+			if let syn::TraitItem::Type(ty) = entry {
+				if ty.default.is_some() || !ty.generics.params.is_empty() || ty.generics.where_clause.is_some() {
+					return Err(syn::Error::new_spanned(
+						ty,
+						"Associated types in RPC traits can't have defaults, generics or where clauses",
+					));
+				}
+
+				assoc_types.push(ty.clone());
+			} else if let syn::TraitItem::Fn(method) = entry {
 				if method.sig.receiver().is_none() {
 					return Err(syn::Error::new_spanned(&method.sig, "First argument of the trait must be '&self'"));
 				}
@@ -330,7 +537,7 @@ impl RpcDescription {
 				if let Some(attr) = find_attr(&method.attrs, "method") {
 					is_method = true;
 
-					let method_data = RpcMethod::from_item(attr.clone(), method.clone())?;
+					let method_data = RpcMethod::from_item(attr.clone(), method.clone(), param_kind, &versions)?;
 
 					methods.push(method_data);
 				}
@@ -343,7 +550,7 @@ impl RpcDescription {
 						));
 					}
 
-					let sub_data = RpcSubscription::from_item(attr.clone(), method.clone())?;
+					let sub_data = RpcSubscription::from_item(attr.clone(), method.clone(), param_kind, &versions)?;
 					subscriptions.push(sub_data);
 				}
 
@@ -354,7 +561,7 @@ impl RpcDescription {
 					));
 				}
 			} else {
-				return Err(syn::Error::new_spanned(entry, "Only methods allowed in RPC traits"));
+				return Err(syn::Error::new_spanned(entry, "Only methods and associated types allowed in RPC traits"));
 			}
 		}
 
@@ -362,17 +569,32 @@ impl RpcDescription {
 			return Err(syn::Error::new_spanned(&item, "RPC cannot be empty"));
 		}
 
+		if error.is_some() {
+			let returns = methods.iter().filter_map(|method| method.returns.as_ref());
+			if let Some(ty) = returns.filter(|ty| is_named_rpc_result(ty)).find(|ty| rpc_result_type(ty).is_none()) {
+				return Err(syn::Error::new_spanned(
+					ty,
+					"Methods must return 'jsonrpsee::core::RpcResult' with its full path if 'error' is specified",
+				));
+			}
+		}
+
 		Ok(Self {
 			jsonrpsee_client_path,
 			jsonrpsee_server_path,
 			needs_server,
 			needs_client,
+			needs_openrpc,
+			needs_mock,
 			namespace,
+			versions,
 			trait_def: item,
 			methods,
 			subscriptions,
+			assoc_types,
 			client_bounds,
 			server_bounds,
+			error,
 		})
 	}
 
@@ -411,6 +633,12 @@ impl RpcDescription {
 			Cow::Borrowed(method)
 		}
 	}
+
+	/// Renders the namespace of a version, for instance `foo_v2` for namespace `foo` and version `2`.
+	pub(crate) fn versioned_namespace(&self, version: &str) -> String {
+		let ns = self.namespace.as_ref().expect("versions require a namespace; qed");
+		format!("{ns}_v{version}")
+	}
 }
 
 fn parse_aliases(arg: Result<Argument, MissingArgument>) -> syn::Result<Vec<String>> {
@@ -419,6 +647,67 @@ fn parse_aliases(arg: Result<Argument, MissingArgument>) -> syn::Result<Vec<Stri
 	Ok(aliases.map(|a| a.list.into_iter().map(|lit| lit.value()).collect()).unwrap_or_default())
 }
 
+/// Versions are part of the names of the methods and of the client traits, so they must be made of ASCII
+/// letters, digits and underscores.
+fn parse_version(lit: LitStr) -> syn::Result<String> {
+	let version = lit.value();
+
+	if version.is_empty() || !version.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+		return Err(syn::Error::new(lit.span(), "Versions must only contain ASCII letters, digits and underscores"));
+	}
+
+	Ok(version)
+}
+
+/// Parses the versions in which a method or subscription is available, defaults to all the versions of the trait.
+fn parse_versions(arg: Result<Argument, MissingArgument>, trait_versions: &[String]) -> syn::Result<Vec<String>> {
+	let Some(versions) = optional(arg, Argument::group::<LitStr>)? else {
+		return Ok(trait_versions.to_vec());
+	};
+
+	if versions.is_empty() {
+		return Err(syn::Error::new(versions.span(), "Attribute 'versions' must list at least one version"));
+	}
+
+	versions
+		.into_iter()
+		.map(|lit| {
+			if trait_versions.contains(&lit.value()) {
+				Ok(lit.value())
+			} else if trait_versions.is_empty() {
+				Err(syn::Error::new(lit.span(), "Versions must be declared with the 'versions' attribute of the trait"))
+			} else {
+				Err(syn::Error::new(
+					lit.span(),
+					format!("Unknown version `{}`, expected one of: `{}`", lit.value(), trait_versions.join("`, `")),
+				))
+			}
+		})
+		.collect()
+}
+
+/// Splits the `context` arguments of a method or subscription from its RPC params.
+fn split_context_params(
+	params: Vec<RpcFnArg>,
+	with_extensions: bool,
+	method: &syn::TraitItemFn,
+) -> syn::Result<(Vec<RpcFnArg>, Vec<RpcFnArg>)> {
+	let (context_params, params): (Vec<_>, Vec<_>) = params.into_iter().partition(|arg| arg.context);
+
+	if with_extensions && !context_params.is_empty() {
+		return Err(syn::Error::new_spanned(
+			&method.sig,
+			"`with_extensions` can't be combined with `context` arguments, use an `&Extensions` argument instead",
+		));
+	}
+
+	Ok((context_params, params))
+}
+
+fn is_extensions(ty: &syn::Type) -> bool {
+	matches!(ty, syn::Type::Path(path) if path.path.segments.last().is_some_and(|seg| seg.ident == "Extensions"))
+}
+
 fn parse_subscribe(arg: Result<Argument, MissingArgument>) -> syn::Result<Option<String>> {
 	let unsub = optional(arg, Argument::string)?;
 
@@ -0,0 +1,123 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any
+// person obtaining a copy of this software and associated
+// documentation files (the "Software"), to deal in the
+// Software without restriction, including without
+// limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software
+// is furnished to do so, subject to the following
+// conditions:
+//
+// The above copyright notice and this permission notice
+// shall be included in all copies or substantial portions
+// of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF
+// ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED
+// TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A
+// PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT
+// SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY
+// CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+// IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Derive of `ToRpcParams` for structs.
+
+use crate::attributes::{optional, parse_param_kind, Argument, AttributeMeta, ParamKind};
+use crate::helpers::find_jsonrpsee_client_crate;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_quote, spanned::Spanned, Data, DeriveInput, Error, Fields, Index, Member};
+
+/// Name of the helper attribute of the derive.
+const ATTRIBUTE: &str = "rpc_params";
+
+/// Renders the `ToRpcParams` implementation of the struct.
+pub(crate) fn derive(input: DeriveInput) -> syn::Result<TokenStream2> {
+	let jsonrpsee = find_jsonrpsee_client_crate()?;
+
+	let param_kind = match find_attribute(&input.attrs) {
+		Some(attr) => {
+			let [param_kind] = AttributeMeta::parse(attr.clone())?.retain(["param_kind"])?;
+			parse_param_kind(param_kind)?.unwrap_or(ParamKind::Array)
+		}
+		None => ParamKind::Array,
+	};
+
+	let fields = match &input.data {
+		Data::Struct(data) => &data.fields,
+		_ => return Err(Error::new(input.ident.span(), "`RpcParams` can only be derived for structs")),
+	};
+
+	if matches!(param_kind, ParamKind::Map) && matches!(fields, Fields::Unnamed(_)) {
+		return Err(Error::new(fields.span(), "`param_kind = map` requires a struct with named fields"));
+	}
+
+	let mut inserts = Vec::new();
+	for (idx, field) in fields.iter().enumerate() {
+		let (rename, skip) = match find_attribute(&field.attrs) {
+			Some(attr) => {
+				let [rename, skip] = AttributeMeta::parse(attr.clone())?.retain(["rename", "skip"])?;
+
+				// Positional parameters have no names, so a rename would be silently dropped.
+				if let (ParamKind::Array, Ok(rename)) = (param_kind, &rename) {
+					return Err(Error::new(rename.label.span(), "`rename` requires `param_kind = map` on the struct"));
+				}
+
+				(optional(rename, Argument::string)?, optional(skip, Argument::flag)?.is_some())
+			}
+			None => (None, false),
+		};
+
+		if skip {
+			continue;
+		}
+
+		let member = match &field.ident {
+			Some(ident) => Member::Named(ident.clone()),
+			None => Member::Unnamed(Index::from(idx)),
+		};
+
+		inserts.push(match param_kind {
+			ParamKind::Array => quote!(params.insert(self.#member)?;),
+			ParamKind::Map => {
+				let name = rename.unwrap_or_else(|| field.ident.as_ref().expect("Named fields; qed").to_string());
+				quote!(params.insert(#name, self.#member)?;)
+			}
+		});
+	}
+
+	let params = match param_kind {
+		ParamKind::Array => quote!(#jsonrpsee::core::params::ArrayParams),
+		ParamKind::Map => quote!(#jsonrpsee::core::params::ObjectParams),
+	};
+
+	let mut generics = input.generics.clone();
+	for param in generics.type_params_mut() {
+		param.bounds.push(parse_quote!(#jsonrpsee::core::__reexports::serde::Serialize));
+	}
+	let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+	let ident = &input.ident;
+
+	Ok(quote! {
+		impl #impl_generics #jsonrpsee::core::traits::ToRpcParams for #ident #ty_generics #where_clause {
+			fn to_rpc_params(
+				self,
+			) -> ::core::result::Result<
+				::core::option::Option<::std::boxed::Box<#jsonrpsee::core::JsonRawValue>>,
+				#jsonrpsee::core::__reexports::serde_json::Error,
+			> {
+				let mut params = #params::new();
+				#(#inserts)*
+				#jsonrpsee::core::traits::ToRpcParams::to_rpc_params(params)
+			}
+		}
+	})
+}
+
+fn find_attribute(attrs: &[syn::Attribute]) -> Option<&syn::Attribute> {
+	attrs.iter().find(|attr| attr.path().is_ident(ATTRIBUTE))
+}
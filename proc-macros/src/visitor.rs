@@ -27,6 +27,7 @@
 use std::collections::HashSet;
 
 use syn::visit::{self, Visit};
+use syn::visit_mut::{self, VisitMut};
 use syn::Ident;
 
 /// Visitor that parses generic type parameters from `syn::Type` by traversing the AST.
@@ -36,6 +37,7 @@ use syn::Ident;
 pub(crate) struct FindSubscriptionParams {
 	pub(crate) generic_sub_params: HashSet<Ident>,
 	pub(crate) all_type_params: HashSet<Ident>,
+	pub(crate) assoc_types: HashSet<Ident>,
 }
 
 /// Visitor for the entire `RPC trait`.
@@ -64,6 +66,16 @@ impl FindAllParams {
 }
 
 impl<'ast> Visit<'ast> for FindAllParams {
+	/// Visit the trait, its associated types are handled like generic type params.
+	fn visit_item_trait(&mut self, item_trait: &'ast syn::ItemTrait) {
+		for item in &item_trait.items {
+			if let syn::TraitItem::Type(ty) = item {
+				self.trait_generics.insert(ty.ident.clone());
+			}
+		}
+		visit::visit_item_trait(self, item_trait);
+	}
+
 	/// Visit generic type param.
 	fn visit_type_param(&mut self, ty_param: &'ast syn::TypeParam) {
 		self.trait_generics.insert(ty_param.ident.clone());
@@ -108,14 +120,15 @@ impl FindSubscriptionParams {
 	}
 
 	/// Create a new subscription parameters visitor that takes all
-	/// generic parameters on the RPC trait as input in order to determine
+	/// generic parameters and associated types on the RPC trait as input in order to determine
 	/// whether a given ident is a generic type param or not when traversing
 	/// one or more types in `FindSubscriptionParams::visit`.
-	pub fn new(all_type_params: HashSet<Ident>) -> Self {
-		Self { generic_sub_params: HashSet::new(), all_type_params }
+	pub fn new(all_type_params: HashSet<Ident>, assoc_types: HashSet<Ident>) -> Self {
+		Self { generic_sub_params: HashSet::new(), all_type_params, assoc_types }
 	}
 
-	/// Visit path, if it's a leaf path and generic type param then add it as a subscription param.
+	/// Visit path, if it's a leaf path and generic type param or `Self::Assoc` with an associated
+	/// type then add it as a subscription param.
 	fn visit_path(&mut self, path: &syn::Path) {
 		if path.leading_colon.is_none() && path.segments.len() == 1 {
 			let id = &path.segments[0].ident;
@@ -123,6 +136,12 @@ impl FindSubscriptionParams {
 				self.generic_sub_params.insert(id.clone());
 			}
 		}
+		if path.leading_colon.is_none() && path.segments.len() == 2 && path.segments[0].ident == "Self" {
+			let id = &path.segments[1].ident;
+			if self.assoc_types.contains(id) {
+				self.generic_sub_params.insert(id.clone());
+			}
+		}
 		for segment in &path.segments {
 			self.visit_path_segment(segment);
 		}
@@ -221,6 +240,37 @@ impl FindSubscriptionParams {
 	fn visit_macro(&mut self, _mac: &syn::Macro) {}
 }
 
+/// Visitor that replaces the associated types of the RPC trait, for instance `Self::Hash`,
+/// by the generic type params of the same name.
+pub(crate) struct ReplaceAssocTypes {
+	pub(crate) assoc_types: HashSet<Ident>,
+}
+
+impl ReplaceAssocTypes {
+	/// Create a visitor that replaces the given associated types.
+	pub fn new(assoc_types: HashSet<Ident>) -> Self {
+		Self { assoc_types }
+	}
+}
+
+impl VisitMut for ReplaceAssocTypes {
+	/// Replace `Self::Assoc` by `Assoc`.
+	fn visit_type_path_mut(&mut self, ty: &mut syn::TypePath) {
+		let segments = &ty.path.segments;
+		if ty.qself.is_none()
+			&& ty.path.leading_colon.is_none()
+			&& segments.len() == 2
+			&& segments[0].ident == "Self"
+			&& segments[1].arguments.is_none()
+			&& self.assoc_types.contains(&segments[1].ident)
+		{
+			ty.path = segments[1].ident.clone().into();
+			return;
+		}
+		visit_mut::visit_type_path_mut(self, ty);
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -235,7 +285,7 @@ mod tests {
 		exp.insert(id);
 		let generics = exp.clone();
 
-		assert_eq!(exp, FindSubscriptionParams::new(generics).visit(&[t]));
+		assert_eq!(exp, FindSubscriptionParams::new(generics, HashSet::new()).visit(&[t]));
 	}
 
 	#[test]
@@ -254,7 +304,7 @@ mod tests {
 		exp.insert(parse_quote!(B));
 		exp.insert(parse_quote!(C));
 
-		assert_eq!(exp, FindSubscriptionParams::new(generics).visit(&[t]));
+		assert_eq!(exp, FindSubscriptionParams::new(generics, HashSet::new()).visit(&[t]));
 	}
 
 	#[test]
@@ -272,6 +322,35 @@ mod tests {
 		exp.insert(parse_quote!(A));
 		exp.insert(parse_quote!(B));
 
-		assert_eq!(exp, FindSubscriptionParams::new(generics).visit(&[t]));
+		assert_eq!(exp, FindSubscriptionParams::new(generics, HashSet::new()).visit(&[t]));
+	}
+
+	#[test]
+	fn assoc_type() {
+		let t: Type = parse_quote!(Vec<(Self::A, B, Other::C)>);
+
+		let mut assoc_types: HashSet<syn::Ident> = HashSet::new();
+		let mut exp = HashSet::new();
+
+		assoc_types.insert(parse_quote!(A));
+		assoc_types.insert(parse_quote!(B));
+		assoc_types.insert(parse_quote!(C));
+
+		exp.insert(parse_quote!(A));
+
+		assert_eq!(exp, FindSubscriptionParams::new(HashSet::new(), assoc_types).visit(&[t]));
+	}
+
+	#[test]
+	fn replace_assoc_types() {
+		let mut t: Type = parse_quote!(Result<Vec<Self::A>, <Self as Foo>::B>);
+		let mut assoc_types: HashSet<syn::Ident> = HashSet::new();
+		assoc_types.insert(parse_quote!(A));
+		assoc_types.insert(parse_quote!(B));
+
+		ReplaceAssocTypes::new(assoc_types).visit_type_mut(&mut t);
+
+		let exp: Type = parse_quote!(Result<Vec<A>, <Self as Foo>::B>);
+		assert_eq!(exp, t);
 	}
 }
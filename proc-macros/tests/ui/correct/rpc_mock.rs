@@ -0,0 +1,25 @@
+//! Test to check that the mock client is generated with documentation.
+
+#![deny(missing_docs)]
+
+use jsonrpsee::proc_macros::rpc;
+
+#[rpc(client, mock)]
+pub trait Api {
+	/// Async method.
+	#[method(name = "foo")]
+	async fn async_method(&self, param_a: u8) -> jsonrpsee::core::RpcResult<u16>;
+
+	/// Subscription docs.
+	#[subscription(name = "sub", unsubscribe = "unsub", item = String)]
+	async fn sub(&self);
+}
+
+#[tokio::main]
+async fn main() {
+	let mock = MockApiClient::new();
+	mock.expect_async_method(Ok(10)).expect_sub(["a".to_string()]);
+
+	assert_eq!(mock.async_method(1).await.unwrap(), 10);
+	assert_eq!(mock.calls().len(), 1);
+}
@@ -0,0 +1,40 @@
+use jsonrpsee::core::traits::ToRpcParams;
+use jsonrpsee::proc_macros::RpcParams;
+
+#[derive(RpcParams)]
+struct Positional<'a> {
+	from: &'a str,
+	amount: u64,
+	#[rpc_params(skip)]
+	_note: String,
+}
+
+#[derive(RpcParams)]
+#[rpc_params(param_kind = map)]
+struct Named<T> {
+	from: String,
+	#[rpc_params(rename = "toAddress")]
+	to: String,
+	amount: T,
+}
+
+#[derive(RpcParams)]
+struct Tuple(u8, &'static str);
+
+#[derive(RpcParams)]
+struct Empty;
+
+fn params(p: impl ToRpcParams) -> Option<String> {
+	p.to_rpc_params().unwrap().map(|raw| raw.get().to_owned())
+}
+
+fn main() {
+	let positional = Positional { from: "alice", amount: 10, _note: "lunch".into() };
+	assert_eq!(params(positional).as_deref(), Some(r#"["alice",10]"#));
+
+	let named = Named { from: "alice".into(), to: "bob".into(), amount: 10_u64 };
+	assert_eq!(params(named).as_deref(), Some(r#"{"from":"alice","toAddress":"bob","amount":10}"#));
+
+	assert_eq!(params(Tuple(1, "a")).as_deref(), Some(r#"[1,"a"]"#));
+	assert_eq!(params(Empty), None);
+}
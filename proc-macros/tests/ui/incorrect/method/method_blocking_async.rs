@@ -0,0 +1,10 @@
+use jsonrpsee::proc_macros::rpc;
+
+// Blocking methods must be synchronous.
+#[rpc(client, server)]
+pub trait BlockingAsync {
+	#[method(name = "foo", blocking)]
+	async fn async_method(&self) -> jsonrpsee::core::RpcResult<u8>;
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use jsonrpsee::proc_macros::rpc;
+
+#[rpc(server)]
+pub trait ContextReference {
+	#[method(name = "foo")]
+	fn foo(&self, #[argument(context)] name: &String) -> jsonrpsee::core::RpcResult<u8>;
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use jsonrpsee::proc_macros::rpc;
+
+// `default` can't be used with `Option` arguments.
+#[rpc(client, server)]
+pub trait DefaultOptionArg {
+	#[method(name = "foo")]
+	async fn async_method(&self, #[argument(default = Some(1))] a: Option<u8>) -> jsonrpsee::core::RpcResult<u8>;
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use jsonrpsee::proc_macros::rpc;
+
+// Methods with a timeout must be asynchronous.
+#[rpc(client, server)]
+pub trait TimeoutSync {
+	#[method(name = "foo", timeout_ms = 100)]
+	fn sync_method(&self) -> jsonrpsee::core::RpcResult<u8>;
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use jsonrpsee::proc_macros::rpc;
+
+#[rpc(client, server, namespace = "foo", versions("1", "2"))]
+pub trait UnknownVersion {
+	#[method(name = "bar", versions("3"))]
+	async fn bar(&self) -> jsonrpsee::core::RpcResult<u8>;
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use jsonrpsee::proc_macros::RpcParams;
+
+// Positional parameters can't be renamed.
+#[derive(RpcParams)]
+struct Transfer {
+	from: String,
+	#[rpc_params(rename = "toAddress")]
+	to: String,
+}
+
+fn main() {}
@@ -0,0 +1,20 @@
+use jsonrpsee::proc_macros::rpc;
+use jsonrpsee::types::ErrorObjectOwned;
+
+pub type RpcResult<T> = Result<T, ErrorObjectOwned>;
+
+pub struct MyError;
+
+impl From<MyError> for ErrorObjectOwned {
+	fn from(_: MyError) -> Self {
+		ErrorObjectOwned::owned(1, "my error", None::<()>)
+	}
+}
+
+#[rpc(server, error = MyError)]
+pub trait ErrorWithRpcResultAlias {
+	#[method(name = "foo")]
+	async fn foo(&self) -> RpcResult<u8>;
+}
+
+fn main() {}
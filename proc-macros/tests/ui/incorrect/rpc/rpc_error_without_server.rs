@@ -0,0 +1,18 @@
+use jsonrpsee::proc_macros::rpc;
+use jsonrpsee::types::ErrorObjectOwned;
+
+pub struct MyError;
+
+impl From<MyError> for ErrorObjectOwned {
+	fn from(_: MyError) -> Self {
+		ErrorObjectOwned::owned(1, "my error", None::<()>)
+	}
+}
+
+#[rpc(client, error = MyError)]
+pub trait ErrorWithoutServer {
+	#[method(name = "foo")]
+	async fn foo(&self) -> Result<u8, MyError>;
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use jsonrpsee::proc_macros::rpc;
+
+#[rpc(client, server, versions("1", "2"))]
+pub trait VersionsWithoutNamespace {
+	#[method(name = "foo")]
+	async fn foo(&self) -> jsonrpsee::core::RpcResult<u8>;
+}
+
+fn main() {}
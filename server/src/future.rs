@@ -26,13 +26,17 @@
 
 //! Utilities for handling async code.
 
+use std::net::TcpListener as StdTcpListener;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
 
 use futures_util::{Future, Stream, StreamExt};
+use jsonrpsee_core::server::SubscriptionStats;
+
+use crate::Utf8Stats;
 use pin_project::pin_project;
-use tokio::sync::{watch, OwnedSemaphorePermit, Semaphore, TryAcquireError};
+use tokio::sync::{mpsc, oneshot, watch, OwnedSemaphorePermit, Semaphore, TryAcquireError};
 use tokio::time::Interval;
 use tokio_stream::wrappers::BroadcastStream;
 
@@ -40,7 +44,9 @@ use tokio_stream::wrappers::BroadcastStream;
 /// the server shall continue to run or not.
 pub fn stop_channel() -> (StopHandle, ServerHandle) {
 	let (tx, rx) = tokio::sync::watch::channel(());
-	(StopHandle::new(rx), ServerHandle::new(tx))
+	// There is no listener to hand off.
+	let (handoff_tx, _) = mpsc::channel(1);
+	(StopHandle::new(rx), ServerHandle::new(tx, SubscriptionStats::new(), Utf8Stats::default(), handoff_tx))
 }
 
 /// Represent a stop handle which is a wrapper over a `multi-consumer receiver`
@@ -59,6 +65,11 @@ impl StopHandle {
 	pub async fn shutdown(mut self) {
 		let _ = self.0.changed().await;
 	}
+
+	/// Whether the server has been stopped.
+	pub(crate) fn is_stopped(&self) -> bool {
+		self.0.has_changed().unwrap_or(true)
+	}
 }
 
 /// Error when the server has already been stopped.
@@ -66,32 +77,119 @@ impl StopHandle {
 #[error("The server is already stopped")]
 pub struct AlreadyStoppedError;
 
+/// Error when the listener of the server can't be handed off, see [`ServerHandle::handoff`].
+#[derive(Debug, thiserror::Error)]
+pub enum HandoffError {
+	/// The server is stopped or the listener was already handed off.
+	#[error("The server is stopped or the listener was already handed off")]
+	Unavailable,
+	/// The listener couldn't be converted into a standard library listener.
+	#[error("The listener could not be handed off: {0}")]
+	Io(#[from] std::io::Error),
+}
+
+/// Requests the accept loop of the server to give up its listener.
+pub(crate) type HandoffRequest = oneshot::Sender<std::io::Result<StdTcpListener>>;
+
 /// Server handle.
 ///
 /// When all [`StopHandle`]'s have been `dropped` or `stop` has been called
 /// the server will be stopped.
 #[derive(Debug, Clone)]
-pub struct ServerHandle(Arc<watch::Sender<()>>);
+pub struct ServerHandle {
+	stop_tx: Arc<watch::Sender<()>>,
+	sub_stats: SubscriptionStats,
+	utf8_stats: Utf8Stats,
+	handoff_tx: mpsc::Sender<HandoffRequest>,
+}
 
 impl ServerHandle {
 	/// Create a new server handle.
-	pub(crate) fn new(tx: watch::Sender<()>) -> Self {
-		Self(Arc::new(tx))
+	pub(crate) fn new(
+		stop_tx: watch::Sender<()>,
+		sub_stats: SubscriptionStats,
+		utf8_stats: Utf8Stats,
+		handoff_tx: mpsc::Sender<HandoffRequest>,
+	) -> Self {
+		Self { stop_tx: Arc::new(stop_tx), sub_stats, utf8_stats, handoff_tx }
+	}
+
+	/// Get the statistics of the subscriptions on the server.
+	///
+	/// The statistics are only tracked for the server that returned this handle from
+	/// [`Server::start`](crate::Server::start) and are empty for the handle of [`stop_channel`].
+	pub fn subscription_stats(&self) -> &SubscriptionStats {
+		&self.sub_stats
+	}
+
+	/// Get the statistics of the invalid UTF-8 in the messages on the server, see
+	/// [`ServerBuilder::set_utf8_policy`](crate::ServerBuilder::set_utf8_policy).
+	///
+	/// Like the subscription statistics these are empty for the handle of [`stop_channel`].
+	pub fn utf8_stats(&self) -> &Utf8Stats {
+		&self.utf8_stats
 	}
 
 	/// Tell the server to stop without waiting for the server to stop.
 	pub fn stop(&self) -> Result<(), AlreadyStoppedError> {
-		self.0.send(()).map_err(|_| AlreadyStoppedError)
+		self.stop_tx.send(()).map_err(|_| AlreadyStoppedError)
 	}
 
 	/// Wait for the server to stop.
 	pub async fn stopped(self) {
-		self.0.closed().await
+		self.stop_tx.closed().await
 	}
 
 	/// Check if the server has been stopped.
 	pub fn is_stopped(&self) -> bool {
-		self.0.is_closed()
+		self.stop_tx.is_closed()
+	}
+
+	/// Stop accepting connections and hand off the listener, such that another server,
+	/// typically a new process during a restart, can accept the connections instead.
+	///
+	/// The server keeps serving the connections that it already accepted, including the subscriptions
+	/// on them, until they are closed by the peers or the server is stopped. Thus [`ServerHandle::stopped`]
+	/// resolves once the connections have been drained.
+	///
+	/// The listener is handed off within the process, and both servers started with [`Server::start`](crate::Server::start)
+	/// and [`Server::start_with_router`](crate::Server::start_with_router) support it. The other server is created from
+	/// the listener with [`ServerBuilder::build_from_tcp`](crate::ServerBuilder::build_from_tcp).
+	///
+	/// The listener is in non-blocking mode, which `build_from_tcp` expects, call
+	/// [`TcpListener::set_nonblocking`](std::net::TcpListener::set_nonblocking) to use it with blocking I/O instead.
+	/// On Unix its descriptor has the `FD_CLOEXEC` flag set, like all the sockets that are created by the
+	/// standard library and tokio, so it's not inherited by a process that is spawned or executed afterwards.
+	/// To pass it to another process the flag must be cleared first, for instance with `fcntl`.
+	/// Alternatively both servers may listen on the same address at once without passing the listener, see
+	/// [`ServerBuilder::set_reuse_port`](crate::ServerBuilder::set_reuse_port).
+	///
+	/// Fails if the server is stopped, has already handed off its listener or was created by [`stop_channel`].
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	/// use std::time::Duration;
+	/// use jsonrpsee_server::{RpcModule, ServerBuilder, ServerHandle};
+	///
+	/// async fn restart(old: ServerHandle) -> ServerHandle {
+	///     let listener = old.handoff().await.unwrap();
+	///
+	///     let server = ServerBuilder::default().build_from_tcp(listener).unwrap();
+	///     let new = server.start(RpcModule::new(()));
+	///
+	///     // Give the clients of the old server some time to reconnect before closing the remaining connections.
+	///     if tokio::time::timeout(Duration::from_secs(60), old.clone().stopped()).await.is_err() {
+	///         _ = old.stop();
+	///     }
+	///     new
+	/// }
+	/// ```
+	pub async fn handoff(&self) -> Result<StdTcpListener, HandoffError> {
+		let (tx, rx) = oneshot::channel();
+		self.handoff_tx.send(tx).await.map_err(|_| HandoffError::Unavailable)?;
+		let listener = rx.await.map_err(|_| HandoffError::Unavailable)??;
+		Ok(listener)
 	}
 }
 
@@ -0,0 +1,504 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any
+// person obtaining a copy of this software and associated
+// documentation files (the "Software"), to deal in the
+// Software without restriction, including without
+// limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software
+// is furnished to do so, subject to the following
+// conditions:
+//
+// The above copyright notice and this permission notice
+// shall be included in all copies or substantial portions
+// of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF
+// ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED
+// TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A
+// PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT
+// SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY
+// CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+// IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Hooks that are invoked when connections are established and terminated,
+//! when method calls have been answered and when raw messages are exchanged,
+//! as well as the audit log of the completed calls.
+
+use std::fmt;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::{HttpBody, HttpResponse, LOG_TARGET};
+use http::HeaderMap;
+use http_body_util::BodyExt;
+use jsonrpsee_core::server::{ConnectionId, MethodResponse};
+use jsonrpsee_types::{ErrorObjectOwned, Params, Request};
+use tokio::sync::mpsc;
+
+type ConnectFuture = Pin<Box<dyn Future<Output = Result<(), HttpResponse>> + Send>>;
+type ResponseFn = dyn Fn(&ResponseInfo, MethodResponse) -> MethodResponse + Send + Sync;
+type SubscribeFn = dyn Fn(&SubscribeInfo) -> Result<(), ErrorObjectOwned> + Send + Sync;
+type AuditFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+type AuditSink = dyn Fn(AuditRecord) -> AuditFuture + Send + Sync;
+
+/// The transport a connection was established on.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum TransportKind {
+	/// A plain HTTP request.
+	Http,
+	/// A WebSocket connection.
+	WebSocket,
+}
+
+/// Information about a connection that is about to be established.
+#[derive(Debug, Clone)]
+pub struct ConnectInfo {
+	/// The connection ID.
+	pub conn_id: ConnectionId,
+	/// The address of the peer, if known.
+	pub remote_addr: Option<SocketAddr>,
+	/// The transport of the connection.
+	pub transport: TransportKind,
+	/// The headers of the HTTP request or the WebSocket handshake.
+	pub headers: HeaderMap,
+}
+
+/// Why a connection was terminated.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DisconnectReason {
+	/// The peer closed the connection or the HTTP request was answered.
+	Closed,
+	/// The server was stopped.
+	ServerStopped,
+	/// The connection failed or the HTTP request was aborted by the peer.
+	Error,
+	/// The WebSocket peer didn't answer the pings, see [`crate::PingConfig`].
+	Unresponsive,
+}
+
+/// Information about a connection that has been terminated.
+#[derive(Debug, Copy, Clone)]
+pub struct DisconnectInfo {
+	/// The connection ID.
+	pub conn_id: ConnectionId,
+	/// The address of the peer, if known.
+	pub remote_addr: Option<SocketAddr>,
+	/// The transport of the connection.
+	pub transport: TransportKind,
+	/// Why the connection was terminated.
+	pub reason: DisconnectReason,
+	/// For how long the connection was open.
+	pub duration: Duration,
+	/// The number of messages that were received on the connection.
+	pub messages: u64,
+	/// The number of calls that were still running when the connection was terminated.
+	pub pending_calls: usize,
+}
+
+/// Information about the method call that a response was produced for.
+#[derive(Debug, Clone)]
+pub struct ResponseInfo {
+	/// The connection ID.
+	pub conn_id: ConnectionId,
+	/// The name of the method that was called.
+	pub method: String,
+}
+
+/// Information about a subscribe call that is about to be dispatched, see [`crate::ServerBuilder::set_on_subscribe`].
+#[derive(Debug)]
+pub struct SubscribeInfo<'a> {
+	/// The connection ID.
+	pub conn_id: ConnectionId,
+	/// The name of the subscribe method.
+	pub method: &'a str,
+	/// The params of the call.
+	pub params: Params<'a>,
+	/// The extensions of the call, which contain for instance what the middleware inserted into the request.
+	pub extensions: &'a http::Extensions,
+}
+
+/// The direction of a [`RawMessage`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum MessageDirection {
+	/// The message was received from the peer.
+	Inbound,
+	/// The message was sent to the peer.
+	Outbound,
+}
+
+/// A copy of a raw message that was exchanged on a connection, see [`crate::ServerBuilder::set_message_tap`].
+#[derive(Debug, Clone)]
+pub struct RawMessage {
+	/// The connection ID.
+	pub conn_id: ConnectionId,
+	/// The transport of the connection.
+	pub transport: TransportKind,
+	/// Whether the message was received or sent.
+	pub direction: MessageDirection,
+	/// The bytes of the message, truncated to the configured max length.
+	pub data: Vec<u8>,
+	/// The length of the entire message in bytes.
+	pub len: usize,
+}
+
+/// The identity of the caller of a method.
+///
+/// It's expected to be inserted into the extensions of the requests, for instance by an HTTP
+/// or RPC middleware which authenticates the caller, and is included in the [`AuditRecord`]s.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CallerIdentity(pub String);
+
+/// The outcome of an audited method call.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AuditOutcome {
+	/// The call succeeded.
+	Success,
+	/// The call failed with the given error code.
+	Error(i32),
+}
+
+/// A record of a completed method call, see [`crate::ServerBuilder::set_audit_log`].
+#[derive(Debug, Clone)]
+pub struct AuditRecord {
+	/// The connection ID.
+	pub conn_id: ConnectionId,
+	/// The identity of the caller, if a [`CallerIdentity`] was inserted into the extensions of the request.
+	pub caller: Option<CallerIdentity>,
+	/// The name of the method that was called.
+	pub method: String,
+	/// The 64-bit FNV-1a hash of the raw JSON params, or of the empty string if there were no params.
+	///
+	/// The digest is stable across releases but it's not a cryptographic hash.
+	pub params_digest: u64,
+	/// The outcome of the call.
+	pub outcome: AuditOutcome,
+	/// When the call was started.
+	pub started_at: SystemTime,
+	/// For how long the call was running.
+	pub duration: Duration,
+}
+
+/// Hook invoked when a connection is established, see [`crate::ServerBuilder::set_on_connect`].
+#[derive(Clone)]
+pub(crate) struct OnConnect(Arc<dyn Fn(ConnectInfo) -> ConnectFuture + Send + Sync>);
+
+impl OnConnect {
+	pub(crate) fn new<F, Fut>(f: F) -> Self
+	where
+		F: Fn(ConnectInfo) -> Fut + Send + Sync + 'static,
+		Fut: Future<Output = Result<(), HttpResponse>> + Send + 'static,
+	{
+		Self(Arc::new(move |info| Box::pin(f(info))))
+	}
+
+	pub(crate) fn call(&self, info: ConnectInfo) -> ConnectFuture {
+		(self.0)(info)
+	}
+}
+
+impl fmt::Debug for OnConnect {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str("OnConnect")
+	}
+}
+
+/// Hook invoked when a connection is terminated, see [`crate::ServerBuilder::set_on_disconnect`].
+#[derive(Clone)]
+pub(crate) struct OnDisconnect(Arc<dyn Fn(DisconnectInfo) + Send + Sync>);
+
+impl OnDisconnect {
+	pub(crate) fn new(f: impl Fn(DisconnectInfo) + Send + Sync + 'static) -> Self {
+		Self(Arc::new(f))
+	}
+
+	pub(crate) fn call(&self, info: DisconnectInfo) {
+		(self.0)(info)
+	}
+}
+
+impl fmt::Debug for OnDisconnect {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str("OnDisconnect")
+	}
+}
+
+/// Hook invoked with the response of each method call, see [`crate::ServerBuilder::set_on_response`].
+#[derive(Clone)]
+pub(crate) struct OnResponse(Arc<ResponseFn>);
+
+impl OnResponse {
+	pub(crate) fn new(f: impl Fn(&ResponseInfo, MethodResponse) -> MethodResponse + Send + Sync + 'static) -> Self {
+		Self(Arc::new(f))
+	}
+
+	pub(crate) fn call(&self, info: &ResponseInfo, rp: MethodResponse) -> MethodResponse {
+		(self.0)(info, rp)
+	}
+}
+
+impl fmt::Debug for OnResponse {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str("OnResponse")
+	}
+}
+
+/// Hook invoked before a subscribe call is dispatched, see [`crate::ServerBuilder::set_on_subscribe`].
+#[derive(Clone)]
+pub(crate) struct OnSubscribe(Arc<SubscribeFn>);
+
+impl OnSubscribe {
+	pub(crate) fn new(f: impl Fn(&SubscribeInfo) -> Result<(), ErrorObjectOwned> + Send + Sync + 'static) -> Self {
+		Self(Arc::new(f))
+	}
+
+	pub(crate) fn call(&self, info: &SubscribeInfo) -> Result<(), ErrorObjectOwned> {
+		(self.0)(info)
+	}
+}
+
+impl fmt::Debug for OnSubscribe {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str("OnSubscribe")
+	}
+}
+
+/// Hook invoked with the raw messages of the connections, see [`crate::ServerBuilder::set_message_tap`].
+#[derive(Clone)]
+pub(crate) struct OnMessage {
+	hook: Arc<dyn Fn(RawMessage) + Send + Sync>,
+	max_len: usize,
+}
+
+impl OnMessage {
+	pub(crate) fn new(max_len: usize, f: impl Fn(RawMessage) + Send + Sync + 'static) -> Self {
+		Self { hook: Arc::new(f), max_len }
+	}
+
+	/// Bind the hook to a connection.
+	pub(crate) fn tap(&self, conn_id: ConnectionId, transport: TransportKind) -> MessageTap {
+		MessageTap { hook: self.clone(), conn_id, transport }
+	}
+}
+
+impl fmt::Debug for OnMessage {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("OnMessage").field("max_len", &self.max_len).finish()
+	}
+}
+
+/// Passes copies of the raw messages of a connection to the [`OnMessage`] hook.
+#[derive(Debug, Clone)]
+pub(crate) struct MessageTap {
+	hook: OnMessage,
+	conn_id: ConnectionId,
+	transport: TransportKind,
+}
+
+impl MessageTap {
+	pub(crate) fn inbound(&self, data: &[u8]) {
+		self.call(MessageDirection::Inbound, data)
+	}
+
+	pub(crate) fn outbound(&self, data: &[u8]) {
+		self.call(MessageDirection::Outbound, data)
+	}
+
+	/// Tap the frames of the body of an HTTP response once they are sent.
+	pub(crate) fn outbound_body(&self, rp: HttpResponse) -> HttpResponse {
+		let tap = self.clone();
+		rp.map(|body| {
+			HttpBody::new(body.map_frame(move |frame| {
+				if let Some(data) = frame.data_ref() {
+					tap.outbound(data);
+				}
+				frame
+			}))
+		})
+	}
+
+	fn call(&self, direction: MessageDirection, data: &[u8]) {
+		(self.hook.hook)(RawMessage {
+			conn_id: self.conn_id,
+			transport: self.transport,
+			direction,
+			data: data[..data.len().min(self.hook.max_len)].to_vec(),
+			len: data.len(),
+		})
+	}
+}
+
+/// Queue of [`AuditRecord`]s that are passed to the sink by a dedicated task,
+/// see [`crate::ServerBuilder::set_audit_log`].
+#[derive(Clone)]
+pub(crate) struct AuditLog(Arc<AuditLogInner>);
+
+struct AuditLogInner {
+	tx: mpsc::Sender<AuditRecord>,
+	// Taken once the task that drains the queue has been spawned.
+	worker: Mutex<Option<(mpsc::Receiver<AuditRecord>, Arc<AuditSink>)>>,
+	capacity: usize,
+	dropped: AtomicU64,
+}
+
+impl AuditLog {
+	pub(crate) fn new<F, Fut>(capacity: usize, f: F) -> Self
+	where
+		F: Fn(AuditRecord) -> Fut + Send + Sync + 'static,
+		Fut: Future<Output = ()> + Send + 'static,
+	{
+		let (tx, rx) = mpsc::channel(capacity.max(1));
+		let sink: Arc<AuditSink> = Arc::new(move |record| Box::pin(f(record)));
+		Self(Arc::new(AuditLogInner { tx, worker: Mutex::new(Some((rx, sink))), capacity, dropped: AtomicU64::new(0) }))
+	}
+
+	/// Start auditing a call.
+	pub(crate) fn begin(&self, conn_id: ConnectionId, req: &Request) -> AuditEntry {
+		let params = req.params.as_ref().map_or("", |p| p.get());
+
+		AuditEntry {
+			log: self.clone(),
+			conn_id,
+			caller: req.extensions().get::<CallerIdentity>().cloned(),
+			method: req.method_name().to_owned(),
+			params_digest: fnv1a(params.as_bytes()),
+			started_at: SystemTime::now(),
+			started: Instant::now(),
+		}
+	}
+
+	/// Spawn the task that drains the queue on the current runtime, if it hasn't been spawned yet.
+	///
+	/// The server spawns it once it's started. It's also spawned by the first record otherwise, for
+	/// instance if the calls are served by a `TowerService`, in which case the records are kept in the
+	/// queue until a record is pushed from within a runtime.
+	pub(crate) fn start(&self) {
+		let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+			return;
+		};
+
+		if let Some((mut rx, sink)) = self.0.worker.lock().expect("lock is not poisoned; qed").take() {
+			runtime.spawn(async move {
+				while let Some(record) = rx.recv().await {
+					sink(record).await;
+				}
+			});
+		}
+	}
+
+	/// Enqueue a record without waiting, the record is dropped if the queue is full.
+	fn push(&self, record: AuditRecord) {
+		self.start();
+
+		if self.0.tx.try_send(record).is_err() {
+			let dropped = self.0.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+			tracing::warn!(target: LOG_TARGET, "Audit log queue is full; dropped record (total dropped: {dropped})");
+		}
+	}
+}
+
+impl fmt::Debug for AuditLog {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("AuditLog")
+			.field("capacity", &self.0.capacity)
+			.field("dropped", &self.0.dropped.load(Ordering::Relaxed))
+			.finish()
+	}
+}
+
+/// A call that is being audited, the record is enqueued once the call has been answered.
+#[derive(Debug)]
+pub(crate) struct AuditEntry {
+	log: AuditLog,
+	conn_id: ConnectionId,
+	caller: Option<CallerIdentity>,
+	method: String,
+	params_digest: u64,
+	started_at: SystemTime,
+	started: Instant,
+}
+
+impl AuditEntry {
+	pub(crate) fn finish(self, rp: &MethodResponse) {
+		let outcome = match rp.as_error_code() {
+			Some(code) => AuditOutcome::Error(code),
+			None => AuditOutcome::Success,
+		};
+
+		self.log.push(AuditRecord {
+			conn_id: self.conn_id,
+			caller: self.caller,
+			method: self.method,
+			params_digest: self.params_digest,
+			outcome,
+			started_at: self.started_at,
+			duration: self.started.elapsed(),
+		});
+	}
+}
+
+fn fnv1a(data: &[u8]) -> u64 {
+	data.iter().fold(0xcbf2_9ce4_8422_2325, |hash, b| (hash ^ u64::from(*b)).wrapping_mul(0x0100_0000_01b3))
+}
+
+/// Invokes the [`OnDisconnect`] hook once the connection it was created for is dropped.
+#[derive(Debug)]
+pub(crate) struct DisconnectGuard {
+	hook: OnDisconnect,
+	conn_id: ConnectionId,
+	remote_addr: Option<SocketAddr>,
+	transport: TransportKind,
+	started: Instant,
+	reason: DisconnectReason,
+	messages: u64,
+	pending_calls: usize,
+}
+
+impl DisconnectGuard {
+	/// Create a new guard, the reason defaults to [`DisconnectReason::Error`] until the connection completes.
+	pub(crate) fn new(
+		hook: OnDisconnect,
+		conn_id: ConnectionId,
+		remote_addr: Option<SocketAddr>,
+		transport: TransportKind,
+	) -> Self {
+		Self {
+			hook,
+			conn_id,
+			remote_addr,
+			transport,
+			started: Instant::now(),
+			reason: DisconnectReason::Error,
+			messages: 0,
+			pending_calls: 0,
+		}
+	}
+
+	/// Record how the connection completed.
+	pub(crate) fn complete(&mut self, reason: DisconnectReason, messages: u64, pending_calls: usize) {
+		self.reason = reason;
+		self.messages = messages;
+		self.pending_calls = pending_calls;
+	}
+}
+
+impl Drop for DisconnectGuard {
+	fn drop(&mut self) {
+		self.hook.call(DisconnectInfo {
+			conn_id: self.conn_id,
+			remote_addr: self.remote_addr,
+			transport: self.transport,
+			reason: self.reason,
+			duration: self.started.elapsed(),
+			messages: self.messages,
+			pending_calls: self.pending_calls,
+		});
+	}
+}
@@ -33,6 +33,9 @@
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
 mod future;
+mod hooks;
+mod long_polling;
+mod router;
 mod server;
 mod transport;
 mod utils;
@@ -42,15 +45,25 @@ pub mod middleware;
 #[cfg(test)]
 mod tests;
 
-pub use future::{stop_channel, AlreadyStoppedError, ConnectionGuard, ConnectionPermit, ServerHandle, StopHandle};
+pub use future::{
+	stop_channel, AlreadyStoppedError, ConnectionGuard, ConnectionPermit, HandoffError, ServerHandle, StopHandle,
+};
+pub use hooks::{
+	AuditOutcome, AuditRecord, CallerIdentity, ConnectInfo, DisconnectInfo, DisconnectReason, MessageDirection,
+	RawMessage, ResponseInfo, SubscribeInfo, TransportKind,
+};
 pub use jsonrpsee_core::error::RegisterMethodError;
 pub use jsonrpsee_core::server::*;
 pub use jsonrpsee_core::{id_providers::*, traits::IdProvider};
 pub use jsonrpsee_types as types;
+pub use long_polling::{LongPollingConfig, LONG_POLLING_METHOD};
 pub use middleware::rpc::RpcServiceBuilder;
+pub use router::Router;
 pub use server::{
-	BatchRequestConfig, Builder as ServerBuilder, ConnectionState, PingConfig, Server, ServerConfig, TowerService,
-	TowerServiceBuilder,
+	BatchErrorClass, BatchExecution, BatchFailFast, BatchOverflow, BatchPolicy, BatchRequestConfig, BatchResponseOrder,
+	Builder as ServerBuilder, ConnectionState, IdPolicy, PeerInfo, PingConfig, Server, ServerConfig, ServerInfo,
+	TowerService, TowerServiceBuilder, Utf8Policy, Utf8Stats, WsFlushPolicy, WsFrameConfig, BATCH_FAIL_FAST_HEADER,
+	MAX_RESPONSE_SIZE_HEADER, SHUTDOWN_NOTIFICATION,
 };
 pub use tracing;
 
@@ -0,0 +1,294 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any
+// person obtaining a copy of this software and associated
+// documentation files (the "Software"), to deal in the
+// Software without restriction, including without
+// limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software
+// is furnished to do so, subject to the following
+// conditions:
+//
+// The above copyright notice and this permission notice
+// shall be included in all copies or substantial portions
+// of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF
+// ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED
+// TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A
+// PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT
+// SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY
+// CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+// IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Long polling of subscriptions over HTTP.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use jsonrpsee_core::server::{BoundedSubscriptions, Extensions, RpcModule, SinkMessage};
+use jsonrpsee_types::error::INVALID_PARAMS_CODE;
+use jsonrpsee_types::{ErrorObject, ErrorObjectOwned, Params, SubscriptionId};
+use serde_json::value::RawValue;
+use tokio::sync::mpsc::{self, error::TryRecvError};
+
+use crate::{CallerIdentity, StopHandle};
+
+/// Name of the method that returns the buffered notifications of a long-polled subscription.
+pub const LONG_POLLING_METHOD: &str = "rpc.poll";
+
+/// Configuration of long polling of subscriptions over HTTP,
+/// see [`Builder::enable_long_polling`](crate::ServerBuilder::enable_long_polling).
+///
+/// Default: buffer capacity: 1024 notifications, max subscriptions: 1024,
+/// poll timeout: 30 seconds and idle timeout: 60 seconds.
+#[derive(Debug, Copy, Clone)]
+pub struct LongPollingConfig {
+	/// Max number of buffered notifications per subscription.
+	pub(crate) buffer_capacity: u32,
+	/// Max number of long-polled subscriptions on the server.
+	pub(crate) max_subscriptions: u32,
+	/// Max time a poll waits for a notification.
+	pub(crate) poll_timeout: Duration,
+	/// Max time a subscription is kept without being polled.
+	pub(crate) idle_timeout: Duration,
+}
+
+impl Default for LongPollingConfig {
+	fn default() -> Self {
+		Self {
+			buffer_capacity: 1024,
+			max_subscriptions: 1024,
+			poll_timeout: Duration::from_secs(30),
+			idle_timeout: Duration::from_secs(60),
+		}
+	}
+}
+
+impl LongPollingConfig {
+	/// Create a new LongPollingConfig.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Configure how many notifications are buffered per subscription until backpressure kicks in.
+	pub fn buffer_capacity(mut self, capacity: u32) -> Self {
+		self.buffer_capacity = capacity;
+		self
+	}
+
+	/// Configure how many long-polled subscriptions the server keeps at once.
+	pub fn max_subscriptions(mut self, max: u32) -> Self {
+		self.max_subscriptions = max;
+		self
+	}
+
+	/// Configure how long a poll waits for a notification if none is buffered.
+	pub fn poll_timeout(mut self, timeout: Duration) -> Self {
+		self.poll_timeout = timeout;
+		self
+	}
+
+	/// Configure how long a subscription is kept without being polled.
+	/// When this limit is expired the subscription is closed.
+	///
+	/// The idle subscriptions are closed by a background task that runs once per idle timeout,
+	/// so a subscription may be kept for up to twice the idle timeout. A subscription is not closed
+	/// while it's polled, the idle timeout starts when the poll completes.
+	pub fn idle_timeout(mut self, timeout: Duration) -> Self {
+		self.idle_timeout = timeout;
+		self
+	}
+}
+
+/// The long-polled subscriptions of a server.
+///
+/// A subscription may only be polled and unsubscribed by the caller which created it, that is
+/// with the same [`CallerIdentity`] in the extensions of the request. If no identity is
+/// inserted by a middleware the subscription ID is the only credential.
+#[derive(Debug, Clone)]
+pub(crate) struct LongPolling {
+	config: LongPollingConfig,
+	subscriptions: Arc<Mutex<HashMap<SubscriptionId<'static>, BufferedSubscription>>>,
+	bounded_subscriptions: BoundedSubscriptions,
+	/// Whether the task that closes the idle subscriptions has been spawned.
+	sweeping: Arc<AtomicBool>,
+}
+
+#[derive(Debug)]
+struct BufferedSubscription {
+	notifications: Arc<tokio::sync::Mutex<mpsc::Receiver<SinkMessage>>>,
+	caller: Option<CallerIdentity>,
+	last_poll: Instant,
+}
+
+impl BufferedSubscription {
+	/// Whether the subscription hasn't been polled within the idle timeout.
+	///
+	/// A subscription is never idle while it's polled, which holds a reference to the notifications,
+	/// such that a poll timeout longer than the idle timeout doesn't close it.
+	fn is_idle(&self, idle_timeout: Duration) -> bool {
+		Arc::strong_count(&self.notifications) == 1 && self.last_poll.elapsed() >= idle_timeout
+	}
+}
+
+impl LongPolling {
+	pub(crate) fn new(config: LongPollingConfig) -> Self {
+		Self {
+			config,
+			subscriptions: Default::default(),
+			bounded_subscriptions: BoundedSubscriptions::new(config.max_subscriptions),
+			sweeping: Default::default(),
+		}
+	}
+
+	/// Max number of buffered notifications per subscription.
+	pub(crate) fn buffer_capacity(&self) -> usize {
+		self.config.buffer_capacity as usize
+	}
+
+	/// The limit of the long-polled subscriptions which is shared by all HTTP requests.
+	pub(crate) fn bounded_subscriptions(&self) -> BoundedSubscriptions {
+		self.bounded_subscriptions.clone()
+	}
+
+	/// Spawn the task that closes the idle subscriptions until the server is stopped,
+	/// if there is a current runtime and it hasn't been spawned yet.
+	pub(crate) fn start(&self, stop_handle: StopHandle) {
+		let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+			return;
+		};
+
+		if self.sweeping.swap(true, Ordering::AcqRel) {
+			return;
+		}
+
+		let this = self.clone();
+		let period = self.config.idle_timeout.max(Duration::from_millis(1));
+
+		runtime.spawn(async move {
+			let mut interval = tokio::time::interval(period);
+			let stopped = stop_handle.shutdown();
+			tokio::pin!(stopped);
+
+			loop {
+				tokio::select! {
+					_ = interval.tick() => this.remove_idle(),
+					_ = &mut stopped => break,
+				}
+			}
+		});
+	}
+
+	/// Keep the notifications of an accepted subscription until they are polled by `caller`.
+	pub(crate) fn insert(
+		&self,
+		sub_id: SubscriptionId<'static>,
+		caller: Option<CallerIdentity>,
+		notifications: mpsc::Receiver<SinkMessage>,
+	) {
+		self.subscriptions.lock().expect("poisoned lock").insert(
+			sub_id,
+			BufferedSubscription {
+				notifications: Arc::new(tokio::sync::Mutex::new(notifications)),
+				caller,
+				last_poll: Instant::now(),
+			},
+		);
+	}
+
+	/// Whether the caller of a request may unsubscribe the subscription in `params`.
+	///
+	/// Unknown subscriptions are left to the unsubscribe method.
+	pub(crate) fn may_unsubscribe(&self, params: &Params, extensions: &Extensions) -> bool {
+		let Ok(sub_id) = params.one::<SubscriptionId>() else {
+			return true;
+		};
+
+		match self.subscriptions.lock().expect("poisoned lock").get(&sub_id) {
+			Some(sub) => sub.caller.as_ref() == extensions.get::<CallerIdentity>(),
+			None => true,
+		}
+	}
+
+	/// The module with the method that polls the subscriptions.
+	pub(crate) fn into_rpc(self) -> RpcModule<Self> {
+		let mut module = RpcModule::new(self);
+		module
+			.register_async_method(LONG_POLLING_METHOD, |params, this, ext| async move {
+				this.poll(params, ext.get::<CallerIdentity>()).await
+			})
+			.expect("RpcModule is empty; qed");
+		module
+	}
+
+	/// Wait until the subscription has notifications, or the poll timeout expired, and return them.
+	///
+	/// Once a closed subscription has been drained it's removed.
+	async fn poll(
+		&self,
+		params: Params<'static>,
+		caller: Option<&CallerIdentity>,
+	) -> Result<Vec<Box<RawValue>>, ErrorObjectOwned> {
+		let sub_id = params.one::<SubscriptionId>()?.into_owned();
+
+		let notifications = {
+			let mut subscriptions = self.subscriptions.lock().expect("poisoned lock");
+
+			// The subscriptions of other callers are reported as unknown to not reveal their existence.
+			let Some(sub) = subscriptions
+				.get_mut(&sub_id)
+				.filter(|sub| sub.caller.as_ref() == caller && !sub.is_idle(self.config.idle_timeout))
+			else {
+				return Err(ErrorObject::owned(INVALID_PARAMS_CODE, "Unknown subscription", Some(sub_id)));
+			};
+			sub.last_poll = Instant::now();
+			sub.notifications.clone()
+		};
+
+		let mut notifications = notifications.lock().await;
+		let mut batch = Vec::new();
+		let mut closed = false;
+
+		match tokio::time::timeout(self.config.poll_timeout, notifications.recv()).await {
+			Ok(Some(notif)) => {
+				batch.push(notif);
+				loop {
+					match notifications.try_recv() {
+						Ok(notif) => batch.push(notif),
+						Err(TryRecvError::Empty) => break,
+						Err(TryRecvError::Disconnected) => {
+							closed = true;
+							break;
+						}
+					}
+				}
+			}
+			Ok(None) => closed = true,
+			Err(_) => (),
+		}
+
+		{
+			let mut subscriptions = self.subscriptions.lock().expect("poisoned lock");
+
+			if closed {
+				subscriptions.remove(&sub_id);
+			} else if let Some(sub) = subscriptions.get_mut(&sub_id) {
+				sub.last_poll = Instant::now();
+			}
+		}
+
+		Ok(batch.into_iter().filter_map(|notif| RawValue::from_string(notif.into_string()).ok()).collect())
+	}
+
+	/// Close the subscriptions that haven't been polled within the idle timeout.
+	fn remove_idle(&self) {
+		let mut subscriptions = self.subscriptions.lock().expect("poisoned lock");
+		subscriptions.retain(|_, sub| !sub.is_idle(self.config.idle_timeout));
+	}
+}
@@ -0,0 +1,155 @@
+// Copyright 2019-2024 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any
+// person obtaining a copy of this software and associated
+// documentation files (the "Software"), to deal in the
+// Software without restriction, including without
+// limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software
+// is furnished to do so, subject to the following
+// conditions:
+//
+// The above copyright notice and this permission notice
+// shall be included in all copies or substantial portions
+// of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF
+// ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED
+// TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A
+// PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT
+// SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY
+// CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+// IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Middleware that wraps the accepted connections.
+//!
+//! In contrast to the HTTP middleware which sees each HTTP request and the RPC middleware which
+//! sees each call, the connection middleware wraps an accepted connection from the moment it is
+//! established until it is closed. This includes the entire session of a WebSocket connection.
+//!
+//! The middleware is a [`tower::Layer`] around [`ServeConnection`](crate::middleware::connection::ServeConnection)
+//! which is configured with [`crate::ServerBuilder::set_connection_middleware`]. It may replace the I/O of the
+//! [`Connection`](crate::middleware::connection::Connection), for instance to account for the bytes that are read and written by the peer.
+
+use std::fmt;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use futures_util::future::BoxFuture;
+use jsonrpsee_core::server::ConnectionId;
+use jsonrpsee_core::BoxError;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::sync::mpsc;
+use tower::util::BoxService;
+use tower::{Layer, Service};
+
+/// I/O of a [`Connection`].
+pub trait ConnectionIo: AsyncRead + AsyncWrite + Send + Unpin + 'static {}
+
+impl<T: AsyncRead + AsyncWrite + Send + Unpin + 'static> ConnectionIo for T {}
+
+/// An accepted connection which is passed through the connection middleware.
+pub struct Connection {
+	/// The connection ID.
+	pub conn_id: ConnectionId,
+	/// The address of the peer.
+	pub remote_addr: SocketAddr,
+	/// The I/O of the connection.
+	pub io: Box<dyn ConnectionIo>,
+}
+
+impl fmt::Debug for Connection {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Connection").field("conn_id", &self.conn_id).field("remote_addr", &self.remote_addr).finish()
+	}
+}
+
+type Serve = Box<dyn FnOnce(Box<dyn ConnectionIo>) -> BoxFuture<'static, ()> + Send>;
+
+/// The innermost service of the connection middleware which serves a [`Connection`].
+///
+/// The future resolves once the connection is closed, which for a WebSocket connection
+/// is when the session is terminated.
+pub struct ServeConnection(Option<Serve>);
+
+impl ServeConnection {
+	pub(crate) fn new(serve: Serve) -> Self {
+		Self(Some(serve))
+	}
+}
+
+impl fmt::Debug for ServeConnection {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str("ServeConnection")
+	}
+}
+
+impl Service<Connection> for ServeConnection {
+	type Response = ();
+	type Error = BoxError;
+	type Future = BoxFuture<'static, Result<(), BoxError>>;
+
+	fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+		Poll::Ready(Ok(()))
+	}
+
+	fn call(&mut self, conn: Connection) -> Self::Future {
+		match self.0.take() {
+			Some(serve) => {
+				let fut = serve(conn.io);
+				Box::pin(async move {
+					fut.await;
+					Ok(())
+				})
+			}
+			None => Box::pin(async { Err("The connection is already served".into()) }),
+		}
+	}
+}
+
+/// Keeps the [`Connection`] open while a session that was upgraded from it is alive.
+///
+/// It's inserted into the extensions of each HTTP request on the connection.
+#[derive(Debug, Clone)]
+pub(crate) struct SessionGuard {
+	_tx: mpsc::Sender<()>,
+}
+
+impl SessionGuard {
+	/// Create a new guard and a receiver which is closed once all guards have been dropped.
+	pub(crate) fn new() -> (Self, mpsc::Receiver<()>) {
+		let (tx, rx) = mpsc::channel(1);
+		(Self { _tx: tx }, rx)
+	}
+}
+
+/// The type-erased connection middleware, see [`crate::ServerBuilder::set_connection_middleware`].
+#[derive(Clone)]
+pub(crate) struct ConnectionMiddleware(
+	Arc<dyn Fn(ServeConnection) -> BoxService<Connection, (), BoxError> + Send + Sync>,
+);
+
+impl ConnectionMiddleware {
+	pub(crate) fn new<L>(layer: L) -> Self
+	where
+		L: Layer<ServeConnection> + Send + Sync + 'static,
+		L::Service: Service<Connection, Response = (), Error = BoxError> + Send + 'static,
+		<L::Service as Service<Connection>>::Future: Send + 'static,
+	{
+		Self(Arc::new(move |serve| BoxService::new(layer.layer(serve))))
+	}
+
+	pub(crate) fn service(&self, serve: ServeConnection) -> BoxService<Connection, (), BoxError> {
+		(self.0)(serve)
+	}
+}
+
+impl fmt::Debug for ConnectionMiddleware {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str("ConnectionMiddleware")
+	}
+}
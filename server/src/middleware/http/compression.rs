@@ -0,0 +1,193 @@
+// Copyright 2019-2023 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any
+// person obtaining a copy of this software and associated
+// documentation files (the "Software"), to deal in the
+// Software without restriction, including without
+// limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software
+// is furnished to do so, subject to the following
+// conditions:
+//
+// The above copyright notice and this permission notice
+// shall be included in all copies or substantial portions
+// of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF
+// ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED
+// TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A
+// PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT
+// SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY
+// CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+// IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! HTTP response compression middleware.
+
+use crate::{HttpBody, HttpRequest, HttpResponse};
+use futures_util::{Future, TryFutureExt};
+use hyper::body::Bytes;
+use jsonrpsee_core::BoxError;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tower::{Layer, Service};
+use tower_http::compression::predicate::{Predicate, SizeAbove};
+
+/// Responses smaller than this are not compressed by default.
+const DEFAULT_MIN_SIZE: u16 = 1024;
+
+/// Middleware to compress the HTTP responses with gzip or brotli if the client
+/// accepts it by the `Accept-Encoding` header.
+///
+/// The responses are compressed after the JSON-RPC calls have been answered, thus the max response
+/// size of the server applies to the uncompressed responses.
+///
+/// Requires the `compression` feature.
+///
+/// # Examples
+///
+/// ```rust
+/// use jsonrpsee_server::{middleware::http::CompressionLayer, ServerBuilder};
+///
+/// let http_middleware = tower::ServiceBuilder::new().layer(CompressionLayer::new().min_size(4096));
+/// let builder = ServerBuilder::default().set_http_middleware(http_middleware);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct CompressionLayer {
+	gzip: bool,
+	br: bool,
+	min_size: u16,
+}
+
+impl CompressionLayer {
+	/// Compress the responses of at least 1024 bytes with gzip or brotli.
+	pub fn new() -> Self {
+		Self { gzip: true, br: true, min_size: DEFAULT_MIN_SIZE }
+	}
+
+	/// Configure whether gzip is used.
+	pub fn gzip(mut self, enable: bool) -> Self {
+		self.gzip = enable;
+		self
+	}
+
+	/// Configure whether brotli is used.
+	pub fn br(mut self, enable: bool) -> Self {
+		self.br = enable;
+		self
+	}
+
+	/// Configure the minimum size in bytes of the responses that are compressed.
+	///
+	/// Responses of which the size is not known upfront such as streamed
+	/// batch responses are always compressed.
+	pub fn min_size(mut self, min_size: u16) -> Self {
+		self.min_size = min_size;
+		self
+	}
+}
+
+impl Default for CompressionLayer {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<S> Layer<S> for CompressionLayer {
+	type Service = Compression<S>;
+
+	fn layer(&self, inner: S) -> Self::Service {
+		let layer = tower_http::compression::CompressionLayer::new()
+			.gzip(self.gzip)
+			.br(self.br)
+			.compress_when(ShouldCompress(SizeAbove::new(self.min_size)));
+
+		Compression(layer.layer(inner))
+	}
+}
+
+/// Middleware to compress the HTTP responses.
+#[derive(Clone)]
+pub struct Compression<S>(tower_http::compression::Compression<S, ShouldCompress>);
+
+impl<S> std::fmt::Debug for Compression<S> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("Compression").finish_non_exhaustive()
+	}
+}
+
+impl<S, B> Service<HttpRequest<B>> for Compression<S>
+where
+	S: Service<HttpRequest<B>, Response = HttpResponse>,
+	S::Error: Into<BoxError> + 'static,
+	S::Future: Send + 'static,
+	B: http_body::Body<Data = Bytes> + Send + 'static,
+{
+	type Response = HttpResponse;
+	type Error = BoxError;
+	type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send + 'static>>;
+
+	fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+		self.0.poll_ready(cx).map_err(Into::into)
+	}
+
+	fn call(&mut self, request: HttpRequest<B>) -> Self::Future {
+		Box::pin(self.0.call(request).map_ok(|rp| rp.map(HttpBody::new)).map_err(Into::into))
+	}
+}
+
+/// Compress the responses that are big enough except for WebSocket upgrades.
+#[derive(Debug, Clone, Copy)]
+pub struct ShouldCompress(SizeAbove);
+
+impl Predicate for ShouldCompress {
+	fn should_compress<B>(&self, response: &http::Response<B>) -> bool
+	where
+		B: http_body::Body,
+	{
+		response.status() != http::StatusCode::SWITCHING_PROTOCOLS && self.0.should_compress(response)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::CompressionLayer;
+	use crate::{HttpBody, HttpRequest, HttpResponse};
+	use http_body_util::BodyExt;
+	use jsonrpsee_core::BoxError;
+	use tower::{Layer, Service, ServiceExt};
+
+	async fn call(layer: CompressionLayer, accept_encoding: &str, body: &'static str) -> HttpResponse {
+		let mut service = layer.layer(tower::service_fn(move |_: HttpRequest| async move {
+			Ok::<_, BoxError>(HttpResponse::new(body.into()))
+		}));
+		let request =
+			HttpRequest::builder().header("accept-encoding", accept_encoding).body(HttpBody::empty()).unwrap();
+
+		service.ready().await.unwrap().call(request).await.unwrap()
+	}
+
+	#[tokio::test]
+	async fn compresses_big_responses() {
+		let body = "a".repeat(2048).leak();
+
+		let rp = call(CompressionLayer::new(), "gzip", body).await;
+		assert_eq!(rp.headers().get("content-encoding").unwrap(), "gzip");
+		assert!(rp.into_body().collect().await.unwrap().to_bytes().len() < body.len());
+
+		let rp = call(CompressionLayer::new(), "br", body).await;
+		assert_eq!(rp.headers().get("content-encoding").unwrap(), "br");
+
+		let rp = call(CompressionLayer::new().br(false), "br", body).await;
+		assert!(rp.headers().get("content-encoding").is_none());
+	}
+
+	#[tokio::test]
+	async fn does_not_compress_small_responses() {
+		let rp = call(CompressionLayer::new().min_size(16), "gzip", "a").await;
+		assert!(rp.headers().get("content-encoding").is_none());
+		assert_eq!(rp.into_body().collect().await.unwrap().to_bytes(), "a");
+	}
+}
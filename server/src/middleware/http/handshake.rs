@@ -0,0 +1,184 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any
+// person obtaining a copy of this software and associated
+// documentation files (the "Software"), to deal in the
+// Software without restriction, including without
+// limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software
+// is furnished to do so, subject to the following
+// conditions:
+//
+// The above copyright notice and this permission notice
+// shall be included in all copies or substantial portions
+// of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF
+// ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED
+// TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A
+// PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT
+// SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY
+// CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+// IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Middleware that accepts or rejects connections by their handshake before any JSON-RPC processing.
+
+use crate::transport::http;
+use crate::transport::ws::is_upgrade_request;
+use crate::{HttpRequest, HttpResponse, PeerInfo, TransportKind, LOG_TARGET};
+use futures_util::{Future, FutureExt, TryFutureExt};
+use hyper::{HeaderMap, Method, StatusCode, Uri};
+use jsonrpsee_core::BoxError;
+use std::fmt;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tower::{Layer, Service};
+
+/// The handshake of a connection, i.e. a WebSocket upgrade request or an HTTP request.
+#[derive(Debug)]
+pub struct HandshakeRequest<'a> {
+	/// The address of the peer, if known.
+	pub remote_addr: Option<SocketAddr>,
+	/// The transport the peer is connecting with.
+	pub transport: TransportKind,
+	/// The HTTP method.
+	pub method: &'a Method,
+	/// The requested URI.
+	pub uri: &'a Uri,
+	/// The HTTP headers.
+	pub headers: &'a HeaderMap,
+}
+
+/// Rejection of a handshake which is answered with an HTTP status and a JSON body,
+/// such that clients are able to tell the reason apart.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HandshakeRejection {
+	status: StatusCode,
+	body: serde_json::Value,
+}
+
+impl HandshakeRejection {
+	/// Reject with `status` and a body of the form `{"reason":"<reason>"}`.
+	pub fn new(status: StatusCode, reason: impl Into<String>) -> Self {
+		Self { status, body: serde_json::json!({ "reason": reason.into() }) }
+	}
+
+	/// Reject with `status` and a custom JSON body.
+	pub fn with_body(status: StatusCode, body: serde_json::Value) -> Self {
+		Self { status, body }
+	}
+
+	/// The HTTP status of the rejection.
+	pub fn status(&self) -> StatusCode {
+		self.status
+	}
+
+	/// The JSON body of the rejection.
+	pub fn body(&self) -> &serde_json::Value {
+		&self.body
+	}
+}
+
+impl From<HandshakeRejection> for HttpResponse {
+	fn from(rejection: HandshakeRejection) -> Self {
+		http::response::rejected(rejection.status, &rejection.body)
+	}
+}
+
+type Filter = Arc<dyn Fn(&HandshakeRequest<'_>) -> Result<(), HandshakeRejection> + Send + Sync>;
+
+/// Layer that applies [`HandshakeFilter`] which rejects connections by their handshake,
+/// for instance by the headers or the address of the peer.
+///
+/// The filter runs before the request is parsed and, for a WebSocket connection, before the upgrade.
+///
+/// # Examples
+///
+/// ```rust
+/// use jsonrpsee_server::middleware::http::{HandshakeFilterLayer, HandshakeRejection};
+/// use jsonrpsee_server::ServerBuilder;
+/// use hyper::StatusCode;
+///
+/// let layer = HandshakeFilterLayer::new(|req| match req.headers.get("x-api-key") {
+///     Some(_) => Ok(()),
+///     None => Err(HandshakeRejection::new(StatusCode::UNAUTHORIZED, "missing_api_key")),
+/// });
+/// let builder = ServerBuilder::default().set_http_middleware(tower::ServiceBuilder::new().layer(layer));
+/// ```
+#[derive(Clone)]
+pub struct HandshakeFilterLayer(Filter);
+
+impl HandshakeFilterLayer {
+	/// Create a new layer which accepts the handshakes for which `filter` returns `Ok(())`.
+	pub fn new<F>(filter: F) -> Self
+	where
+		F: Fn(&HandshakeRequest<'_>) -> Result<(), HandshakeRejection> + Send + Sync + 'static,
+	{
+		Self(Arc::new(filter))
+	}
+}
+
+impl fmt::Debug for HandshakeFilterLayer {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str("HandshakeFilterLayer")
+	}
+}
+
+impl<S> Layer<S> for HandshakeFilterLayer {
+	type Service = HandshakeFilter<S>;
+
+	fn layer(&self, inner: S) -> Self::Service {
+		HandshakeFilter { inner, filter: self.0.clone() }
+	}
+}
+
+/// Middleware that rejects connections by their handshake.
+#[derive(Clone)]
+pub struct HandshakeFilter<S> {
+	inner: S,
+	filter: Filter,
+}
+
+impl<S: fmt::Debug> fmt::Debug for HandshakeFilter<S> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("HandshakeFilter").field("inner", &self.inner).finish()
+	}
+}
+
+impl<S, B> Service<HttpRequest<B>> for HandshakeFilter<S>
+where
+	S: Service<HttpRequest<B>, Response = HttpResponse>,
+	S::Error: Into<BoxError> + 'static,
+	S::Future: Send + 'static,
+{
+	type Response = S::Response;
+	type Error = BoxError;
+	type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send + 'static>>;
+
+	fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+		self.inner.poll_ready(cx).map_err(Into::into)
+	}
+
+	fn call(&mut self, request: HttpRequest<B>) -> Self::Future {
+		let handshake = HandshakeRequest {
+			remote_addr: request.extensions().get::<PeerInfo>().and_then(|peer| peer.remote_addr),
+			transport: if is_upgrade_request(&request) { TransportKind::WebSocket } else { TransportKind::Http },
+			method: request.method(),
+			uri: request.uri(),
+			headers: request.headers(),
+		};
+
+		match (self.filter)(&handshake) {
+			Ok(()) => Box::pin(self.inner.call(request).map_err(Into::into)),
+			Err(rejection) => {
+				tracing::debug!(target: LOG_TARGET, "Rejected handshake: {:?}", rejection);
+				async move { Ok(rejection.into()) }.boxed()
+			}
+		}
+	}
+}
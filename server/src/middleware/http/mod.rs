@@ -28,9 +28,18 @@
 
 /// Utility and types related to the authority of an URI.
 mod authority;
+/// HTTP response compression middleware.
+#[cfg(feature = "compression")]
+mod compression;
+/// Handshake filtering middleware.
+mod handshake;
 /// HTTP Host filtering middleware.
 mod host_filter;
 /// Proxy `GET /path` to internal RPC methods.
 mod proxy_get_request;
 
-pub use {authority::*, host_filter::*, proxy_get_request::*};
+pub use {authority::*, handshake::*, host_filter::*, proxy_get_request::*};
+
+#[cfg(feature = "compression")]
+#[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+pub use compression::*;
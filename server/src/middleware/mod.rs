@@ -26,6 +26,8 @@
 
 //! jsonrpsee-server middleware
 
+/// Connection related middleware.
+pub mod connection;
 /// HTTP related middleware.
 pub mod http;
 /// JSON-RPC specific middleware.
@@ -0,0 +1,262 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any
+// person obtaining a copy of this software and associated
+// documentation files (the "Software"), to deal in the
+// Software without restriction, including without
+// limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software
+// is furnished to do so, subject to the following
+// conditions:
+//
+// The above copyright notice and this permission notice
+// shall be included in all copies or substantial portions
+// of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF
+// ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED
+// TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A
+// PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT
+// SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY
+// CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+// IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! RPC layer that limits the number of concurrent calls and admits the waiting calls by priority.
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+use futures_util::Future;
+use jsonrpsee_core::server::{MethodResponse, Methods, ResponsePriority};
+use jsonrpsee_types::{ErrorCode, Request};
+use pin_project::pin_project;
+use tokio::sync::oneshot;
+
+use crate::middleware::rpc::RpcServiceT;
+
+/// RPC layer that limits the number of calls which are executed at the same time and,
+/// once the limit is reached, admits the waiting calls in the order of their [`ResponsePriority`].
+///
+/// The limit is shared by all connections of the server, such that for instance health checks
+/// and unsubscribes are still executed quickly when the server is busy with bulk queries.
+/// Calls of the same priority are admitted in the order they arrived in.
+///
+/// # Examples
+///
+/// ```rust
+/// use jsonrpsee_server::{ResponsePriority, RpcModule};
+/// use jsonrpsee_server::middleware::rpc::{PriorityAdmissionLayer, RpcServiceBuilder};
+///
+/// let mut module = RpcModule::new(());
+/// module.register_method("health", |_, _, _| "ok").unwrap();
+/// module.set_response_priority("health", ResponsePriority::High).unwrap();
+///
+/// let rpc_middleware = RpcServiceBuilder::new().layer(PriorityAdmissionLayer::new(&module, 64));
+/// ```
+#[derive(Debug, Clone)]
+pub struct PriorityAdmissionLayer {
+	methods: Methods,
+	scheduler: Arc<Scheduler>,
+}
+
+impl PriorityAdmissionLayer {
+	/// Create a new layer that executes at most `max_concurrent_calls` calls to `methods` at the same time.
+	///
+	/// # Panics
+	///
+	/// Panics if `max_concurrent_calls` is zero.
+	pub fn new(methods: &Methods, max_concurrent_calls: usize) -> Self {
+		assert!(max_concurrent_calls > 0, "max_concurrent_calls must be greater than zero");
+
+		let scheduler = Scheduler { max_concurrent_calls, state: Default::default() };
+		Self { methods: methods.clone(), scheduler: Arc::new(scheduler) }
+	}
+}
+
+impl<S> tower::Layer<S> for PriorityAdmissionLayer {
+	type Service = PriorityAdmission<S>;
+
+	fn layer(&self, service: S) -> Self::Service {
+		PriorityAdmission {
+			service: Arc::new(service),
+			methods: self.methods.clone(),
+			scheduler: self.scheduler.clone(),
+		}
+	}
+}
+
+/// A middleware that admits calls by priority, see [`PriorityAdmissionLayer`].
+#[derive(Debug)]
+pub struct PriorityAdmission<S> {
+	service: Arc<S>,
+	methods: Methods,
+	scheduler: Arc<Scheduler>,
+}
+
+impl<'a, S> RpcServiceT<'a> for PriorityAdmission<S>
+where
+	S: RpcServiceT<'a> + Send + Sync,
+{
+	type Future = ResponseFuture<'a, S>;
+
+	fn call(&self, request: Request<'a>) -> Self::Future {
+		let priority = self.methods.response_priority(request.method_name());
+
+		let state = match self.scheduler.acquire(priority) {
+			Ok(permit) => State::Call { fut: self.service.call(request), _permit: permit },
+			Err(rx) => State::Wait { rx, request: Some(request), service: self.service.clone() },
+		};
+
+		ResponseFuture { state }
+	}
+}
+
+/// Keeps track of the calls that are executed and of the calls that wait to be admitted.
+#[derive(Debug)]
+struct Scheduler {
+	max_concurrent_calls: usize,
+	state: Mutex<SchedulerState>,
+}
+
+#[derive(Debug, Default)]
+struct SchedulerState {
+	running: usize,
+	waiting: BinaryHeap<WaitingCall>,
+	/// Number of calls that have waited which orders the calls of the same priority.
+	count: u64,
+}
+
+#[derive(Debug)]
+struct WaitingCall {
+	priority: ResponsePriority,
+	seq: Reverse<u64>,
+	admit: oneshot::Sender<Permit>,
+}
+
+impl PartialEq for WaitingCall {
+	fn eq(&self, other: &Self) -> bool {
+		self.cmp(other).is_eq()
+	}
+}
+
+impl Eq for WaitingCall {}
+
+impl PartialOrd for WaitingCall {
+	fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl Ord for WaitingCall {
+	fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+		(self.priority, self.seq).cmp(&(other.priority, other.seq))
+	}
+}
+
+impl Scheduler {
+	/// Admit a call right away if fewer than the max number of calls are executed,
+	/// or else return a receiver which gets a permit once the call is admitted.
+	fn acquire(self: &Arc<Self>, priority: ResponsePriority) -> Result<Permit, oneshot::Receiver<Permit>> {
+		let mut state = self.state.lock().expect("poisoned lock");
+
+		if state.running < self.max_concurrent_calls {
+			state.running += 1;
+			return Ok(Permit { scheduler: Some(self.clone()) });
+		}
+
+		let (tx, rx) = oneshot::channel();
+		let seq = Reverse(state.count);
+		state.count += 1;
+		state.waiting.push(WaitingCall { priority, seq, admit: tx });
+		Err(rx)
+	}
+
+	/// Hand over the permit of a completed call to the waiting call with the highest priority.
+	fn release(self: &Arc<Self>) {
+		let mut state = self.state.lock().expect("poisoned lock");
+
+		while let Some(call) = state.waiting.pop() {
+			match call.admit.send(Permit { scheduler: Some(self.clone()) }) {
+				Ok(()) => return,
+				// The waiting call was dropped.
+				Err(mut permit) => permit.scheduler = None,
+			}
+		}
+
+		state.running -= 1;
+	}
+}
+
+/// Admission of a call which is handed over to a waiting call once dropped.
+#[derive(Debug)]
+struct Permit {
+	scheduler: Option<Arc<Scheduler>>,
+}
+
+impl Drop for Permit {
+	fn drop(&mut self) {
+		if let Some(scheduler) = self.scheduler.take() {
+			scheduler.release();
+		}
+	}
+}
+
+/// Response future of a call that is admitted by priority.
+#[pin_project]
+pub struct ResponseFuture<'a, S: RpcServiceT<'a>> {
+	#[pin]
+	state: State<'a, S>,
+}
+
+#[pin_project(project = StateProj)]
+enum State<'a, S: RpcServiceT<'a>> {
+	Call {
+		#[pin]
+		fut: S::Future,
+		_permit: Permit,
+	},
+	Wait {
+		#[pin]
+		rx: oneshot::Receiver<Permit>,
+		request: Option<Request<'a>>,
+		service: Arc<S>,
+	},
+}
+
+impl<'a, S: RpcServiceT<'a>> std::fmt::Debug for ResponseFuture<'a, S> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str("ResponseFuture")
+	}
+}
+
+impl<'a, S: RpcServiceT<'a>> Future for ResponseFuture<'a, S> {
+	type Output = MethodResponse;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let mut state = self.project().state;
+
+		loop {
+			match state.as_mut().project() {
+				StateProj::Call { fut, .. } => return fut.poll(cx),
+				StateProj::Wait { rx, request, service } => {
+					let permit = futures_util::ready!(rx.poll(cx));
+					let request = request.take().expect("polled after completion");
+
+					// The scheduler only drops the sender without admitting the call once it's dropped itself.
+					let Ok(permit) = permit else {
+						return Poll::Ready(MethodResponse::error(request.id, ErrorCode::InternalError));
+					};
+					let fut = service.call(request);
+
+					state.set(State::Call { fut, _permit: permit });
+				}
+			}
+		}
+	}
+}
@@ -0,0 +1,418 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any
+// person obtaining a copy of this software and associated
+// documentation files (the "Software"), to deal in the
+// Software without restriction, including without
+// limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software
+// is furnished to do so, subject to the following
+// conditions:
+//
+// The above copyright notice and this permission notice
+// shall be included in all copies or substantial portions
+// of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF
+// ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED
+// TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A
+// PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT
+// SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY
+// CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+// IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! RPC response cache layer.
+
+use std::collections::{HashMap, VecDeque};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use futures_util::Future;
+use jsonrpsee_core::server::{MethodResponse, StreamedResponseSupport};
+use jsonrpsee_core::JsonRawValue;
+use jsonrpsee_types::{ErrorObjectOwned, Request, Response};
+use pin_project::pin_project;
+
+use crate::middleware::rpc::RpcServiceT;
+use crate::CallerIdentity;
+
+/// The key of a cached response.
+///
+/// Responses are only shared between calls of the same caller.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct CacheKey {
+	method: String,
+	caller: Option<CallerIdentity>,
+	params: String,
+}
+
+impl CacheKey {
+	pub(crate) fn new(request: &Request) -> Self {
+		Self {
+			method: request.method_name().to_owned(),
+			caller: request.extensions().get::<CallerIdentity>().cloned(),
+			params: canonical_params(request.params.as_ref().map(|p| p.get())),
+		}
+	}
+}
+
+/// The cached result of a method call, or the result of an identical call shared by [`super::dedup::RequestDedup`].
+///
+/// It's passed on to the [`crate::middleware::rpc::RpcService`] which answers the call with it instead of
+/// invoking the method handler, such that the response size limit of the connection is applied.
+#[derive(Debug, Clone)]
+pub(crate) struct CachedResult(pub(crate) Result<Box<JsonRawValue>, ErrorObjectOwned>);
+
+#[derive(Debug)]
+struct Entry {
+	result: Box<JsonRawValue>,
+	expires_at: Instant,
+}
+
+#[derive(Debug)]
+struct Inner {
+	entries: HashMap<CacheKey, Entry>,
+	/// Insertion order of the entries, used to evict the oldest entries when the cache is full.
+	order: VecDeque<CacheKey>,
+	/// The total size of the cached results in bytes.
+	size: usize,
+	max_size: usize,
+}
+
+impl Inner {
+	fn get(&mut self, key: &CacheKey) -> Option<Box<JsonRawValue>> {
+		let entry = self.entries.get(key)?;
+
+		if entry.expires_at > Instant::now() {
+			Some(entry.result.clone())
+		} else {
+			self.remove(key);
+			None
+		}
+	}
+
+	fn insert(&mut self, key: CacheKey, result: Box<JsonRawValue>, ttl: Duration) {
+		let len = result.get().len();
+		if len > self.max_size {
+			return;
+		}
+
+		self.remove(&key);
+		self.size += len;
+		self.entries.insert(key.clone(), Entry { result, expires_at: Instant::now() + ttl });
+		self.order.push_back(key);
+
+		while self.size > self.max_size {
+			let Some(oldest) = self.order.pop_front() else { break };
+			if let Some(entry) = self.entries.remove(&oldest) {
+				self.size -= entry.result.get().len();
+			}
+		}
+	}
+
+	fn remove(&mut self, key: &CacheKey) {
+		if let Some(entry) = self.entries.remove(key) {
+			self.size -= entry.result.get().len();
+			self.order.retain(|k| k != key);
+		}
+	}
+
+	fn retain(&mut self, mut f: impl FnMut(&CacheKey) -> bool) {
+		let mut removed = 0;
+		self.entries.retain(|k, entry| {
+			let keep = f(k);
+			if !keep {
+				removed += entry.result.get().len();
+			}
+			keep
+		});
+		self.size -= removed;
+
+		let entries = &self.entries;
+		self.order.retain(|k| entries.contains_key(k));
+	}
+}
+
+/// Handle to the responses cached by a [`ResponseCacheLayer`].
+///
+/// It may be used to invalidate cached responses when the underlying data changes.
+#[derive(Debug, Clone)]
+pub struct ResponseCache(Arc<Mutex<Inner>>);
+
+impl ResponseCache {
+	/// Remove all cached responses of a method.
+	pub fn invalidate(&self, method: &str) {
+		self.0.lock().expect("poisoned lock").retain(|k| k.method != method);
+	}
+
+	/// Remove the cached responses of a method called with the given parameters, for all callers.
+	///
+	/// `params` is the JSON encoding of the parameters or `None` if the call had no parameters.
+	pub fn invalidate_call(&self, method: &str, params: Option<&str>) {
+		let params = canonical_params(params);
+		self.0.lock().expect("poisoned lock").retain(|k| k.method != method || k.params != params);
+	}
+
+	/// Remove all cached responses.
+	pub fn clear(&self) {
+		let mut inner = self.0.lock().expect("poisoned lock");
+		inner.entries.clear();
+		inner.order.clear();
+		inner.size = 0;
+	}
+
+	/// Returns the number of cached responses, including the ones that have expired but not been evicted yet.
+	pub fn len(&self) -> usize {
+		self.0.lock().expect("poisoned lock").entries.len()
+	}
+
+	/// Returns whether the cache is empty.
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// Returns the total size of the cached results in bytes.
+	pub fn size(&self) -> usize {
+		self.0.lock().expect("poisoned lock").size
+	}
+}
+
+/// RPC layer that caches the successful responses of selected methods.
+///
+/// Responses are keyed by the method name, the [`CallerIdentity`] in the extensions of the request
+/// and the canonicalized parameters, such that whitespace and the order of the keys in by-name
+/// parameters don't matter. A cached response is served without invoking the method handler until
+/// its TTL expires, the response size limit of the connection still applies to it.
+///
+/// Only method calls are cached, subscriptions and error responses are never cached.
+///
+/// The layer must be placed after the middleware which inserts the [`CallerIdentity`], if any,
+/// such that it's inside of it. Otherwise the cache doesn't see the identity and the responses
+/// to one caller are served to the other callers.
+/// Cached results are passed on to the inner services, such that the other middleware observe
+/// the calls which are served from the cache.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::time::Duration;
+/// use jsonrpsee_server::middleware::rpc::{ResponseCacheLayer, RpcServiceBuilder};
+///
+/// let cache = ResponseCacheLayer::new(1024 * 1024).cache_method("chain_genesis", Duration::from_secs(3600));
+/// let handle = cache.cache();
+/// let rpc_middleware = RpcServiceBuilder::new().layer(cache);
+///
+/// // Later, once the data has changed.
+/// handle.invalidate("chain_genesis");
+/// ```
+#[derive(Debug, Clone)]
+pub struct ResponseCacheLayer {
+	cache: ResponseCache,
+	ttls: Arc<HashMap<String, Duration>>,
+}
+
+impl ResponseCacheLayer {
+	/// Create a new cache layer which keeps at most `max_size` bytes of results.
+	///
+	/// When the cache is full the oldest responses are evicted, results that are larger than
+	/// `max_size` are never cached.
+	pub fn new(max_size: usize) -> Self {
+		let inner = Inner { entries: HashMap::new(), order: VecDeque::new(), size: 0, max_size };
+		Self { cache: ResponseCache(Arc::new(Mutex::new(inner))), ttls: Arc::new(HashMap::new()) }
+	}
+
+	/// Cache the responses of `method` for `ttl`.
+	pub fn cache_method(mut self, method: impl Into<String>, ttl: Duration) -> Self {
+		Arc::make_mut(&mut self.ttls).insert(method.into(), ttl);
+		self
+	}
+
+	/// Returns a handle to the cached responses.
+	pub fn cache(&self) -> ResponseCache {
+		self.cache.clone()
+	}
+}
+
+impl<S> tower::Layer<S> for ResponseCacheLayer {
+	type Service = ResponseCacheService<S>;
+
+	fn layer(&self, service: S) -> Self::Service {
+		ResponseCacheService { service, cache: self.cache.clone(), ttls: self.ttls.clone() }
+	}
+}
+
+/// A middleware that serves cached responses, see [`ResponseCacheLayer`].
+#[derive(Debug)]
+pub struct ResponseCacheService<S> {
+	service: S,
+	cache: ResponseCache,
+	ttls: Arc<HashMap<String, Duration>>,
+}
+
+impl<'a, S> RpcServiceT<'a> for ResponseCacheService<S>
+where
+	S: RpcServiceT<'a>,
+{
+	type Future = ResponseFuture<S::Future>;
+
+	fn call(&self, mut request: Request<'a>) -> Self::Future {
+		let Some(ttl) = self.ttls.get(request.method_name()).copied() else {
+			return ResponseFuture { fut: self.service.call(request), insert: None };
+		};
+
+		// A streamed response can't be cached.
+		request.extensions_mut().remove::<StreamedResponseSupport>();
+
+		let key = CacheKey::new(&request);
+
+		let cached = self.cache.0.lock().expect("poisoned lock").get(&key);
+		let insert = match cached {
+			Some(result) => {
+				request.extensions_mut().insert(CachedResult(Ok(result)));
+				None
+			}
+			None => Some((self.cache.clone(), key, ttl)),
+		};
+
+		ResponseFuture { fut: self.service.call(request), insert }
+	}
+}
+
+/// Response future that caches the response of a method call.
+#[pin_project]
+pub struct ResponseFuture<F> {
+	#[pin]
+	fut: F,
+	insert: Option<(ResponseCache, CacheKey, Duration)>,
+}
+
+impl<F> std::fmt::Debug for ResponseFuture<F> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str("ResponseFuture")
+	}
+}
+
+impl<F: Future<Output = MethodResponse>> Future for ResponseFuture<F> {
+	type Output = MethodResponse;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let this = self.project();
+		let rp = futures_util::ready!(this.fut.poll(cx));
+
+		if let Some((cache, key, ttl)) = this.insert.take() {
+			if rp.is_success() && rp.is_method_call() {
+				if let Ok(Response { payload: jsonrpsee_types::ResponsePayload::Success(result), .. }) =
+					serde_json::from_str::<Response<Box<JsonRawValue>>>(rp.as_result())
+				{
+					cache.0.lock().expect("poisoned lock").insert(key, result.into_owned(), ttl);
+				}
+			}
+		}
+
+		Poll::Ready(rp)
+	}
+}
+
+/// Serialize the parameters in a canonical form, such that equivalent parameters get the same key.
+pub(crate) fn canonical_params(params: Option<&str>) -> String {
+	let Some(params) = params else {
+		return String::new();
+	};
+
+	match serde_json::from_str::<serde_json::Value>(params) {
+		Ok(value) => {
+			let mut out = String::with_capacity(params.len());
+			write_canonical(&value, &mut out);
+			out
+		}
+		Err(_) => params.to_owned(),
+	}
+}
+
+/// Write the compact JSON encoding of `value` with the keys of the objects sorted.
+///
+/// The keys are sorted explicitly because the order of a `serde_json::Map` depends on the `preserve_order` feature.
+fn write_canonical(value: &serde_json::Value, out: &mut String) {
+	match value {
+		serde_json::Value::Object(map) => {
+			let mut entries: Vec<_> = map.iter().collect();
+			entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+
+			out.push('{');
+			for (i, (key, value)) in entries.into_iter().enumerate() {
+				if i > 0 {
+					out.push(',');
+				}
+				out.push_str(&serde_json::Value::from(key.as_str()).to_string());
+				out.push(':');
+				write_canonical(value, out);
+			}
+			out.push('}');
+		}
+		serde_json::Value::Array(values) => {
+			out.push('[');
+			for (i, value) in values.iter().enumerate() {
+				if i > 0 {
+					out.push(',');
+				}
+				write_canonical(value, out);
+			}
+			out.push(']');
+		}
+		value => out.push_str(&value.to_string()),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn raw(s: &str) -> Box<JsonRawValue> {
+		JsonRawValue::from_string(s.to_owned()).unwrap()
+	}
+
+	fn key(method: &str) -> CacheKey {
+		CacheKey { method: method.to_owned(), caller: None, params: String::new() }
+	}
+
+	#[test]
+	fn canonical_params_sorts_nested_keys() {
+		let a = canonical_params(Some(r#"{"b": [{"d": 1, "c": {"z": null, "y": "x"}}], "a": 1}"#));
+		let b = canonical_params(Some(r#"{"a":1,"b":[{"c":{"y":"x","z":null},"d":1}]}"#));
+		assert_eq!(a, b);
+		assert_eq!(a, r#"{"a":1,"b":[{"c":{"y":"x","z":null},"d":1}]}"#);
+		assert_eq!(canonical_params(Some("[1, 2]")), "[1,2]");
+		assert_eq!(canonical_params(None), "");
+	}
+
+	#[test]
+	fn cache_is_bounded_by_size() {
+		let mut inner = Inner { entries: HashMap::new(), order: VecDeque::new(), size: 0, max_size: 10 };
+		let ttl = Duration::from_secs(60);
+
+		inner.insert(key("a"), raw(r#""aaaa""#), ttl);
+		inner.insert(key("b"), raw(r#""bbbb""#), ttl);
+		// The oldest result is evicted when the cache is full.
+		assert_eq!(inner.size, 6);
+		assert!(inner.get(&key("a")).is_none());
+
+		// Results larger than the cache are never cached.
+		inner.insert(key("c"), raw(r#""ccccccccccc""#), ttl);
+		assert!(inner.get(&key("c")).is_none());
+
+		// Replacing an entry updates the size.
+		inner.insert(key("b"), raw("1"), ttl);
+		assert_eq!(inner.size, 1);
+
+		inner.insert(key("d"), raw("[1,2,3]"), ttl);
+		assert_eq!(inner.size, 8);
+		inner.retain(|k| k.method != "d");
+		assert_eq!(inner.size, 1);
+		assert_eq!(inner.order, [key("b")]);
+	}
+}
@@ -0,0 +1,289 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any
+// person obtaining a copy of this software and associated
+// documentation files (the "Software"), to deal in the
+// Software without restriction, including without
+// limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software
+// is furnished to do so, subject to the following
+// conditions:
+//
+// The above copyright notice and this permission notice
+// shall be included in all copies or substantial portions
+// of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF
+// ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED
+// TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A
+// PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT
+// SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY
+// CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+// IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! RPC layer that deduplicates identical in-flight calls.
+
+use std::collections::{HashMap, HashSet};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+use futures_util::Future;
+use jsonrpsee_core::server::{MethodResponse, StreamedResponseSupport};
+use jsonrpsee_core::JsonRawValue;
+use jsonrpsee_types::{ErrorCode, ErrorObjectOwned, Request, Response};
+use pin_project::pin_project;
+use tokio::sync::oneshot;
+
+use super::cache::{CacheKey, CachedResult};
+use crate::middleware::rpc::RpcServiceT;
+
+type SharedResult = Result<Box<JsonRawValue>, ErrorObjectOwned>;
+type Waiters = Arc<Mutex<HashMap<CacheKey, Vec<oneshot::Sender<Outcome>>>>>;
+
+/// What a waiting call is told by the call that it waits for.
+#[derive(Debug)]
+enum Outcome {
+	/// The result of the call.
+	Completed(SharedResult),
+	/// The call was dropped before it completed and the waiting call executes the method handler instead.
+	Promoted,
+}
+
+/// RPC layer that coalesces identical concurrent calls onto one execution of the method handler.
+///
+/// Calls are identical if they have the same method name, [`crate::CallerIdentity`] and canonicalized parameters.
+/// While a call is executed, identical calls wait for it to complete and get a copy of
+/// its result instead of executing the method handler again. If the executing call is dropped,
+/// for instance because its connection is closed, one of the waiting calls executes the method handler instead.
+/// The shared result is passed on to the inner services, such that the other middleware observe
+/// the waiting calls and the response size limit of their connection is applied.
+///
+/// Only the selected methods are deduplicated, these must be regular method calls
+/// and not subscriptions.
+///
+/// # Examples
+///
+/// ```rust
+/// use jsonrpsee_server::middleware::rpc::{RequestDedupLayer, RpcServiceBuilder};
+///
+/// let rpc_middleware = RpcServiceBuilder::new().layer(RequestDedupLayer::new().dedup_method("eth_call"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RequestDedupLayer {
+	methods: Arc<HashSet<String>>,
+	waiters: Waiters,
+}
+
+impl RequestDedupLayer {
+	/// Create a new deduplication layer.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Deduplicate identical in-flight calls to `method`.
+	pub fn dedup_method(mut self, method: impl Into<String>) -> Self {
+		Arc::make_mut(&mut self.methods).insert(method.into());
+		self
+	}
+}
+
+impl<S> tower::Layer<S> for RequestDedupLayer {
+	type Service = RequestDedup<S>;
+
+	fn layer(&self, service: S) -> Self::Service {
+		RequestDedup { service: Arc::new(service), methods: self.methods.clone(), waiters: self.waiters.clone() }
+	}
+}
+
+/// A middleware that deduplicates identical in-flight calls, see [`RequestDedupLayer`].
+#[derive(Debug)]
+pub struct RequestDedup<S> {
+	service: Arc<S>,
+	methods: Arc<HashSet<String>>,
+	waiters: Waiters,
+}
+
+impl<'a, S> RpcServiceT<'a> for RequestDedup<S>
+where
+	S: RpcServiceT<'a> + Send + Sync,
+{
+	type Future = ResponseFuture<'a, S>;
+
+	fn call(&self, mut request: Request<'a>) -> Self::Future {
+		if !self.methods.contains(request.method_name()) {
+			return ResponseFuture { state: State::Call { fut: self.service.call(request), leader: None } };
+		}
+
+		let key = CacheKey::new(&request);
+		let mut waiters = self.waiters.lock().expect("poisoned lock");
+
+		if let Some(w) = waiters.get_mut(&key) {
+			let (tx, rx) = oneshot::channel();
+			w.push(tx);
+			let waiter = Waiter { rx, waiters: self.waiters.clone(), key };
+			return ResponseFuture {
+				state: State::Wait { waiter, request: Some(request), service: self.service.clone() },
+			};
+		}
+
+		waiters.insert(key.clone(), Vec::new());
+		drop(waiters);
+
+		// A streamed response can't be shared with the waiting calls.
+		request.extensions_mut().remove::<StreamedResponseSupport>();
+
+		let leader = Leader { waiters: self.waiters.clone(), key, completed: false };
+		ResponseFuture { state: State::Call { fut: self.service.call(request), leader: Some(leader) } }
+	}
+}
+
+/// Let the first of the calls waiting for `key` that is still alive execute the method handler,
+/// such that the other calls wait for it instead.
+fn promote(waiters: &Waiters, key: &CacheKey) {
+	let mut waiters = waiters.lock().expect("poisoned lock");
+
+	let Some(w) = waiters.get_mut(key) else {
+		return;
+	};
+
+	while !w.is_empty() {
+		if w.remove(0).send(Outcome::Promoted).is_ok() {
+			return;
+		}
+	}
+
+	waiters.remove(key);
+}
+
+/// The call that executes the method handler on behalf of the identical calls.
+///
+/// If the call is dropped before it completes one of the waiting calls is promoted to execute the method handler.
+struct Leader {
+	waiters: Waiters,
+	key: CacheKey,
+	completed: bool,
+}
+
+impl Leader {
+	fn complete(mut self, rp: &MethodResponse) {
+		self.completed = true;
+
+		let Some(waiters) = self.waiters.lock().expect("poisoned lock").remove(&self.key) else {
+			return;
+		};
+
+		if waiters.is_empty() {
+			return;
+		}
+
+		let result = match serde_json::from_str::<Response<Box<JsonRawValue>>>(rp.as_result()) {
+			Ok(Response { payload: jsonrpsee_types::ResponsePayload::Success(result), .. }) => Ok(result.into_owned()),
+			Ok(Response { payload: jsonrpsee_types::ResponsePayload::Error(err), .. }) => Err(err.into_owned()),
+			Err(_) => Err(ErrorCode::InternalError.into()),
+		};
+
+		for tx in waiters {
+			_ = tx.send(Outcome::Completed(result.clone()));
+		}
+	}
+}
+
+impl Drop for Leader {
+	fn drop(&mut self) {
+		if !self.completed {
+			promote(&self.waiters, &self.key);
+		}
+	}
+}
+
+/// A call that waits for the identical call which executes the method handler.
+struct Waiter {
+	rx: oneshot::Receiver<Outcome>,
+	waiters: Waiters,
+	key: CacheKey,
+}
+
+impl Drop for Waiter {
+	fn drop(&mut self) {
+		// The call is dropped after it was promoted but before it executed the method handler.
+		if let Ok(Outcome::Promoted) = self.rx.try_recv() {
+			promote(&self.waiters, &self.key);
+		}
+	}
+}
+
+/// Response future of a deduplicated call.
+#[pin_project]
+pub struct ResponseFuture<'a, S: RpcServiceT<'a>> {
+	#[pin]
+	state: State<'a, S>,
+}
+
+#[pin_project(project = StateProj)]
+enum State<'a, S: RpcServiceT<'a>> {
+	Call {
+		#[pin]
+		fut: S::Future,
+		leader: Option<Leader>,
+	},
+	Wait {
+		waiter: Waiter,
+		request: Option<Request<'a>>,
+		service: Arc<S>,
+	},
+}
+
+impl<'a, S: RpcServiceT<'a>> std::fmt::Debug for ResponseFuture<'a, S> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str("ResponseFuture")
+	}
+}
+
+impl<'a, S: RpcServiceT<'a>> Future for ResponseFuture<'a, S> {
+	type Output = MethodResponse;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let mut state = self.project().state;
+
+		loop {
+			match state.as_mut().project() {
+				StateProj::Call { fut, leader } => {
+					let rp = futures_util::ready!(fut.poll(cx));
+
+					if let Some(leader) = leader.take() {
+						leader.complete(&rp);
+					}
+
+					return Poll::Ready(rp);
+				}
+				StateProj::Wait { waiter, request, service } => {
+					let outcome = futures_util::ready!(Pin::new(&mut waiter.rx).poll(cx));
+					let mut request = request.take().expect("polled after completion");
+
+					let leader = match outcome {
+						Ok(Outcome::Completed(result)) => {
+							// The shared result is answered by the inner service, which applies the limits of this connection.
+							request.extensions_mut().insert(CachedResult(result));
+							None
+						}
+						Ok(Outcome::Promoted) => {
+							request.extensions_mut().remove::<StreamedResponseSupport>();
+							Some(Leader { waiters: waiter.waiters.clone(), key: waiter.key.clone(), completed: false })
+						}
+						Err(_) => {
+							request.extensions_mut().insert(CachedResult(Err(ErrorCode::InternalError.into())));
+							None
+						}
+					};
+					let fut = service.call(request);
+
+					state.set(State::Call { fut, leader });
+				}
+			}
+		}
+	}
+}
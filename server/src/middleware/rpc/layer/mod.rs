@@ -26,12 +26,22 @@
 
 //! Specific middleware layer implementation provided by jsonrpsee.
 
+pub mod admission;
+pub mod cache;
+pub mod dedup;
 pub mod either;
 pub mod logger;
+pub mod module;
 pub mod rpc_service;
+pub mod validation;
 
+pub use admission::*;
+pub use cache::*;
+pub use dedup::*;
 pub use logger::*;
+pub use module::*;
 pub use rpc_service::*;
+pub use validation::*;
 
 use std::pin::Pin;
 use std::task::{Context, Poll};
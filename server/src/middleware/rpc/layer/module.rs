@@ -0,0 +1,109 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any
+// person obtaining a copy of this software and associated
+// documentation files (the "Software"), to deal in the
+// Software without restriction, including without
+// limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software
+// is furnished to do so, subject to the following
+// conditions:
+//
+// The above copyright notice and this permission notice
+// shall be included in all copies or substantial portions
+// of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF
+// ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED
+// TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A
+// PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT
+// SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY
+// CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+// IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! RPC layer that applies a middleware to the methods of a module only.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use futures_util::future::Either;
+use jsonrpsee_core::server::Methods;
+use jsonrpsee_types::Request;
+
+use crate::middleware::rpc::RpcServiceT;
+
+/// RPC layer that applies the middleware `L` to the calls of the methods of a module,
+/// while the calls of other methods bypass it.
+///
+/// This is useful to attach for instance authorization, caching or rate limits to a
+/// module which is merged with other modules into the server. The methods are captured
+/// when the layer is created, thus methods registered afterwards are not covered.
+///
+/// # Examples
+///
+/// ```rust
+/// use jsonrpsee_server::RpcModule;
+/// use jsonrpsee_server::middleware::rpc::{ModuleLayer, RpcLoggerLayer, RpcServiceBuilder};
+///
+/// let mut admin = RpcModule::new(());
+/// admin.register_method("admin_restart", |_, _, _| "ok").unwrap();
+///
+/// let mut module = RpcModule::new(());
+/// module.register_method("say_hello", |_, _, _| "lo").unwrap();
+///
+/// // Only the calls of `admin_restart` are logged.
+/// let rpc_middleware = RpcServiceBuilder::new().layer(ModuleLayer::new(&admin, RpcLoggerLayer::new(1024)));
+///
+/// module.merge(admin).unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct ModuleLayer<L> {
+	methods: Arc<HashSet<&'static str>>,
+	layer: L,
+}
+
+impl<L> ModuleLayer<L> {
+	/// Create a new layer that applies `layer` to the calls of `methods`.
+	pub fn new(methods: &Methods, layer: L) -> Self {
+		Self { methods: Arc::new(methods.method_names().collect()), layer }
+	}
+}
+
+impl<S, L> tower::Layer<S> for ModuleLayer<L>
+where
+	S: Clone,
+	L: tower::Layer<S>,
+{
+	type Service = ModuleService<S, L::Service>;
+
+	fn layer(&self, service: S) -> Self::Service {
+		ModuleService { scoped: self.layer.layer(service.clone()), service, methods: self.methods.clone() }
+	}
+}
+
+/// A middleware that applies a middleware to the calls of the methods of a module, see [`ModuleLayer`].
+#[derive(Debug, Clone)]
+pub struct ModuleService<S, M> {
+	service: S,
+	scoped: M,
+	methods: Arc<HashSet<&'static str>>,
+}
+
+impl<'a, S, M> RpcServiceT<'a> for ModuleService<S, M>
+where
+	S: RpcServiceT<'a>,
+	M: RpcServiceT<'a>,
+{
+	type Future = Either<M::Future, S::Future>;
+
+	fn call(&self, request: Request<'a>) -> Self::Future {
+		if self.methods.contains(request.method_name()) {
+			Either::Left(self.scoped.call(request))
+		} else {
+			Either::Right(self.service.call(request))
+		}
+	}
+}
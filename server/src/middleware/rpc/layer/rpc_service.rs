@@ -26,18 +26,24 @@
 
 //! JSON-RPC service middleware.
 
+use super::cache::CachedResult;
 use super::ResponseFuture;
+use std::panic::AssertUnwindSafe;
 use std::sync::Arc;
+use std::time::Duration;
 
+use crate::hooks::{AuditLog, OnResponse, OnSubscribe, ResponseInfo, SubscribeInfo};
+use crate::long_polling::LongPolling;
 use crate::middleware::rpc::RpcServiceT;
 use crate::ConnectionId;
-use futures_util::future::BoxFuture;
+use futures_util::future::{BoxFuture, FutureExt};
 use jsonrpsee_core::server::{
-	BoundedSubscriptions, MethodCallback, MethodResponse, MethodSink, Methods, SubscriptionState,
+	report_panic, BoundedSubscriptions, MethodCallback, MethodResponse, MethodSink, Methods, PanicHook,
+	ResponseExtensions, ResponsePayload, ResponsePriority, SubscriptionState,
 };
 use jsonrpsee_core::traits::IdProvider;
-use jsonrpsee_types::error::{reject_too_many_subscriptions, ErrorCode};
-use jsonrpsee_types::{ErrorObject, Request};
+use jsonrpsee_types::error::{reject_method_timeout, reject_too_many_subscriptions, ErrorCode};
+use jsonrpsee_types::{ErrorObject, Id, Request};
 
 /// JSON-RPC service middleware.
 #[derive(Clone, Debug)]
@@ -45,6 +51,10 @@ pub struct RpcService {
 	conn_id: ConnectionId,
 	methods: Methods,
 	max_response_body_size: usize,
+	client_max_response_size: Option<usize>,
+	method_timeout: Option<Duration>,
+	on_response: Option<OnResponse>,
+	audit_log: Option<AuditLog>,
 	cfg: RpcServiceCfg,
 }
 
@@ -58,19 +68,37 @@ pub(crate) enum RpcServiceCfg {
 		bounded_subscriptions: BoundedSubscriptions,
 		sink: MethodSink,
 		id_provider: Arc<dyn IdProvider>,
+		on_subscribe: Option<OnSubscribe>,
+		/// The connection ID which the subscriptions are tracked by. The subscriptions that are long-polled
+		/// over HTTP share one, such that they can be polled and unsubscribed by other HTTP requests.
+		subscription_conn_id: ConnectionId,
 		_pending_calls: tokio::sync::mpsc::Sender<()>,
 	},
 }
 
 impl RpcService {
 	/// Create a new service.
+	#[allow(clippy::too_many_arguments)]
 	pub(crate) fn new(
 		methods: Methods,
 		max_response_body_size: usize,
+		client_max_response_size: Option<u32>,
+		method_timeout: Option<Duration>,
+		on_response: Option<OnResponse>,
+		audit_log: Option<AuditLog>,
 		conn_id: ConnectionId,
 		cfg: RpcServiceCfg,
 	) -> Self {
-		Self { methods, max_response_body_size, conn_id, cfg }
+		Self {
+			methods,
+			max_response_body_size,
+			client_max_response_size: client_max_response_size.map(|s| s as usize),
+			method_timeout,
+			on_response,
+			audit_log,
+			conn_id,
+			cfg,
+		}
 	}
 }
 
@@ -80,28 +108,120 @@ impl<'a> RpcServiceT<'a> for RpcService {
 	type Future = ResponseFuture<BoxFuture<'a, MethodResponse>>;
 
 	fn call(&self, req: Request<'a>) -> Self::Future {
+		if self.on_response.is_none() && self.audit_log.is_none() {
+			return self.call_method(req);
+		}
+
+		let on_response = self.on_response.clone().map(|hook| {
+			let info = ResponseInfo { conn_id: self.conn_id, method: req.method_name().to_owned() };
+			(hook, info)
+		});
+		let audit = self.audit_log.as_ref().map(|log| log.begin(self.conn_id, &req));
+		let fut = self.call_method(req);
+
+		ResponseFuture::future(
+			async move {
+				let mut rp = fut.await;
+				if let Some((hook, info)) = on_response {
+					rp = hook.call(&info, rp);
+				}
+				// The audit record reflects the response that is actually sent to the client.
+				if let Some(audit) = audit {
+					audit.finish(&rp);
+				}
+				rp
+			}
+			.boxed(),
+		)
+	}
+}
+
+impl RpcService {
+	fn call_method<'a>(&self, req: Request<'a>) -> ResponseFuture<BoxFuture<'a, MethodResponse>> {
 		let conn_id = self.conn_id;
-		let max_response_body_size = self.max_response_body_size;
 
-		let Request { id, method, params, extensions, .. } = req;
+		let Request { id, method, params, mut extensions, .. } = req;
 		let params = jsonrpsee_types::Params::new(params.as_ref().map(|p| serde_json::value::RawValue::get(p)));
 
-		match self.methods.method_with_name(&method) {
+		// The handle is passed on to the response, such that the values which the
+		// method handler attaches to it are observed by the middleware.
+		if extensions.get::<ResponseExtensions>().is_none() {
+			extensions.insert(ResponseExtensions::new());
+		}
+
+		let method = self.methods.method_with_name(&method);
+		let config = method.and_then(|(name, _)| self.methods.method_config(name));
+		// The limit of a method may exceed the limit of the server but never the limit that the client advertised.
+		let max_response_body_size =
+			config.and_then(|c| c.max_response_size()).map_or(self.max_response_body_size, |s| {
+				self.client_max_response_size.map_or(s as usize, |client| client.min(s as usize))
+			});
+
+		// The priority is passed on to the response such that the transport can send it accordingly.
+		if let Some((name, _)) = method {
+			let priority = self.methods.response_priority(name);
+			if priority != ResponsePriority::Normal {
+				extensions.insert(priority);
+			}
+		}
+
+		// A result served by the response cache or shared by an identical call is answered without invoking
+		// the method handler.
+		if let (Some(_), Some(CachedResult(result))) = (method, extensions.remove::<CachedResult>()) {
+			let rp = match result {
+				Ok(result) => MethodResponse::response(id, ResponsePayload::success(result), max_response_body_size),
+				Err(err) => MethodResponse::error(id, err),
+			};
+			return ResponseFuture::ready(rp.with_extensions(extensions));
+		}
+
+		// A panic of the method handler is answered with an error instead of tearing down the connection.
+		let panic_hook = extensions.get::<PanicHook>().cloned();
+
+		match method {
 			None => {
 				let rp =
 					MethodResponse::error(id, ErrorObject::from(ErrorCode::MethodNotFound)).with_extensions(extensions);
 				ResponseFuture::ready(rp)
 			}
-			Some((_name, method)) => match method {
+			Some((name, method)) => match method {
 				MethodCallback::Async(callback) => {
 					let params = params.into_owned();
 					let id = id.into_owned();
+					let timeout = config.and_then(|c| c.timeout()).or(self.method_timeout);
+					let catch_panic = move |fut: BoxFuture<'static, MethodResponse>, id: Id<'static>| {
+						AssertUnwindSafe(fut).catch_unwind().map(move |rp| {
+							rp.unwrap_or_else(|payload| {
+								MethodResponse::error(id, report_panic(conn_id, name, payload, panic_hook.as_ref()))
+							})
+						})
+					};
+
+					match timeout {
+						Some(timeout) => {
+							let ext = extensions.clone();
+							let fut = (callback)(id.clone(), params, conn_id, max_response_body_size, extensions);
+							let fut = catch_panic(fut, id.clone());
 
-					let fut = (callback)(id, params, conn_id, max_response_body_size, extensions);
-					ResponseFuture::future(fut)
+							// The method call future is dropped if the timeout expires.
+							let fut = tokio::time::timeout(timeout, fut).map(move |rp| match rp {
+								Ok(rp) => rp,
+								Err(_) => {
+									MethodResponse::error(id, reject_method_timeout(timeout)).with_extensions(ext)
+								}
+							});
+							ResponseFuture::future(fut.boxed())
+						}
+						None => {
+							let fut = (callback)(id.clone(), params, conn_id, max_response_body_size, extensions);
+							ResponseFuture::future(catch_panic(fut, id).boxed())
+						}
+					}
 				}
 				MethodCallback::Sync(callback) => {
-					let rp = (callback)(id, params, max_response_body_size, extensions);
+					let rp = call_sync(conn_id, name, id.clone(), panic_hook, || {
+						(callback)(id, params, max_response_body_size, extensions)
+					});
 					ResponseFuture::ready(rp)
 				}
 				MethodCallback::Subscription(callback) => {
@@ -109,6 +229,8 @@ impl<'a> RpcServiceT<'a> for RpcService {
 						bounded_subscriptions,
 						sink,
 						id_provider,
+						on_subscribe,
+						subscription_conn_id,
 						_pending_calls,
 					} = self.cfg.clone()
 					else {
@@ -118,12 +240,47 @@ impl<'a> RpcServiceT<'a> for RpcService {
 						return ResponseFuture::ready(rp);
 					};
 
+					if let Some(on_subscribe) = on_subscribe {
+						let info =
+							SubscribeInfo { conn_id, method: name, params: params.clone(), extensions: &extensions };
+						if let Err(err) = on_subscribe.call(&info) {
+							return ResponseFuture::ready(MethodResponse::error(id, err).with_extensions(extensions));
+						}
+					}
+
+					let method_permit = match config.and_then(|c| c.max_subscriptions_per_connection()) {
+						Some(max) => match bounded_subscriptions.acquire_for_method(name, max) {
+							Some(p) => Some(p),
+							None => {
+								let rp = MethodResponse::error(id, reject_too_many_subscriptions(max))
+									.with_extensions(extensions);
+								return ResponseFuture::ready(rp);
+							}
+						},
+						None => None,
+					};
+
 					if let Some(p) = bounded_subscriptions.acquire() {
-						let conn_state =
-							SubscriptionState { conn_id, id_provider: &*id_provider.clone(), subscription_permit: p };
+						let conn_state = SubscriptionState::new(
+							subscription_conn_id,
+							&*id_provider,
+							p,
+							config.copied().unwrap_or_default(),
+						)
+						.with_method_permit(method_permit);
 
-						let fut = callback(id.clone(), params, sink, conn_state, extensions);
-						ResponseFuture::future(fut)
+						// The callback takes the extensions, a copy is kept for the response if it panics.
+						let ext = extensions.clone();
+						let rp = std::panic::catch_unwind(AssertUnwindSafe(|| {
+							callback(id.clone(), params, sink, conn_state, extensions)
+						}));
+						match rp {
+							Ok(fut) => ResponseFuture::future(fut),
+							Err(payload) => {
+								let err = report_panic(conn_id, name, payload, panic_hook.as_ref());
+								ResponseFuture::ready(MethodResponse::error(id, err).with_extensions(ext))
+							}
+						}
 					} else {
 						let max = bounded_subscriptions.max();
 						let rp =
@@ -134,17 +291,41 @@ impl<'a> RpcServiceT<'a> for RpcService {
 				MethodCallback::Unsubscription(callback) => {
 					// Don't adhere to any resource or subscription limits; always let unsubscribing happen!
 
-					let RpcServiceCfg::CallsAndSubscriptions { .. } = self.cfg else {
+					let RpcServiceCfg::CallsAndSubscriptions { subscription_conn_id, .. } = self.cfg else {
 						tracing::warn!("Subscriptions not supported");
 						let rp = MethodResponse::error(id, ErrorObject::from(ErrorCode::InternalError))
 							.with_extensions(extensions);
 						return ResponseFuture::ready(rp);
 					};
 
-					let rp = callback(id, params, conn_id, max_response_body_size, extensions);
+					// A long-polled subscription may only be unsubscribed by the caller which created it.
+					if let Some(long_polling) = extensions.get::<LongPolling>() {
+						if !long_polling.may_unsubscribe(&params, &extensions) {
+							let rp =
+								MethodResponse::response(id, ResponsePayload::success(false), max_response_body_size);
+							return ResponseFuture::ready(rp.with_extensions(extensions));
+						}
+					}
+
+					let rp = call_sync(conn_id, name, id.clone(), panic_hook, || {
+						callback(id, params, subscription_conn_id, max_response_body_size, extensions)
+					});
 					ResponseFuture::ready(rp)
 				}
 			},
 		}
 	}
 }
+
+/// Call a synchronous method handler and answer the call with an error if the handler panics.
+fn call_sync(
+	conn_id: ConnectionId,
+	method: &str,
+	id: Id,
+	panic_hook: Option<PanicHook>,
+	f: impl FnOnce() -> MethodResponse,
+) -> MethodResponse {
+	std::panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|payload| {
+		MethodResponse::error(id, report_panic(conn_id, method, payload, panic_hook.as_ref()))
+	})
+}
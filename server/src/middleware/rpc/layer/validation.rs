@@ -0,0 +1,116 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any
+// person obtaining a copy of this software and associated
+// documentation files (the "Software"), to deal in the
+// Software without restriction, including without
+// limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software
+// is furnished to do so, subject to the following
+// conditions:
+//
+// The above copyright notice and this permission notice
+// shall be included in all copies or substantial portions
+// of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF
+// ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED
+// TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A
+// PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT
+// SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY
+// CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+// IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! RPC layer that validates the params of calls against a JSON Schema.
+
+use jsonrpsee_core::server::{validate_params, MethodResponse, Methods};
+use jsonrpsee_types::error::{ErrorCode, INVALID_PARAMS_MSG};
+use jsonrpsee_types::{ErrorObject, Request};
+use serde_json::Value;
+
+use crate::middleware::rpc::{ResponseFuture, RpcServiceT};
+
+pub use jsonrpsee_core::server::SchemaViolation;
+
+/// RPC layer that validates the params of calls before they are dispatched, see
+/// [`Methods::set_method_params_schema`] for how to attach a schema to a method.
+///
+/// Calls whose params don't conform to the schema are answered with an `Invalid params`
+/// error with a list of [`SchemaViolation`]s as data. Missing params are validated as an
+/// empty array and methods without a schema are not validated.
+///
+/// The following subset of JSON Schema is supported: `$ref` to subschemas of the schema itself, `type`, `enum`,
+/// `const`, `properties`, `required`, `additionalProperties`, `items`, `prefixItems`, `additionalItems`,
+/// `minItems`, `maxItems`, `uniqueItems`, `minimum`, `maximum`, `exclusiveMinimum`, `exclusiveMaximum`,
+/// `minLength`, `maxLength`, `allOf`, `anyOf`, `oneOf` and `not`, as well as annotations such as `format`.
+/// Schemas with other keywords are rejected by [`Methods::set_method_params_schema`].
+///
+/// # Examples
+///
+/// ```rust
+/// use jsonrpsee_server::RpcModule;
+/// use jsonrpsee_server::middleware::rpc::{ParamsValidationLayer, RpcServiceBuilder};
+///
+/// let mut module = RpcModule::new(());
+/// module.register_method("echo", |params, _, _| params.one::<String>().unwrap()).unwrap();
+/// module.set_method_params_schema("echo", serde_json::json!({ "type": "array", "items": [{ "type": "string" }] })).unwrap();
+///
+/// let rpc_middleware = RpcServiceBuilder::new().layer(ParamsValidationLayer::new(&module));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ParamsValidationLayer {
+	methods: Methods,
+}
+
+impl ParamsValidationLayer {
+	/// Create a new layer that validates the params against the schemas attached to `methods`.
+	pub fn new(methods: &Methods) -> Self {
+		Self { methods: methods.clone() }
+	}
+}
+
+impl<S> tower::Layer<S> for ParamsValidationLayer {
+	type Service = ParamsValidation<S>;
+
+	fn layer(&self, service: S) -> Self::Service {
+		ParamsValidation { service, methods: self.methods.clone() }
+	}
+}
+
+/// A middleware that validates the params of calls, see [`ParamsValidationLayer`].
+#[derive(Debug)]
+pub struct ParamsValidation<S> {
+	service: S,
+	methods: Methods,
+}
+
+impl<'a, S> RpcServiceT<'a> for ParamsValidation<S>
+where
+	S: RpcServiceT<'a>,
+{
+	type Future = ResponseFuture<S::Future>;
+
+	fn call(&self, request: Request<'a>) -> Self::Future {
+		let Some(schema) = self.methods.method_params_schema(request.method_name()) else {
+			return ResponseFuture::future(self.service.call(request));
+		};
+
+		let params = match request.params.as_ref().map(|p| serde_json::from_str::<Value>(p.get())) {
+			None => Value::Array(Vec::new()),
+			Some(Ok(params)) => params,
+			Some(Err(_)) => return ResponseFuture::ready(MethodResponse::error(request.id, ErrorCode::InvalidParams)),
+		};
+
+		let violations = validate_params(schema, &params);
+
+		if violations.is_empty() {
+			ResponseFuture::future(self.service.call(request))
+		} else {
+			let err = ErrorObject::owned(ErrorCode::InvalidParams.code(), INVALID_PARAMS_MSG, Some(violations));
+			ResponseFuture::ready(MethodResponse::error(request.id, err))
+		}
+	}
+}
@@ -0,0 +1,166 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any
+// person obtaining a copy of this software and associated
+// documentation files (the "Software"), to deal in the
+// Software without restriction, including without
+// limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software
+// is furnished to do so, subject to the following
+// conditions:
+//
+// The above copyright notice and this permission notice
+// shall be included in all copies or substantial portions
+// of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF
+// ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED
+// TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A
+// PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT
+// SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY
+// CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+// IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Routing of the requests on one listener to different services based on the path.
+
+use std::future::Future;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+use crate::future::StopHandle;
+use crate::server::with_builtin_methods;
+use crate::transport::http;
+use crate::{HttpBody, HttpRequest, HttpResponse, TowerService, TowerServiceBuilder};
+
+use futures_util::FutureExt;
+use hyper::body::Bytes;
+use jsonrpsee_core::server::Methods;
+use jsonrpsee_core::BoxError;
+use tower::util::BoxCloneService;
+use tower::{Service, ServiceExt};
+
+type RouteService = BoxCloneService<HttpRequest, HttpResponse, BoxError>;
+type MakeRoute = Arc<dyn Fn(u32, SocketAddr, StopHandle) -> RouteService + Send + Sync>;
+
+/// Routes the requests on one listener to different services based on the path of the request,
+/// such that for instance `/public` and `/admin` are served with different methods, limits and middleware.
+///
+/// Requests to a path without a route are rejected with `404 Not Found`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use jsonrpsee_server::{Router, RpcModule, Server};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let public = Server::builder().max_request_body_size(1024).to_service_builder();
+///     let admin = Server::builder().max_connections(2).to_service_builder();
+///
+///     let router = Router::new().route("/public", public, RpcModule::new(())).route("/admin", admin, RpcModule::new(()));
+///
+///     let server = Server::builder().build("127.0.0.1:0").await.unwrap();
+///     let handle = server.start_with_router(router);
+///     handle.stopped().await;
+/// }
+/// ```
+#[derive(Clone, Default)]
+pub struct Router {
+	routes: Vec<(String, MakeRoute)>,
+}
+
+impl std::fmt::Debug for Router {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("Router").field("routes", &self.routes.iter().map(|(path, _)| path).collect::<Vec<_>>()).finish()
+	}
+}
+
+impl Router {
+	/// Create a router without any routes.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Serve the HTTP requests and WebSocket connections to `path` with `methods` and
+	/// the configuration and middleware of `service_builder`.
+	///
+	/// The connection limit of `service_builder` applies to the route only and the transports
+	/// of the route can be restricted by [`Builder::http_only`](crate::ServerBuilder::http_only)
+	/// and [`Builder::ws_only`](crate::ServerBuilder::ws_only).
+	///
+	/// If a route for `path` already exists it's replaced.
+	pub fn route<RpcMiddleware, HttpMiddleware, B>(
+		mut self,
+		path: impl Into<String>,
+		service_builder: TowerServiceBuilder<RpcMiddleware, HttpMiddleware>,
+		methods: impl Into<Methods>,
+	) -> Self
+	where
+		RpcMiddleware: Clone + Send + Sync + 'static,
+		HttpMiddleware: Clone + Send + Sync + 'static,
+		TowerService<RpcMiddleware, HttpMiddleware>:
+			Service<HttpRequest, Response = HttpResponse<B>, Error = BoxError> + Clone + Send + 'static,
+		<TowerService<RpcMiddleware, HttpMiddleware> as Service<HttpRequest>>::Future: Send + 'static,
+		B: http_body::Body<Data = Bytes> + Send + 'static,
+		B::Error: Into<BoxError>,
+	{
+		let path = path.into();
+
+		// The methods are built once instead of for every connection.
+		let methods = with_builtin_methods(methods.into(), &service_builder.server_cfg);
+
+		let make_route = move |conn_id, remote_addr, stop_handle| {
+			let service = service_builder
+				.clone()
+				.connection_id(conn_id)
+				.remote_addr(remote_addr)
+				.build_with_builtin_methods(methods.clone(), stop_handle);
+			BoxCloneService::new(service.map_response(|rp| rp.map(HttpBody::new)))
+		};
+
+		self.routes.retain(|(p, _)| *p != path);
+		self.routes.push((path, Arc::new(make_route)));
+		self
+	}
+
+	/// Create the services of the routes for a connection.
+	pub(crate) fn service(&self, conn_id: u32, remote_addr: SocketAddr, stop_handle: &StopHandle) -> RouterService {
+		let routes = self
+			.routes
+			.iter()
+			.map(|(path, make_route)| (path.clone(), Mutex::new(make_route(conn_id, remote_addr, stop_handle.clone()))))
+			.collect();
+
+		RouterService { routes: Arc::new(routes) }
+	}
+}
+
+/// The services of the routes of a [`Router`] for a connection.
+#[derive(Clone)]
+pub(crate) struct RouterService {
+	routes: Arc<Vec<(String, Mutex<RouteService>)>>,
+}
+
+impl Service<HttpRequest> for RouterService {
+	type Response = HttpResponse;
+	type Error = BoxError;
+	type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+	fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+		Poll::Ready(Ok(()))
+	}
+
+	fn call(&mut self, request: HttpRequest) -> Self::Future {
+		let Some((_, service)) = self.routes.iter().find(|(path, _)| path == request.uri().path()) else {
+			return async { Ok(http::response::not_found()) }.boxed();
+		};
+
+		let service = service.lock().expect("poisoned lock").clone();
+		service.oneshot(request).boxed()
+	}
+}
@@ -24,45 +24,62 @@
 // IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
 // DEALINGS IN THE SOFTWARE.
 
+use std::borrow::Cow;
+use std::collections::HashMap;
 use std::error::Error as StdError;
 use std::future::Future;
 use std::net::{SocketAddr, TcpListener as StdTcpListener};
+use std::ops::RangeInclusive;
 use std::pin::Pin;
-use std::sync::atomic::AtomicU32;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use std::task::Poll;
 use std::time::Duration;
 
-use crate::future::{session_close, ConnectionGuard, ServerHandle, SessionClose, SessionClosedFuture, StopHandle};
+use crate::future::{
+	session_close, ConnectionGuard, HandoffRequest, ServerHandle, SessionClose, SessionClosedFuture, StopHandle,
+};
+use crate::hooks::{
+	AuditLog, AuditRecord, ConnectInfo, DisconnectGuard, DisconnectInfo, DisconnectReason, OnConnect, OnDisconnect,
+	OnMessage, OnResponse, OnSubscribe, RawMessage, ResponseInfo, SubscribeInfo, TransportKind,
+};
+use crate::long_polling::{LongPolling, LongPollingConfig, LONG_POLLING_METHOD};
+use crate::middleware::connection::{Connection, ConnectionMiddleware, ServeConnection, SessionGuard};
 use crate::middleware::rpc::{RpcService, RpcServiceBuilder, RpcServiceCfg, RpcServiceT};
+use crate::router::Router;
+use crate::transport::frame::WsFrameIo;
 use crate::transport::ws::BackgroundTaskParams;
 use crate::transport::{http, ws};
-use crate::utils::deserialize;
 use crate::{Extensions, HttpBody, HttpRequest, HttpResponse, LOG_TARGET};
 
 use futures_util::future::{self, Either, FutureExt};
 use futures_util::io::{BufReader, BufWriter};
+use futures_util::stream::{self, StreamExt};
 
 use hyper::body::Bytes;
 use hyper_util::rt::{TokioExecutor, TokioIo};
+use jsonrpsee_core::codec::Codecs;
 use jsonrpsee_core::id_providers::RandomIntegerIdProvider;
 use jsonrpsee_core::server::helpers::prepare_error;
 use jsonrpsee_core::server::{
-	BatchResponseBuilder, BoundedSubscriptions, ConnectionId, MethodResponse, MethodSink, Methods,
+	BatchResponseBuilder, BoundedSubscriptions, ConnectionExtensions, ConnectionId, MethodCallback, MethodMetadata,
+	MethodPanic, MethodResponse, MethodSink, Methods, OpenRpcInfo, PanicHook, ResponsePriority, RpcModule, SinkMessage,
+	StreamedResponseSupport, SubscriptionStats,
 };
 use jsonrpsee_core::traits::IdProvider;
-use jsonrpsee_core::{BoxError, JsonRawValue, TEN_MB_SIZE_BYTES};
+use jsonrpsee_core::{BoxError, JsonRawValue, Limits, TEN_MB_SIZE_BYTES};
 
 use jsonrpsee_types::error::{
-	reject_too_big_batch_request, ErrorCode, BATCHES_NOT_SUPPORTED_CODE, BATCHES_NOT_SUPPORTED_MSG,
+	reject_too_big_batch_request, reject_too_big_request, ErrorCode, BATCHES_NOT_SUPPORTED_CODE,
+	BATCHES_NOT_SUPPORTED_MSG, BATCH_CALL_NOT_PROCESSED_CODE, BATCH_CALL_NOT_PROCESSED_MSG,
 };
-use jsonrpsee_types::{ErrorObject, Id, InvalidRequest, Notification};
+use jsonrpsee_types::{ErrorObject, ErrorObjectOwned, Id, InvalidRequest, Notification, RawId, Request, TwoPointZero};
 use soketto::handshake::http::is_upgrade_request;
 use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
 use tokio::sync::{mpsc, watch, OwnedSemaphorePermit};
 use tokio_util::compat::TokioAsyncReadCompatExt;
 use tower::layer::util::Identity;
-use tower::{Layer, Service};
+use tower::{Layer, Service, ServiceExt};
 use tracing::{instrument, Instrument};
 
 type Notif<'a> = Notification<'a, Option<&'a JsonRawValue>>;
@@ -96,6 +113,45 @@ impl<RpcMiddleware, HttpMiddleware> Server<RpcMiddleware, HttpMiddleware> {
 	pub fn local_addr(&self) -> std::io::Result<SocketAddr> {
 		self.listener.local_addr()
 	}
+
+	/// Start responding to connection requests with the routes of the [`Router`].
+	///
+	/// Each route is served with its own configuration, middleware and methods, thus only the
+	/// settings of the server that concern the listener itself such as [`Builder::set_tcp_no_delay`],
+	/// [`Builder::custom_tokio_runtime`], [`Builder::custom_connection_runtime`] and
+	/// [`Builder::set_connection_middleware`] are used.
+	///
+	/// This will run on the tokio runtime until the server is stopped or the `ServerHandle` is dropped.
+	pub fn start_with_router(mut self, router: Router) -> ServerHandle {
+		let (stop_tx, stop_rx) = watch::channel(());
+
+		let stop_handle = StopHandle::new(stop_rx);
+		let subscription_stats = self.server_cfg.subscription_stats.clone();
+		let utf8_stats = self.server_cfg.utf8_stats.clone();
+		let (handoff_tx, handoff_rx) = mpsc::channel(1);
+
+		let make_service = {
+			let stop_handle = stop_handle.clone();
+			move |conn_id, remote_addr| router.service(conn_id, remote_addr, &stop_handle)
+		};
+		let accept = accept_connections(
+			self.listener,
+			self.server_cfg.tcp_no_delay,
+			self.server_cfg.connection_runtime.clone(),
+			self.server_cfg.connection_middleware.clone(),
+			self.server_cfg.drain_notice,
+			stop_handle,
+			handoff_rx,
+			make_service,
+		);
+
+		match self.server_cfg.tokio_runtime.take() {
+			Some(rt) => rt.spawn(accept),
+			None => tokio::spawn(accept),
+		};
+
+		ServerHandle::new(stop_tx, subscription_stats, utf8_stats, handoff_tx)
+	}
 }
 
 impl<HttpMiddleware, RpcMiddleware, Body> Server<HttpMiddleware, RpcMiddleware>
@@ -114,63 +170,70 @@ where
 	///
 	/// This will run on the tokio runtime until the server is stopped or the `ServerHandle` is dropped.
 	pub fn start(mut self, methods: impl Into<Methods>) -> ServerHandle {
-		let methods = methods.into();
+		let methods = with_builtin_methods(methods.into(), &self.server_cfg);
 		let (stop_tx, stop_rx) = watch::channel(());
 
 		let stop_handle = StopHandle::new(stop_rx);
+		let subscription_stats = self.server_cfg.subscription_stats.clone();
+		let utf8_stats = self.server_cfg.utf8_stats.clone();
+		let (handoff_tx, handoff_rx) = mpsc::channel(1);
 
 		match self.server_cfg.tokio_runtime.take() {
-			Some(rt) => rt.spawn(self.start_inner(methods, stop_handle)),
-			None => tokio::spawn(self.start_inner(methods, stop_handle)),
+			Some(rt) => rt.spawn(self.start_inner(methods, stop_handle, handoff_rx)),
+			None => tokio::spawn(self.start_inner(methods, stop_handle, handoff_rx)),
 		};
 
-		ServerHandle::new(stop_tx)
+		ServerHandle::new(stop_tx, subscription_stats, utf8_stats, handoff_tx)
 	}
 
-	async fn start_inner(self, methods: Methods, stop_handle: StopHandle) {
-		let mut id: u32 = 0;
-		let connection_guard = ConnectionGuard::new(self.server_cfg.max_connections as usize);
-		let listener = self.listener;
-
-		let stopped = stop_handle.clone().shutdown();
-		tokio::pin!(stopped);
-
-		let (drop_on_completion, mut process_connection_awaiter) = mpsc::channel::<()>(1);
-
-		loop {
-			match try_accept_conn(&listener, stopped).await {
-				AcceptConnection::Established { socket, remote_addr, stop } => {
-					process_connection(ProcessConnection {
-						http_middleware: &self.http_middleware,
-						rpc_middleware: self.rpc_middleware.clone(),
-						remote_addr,
-						methods: methods.clone(),
-						stop_handle: stop_handle.clone(),
-						conn_id: id,
-						server_cfg: self.server_cfg.clone(),
-						conn_guard: &connection_guard,
-						socket,
-						drop_on_completion: drop_on_completion.clone(),
-					});
-					id = id.wrapping_add(1);
-					stopped = stop;
-				}
-				AcceptConnection::Err((e, stop)) => {
-					tracing::debug!(target: LOG_TARGET, "Error while awaiting a new connection: {:?}", e);
-					stopped = stop;
-				}
-				AcceptConnection::Shutdown => break,
-			}
+	async fn start_inner(self, methods: Methods, stop_handle: StopHandle, handoff: mpsc::Receiver<HandoffRequest>) {
+		let Server { listener, server_cfg, rpc_middleware, http_middleware } = self;
+		if let Some(audit_log) = &server_cfg.audit_log {
+			audit_log.start();
+		}
+		if let Some(long_polling) = &server_cfg.long_polling {
+			long_polling.start(stop_handle.clone());
 		}
+		let connection_guard = ConnectionGuard::new(server_cfg.max_connections as usize);
+		let tcp_no_delay = server_cfg.tcp_no_delay;
+		let connection_runtime = server_cfg.connection_runtime.clone();
+		let connection_middleware = server_cfg.connection_middleware.clone();
+		let drain_notice = server_cfg.drain_notice;
+		let conn_stop_handle = stop_handle.clone();
+
+		let make_service = move |conn_id, remote_addr| {
+			let tower_service = TowerServiceNoHttp {
+				inner: ServiceData {
+					server_cfg: server_cfg.clone(),
+					methods: methods.clone(),
+					stop_handle: conn_stop_handle.clone(),
+					conn_id,
+					conn_guard: connection_guard.clone(),
+					conn_extensions: ConnectionExtensions::new(),
+					remote_addr: Some(remote_addr),
+				},
+				rpc_middleware: rpc_middleware.clone(),
+				on_session_close: None,
+			};
 
-		// Drop the last Sender
-		drop(drop_on_completion);
+			let service = http_middleware.service(tower_service);
+			tower::util::MapRequest::new(service, move |mut request: HttpRequest| {
+				insert_peer_info(&mut request, conn_id, Some(remote_addr));
+				request
+			})
+		};
 
-		// Once this channel is closed it is safe to assume that all connections have been gracefully shutdown
-		while process_connection_awaiter.recv().await.is_some() {
-			// Generally, messages should not be sent across this channel,
-			// but we'll loop here to wait for `None` just to be on the safe side
-		}
+		accept_connections(
+			listener,
+			tcp_no_delay,
+			connection_runtime,
+			connection_middleware,
+			drain_notice,
+			stop_handle,
+			handoff,
+			make_service,
+		)
+		.await
 	}
 }
 
@@ -187,8 +250,36 @@ pub struct ServerConfig {
 	pub(crate) max_subscriptions_per_connection: u32,
 	/// Whether batch requests are supported by this server or not.
 	pub(crate) batch_requests_config: BatchRequestConfig,
+	/// How the calls of a batch request are executed.
+	pub(crate) batch_execution: BatchExecution,
+	/// The order of the responses in a batch response.
+	pub(crate) batch_response_order: BatchResponseOrder,
+	/// Whether a batch request is aborted once a call failed.
+	pub(crate) batch_fail_fast: BatchFailFast,
+	/// Which ids of the calls are accepted.
+	pub(crate) id_policy: IdPolicy,
+	/// How invalid UTF-8 in the messages is handled.
+	pub(crate) utf8_policy: Utf8Policy,
+	/// The codecs which the encoding of the messages is negotiated from.
+	pub(crate) codecs: Codecs,
+	/// How batch requests that exceed the max length are handled.
+	pub(crate) batch_overflow: BatchOverflow,
+	/// Whether JSON-RPC 1.0 requests are accepted.
+	pub(crate) json_rpc_v1_compat: bool,
+	/// Whether the responses of HTTP batch requests are streamed.
+	pub(crate) stream_batch_responses: bool,
+	/// Whether subscriptions are supported over HTTP with server-sent events.
+	pub(crate) enable_sse_subscriptions: bool,
+	/// The long-polled subscriptions if subscriptions are supported over HTTP with long polling.
+	pub(crate) long_polling: Option<LongPolling>,
 	/// Custom tokio runtime to run the server on.
 	pub(crate) tokio_runtime: Option<tokio::runtime::Handle>,
+	/// Custom tokio runtime to run the connections on.
+	pub(crate) connection_runtime: Option<tokio::runtime::Handle>,
+	/// Middleware wrapping the accepted connections.
+	pub(crate) connection_middleware: Option<ConnectionMiddleware>,
+	/// Custom tokio runtime to execute the method calls on.
+	pub(crate) method_runtime: Option<tokio::runtime::Handle>,
 	/// Enable HTTP.
 	pub(crate) enable_http: bool,
 	/// Enable WS.
@@ -197,10 +288,49 @@ pub struct ServerConfig {
 	pub(crate) message_buffer_capacity: u32,
 	/// Ping settings.
 	pub(crate) ping_config: Option<PingConfig>,
+	/// How the messages on WebSocket connections are flushed.
+	pub(crate) ws_flush_policy: WsFlushPolicy,
+	/// Limits of the frames on WebSocket connections.
+	pub(crate) ws_frame_config: WsFrameConfig,
+	/// Max number of serialization buffers that are pooled per WebSocket connection.
+	pub(crate) buffer_pool_size: u32,
 	/// ID provider.
 	pub(crate) id_provider: Arc<dyn IdProvider>,
 	/// `TCP_NODELAY` settings.
 	pub(crate) tcp_no_delay: bool,
+	/// Whether the listener is bound with `SO_REUSEPORT`.
+	#[cfg(all(unix, not(any(target_os = "solaris", target_os = "illumos"))))]
+	pub(crate) reuse_port: bool,
+	/// How long the connections are still served once the server is stopped.
+	pub(crate) drain_notice: Option<Duration>,
+	/// Default max execution time of a method call.
+	pub(crate) method_timeout: Option<Duration>,
+	/// Statistics of the subscriptions on the server.
+	pub(crate) subscription_stats: SubscriptionStats,
+	/// Statistics of the invalid UTF-8 in the messages.
+	pub(crate) utf8_stats: Utf8Stats,
+	/// Hook invoked when a connection is established.
+	pub(crate) on_connect: Option<OnConnect>,
+	/// Hook invoked when a connection is terminated.
+	pub(crate) on_disconnect: Option<OnDisconnect>,
+	/// Hook invoked with the response of each method call.
+	pub(crate) on_response: Option<OnResponse>,
+	/// Hook invoked when a method handler or a subscription task panics.
+	pub(crate) on_panic: Option<PanicHook>,
+	/// Hook invoked with the raw messages of the connections.
+	pub(crate) on_message: Option<OnMessage>,
+	/// Hook invoked before a subscribe call is dispatched.
+	pub(crate) on_subscribe: Option<OnSubscribe>,
+	/// Audit log of the completed method calls.
+	pub(crate) audit_log: Option<AuditLog>,
+	/// The OpenRPC document which is served at a HTTP path.
+	pub(crate) openrpc: Option<ServedOpenRpc>,
+	/// Register the `rpc.discover` method.
+	pub(crate) rpc_discover: Option<OpenRpcInfo>,
+	/// Register the `system_methods` method.
+	pub(crate) methods_introspection: bool,
+	/// Information served to plain HTTP `GET` requests.
+	pub(crate) server_info: Option<ServerInfo>,
 }
 
 #[derive(Debug, Clone)]
@@ -215,6 +345,28 @@ pub struct ServerConfigBuilder {
 	max_subscriptions_per_connection: u32,
 	/// Whether batch requests are supported by this server or not.
 	batch_requests_config: BatchRequestConfig,
+	/// How the calls of a batch request are executed.
+	batch_execution: BatchExecution,
+	/// The order of the responses in a batch response.
+	batch_response_order: BatchResponseOrder,
+	/// Whether a batch request is aborted once a call failed.
+	batch_fail_fast: BatchFailFast,
+	/// Which ids of the calls are accepted.
+	id_policy: IdPolicy,
+	/// How invalid UTF-8 in the messages is handled.
+	utf8_policy: Utf8Policy,
+	/// The codecs which the encoding of the messages is negotiated from.
+	codecs: Codecs,
+	/// How batch requests that exceed the max length are handled.
+	batch_overflow: BatchOverflow,
+	/// Whether JSON-RPC 1.0 requests are accepted.
+	json_rpc_v1_compat: bool,
+	/// Whether the responses of HTTP batch requests are streamed.
+	stream_batch_responses: bool,
+	/// Whether subscriptions are supported over HTTP with server-sent events.
+	enable_sse_subscriptions: bool,
+	/// Long polling settings.
+	long_polling: Option<LongPollingConfig>,
 	/// Enable HTTP.
 	enable_http: bool,
 	/// Enable WS.
@@ -223,8 +375,20 @@ pub struct ServerConfigBuilder {
 	message_buffer_capacity: u32,
 	/// Ping settings.
 	ping_config: Option<PingConfig>,
+	/// How the messages on WebSocket connections are flushed.
+	ws_flush_policy: WsFlushPolicy,
+	/// Limits of the frames on WebSocket connections.
+	ws_frame_config: WsFrameConfig,
+	/// Max number of serialization buffers that are pooled per WebSocket connection.
+	buffer_pool_size: u32,
+	/// How long the connections are still served once the server is stopped.
+	drain_notice: Option<Duration>,
 	/// ID provider.
 	id_provider: Arc<dyn IdProvider>,
+	/// Default max execution time of a method call.
+	method_timeout: Option<Duration>,
+	/// Custom tokio runtime to execute the method calls on.
+	method_runtime: Option<tokio::runtime::Handle>,
 }
 
 /// Builder for [`TowerService`].
@@ -240,6 +404,112 @@ pub struct TowerServiceBuilder<RpcMiddleware, HttpMiddleware> {
 	pub(crate) conn_id: Arc<AtomicU32>,
 	/// Connection guard.
 	pub(crate) conn_guard: ConnectionGuard,
+	/// The address of the peer.
+	pub(crate) remote_addr: Option<SocketAddr>,
+	/// The methods with the built-in methods, shared by the clones of the builder.
+	pub(crate) builtin_methods: BuiltinMethodsCache,
+}
+
+/// The methods with the built-in methods of the last [`TowerServiceBuilder::build`].
+///
+/// The built-in methods are only built once if the same methods are served on every connection.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct BuiltinMethodsCache(Arc<Mutex<Option<(Methods, Methods)>>>);
+
+impl BuiltinMethodsCache {
+	pub(crate) fn get_or_build(&self, mut methods: Methods, server_cfg: &ServerConfig) -> Methods {
+		let mut cache = self.0.lock().expect("poisoned lock");
+
+		let mut built = match cache.as_ref() {
+			Some((input, built)) if input.ptr_eq(&methods) => built.clone(),
+			_ => {
+				let built = with_builtin_methods(methods.clone(), server_cfg);
+				*cache = Some((methods.clone(), built.clone()));
+				built
+			}
+		};
+
+		// The extensions are not cached because they are not compared.
+		*built.extensions_mut() = std::mem::take(methods.extensions_mut());
+		built
+	}
+}
+
+/// The OpenRPC document which is served at a HTTP path, see [`Builder::serve_openrpc`].
+///
+/// The document is only generated once if the same methods are served on every connection.
+#[derive(Debug, Clone)]
+pub(crate) struct ServedOpenRpc {
+	path: String,
+	info: OpenRpcInfo,
+	/// The document of the methods it was last generated for.
+	cache: Arc<Mutex<Option<CachedOpenRpc>>>,
+}
+
+/// The methods and their OpenRPC document.
+type CachedOpenRpc = (Methods, Arc<str>);
+
+impl ServedOpenRpc {
+	fn new(path: String, info: OpenRpcInfo) -> Self {
+		Self { path, info, cache: Arc::default() }
+	}
+
+	/// Get the document of `methods`.
+	pub(crate) fn document(&self, methods: &Methods) -> Arc<str> {
+		let mut cache = self.cache.lock().expect("poisoned lock");
+
+		match cache.as_ref() {
+			Some((cached, doc)) if cached.ptr_eq(methods) => doc.clone(),
+			_ => {
+				let doc: Arc<str> = methods.openrpc(&self.info).to_string().into();
+				*cache = Some((methods.clone(), doc.clone()));
+				doc
+			}
+		}
+	}
+}
+
+/// Information about the server which is served to plain HTTP `GET` requests,
+/// see [`Builder::serve_server_info`].
+#[derive(Debug, Clone)]
+pub struct ServerInfo {
+	name: String,
+	version: String,
+}
+
+impl ServerInfo {
+	/// Create the server information with the name and version of the server.
+	pub fn new(name: impl Into<String>, version: impl Into<String>) -> Self {
+		Self { name: name.into(), version: version.into() }
+	}
+
+	/// The JSON document with the server information, the supported transports and the limits of `cfg`.
+	fn to_document(&self, cfg: &ServerConfig) -> String {
+		let transports: Vec<_> = [(cfg.enable_http, "http"), (cfg.enable_ws, "ws")]
+			.into_iter()
+			.filter_map(|(e, t)| e.then_some(t))
+			.collect();
+
+		let max_batch_len = match cfg.batch_requests_config {
+			BatchRequestConfig::Limit(limit) => Some(limit),
+			BatchRequestConfig::Disabled | BatchRequestConfig::Unlimited => None,
+		};
+
+		serde_json::json!({
+			"name": self.name,
+			"version": self.version,
+			"transports": transports,
+			"limits": {
+				"maxRequestBodySize": cfg.max_request_body_size,
+				"maxResponseBodySize": cfg.max_response_body_size,
+				"maxConnections": cfg.max_connections,
+				"maxSubscriptionsPerConnection": cfg.max_subscriptions_per_connection,
+				"batchRequests": !matches!(cfg.batch_requests_config, BatchRequestConfig::Disabled),
+				"maxBatchLen": max_batch_len,
+			},
+		})
+		.to_string()
+	}
 }
 
 /// Configuration for batch request handling.
@@ -253,6 +523,444 @@ pub enum BatchRequestConfig {
 	Unlimited,
 }
 
+fn batch_config_from_limits(limits: &Limits) -> BatchRequestConfig {
+	match limits.max_batch_len {
+		Some(len) => BatchRequestConfig::Limit(len),
+		None => BatchRequestConfig::Unlimited,
+	}
+}
+
+/// Batch request policy of a connection which is decided by a middleware.
+///
+/// An HTTP middleware may insert the policy into the extensions of an HTTP request, for instance once
+/// the user has been authenticated, and it then overrides the batch request configuration of the server
+/// for the HTTP request or for the WebSocket connection that is established by the request.
+///
+/// # Examples
+///
+/// ```rust
+/// use jsonrpsee_server::{BatchPolicy, BatchRequestConfig, HttpRequest, ServerBuilder};
+///
+/// // Authorized users may send batches of up to 1000 calls and everyone else batches of up to 10 calls.
+/// let http_middleware = tower::ServiceBuilder::new().map_request(|mut req: HttpRequest| {
+///     let limit = if req.headers().contains_key("authorization") { 1000 } else { 10 };
+///     req.extensions_mut().insert(BatchPolicy::new(BatchRequestConfig::Limit(limit)));
+///     req
+/// });
+///
+/// let builder = ServerBuilder::default().set_http_middleware(http_middleware);
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct BatchPolicy {
+	config: BatchRequestConfig,
+	max_response_size: Option<u32>,
+}
+
+impl BatchPolicy {
+	/// Create a new batch policy which overrides [`Builder::set_batch_request_config`].
+	pub fn new(config: BatchRequestConfig) -> Self {
+		Self { config, max_response_size: None }
+	}
+
+	/// Limit the size of batch responses in bytes instead of [`Builder::max_response_body_size`].
+	pub fn max_response_size(mut self, size: u32) -> Self {
+		self.max_response_size = Some(size);
+		self
+	}
+}
+
+/// Policy which decides which ids of the calls are accepted, see [`Builder::set_id_policy`].
+///
+/// String ids are always accepted and echoed, which includes the UUIDs of clients that need ids which
+/// are unique across processes.
+///
+/// The ids which are not strings, unsigned integers or `null` are classified as:
+/// - floats, numbers with a fraction or exponent whose value is an unsigned integer such as `1.0` or `1e3`.
+///   An accepted float is passed to the method as the unsigned integer.
+/// - fractional numbers, all other numbers such as `1.5` or `-1`. An accepted fractional number is passed
+///   to the method as a string and is always echoed as it was sent.
+/// - other JSON values, such as objects, arrays or booleans. An accepted value is passed to the method
+///   as an [`Id::Other`].
+///
+/// The original formatting of an id isn't restored in the response of a subscription over WebSocket.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct IdPolicy {
+	floats: bool,
+	fractional: bool,
+	null: bool,
+	other: bool,
+	preserve_format: bool,
+}
+
+impl Default for IdPolicy {
+	fn default() -> Self {
+		Self { floats: false, fractional: false, null: true, other: false, preserve_format: false }
+	}
+}
+
+impl IdPolicy {
+	/// Accept ids which are floats, such as `1.0`.
+	pub fn accept_floats(mut self, accept: bool) -> Self {
+		self.floats = accept;
+		self
+	}
+
+	/// Accept ids which are fractional numbers, such as `1.5` or `-1`.
+	pub fn accept_fractional(mut self, accept: bool) -> Self {
+		self.fractional = accept;
+		self
+	}
+
+	/// Accept calls whose id is `null`.
+	pub fn accept_null(mut self, accept: bool) -> Self {
+		self.null = accept;
+		self
+	}
+
+	/// Accept ids which are other JSON values such as objects, arrays or booleans, which are
+	/// passed to the method as [`Id::Other`] and echoed in the response.
+	pub fn accept_other(mut self, accept: bool) -> Self {
+		self.other = accept;
+		self
+	}
+
+	/// Echo the ids exactly as they were sent, for instance `1.0` instead of `1` or
+	/// `"\u0041"` instead of `"A"`.
+	pub fn preserve_format(mut self, preserve: bool) -> Self {
+		self.preserve_format = preserve;
+		self
+	}
+
+	/// Parse a call whose id is accepted by the policy.
+	///
+	/// Returns the request and the raw id which must be echoed in the response if it differs from the
+	/// serialized id of the request.
+	pub(crate) fn parse_call<'a>(
+		&self,
+		call: &'a [u8],
+		extensions: Extensions,
+	) -> Result<(Request<'a>, Option<&'a str>), InvalidId> {
+		#[derive(serde::Deserialize)]
+		struct RawCall<'a> {
+			jsonrpc: TwoPointZero,
+			#[serde(borrow)]
+			id: &'a JsonRawValue,
+			#[serde(borrow)]
+			method: Cow<'a, str>,
+			#[serde(borrow)]
+			params: Option<Cow<'a, JsonRawValue>>,
+		}
+
+		let (mut req, raw_id) = match serde_json::from_slice::<Request>(call) {
+			Ok(req) if self.preserve_format => {
+				let raw_id = serde_json::from_slice::<RawCall>(call).ok().map(|c| c.id.get());
+				(req, raw_id)
+			}
+			Ok(req) => (req, None),
+			// The call may only be invalid because of its id.
+			Err(_) => {
+				let call = serde_json::from_slice::<RawCall>(call).map_err(|_| InvalidId::NotACall)?;
+				let raw_id = call.id.get();
+				let id = if self.other && !raw_id.starts_with(|c: char| c == '-' || c.is_ascii_digit()) {
+					Id::Other(RawId::new(Cow::Borrowed(call.id)))
+				} else {
+					self.parse_number(raw_id)?
+				};
+				let echo = self.preserve_format || matches!(id, Id::Str(_));
+				let req = Request {
+					jsonrpc: call.jsonrpc,
+					id,
+					method: call.method,
+					params: call.params,
+					extensions: Extensions::new(),
+				};
+				(req, echo.then_some(raw_id))
+			}
+		};
+
+		if !self.null && req.id == Id::Null {
+			return Err(InvalidId::Rejected("the id must not be null"));
+		}
+
+		req.extensions = extensions;
+		let raw_id = raw_id.filter(|raw| serde_json::to_string(&req.id).map_or(true, |id| id != *raw));
+
+		Ok((req, raw_id))
+	}
+
+	/// Parse an id which is a number but not an unsigned integer.
+	fn parse_number<'a>(&self, raw: &'a str) -> Result<Id<'a>, InvalidId> {
+		let num: f64 = raw.parse().map_err(|_| InvalidId::NotACall)?;
+		let is_float = raw.contains(['.', 'e', 'E']);
+
+		if is_float && num.fract() == 0.0 && num >= 0.0 && num < u64::MAX as f64 {
+			if !self.floats {
+				return Err(InvalidId::Rejected("the id must not be a float"));
+			}
+			Ok(Id::Number(num as u64))
+		} else {
+			if !self.fractional {
+				return Err(InvalidId::Rejected("the id must be a string or an unsigned integer"));
+			}
+			Ok(Id::Str(Cow::Borrowed(raw)))
+		}
+	}
+}
+
+/// Why the id of a call couldn't be parsed.
+pub(crate) enum InvalidId {
+	/// The call isn't a valid call at all.
+	NotACall,
+	/// The id was rejected by the [`IdPolicy`] for the given reason.
+	Rejected(&'static str),
+}
+
+impl InvalidId {
+	/// The response to a call whose id was rejected.
+	fn into_response(self) -> Option<MethodResponse> {
+		match self {
+			Self::NotACall => None,
+			Self::Rejected(reason) => {
+				let code = ErrorCode::InvalidRequest;
+				Some(MethodResponse::error(Id::Null, ErrorObject::owned(code.code(), code.message(), Some(reason))))
+			}
+		}
+	}
+}
+
+/// How invalid UTF-8 in the messages is handled, see [`Builder::set_utf8_policy`].
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum Utf8Policy {
+	/// The message is rejected with a parse error.
+	#[default]
+	Strict,
+	/// The invalid sequences are replaced with `U+FFFD REPLACEMENT CHARACTER` and the message is processed.
+	Lossy,
+}
+
+impl Utf8Policy {
+	/// Validate the UTF-8 of `msg` which is returned as it should be processed.
+	///
+	/// Fails with the parse error if the message is rejected.
+	fn validate<'a>(&self, msg: &'a [u8], stats: Option<&Utf8Stats>) -> Result<Cow<'a, [u8]>, MethodResponse> {
+		match self {
+			Self::Strict => match std::str::from_utf8(msg) {
+				Ok(_) => Ok(Cow::Borrowed(msg)),
+				Err(e) => {
+					if let Some(stats) = stats {
+						stats.0.rejected.fetch_add(1, Ordering::Relaxed);
+					}
+					let code = ErrorCode::ParseError;
+					let data = format!("invalid UTF-8 at byte {}", e.valid_up_to());
+					Err(MethodResponse::error(Id::Null, ErrorObject::owned(code.code(), code.message(), Some(data))))
+				}
+			},
+			Self::Lossy => match String::from_utf8_lossy(msg) {
+				Cow::Borrowed(msg) => Ok(Cow::Borrowed(msg.as_bytes())),
+				Cow::Owned(msg) => {
+					if let Some(stats) = stats {
+						stats.0.replaced.fetch_add(1, Ordering::Relaxed);
+					}
+					Ok(Cow::Owned(msg.into_bytes()))
+				}
+			},
+		}
+	}
+}
+
+/// Statistics of the invalid UTF-8 in the messages on a server, see [`ServerHandle::utf8_stats`].
+#[derive(Debug, Default, Clone)]
+pub struct Utf8Stats(Arc<Utf8Counters>);
+
+#[derive(Debug, Default)]
+struct Utf8Counters {
+	rejected: AtomicU64,
+	replaced: AtomicU64,
+}
+
+impl Utf8Stats {
+	/// Number of messages that were rejected by [`Utf8Policy::Strict`].
+	pub fn rejected(&self) -> u64 {
+		self.0.rejected.load(Ordering::Relaxed)
+	}
+
+	/// Number of messages whose invalid sequences were replaced by [`Utf8Policy::Lossy`].
+	pub fn replaced(&self) -> u64 {
+		self.0.replaced.load(Ordering::Relaxed)
+	}
+}
+
+/// Configuration of how the calls of a batch request are executed.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum BatchExecution {
+	/// The calls are executed one after another.
+	#[default]
+	Sequential,
+	/// The calls are executed concurrently but at most `limit` calls of the same batch are executed at once.
+	Concurrent(u32),
+}
+
+impl BatchExecution {
+	/// The max number of calls of the same batch that are executed at once.
+	fn max_concurrency(&self) -> usize {
+		match self {
+			Self::Sequential => 1,
+			Self::Concurrent(limit) => *limit as usize,
+		}
+	}
+}
+
+/// Configuration of when the messages on a WebSocket connection are flushed to the socket.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum WsFlushPolicy {
+	/// Each message is flushed right away, which has the lowest latency.
+	#[default]
+	Immediate,
+	/// The messages are written to the buffer of the connection and flushed once `max_messages` are
+	/// buffered or `window` has passed since the first one, whichever comes first.
+	///
+	/// This saves syscalls when many small messages such as notifications are sent at the cost of
+	/// delaying the messages by at most `window`.
+	Coalesce {
+		/// Max number of messages per flush.
+		max_messages: u32,
+		/// Max time a message is delayed.
+		window: Duration,
+	},
+}
+
+/// Limits of the frames on WebSocket connections.
+///
+/// Default: frames of up to 256 MB, any number of frames per message and the messages are sent in a single frame.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct WsFrameConfig {
+	/// Max size of the payload of an incoming frame.
+	pub(crate) max_frame_size: Option<u32>,
+	/// Max number of frames of an incoming message.
+	pub(crate) max_frames_per_message: Option<u32>,
+	/// Max size of the payload of an outgoing frame.
+	pub(crate) fragmentation_threshold: Option<u32>,
+}
+
+impl WsFrameConfig {
+	/// Create a new WsFrameConfig.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Configure the max size in bytes of the payload of a frame that is received.
+	pub fn max_frame_size(mut self, size: u32) -> Self {
+		self.max_frame_size = Some(size);
+		self
+	}
+
+	/// Configure the max number of frames a received message may be fragmented into.
+	///
+	/// Interleaved control frames such as pings aren't counted. A connection that receives
+	/// a message of more frames is closed with the status code 1002 (protocol error).
+	///
+	/// # Panics
+	///
+	/// This method panics if `max` == 0.
+	pub fn max_frames_per_message(mut self, max: u32) -> Self {
+		assert!(max > 0);
+		self.max_frames_per_message = Some(max);
+		self
+	}
+
+	/// Configure the size in bytes above which the messages that are sent are fragmented
+	/// into frames of at most `size` bytes, e.g. for proxies that limit the size of frames.
+	///
+	/// # Panics
+	///
+	/// This method panics if `size` == 0.
+	pub fn fragmentation_threshold(mut self, size: u32) -> Self {
+		assert!(size > 0);
+		self.fragmentation_threshold = Some(size);
+		self
+	}
+}
+
+/// Configuration of the order of the responses in a batch response.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum BatchResponseOrder {
+	/// The responses are in the same order as the calls of the batch.
+	#[default]
+	Request,
+	/// The responses are in the order in which the calls completed.
+	Completion,
+}
+
+/// How a batch request with more calls than the [`BatchRequestConfig::Limit`] is handled,
+/// see [`Builder::set_batch_overflow`].
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum BatchOverflow {
+	/// The entire batch request is rejected with a single error.
+	#[default]
+	Reject,
+	/// The first calls up to the limit are processed and the remaining calls are answered
+	/// with a "batch request too large" error.
+	ProcessFirst,
+}
+
+/// Header by which a WebSocket client advertises the max size in bytes of the responses it accepts
+/// during the handshake.
+///
+/// The server then uses the lower of its own limit and the limit of the client for the connection.
+pub const MAX_RESPONSE_SIZE_HEADER: &str = "x-jsonrpc-max-response-size";
+
+/// Header by which a batch request opts in to [`BatchFailFast::OptIn`].
+///
+/// For WebSocket connections the header of the upgrade request applies to all batches of the connection.
+pub const BATCH_FAIL_FAST_HEADER: &str = "x-jsonrpc-batch-fail-fast";
+
+/// Configuration of whether the remaining calls of a batch request are processed once a call failed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum BatchFailFast {
+	/// All calls of a batch are processed.
+	#[default]
+	Disabled,
+	/// Every batch is aborted once a call fails with an error of the class.
+	Always(BatchErrorClass),
+	/// Batches that are sent with the [`BATCH_FAIL_FAST_HEADER`] header set to `true`
+	/// are aborted once a call fails with an error of the class.
+	OptIn(BatchErrorClass),
+}
+
+impl BatchFailFast {
+	/// Returns the class of errors that aborts a batch sent with the given headers, if any.
+	pub(crate) fn resolve(&self, headers: &hyper::HeaderMap) -> Option<&BatchErrorClass> {
+		match self {
+			Self::Disabled => None,
+			Self::Always(errors) => Some(errors),
+			Self::OptIn(errors) => headers
+				.get(BATCH_FAIL_FAST_HEADER)
+				.and_then(|val| val.to_str().ok())
+				.is_some_and(|val| val.eq_ignore_ascii_case("true"))
+				.then_some(errors),
+		}
+	}
+}
+
+/// Class of errors that aborts a fail-fast batch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BatchErrorClass {
+	/// Any error.
+	Any,
+	/// Errors with a code in the range.
+	Codes(RangeInclusive<i32>),
+}
+
+impl BatchErrorClass {
+	/// Returns whether the error code belongs to the class.
+	fn contains(&self, code: i32) -> bool {
+		match self {
+			Self::Any => true,
+			Self::Codes(codes) => codes.contains(&code),
+		}
+	}
+}
+
 /// Connection related state that is needed
 /// to execute JSON-RPC calls.
 #[derive(Debug, Clone)]
@@ -272,6 +980,23 @@ impl ConnectionState {
 	}
 }
 
+/// Information about the peer of a connection.
+///
+/// This is inserted in the [`Extensions`] of every JSON-RPC call and of the HTTP requests
+/// that are passed to the HTTP middleware, and may be used to implement IP allow-lists or audit logs.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PeerInfo {
+	/// The connection ID.
+	pub conn_id: ConnectionId,
+	/// The address of the peer.
+	///
+	/// This is `None` if the service was built by [`TowerServiceBuilder`]
+	/// without [`TowerServiceBuilder::remote_addr`].
+	pub remote_addr: Option<SocketAddr>,
+	/// The transport of the connection.
+	pub transport: TransportKind,
+}
+
 /// Configuration for WebSocket ping/pong mechanism and it may be used to disconnect
 /// an inactive connection.
 ///
@@ -283,7 +1008,9 @@ impl ConnectionState {
 /// WebSocket ping takes or it might be missed and may end up
 /// terminating the connection.
 ///
-/// Default: ping_interval: 30 seconds, max failures: 1 and inactive limit: 40 seconds.
+/// A connection that is closed because of inactivity is reported with [`crate::DisconnectReason::Unresponsive`].
+///
+/// Default: ping_interval: 30 seconds, max failures: 1, inactive limit: 40 seconds and no limit of missed pongs.
 #[derive(Debug, Copy, Clone)]
 pub struct PingConfig {
 	/// Period which the server pings the connected client.
@@ -292,11 +1019,18 @@ pub struct PingConfig {
 	pub(crate) inactive_limit: Duration,
 	/// Max failures.
 	pub(crate) max_failures: usize,
+	/// Max pongs that are missed in a row.
+	pub(crate) max_missed_pongs: Option<usize>,
 }
 
 impl Default for PingConfig {
 	fn default() -> Self {
-		Self { ping_interval: Duration::from_secs(30), max_failures: 1, inactive_limit: Duration::from_secs(40) }
+		Self {
+			ping_interval: Duration::from_secs(30),
+			max_failures: 1,
+			inactive_limit: Duration::from_secs(40),
+			max_missed_pongs: None,
+		}
 	}
 }
 
@@ -333,6 +1067,20 @@ impl PingConfig {
 		self.max_failures = max;
 		self
 	}
+
+	/// Configure how many pongs in a row the remote peer is allowed to miss until the connection is closed.
+	///
+	/// In contrast to [`PingConfig::max_failures`] the count is reset once a pong is received
+	/// and it replaces [`PingConfig::max_failures`] when configured.
+	///
+	/// # Panics
+	///
+	/// This method panics if `max` == 0.
+	pub fn max_missed_pongs(mut self, max: usize) -> Self {
+		assert!(max > 0);
+		self.max_missed_pongs = Some(max);
+		self
+	}
 }
 
 impl Default for ServerConfig {
@@ -343,13 +1091,47 @@ impl Default for ServerConfig {
 			max_connections: MAX_CONNECTIONS,
 			max_subscriptions_per_connection: 1024,
 			batch_requests_config: BatchRequestConfig::Unlimited,
+			batch_execution: BatchExecution::Sequential,
+			batch_response_order: BatchResponseOrder::Request,
+			batch_fail_fast: BatchFailFast::Disabled,
+			id_policy: IdPolicy::default(),
+			utf8_policy: Utf8Policy::default(),
+			codecs: Codecs::default(),
+			batch_overflow: BatchOverflow::default(),
+			json_rpc_v1_compat: false,
+			stream_batch_responses: false,
+			enable_sse_subscriptions: false,
+			long_polling: None,
 			tokio_runtime: None,
+			connection_runtime: None,
+			connection_middleware: None,
+			method_runtime: None,
 			enable_http: true,
 			enable_ws: true,
 			message_buffer_capacity: 1024,
 			ping_config: None,
+			ws_flush_policy: WsFlushPolicy::Immediate,
+			ws_frame_config: WsFrameConfig::default(),
+			buffer_pool_size: 0,
 			id_provider: Arc::new(RandomIntegerIdProvider),
 			tcp_no_delay: true,
+			#[cfg(all(unix, not(any(target_os = "solaris", target_os = "illumos"))))]
+			reuse_port: false,
+			drain_notice: None,
+			method_timeout: None,
+			subscription_stats: SubscriptionStats::new(),
+			utf8_stats: Utf8Stats::default(),
+			on_connect: None,
+			on_disconnect: None,
+			on_response: None,
+			on_panic: None,
+			on_message: None,
+			on_subscribe: None,
+			audit_log: None,
+			openrpc: None,
+			rpc_discover: None,
+			methods_introspection: false,
+			server_info: None,
 		}
 	}
 }
@@ -371,11 +1153,28 @@ impl Default for ServerConfigBuilder {
 			max_connections: this.max_connections,
 			max_subscriptions_per_connection: this.max_subscriptions_per_connection,
 			batch_requests_config: this.batch_requests_config,
+			batch_execution: this.batch_execution,
+			batch_response_order: this.batch_response_order,
+			batch_fail_fast: this.batch_fail_fast,
+			id_policy: this.id_policy,
+			utf8_policy: this.utf8_policy,
+			codecs: this.codecs,
+			batch_overflow: this.batch_overflow,
+			json_rpc_v1_compat: this.json_rpc_v1_compat,
+			stream_batch_responses: this.stream_batch_responses,
+			enable_sse_subscriptions: this.enable_sse_subscriptions,
+			long_polling: None,
 			enable_http: this.enable_http,
 			enable_ws: this.enable_ws,
 			message_buffer_capacity: this.message_buffer_capacity,
 			ping_config: this.ping_config,
+			ws_flush_policy: this.ws_flush_policy,
+			ws_frame_config: this.ws_frame_config,
+			buffer_pool_size: this.buffer_pool_size,
+			drain_notice: this.drain_notice,
 			id_provider: this.id_provider,
+			method_timeout: this.method_timeout,
+			method_runtime: this.method_runtime,
 		}
 	}
 }
@@ -386,6 +1185,15 @@ impl ServerConfigBuilder {
 		Self::default()
 	}
 
+	/// See [`Builder::with_limits`] for documentation.
+	pub fn with_limits(mut self, limits: Limits) -> Self {
+		self.max_request_body_size = limits.max_request_size;
+		self.max_response_body_size = limits.max_response_size;
+		self.batch_requests_config = batch_config_from_limits(&limits);
+		self.method_timeout = Some(limits.timeout);
+		self
+	}
+
 	/// See [`Builder::max_request_body_size`] for documentation.
 	pub fn max_request_body_size(mut self, size: u32) -> Self {
 		self.max_request_body_size = size;
@@ -410,58 +1218,161 @@ impl ServerConfigBuilder {
 		self
 	}
 
-	/// See [`Builder::max_subscriptions_per_connection`] for documentation.
-	pub fn max_subscriptions_per_connection(mut self, max: u32) -> Self {
-		self.max_subscriptions_per_connection = max;
+	/// See [`Builder::set_batch_execution`] for documentation.
+	pub fn set_batch_execution(mut self, execution: BatchExecution) -> Self {
+		assert!(execution.max_concurrency() > 0, "The batch concurrency limit must be non-zero");
+		self.batch_execution = execution;
 		self
 	}
 
-	/// See [`Builder::http_only`] for documentation.
-	pub fn http_only(mut self) -> Self {
-		self.enable_http = true;
-		self.enable_ws = false;
+	/// See [`Builder::set_batch_response_order`] for documentation.
+	pub fn set_batch_response_order(mut self, order: BatchResponseOrder) -> Self {
+		self.batch_response_order = order;
 		self
 	}
 
-	/// See [`Builder::ws_only`] for documentation.
-	pub fn ws_only(mut self) -> Self {
-		self.enable_http = false;
-		self.enable_ws = true;
+	/// See [`Builder::set_batch_fail_fast`] for documentation.
+	pub fn set_batch_fail_fast(mut self, fail_fast: BatchFailFast) -> Self {
+		self.batch_fail_fast = fail_fast;
 		self
 	}
 
-	/// See [`Builder::set_message_buffer_capacity`] for documentation.
-	pub fn set_message_buffer_capacity(mut self, c: u32) -> Self {
-		self.message_buffer_capacity = c;
+	/// See [`Builder::set_id_policy`] for documentation.
+	pub fn set_id_policy(mut self, policy: IdPolicy) -> Self {
+		self.id_policy = policy;
 		self
 	}
 
-	/// See [`Builder::enable_ws_ping`] for documentation.
-	pub fn enable_ws_ping(mut self, config: PingConfig) -> Self {
-		self.ping_config = Some(config);
+	/// See [`Builder::set_utf8_policy`] for documentation.
+	pub fn set_utf8_policy(mut self, policy: Utf8Policy) -> Self {
+		self.utf8_policy = policy;
 		self
 	}
 
-	/// See [`Builder::disable_ws_ping`] for documentation.
-	pub fn disable_ws_ping(mut self) -> Self {
-		self.ping_config = None;
+	/// See [`Builder::set_codecs`] for documentation.
+	pub fn set_codecs(mut self, codecs: Codecs) -> Self {
+		self.codecs = codecs;
 		self
 	}
 
-	/// See [`Builder::set_id_provider`] for documentation.
-	pub fn set_id_provider<I: IdProvider + 'static>(mut self, id_provider: I) -> Self {
-		self.id_provider = Arc::new(id_provider);
+	/// See [`Builder::set_batch_overflow`] for documentation.
+	pub fn set_batch_overflow(mut self, overflow: BatchOverflow) -> Self {
+		self.batch_overflow = overflow;
 		self
 	}
-}
-
-/// Builder to configure and create a JSON-RPC server
-#[derive(Debug)]
-pub struct Builder<HttpMiddleware, RpcMiddleware> {
-	server_cfg: ServerConfig,
-	rpc_middleware: RpcServiceBuilder<RpcMiddleware>,
-	http_middleware: tower::ServiceBuilder<HttpMiddleware>,
-}
+
+	/// See [`Builder::set_json_rpc_v1_compat`] for documentation.
+	pub fn set_json_rpc_v1_compat(mut self, enabled: bool) -> Self {
+		self.json_rpc_v1_compat = enabled;
+		self
+	}
+
+	/// See [`Builder::enable_batch_response_streaming`] for documentation.
+	pub fn enable_batch_response_streaming(mut self) -> Self {
+		self.stream_batch_responses = true;
+		self
+	}
+
+	/// See [`Builder::enable_sse_subscriptions`] for documentation.
+	pub fn enable_sse_subscriptions(mut self) -> Self {
+		self.enable_sse_subscriptions = true;
+		self
+	}
+
+	/// See [`Builder::enable_long_polling`] for documentation.
+	pub fn enable_long_polling(mut self, config: LongPollingConfig) -> Self {
+		self.long_polling = Some(config);
+		self
+	}
+
+	/// See [`Builder::max_subscriptions_per_connection`] for documentation.
+	pub fn max_subscriptions_per_connection(mut self, max: u32) -> Self {
+		self.max_subscriptions_per_connection = max;
+		self
+	}
+
+	/// See [`Builder::http_only`] for documentation.
+	pub fn http_only(mut self) -> Self {
+		self.enable_http = true;
+		self.enable_ws = false;
+		self
+	}
+
+	/// See [`Builder::ws_only`] for documentation.
+	pub fn ws_only(mut self) -> Self {
+		self.enable_http = false;
+		self.enable_ws = true;
+		self
+	}
+
+	/// See [`Builder::set_message_buffer_capacity`] for documentation.
+	pub fn set_message_buffer_capacity(mut self, c: u32) -> Self {
+		self.message_buffer_capacity = c;
+		self
+	}
+
+	/// See [`Builder::enable_ws_ping`] for documentation.
+	pub fn enable_ws_ping(mut self, config: PingConfig) -> Self {
+		self.ping_config = Some(config);
+		self
+	}
+
+	/// See [`Builder::disable_ws_ping`] for documentation.
+	pub fn disable_ws_ping(mut self) -> Self {
+		self.ping_config = None;
+		self
+	}
+
+	/// See [`Builder::set_ws_flush_policy`] for documentation.
+	pub fn set_ws_flush_policy(mut self, policy: WsFlushPolicy) -> Self {
+		self.ws_flush_policy = policy;
+		self
+	}
+
+	/// See [`Builder::set_ws_frame_config`] for documentation.
+	pub fn set_ws_frame_config(mut self, config: WsFrameConfig) -> Self {
+		self.ws_frame_config = config;
+		self
+	}
+
+	/// See [`Builder::set_buffer_pool_size`] for documentation.
+	pub fn set_buffer_pool_size(mut self, size: u32) -> Self {
+		self.buffer_pool_size = size;
+		self
+	}
+
+	/// See [`Builder::set_drain_notice`] for documentation.
+	pub fn set_drain_notice(mut self, deadline: Duration) -> Self {
+		self.drain_notice = Some(deadline);
+		self
+	}
+
+	/// See [`Builder::set_id_provider`] for documentation.
+	pub fn set_id_provider<I: IdProvider + 'static>(mut self, id_provider: I) -> Self {
+		self.id_provider = Arc::new(id_provider);
+		self
+	}
+
+	/// See [`Builder::set_method_timeout`] for documentation.
+	pub fn set_method_timeout(mut self, timeout: Duration) -> Self {
+		self.method_timeout = Some(timeout);
+		self
+	}
+
+	/// See [`Builder::custom_method_runtime`] for documentation.
+	pub fn custom_method_runtime(mut self, rt: tokio::runtime::Handle) -> Self {
+		self.method_runtime = Some(rt);
+		self
+	}
+}
+
+/// Builder to configure and create a JSON-RPC server
+#[derive(Debug)]
+pub struct Builder<HttpMiddleware, RpcMiddleware> {
+	server_cfg: ServerConfig,
+	rpc_middleware: RpcServiceBuilder<RpcMiddleware>,
+	http_middleware: tower::ServiceBuilder<HttpMiddleware>,
+}
 
 impl Default for Builder<Identity, Identity> {
 	fn default() -> Self {
@@ -482,21 +1393,40 @@ impl Builder<Identity, Identity> {
 
 impl<RpcMiddleware, HttpMiddleware> TowerServiceBuilder<RpcMiddleware, HttpMiddleware> {
 	/// Build a tower service.
+	///
+	/// The built-in methods, such as `rpc.discover`, are built once and shared by the services
+	/// which are built from clones of this builder with clones of the same `methods`.
 	pub fn build(
 		self,
 		methods: impl Into<Methods>,
 		stop_handle: StopHandle,
+	) -> TowerService<RpcMiddleware, HttpMiddleware> {
+		let methods = self.builtin_methods.get_or_build(methods.into(), &self.server_cfg);
+		self.build_with_builtin_methods(methods, stop_handle)
+	}
+
+	/// Similar to [`TowerServiceBuilder::build`] but the built-in methods are already registered.
+	pub(crate) fn build_with_builtin_methods(
+		self,
+		methods: Methods,
+		stop_handle: StopHandle,
 	) -> TowerService<RpcMiddleware, HttpMiddleware> {
 		let conn_id = self.conn_id.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
 
+		if let Some(long_polling) = &self.server_cfg.long_polling {
+			long_polling.start(stop_handle.clone());
+		}
+
 		let rpc_middleware = TowerServiceNoHttp {
 			rpc_middleware: self.rpc_middleware,
 			inner: ServiceData {
-				methods: methods.into(),
+				methods,
 				stop_handle,
 				conn_id,
 				conn_guard: self.conn_guard,
 				server_cfg: self.server_cfg,
+				conn_extensions: ConnectionExtensions::new(),
+				remote_addr: self.remote_addr,
 			},
 			on_session_close: None,
 		};
@@ -512,6 +1442,14 @@ impl<RpcMiddleware, HttpMiddleware> TowerServiceBuilder<RpcMiddleware, HttpMiddl
 		self
 	}
 
+	/// Configure the address of the peer that the built services are serving.
+	///
+	/// It is exposed to the method calls via [`PeerInfo`] and to the connection hooks.
+	pub fn remote_addr(mut self, addr: SocketAddr) -> Self {
+		self.remote_addr = Some(addr);
+		self
+	}
+
 	/// Configure the max allowed connections on the server.
 	pub fn max_connections(mut self, limit: u32) -> Self {
 		self.conn_guard = ConnectionGuard::new(limit as usize);
@@ -526,6 +1464,8 @@ impl<RpcMiddleware, HttpMiddleware> TowerServiceBuilder<RpcMiddleware, HttpMiddl
 			http_middleware: self.http_middleware,
 			conn_id: self.conn_id,
 			conn_guard: self.conn_guard,
+			remote_addr: self.remote_addr,
+			builtin_methods: self.builtin_methods,
 		}
 	}
 
@@ -540,11 +1480,35 @@ impl<RpcMiddleware, HttpMiddleware> TowerServiceBuilder<RpcMiddleware, HttpMiddl
 			http_middleware,
 			conn_id: self.conn_id,
 			conn_guard: self.conn_guard,
+			remote_addr: self.remote_addr,
+			builtin_methods: self.builtin_methods,
 		}
 	}
 }
 
 impl<HttpMiddleware, RpcMiddleware> Builder<HttpMiddleware, RpcMiddleware> {
+	/// Apply a profile of [`Limits`] at once.
+	///
+	/// It sets the max request and response body size, limits the batch requests to
+	/// [`Limits::max_batch_len`] calls and uses [`Limits::timeout`] as method timeout.
+	/// The max log length isn't used by the server, see the logger middleware instead.
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use jsonrpsee_core::Limits;
+	/// use jsonrpsee_server::ServerBuilder;
+	///
+	/// let builder = ServerBuilder::default().with_limits(Limits::public());
+	/// ```
+	pub fn with_limits(mut self, limits: Limits) -> Self {
+		self.server_cfg.max_request_body_size = limits.max_request_size;
+		self.server_cfg.max_response_body_size = limits.max_response_size;
+		self.server_cfg.batch_requests_config = batch_config_from_limits(&limits);
+		self.server_cfg.method_timeout = Some(limits.timeout);
+		self
+	}
+
 	/// Set the maximum size of a request body in bytes. Default is 10 MiB.
 	pub fn max_request_body_size(mut self, size: u32) -> Self {
 		self.server_cfg.max_request_body_size = size;
@@ -552,6 +1516,9 @@ impl<HttpMiddleware, RpcMiddleware> Builder<HttpMiddleware, RpcMiddleware> {
 	}
 
 	/// Set the maximum size of a response body in bytes. Default is 10 MiB.
+	///
+	/// WebSocket clients may lower the limit for their connection by the [`MAX_RESPONSE_SIZE_HEADER`]
+	/// header such that they get an error response instead of a response that they can't handle.
 	pub fn max_response_body_size(mut self, size: u32) -> Self {
 		self.server_cfg.max_response_body_size = size;
 		self
@@ -566,12 +1533,269 @@ impl<HttpMiddleware, RpcMiddleware> Builder<HttpMiddleware, RpcMiddleware> {
 	/// Configure how [batch requests](https://www.jsonrpc.org/specification#batch) shall be handled
 	/// by the server.
 	///
+	/// The configuration may be overridden per connection by a middleware, see [`BatchPolicy`].
+	///
 	/// Default: batch requests are allowed and can be arbitrary big but the maximum payload size is limited.
 	pub fn set_batch_request_config(mut self, cfg: BatchRequestConfig) -> Self {
 		self.server_cfg.batch_requests_config = cfg;
 		self
 	}
 
+	/// Configure how a [batch request](https://www.jsonrpc.org/specification#batch) with more calls
+	/// than the [`BatchRequestConfig::Limit`] is handled.
+	///
+	/// With [`BatchOverflow::ProcessFirst`] the calls up to the limit are processed as usual and each of
+	/// the remaining calls is answered with a "batch request too large" error, except for notifications.
+	///
+	/// Default: [`BatchOverflow::Reject`], the entire batch request is rejected.
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use jsonrpsee_server::{BatchOverflow, BatchRequestConfig, ServerBuilder};
+	///
+	/// let builder = ServerBuilder::default()
+	///     .set_batch_request_config(BatchRequestConfig::Limit(10))
+	///     .set_batch_overflow(BatchOverflow::ProcessFirst);
+	/// ```
+	pub fn set_batch_overflow(mut self, overflow: BatchOverflow) -> Self {
+		self.server_cfg.batch_overflow = overflow;
+		self
+	}
+
+	/// Accept [JSON-RPC 1.0](https://www.jsonrpc.org/specification_v1) requests, i.e. requests without
+	/// the `jsonrpc` member, to bridge clients of legacy daemons such as bitcoind.
+	///
+	/// A 1.0 request whose `id` is `null` is a notification and the other 1.0 requests are answered
+	/// with 1.0 responses, which have both the `result` and `error` members, see [`jsonrpsee_types::v1`].
+	/// The JSON-RPC 2.0 requests are handled as usual.
+	///
+	/// The notifications of subscriptions are always sent as JSON-RPC 2.0 notifications.
+	///
+	/// Default: `false`.
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use jsonrpsee_server::ServerBuilder;
+	///
+	/// let builder = ServerBuilder::default().set_json_rpc_v1_compat(true);
+	/// ```
+	pub fn set_json_rpc_v1_compat(mut self, enabled: bool) -> Self {
+		self.server_cfg.json_rpc_v1_compat = enabled;
+		self
+	}
+
+	/// Configure whether the calls of a [batch request](https://www.jsonrpc.org/specification#batch)
+	/// are executed sequentially or concurrently.
+	///
+	/// [`BatchExecution::Concurrent`] caps how many calls of the same batch are executed at once
+	/// such that a single big batch can't saturate the runtime.
+	/// See [`Builder::set_batch_response_order`] for the order of the responses.
+	///
+	/// Default: the calls are executed sequentially.
+	///
+	/// # Panics
+	///
+	/// This method panics if the concurrency limit is 0.
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use jsonrpsee_server::{BatchExecution, ServerBuilder};
+	///
+	/// // Execute at most 8 calls of a batch at once.
+	/// let builder = ServerBuilder::default().set_batch_execution(BatchExecution::Concurrent(8));
+	/// ```
+	pub fn set_batch_execution(mut self, execution: BatchExecution) -> Self {
+		assert!(execution.max_concurrency() > 0, "The batch concurrency limit must be non-zero");
+		self.server_cfg.batch_execution = execution;
+		self
+	}
+
+	/// Configure whether the responses of a [batch request](https://www.jsonrpc.org/specification#batch)
+	/// are in the same order as the calls of the batch or in the order in which the calls completed.
+	///
+	/// The specification allows either but some clients rely on the responses being in request order.
+	/// With [`BatchResponseOrder::Completion`] a slow call doesn't hold back the responses of the
+	/// calls that complete after it, which only makes a difference with [`BatchExecution::Concurrent`].
+	///
+	/// Default: the responses are in request order.
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use jsonrpsee_server::{BatchExecution, BatchResponseOrder, ServerBuilder};
+	///
+	/// let builder = ServerBuilder::default()
+	///     .set_batch_execution(BatchExecution::Concurrent(8))
+	///     .set_batch_response_order(BatchResponseOrder::Completion);
+	/// ```
+	pub fn set_batch_response_order(mut self, order: BatchResponseOrder) -> Self {
+		self.server_cfg.batch_response_order = order;
+		self
+	}
+
+	/// Configure whether the remaining calls of a [batch request](https://www.jsonrpc.org/specification#batch)
+	/// are processed once a call failed with an error of a certain class, which avoids that a batch
+	/// of state-changing calls is only partially applied.
+	///
+	/// The calls that are not processed are answered with a
+	/// [`BATCH_CALL_NOT_PROCESSED_CODE`](jsonrpsee_types::error::BATCH_CALL_NOT_PROCESSED_CODE) error.
+	/// Fail-fast is only meaningful with [`BatchExecution::Sequential`] because calls that are
+	/// executed concurrently with the failed call may already have been executed.
+	///
+	/// Default: all calls of a batch are processed.
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use jsonrpsee_server::{BatchErrorClass, BatchFailFast, ServerBuilder};
+	///
+	/// // Abort the batches that opt in by the `x-jsonrpc-batch-fail-fast: true` header
+	/// // once a call fails with an implementation-defined server error.
+	/// let builder =
+	///     ServerBuilder::default().set_batch_fail_fast(BatchFailFast::OptIn(BatchErrorClass::Codes(-32099..=-32000)));
+	/// ```
+	pub fn set_batch_fail_fast(mut self, fail_fast: BatchFailFast) -> Self {
+		self.server_cfg.batch_fail_fast = fail_fast;
+		self
+	}
+
+	/// Configure which ids of the calls are accepted and whether they are echoed exactly as they were sent,
+	/// for interoperability with clients that send floats or other numbers as ids.
+	///
+	/// A call with a rejected id is answered with an invalid request error whose data tells why the id was
+	/// rejected. The policy may be overridden by inserting an [`IdPolicy`] into the extensions of the HTTP request
+	/// in a middleware.
+	///
+	/// Default: strings, unsigned integers and `null` are accepted and the ids are serialized again in the responses.
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use jsonrpsee_server::{IdPolicy, ServerBuilder};
+	///
+	/// // Answer the calls with an id such as `1.0` and echo the id as it was sent.
+	/// let builder = ServerBuilder::default().set_id_policy(IdPolicy::default().accept_floats(true).preserve_format(true));
+	/// ```
+	pub fn set_id_policy(mut self, policy: IdPolicy) -> Self {
+		self.server_cfg.id_policy = policy;
+		self
+	}
+
+	/// Configure how invalid UTF-8 in the WebSocket messages and HTTP request bodies is handled.
+	///
+	/// The number of messages with invalid UTF-8 are counted in [`ServerHandle::utf8_stats`].
+	///
+	/// Default: [`Utf8Policy::Strict`].
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use jsonrpsee_server::{ServerBuilder, Utf8Policy};
+	///
+	/// let builder = ServerBuilder::default().set_utf8_policy(Utf8Policy::Lossy);
+	/// ```
+	pub fn set_utf8_policy(mut self, policy: Utf8Policy) -> Self {
+		self.server_cfg.utf8_policy = policy;
+		self
+	}
+
+	/// Configure the codecs which the encoding of the messages is negotiated from, see [`jsonrpsee_core::codec`].
+	///
+	/// The HTTP requests are accepted in JSON and in the codecs of their `Content-Type` and the WebSocket
+	/// connections use the first codec of the subprotocols offered by the client or JSON otherwise.
+	///
+	/// Default: the builtin codecs which are enabled by the crate features.
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use jsonrpsee_core::codec::{self, Codec, Codecs};
+	/// use jsonrpsee_server::ServerBuilder;
+	///
+	/// // Only accept JSON.
+	/// let builder = ServerBuilder::default().set_codecs(Codecs::new([]));
+	///
+	/// // Accept the builtin codecs and a custom codec.
+	/// # #[derive(Debug)]
+	/// # struct MyCodec;
+	/// # impl Codec for MyCodec {
+	/// #     fn content_type(&self) -> &'static str { "application/x-my-codec" }
+	/// #     fn ws_subprotocol(&self) -> &'static str { "my-codec" }
+	/// #     fn encode(&self, json: &[u8]) -> Result<Vec<u8>, codec::CodecError> { Ok(json.to_vec()) }
+	/// #     fn decode(&self, data: &[u8]) -> Result<Vec<u8>, codec::CodecError> { Ok(data.to_vec()) }
+	/// # }
+	/// let codecs = Codecs::new(codec::builtin().iter().copied().chain([&MyCodec as &dyn Codec]));
+	/// let builder = ServerBuilder::default().set_codecs(codecs);
+	/// ```
+	pub fn set_codecs(mut self, codecs: Codecs) -> Self {
+		self.server_cfg.codecs = codecs;
+		self
+	}
+
+	/// Stream the responses of [batch requests](https://www.jsonrpc.org/specification#batch) over HTTP.
+	///
+	/// The response array is written incrementally in a chunked HTTP response as the calls of the
+	/// batch complete instead of buffering the whole batch response. As a consequence the max response
+	/// size only applies to the responses of the individual calls and not to the whole batch response.
+	///
+	/// This doesn't affect WebSocket connections.
+	///
+	/// Default: the batch responses are buffered.
+	pub fn enable_batch_response_streaming(mut self) -> Self {
+		self.server_cfg.stream_batch_responses = true;
+		self
+	}
+
+	/// Support subscriptions over HTTP with [server-sent events](https://html.spec.whatwg.org/multipage/server-sent-events.html).
+	///
+	/// A HTTP request with the `Accept: text/event-stream` header is answered with an event stream
+	/// where the first event is the response to the call. If the call was an accepted subscription the
+	/// notifications of the subscription are sent as the following events and the event stream ends
+	/// once the subscription is closed. Each event is a serialized JSON-RPC message in the `data` field.
+	///
+	/// The client unsubscribes by closing the event stream, because the unsubscribe method can't reach
+	/// the subscription from another HTTP request.
+	///
+	/// Default: HTTP requests only support method calls.
+	pub fn enable_sse_subscriptions(mut self) -> Self {
+		self.server_cfg.enable_sse_subscriptions = true;
+		self
+	}
+
+	/// Support subscriptions over HTTP with long polling, for clients that can't keep a
+	/// WebSocket connection or an event stream open.
+	///
+	/// The notifications of a subscription which is accepted over HTTP are buffered by the server and
+	/// the client fetches them by calling the [`LONG_POLLING_METHOD`](crate::LONG_POLLING_METHOD) method
+	/// with the subscription ID as parameter. The call responds with the buffered notifications as soon as
+	/// there is at least one or with an empty array once the poll timeout expired. Once a closed subscription
+	/// has been drained, polling it fails with an "Unknown subscription" error.
+	///
+	/// The subscription is closed by calling its unsubscribe method over HTTP or by not polling it within
+	/// the idle timeout. Subscriptions in batch requests are not supported and closed right away.
+	///
+	/// A subscription may only be polled and unsubscribed with the [`crate::CallerIdentity`] that it was
+	/// created with, if a middleware inserts one into the extensions of the requests.
+	///
+	/// Default: HTTP requests only support method calls.
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use std::time::Duration;
+	/// use jsonrpsee_server::{LongPollingConfig, ServerBuilder};
+	///
+	/// let builder = ServerBuilder::default()
+	///     .enable_long_polling(LongPollingConfig::new().poll_timeout(Duration::from_secs(10)));
+	/// ```
+	pub fn enable_long_polling(mut self, config: LongPollingConfig) -> Self {
+		self.server_cfg.long_polling = Some(LongPolling::new(config));
+		self
+	}
+
 	/// Set the maximum number of connections allowed. Default is 1024.
 	pub fn max_subscriptions_per_connection(mut self, max: u32) -> Self {
 		self.server_cfg.max_subscriptions_per_connection = max;
@@ -648,6 +1872,44 @@ impl<HttpMiddleware, RpcMiddleware> Builder<HttpMiddleware, RpcMiddleware> {
 		self
 	}
 
+	/// Configure a custom [`tokio::runtime::Handle`] to run the connections on.
+	///
+	/// The I/O of the accepted sockets is driven by this runtime, so that busy connections
+	/// can't starve the accept loop which keeps running on the runtime of [`Builder::custom_tokio_runtime`].
+	///
+	/// Default: the runtime of the accept loop.
+	pub fn custom_connection_runtime(mut self, rt: tokio::runtime::Handle) -> Self {
+		self.server_cfg.connection_runtime = Some(rt);
+		self
+	}
+
+	/// Configure a custom [`tokio::runtime::Handle`] to execute the method calls on.
+	///
+	/// Each call of an HTTP request or a WebSocket message is spawned on this runtime, which
+	/// isolates expensive methods from the I/O of the connections. The call is aborted
+	/// if the HTTP request is dropped before the call has completed.
+	///
+	/// Default: the runtime of the connection.
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	/// use jsonrpsee_server::ServerBuilder;
+	///
+	/// let rt = tokio::runtime::Builder::new_multi_thread()
+	///     .worker_threads(4)
+	///     .thread_name("rpc-methods")
+	///     .enable_all()
+	///     .build()
+	///     .unwrap();
+	///
+	/// let builder = ServerBuilder::default().custom_method_runtime(rt.handle().clone());
+	/// ```
+	pub fn custom_method_runtime(mut self, rt: tokio::runtime::Handle) -> Self {
+		self.server_cfg.method_runtime = Some(rt);
+		self
+	}
+
 	/// Enable WebSocket ping/pong on the server.
 	///
 	/// Default: pings are disabled.
@@ -675,6 +1937,61 @@ impl<HttpMiddleware, RpcMiddleware> Builder<HttpMiddleware, RpcMiddleware> {
 		self
 	}
 
+	/// Configure when the messages on WebSocket connections are flushed to the socket.
+	///
+	/// Default: each message is flushed right away.
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use std::time::Duration;
+	/// use jsonrpsee_server::{ServerBuilder, WsFlushPolicy};
+	///
+	/// // Flush every 64 messages or 200 microseconds after the first buffered message.
+	/// let policy = WsFlushPolicy::Coalesce { max_messages: 64, window: Duration::from_micros(200) };
+	/// let builder = ServerBuilder::default().set_ws_flush_policy(policy);
+	/// ```
+	pub fn set_ws_flush_policy(mut self, policy: WsFlushPolicy) -> Self {
+		self.server_cfg.ws_flush_policy = policy;
+		self
+	}
+
+	/// Configure the limits of the frames on WebSocket connections.
+	///
+	/// The limits are independent of the max size of a message, see [`Builder::max_request_body_size`],
+	/// and protect against messages that are split into a flood of tiny fragments.
+	/// A connection that violates the limits is closed.
+	///
+	/// Default: frames of up to 256 MB, any number of frames per message and
+	/// the messages are sent in a single frame.
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use jsonrpsee_server::{ServerBuilder, WsFrameConfig};
+	///
+	/// // Accept messages of at most 16 frames of 64 KB and send the messages in frames of 16 KB.
+	/// let config =
+	///     WsFrameConfig::new().max_frame_size(64 * 1024).max_frames_per_message(16).fragmentation_threshold(16 * 1024);
+	/// let builder = ServerBuilder::default().set_ws_frame_config(config);
+	/// ```
+	pub fn set_ws_frame_config(mut self, config: WsFrameConfig) -> Self {
+		self.server_cfg.ws_frame_config = config;
+		self
+	}
+
+	/// Configure how many serialization buffers are pooled per WebSocket connection.
+	///
+	/// The responses and notifications of a connection are serialized into buffers of the pool
+	/// which are reused once the messages have been sent, instead of allocating a buffer per message.
+	/// Set it to at least the number of messages that are typically in flight on a connection.
+	///
+	/// Default: 0, the buffers are not pooled.
+	pub fn set_buffer_pool_size(mut self, size: u32) -> Self {
+		self.server_cfg.buffer_pool_size = size;
+		self
+	}
+
 	/// Configure custom `subscription ID` provider for the server to use
 	/// to when getting new subscription calls.
 	///
@@ -700,6 +2017,310 @@ impl<HttpMiddleware, RpcMiddleware> Builder<HttpMiddleware, RpcMiddleware> {
 		self
 	}
 
+	/// Configure the default max execution time for asynchronous method calls.
+	///
+	/// When the timeout expires the future of the method call is dropped and the call
+	/// is answered with a "method call timed out" error instead. Individual methods may
+	/// override this with [`Methods::set_method_timeout`].
+	///
+	/// Synchronous methods never time out because they're executed to completion,
+	/// and subscriptions are not affected either.
+	///
+	/// Default: no timeout.
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use std::time::Duration;
+	/// use jsonrpsee_server::ServerBuilder;
+	///
+	/// let builder = ServerBuilder::default().set_method_timeout(Duration::from_secs(30));
+	/// ```
+	pub fn set_method_timeout(mut self, timeout: Duration) -> Self {
+		self.server_cfg.method_timeout = Some(timeout);
+		self
+	}
+
+	/// Configure a hook that is invoked before a connection is accepted.
+	///
+	/// The hook is invoked once per WebSocket connection, before the handshake is answered,
+	/// and once per HTTP request. Returning an error rejects the connection with the
+	/// provided HTTP response, which may be built from a [`crate::middleware::http::HandshakeRejection`]
+	/// to answer with a specific status and JSON body.
+	///
+	/// Default: no hook.
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use jsonrpsee_server::{http, ServerBuilder};
+	///
+	/// let builder = ServerBuilder::default().set_on_connect(|info| async move {
+	///     if info.headers.contains_key("authorization") {
+	///         Ok(())
+	///     } else {
+	///         Err(http::response::denied())
+	///     }
+	/// });
+	/// ```
+	pub fn set_on_connect<F, Fut>(mut self, f: F) -> Self
+	where
+		F: Fn(ConnectInfo) -> Fut + Send + Sync + 'static,
+		Fut: Future<Output = Result<(), HttpResponse>> + Send + 'static,
+	{
+		self.server_cfg.on_connect = Some(OnConnect::new(f));
+		self
+	}
+
+	/// Configure a hook that is invoked when a connection has been terminated.
+	///
+	/// The hook is invoked for every connection that was accepted, once the WebSocket connection
+	/// is closed or the HTTP request has been answered.
+	///
+	/// Default: no hook.
+	pub fn set_on_disconnect(mut self, f: impl Fn(DisconnectInfo) + Send + Sync + 'static) -> Self {
+		self.server_cfg.on_disconnect = Some(OnDisconnect::new(f));
+		self
+	}
+
+	/// Configure a hook that may inspect and replace the response of each method call
+	/// before it's passed to the RPC middleware and sent to the client.
+	///
+	/// The hook is invoked for the calls of single and batch requests as well as for the responses to
+	/// subscription calls, see [`MethodResponse::map_error`] to modify the error of a failed call.
+	///
+	/// Default: no hook.
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use jsonrpsee_server::ServerBuilder;
+	/// use jsonrpsee_server::types::ErrorObject;
+	///
+	/// // Hide the details of internal errors from the clients.
+	/// let builder = ServerBuilder::default().set_on_response(|_info, rp| {
+	///     rp.map_error(|err| match err.code() {
+	///         -32603 => ErrorObject::owned(err.code(), "Internal error", None::<()>),
+	///         _ => err,
+	///     })
+	/// });
+	/// ```
+	pub fn set_on_response(
+		mut self,
+		f: impl Fn(&ResponseInfo, MethodResponse) -> MethodResponse + Send + Sync + 'static,
+	) -> Self {
+		self.server_cfg.on_response = Some(OnResponse::new(f));
+		self
+	}
+
+	/// Configure a hook that is invoked when a method handler or a subscription task panics.
+	///
+	/// The panics are always caught and the call is answered with an `Internal error` that contains
+	/// the correlation ID of the panic as `{"correlationId": u64}`, while the connection is kept alive.
+	/// A panic of a subscription task closes the subscription.
+	///
+	/// Default: the panics are only logged.
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use jsonrpsee_server::ServerBuilder;
+	///
+	/// let builder = ServerBuilder::default().set_on_panic(|panic| {
+	///     eprintln!("`{}` panicked ({}): {:?}", panic.method, panic.correlation_id, panic.message);
+	/// });
+	/// ```
+	pub fn set_on_panic(mut self, f: impl Fn(&MethodPanic) + Send + Sync + 'static) -> Self {
+		self.server_cfg.on_panic = Some(PanicHook::new(f));
+		self
+	}
+
+	/// Configure a hook that authorizes the subscribe calls before they are dispatched.
+	///
+	/// If the hook returns an error, the subscribe call is answered with the error and the subscription
+	/// callback isn't invoked. The [`SubscribeInfo`] contains the extensions of the call, such that
+	/// for instance an identity that was inserted by a middleware can be checked against the method.
+	///
+	/// To limit how many subscriptions of a method a connection may open, see
+	/// [`Methods::set_max_subscriptions_per_connection`].
+	///
+	/// Default: no hook.
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use jsonrpsee_server::ServerBuilder;
+	/// use jsonrpsee_server::types::ErrorObject;
+	///
+	/// #[derive(Clone)]
+	/// struct Admin;
+	///
+	/// let builder = ServerBuilder::default().set_on_subscribe(|info| {
+	///     if info.method.starts_with("admin_") && info.extensions.get::<Admin>().is_none() {
+	///         return Err(ErrorObject::owned(-32001, "Unauthorized", None::<()>));
+	///     }
+	///     Ok(())
+	/// });
+	/// ```
+	pub fn set_on_subscribe(
+		mut self,
+		f: impl Fn(&SubscribeInfo) -> Result<(), ErrorObjectOwned> + Send + Sync + 'static,
+	) -> Self {
+		self.server_cfg.on_subscribe = Some(OnSubscribe::new(f));
+		self
+	}
+
+	/// Configure a hook that observes the raw messages which are received and sent on the connections,
+	/// for instance to debug or capture the traffic at the wire level.
+	///
+	/// The hook receives a copy of the first `max_len` bytes of each message before it's parsed or once
+	/// it's sent, that is the bodies of HTTP requests and responses and the WebSocket text messages,
+	/// including the notifications of subscriptions. It's invoked on the task of the connection
+	/// and thus must not block.
+	///
+	/// Default: no hook.
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use jsonrpsee_server::{MessageDirection, ServerBuilder};
+	///
+	/// let builder = ServerBuilder::default().set_message_tap(1024, |msg| {
+	///     let arrow = if msg.direction == MessageDirection::Inbound { "->" } else { "<-" };
+	///     println!("{:?} {arrow} {}", msg.conn_id, String::from_utf8_lossy(&msg.data));
+	/// });
+	/// ```
+	pub fn set_message_tap(mut self, max_len: usize, f: impl Fn(RawMessage) + Send + Sync + 'static) -> Self {
+		self.server_cfg.on_message = Some(OnMessage::new(max_len, f));
+		self
+	}
+
+	/// Configure an audit log which receives a record of every completed method call.
+	///
+	/// The [`AuditRecord`] contains the caller identity, if a [`crate::CallerIdentity`] was inserted into
+	/// the extensions of the request by a middleware, the method name, a digest of the params, the outcome
+	/// and the timing of the call.
+	///
+	/// The records are enqueued in a queue that holds at most `capacity` records and are passed to the
+	/// async sink `f` one at a time by a dedicated task, such that a slow sink doesn't stall the processing of
+	/// the requests. Records are dropped with a warning if the queue is full.
+	///
+	/// Default: no audit log.
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use jsonrpsee_server::ServerBuilder;
+	///
+	/// let builder = ServerBuilder::default().set_audit_log(1024, |record| async move {
+	///     // Write the record to durable storage.
+	///     println!("{:?} called {} -> {:?}", record.caller, record.method, record.outcome);
+	/// });
+	/// ```
+	pub fn set_audit_log<F, Fut>(mut self, capacity: usize, f: F) -> Self
+	where
+		F: Fn(AuditRecord) -> Fut + Send + Sync + 'static,
+		Fut: Future<Output = ()> + Send + 'static,
+	{
+		self.server_cfg.audit_log = Some(AuditLog::new(capacity, f));
+		self
+	}
+
+	/// Serve an OpenRPC document of the registered methods at the HTTP path `path`, for instance `/openrpc.json`.
+	///
+	/// The document is generated by [`Methods::openrpc`] when it's first requested and is served on `GET` requests.
+	///
+	/// Default: the OpenRPC document is not served.
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use jsonrpsee_server::{OpenRpcInfo, ServerBuilder};
+	///
+	/// let builder = ServerBuilder::default().serve_openrpc("/openrpc.json", OpenRpcInfo::new("my-api", "1.0.0"));
+	/// ```
+	pub fn serve_openrpc(mut self, path: impl Into<String>, info: OpenRpcInfo) -> Self {
+		self.server_cfg.openrpc = Some(ServedOpenRpc::new(path.into(), info));
+		self
+	}
+
+	/// Respond to plain HTTP `GET` requests with a JSON document that contains the name and version
+	/// of the server, the supported transports and the limits of the server.
+	///
+	/// The document is served on any path except the one of [`Builder::serve_openrpc`], for instance:
+	///
+	/// ```json
+	/// {
+	///   "name": "my-server",
+	///   "version": "1.0.0",
+	///   "transports": ["http", "ws"],
+	///   "limits": {
+	///     "maxRequestBodySize": 10485760,
+	///     "maxResponseBodySize": 10485760,
+	///     "maxConnections": 100,
+	///     "maxSubscriptionsPerConnection": 1024,
+	///     "batchRequests": true,
+	///     "maxBatchLen": null
+	///   }
+	/// }
+	/// ```
+	///
+	/// Default: `GET` requests are rejected with `405 Method Not Allowed`.
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use jsonrpsee_server::{ServerBuilder, ServerInfo};
+	///
+	/// let builder = ServerBuilder::default().serve_server_info(ServerInfo::new("my-server", "1.0.0"));
+	/// ```
+	pub fn serve_server_info(mut self, info: ServerInfo) -> Self {
+		self.server_cfg.server_info = Some(info);
+		self
+	}
+
+	/// Register the standard `rpc.discover` method which returns an OpenRPC document of the registered methods.
+	///
+	/// The document is generated by [`Methods::openrpc`] once the methods are passed to the server
+	/// and methods hidden by [`Methods::hide_method`] are not included.
+	/// If a method called `rpc.discover` is already registered it's left as is.
+	///
+	/// Default: `rpc.discover` is not registered.
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use jsonrpsee_server::{OpenRpcInfo, ServerBuilder};
+	///
+	/// let builder = ServerBuilder::default().enable_rpc_discover(OpenRpcInfo::new("my-api", "1.0.0"));
+	/// ```
+	pub fn enable_rpc_discover(mut self, info: OpenRpcInfo) -> Self {
+		self.server_cfg.rpc_discover = Some(info);
+		self
+	}
+
+	/// Register the `system_methods` method which lists the registered methods and subscriptions
+	/// such that it can be verified at runtime what a deployed server actually serves.
+	///
+	/// Each method is listed with its kind, aliases, deprecation note, required authorization,
+	/// priority and the effective limits of the server, see [`Methods::deprecate_method`] and
+	/// [`Methods::require_auth`]. Methods hidden by [`Methods::hide_method`] are not included.
+	/// If a method called `system_methods` is already registered it's left as is.
+	///
+	/// Default: `system_methods` is not registered.
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use jsonrpsee_server::ServerBuilder;
+	///
+	/// let builder = ServerBuilder::default().enable_methods_introspection();
+	/// ```
+	pub fn enable_methods_introspection(mut self) -> Self {
+		self.server_cfg.methods_introspection = true;
+		self
+	}
+
 	/// Configure a custom [`tower::ServiceBuilder`] middleware for composing layers to be applied to the RPC service.
 	///
 	/// Default: No tower layers are applied to the RPC service.
@@ -726,16 +2347,102 @@ impl<HttpMiddleware, RpcMiddleware> Builder<HttpMiddleware, RpcMiddleware> {
 		Builder { server_cfg: self.server_cfg, http_middleware, rpc_middleware: self.rpc_middleware }
 	}
 
-	/// Configure `TCP_NODELAY` on the socket to the supplied value `nodelay`.
+	/// Configure a [tower::Layer] that wraps each accepted connection.
+	///
+	/// The middleware sees the connection from the moment it's established until it's closed, including
+	/// the entire session of a WebSocket connection, and it may replace the I/O of the connection
+	/// to observe the data that is exchanged with the peer. See [`crate::middleware::connection`] for further details.
+	///
+	/// Default: no middleware.
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use std::task::{Context, Poll};
+	///
+	/// use jsonrpsee_server::middleware::connection::{Connection, ServeConnection};
+	/// use jsonrpsee_server::ServerBuilder;
+	/// use tower::{layer::layer_fn, Service};
+	/// use tracing::{instrument::Instrumented, Instrument};
+	///
+	/// /// Runs the entire connection in a tracing span.
+	/// struct SessionSpan<S>(S);
+	///
+	/// impl<S: Service<Connection>> Service<Connection> for SessionSpan<S> {
+	///     type Response = S::Response;
+	///     type Error = S::Error;
+	///     type Future = Instrumented<S::Future>;
+	///
+	///     fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+	///         self.0.poll_ready(cx)
+	///     }
+	///
+	///     fn call(&mut self, conn: Connection) -> Self::Future {
+	///         let span = tracing::info_span!("session", conn_id = conn.conn_id.0, remote_addr = %conn.remote_addr);
+	///         self.0.call(conn).instrument(span)
+	///     }
+	/// }
+	///
+	/// let builder = ServerBuilder::default().set_connection_middleware(layer_fn(|s: ServeConnection| SessionSpan(s)));
+	/// ```
+	pub fn set_connection_middleware<L>(mut self, layer: L) -> Self
+	where
+		L: Layer<ServeConnection> + Send + Sync + 'static,
+		L::Service: Service<Connection, Response = (), Error = BoxError> + Send + 'static,
+		<L::Service as Service<Connection>>::Future: Send + 'static,
+	{
+		self.server_cfg.connection_middleware = Some(ConnectionMiddleware::new(layer));
+		self
+	}
+
+	/// Configure `TCP_NODELAY` on the socket to the supplied value `nodelay`.
+	///
+	/// Default is `true`.
+	pub fn set_tcp_no_delay(mut self, no_delay: bool) -> Self {
+		self.server_cfg.tcp_no_delay = no_delay;
+		self
+	}
+
+	/// Configure `SO_REUSEPORT` on the listener that is bound by [`Builder::build`].
+	///
+	/// This lets a new server process bind the same address while the old process is still accepting
+	/// connections, for instance to restart without downtime, see [`ServerHandle::handoff`].
+	///
+	/// Default is `false`.
+	#[cfg(all(unix, not(any(target_os = "solaris", target_os = "illumos"))))]
+	pub fn set_reuse_port(mut self, reuse_port: bool) -> Self {
+		self.server_cfg.reuse_port = reuse_port;
+		self
+	}
+
+	/// Configure the server to notify the clients when it is stopped and to keep serving
+	/// the open connections for `deadline`, such that the clients can reconnect elsewhere
+	/// before the connections are closed.
 	///
-	/// Default is `true`.
-	pub fn set_tcp_no_delay(mut self, no_delay: bool) -> Self {
-		self.server_cfg.tcp_no_delay = no_delay;
+	/// WebSocket connections receive a [`SHUTDOWN_NOTIFICATION`] notification with the deadline
+	/// in milliseconds as `{"deadlineMs": u64}` and the HTTP responses are sent with
+	/// `Connection: close` during that period.
+	///
+	/// Default: the connections are closed as soon as the server is stopped.
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use std::time::Duration;
+	/// use jsonrpsee_server::ServerBuilder;
+	///
+	/// let builder = ServerBuilder::default().set_drain_notice(Duration::from_secs(10));
+	/// ```
+	pub fn set_drain_notice(mut self, deadline: Duration) -> Self {
+		self.server_cfg.drain_notice = Some(deadline);
 		self
 	}
 
 	/// Configure the server to only serve JSON-RPC HTTP requests.
 	///
+	/// That implies that server rejects WebSocket upgrade requests with `403 Forbidden`.
+	/// Use a [`Router`] to restrict the transport per path.
+	///
 	/// Default: both http and ws are enabled.
 	pub fn http_only(mut self) -> Self {
 		self.server_cfg.enable_http = true;
@@ -745,7 +2452,8 @@ impl<HttpMiddleware, RpcMiddleware> Builder<HttpMiddleware, RpcMiddleware> {
 
 	/// Configure the server to only serve JSON-RPC WebSocket requests.
 	///
-	/// That implies that server just denies HTTP requests which isn't a WebSocket upgrade request
+	/// That implies that server rejects HTTP requests which aren't a WebSocket upgrade request with `403 Forbidden`.
+	/// Use a [`Router`] to restrict the transport per path.
 	///
 	/// Default: both http and ws are enabled.
 	pub fn ws_only(mut self) -> Self {
@@ -859,6 +2567,8 @@ impl<HttpMiddleware, RpcMiddleware> Builder<HttpMiddleware, RpcMiddleware> {
 			http_middleware: self.http_middleware,
 			conn_id: Arc::new(AtomicU32::new(0)),
 			conn_guard: ConnectionGuard::new(max_conns),
+			remote_addr: None,
+			builtin_methods: BuiltinMethodsCache::default(),
 		}
 	}
 
@@ -879,6 +2589,12 @@ impl<HttpMiddleware, RpcMiddleware> Builder<HttpMiddleware, RpcMiddleware> {
 	/// ```
 	///
 	pub async fn build(self, addrs: impl ToSocketAddrs) -> std::io::Result<Server<HttpMiddleware, RpcMiddleware>> {
+		#[cfg(all(unix, not(any(target_os = "solaris", target_os = "illumos"))))]
+		let listener = match self.server_cfg.reuse_port {
+			true => bind_reuse_port(addrs).await?,
+			false => TcpListener::bind(addrs).await?,
+		};
+		#[cfg(not(all(unix, not(any(target_os = "solaris", target_os = "illumos")))))]
 		let listener = TcpListener::bind(addrs).await?;
 
 		Ok(Server {
@@ -927,6 +2643,26 @@ impl<HttpMiddleware, RpcMiddleware> Builder<HttpMiddleware, RpcMiddleware> {
 	}
 }
 
+/// Bind the first of `addrs` that succeeds with `SO_REUSEADDR` and `SO_REUSEPORT`.
+#[cfg(all(unix, not(any(target_os = "solaris", target_os = "illumos"))))]
+async fn bind_reuse_port(addrs: impl ToSocketAddrs) -> std::io::Result<TcpListener> {
+	let mut last_err = None;
+
+	for addr in tokio::net::lookup_host(addrs).await? {
+		let socket = if addr.is_ipv4() { tokio::net::TcpSocket::new_v4()? } else { tokio::net::TcpSocket::new_v6()? };
+		socket.set_reuseaddr(true)?;
+		socket.set_reuseport(true)?;
+
+		match socket.bind(addr) {
+			Ok(()) => return socket.listen(1024),
+			Err(e) => last_err = Some(e),
+		}
+	}
+
+	Err(last_err
+		.unwrap_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidInput, "could not resolve to any address")))
+}
+
 /// Data required by the server to handle requests.
 #[derive(Debug, Clone)]
 struct ServiceData {
@@ -940,6 +2676,10 @@ struct ServiceData {
 	conn_guard: ConnectionGuard,
 	/// ServerConfig
 	server_cfg: ServerConfig,
+	/// Storage that is shared by all calls on the connection.
+	conn_extensions: ConnectionExtensions,
+	/// The address of the peer, if known.
+	remote_addr: Option<SocketAddr>,
 }
 
 /// jsonrpsee tower service
@@ -977,7 +2717,7 @@ where
 	for<'a> <RpcMiddleware as Layer<RpcService>>::Service: RpcServiceT<'a>,
 	HttpMiddleware: Layer<TowerServiceNoHttp<RpcMiddleware>> + Send + 'static,
 	<HttpMiddleware as Layer<TowerServiceNoHttp<RpcMiddleware>>>::Service:
-		Send + Service<HttpRequest<RequestBody>, Response = HttpResponse<ResponseBody>, Error = Box<(dyn StdError + Send + Sync + 'static)>>,
+		Send + Service<HttpRequest<RequestBody>, Response = HttpResponse<ResponseBody>, Error = Box<dyn StdError + Send + Sync + 'static>>,
 	<<HttpMiddleware as Layer<TowerServiceNoHttp<RpcMiddleware>>>::Service as Service<HttpRequest<RequestBody>>>::Future:
 		Send + 'static,
 	RequestBody: http_body::Body<Data = Bytes> + Send + 'static,
@@ -991,11 +2731,20 @@ where
 		Poll::Ready(Ok(()))
 	}
 
-	fn call(&mut self, request: HttpRequest<RequestBody>) -> Self::Future {
+	fn call(&mut self, mut request: HttpRequest<RequestBody>) -> Self::Future {
+		let inner = &self.rpc_middleware.inner;
+		insert_peer_info(&mut request, inner.conn_id, inner.remote_addr);
+
 		Box::pin(self.http_middleware.service(self.rpc_middleware.clone()).call(request))
 	}
 }
 
+/// Make the peer known to the HTTP middleware, e.g. to reject a handshake by the address of the peer.
+fn insert_peer_info<B>(request: &mut HttpRequest<B>, conn_id: u32, remote_addr: Option<SocketAddr>) {
+	let transport = if is_upgrade_request(request) { TransportKind::WebSocket } else { TransportKind::Http };
+	request.extensions_mut().insert(PeerInfo { conn_id: conn_id.into(), remote_addr, transport });
+}
+
 /// jsonrpsee tower service without HTTP specific middleware.
 ///
 /// # Note
@@ -1047,254 +2796,861 @@ where
 		let curr_conns = max_conns - conn_guard.available_connections();
 		tracing::debug!(target: LOG_TARGET, "Accepting new connection {}/{}", curr_conns, max_conns);
 
+		let is_upgrade_request = is_upgrade_request(&request);
+
+		if let Some(openrpc) = &self.inner.server_cfg.openrpc {
+			if !is_upgrade_request && request.method() == hyper::Method::GET && request.uri().path() == openrpc.path {
+				let doc = openrpc.document(&self.inner.methods).to_string();
+				return async move { Ok(http::response::ok_response(doc)) }.boxed();
+			}
+		}
+
+		if let Some(info) = &self.inner.server_cfg.server_info {
+			if !is_upgrade_request && request.method() == hyper::Method::GET {
+				let doc = info.to_document(&self.inner.server_cfg);
+				return async move { Ok(http::response::ok_response(doc)) }.boxed();
+			}
+		}
+
 		let req_ext = request.extensions_mut();
 		req_ext.insert::<ConnectionGuard>(conn_guard.clone());
 		req_ext.insert::<ConnectionId>(conn.conn_id.into());
-
-		let is_upgrade_request = is_upgrade_request(&request);
+		req_ext.insert::<SubscriptionStats>(self.inner.server_cfg.subscription_stats.clone());
+		req_ext.insert::<ConnectionExtensions>(self.inner.conn_extensions.clone());
+		// A policy inserted by a middleware overrides the policy of the server.
+		if req_ext.get::<IdPolicy>().is_none() {
+			req_ext.insert(self.inner.server_cfg.id_policy);
+		}
+		req_ext.insert(self.inner.server_cfg.utf8_policy);
+		req_ext.insert(self.inner.server_cfg.codecs.clone());
+		req_ext.insert(self.inner.server_cfg.batch_overflow);
+		if self.inner.server_cfg.json_rpc_v1_compat {
+			req_ext.insert(JsonRpcV1Compat);
+		}
+		if let Some(hook) = &self.inner.server_cfg.on_panic {
+			req_ext.insert(hook.clone());
+		}
+		req_ext.insert(self.inner.server_cfg.utf8_stats.clone());
+		req_ext.insert::<PeerInfo>(PeerInfo {
+			conn_id: conn.conn_id.into(),
+			remote_addr: self.inner.remote_addr,
+			transport: if is_upgrade_request { TransportKind::WebSocket } else { TransportKind::Http },
+		});
 
 		if self.inner.server_cfg.enable_ws && is_upgrade_request {
-			let this = self.inner.clone();
+			let mut this = self.inner.clone();
+			let client_max_response_size = ws::client_max_response_size(request.headers());
+			this.server_cfg.max_response_body_size =
+				ws::negotiate_max_response_size(client_max_response_size, this.server_cfg.max_response_body_size);
 
 			let mut server = soketto::handshake::http::Server::new();
 
-			let response = match server.receive_request(&request) {
-				Ok(response) => {
-					let (tx, rx) = mpsc::channel::<String>(this.server_cfg.message_buffer_capacity as usize);
-					let sink = MethodSink::new(tx);
-
-					// On each method call the `pending_calls` is cloned
-					// then when all pending_calls are dropped
-					// a graceful shutdown can occur.
-					let (pending_calls, pending_calls_completed) = mpsc::channel::<()>(1);
-
-					let cfg = RpcServiceCfg::CallsAndSubscriptions {
-						bounded_subscriptions: BoundedSubscriptions::new(
-							this.server_cfg.max_subscriptions_per_connection,
-						),
-						id_provider: this.server_cfg.id_provider.clone(),
-						sink: sink.clone(),
-						_pending_calls: pending_calls,
-					};
-
-					let rpc_service = RpcService::new(
-						this.methods.clone(),
-						this.server_cfg.max_response_body_size as usize,
-						this.conn_id.into(),
-						cfg,
-					);
-
-					let rpc_service = self.rpc_middleware.service(rpc_service);
-
-					tokio::spawn(
-						async move {
-							let extensions = request.extensions().clone();
-
-							let upgraded = match hyper::upgrade::on(request).await {
-								Ok(u) => u,
-								Err(e) => {
-									tracing::debug!(target: LOG_TARGET, "Could not upgrade connection: {}", e);
-									return;
-								}
-							};
-
-							let io = hyper_util::rt::TokioIo::new(upgraded);
-
-							let stream = BufReader::new(BufWriter::new(io.compat()));
-							let mut ws_builder = server.into_builder(stream);
-							ws_builder.set_max_message_size(this.server_cfg.max_request_body_size as usize);
-							let (sender, receiver) = ws_builder.finish();
-
-							let params = BackgroundTaskParams {
-								server_cfg: this.server_cfg,
-								conn,
-								ws_sender: sender,
-								ws_receiver: receiver,
-								rpc_service,
-								sink,
-								rx,
-								pending_calls_completed,
-								on_session_close,
-								extensions,
-							};
-
-							ws::background_task(params).await;
-						}
-						.in_current_span(),
-					);
-
-					response.map(|()| HttpBody::empty())
+			let (response, encoding) = match server.receive_request(&request) {
+				Ok(mut response) => {
+					let encoding = ws::negotiate_encoding(request.headers(), &this.server_cfg.codecs, &mut response);
+					(response.map(|()| HttpBody::empty()), encoding)
 				}
 				Err(e) => {
 					tracing::debug!(target: LOG_TARGET, "Could not upgrade connection: {}", e);
-					HttpResponse::new(HttpBody::from(format!("Could not upgrade connection: {e}")))
+					let rp = HttpResponse::new(HttpBody::from(format!("Could not upgrade connection: {e}")));
+					return async { Ok(rp) }.boxed();
 				}
 			};
 
-			async { Ok(response) }.boxed()
+			let (tx, rx) = mpsc::channel::<SinkMessage>(this.server_cfg.message_buffer_capacity as usize);
+			let sink = ws::method_sink(tx, &this.server_cfg);
+
+			// On each method call the `pending_calls` is cloned
+			// then when all pending_calls are dropped
+			// a graceful shutdown can occur.
+			let (pending_calls, pending_calls_completed) = mpsc::channel::<()>(1);
+
+			let cfg = RpcServiceCfg::CallsAndSubscriptions {
+				bounded_subscriptions: BoundedSubscriptions::new(this.server_cfg.max_subscriptions_per_connection),
+				id_provider: this.server_cfg.id_provider.clone(),
+				on_subscribe: this.server_cfg.on_subscribe.clone(),
+				sink: sink.clone(),
+				subscription_conn_id: this.conn_id.into(),
+				_pending_calls: pending_calls,
+			};
+
+			let rpc_service = RpcService::new(
+				this.methods.clone(),
+				this.server_cfg.max_response_body_size as usize,
+				client_max_response_size,
+				this.server_cfg.method_timeout,
+				this.server_cfg.on_response.clone(),
+				this.server_cfg.audit_log.clone(),
+				this.conn_id.into(),
+				cfg,
+			);
+
+			let rpc_service = self.rpc_middleware.service(rpc_service);
+
+			async move {
+				let conn_id = this.conn_id.into();
+
+				if let Some(on_connect) = &this.server_cfg.on_connect {
+					let info = ConnectInfo {
+						conn_id,
+						remote_addr: this.remote_addr,
+						transport: TransportKind::WebSocket,
+						headers: request.headers().clone(),
+					};
+
+					if let Err(rp) = on_connect.call(info).await {
+						return Ok(rp);
+					}
+				}
+
+				let mut disconnect_guard = this
+					.server_cfg
+					.on_disconnect
+					.clone()
+					.map(|hook| DisconnectGuard::new(hook, conn_id, this.remote_addr, TransportKind::WebSocket));
+
+				tokio::spawn(
+					async move {
+						let extensions = request.extensions().clone();
+						let batch_fail_fast = this.server_cfg.batch_fail_fast.resolve(request.headers()).cloned();
+
+						let upgraded = match hyper::upgrade::on(request).await {
+							Ok(u) => u,
+							Err(e) => {
+								tracing::debug!(target: LOG_TARGET, "Could not upgrade connection: {}", e);
+								return;
+							}
+						};
+
+						let io = hyper_util::rt::TokioIo::new(upgraded);
+
+						let frame_cfg = this.server_cfg.ws_frame_config;
+						let stream = BufReader::new(BufWriter::new(WsFrameIo::new(io.compat(), &frame_cfg)));
+						let mut ws_builder = server.into_builder(stream);
+						ws_builder.set_max_message_size(transport_max_request_size(
+							&this.methods,
+							this.server_cfg.max_request_body_size,
+						) as usize);
+						if let Some(max) = frame_cfg.max_frame_size {
+							ws_builder.set_max_frame_size(max as usize);
+						}
+						let (sender, receiver) = ws_builder.finish();
+
+						let params = BackgroundTaskParams {
+							server_cfg: this.server_cfg,
+							conn,
+							ws_sender: sender,
+							ws_receiver: receiver,
+							rpc_service,
+							methods: this.methods,
+							sink,
+							rx,
+							pending_calls_completed,
+							on_session_close,
+							extensions,
+							batch_fail_fast,
+							encoding,
+						};
+
+						let (reason, messages, pending_calls) = ws::background_task(params).await;
+
+						if let Some(guard) = disconnect_guard.as_mut() {
+							guard.complete(reason, messages, pending_calls);
+						}
+					}
+					.in_current_span(),
+				);
+
+				Ok(response)
+			}
+			.boxed()
 		} else if self.inner.server_cfg.enable_http && !is_upgrade_request {
 			let this = &self.inner;
 			let max_response_size = this.server_cfg.max_response_body_size;
 			let max_request_size = this.server_cfg.max_request_body_size;
 			let methods = this.methods.clone();
 			let batch_config = this.server_cfg.batch_requests_config;
+			let batch_execution = this.server_cfg.batch_execution;
+			let batch_response_order = this.server_cfg.batch_response_order;
+			let batch_fail_fast = this.server_cfg.batch_fail_fast.clone();
+			let stream_batch_responses = this.server_cfg.stream_batch_responses;
+
+			let sse = this.server_cfg.enable_sse_subscriptions && http::accepts_event_stream(request.headers());
+			let long_polling = if sse { None } else { this.server_cfg.long_polling.clone() };
+
+			// The messages of a call with server-sent events are sent to the event stream and
+			// the messages of a long-polled subscription are buffered until they are polled.
+			let (cfg, events) = if sse {
+				let (tx, rx) = mpsc::channel::<SinkMessage>(this.server_cfg.message_buffer_capacity as usize);
+				let (pending_calls, _) = mpsc::channel::<()>(1);
+
+				let cfg = RpcServiceCfg::CallsAndSubscriptions {
+					bounded_subscriptions: BoundedSubscriptions::new(this.server_cfg.max_subscriptions_per_connection),
+					id_provider: this.server_cfg.id_provider.clone(),
+					on_subscribe: this.server_cfg.on_subscribe.clone(),
+					sink: MethodSink::new(tx),
+					subscription_conn_id: this.conn_id.into(),
+					_pending_calls: pending_calls,
+				};
+				(cfg, Some(rx))
+			} else if let Some(long_polling) = long_polling.as_ref() {
+				let (tx, rx) = mpsc::channel::<SinkMessage>(long_polling.buffer_capacity());
+				let (pending_calls, _) = mpsc::channel::<()>(1);
+
+				let cfg = RpcServiceCfg::CallsAndSubscriptions {
+					bounded_subscriptions: long_polling.bounded_subscriptions(),
+					id_provider: this.server_cfg.id_provider.clone(),
+					on_subscribe: this.server_cfg.on_subscribe.clone(),
+					sink: MethodSink::new(tx),
+					// The long-polled subscriptions share a connection ID such that they can be unsubscribed
+					// by another HTTP request.
+					subscription_conn_id: LONG_POLLING_CONN_ID,
+					_pending_calls: pending_calls,
+				};
+				(cfg, Some(rx))
+			} else {
+				(RpcServiceCfg::OnlyCalls, None)
+			};
 
 			let rpc_service = self.rpc_middleware.service(RpcService::new(
-				methods,
+				methods.clone(),
 				max_response_size as usize,
+				None,
+				this.server_cfg.method_timeout,
+				this.server_cfg.on_response.clone(),
+				this.server_cfg.audit_log.clone(),
 				this.conn_id.into(),
-				RpcServiceCfg::OnlyCalls,
+				cfg,
 			));
 
-			Box::pin(async move {
-				let rp =
-					http::call_with_service(request, batch_config, max_request_size, rpc_service, max_response_size)
-						.await;
-				// NOTE: The `conn guard` must be held until the response is processed
-				// to respect the `max_connections` limit.
-				drop(conn);
-				Ok(rp)
-			})
+			let conn_id = this.conn_id.into();
+			let remote_addr = this.remote_addr;
+			let on_connect = this.server_cfg.on_connect.clone();
+			let on_disconnect = this.server_cfg.on_disconnect.clone();
+			let method_runtime = this.server_cfg.method_runtime.clone();
+			let tap = this.server_cfg.on_message.as_ref().map(|hook| hook.tap(conn_id, TransportKind::Http));
+			let draining = this.server_cfg.drain_notice.map(|_| this.stop_handle.clone());
+
+			if let Some(tap) = &tap {
+				request.extensions_mut().insert(tap.clone());
+			}
+			if let Some(long_polling) = &long_polling {
+				request.extensions_mut().insert(long_polling.clone());
+			}
+
+			let call = async move {
+				if let Some(on_connect) = on_connect {
+					let info = ConnectInfo {
+						conn_id,
+						remote_addr,
+						transport: TransportKind::Http,
+						headers: request.headers().clone(),
+					};
+
+					if let Err(rp) = on_connect.call(info).await {
+						return Ok(rp);
+					}
+				}
+
+				// If the request is aborted by the peer this future is dropped
+				// and the hook is invoked with `DisconnectReason::Error`.
+				let mut disconnect_guard =
+					on_disconnect.map(|hook| DisconnectGuard::new(hook, conn_id, remote_addr, TransportKind::Http));
+
+				let rp = match (events, long_polling) {
+					(Some(events), Some(long_polling)) => {
+						http::call_with_long_polling(
+							request,
+							batch_config,
+							max_request_size,
+							&methods,
+							rpc_service,
+							max_response_size,
+							events,
+							&long_polling,
+							conn,
+						)
+						.await
+					}
+					(Some(events), None) => {
+						http::call_with_event_stream(
+							request,
+							batch_config,
+							max_request_size,
+							&methods,
+							rpc_service,
+							max_response_size,
+							events,
+							conn,
+						)
+						.await
+					}
+					(None, _) => {
+						http::call_with_service_and_methods(
+							request,
+							batch_config,
+							batch_execution,
+							batch_response_order,
+							&batch_fail_fast,
+							stream_batch_responses,
+							max_request_size,
+							&methods,
+							rpc_service,
+							max_response_size,
+							// NOTE: The `conn guard` must be held until the response is processed
+							// to respect the `max_connections` limit.
+							conn,
+						)
+						.await
+					}
+				};
+
+				if let Some(guard) = disconnect_guard.as_mut() {
+					guard.complete(DisconnectReason::Closed, 1, 0);
+				}
+
+				// Ask the client to reconnect elsewhere once the server is stopped.
+				let mut rp = rp;
+				if draining.is_some_and(|stop_handle| stop_handle.is_stopped()) {
+					rp.headers_mut()
+						.insert(hyper::header::CONNECTION, hyper::header::HeaderValue::from_static("close"));
+				}
+
+				Ok(match tap {
+					Some(tap) => tap.outbound_body(rp),
+					None => rp,
+				})
+			};
+
+			match method_runtime {
+				Some(rt) => spawn_call(&rt, call).boxed(),
+				None => call.boxed(),
+			}
 		} else {
 			// NOTE: the `conn guard` is dropped when this function which is fine
 			// because it doesn't rely on any async operations.
-			Box::pin(async { Ok(http::response::denied()) })
+			let rp = if is_upgrade_request {
+				http::response::ws_not_supported()
+			} else {
+				http::response::http_not_supported()
+			};
+			Box::pin(async { Ok(rp) })
 		}
 	}
 }
 
-struct ProcessConnection<'a, HttpMiddleware, RpcMiddleware> {
-	http_middleware: &'a tower::ServiceBuilder<HttpMiddleware>,
-	rpc_middleware: RpcServiceBuilder<RpcMiddleware>,
-	conn_guard: &'a ConnectionGuard,
+/// Connection ID of the subscriptions that are long-polled over HTTP.
+const LONG_POLLING_CONN_ID: ConnectionId = ConnectionId(usize::MAX);
+
+/// Name of the method that returns the OpenRPC document of the server.
+const RPC_DISCOVER: &str = "rpc.discover";
+
+/// Name of the method that lists the registered methods of the server.
+const SYSTEM_METHODS: &str = "system_methods";
+
+/// Name of the notification that is sent to the WebSocket connections when the server is stopped,
+/// see [`Builder::set_drain_notice`].
+pub const SHUTDOWN_NOTIFICATION: &str = "system_shuttingDown";
+
+/// Serialize the [`SHUTDOWN_NOTIFICATION`] with the deadline after which the connection is closed.
+pub(crate) fn shutdown_notification(deadline: Duration) -> String {
+	serde_json::json!({
+		"jsonrpc": "2.0",
+		"method": SHUTDOWN_NOTIFICATION,
+		"params": { "deadlineMs": deadline.as_millis() as u64 },
+	})
+	.to_string()
+}
+
+/// Register the `rpc.discover`, long polling and `system_methods` methods if they are enabled.
+pub(crate) fn with_builtin_methods(mut methods: Methods, server_cfg: &ServerConfig) -> Methods {
+	if let Some(info) = server_cfg.rpc_discover.as_ref() {
+		let doc = methods.openrpc(info);
+		let mut discover = RpcModule::new(());
+		discover.register_method(RPC_DISCOVER, move |_, _, _| doc.clone()).expect("RpcModule is empty; qed");
+
+		if methods.merge(discover).is_err() {
+			tracing::warn!(target: LOG_TARGET, "`{}` is already registered, the built-in method is not used", RPC_DISCOVER);
+		}
+	}
+
+	if let Some(long_polling) = server_cfg.long_polling.clone() {
+		if methods.merge(long_polling.into_rpc()).is_err() {
+			tracing::warn!(
+				target: LOG_TARGET,
+				"`{}` is already registered, the built-in method is not used",
+				LONG_POLLING_METHOD
+			);
+		}
+	}
+
+	if server_cfg.methods_introspection {
+		let doc = methods_document(&methods, server_cfg);
+		let mut introspection = RpcModule::new(());
+		introspection.register_method(SYSTEM_METHODS, move |_, _, _| doc.clone()).expect("RpcModule is empty; qed");
+
+		if methods.merge(introspection).is_err() {
+			tracing::warn!(target: LOG_TARGET, "`{}` is already registered, the built-in method is not used", SYSTEM_METHODS);
+		}
+	}
+
+	methods
+}
+
+/// The document returned by `system_methods` which describes the methods that are not hidden.
+///
+/// Aliases are listed with the method they are an alias of, such that a deprecated alias of a renamed method
+/// can be told apart, and the limits fall back to the limits of the server.
+fn methods_document(methods: &Methods, cfg: &ServerConfig) -> serde_json::Value {
+	let no_metadata = MethodMetadata::default();
+	let metadata_of = |name| methods.method_metadata(name).unwrap_or(&no_metadata);
+
+	let mut names = Vec::new();
+	let mut aliases_of: HashMap<&str, Vec<&str>> = HashMap::new();
+
+	for name in methods.method_names() {
+		let metadata = metadata_of(name);
+
+		match metadata.alias_of().filter(|target| methods.method(target).is_some()) {
+			Some(target) => aliases_of.entry(target).or_default().push(name),
+			None if !metadata.is_hidden() => names.push(name),
+			None => (),
+		}
+	}
+	names.sort_unstable();
+
+	let entries: Vec<_> = names
+		.into_iter()
+		.map(|name| {
+			let metadata = metadata_of(name);
+			let config = methods.method_config(name).copied().unwrap_or_default();
+
+			let mut aliases = aliases_of.remove(name).unwrap_or_default();
+			aliases.sort_unstable();
+			let aliases: Vec<_> = aliases
+				.into_iter()
+				.map(|alias| serde_json::json!({ "name": alias, "deprecated": metadata_of(alias).deprecation() }))
+				.collect();
+
+			let kind = match methods.method(name) {
+				Some(MethodCallback::Subscription(_)) => "subscription",
+				Some(MethodCallback::Unsubscription(_)) => "unsubscription",
+				_ => "method",
+			};
+
+			let priority = match methods.response_priority(name) {
+				ResponsePriority::Low => "low",
+				ResponsePriority::Normal => "normal",
+				ResponsePriority::High => "high",
+			};
+
+			let mut entry = serde_json::json!({
+				"name": name,
+				"kind": kind,
+				"aliases": aliases,
+				"deprecated": metadata.deprecation(),
+				"auth": metadata.auth(),
+				"priority": priority,
+				"limits": {
+					"timeoutMs": config.timeout().or(cfg.method_timeout).map(|t| t.as_millis() as u64),
+					"maxRequestSize": config.max_request_size().unwrap_or(cfg.max_request_body_size),
+					"maxResponseSize": config.max_response_size().unwrap_or(cfg.max_response_body_size),
+				},
+			});
+
+			if let Some(sub) = metadata.subscription() {
+				entry["notification"] = sub.notification().into();
+				entry["unsubscribe"] = sub.unsubscribe().into();
+			}
+
+			entry
+		})
+		.collect();
+
+	serde_json::json!({ "methods": entries })
+}
+
+/// Spawn the `call` on the runtime `rt` where the task is aborted once the returned future is dropped.
+fn spawn_call<T: Send + 'static>(
+	rt: &tokio::runtime::Handle,
+	call: impl Future<Output = Result<T, BoxError>> + Send + 'static,
+) -> impl Future<Output = Result<T, BoxError>> {
+	struct AbortOnDrop(tokio::task::AbortHandle);
+
+	impl Drop for AbortOnDrop {
+		fn drop(&mut self) {
+			self.0.abort();
+		}
+	}
+
+	let task = rt.spawn(call);
+	let abort = AbortOnDrop(task.abort_handle());
+
+	async move {
+		let _abort = abort;
+		task.await?
+	}
+}
+
+/// Accept connections on the listener until the server is stopped and serve each
+/// connection with the service created by `make_service`.
+#[allow(clippy::too_many_arguments)]
+async fn accept_connections<F, S, B>(
+	listener: TcpListener,
+	tcp_no_delay: bool,
+	runtime: Option<tokio::runtime::Handle>,
+	middleware: Option<ConnectionMiddleware>,
+	drain_notice: Option<Duration>,
+	stop_handle: StopHandle,
+	mut handoff: mpsc::Receiver<HandoffRequest>,
+	mut make_service: F,
+) where
+	F: FnMut(u32, SocketAddr) -> S,
+	S: Service<HttpRequest, Response = HttpResponse<B>, Error = BoxError> + Clone + Send + 'static,
+	S::Future: Send + 'static,
+	B: http_body::Body<Data = Bytes> + Send + 'static,
+	B::Error: Into<BoxError>,
+	B::Data: Send,
+{
+	let mut id: u32 = 0;
+
+	let stopped = stop_handle.clone().shutdown();
+	tokio::pin!(stopped);
+
+	let (drop_on_completion, mut process_connection_awaiter) = mpsc::channel::<()>(1);
+
+	loop {
+		match try_accept_conn(&listener, stopped, &mut handoff).await {
+			AcceptConnection::Established { socket, remote_addr, stop } => {
+				process_connection(ProcessConnection {
+					service: make_service(id, remote_addr),
+					remote_addr,
+					stop_handle: stop_handle.clone(),
+					conn_id: id,
+					tcp_no_delay,
+					runtime: runtime.clone(),
+					middleware: middleware.clone(),
+					drain_notice,
+					socket,
+					drop_on_completion: drop_on_completion.clone(),
+				});
+				id = id.wrapping_add(1);
+				stopped = stop;
+			}
+			AcceptConnection::Err((e, stop)) => {
+				tracing::debug!(target: LOG_TARGET, "Error while awaiting a new connection: {:?}", e);
+				stopped = stop;
+			}
+			AcceptConnection::Shutdown => break,
+			AcceptConnection::Handoff(tx) => {
+				// The connections that were already accepted are served until they are closed.
+				_ = tx.send(listener.into_std());
+				break;
+			}
+		}
+	}
+
+	// Drop the last Sender
+	drop(drop_on_completion);
+
+	// Once this channel is closed it is safe to assume that all connections have been gracefully shutdown
+	while process_connection_awaiter.recv().await.is_some() {
+		// Generally, messages should not be sent across this channel,
+		// but we'll loop here to wait for `None` just to be on the safe side
+	}
+}
+
+struct ProcessConnection<S> {
+	service: S,
 	conn_id: u32,
-	server_cfg: ServerConfig,
+	tcp_no_delay: bool,
+	runtime: Option<tokio::runtime::Handle>,
+	middleware: Option<ConnectionMiddleware>,
+	drain_notice: Option<Duration>,
 	stop_handle: StopHandle,
 	socket: TcpStream,
 	drop_on_completion: mpsc::Sender<()>,
 	remote_addr: SocketAddr,
-	methods: Methods,
 }
 
 #[instrument(name = "connection", skip_all, fields(remote_addr = %params.remote_addr, conn_id = %params.conn_id), level = "INFO")]
-fn process_connection<'a, RpcMiddleware, HttpMiddleware, Body>(params: ProcessConnection<HttpMiddleware, RpcMiddleware>)
+fn process_connection<S, B>(params: ProcessConnection<S>)
 where
-	RpcMiddleware: 'static,
-	HttpMiddleware: Layer<TowerServiceNoHttp<RpcMiddleware>> + Send + 'static,
-	<HttpMiddleware as Layer<TowerServiceNoHttp<RpcMiddleware>>>::Service:
-		Send + 'static + Clone + Service<HttpRequest, Response = HttpResponse<Body>, Error = BoxError>,
-	<<HttpMiddleware as Layer<TowerServiceNoHttp<RpcMiddleware>>>::Service as Service<HttpRequest>>::Future:
-		Send + 'static,
-	Body: http_body::Body<Data = Bytes> + Send + 'static,
-	<Body as http_body::Body>::Error: Into<BoxError>,
-	<Body as http_body::Body>::Data: Send,
+	S: Service<HttpRequest, Response = HttpResponse<B>, Error = BoxError> + Clone + Send + 'static,
+	S::Future: Send + 'static,
+	B: http_body::Body<Data = Bytes> + Send + 'static,
+	B::Error: Into<BoxError>,
+	B::Data: Send,
 {
 	let ProcessConnection {
-		http_middleware,
-		rpc_middleware,
-		conn_guard,
+		service,
 		conn_id,
-		server_cfg,
+		remote_addr,
+		tcp_no_delay,
+		runtime,
+		middleware,
+		drain_notice,
 		socket,
 		stop_handle,
 		drop_on_completion,
-		methods,
-		..
 	} = params;
 
-	if let Err(e) = socket.set_nodelay(server_cfg.tcp_no_delay) {
+	if let Err(e) = socket.set_nodelay(tcp_no_delay) {
 		tracing::warn!(target: LOG_TARGET, "Could not set NODELAY on socket: {:?}", e);
 		return;
 	}
 
-	let tower_service = TowerServiceNoHttp {
-		inner: ServiceData {
-			server_cfg,
-			methods,
-			stop_handle: stop_handle.clone(),
-			conn_id,
-			conn_guard: conn_guard.clone(),
-		},
-		rpc_middleware,
-		on_session_close: None,
-	};
+	let serve = move |socket: TcpStream| async move {
+		let Some(middleware) = middleware else {
+			serve_connection(service, socket, stop_handle, drain_notice, drop_on_completion).await;
+			return;
+		};
 
-	let service = http_middleware.service(tower_service);
+		// The sessions that are upgraded from the connection hold a guard such that
+		// the middleware sees the connection until the last session is closed.
+		let (session, mut sessions_closed) = SessionGuard::new();
+		let service = service.map_request(move |mut request: HttpRequest| {
+			request.extensions_mut().insert(session.clone());
+			request
+		});
 
-	tokio::spawn(async {
-		// this requires Clone.
-		let service = crate::utils::TowerToHyperService::new(service);
-		let io = TokioIo::new(socket);
-		let builder = hyper_util::server::conn::auto::Builder::new(TokioExecutor::new());
+		let serve = ServeConnection::new(Box::new(move |io| {
+			Box::pin(async move {
+				serve_connection(service, io, stop_handle, drain_notice, drop_on_completion).await;
+				while sessions_closed.recv().await.is_some() {}
+			})
+		}));
 
-		let conn = builder.serve_connection_with_upgrades(io, service);
-		let stopped = stop_handle.shutdown();
+		let conn = Connection { conn_id: conn_id.into(), remote_addr, io: Box::new(socket) };
 
-		tokio::pin!(stopped, conn);
+		if let Err(e) = middleware.service(serve).oneshot(conn).await {
+			tracing::debug!(target: LOG_TARGET, "Connection middleware failed {:?}", e);
+		}
+	};
 
-		let res = match future::select(conn, stopped).await {
-			Either::Left((conn, _)) => conn,
-			Either::Right((_, mut conn)) => {
-				// NOTE: the connection should continue to be polled until shutdown can finish.
-				// Thus, both lines below are needed and not a nit.
-				conn.as_mut().graceful_shutdown();
-				conn.await
-			}
-		};
+	let Some(rt) = runtime else {
+		tokio::spawn(serve(socket));
+		return;
+	};
+
+	// The socket is registered with the I/O driver of the runtime that accepted it,
+	// thus it must be re-registered to be driven by the connection runtime.
+	let socket = match socket.into_std() {
+		Ok(socket) => socket,
+		Err(e) => {
+			tracing::warn!(target: LOG_TARGET, "Could not move socket to the connection runtime: {:?}", e);
+			return;
+		}
+	};
 
-		if let Err(e) = res {
-			tracing::debug!(target: LOG_TARGET, "HTTP serve connection failed {:?}", e);
+	rt.spawn(async move {
+		match TcpStream::from_std(socket) {
+			Ok(socket) => serve(socket).await,
+			Err(e) => tracing::warn!(target: LOG_TARGET, "Could not move socket to the connection runtime: {:?}", e),
 		}
-		drop(drop_on_completion)
 	});
 }
 
+async fn serve_connection<S, B, I>(
+	service: S,
+	io: I,
+	stop_handle: StopHandle,
+	drain_notice: Option<Duration>,
+	drop_on_completion: mpsc::Sender<()>,
+) where
+	I: tokio::io::AsyncRead + tokio::io::AsyncWrite + Send + Unpin + 'static,
+	S: Service<HttpRequest, Response = HttpResponse<B>, Error = BoxError> + Clone + Send + 'static,
+	S::Future: Send + 'static,
+	B: http_body::Body<Data = Bytes> + Send + 'static,
+	B::Error: Into<BoxError>,
+	B::Data: Send,
+{
+	// this requires Clone.
+	let service = crate::utils::TowerToHyperService::new(service);
+	let io = TokioIo::new(io);
+	let builder = hyper_util::server::conn::auto::Builder::new(TokioExecutor::new());
+
+	let conn = builder.serve_connection_with_upgrades(io, service);
+	let stopped = stop_handle.shutdown();
+
+	tokio::pin!(stopped, conn);
+
+	let res = match future::select(conn, stopped).await {
+		Either::Left((conn, _)) => conn,
+		Either::Right((_, mut conn)) => {
+			// The connection is still served until the deadline such that the client can
+			// reconnect elsewhere, the responses are sent with `Connection: close` meanwhile.
+			let drained = match drain_notice {
+				Some(deadline) => tokio::time::timeout(deadline, conn.as_mut()).await.ok(),
+				None => None,
+			};
+
+			match drained {
+				Some(res) => res,
+				None => {
+					// NOTE: the connection should continue to be polled until shutdown can finish.
+					// Thus, both lines below are needed and not a nit.
+					conn.as_mut().graceful_shutdown();
+					conn.await
+				}
+			}
+		}
+	};
+
+	if let Err(e) = res {
+		tracing::debug!(target: LOG_TARGET, "HTTP serve connection failed {:?}", e);
+	}
+	drop(drop_on_completion)
+}
+
 enum AcceptConnection<S> {
 	Shutdown,
+	Handoff(HandoffRequest),
 	Established { socket: TcpStream, remote_addr: SocketAddr, stop: S },
 	Err((std::io::Error, S)),
 }
 
-async fn try_accept_conn<S>(listener: &TcpListener, stopped: S) -> AcceptConnection<S>
+async fn try_accept_conn<S>(
+	listener: &TcpListener,
+	stopped: S,
+	handoff: &mut mpsc::Receiver<HandoffRequest>,
+) -> AcceptConnection<S>
 where
 	S: Future + Unpin,
 {
 	let accept = listener.accept();
 	tokio::pin!(accept);
+	let handoff = handoff.recv();
+	tokio::pin!(handoff);
 
-	match futures_util::future::select(accept, stopped).await {
-		Either::Left((res, stop)) => match res {
+	match futures_util::future::select(futures_util::future::select(accept, handoff), stopped).await {
+		Either::Left((Either::Left((res, _)), stop)) => match res {
 			Ok((socket, remote_addr)) => AcceptConnection::Established { socket, remote_addr, stop },
 			Err(e) => AcceptConnection::Err((e, stop)),
 		},
-		Either::Right(_) => AcceptConnection::Shutdown,
+		Either::Left((Either::Right((Some(tx), _)), _)) => AcceptConnection::Handoff(tx),
+		// All server handles were dropped which stops the server as well.
+		Either::Left((Either::Right((None, _)), _)) | Either::Right(_) => AcceptConnection::Shutdown,
+	}
+}
+
+/// The max request size that the transport must accept which is the
+/// biggest of the server limit and the limits of the individual methods.
+pub(crate) fn transport_max_request_size(methods: &Methods, max_request_size: u32) -> u32 {
+	methods.method_configs().filter_map(|(_, c)| c.max_request_size()).fold(max_request_size, u32::max)
+}
+
+/// Checks the size of a single call or notification against the max request size of its method
+/// before it's parsed, only its method and id are read if it exceeds the limit of the server.
+///
+/// Fails with the response to a call which is too big, or with `None` for a notification
+/// which is too big and dropped.
+fn check_request_size(body: &[u8], methods: &Methods, max_request_size: u32) -> Result<(), Option<MethodResponse>> {
+	#[derive(serde::Deserialize)]
+	struct CallHead<'a> {
+		#[serde(borrow)]
+		method: Cow<'a, str>,
+		#[serde(borrow, default, deserialize_with = "some_id")]
+		id: Option<Id<'a>>,
+	}
+
+	// A `null` id is a call with that id and not a notification.
+	fn some_id<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Option<Id<'de>>, D::Error> {
+		serde::Deserialize::deserialize(deserializer).map(Some)
+	}
+
+	if body.len() <= max_request_size as usize {
+		return Ok(());
+	}
+
+	let head = serde_json::from_slice::<CallHead>(body).ok();
+	let max_request_size = head
+		.as_ref()
+		.and_then(|head| methods.method_config(&head.method))
+		.and_then(|c| c.max_request_size())
+		.unwrap_or(max_request_size);
+
+	if body.len() <= max_request_size as usize {
+		return Ok(());
+	}
+
+	match head {
+		Some(CallHead { id: None, .. }) => Err(None),
+		Some(CallHead { id: Some(id), .. }) => {
+			Err(Some(MethodResponse::error(id.into_owned(), reject_too_big_request(max_request_size))))
+		}
+		None => Err(Some(MethodResponse::error(Id::Null, reject_too_big_request(max_request_size)))),
 	}
 }
 
+#[allow(clippy::too_many_arguments)]
 pub(crate) async fn handle_rpc_call<S>(
 	body: &[u8],
 	is_single: bool,
 	batch_config: BatchRequestConfig,
+	batch_execution: BatchExecution,
+	batch_response_order: BatchResponseOrder,
+	batch_fail_fast: Option<&BatchErrorClass>,
+	max_request_size: u32,
 	max_response_size: u32,
+	methods: &Methods,
 	rpc_service: &S,
-	extensions: Extensions,
+	mut extensions: Extensions,
+	batch_stream: Option<mpsc::Sender<String>>,
 ) -> Option<MethodResponse>
 where
 	for<'a> S: RpcServiceT<'a> + Send,
 {
+	let utf8_policy = extensions.get::<Utf8Policy>().copied().unwrap_or_default();
+	let body = match utf8_policy.validate(body, extensions.get::<Utf8Stats>()) {
+		Ok(body) => body,
+		Err(rp) => return Some(rp),
+	};
+	let body = &*body;
+	let id_policy = extensions.get::<IdPolicy>().copied().unwrap_or_default();
+
 	// Single request or notification
 	if is_single {
-		if let Ok(req) = deserialize::from_slice_with_extensions(body, extensions) {
-			Some(rpc_service.call(req).await)
-		} else if let Ok(_notif) = serde_json::from_slice::<Notif>(body) {
-			None
+		// A JSON-RPC 1.0 request is handled as a 2.0 request whose response is converted back.
+		let v1_request = extensions.get::<JsonRpcV1Compat>().and_then(|_| jsonrpsee_types::v1::request_to_v2(body));
+		let body = v1_request.as_deref().unwrap_or(body);
+		if v1_request.is_some() {
+			// The chunks of a streamed response are written as is.
+			extensions.remove::<StreamedResponseSupport>();
+		}
+
+		let rp = match check_request_size(body, methods, max_request_size) {
+			Err(rp) => rp,
+			Ok(()) => match id_policy.parse_call(body, extensions) {
+				Ok((mut req, raw_id)) => {
+					let id = req.id.clone();
+					if raw_id.is_some() {
+						// The id of a streamed response isn't echoed as it was sent.
+						req.extensions_mut().remove::<StreamedResponseSupport>();
+					}
+					Some(echo_raw_id(rpc_service.call(req).await, &id, raw_id))
+				}
+				Err(InvalidId::Rejected(reason)) => InvalidId::Rejected(reason).into_response(),
+				Err(InvalidId::NotACall) if serde_json::from_slice::<Notif>(body).is_ok() => None,
+				Err(InvalidId::NotACall) => {
+					let (id, code) = prepare_error(body);
+					Some(MethodResponse::error(id, ErrorObject::from(code)))
+				}
+			},
+		};
+
+		if v1_request.is_some() {
+			rp.map(MethodResponse::into_json_rpc_v1)
 		} else {
-			let (id, code) = prepare_error(body);
-			Some(MethodResponse::error(id, ErrorObject::from(code)))
+			rp
 		}
 	}
 	// Batch of requests.
 	else {
+		let policy = extensions.get::<BatchPolicy>().copied();
+		let batch_config = policy.map_or(batch_config, |p| p.config);
+		let max_batch_response_size = policy.and_then(|p| p.max_response_size).unwrap_or(max_response_size);
+
 		let max_len = match batch_config {
 			BatchRequestConfig::Disabled => {
 				let rp = MethodResponse::error(
@@ -1307,47 +3663,179 @@ where
 			BatchRequestConfig::Unlimited => usize::MAX,
 		};
 
-		if let Ok(batch) = serde_json::from_slice::<Vec<&JsonRawValue>>(body) {
-			if batch.len() > max_len {
-				return Some(MethodResponse::error(Id::Null, reject_too_big_batch_request(max_len)));
-			}
+		// The transport limit may have been raised by some method
+		// but that doesn't apply to batches.
+		if body.len() > max_request_size as usize {
+			return Some(MethodResponse::error(Id::Null, reject_too_big_request(max_request_size)));
+		}
 
-			let mut got_notif = false;
-			let mut batch_response = BatchResponseBuilder::new_with_limit(max_response_size as usize);
+		if let Ok(mut batch) = serde_json::from_slice::<Vec<&JsonRawValue>>(body) {
+			let overflow = if batch.len() > max_len {
+				match extensions.get::<BatchOverflow>().copied().unwrap_or_default() {
+					BatchOverflow::Reject => {
+						return Some(MethodResponse::error(Id::Null, reject_too_big_batch_request(max_len)))
+					}
+					BatchOverflow::ProcessFirst => batch.split_off(max_len),
+				}
+			} else {
+				Vec::new()
+			};
 
-			for call in batch {
-				if let Ok(req) = deserialize::from_str_with_extensions(call.get(), extensions.clone()) {
-					let rp = rpc_service.call(req).await;
+			let mut got_notif = false;
+			let mut batch_response = match batch_stream {
+				Some(tx) => BatchSink::Streamed { tx, is_empty: true },
+				None => BatchSink::Buffered(BatchResponseBuilder::new_with_limit(max_batch_response_size as usize)),
+			};
 
-					if let Err(too_large) = batch_response.append(&rp) {
-						return Some(too_large);
+			let calls: Vec<_> = batch
+				.iter()
+				.enumerate()
+				.map(|(idx, &call)| {
+					let extensions = extensions.clone();
+
+					async move {
+						let rp = match id_policy.parse_call(call.get().as_bytes(), extensions) {
+							Ok((req, raw_id)) => {
+								let id = req.id.clone();
+								Some(echo_raw_id(rpc_service.call(req).await, &id, raw_id))
+							}
+							Err(InvalidId::Rejected(reason)) => InvalidId::Rejected(reason).into_response(),
+							// notifications should not be answered.
+							Err(InvalidId::NotACall) if serde_json::from_str::<Notif>(call.get()).is_ok() => None,
+							Err(InvalidId::NotACall) => {
+								// valid JSON but could be not parsable as `InvalidRequest`
+								let id = match serde_json::from_str::<InvalidRequest>(call.get()) {
+									Ok(err) => err.id,
+									Err(_) => Id::Null,
+								};
+
+								Some(MethodResponse::error(id, ErrorObject::from(ErrorCode::InvalidRequest)))
+							}
+						};
+
+						(idx, rp)
 					}
-				} else if let Ok(_notif) = serde_json::from_str::<Notif>(call.get()) {
-					// notifications should not be answered.
+				})
+				.collect();
+
+			let calls = stream::iter(calls);
+			let max_concurrency = batch_execution.max_concurrency();
+			let mut responses = match batch_response_order {
+				BatchResponseOrder::Request => calls.buffered(max_concurrency).left_stream(),
+				BatchResponseOrder::Completion => calls.buffer_unordered(max_concurrency).right_stream(),
+			};
+
+			let mut processed = vec![false; batch.len()];
+
+			while let Some((idx, rp)) = responses.next().await {
+				processed[idx] = true;
+
+				let Some(rp) = rp else {
 					got_notif = true;
-				} else {
-					// valid JSON but could be not parsable as `InvalidRequest`
-					let id = match serde_json::from_str::<InvalidRequest>(call.get()) {
-						Ok(err) => err.id,
-						Err(_) => Id::Null,
-					};
+					continue;
+				};
 
-					if let Err(too_large) =
-						batch_response.append(&MethodResponse::error(id, ErrorObject::from(ErrorCode::InvalidRequest)))
-					{
-						return Some(too_large);
-					}
+				if let Err(too_large) = batch_response.append(&rp).await {
+					return Some(too_large);
+				}
+
+				if batch_fail_fast.zip(rp.as_error_code()).is_some_and(|(errors, code)| errors.contains(code)) {
+					break;
 				}
 			}
 
-			if got_notif && batch_response.is_empty() {
-				None
-			} else {
-				let batch_rp = batch_response.finish();
-				Some(MethodResponse::from_batch(batch_rp))
+			// Abort the calls that are still running and answer the remaining
+			// calls if the batch was aborted.
+			drop(responses);
+
+			for (call, _) in batch.iter().zip(processed).filter(|(_, processed)| !processed) {
+				let id = match serde_json::from_str::<InvalidRequest>(call.get()) {
+					Ok(req) => req.id,
+					// notifications should not be answered.
+					Err(_) if serde_json::from_str::<Notif>(call.get()).is_ok() => continue,
+					Err(_) => Id::Null,
+				};
+
+				let err = ErrorObject::borrowed(BATCH_CALL_NOT_PROCESSED_CODE, BATCH_CALL_NOT_PROCESSED_MSG, None);
+				if let Err(too_large) = batch_response.append(&MethodResponse::error(id, err)).await {
+					return Some(too_large);
+				}
+			}
+
+			// Answer the calls beyond the max length of the batch.
+			for call in overflow {
+				let id = match serde_json::from_str::<InvalidRequest>(call.get()) {
+					Ok(req) => req.id,
+					// notifications should not be answered.
+					Err(_) if serde_json::from_str::<Notif>(call.get()).is_ok() => continue,
+					Err(_) => Id::Null,
+				};
+
+				let rp = MethodResponse::error(id, reject_too_big_batch_request(max_len));
+				if let Err(too_large) = batch_response.append(&rp).await {
+					return Some(too_large);
+				}
 			}
+
+			batch_response.finish(got_notif).await
 		} else {
 			Some(MethodResponse::error(Id::Null, ErrorObject::from(ErrorCode::ParseError)))
 		}
 	}
 }
+
+/// Marker in the extensions of a request that JSON-RPC 1.0 requests are accepted,
+/// see [`Builder::set_json_rpc_v1_compat`].
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct JsonRpcV1Compat;
+
+/// Echo the id of the call as it was sent if it's different from the id of the request.
+fn echo_raw_id(rp: MethodResponse, id: &Id, raw_id: Option<&str>) -> MethodResponse {
+	match raw_id {
+		Some(raw_id) => rp.with_raw_id(id, raw_id),
+		None => rp,
+	}
+}
+
+/// Where the responses of the calls of a batch request are written to.
+enum BatchSink {
+	/// The responses are collected into a single batch response which is limited to the max response size.
+	Buffered(BatchResponseBuilder),
+	/// The responses are sent out one by one as soon as they are ready.
+	Streamed { tx: mpsc::Sender<String>, is_empty: bool },
+}
+
+impl BatchSink {
+	/// Append the response of a call of the batch.
+	///
+	/// Fails if the batch response became too big.
+	async fn append(&mut self, rp: &MethodResponse) -> Result<(), MethodResponse> {
+		match self {
+			Self::Buffered(batch_response) => batch_response.append(rp),
+			Self::Streamed { tx, is_empty } => {
+				let separator = if std::mem::take(is_empty) { '[' } else { ',' };
+				// The receiver is only dropped if the response is not sent anymore.
+				_ = tx.send(format!("{separator}{}", rp.as_result())).await;
+				Ok(())
+			}
+		}
+	}
+
+	/// Finish the batch response.
+	///
+	/// Returns the response that is still to be sent, if any.
+	async fn finish(self, got_notif: bool) -> Option<MethodResponse> {
+		match self {
+			Self::Buffered(batch_response) if got_notif && batch_response.is_empty() => None,
+			Self::Buffered(batch_response) => Some(MethodResponse::from_batch(batch_response.finish())),
+			Self::Streamed { is_empty: true, .. } if got_notif => None,
+			Self::Streamed { is_empty: true, .. } => {
+				Some(MethodResponse::error(Id::Null, ErrorObject::from(ErrorCode::InvalidRequest)))
+			}
+			Self::Streamed { tx, is_empty: false } => {
+				_ = tx.send("]".to_string()).await;
+				None
+			}
+		}
+	}
+}
@@ -25,14 +25,30 @@
 // DEALINGS IN THE SOFTWARE.
 
 use std::net::SocketAddr;
-
-use crate::{BatchRequestConfig, RegisterMethodError, RpcModule, ServerBuilder, ServerHandle};
-use jsonrpsee_core::RpcResult;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::middleware::http::{HandshakeFilterLayer, HandshakeRejection};
+use crate::middleware::rpc::{
+	ModuleLayer, ParamsValidationLayer, PriorityAdmissionLayer, RequestDedupLayer, ResponseCacheLayer, ResponseFuture,
+	RpcServiceBuilder, RpcServiceT,
+};
+use crate::{
+	AuditOutcome, BatchErrorClass, BatchExecution, BatchFailFast, BatchOverflow, BatchPolicy, BatchRequestConfig,
+	BatchResponseOrder, CallerIdentity, IdPolicy, LongPollingConfig, PeerInfo, RegisterMethodError, Router, RpcModule,
+	ServerBuilder, ServerHandle, TransportKind, BATCH_FAIL_FAST_HEADER, LONG_POLLING_METHOD,
+};
+use jsonrpsee_core::server::{
+	CancellationToken, ConnectionId, MethodResponse, ResponseExtensions, ResponsePriority, SubscriptionMessage,
+};
+use jsonrpsee_core::{Limits, RpcResult, StringError};
 use jsonrpsee_test_utils::helpers::*;
-use jsonrpsee_test_utils::mocks::{Id, StatusCode};
+use jsonrpsee_test_utils::mocks::{Id, StatusCode, WebSocketTestClient, WebSocketTestError};
 use jsonrpsee_test_utils::TimeoutFutureExt;
 use jsonrpsee_types::ErrorObjectOwned;
 use serde_json::Value as JsonValue;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
 use super::helpers::{MyAppError, TestContext};
 
@@ -154,7 +170,7 @@ async fn single_method_call_with_multiple_params_of_different_types() {
 async fn single_method_call_with_faulty_params_returns_err() {
 	let (addr, _handle) = server().with_default_timeout().await.unwrap();
 	let uri = to_http_uri(addr);
-	let expected = r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32602,"message":"Invalid params","data":"invalid type: string \"this should be a number\", expected u64 at line 1 column 26"}}"#;
+	let expected = r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32602,"message":"Invalid params","data":{"position":0,"message":"invalid type: string \"this should be a number\", expected u64 at line 1 column 26"}}}"#;
 
 	let req = r#"{"jsonrpc":"2.0","method":"add", "params":["this should be a number"],"id":1}"#;
 	let response = http_request(req.into(), uri).with_default_timeout().await.unwrap().unwrap();
@@ -565,14 +581,1635 @@ async fn batch_limit_works() {
 }
 
 #[tokio::test]
-async fn http2_method_call_works() {
-	init_logger();
+async fn limits_profile_works() {
+	let mut limits = Limits::strict();
+	limits.max_request_size = 200;
+	limits.max_batch_len = Some(1);
 
-	let (addr, _handle) = server().with_default_timeout().await.unwrap();
+	let server = ServerBuilder::default().with_limits(limits).build("127.0.0.1:0").await.unwrap();
+	let mut module = RpcModule::new(());
+	module.register_method("should_ok", |_, _ctx, _| "ok").unwrap();
+	let addr = server.local_addr().unwrap();
 	let uri = to_http_uri(addr);
+	let handle = server.start(module);
 
-	let req = r#"{"jsonrpc":"2.0","method":"add", "params":[1, 2],"id":1}"#;
-	let response = http2_request(req.into(), uri).with_default_timeout().await.unwrap().unwrap();
-	assert_eq!(response.status, StatusCode::OK);
-	assert_eq!(response.body, ok_response(JsonValue::Number(3.into()), Id::Num(1)));
+	let req = format!(r#"{{"jsonrpc":"2.0", "method":"{}", "id":1}}"#, "a".repeat(200));
+	let response = http_request(req.into(), uri.clone()).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.body, oversized_request(200));
+
+	let req = r#"[
+		{"jsonrpc":"2.0","method":"should_ok", "params":[],"id":1},
+		{"jsonrpc":"2.0","method":"should_ok", "params":[],"id":2}
+	]"#;
+	let response = http_request(req.into(), uri.clone()).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.body, batches_too_large(1));
+
+	let req = r#"{"jsonrpc":"2.0","method":"should_ok", "params":[],"id":1}"#;
+	let response = http_request(req.into(), uri).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.body, ok_response("ok".into(), Id::Num(1)));
+
+	handle.stop().unwrap();
+	handle.stopped().await;
+}
+
+#[tokio::test]
+async fn batch_overflow_process_first_works() {
+	let server = ServerBuilder::default()
+		.set_batch_request_config(BatchRequestConfig::Limit(2))
+		.set_batch_overflow(BatchOverflow::ProcessFirst)
+		.build("127.0.0.1:0")
+		.await
+		.unwrap();
+	let mut module = RpcModule::new(());
+	module.register_method("should_ok", |_, _ctx, _| "ok").unwrap();
+	let addr = server.local_addr().unwrap();
+	let uri = to_http_uri(addr);
+	let handle = server.start(module);
+
+	let req = r#"[
+		{"jsonrpc":"2.0","method":"should_ok","params":[],"id":1},
+		{"jsonrpc":"2.0","method":"should_ok","params":[],"id":2},
+		{"jsonrpc":"2.0","method":"should_ok","params":[],"id":3},
+		{"jsonrpc":"2.0","method":"should_ok","params":[]},
+		{"jsonrpc":"2.0","method":"should_ok","params":[],"id":"four"}
+	]"#;
+	let response = http_request(req.into(), uri.clone()).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(
+		response.body,
+		r#"[{"jsonrpc":"2.0","id":1,"result":"ok"},{"jsonrpc":"2.0","id":2,"result":"ok"},{"jsonrpc":"2.0","id":3,"error":{"code":-32010,"message":"The batch request was too large","data":"Exceeded max limit of 2"}},{"jsonrpc":"2.0","id":"four","error":{"code":-32010,"message":"The batch request was too large","data":"Exceeded max limit of 2"}}]"#
+	);
+
+	// A batch within the limit is processed as usual.
+	let req = r#"[{"jsonrpc":"2.0","method":"should_ok","params":[],"id":1}]"#;
+	let response = http_request(req.into(), uri).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.body, r#"[{"jsonrpc":"2.0","id":1,"result":"ok"}]"#);
+
+	handle.stop().unwrap();
+	handle.stopped().await;
+}
+
+async fn run_batch_with_execution(execution: BatchExecution, order: BatchResponseOrder) -> (String, usize) {
+	let server = ServerBuilder::default()
+		.set_batch_execution(execution)
+		.set_batch_response_order(order)
+		.build("127.0.0.1:0")
+		.await
+		.unwrap();
+	let running = Arc::new(AtomicUsize::new(0));
+	let max_running = Arc::new(AtomicUsize::new(0));
+	let mut module = RpcModule::new((running, max_running.clone()));
+	module
+		.register_async_method("sleep_for", |params, ctx, _| async move {
+			let ms: u64 = params.one().unwrap();
+			let (running, max_running) = &*ctx;
+			let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+			max_running.fetch_max(now, Ordering::SeqCst);
+			tokio::time::sleep(Duration::from_millis(ms)).await;
+			running.fetch_sub(1, Ordering::SeqCst);
+			ms
+		})
+		.unwrap();
+	let uri = to_http_uri(server.local_addr().unwrap());
+	let handle = server.start(module);
+
+	let req = r#"[
+		{"jsonrpc":"2.0","method":"sleep_for","params":[200],"id":1},
+		{"jsonrpc":"2.0","method":"sleep_for","params":[100],"id":2},
+		{"jsonrpc":"2.0","method":"sleep_for","params":[50],"id":3},
+		{"jsonrpc":"2.0","method":"sleep_for","params":[10],"id":4}
+	]"#;
+	let response = http_request(req.into(), uri).with_default_timeout().await.unwrap().unwrap();
+
+	handle.stop().unwrap();
+	handle.stopped().await;
+
+	(response.body, max_running.load(Ordering::SeqCst))
+}
+
+#[tokio::test]
+async fn batch_execution_works() {
+	init_logger();
+
+	let expected = r#"[{"jsonrpc":"2.0","id":1,"result":200},{"jsonrpc":"2.0","id":2,"result":100},{"jsonrpc":"2.0","id":3,"result":50},{"jsonrpc":"2.0","id":4,"result":10}]"#;
+
+	let (body, max_running) = run_batch_with_execution(BatchExecution::Sequential, BatchResponseOrder::Request).await;
+	assert_eq!(body, expected);
+	assert_eq!(max_running, 1);
+
+	let (body, max_running) =
+		run_batch_with_execution(BatchExecution::Concurrent(2), BatchResponseOrder::Request).await;
+	assert_eq!(body, expected);
+	assert_eq!(max_running, 2);
+}
+
+#[tokio::test]
+async fn batch_response_order_works() {
+	init_logger();
+
+	let expected = r#"[{"jsonrpc":"2.0","id":4,"result":10},{"jsonrpc":"2.0","id":3,"result":50},{"jsonrpc":"2.0","id":2,"result":100},{"jsonrpc":"2.0","id":1,"result":200}]"#;
+
+	let (body, max_running) =
+		run_batch_with_execution(BatchExecution::Concurrent(4), BatchResponseOrder::Completion).await;
+	assert_eq!(body, expected);
+	assert_eq!(max_running, 4);
+}
+
+#[tokio::test]
+async fn batch_fail_fast_works() {
+	init_logger();
+
+	let server = ServerBuilder::default()
+		.set_batch_fail_fast(BatchFailFast::OptIn(BatchErrorClass::Codes(-32099..=-32000)))
+		.build("127.0.0.1:0")
+		.await
+		.unwrap();
+	let mut module = RpcModule::new(());
+	module.register_method("should_ok", |_, _ctx, _| "ok").unwrap();
+	module
+		.register_method("should_err", |_, _ctx, _| Err::<(), _>(ErrorObjectOwned::owned(-32000, "err", None::<()>)))
+		.unwrap();
+	let uri = to_http_uri(server.local_addr().unwrap());
+	let handle = server.start(module);
+
+	let req = r#"[
+		{"jsonrpc":"2.0","method":"should_ok","id":1},
+		{"jsonrpc":"2.0","method":"unknown_method","id":2},
+		{"jsonrpc":"2.0","method":"should_err","id":3},
+		{"jsonrpc":"2.0","method":"should_ok","id":4},
+		{"jsonrpc":"2.0","method":"should_ok"}
+	]"#;
+
+	// The batch didn't opt in so all calls are processed.
+	let response = http_request(req.into(), uri.clone()).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(
+		response.body,
+		r#"[{"jsonrpc":"2.0","id":1,"result":"ok"},{"jsonrpc":"2.0","id":2,"error":{"code":-32601,"message":"Method not found"}},{"jsonrpc":"2.0","id":3,"error":{"code":-32000,"message":"err"}},{"jsonrpc":"2.0","id":4,"result":"ok"}]"#
+	);
+
+	// Method not found is not in the class of errors that aborts the batch.
+	let mut headers = hyper::HeaderMap::new();
+	headers.insert(BATCH_FAIL_FAST_HEADER, hyper::header::HeaderValue::from_static("true"));
+	let response = http_request_with_headers(req.into(), uri, headers).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(
+		response.body,
+		r#"[{"jsonrpc":"2.0","id":1,"result":"ok"},{"jsonrpc":"2.0","id":2,"error":{"code":-32601,"message":"Method not found"}},{"jsonrpc":"2.0","id":3,"error":{"code":-32000,"message":"err"}},{"jsonrpc":"2.0","id":4,"error":{"code":-32013,"message":"The call was not processed because a previous call of the batch failed"}}]"#
+	);
+
+	handle.stop().unwrap();
+	handle.stopped().await;
+}
+
+#[tokio::test]
+async fn batch_response_streaming_works() {
+	init_logger();
+
+	// Set the max response size to 100 bytes
+	let server = ServerBuilder::default()
+		.max_response_body_size(100)
+		.enable_batch_response_streaming()
+		.build("127.0.0.1:0")
+		.await
+		.unwrap();
+	let mut module = RpcModule::new(());
+	module.register_method("anything", |_p, _cx, _| "a".repeat(51)).unwrap();
+	let uri = to_http_uri(server.local_addr().unwrap());
+	let handle = server.start(module);
+
+	// The batch response is bigger than the max response size but it's not buffered.
+	let req = r#"[{"jsonrpc":"2.0", "method":"anything", "id":1},{"jsonrpc":"2.0", "method":"anything"},{"jsonrpc":"2.0", "method":"anything", "id":2}]"#;
+	let response = http_request(req.into(), uri.clone()).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.header.get(hyper::header::TRANSFER_ENCODING).unwrap(), "chunked");
+	let a = "a".repeat(51);
+	assert_eq!(
+		response.body,
+		format!(r#"[{{"jsonrpc":"2.0","id":1,"result":"{a}"}},{{"jsonrpc":"2.0","id":2,"result":"{a}"}}]"#)
+	);
+
+	// Batch of notifications.
+	let req = r#"[{"jsonrpc":"2.0", "method":"anything"}]"#;
+	let response = http_request(req.into(), uri.clone()).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.body, "");
+
+	// Empty batch.
+	let response = http_request("[]".into(), uri).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.body, invalid_request(Id::Null));
+
+	handle.stop().unwrap();
+	handle.stopped().await;
+}
+
+#[tokio::test]
+async fn streaming_method_works() {
+	init_logger();
+
+	let server = ServerBuilder::default().max_response_body_size(150).build("127.0.0.1:0").await.unwrap();
+	let mut module = RpcModule::new(());
+	module
+		.register_streaming_method("numbers", |_p, _cx, _| async {
+			Ok::<_, ErrorObjectOwned>(futures_util::stream::iter(0..100))
+		})
+		.unwrap();
+	module
+		.register_streaming_method("fails", |_p, _cx, _| async {
+			Err::<futures_util::stream::Empty<u8>, _>(ErrorObjectOwned::owned(-32000, "Not ready", None::<()>))
+		})
+		.unwrap();
+	let uri = to_http_uri(server.local_addr().unwrap());
+	let handle = server.start(module);
+
+	// A single call is streamed and isn't limited by the max response size.
+	let req = r#"{"jsonrpc":"2.0","method":"numbers","id":1}"#;
+	let response = http_request(req.into(), uri.clone()).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.header.get(hyper::header::TRANSFER_ENCODING).unwrap(), "chunked");
+	let numbers: Vec<_> = (0..100).collect();
+	assert_eq!(response.body, ok_response(numbers.into(), Id::Num(1)));
+
+	// In a batch the response is buffered.
+	let req = r#"[{"jsonrpc":"2.0","method":"numbers","id":1}]"#;
+	let response = http_request(req.into(), uri.clone()).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.body, format!("[{}]", oversized_response(Id::Num(1), 150)));
+
+	let req = r#"{"jsonrpc":"2.0","method":"fails","id":1}"#;
+	let response = http_request(req.into(), uri).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.body, r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32000,"message":"Not ready"}}"#);
+
+	handle.stop().unwrap();
+	handle.stopped().await;
+}
+
+#[tokio::test]
+async fn router_works() {
+	init_logger();
+
+	let mut public = RpcModule::new(());
+	public.register_method("say_hello", |_, _, _| "public").unwrap();
+	let mut admin = RpcModule::new(());
+	admin.register_method("say_hello", |_, _, _| "admin").unwrap();
+
+	let router = Router::new()
+		.route("/public", ServerBuilder::default().max_request_body_size(100).to_service_builder(), public)
+		.route("/admin", ServerBuilder::default().to_service_builder(), admin)
+		.route("/ws", ServerBuilder::default().ws_only().to_service_builder(), RpcModule::new(()));
+	let server = ServerBuilder::default().build("127.0.0.1:0").await.unwrap();
+	let addr = server.local_addr().unwrap();
+	let handle = server.start_with_router(router);
+
+	let uri = |path: &str| format!("http://{addr}{path}").parse::<hyper::Uri>().unwrap();
+
+	let req = r#"{"jsonrpc":"2.0","method":"say_hello","id":1}"#;
+	let response = http_request(req.into(), uri("/public")).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.body, ok_response("public".into(), Id::Num(1)));
+	let response = http_request(req.into(), uri("/admin")).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.body, ok_response("admin".into(), Id::Num(1)));
+
+	// The limits are configured per route.
+	let req = format!(r#"{{"jsonrpc":"2.0","method":"say_hello","params":["{}"],"id":1}}"#, "a".repeat(100));
+	let response = http_request(req.clone().into(), uri("/public")).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.status, StatusCode::PAYLOAD_TOO_LARGE);
+	let response = http_request(req.into(), uri("/admin")).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.body, ok_response("admin".into(), Id::Num(1)));
+
+	let req = r#"{"jsonrpc":"2.0","method":"say_hello","id":1}"#;
+	let response = http_request(req.into(), uri("/")).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.status, StatusCode::NOT_FOUND);
+
+	// The transports are restricted per route.
+	let response = http_request(req.into(), uri("/ws")).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.status, StatusCode::FORBIDDEN);
+	assert_eq!(response.body, "Only WebSocket connections are accepted, HTTP requests are not supported\n");
+
+	handle.stop().unwrap();
+	handle.stopped().await;
+}
+
+#[tokio::test]
+async fn router_handoff_works() {
+	init_logger();
+
+	let mut old_module = RpcModule::new(());
+	old_module.register_method("version", |_, _, _| "old").unwrap();
+	let router = Router::new().route("/rpc", ServerBuilder::default().to_service_builder(), old_module);
+	let server = ServerBuilder::default().build("127.0.0.1:0").await.unwrap();
+	let addr = server.local_addr().unwrap();
+	let old = server.start_with_router(router);
+
+	let listener = old.handoff().with_default_timeout().await.unwrap().unwrap();
+	// The old server has no connections to drain.
+	old.clone().stopped().with_default_timeout().await.unwrap();
+
+	let mut new_module = RpcModule::new(());
+	new_module.register_method("version", |_, _, _| "new").unwrap();
+	let router = Router::new().route("/rpc", ServerBuilder::default().to_service_builder(), new_module);
+	let new = ServerBuilder::default().build_from_tcp(listener).unwrap().start_with_router(router);
+
+	let uri = format!("http://{addr}/rpc").parse::<hyper::Uri>().unwrap();
+	let req = r#"{"jsonrpc":"2.0","method":"version","id":1}"#;
+	let response = http_request(req.into(), uri).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.body, ok_response("new".into(), Id::Num(1)));
+
+	new.stop().unwrap();
+	new.stopped().await;
+}
+
+#[tokio::test]
+async fn sse_subscription_works() {
+	init_logger();
+
+	let server = ServerBuilder::default().enable_sse_subscriptions().build("127.0.0.1:0").await.unwrap();
+	let mut module = RpcModule::new(());
+	module
+		.register_subscription::<Result<(), StringError>, _, _>(
+			"subscribe_numbers",
+			"numbers",
+			"unsubscribe_numbers",
+			|_, pending, _, _| async move {
+				let sink = pending.accept().await?;
+
+				for n in 0..3 {
+					sink.send(SubscriptionMessage::from_json(&n)?).await?;
+				}
+
+				Ok(())
+			},
+		)
+		.unwrap();
+	module.register_method("say_hello", |_, _, _| "hello").unwrap();
+	let uri = to_http_uri(server.local_addr().unwrap());
+	let handle = server.start(module);
+
+	let mut headers = hyper::HeaderMap::new();
+	headers.insert(hyper::header::ACCEPT, hyper::header::HeaderValue::from_static("text/event-stream"));
+
+	let req = r#"{"jsonrpc":"2.0","method":"subscribe_numbers","id":1}"#;
+	let response = http_request_with_headers(req.into(), uri.clone(), headers.clone())
+		.with_default_timeout()
+		.await
+		.unwrap()
+		.unwrap();
+	assert_eq!(response.header.get(hyper::header::CONTENT_TYPE).unwrap(), "text/event-stream");
+
+	let events: Vec<JsonValue> = response
+		.body
+		.split_terminator("\n\n")
+		.map(|event| serde_json::from_str(event.strip_prefix("data: ").unwrap()).unwrap())
+		.collect();
+	assert_eq!(events.len(), 4);
+	let sub_id = events[0]["result"].clone();
+	for (n, event) in events[1..].iter().enumerate() {
+		assert_eq!(event["method"], "numbers");
+		assert_eq!(event["params"]["subscription"], sub_id);
+		assert_eq!(event["params"]["result"], n);
+	}
+
+	// A method call is answered with a single event.
+	let req = r#"{"jsonrpc":"2.0","method":"say_hello","id":1}"#;
+	let response =
+		http_request_with_headers(req.into(), uri.clone(), headers).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.body, format!("data: {}\n\n", ok_response("hello".into(), Id::Num(1))));
+
+	// Subscriptions aren't supported without server-sent events.
+	let req = r#"{"jsonrpc":"2.0","method":"subscribe_numbers","id":1}"#;
+	let response = http_request(req.into(), uri).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.body, internal_error(Id::Num(1)));
+
+	handle.stop().unwrap();
+	handle.stopped().await;
+}
+
+#[tokio::test]
+async fn long_polling_works() {
+	init_logger();
+
+	let server = ServerBuilder::default()
+		.enable_long_polling(LongPollingConfig::new().poll_timeout(Duration::from_secs(5)))
+		.build("127.0.0.1:0")
+		.await
+		.unwrap();
+	let mut module = RpcModule::new(());
+	module
+		.register_subscription::<Result<(), StringError>, _, _>(
+			"subscribe_numbers",
+			"numbers",
+			"unsubscribe_numbers",
+			|_, pending, _, _| async move {
+				let sink = pending.accept().await?;
+
+				for n in 0..3 {
+					sink.send(SubscriptionMessage::from_json(&n)?).await?;
+				}
+				sink.closed().await;
+
+				Ok(())
+			},
+		)
+		.unwrap();
+	let uri = to_http_uri(server.local_addr().unwrap());
+	let handle = server.start(module);
+
+	let call = |method: &str, params: JsonValue| {
+		let req = serde_json::json!({ "jsonrpc": "2.0", "method": method, "params": params, "id": 1 }).to_string();
+		let uri = uri.clone();
+		async move {
+			let response = http_request(req.into(), uri).with_default_timeout().await.unwrap().unwrap();
+			serde_json::from_str::<JsonValue>(&response.body).unwrap()
+		}
+	};
+
+	let sub_id = call("subscribe_numbers", serde_json::json!([])).await["result"].clone();
+
+	let mut notifs = Vec::new();
+	while notifs.len() < 3 {
+		let rp = call(LONG_POLLING_METHOD, serde_json::json!([sub_id])).await;
+		notifs.extend(rp["result"].as_array().unwrap().iter().cloned());
+	}
+	for (n, notif) in notifs.iter().enumerate() {
+		assert_eq!(notif["method"], "numbers");
+		assert_eq!(notif["params"]["subscription"], sub_id);
+		assert_eq!(notif["params"]["result"], n);
+	}
+
+	// The subscription is closed by another HTTP request.
+	assert_eq!(call("unsubscribe_numbers", serde_json::json!([sub_id])).await["result"], true);
+	assert_eq!(call(LONG_POLLING_METHOD, serde_json::json!([sub_id])).await["result"], serde_json::json!([]));
+
+	// The drained subscription is removed.
+	let rp = call(LONG_POLLING_METHOD, serde_json::json!([sub_id])).await;
+	assert_eq!(rp["error"]["message"], "Unknown subscription");
+
+	handle.stop().unwrap();
+	handle.stopped().await;
+}
+
+#[tokio::test]
+async fn long_polled_subscriptions_are_bound_to_the_caller() {
+	init_logger();
+
+	let http_middleware = tower::ServiceBuilder::new().map_request(|mut req: crate::HttpRequest| {
+		if let Some(user) = req.headers().get("x-user").and_then(|v| v.to_str().ok()) {
+			let identity = CallerIdentity(user.to_owned());
+			req.extensions_mut().insert(identity);
+		}
+		req
+	});
+	let server = ServerBuilder::default()
+		.set_http_middleware(http_middleware)
+		.enable_long_polling(LongPollingConfig::new().poll_timeout(Duration::from_millis(100)))
+		.build("127.0.0.1:0")
+		.await
+		.unwrap();
+	let mut module = RpcModule::new(());
+	module
+		.register_subscription::<Result<(), StringError>, _, _>(
+			"subscribe_numbers",
+			"numbers",
+			"unsubscribe_numbers",
+			|_, pending, _, _| async move {
+				let sink = pending.accept().await?;
+				sink.closed().await;
+				Ok(())
+			},
+		)
+		.unwrap();
+	let uri = to_http_uri(server.local_addr().unwrap());
+	let handle = server.start(module);
+
+	let call = |user: &'static str, method: &str, params: JsonValue| {
+		let req = serde_json::json!({ "jsonrpc": "2.0", "method": method, "params": params, "id": 1 }).to_string();
+		let uri = uri.clone();
+		async move {
+			let mut headers = hyper::HeaderMap::new();
+			headers.insert("x-user", hyper::header::HeaderValue::from_static(user));
+			let response =
+				http_request_with_headers(req.into(), uri, headers).with_default_timeout().await.unwrap().unwrap();
+			serde_json::from_str::<JsonValue>(&response.body).unwrap()
+		}
+	};
+
+	let sub_id = call("alice", "subscribe_numbers", serde_json::json!([])).await["result"].clone();
+
+	let rp = call("bob", LONG_POLLING_METHOD, serde_json::json!([sub_id])).await;
+	assert_eq!(rp["error"]["message"], "Unknown subscription");
+	assert_eq!(call("bob", "unsubscribe_numbers", serde_json::json!([sub_id])).await["result"], false);
+
+	let rp = call("alice", LONG_POLLING_METHOD, serde_json::json!([sub_id])).await;
+	assert_eq!(rp["result"], serde_json::json!([]));
+	assert_eq!(call("alice", "unsubscribe_numbers", serde_json::json!([sub_id])).await["result"], true);
+
+	handle.stop().unwrap();
+	handle.stopped().await;
+}
+
+#[tokio::test]
+async fn idle_long_polled_subscriptions_are_closed() {
+	init_logger();
+
+	let server = ServerBuilder::default()
+		.enable_long_polling(LongPollingConfig::new().idle_timeout(Duration::from_millis(100)))
+		.build("127.0.0.1:0")
+		.await
+		.unwrap();
+	let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+	let mut module = RpcModule::new(tx);
+	module
+		.register_subscription::<Result<(), StringError>, _, _>(
+			"subscribe_numbers",
+			"numbers",
+			"unsubscribe_numbers",
+			|_, pending, tx, _| async move {
+				let sink = pending.accept().await?;
+				sink.closed().await;
+				tx.send(()).unwrap();
+				Ok(())
+			},
+		)
+		.unwrap();
+	let uri = to_http_uri(server.local_addr().unwrap());
+	let handle = server.start(module);
+
+	// The subscription is closed without any other request.
+	let req = r#"{"jsonrpc":"2.0","method":"subscribe_numbers","id":1}"#;
+	http_request(req.into(), uri).with_default_timeout().await.unwrap().unwrap();
+	rx.recv().with_default_timeout().await.unwrap().unwrap();
+
+	handle.stop().unwrap();
+	handle.stopped().await;
+}
+
+#[tokio::test]
+async fn long_polled_subscriptions_are_not_closed_while_polled() {
+	init_logger();
+
+	let config =
+		LongPollingConfig::new().poll_timeout(Duration::from_millis(500)).idle_timeout(Duration::from_millis(100));
+	let server = ServerBuilder::default().enable_long_polling(config).build("127.0.0.1:0").await.unwrap();
+	let mut module = RpcModule::new(());
+	module
+		.register_subscription::<Result<(), StringError>, _, _>(
+			"subscribe_numbers",
+			"numbers",
+			"unsubscribe_numbers",
+			|_, pending, _, _| async move {
+				let sink = pending.accept().await?;
+				sink.closed().await;
+				Ok(())
+			},
+		)
+		.unwrap();
+	let uri = to_http_uri(server.local_addr().unwrap());
+	let handle = server.start(module);
+
+	let call = |method: &str, params: JsonValue| {
+		let req = serde_json::json!({ "jsonrpc": "2.0", "method": method, "params": params, "id": 1 }).to_string();
+		let uri = uri.clone();
+		async move {
+			let response = http_request(req.into(), uri).with_default_timeout().await.unwrap().unwrap();
+			serde_json::from_str::<JsonValue>(&response.body).unwrap()
+		}
+	};
+
+	let sub_id = call("subscribe_numbers", serde_json::json!([])).await["result"].clone();
+
+	// The poll outlasts several idle timeouts.
+	assert_eq!(call(LONG_POLLING_METHOD, serde_json::json!([sub_id])).await["result"], serde_json::json!([]));
+	assert_eq!(call("unsubscribe_numbers", serde_json::json!([sub_id])).await["result"], true);
+
+	handle.stop().unwrap();
+	handle.stopped().await;
+}
+
+#[tokio::test]
+async fn long_polling_keeps_the_connection_id_of_calls() {
+	init_logger();
+
+	let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+	let server = ServerBuilder::default()
+		.enable_long_polling(LongPollingConfig::new())
+		.set_on_response(move |info, rp| {
+			tx.send(info.conn_id).unwrap();
+			rp
+		})
+		.build("127.0.0.1:0")
+		.await
+		.unwrap();
+	let mut module = RpcModule::new(());
+	module.register_method("conn_id", |_, _, ext| ext.get::<ConnectionId>().unwrap().0).unwrap();
+	let uri = to_http_uri(server.local_addr().unwrap());
+	let handle = server.start(module);
+
+	let req = r#"{"jsonrpc":"2.0","method":"conn_id","id":1}"#;
+	let response = http_request(req.into(), uri).with_default_timeout().await.unwrap().unwrap();
+	let conn_id = rx.recv().await.unwrap();
+	assert_ne!(conn_id, ConnectionId(usize::MAX));
+	assert_eq!(response.body, ok_response(conn_id.0.into(), Id::Num(1)));
+
+	handle.stop().unwrap();
+	handle.stopped().await;
+}
+
+#[tokio::test]
+async fn http2_method_call_works() {
+	init_logger();
+
+	let (addr, _handle) = server().with_default_timeout().await.unwrap();
+	let uri = to_http_uri(addr);
+
+	let req = r#"{"jsonrpc":"2.0","method":"add", "params":[1, 2],"id":1}"#;
+	let response = http2_request(req.into(), uri).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.status, StatusCode::OK);
+	assert_eq!(response.body, ok_response(JsonValue::Number(3.into()), Id::Num(1)));
+}
+
+#[tokio::test]
+async fn method_timeout_works() {
+	init_logger();
+
+	let server =
+		ServerBuilder::default().set_method_timeout(Duration::from_millis(50)).build("127.0.0.1:0").await.unwrap();
+	let mut module = RpcModule::new(());
+	module
+		.register_async_method("sleep", |_, _, _| async {
+			tokio::time::sleep(Duration::from_millis(500)).await;
+			"done"
+		})
+		.unwrap();
+	module.register_alias("sleep_long_timeout", "sleep").unwrap();
+	module.set_method_timeout("sleep_long_timeout", Duration::from_secs(5)).unwrap();
+	let addr = server.local_addr().unwrap();
+	let uri = to_http_uri(addr);
+	let handle = server.start(module);
+
+	let req = r#"{"jsonrpc":"2.0","method":"sleep","id":1}"#;
+	let response = http_request(req.into(), uri.clone()).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(
+		response.body,
+		r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32012,"message":"Method call timed out","data":"Exceeded max execution time of 50ms"}}"#
+	);
+
+	// The per-method timeout overrides the server default.
+	let req = r#"{"jsonrpc":"2.0","method":"sleep_long_timeout","id":2}"#;
+	let response = http_request(req.into(), uri).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.body, ok_response("done".into(), Id::Num(2)));
+
+	handle.stop().unwrap();
+	handle.stopped().await;
+}
+
+#[tokio::test]
+async fn method_size_limits_works() {
+	init_logger();
+
+	let server = ServerBuilder::default().max_request_body_size(100).build("127.0.0.1:0").await.unwrap();
+	let mut module = RpcModule::new(());
+	module.register_method("echo", |params, _, _| params.one::<String>().unwrap()).unwrap();
+	module.register_alias("echo_big", "echo").unwrap();
+	module.set_method_max_request_size("echo_big", 1000).unwrap();
+	module.register_alias("echo_small_response", "echo").unwrap();
+	module.set_method_max_response_size("echo_small_response", 30).unwrap();
+	let addr = server.local_addr().unwrap();
+	let uri = to_http_uri(addr);
+	let handle = server.start(module);
+
+	let big = "a".repeat(200);
+
+	// The server limit still applies to methods without an override.
+	let req = format!(r#"{{"jsonrpc":"2.0","method":"echo","params":["{big}"],"id":1}}"#);
+	let response = http_request(req.into(), uri.clone()).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(
+		response.body,
+		r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32007,"message":"Request is too big","data":"Exceeded max limit of 100"}}"#
+	);
+
+	let req = format!(r#"{{"jsonrpc":"2.0","method":"echo_big","params":["{big}"],"id":2}}"#);
+	let response = http_request(req.into(), uri.clone()).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.body, ok_response(big.as_str().into(), Id::Num(2)));
+
+	// Too big notifications are dropped and too big messages are rejected before they're parsed.
+	let req = format!(r#"{{"jsonrpc":"2.0","method":"echo","params":["{big}"]}}"#);
+	let response = http_request(req.into(), uri.clone()).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.body, "");
+
+	let req = format!(r#"{{"jsonrpc":"2.0","method":"echo_big","params":["{big}"],"id":"#);
+	let response = http_request(req.into(), uri.clone()).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.body, oversized_request(100));
+
+	// Batches are bounded by the server limit.
+	let req = format!(r#"[{{"jsonrpc":"2.0","method":"echo_big","params":["{big}"],"id":3}}]"#);
+	let response = http_request(req.into(), uri.clone()).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.body, oversized_request(100));
+
+	let req = r#"{"jsonrpc":"2.0","method":"echo_small_response","params":["hello"],"id":4}"#;
+	let response = http_request(req.into(), uri).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.body, oversized_response(Id::Num(4), 30));
+
+	handle.stop().unwrap();
+	handle.stopped().await;
+}
+
+#[tokio::test]
+async fn aborted_request_cancels_the_call() {
+	init_logger();
+
+	let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+	let server = ServerBuilder::default().build("127.0.0.1:0").await.unwrap();
+	let mut module = RpcModule::new(tx);
+	module
+		.register_async_method("sleep", |_, tx, ext| async move {
+			let token = ext.get::<CancellationToken>().cloned().unwrap();
+			tokio::spawn(async move {
+				token.cancelled().await;
+				tx.send(()).unwrap();
+			});
+			futures_util::future::pending::<()>().await;
+		})
+		.unwrap();
+	let addr = server.local_addr().unwrap();
+	let uri = to_http_uri(addr);
+	let handle = server.start(module);
+
+	let req = r#"{"jsonrpc":"2.0","method":"sleep","id":1}"#;
+	let call = tokio::spawn(http_request(req.into(), uri));
+	tokio::time::sleep(Duration::from_millis(100)).await;
+	call.abort();
+
+	assert!(rx.recv().with_default_timeout().await.unwrap().is_some());
+
+	handle.stop().unwrap();
+	handle.stopped().await;
+}
+
+#[tokio::test]
+async fn peer_info_is_in_extensions() {
+	init_logger();
+
+	let server = ServerBuilder::default().build("127.0.0.1:0").with_default_timeout().await.unwrap().unwrap();
+	let mut module = RpcModule::new(());
+	module
+		.register_method("peer", |_, _, ext| {
+			let peer = ext.get::<PeerInfo>().unwrap();
+			assert_eq!(peer.transport, TransportKind::Http);
+			assert_eq!(Some(peer.conn_id), ext.get::<ConnectionId>().copied());
+			peer.remote_addr.unwrap().ip().to_string()
+		})
+		.unwrap();
+	let addr = server.local_addr().unwrap();
+	let uri = to_http_uri(addr);
+	let _handle = server.start(module);
+
+	let req = r#"{"jsonrpc":"2.0","method":"peer","id":1}"#;
+	let response = http_request(req.into(), uri).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.body, ok_response("127.0.0.1".into(), Id::Num(1)));
+}
+
+#[tokio::test]
+async fn response_cache_works() {
+	init_logger();
+
+	let cache = ResponseCacheLayer::new(1024).cache_method("genesis", Duration::from_secs(60));
+	let handle = cache.cache();
+	let server = ServerBuilder::default()
+		.set_rpc_middleware(RpcServiceBuilder::new().layer(cache))
+		.build("127.0.0.1:0")
+		.with_default_timeout()
+		.await
+		.unwrap()
+		.unwrap();
+	let calls = Arc::new(AtomicUsize::new(0));
+	let mut module = RpcModule::new(calls.clone());
+	module.register_method("genesis", |_, calls, _| calls.fetch_add(1, Ordering::SeqCst)).unwrap();
+	module.register_method("uncached", |_, calls, _| calls.fetch_add(1, Ordering::SeqCst)).unwrap();
+	let addr = server.local_addr().unwrap();
+	let uri = to_http_uri(addr);
+	let _handle = server.start(module);
+
+	let req = r#"{"jsonrpc":"2.0","method":"genesis","params":{"a":1,"b":2},"id":1}"#;
+	let response = http_request(req.into(), uri.clone()).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.body, ok_response(0.into(), Id::Num(1)));
+
+	// Equivalent parameters are served from the cache with the id of the new request.
+	let req = r#"{"jsonrpc":"2.0","method":"genesis","params":{ "b": 2, "a": 1 },"id":2}"#;
+	let response = http_request(req.into(), uri.clone()).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.body, ok_response(0.into(), Id::Num(2)));
+	assert_eq!(calls.load(Ordering::SeqCst), 1);
+	assert_eq!(handle.len(), 1);
+
+	let req = r#"{"jsonrpc":"2.0","method":"uncached","id":3}"#;
+	let response = http_request(req.into(), uri.clone()).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.body, ok_response(1.into(), Id::Num(3)));
+	assert_eq!(handle.len(), 1);
+
+	handle.invalidate("genesis");
+	assert!(handle.is_empty());
+
+	let req = r#"{"jsonrpc":"2.0","method":"genesis","params":{"a":1,"b":2},"id":4}"#;
+	let response = http_request(req.into(), uri).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.body, ok_response(2.into(), Id::Num(4)));
+}
+
+#[tokio::test]
+async fn response_cache_is_per_caller() {
+	init_logger();
+
+	let cache = ResponseCacheLayer::new(1024).cache_method("balance", Duration::from_secs(60));
+	let handle = cache.cache();
+	let http_middleware = tower::ServiceBuilder::new().map_request(|mut req: crate::HttpRequest| {
+		if let Some(user) = req.headers().get("x-user").and_then(|v| v.to_str().ok()) {
+			let identity = CallerIdentity(user.to_owned());
+			req.extensions_mut().insert(identity);
+		}
+		req
+	});
+	let server = ServerBuilder::default()
+		.set_http_middleware(http_middleware)
+		.set_rpc_middleware(RpcServiceBuilder::new().layer(cache))
+		.build("127.0.0.1:0")
+		.with_default_timeout()
+		.await
+		.unwrap()
+		.unwrap();
+	let mut module = RpcModule::new(());
+	module
+		.register_method("balance", |_, _, ext| ext.get::<CallerIdentity>().map(|c| c.0.clone()).unwrap_or_default())
+		.unwrap();
+	let addr = server.local_addr().unwrap();
+	let uri = to_http_uri(addr);
+	let _handle = server.start(module);
+
+	for (id, user) in [(1, "alice"), (2, "bob"), (3, "alice")] {
+		let req = format!(r#"{{"jsonrpc":"2.0","method":"balance","id":{id}}}"#);
+		let mut headers = hyper::HeaderMap::new();
+		headers.insert("x-user", hyper::header::HeaderValue::from_static(user));
+		let response =
+			http_request_with_headers(req.into(), uri.clone(), headers).with_default_timeout().await.unwrap().unwrap();
+		assert_eq!(response.body, ok_response(user.into(), Id::Num(id)));
+	}
+	assert_eq!(handle.len(), 2);
+	assert_eq!(handle.size(), r#""alice""#.len() + r#""bob""#.len());
+
+	handle.invalidate_call("balance", None);
+	assert!(handle.is_empty());
+	assert_eq!(handle.size(), 0);
+}
+
+#[tokio::test]
+async fn request_dedup_works() {
+	init_logger();
+
+	let server = ServerBuilder::default()
+		.set_rpc_middleware(RpcServiceBuilder::new().layer(RequestDedupLayer::new().dedup_method("expensive")))
+		.build("127.0.0.1:0")
+		.with_default_timeout()
+		.await
+		.unwrap()
+		.unwrap();
+	let calls = Arc::new(AtomicUsize::new(0));
+	let mut module = RpcModule::new(calls.clone());
+	module
+		.register_async_method("expensive", |_, calls, _| async move {
+			let n = calls.fetch_add(1, Ordering::SeqCst);
+			tokio::time::sleep(Duration::from_millis(200)).await;
+			n
+		})
+		.unwrap();
+	let addr = server.local_addr().unwrap();
+	let uri = to_http_uri(addr);
+	let _handle = server.start(module);
+
+	let requests = (1..=5).map(|id| {
+		let req = format!(r#"{{"jsonrpc":"2.0","method":"expensive","params":[1],"id":{id}}}"#);
+		http_request(req.into(), uri.clone())
+	});
+	let responses = futures_util::future::join_all(requests).with_default_timeout().await.unwrap();
+	for (id, response) in (1..=5).zip(responses) {
+		assert_eq!(response.unwrap().body, ok_response(0.into(), Id::Num(id)));
+	}
+	assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+	// Calls that are not in-flight are executed again.
+	let req = r#"{"jsonrpc":"2.0","method":"expensive","params":[1],"id":6}"#;
+	let response = http_request(req.into(), uri).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.body, ok_response(1.into(), Id::Num(6)));
+}
+
+#[tokio::test]
+async fn request_dedup_promotes_a_waiting_call_if_the_leader_is_dropped() {
+	init_logger();
+
+	let server = ServerBuilder::default()
+		.set_rpc_middleware(RpcServiceBuilder::new().layer(RequestDedupLayer::new().dedup_method("expensive")))
+		.build("127.0.0.1:0")
+		.with_default_timeout()
+		.await
+		.unwrap()
+		.unwrap();
+	let calls = Arc::new(AtomicUsize::new(0));
+	let mut module = RpcModule::new(calls.clone());
+	module
+		.register_async_method("expensive", |_, calls, _| async move {
+			let n = calls.fetch_add(1, Ordering::SeqCst);
+			tokio::time::sleep(Duration::from_millis(200)).await;
+			n
+		})
+		.unwrap();
+	let addr = server.local_addr().unwrap();
+	let uri = to_http_uri(addr);
+	let _handle = server.start(module);
+
+	let req = r#"{"jsonrpc":"2.0","method":"expensive","params":[1],"id":1}"#;
+	let leader = tokio::spawn(http_request(req.into(), uri.clone()));
+	tokio::time::sleep(Duration::from_millis(50)).await;
+
+	let req = r#"{"jsonrpc":"2.0","method":"expensive","params":[1],"id":2}"#;
+	let waiters = futures_util::future::join_all((0..2).map(|_| http_request(req.into(), uri.clone())));
+	let waiters = tokio::spawn(waiters);
+	tokio::time::sleep(Duration::from_millis(50)).await;
+
+	// The connection of the leader is closed while it executes the method handler.
+	leader.abort();
+
+	let responses = waiters.with_default_timeout().await.unwrap().unwrap();
+	for response in responses {
+		assert_eq!(response.unwrap().body, ok_response(1.into(), Id::Num(2)));
+	}
+	assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn priority_admission_works() {
+	init_logger();
+
+	let order = Arc::new(Mutex::new(Vec::new()));
+	let mut module = RpcModule::new(order.clone());
+	module
+		.register_async_method("bulk", |_, order, _| async move {
+			order.lock().unwrap().push("bulk");
+			tokio::time::sleep(Duration::from_millis(100)).await;
+		})
+		.unwrap();
+	module
+		.register_method("health", |_, order, _| {
+			order.lock().unwrap().push("health");
+		})
+		.unwrap();
+	module.set_response_priority("health", ResponsePriority::High).unwrap();
+
+	let server = ServerBuilder::default()
+		.set_rpc_middleware(RpcServiceBuilder::new().layer(PriorityAdmissionLayer::new(&module, 1)))
+		.build("127.0.0.1:0")
+		.with_default_timeout()
+		.await
+		.unwrap()
+		.unwrap();
+	let uri = to_http_uri(server.local_addr().unwrap());
+	let _handle = server.start(module);
+
+	// The bulk calls saturate the server and the health check arrives last.
+	let mut calls = Vec::new();
+	for method in ["bulk", "bulk", "bulk", "health"] {
+		let req = format!(r#"{{"jsonrpc":"2.0","method":"{method}","id":1}}"#);
+		calls.push(tokio::spawn(http_request(req.into(), uri.clone())));
+		tokio::time::sleep(Duration::from_millis(20)).await;
+	}
+	for call in calls {
+		assert_eq!(call.with_default_timeout().await.unwrap().unwrap().unwrap().status, StatusCode::OK);
+	}
+
+	assert_eq!(*order.lock().unwrap(), ["bulk", "health", "bulk", "bulk"]);
+}
+
+#[tokio::test]
+async fn params_validation_works() {
+	init_logger();
+
+	let mut module = RpcModule::new(());
+	module.register_method("transfer", |_, _, _| "ok").unwrap();
+	module
+		.set_method_params_schema(
+			"transfer",
+			serde_json::json!({
+				"type": "object",
+				"properties": { "to": { "type": "string" }, "amount": { "type": "integer", "minimum": 1 } },
+				"required": ["to", "amount"]
+			}),
+		)
+		.unwrap();
+	module.register_method("unchecked", |_, _, _| "ok").unwrap();
+
+	let server = ServerBuilder::default()
+		.set_rpc_middleware(RpcServiceBuilder::new().layer(ParamsValidationLayer::new(&module)))
+		.build("127.0.0.1:0")
+		.with_default_timeout()
+		.await
+		.unwrap()
+		.unwrap();
+	let addr = server.local_addr().unwrap();
+	let uri = to_http_uri(addr);
+	let _handle = server.start(module);
+
+	let req = r#"{"jsonrpc":"2.0","method":"transfer","params":{"to":"bob","amount":1},"id":1}"#;
+	let response = http_request(req.into(), uri.clone()).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.body, ok_response("ok".into(), Id::Num(1)));
+
+	let req = r#"{"jsonrpc":"2.0","method":"transfer","params":{"amount":0},"id":2}"#;
+	let response = http_request(req.into(), uri.clone()).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(
+		response.body,
+		r#"{"jsonrpc":"2.0","id":2,"error":{"code":-32602,"message":"Invalid params","data":[{"path":"","message":"missing required property `to`"},{"path":"/amount","message":"expected a number >= 1"}]}}"#
+	);
+
+	let req = r#"{"jsonrpc":"2.0","method":"unchecked","params":[1],"id":3}"#;
+	let response = http_request(req.into(), uri).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.body, ok_response("ok".into(), Id::Num(3)));
+}
+
+#[tokio::test]
+async fn params_validation_follows_references() {
+	init_logger();
+
+	let mut module = RpcModule::new(());
+	module.register_method("transfer", |_, _, _| "ok").unwrap();
+	// Schemas which use unsupported keywords or references outside of the schema are rejected.
+	assert!(module
+		.set_method_params_schema("transfer", serde_json::json!({ "type": "string", "pattern": "^0x" }))
+		.is_err());
+	assert!(module
+		.set_method_params_schema("transfer", serde_json::json!({ "$ref": "#/components/schemas/Transfer" }))
+		.is_err());
+	module
+		.set_method_params_schema(
+			"transfer",
+			serde_json::json!({
+				"type": "array",
+				"prefixItems": [{ "$ref": "#/$defs/Transfer" }],
+				"$defs": {
+					"Transfer": {
+						"type": "object",
+						"properties": { "amount": { "type": "integer", "format": "uint64", "minimum": 1 } },
+						"required": ["amount"]
+					}
+				}
+			}),
+		)
+		.unwrap();
+
+	let server = ServerBuilder::default()
+		.set_rpc_middleware(RpcServiceBuilder::new().layer(ParamsValidationLayer::new(&module)))
+		.build("127.0.0.1:0")
+		.with_default_timeout()
+		.await
+		.unwrap()
+		.unwrap();
+	let addr = server.local_addr().unwrap();
+	let uri = to_http_uri(addr);
+	let _handle = server.start(module);
+
+	let req = r#"{"jsonrpc":"2.0","method":"transfer","params":[{"amount":1}],"id":1}"#;
+	let response = http_request(req.into(), uri.clone()).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.body, ok_response("ok".into(), Id::Num(1)));
+
+	let req = r#"{"jsonrpc":"2.0","method":"transfer","params":[{"amount":0}],"id":2}"#;
+	let response = http_request(req.into(), uri).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(
+		response.body,
+		r#"{"jsonrpc":"2.0","id":2,"error":{"code":-32602,"message":"Invalid params","data":[{"path":"/0/amount","message":"expected a number >= 1"}]}}"#
+	);
+}
+
+#[tokio::test]
+async fn module_layer_only_applies_to_module() {
+	init_logger();
+
+	#[derive(Clone)]
+	struct Deny<S>(S);
+
+	impl<'a, S: RpcServiceT<'a>> RpcServiceT<'a> for Deny<S> {
+		type Future = ResponseFuture<S::Future>;
+
+		fn call(&self, request: jsonrpsee_types::Request<'a>) -> Self::Future {
+			let err = ErrorObjectOwned::owned(-32001, "Unauthorized", None::<()>);
+			ResponseFuture::ready(jsonrpsee_core::server::MethodResponse::error(request.id, err))
+		}
+	}
+
+	let mut admin = RpcModule::new(());
+	admin.register_method("admin_restart", |_, _, _| "ok").unwrap();
+	let mut module = RpcModule::new(());
+	module.register_method("say_hello", |_, _, _| "hello").unwrap();
+
+	let rpc_middleware = RpcServiceBuilder::new().layer(ModuleLayer::new(&admin, tower::layer::layer_fn(Deny)));
+	module.merge(admin).unwrap();
+
+	let server = ServerBuilder::default()
+		.set_rpc_middleware(rpc_middleware)
+		.build("127.0.0.1:0")
+		.with_default_timeout()
+		.await
+		.unwrap()
+		.unwrap();
+	let uri = to_http_uri(server.local_addr().unwrap());
+	let _handle = server.start(module);
+
+	let req = r#"{"jsonrpc":"2.0","method":"admin_restart","id":1}"#;
+	let response = http_request(req.into(), uri.clone()).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.body, r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32001,"message":"Unauthorized"}}"#);
+
+	let req = r#"{"jsonrpc":"2.0","method":"say_hello","id":2}"#;
+	let response = http_request(req.into(), uri).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.body, ok_response("hello".into(), Id::Num(2)));
+}
+
+#[tokio::test]
+async fn on_response_hook_works() {
+	init_logger();
+
+	let mut module = RpcModule::new(());
+	module.register_method("say_hello", |_, _, _| "hello").unwrap();
+	module
+		.register_method::<Result<(), ErrorObjectOwned>, _>("fail", |_, _, _| {
+			Err(ErrorObjectOwned::owned(-32603, "Internal error", Some("database connection lost")))
+		})
+		.unwrap();
+
+	let server = ServerBuilder::default()
+		.set_on_response(|info, rp| {
+			rp.map_error(|err| ErrorObjectOwned::owned(err.code(), err.message().to_owned(), Some(&info.method)))
+		})
+		.build("127.0.0.1:0")
+		.with_default_timeout()
+		.await
+		.unwrap()
+		.unwrap();
+	let addr = server.local_addr().unwrap();
+	let uri = to_http_uri(addr);
+	let _handle = server.start(module);
+
+	let req = r#"{"jsonrpc":"2.0","method":"say_hello","id":1}"#;
+	let response = http_request(req.into(), uri.clone()).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.body, ok_response("hello".into(), Id::Num(1)));
+
+	let req = r#"[{"jsonrpc":"2.0","method":"fail","id":"a"},{"jsonrpc":"2.0","method":"unknown","id":2}]"#;
+	let response = http_request(req.into(), uri).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(
+		response.body,
+		r#"[{"jsonrpc":"2.0","id":"a","error":{"code":-32603,"message":"Internal error","data":"fail"}},{"jsonrpc":"2.0","id":2,"error":{"code":-32601,"message":"Method not found","data":"unknown"}}]"#
+	);
+}
+
+#[tokio::test]
+async fn audit_log_works() {
+	init_logger();
+
+	let mut module = RpcModule::new(());
+	module.register_method("say_hello", |_, _, _| "hello").unwrap();
+	module
+		.register_method::<Result<(), ErrorObjectOwned>, _>("fail", |_, _, _| {
+			Err(ErrorObjectOwned::owned(-32000, "Failed", None::<()>))
+		})
+		.unwrap();
+
+	let http_middleware = tower::ServiceBuilder::new().map_request(|mut req: crate::HttpRequest| {
+		if let Some(user) = req.headers().get("x-user").and_then(|v| v.to_str().ok()) {
+			let identity = CallerIdentity(user.to_owned());
+			req.extensions_mut().insert(identity);
+		}
+		req
+	});
+
+	let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+	let server = ServerBuilder::default()
+		.set_http_middleware(http_middleware)
+		.set_audit_log(16, move |record| {
+			let tx = tx.clone();
+			async move {
+				tx.send(record).unwrap();
+			}
+		})
+		.build("127.0.0.1:0")
+		.with_default_timeout()
+		.await
+		.unwrap()
+		.unwrap();
+	let addr = server.local_addr().unwrap();
+	let uri = to_http_uri(addr);
+	let _handle = server.start(module);
+
+	let req = r#"{"jsonrpc":"2.0","method":"say_hello","params":[1],"id":1}"#;
+	let mut headers = hyper::HeaderMap::new();
+	headers.insert("x-user", hyper::header::HeaderValue::from_static("alice"));
+	let response =
+		http_request_with_headers(req.into(), uri.clone(), headers).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.body, ok_response("hello".into(), Id::Num(1)));
+
+	let req = r#"[{"jsonrpc":"2.0","method":"fail","params":[1],"id":2},{"jsonrpc":"2.0","method":"unknown","id":3}]"#;
+	http_request(req.into(), uri).with_default_timeout().await.unwrap().unwrap();
+
+	let mut records = Vec::new();
+	for _ in 0..3 {
+		records.push(rx.recv().with_default_timeout().await.unwrap().unwrap());
+	}
+	records.sort_by(|a, b| a.method.cmp(&b.method));
+
+	assert_eq!(records[0].method, "fail");
+	assert_eq!(records[0].caller, None);
+	assert_eq!(records[0].outcome, AuditOutcome::Error(-32000));
+	assert_eq!(records[1].method, "say_hello");
+	assert_eq!(records[1].caller, Some(CallerIdentity("alice".to_owned())));
+	assert_eq!(records[1].outcome, AuditOutcome::Success);
+	assert_eq!(records[1].params_digest, records[0].params_digest);
+	assert_eq!(records[2].method, "unknown");
+	assert_eq!(records[2].outcome, AuditOutcome::Error(-32601));
+	assert_ne!(records[2].params_digest, records[0].params_digest);
+}
+
+#[tokio::test]
+async fn audit_log_with_slow_sink_does_not_stall_calls() {
+	init_logger();
+
+	let mut module = RpcModule::new(());
+	module.register_method("say_hello", |_, _, _| "hello").unwrap();
+
+	let server = ServerBuilder::default()
+		.set_audit_log(1, |_| futures_util::future::pending())
+		.build("127.0.0.1:0")
+		.with_default_timeout()
+		.await
+		.unwrap()
+		.unwrap();
+	let addr = server.local_addr().unwrap();
+	let uri = to_http_uri(addr);
+	let _handle = server.start(module);
+
+	for id in 0..5 {
+		let req = format!(r#"{{"jsonrpc":"2.0","method":"say_hello","id":{id}}}"#);
+		let response = http_request(req.into(), uri.clone()).with_default_timeout().await.unwrap().unwrap();
+		assert_eq!(response.body, ok_response("hello".into(), Id::Num(id)));
+	}
+}
+
+#[test]
+fn audit_log_outside_of_a_runtime() {
+	use jsonrpsee_core::server::{ConnectionId, MethodResponse, ResponsePayload};
+
+	let (tx, rx) = std::sync::mpsc::channel();
+	let log = crate::hooks::AuditLog::new(16, move |record| {
+		let tx = tx.clone();
+		async move { tx.send(record.method).unwrap() }
+	});
+	let audit = |method: &str| {
+		let req = format!(r#"{{"jsonrpc":"2.0","method":"{method}","id":1}}"#);
+		let req: jsonrpsee_types::Request = serde_json::from_str(&req).unwrap();
+		let rp = MethodResponse::response(req.id(), ResponsePayload::success("ok"), usize::MAX);
+		log.begin(ConnectionId(0), &req).finish(&rp);
+	};
+
+	// The record is kept in the queue until the log is used within a runtime.
+	audit("outside");
+	let rt = tokio::runtime::Runtime::new().unwrap();
+	rt.block_on(async { audit("inside") });
+
+	let methods: Vec<_> = (0..2).map(|_| rx.recv_timeout(Duration::from_secs(60)).unwrap()).collect();
+	assert_eq!(methods, ["outside", "inside"]);
+}
+
+#[tokio::test]
+async fn batch_policy_from_middleware_works() {
+	init_logger();
+
+	let http_middleware = tower::ServiceBuilder::new().map_request(|mut req: crate::HttpRequest| {
+		let policy = if req.headers().contains_key("authorization") {
+			BatchPolicy::new(BatchRequestConfig::Limit(3)).max_response_size(100)
+		} else {
+			BatchPolicy::new(BatchRequestConfig::Disabled)
+		};
+		req.extensions_mut().insert(policy);
+		req
+	});
+
+	let mut module = RpcModule::new(());
+	module.register_method("say_hello", |_, _, _| "hello").unwrap();
+
+	let server = ServerBuilder::default()
+		.set_batch_request_config(BatchRequestConfig::Limit(1))
+		.set_http_middleware(http_middleware)
+		.build("127.0.0.1:0")
+		.with_default_timeout()
+		.await
+		.unwrap()
+		.unwrap();
+	let addr = server.local_addr().unwrap();
+	let uri = to_http_uri(addr);
+	let _handle = server.start(module);
+
+	let call = |id: u32| format!(r#"{{"jsonrpc":"2.0","method":"say_hello","id":{id}}}"#);
+	let batch = |len: u32| format!("[{}]", (0..len).map(call).collect::<Vec<_>>().join(","));
+	let mut headers = hyper::HeaderMap::new();
+	headers.insert(hyper::header::AUTHORIZATION, hyper::header::HeaderValue::from_static("secret"));
+	let authorized = |body: String| {
+		let (uri, headers) = (uri.clone(), headers.clone());
+		async move { http_request_with_headers(body.into(), uri, headers).with_default_timeout().await.unwrap().unwrap() }
+	};
+
+	let response = http_request(batch(1).into(), uri.clone()).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.body, batches_not_supported());
+
+	let response = authorized(batch(2)).await;
+	assert_eq!(
+		response.body,
+		r#"[{"jsonrpc":"2.0","id":0,"result":"hello"},{"jsonrpc":"2.0","id":1,"result":"hello"}]"#
+	);
+
+	let response = authorized(batch(4)).await;
+	assert_eq!(response.body, batches_too_large(3));
+
+	let response = authorized(batch(3)).await;
+	assert_eq!(response.body, batch_response_too_large(100));
+}
+
+#[tokio::test]
+async fn id_policy_works() {
+	init_logger();
+
+	let mut module = RpcModule::new(());
+	module.register_method("say_hello", |_, _, _| "hello").unwrap();
+
+	let start = |policy: IdPolicy| {
+		let module = module.clone();
+		async move {
+			let server = ServerBuilder::default()
+				.set_id_policy(policy)
+				.build("127.0.0.1:0")
+				.with_default_timeout()
+				.await
+				.unwrap()
+				.unwrap();
+			let uri = to_http_uri(server.local_addr().unwrap());
+			(uri, server.start(module))
+		}
+	};
+	let call = |id: &str| format!(r#"{{"jsonrpc":"2.0","method":"say_hello","id":{id}}}"#);
+	let rejected = |reason: &str| {
+		format!(
+			r#"{{"jsonrpc":"2.0","id":null,"error":{{"code":-32600,"message":"Invalid request","data":"{reason}"}}}}"#
+		)
+	};
+
+	let (strict, _strict_handle) = start(IdPolicy::default().accept_null(false)).await;
+	let (lenient, _lenient_handle) =
+		start(IdPolicy::default().accept_floats(true).accept_fractional(true).preserve_format(true)).await;
+	let (normalized, _normalized_handle) = start(IdPolicy::default().accept_floats(true)).await;
+	let (other, _other_handle) = start(IdPolicy::default().accept_other(true)).await;
+
+	let cases = [
+		(&strict, "1.0", rejected("the id must not be a float")),
+		(&strict, "1.5", rejected("the id must be a string or an unsigned integer")),
+		(&strict, "-1", rejected("the id must be a string or an unsigned integer")),
+		(&strict, "null", rejected("the id must not be null")),
+		(&strict, r#""A""#, ok_response("hello".into(), Id::Str("A".into()))),
+		(&lenient, "1.0", r#"{"jsonrpc":"2.0","id":1.0,"result":"hello"}"#.to_owned()),
+		(&lenient, "1.5", r#"{"jsonrpc":"2.0","id":1.5,"result":"hello"}"#.to_owned()),
+		(&lenient, "-1", r#"{"jsonrpc":"2.0","id":-1,"result":"hello"}"#.to_owned()),
+		(&lenient, r#""\u0041""#, r#"{"jsonrpc":"2.0","id":"\u0041","result":"hello"}"#.to_owned()),
+		(&normalized, r#""\u0041""#, ok_response("hello".into(), Id::Str("A".into()))),
+		(&lenient, "null", ok_response("hello".into(), Id::Null)),
+		(&normalized, "1e3", ok_response("hello".into(), Id::Num(1000))),
+		(
+			&other,
+			r#"{"shard": 7, "id": 1}"#,
+			r#"{"jsonrpc":"2.0","id":{"shard": 7, "id": 1},"result":"hello"}"#.to_owned(),
+		),
+		(&other, "true", r#"{"jsonrpc":"2.0","id":true,"result":"hello"}"#.to_owned()),
+		(&other, "-1", rejected("the id must be a string or an unsigned integer")),
+	];
+
+	for (uri, id, expected) in cases {
+		let response = http_request(call(id).into(), uri.clone()).with_default_timeout().await.unwrap().unwrap();
+		assert_eq!(response.body, expected, "id: {id}");
+	}
+
+	// The ids of the calls of a batch are checked individually.
+	let batch = format!("[{},{}]", call("2.5"), call("3"));
+	let response = http_request(batch.into(), lenient.clone()).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(
+		response.body,
+		r#"[{"jsonrpc":"2.0","id":2.5,"result":"hello"},{"jsonrpc":"2.0","id":3,"result":"hello"}]"#
+	);
+}
+
+#[tokio::test]
+async fn drain_notice_closes_keep_alive_connections() {
+	init_logger();
+
+	let mut module = RpcModule::new(());
+	module.register_method("say_hello", |_, _, _| "hello").unwrap();
+	let server = ServerBuilder::default()
+		.set_drain_notice(Duration::from_secs(5))
+		.build("127.0.0.1:0")
+		.with_default_timeout()
+		.await
+		.unwrap()
+		.unwrap();
+	let addr = server.local_addr().unwrap();
+	let handle = server.start(module);
+
+	let request = |id: u32| {
+		let body = format!(r#"{{"jsonrpc":"2.0","method":"say_hello","id":{id}}}"#);
+		format!(
+			"POST / HTTP/1.1\r\nhost: {addr}\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{body}",
+			body.len()
+		)
+	};
+
+	let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+	stream.write_all(request(1).as_bytes()).await.unwrap();
+	let mut buf = vec![0; 1024];
+	let n = stream.read(&mut buf).with_default_timeout().await.unwrap().unwrap();
+	let response = String::from_utf8_lossy(&buf[..n]).to_lowercase();
+	assert!(response.ends_with(&ok_response("hello".into(), Id::Num(1))));
+	assert!(!response.contains("connection: close"));
+
+	handle.stop().unwrap();
+
+	// The keep-alive connection is still served but the client is asked to close it.
+	stream.write_all(request(2).as_bytes()).await.unwrap();
+	let mut response = String::new();
+	stream.read_to_string(&mut response).with_default_timeout().await.unwrap().unwrap();
+	assert!(response.to_lowercase().contains("connection: close"));
+	assert!(response.ends_with(&ok_response("hello".into(), Id::Num(2))));
+
+	handle.stopped().with_default_timeout().await.unwrap();
+}
+
+#[tokio::test]
+async fn handshake_filter_rejects_with_status_and_reason() {
+	init_logger();
+
+	let layer = HandshakeFilterLayer::new(|req| {
+		assert!(req.remote_addr.is_some());
+		match req.headers.get("x-api-key") {
+			Some(_) => Ok(()),
+			None if req.transport == TransportKind::WebSocket => {
+				Err(HandshakeRejection::new(hyper::StatusCode::UNAUTHORIZED, "missing_api_key"))
+			}
+			None => Err(HandshakeRejection::with_body(
+				hyper::StatusCode::FORBIDDEN,
+				serde_json::json!({ "reason": "missing_api_key", "transport": "http" }),
+			)),
+		}
+	});
+
+	let mut module = RpcModule::new(());
+	module.register_method("say_hello", |_, _, _| "hello").unwrap();
+
+	let server = ServerBuilder::default()
+		.set_http_middleware(tower::ServiceBuilder::new().layer(layer))
+		.build("127.0.0.1:0")
+		.with_default_timeout()
+		.await
+		.unwrap()
+		.unwrap();
+	let addr = server.local_addr().unwrap();
+	let uri = to_http_uri(addr);
+	let _handle = server.start(module);
+
+	let req = r#"{"jsonrpc":"2.0","method":"say_hello","id":1}"#;
+	let response = http_request(req.into(), uri.clone()).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.status, StatusCode::FORBIDDEN);
+	assert_eq!(response.header.get("content-type").unwrap(), "application/json; charset=utf-8");
+	let body: JsonValue = serde_json::from_str(&response.body).unwrap();
+	assert_eq!(body, serde_json::json!({ "reason": "missing_api_key", "transport": "http" }));
+
+	let mut headers = hyper::HeaderMap::new();
+	headers.insert("x-api-key", hyper::header::HeaderValue::from_static("key"));
+	let response = http_request_with_headers(req.into(), uri, headers).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.body, ok_response("hello".into(), Id::Num(1)));
+
+	let rejected = WebSocketTestClient::new(addr).with_default_timeout().await.unwrap();
+	assert!(matches!(rejected, Err(WebSocketTestError::RejectedWithStatusCode(401))));
+
+	let mut client = WebSocketTestClient::new_with_headers(addr, &[("x-api-key", "key")])
+		.with_default_timeout()
+		.await
+		.unwrap()
+		.unwrap();
+	let response = client.send_request_text(req).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response, ok_response("hello".into(), Id::Num(1)));
+}
+
+#[tokio::test]
+async fn response_extensions_round_trip() {
+	init_logger();
+
+	#[derive(Debug, Clone)]
+	struct TraceId(String);
+
+	#[derive(Debug, Clone, Copy)]
+	struct CacheHint(u64);
+
+	#[derive(Clone)]
+	struct CacheControl<S>(S);
+
+	impl<'a, S> RpcServiceT<'a> for CacheControl<S>
+	where
+		S: RpcServiceT<'a> + Send + Sync,
+		S::Future: Send + 'a,
+	{
+		type Future = futures_util::future::BoxFuture<'a, MethodResponse>;
+
+		fn call(&self, mut request: jsonrpsee_types::Request<'a>) -> Self::Future {
+			request.extensions_mut().insert(TraceId("abc".to_owned()));
+			let fut = self.0.call(request);
+
+			Box::pin(async move {
+				let rp = fut.await;
+				let rp_ext = rp.extensions().get::<ResponseExtensions>().unwrap();
+				if let Some(CacheHint(secs)) = rp_ext.get::<CacheHint>() {
+					let mut headers = hyper::HeaderMap::new();
+					headers.insert("cache-control", format!("max-age={secs}").parse().unwrap());
+					rp_ext.insert(headers);
+				}
+				rp
+			})
+		}
+	}
+
+	let mut module = RpcModule::new(());
+	module
+		.register_method("trace", |_, _, ext| {
+			ext.get::<ResponseExtensions>().unwrap().insert(CacheHint(60));
+			ext.get::<TraceId>().unwrap().0.clone()
+		})
+		.unwrap();
+
+	let http_middleware = tower::ServiceBuilder::new().map_response(|mut rp: crate::HttpResponse| {
+		if let Some(CacheHint(secs)) = rp.extensions().get::<CacheHint>().copied() {
+			rp.headers_mut().insert("x-cache-hint", secs.into());
+		}
+		rp
+	});
+
+	let server = ServerBuilder::default()
+		.set_http_middleware(http_middleware)
+		.set_rpc_middleware(RpcServiceBuilder::new().layer_fn(CacheControl))
+		.build("127.0.0.1:0")
+		.with_default_timeout()
+		.await
+		.unwrap()
+		.unwrap();
+	let uri = to_http_uri(server.local_addr().unwrap());
+	let _handle = server.start(module);
+
+	let req = r#"{"jsonrpc":"2.0","method":"trace","id":1}"#;
+	let response = http_request(req.into(), uri.clone()).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.body, ok_response("abc".into(), Id::Num(1)));
+	assert_eq!(response.header.get("cache-control").unwrap(), "max-age=60");
+	assert_eq!(response.header.get("x-cache-hint").unwrap(), "60");
+
+	// The values of the calls in a batch aren't moved into the HTTP response.
+	let req = r#"[{"jsonrpc":"2.0","method":"trace","id":1}]"#;
+	let response = http_request(req.into(), uri).with_default_timeout().await.unwrap().unwrap();
+	assert!(response.header.get("cache-control").is_none());
+	assert!(response.header.get("x-cache-hint").is_none());
+}
+
+#[test]
+fn builtin_methods_are_built_once() {
+	let builder = ServerBuilder::default()
+		.enable_rpc_discover(crate::OpenRpcInfo::new("test", "1.0.0"))
+		.enable_methods_introspection()
+		.to_service_builder();
+	let mut module = RpcModule::new(());
+	module.register_method("say_hello", |_, _, _| "hello").unwrap();
+	let methods: jsonrpsee_core::server::Methods = module.into();
+
+	let cache = builder.clone().builtin_methods;
+	let first = cache.get_or_build(methods.clone(), &builder.server_cfg);
+	let second = cache.get_or_build(methods.clone(), &builder.server_cfg);
+	assert!(first.ptr_eq(&second));
+	assert!(first.method("rpc.discover").is_some());
+
+	// Other methods are built again.
+	let mut module = RpcModule::new(());
+	module.register_method("say_bye", |_, _, _| "bye").unwrap();
+	let other = cache.get_or_build(module.into(), &builder.server_cfg);
+	assert!(!other.ptr_eq(&first));
+	assert!(other.method("say_bye").is_some());
+}
+
+#[test]
+fn served_openrpc_document_is_generated_once() {
+	let builder = ServerBuilder::default()
+		.serve_openrpc("/openrpc.json", crate::OpenRpcInfo::new("test", "1.0.0"))
+		.to_service_builder();
+	let openrpc = builder.server_cfg.openrpc.clone().unwrap();
+	let mut module = RpcModule::new(());
+	module.register_method("say_hello", |_, _, _| "hello").unwrap();
+	let methods: jsonrpsee_core::server::Methods = module.into();
+
+	let first = openrpc.document(&methods);
+	assert!(Arc::ptr_eq(&first, &openrpc.document(&methods.clone())));
+	assert!(first.contains("say_hello"));
+
+	// The document of other methods is generated again.
+	let mut module = RpcModule::new(());
+	module.register_method("say_bye", |_, _, _| "bye").unwrap();
+	assert!(openrpc.document(&module.into()).contains("say_bye"));
 }
@@ -83,8 +83,272 @@ async fn ws_only_works() {
 	let req = r#"{"jsonrpc":"2.0","method":"say_hello","id":1}"#;
 	let response = http_request(req.into(), to_http_uri(addr)).with_default_timeout().await.unwrap().unwrap();
 	assert_eq!(response.status, StatusCode::FORBIDDEN);
+	assert_eq!(response.body, "Only WebSocket connections are accepted, HTTP requests are not supported\n");
 
 	let mut client = WebSocketTestClient::new(addr).with_default_timeout().await.unwrap().unwrap();
 	let response = client.send_request_text(req.to_string()).await.unwrap();
 	assert_eq!(response, ok_response("hello".to_string().into(), Id::Num(1)));
 }
+
+#[tokio::test]
+async fn custom_runtimes_work() {
+	use crate::{RpcModule, ServerBuilder};
+
+	init_logger();
+
+	let runtime = |name: &str| {
+		tokio::runtime::Builder::new_multi_thread().worker_threads(1).thread_name(name).enable_all().build().unwrap()
+	};
+	let conn_rt = runtime("conn-runtime");
+	let method_rt = runtime("method-runtime");
+
+	let mut module = RpcModule::new(());
+	module.register_method("thread_name", |_, _, _| std::thread::current().name().map(ToOwned::to_owned)).unwrap();
+
+	let server = ServerBuilder::default()
+		.custom_connection_runtime(conn_rt.handle().clone())
+		.build("127.0.0.1:0")
+		.with_default_timeout()
+		.await
+		.unwrap()
+		.unwrap();
+	let conn_addr = server.local_addr().unwrap();
+	let _conn_handle = server.start(module.clone());
+
+	let server = ServerBuilder::default()
+		.custom_method_runtime(method_rt.handle().clone())
+		.build("127.0.0.1:0")
+		.with_default_timeout()
+		.await
+		.unwrap()
+		.unwrap();
+	let method_addr = server.local_addr().unwrap();
+	let _method_handle = server.start(module);
+
+	let req = r#"{"jsonrpc":"2.0","method":"thread_name","id":1}"#;
+
+	// HTTP calls are executed on the connection unless a method runtime is configured.
+	let response = http_request(req.into(), to_http_uri(conn_addr)).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.body, ok_response("conn-runtime".into(), Id::Num(1)));
+
+	let response = http_request(req.into(), to_http_uri(method_addr)).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.body, ok_response("method-runtime".into(), Id::Num(1)));
+
+	let mut client = WebSocketTestClient::new(method_addr).with_default_timeout().await.unwrap().unwrap();
+	let response = client.send_request_text(req).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response, ok_response("method-runtime".into(), Id::Num(1)));
+
+	conn_rt.shutdown_background();
+	method_rt.shutdown_background();
+}
+
+#[tokio::test]
+async fn connection_middleware_wraps_websocket_session() {
+	use crate::middleware::connection::{Connection, ConnectionIo, ServeConnection};
+	use crate::{RpcModule, ServerBuilder};
+	use futures_util::future::BoxFuture;
+	use jsonrpsee_core::BoxError;
+	use std::pin::Pin;
+	use std::sync::atomic::{AtomicUsize, Ordering};
+	use std::sync::Arc;
+	use std::task::{Context, Poll};
+	use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+	use tokio::sync::mpsc;
+	use tower::Service;
+
+	struct CountingIo {
+		io: Box<dyn ConnectionIo>,
+		read: Arc<AtomicUsize>,
+	}
+
+	impl AsyncRead for CountingIo {
+		fn poll_read(
+			mut self: Pin<&mut Self>,
+			cx: &mut Context<'_>,
+			buf: &mut ReadBuf<'_>,
+		) -> Poll<std::io::Result<()>> {
+			let before = buf.filled().len();
+			let res = Pin::new(&mut self.io).poll_read(cx, buf);
+			self.read.fetch_add(buf.filled().len() - before, Ordering::SeqCst);
+			res
+		}
+	}
+
+	impl AsyncWrite for CountingIo {
+		fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<std::io::Result<usize>> {
+			Pin::new(&mut self.io).poll_write(cx, buf)
+		}
+
+		fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+			Pin::new(&mut self.io).poll_flush(cx)
+		}
+
+		fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+			Pin::new(&mut self.io).poll_shutdown(cx)
+		}
+	}
+
+	struct Accounting {
+		inner: ServeConnection,
+		read: Arc<AtomicUsize>,
+		closed: mpsc::UnboundedSender<usize>,
+	}
+
+	impl Service<Connection> for Accounting {
+		type Response = ();
+		type Error = BoxError;
+		type Future = BoxFuture<'static, Result<(), BoxError>>;
+
+		fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+			self.inner.poll_ready(cx)
+		}
+
+		fn call(&mut self, mut conn: Connection) -> Self::Future {
+			conn.io = Box::new(CountingIo { io: conn.io, read: self.read.clone() });
+			let fut = self.inner.call(conn);
+			let read = self.read.clone();
+			let closed = self.closed.clone();
+
+			Box::pin(async move {
+				let res = fut.await;
+				closed.send(read.load(Ordering::SeqCst)).unwrap();
+				res
+			})
+		}
+	}
+
+	init_logger();
+
+	let read = Arc::new(AtomicUsize::new(0));
+	let (closed_tx, mut closed_rx) = mpsc::unbounded_channel();
+
+	let layer = {
+		let read = read.clone();
+		tower::layer::layer_fn(move |inner| Accounting { inner, read: read.clone(), closed: closed_tx.clone() })
+	};
+
+	let mut module = RpcModule::new(());
+	module.register_method("say_hello", |_, _, _| "hello").unwrap();
+
+	let server = ServerBuilder::default()
+		.set_connection_middleware(layer)
+		.build("127.0.0.1:0")
+		.with_default_timeout()
+		.await
+		.unwrap()
+		.unwrap();
+	let addr = server.local_addr().unwrap();
+	let _handle = server.start(module);
+
+	let mut client = WebSocketTestClient::new(addr).with_default_timeout().await.unwrap().unwrap();
+	let req = r#"{"jsonrpc":"2.0","method":"say_hello","id":1}"#;
+	let response = client.send_request_text(req).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response, ok_response("hello".into(), Id::Num(1)));
+
+	// The connection is still open after the WebSocket upgrade.
+	assert!(closed_rx.try_recv().is_err());
+
+	client.close().with_default_timeout().await.unwrap().unwrap();
+
+	let read_on_close = closed_rx.recv().with_default_timeout().await.unwrap().unwrap();
+	assert!(read_on_close > req.len());
+}
+
+#[tokio::test]
+async fn message_tap_works() {
+	use crate::{MessageDirection, RawMessage, RpcModule, ServerBuilder, TransportKind};
+	use tokio::sync::mpsc;
+
+	init_logger();
+
+	let (tx, mut rx) = mpsc::unbounded_channel::<RawMessage>();
+
+	let mut module = RpcModule::new(());
+	module.register_method("say_hello", |_, _, _| "hello").unwrap();
+
+	let server = ServerBuilder::default()
+		.set_message_tap(16, move |msg| {
+			let _ = tx.send(msg);
+		})
+		.build("127.0.0.1:0")
+		.with_default_timeout()
+		.await
+		.unwrap()
+		.unwrap();
+	let addr = server.local_addr().unwrap();
+	let _handle = server.start(module);
+
+	let req = r#"{"jsonrpc":"2.0","method":"say_hello","id":1}"#;
+	let expected = ok_response("hello".into(), Id::Num(1));
+
+	let mut assert_tapped = |transport: TransportKind, direction: MessageDirection, msg: &str| {
+		let tapped = rx.try_recv().unwrap();
+		assert_eq!(tapped.transport, transport);
+		assert_eq!(tapped.direction, direction);
+		// The copies are truncated to the configured max length.
+		assert_eq!(tapped.data, msg.as_bytes()[..16]);
+		assert_eq!(tapped.len, msg.len());
+	};
+
+	let response = http_request(req.into(), to_http_uri(addr)).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.body, expected);
+	assert_tapped(TransportKind::Http, MessageDirection::Inbound, req);
+	assert_tapped(TransportKind::Http, MessageDirection::Outbound, &expected);
+
+	let mut client = WebSocketTestClient::new(addr).with_default_timeout().await.unwrap().unwrap();
+	let response = client.send_request_text(req).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response, expected);
+	assert_tapped(TransportKind::WebSocket, MessageDirection::Inbound, req);
+	assert_tapped(TransportKind::WebSocket, MessageDirection::Outbound, &expected);
+}
+
+#[tokio::test]
+async fn utf8_policy_works() {
+	use crate::{RpcModule, ServerBuilder, Utf8Policy};
+
+	init_logger();
+
+	let mut module = RpcModule::new(());
+	module.register_method("echo", |params, _, _| params.one::<String>().unwrap()).unwrap();
+
+	let start = |policy: Utf8Policy| {
+		let module = module.clone();
+		async move {
+			let server = ServerBuilder::default()
+				.set_utf8_policy(policy)
+				.build("127.0.0.1:0")
+				.with_default_timeout()
+				.await
+				.unwrap()
+				.unwrap();
+			let addr = server.local_addr().unwrap();
+			(addr, server.start(module))
+		}
+	};
+
+	let (strict, strict_handle) = start(Utf8Policy::Strict).await;
+	let (lossy, lossy_handle) = start(Utf8Policy::Lossy).await;
+
+	let mut req = br#"{"jsonrpc":"2.0","method":"echo","params":["a"#.to_vec();
+	req.push(0xFF);
+	req.extend_from_slice(br#"b"],"id":1}"#);
+
+	let rejected = r#"{"jsonrpc":"2.0","id":null,"error":{"code":-32700,"message":"Parse error","data":"invalid UTF-8 at byte 45"}}"#;
+	let replaced = ok_response("a\u{FFFD}b".into(), Id::Num(1));
+
+	let response = http_request(req.clone().into(), to_http_uri(strict)).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.body, rejected);
+	let response = http_request(req.clone().into(), to_http_uri(lossy)).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.body, replaced);
+
+	let mut client = WebSocketTestClient::new(strict).with_default_timeout().await.unwrap().unwrap();
+	let response = client.send_request_binary(&req).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response, rejected);
+
+	let mut client = WebSocketTestClient::new(lossy).with_default_timeout().await.unwrap().unwrap();
+	let response = client.send_request_binary(&req).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response, replaced);
+
+	assert_eq!((strict_handle.utf8_stats().rejected(), strict_handle.utf8_stats().replaced()), (2, 0));
+	assert_eq!((lossy_handle.utf8_stats().rejected(), lossy_handle.utf8_stats().replaced()), (0, 2));
+}
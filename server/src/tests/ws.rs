@@ -24,20 +24,30 @@
 // IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
 // DEALINGS IN THE SOFTWARE.
 
-use std::sync::atomic::Ordering;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 
+use crate::middleware::rpc::{RequestDedupLayer, ResponseCacheLayer, RpcServiceBuilder, RpcServiceT};
 use crate::tests::helpers::{deser_call, init_logger, server_with_context, ws_server_with_stats, Metrics};
 use crate::types::SubscriptionId;
-use crate::{BatchRequestConfig, RegisterMethodError};
+use crate::{
+	BatchRequestConfig, ConnectInfo, DisconnectReason, HandoffError, HttpBody, HttpResponse, RegisterMethodError,
+	TransportKind, WsFlushPolicy, WsFrameConfig, MAX_RESPONSE_SIZE_HEADER, SHUTDOWN_NOTIFICATION,
+};
 use crate::{RpcModule, ServerBuilder};
-use jsonrpsee_core::server::{SendTimeoutError, SubscriptionMessage};
+use jsonrpsee_core::server::{
+	CancellationToken, ConnectionExtensions, MethodResponse, SendTimeoutError, SubscriptionMessage,
+	MAX_COALESCED_NOTIFICATIONS,
+};
 use jsonrpsee_core::traits::IdProvider;
 use jsonrpsee_test_utils::helpers::*;
 use jsonrpsee_test_utils::mocks::{Id, WebSocketTestClient, WebSocketTestError};
 use jsonrpsee_test_utils::TimeoutFutureExt;
 use jsonrpsee_types::SubscriptionResponse;
 use serde_json::Value as JsonValue;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
 
 use super::helpers::server;
 
@@ -92,6 +102,37 @@ async fn can_set_the_max_response_body_size() {
 	server_handle.stopped().await;
 }
 
+#[tokio::test]
+async fn streaming_method_is_buffered() {
+	init_logger();
+
+	let server = ServerBuilder::default().max_response_body_size(100).build("127.0.0.1:0").await.unwrap();
+	let mut module = RpcModule::new(());
+	module
+		.register_streaming_method("numbers", |params, _, _| async move {
+			let count: usize = params.one()?;
+			Ok::<_, jsonrpsee_types::ErrorObjectOwned>(futures_util::stream::iter(0..count))
+		})
+		.unwrap();
+	let addr = server.local_addr().unwrap();
+	let server_handle = server.start(module);
+
+	let mut client = WebSocketTestClient::new(addr).await.unwrap();
+
+	// The items are collected into a regular response over WebSocket.
+	let req = r#"{"jsonrpc":"2.0","method":"numbers","params":[3],"id":1}"#;
+	let response = client.send_request_text(req).await.unwrap();
+	assert_eq!(response, ok_response(vec![0, 1, 2].into(), Id::Num(1)));
+
+	// Which is limited by the max response size.
+	let req = r#"{"jsonrpc":"2.0","method":"numbers","params":[100],"id":1}"#;
+	let response = client.send_request_text(req).await.unwrap();
+	assert_eq!(response, oversized_response(Id::Num(1), 100));
+
+	server_handle.stop().unwrap();
+	server_handle.stopped().await;
+}
+
 #[tokio::test]
 async fn can_set_the_max_response_size_to_batch() {
 	init_logger();
@@ -115,6 +156,120 @@ async fn can_set_the_max_response_size_to_batch() {
 	server_handle.stopped().await;
 }
 
+#[tokio::test]
+async fn client_can_lower_the_max_response_size() {
+	init_logger();
+
+	let server = ServerBuilder::default().max_response_body_size(200).build("127.0.0.1:0").await.unwrap();
+	let mut module = RpcModule::new(());
+	module.register_method("anything", |_p, _cx, _| "a".repeat(100)).unwrap();
+	module.register_method("small", |_p, _cx, _| "a").unwrap();
+	module.register_method("big", |_p, _cx, _| "a".repeat(300)).unwrap();
+	module.set_method_max_response_size("big", 1000).unwrap();
+	let addr = server.local_addr().unwrap();
+	let server_handle = server.start(module);
+
+	let mut client = WebSocketTestClient::new_with_headers(addr, &[(MAX_RESPONSE_SIZE_HEADER, "100")]).await.unwrap();
+
+	let req = r#"{"jsonrpc":"2.0", "method":"anything", "id":1}"#;
+	let response = client.send_request_text(req).await.unwrap();
+	assert_eq!(response, oversized_response(Id::Num(1), 100));
+
+	// The limit of a method doesn't raise the limit of the client.
+	let req = r#"{"jsonrpc":"2.0", "method":"big", "id":1}"#;
+	let response = client.send_request_text(req).await.unwrap();
+	assert_eq!(response, oversized_response(Id::Num(1), 100));
+
+	// The connection is still open.
+	let req = r#"{"jsonrpc":"2.0", "method":"small", "id":2}"#;
+	let response = client.send_request_text(req).await.unwrap();
+	assert_eq!(response, ok_response("a".into(), Id::Num(2)));
+
+	// The limit of the server is used if the client advertises a bigger one.
+	let mut client = WebSocketTestClient::new_with_headers(addr, &[(MAX_RESPONSE_SIZE_HEADER, "1000")]).await.unwrap();
+	let req = r#"{"jsonrpc":"2.0", "method":"anything", "id":1}"#;
+	let response = client.send_request_text(req).await.unwrap();
+	assert_eq!(response, ok_response("a".repeat(100).into(), Id::Num(1)));
+
+	// The limit of a method may raise the limit of the server up to the limit of the client.
+	let req = r#"{"jsonrpc":"2.0", "method":"big", "id":1}"#;
+	let response = client.send_request_text(req).await.unwrap();
+	assert_eq!(response, ok_response("a".repeat(300).into(), Id::Num(1)));
+
+	let req = r#"[{"jsonrpc":"2.0", "method":"anything", "id":1},{"jsonrpc":"2.0", "method":"anything", "id":2}]"#;
+	let response = client.send_request_text(req).await.unwrap();
+	assert_eq!(response, batch_response_too_large(200));
+
+	server_handle.stop().unwrap();
+	server_handle.stopped().await;
+}
+
+#[tokio::test]
+async fn cached_responses_respect_the_max_response_size_of_the_client() {
+	init_logger();
+
+	let cache = ResponseCacheLayer::new(1024).cache_method("anything", Duration::from_secs(60));
+	let server = ServerBuilder::default()
+		.set_rpc_middleware(RpcServiceBuilder::new().layer(cache))
+		.build("127.0.0.1:0")
+		.await
+		.unwrap();
+	let mut module = RpcModule::new(());
+	module.register_method("anything", |_p, _cx, _| "a".repeat(100)).unwrap();
+	let addr = server.local_addr().unwrap();
+	let server_handle = server.start(module);
+
+	let mut client = WebSocketTestClient::new(addr).await.unwrap();
+	let req = r#"{"jsonrpc":"2.0", "method":"anything", "id":1}"#;
+	let response = client.send_request_text(req).await.unwrap();
+	assert_eq!(response, ok_response("a".repeat(100).into(), Id::Num(1)));
+
+	let mut client = WebSocketTestClient::new_with_headers(addr, &[(MAX_RESPONSE_SIZE_HEADER, "100")]).await.unwrap();
+	let response = client.send_request_text(req).await.unwrap();
+	assert_eq!(response, oversized_response(Id::Num(1), 100));
+
+	server_handle.stop().unwrap();
+	server_handle.stopped().await;
+}
+
+#[tokio::test]
+async fn deduplicated_responses_respect_the_max_response_size_of_the_client() {
+	init_logger();
+
+	let server = ServerBuilder::default()
+		.set_rpc_middleware(RpcServiceBuilder::new().layer(RequestDedupLayer::new().dedup_method("expensive")))
+		.build("127.0.0.1:0")
+		.await
+		.unwrap();
+	let calls = Arc::new(AtomicUsize::new(0));
+	let mut module = RpcModule::new(calls.clone());
+	module
+		.register_async_method("expensive", |_, calls, _| async move {
+			calls.fetch_add(1, Ordering::SeqCst);
+			tokio::time::sleep(Duration::from_millis(200)).await;
+			"a".repeat(100)
+		})
+		.unwrap();
+	let addr = server.local_addr().unwrap();
+	let server_handle = server.start(module);
+
+	let mut leader = WebSocketTestClient::new(addr).await.unwrap();
+	let mut waiter = WebSocketTestClient::new_with_headers(addr, &[(MAX_RESPONSE_SIZE_HEADER, "100")]).await.unwrap();
+	let req = r#"{"jsonrpc":"2.0", "method":"expensive", "id":1}"#;
+
+	let (leader_response, waiter_response) = tokio::join!(leader.send_request_text(req), async {
+		// The call of the waiter arrives while the call of the leader is in-flight.
+		tokio::time::sleep(Duration::from_millis(50)).await;
+		waiter.send_request_text(req).await
+	});
+	assert_eq!(leader_response.unwrap(), ok_response("a".repeat(100).into(), Id::Num(1)));
+	assert_eq!(waiter_response.unwrap(), oversized_response(Id::Num(1), 100));
+	assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+	server_handle.stop().unwrap();
+	server_handle.stopped().await;
+}
+
 #[tokio::test]
 async fn can_set_max_connections() {
 	init_logger();
@@ -305,7 +460,7 @@ async fn single_method_call_with_params_works() {
 async fn single_method_call_with_faulty_params_returns_err() {
 	let addr = server().await;
 	let mut client = WebSocketTestClient::new(addr).with_default_timeout().await.unwrap().unwrap();
-	let expected = r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32602,"message":"Invalid params","data":"invalid type: string \"should be a number\", expected u64 at line 1 column 21"}}"#;
+	let expected = r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32602,"message":"Invalid params","data":{"position":0,"message":"invalid type: string \"should be a number\", expected u64 at line 1 column 21"}}}"#;
 
 	let req = r#"{"jsonrpc":"2.0","method":"add", "params":["should be a number"],"id":1}"#;
 	let response = client.send_request_text(req).with_default_timeout().await.unwrap().unwrap();
@@ -591,6 +746,57 @@ async fn custom_subscription_id_works() {
 	assert_eq!(&unsub, r#"{"jsonrpc":"2.0","id":1,"result":true}"#);
 }
 
+#[tokio::test]
+async fn subscription_id_from_params_works() {
+	#[derive(Debug, Clone)]
+	struct TopicSubscriptionId;
+
+	impl IdProvider for TopicSubscriptionId {
+		fn next_id(&self) -> SubscriptionId<'static> {
+			"fallback".to_owned().into()
+		}
+
+		fn next_id_for(&self, method: &str, params: &crate::types::Params) -> SubscriptionId<'static> {
+			let topic: String = params.one().unwrap_or_default();
+			format!("{method}/{topic}").into()
+		}
+	}
+
+	init_logger();
+	let server = ServerBuilder::default()
+		.set_id_provider(TopicSubscriptionId)
+		.build("127.0.0.1:0")
+		.with_default_timeout()
+		.await
+		.unwrap()
+		.unwrap();
+	let addr = server.local_addr().unwrap();
+	let mut module = RpcModule::new(());
+	module
+		.register_subscription::<(), _, _>("subscribe_topic", "topic", "unsubscribe_topic", |_, sink, _, _| async {
+			let _sink = sink.accept().await.unwrap();
+			// Keep idle until it's unsubscribed.
+			futures_util::future::pending::<()>().await;
+		})
+		.unwrap();
+	let _handle = server.start(module);
+
+	let mut client = WebSocketTestClient::new(addr).with_default_timeout().await.unwrap().unwrap();
+
+	let sub = client.send_request_text(call("subscribe_topic", vec!["news"], Id::Num(0))).await.unwrap();
+	assert_eq!(&sub, r#"{"jsonrpc":"2.0","id":0,"result":"subscribe_topic/news"}"#);
+
+	// The ID of an active subscription isn't reused.
+	let sub = client.send_request_text(call("subscribe_topic", vec!["news"], Id::Num(1))).await.unwrap();
+	assert_eq!(&sub, r#"{"jsonrpc":"2.0","id":1,"result":"fallback"}"#);
+
+	let unsub =
+		client.send_request_text(call("unsubscribe_topic", vec!["subscribe_topic/news"], Id::Num(2))).await.unwrap();
+	assert_eq!(&unsub, r#"{"jsonrpc":"2.0","id":2,"result":true}"#);
+	let unsub = client.send_request_text(call("unsubscribe_topic", vec!["fallback"], Id::Num(3))).await.unwrap();
+	assert_eq!(&unsub, r#"{"jsonrpc":"2.0","id":3,"result":true}"#);
+}
+
 #[tokio::test]
 async fn disabled_batches() {
 	// Disable batches support.
@@ -931,3 +1137,833 @@ async fn server_with_infinite_call(
 
 	(server.start(module), addr)
 }
+
+#[tokio::test]
+async fn closed_connection_cancels_the_call() {
+	init_logger();
+
+	let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+	let server = ServerBuilder::default().build("127.0.0.1:0").with_default_timeout().await.unwrap().unwrap();
+	let mut module = RpcModule::new(tx);
+	module
+		.register_async_method("wait_for_close", |_, tx, ext| async move {
+			let token = ext.get::<CancellationToken>().cloned().unwrap();
+			token.cancelled().await;
+			tx.send(()).unwrap();
+		})
+		.unwrap();
+	let addr = server.local_addr().unwrap();
+	let handle = server.start(module);
+
+	let mut client = WebSocketTestClient::new(addr).with_default_timeout().await.unwrap().unwrap();
+	client.send(r#"{"jsonrpc":"2.0","method":"wait_for_close","id":1}"#).with_default_timeout().await.unwrap().unwrap();
+	client.close().with_default_timeout().await.unwrap().unwrap();
+
+	assert!(rx.recv().with_default_timeout().await.unwrap().is_some());
+
+	handle.stop().unwrap();
+	handle.stopped().await;
+}
+
+#[tokio::test]
+async fn subscription_batch_and_coalescing_works() {
+	init_logger();
+
+	let server = ServerBuilder::default().build("127.0.0.1:0").with_default_timeout().await.unwrap().unwrap();
+	let addr = server.local_addr().unwrap();
+	let mut module = RpcModule::new(());
+	module
+		.register_subscription::<(), _, _>("subscribe_batch", "batch", "unsubscribe_batch", |_, sink, _, _| async {
+			let sink = sink.accept().await.unwrap();
+			let msgs = (0..3).map(|i| SubscriptionMessage::from_json(&i).unwrap());
+			sink.send_batch(msgs).await.unwrap();
+			futures_util::future::pending::<()>().await;
+		})
+		.unwrap();
+	module
+		.register_subscription::<(), _, _>(
+			"subscribe_coalesced",
+			"coalesced",
+			"unsubscribe_coalesced",
+			|_, sink, _, _| async {
+				let sink = sink.accept().await.unwrap();
+				for i in 0..3 {
+					sink.send(SubscriptionMessage::from_json(&i).unwrap()).await.unwrap();
+				}
+				futures_util::future::pending::<()>().await;
+			},
+		)
+		.unwrap();
+	module.set_subscription_coalescing_window("subscribe_coalesced", Duration::from_millis(50)).unwrap();
+	let _handle = server.start(module);
+
+	let mut client = WebSocketTestClient::new(addr).with_default_timeout().await.unwrap().unwrap();
+
+	for method in ["subscribe_batch", "subscribe_coalesced"] {
+		let sub_id: JsonValue =
+			deser_call(client.send_request_text(call(method, Vec::<()>::new(), Id::Num(0))).await.unwrap());
+		let batch: Vec<JsonValue> = serde_json::from_str(&client.receive().await.unwrap()).unwrap();
+
+		assert_eq!(batch.len(), 3);
+		for (i, notif) in batch.into_iter().enumerate() {
+			assert_eq!(notif["params"]["subscription"], sub_id);
+			assert_eq!(notif["params"]["result"], i);
+		}
+	}
+}
+
+#[tokio::test]
+async fn full_coalescing_buffer_is_flushed_early() {
+	init_logger();
+
+	let server = ServerBuilder::default().build("127.0.0.1:0").with_default_timeout().await.unwrap().unwrap();
+	let addr = server.local_addr().unwrap();
+	let mut module = RpcModule::new(());
+	module
+		.register_subscription::<(), _, _>(
+			"subscribe_coalesced",
+			"coalesced",
+			"unsubscribe_coalesced",
+			|_, sink, _, _| async {
+				let sink = sink.accept().await.unwrap();
+				for i in 0..MAX_COALESCED_NOTIFICATIONS + 1 {
+					sink.send(SubscriptionMessage::from_json(&i).unwrap()).await.unwrap();
+				}
+				futures_util::future::pending::<()>().await;
+			},
+		)
+		.unwrap();
+	module.set_subscription_coalescing_window("subscribe_coalesced", Duration::from_secs(3600)).unwrap();
+	let _handle = server.start(module);
+
+	let mut client = WebSocketTestClient::new(addr).with_default_timeout().await.unwrap().unwrap();
+
+	let _sub_id: JsonValue =
+		deser_call(client.send_request_text(call("subscribe_coalesced", Vec::<()>::new(), Id::Num(0))).await.unwrap());
+	let batch: Vec<JsonValue> =
+		serde_json::from_str(&client.receive().with_default_timeout().await.unwrap().unwrap()).unwrap();
+
+	assert_eq!(batch.len(), MAX_COALESCED_NOTIFICATIONS);
+	for (i, notif) in batch.into_iter().enumerate() {
+		assert_eq!(notif["params"]["result"], i);
+	}
+}
+
+#[tokio::test]
+async fn subscription_heartbeat_works() {
+	init_logger();
+
+	let server = ServerBuilder::default().build("127.0.0.1:0").with_default_timeout().await.unwrap().unwrap();
+	let addr = server.local_addr().unwrap();
+	let mut module = RpcModule::new(());
+	module
+		.register_subscription::<(), _, _>("subscribe_quiet", "quiet", "unsubscribe_quiet", |_, sink, _, _| async {
+			let _sink = sink.accept().await.unwrap();
+			futures_util::future::pending::<()>().await;
+		})
+		.unwrap();
+	module.set_subscription_heartbeat("subscribe_quiet", Duration::from_millis(50)).unwrap();
+	let _handle = server.start(module);
+
+	let mut client = WebSocketTestClient::new(addr).with_default_timeout().await.unwrap().unwrap();
+
+	let sub_id: JsonValue =
+		deser_call(client.send_request_text(call("subscribe_quiet", Vec::<()>::new(), Id::Num(0))).await.unwrap());
+
+	for _ in 0..2 {
+		let heartbeat: JsonValue =
+			serde_json::from_str(&client.receive().with_default_timeout().await.unwrap().unwrap()).unwrap();
+		assert_eq!(heartbeat["method"], "quiet");
+		assert_eq!(heartbeat["params"]["subscription"], sub_id);
+		assert_eq!(heartbeat["params"]["heartbeat"], true);
+	}
+}
+
+#[tokio::test]
+async fn subscription_accept_with_custom_result_works() {
+	init_logger();
+
+	let server = ServerBuilder::default().build("127.0.0.1:0").with_default_timeout().await.unwrap().unwrap();
+	let addr = server.local_addr().unwrap();
+	let mut module = RpcModule::new(());
+	module
+		.register_subscription::<(), _, _>("subscribe_state", "state", "unsubscribe_state", |_, pending, _, _| async {
+			let result = serde_json::json!({ "subscription": pending.subscription_id(), "snapshot": 42 });
+			let sink = pending.accept_with(result).await.unwrap();
+			sink.send(SubscriptionMessage::from_json(&43).unwrap()).await.unwrap();
+			futures_util::future::pending::<()>().await;
+		})
+		.unwrap();
+	let _handle = server.start(module);
+
+	let mut client = WebSocketTestClient::new(addr).with_default_timeout().await.unwrap().unwrap();
+
+	let result: JsonValue =
+		deser_call(client.send_request_text(call("subscribe_state", Vec::<()>::new(), Id::Num(0))).await.unwrap());
+	assert_eq!(result["snapshot"], 42);
+
+	let notif: JsonValue =
+		serde_json::from_str(&client.receive().with_default_timeout().await.unwrap().unwrap()).unwrap();
+	assert_eq!(notif["params"]["subscription"], result["subscription"]);
+	assert_eq!(notif["params"]["result"], 43);
+}
+
+#[tokio::test]
+async fn subscription_stats_works() {
+	init_logger();
+
+	let server = ServerBuilder::default().build("127.0.0.1:0").with_default_timeout().await.unwrap().unwrap();
+	let addr = server.local_addr().unwrap();
+	let mut module = RpcModule::new(());
+	module
+		.register_subscription::<(), _, _>(
+			"subscribe_counted",
+			"counted",
+			"unsubscribe_counted",
+			|_, sink, _, _| async {
+				let sink = sink.accept().await.unwrap();
+				for i in 0..3 {
+					sink.send(SubscriptionMessage::from_json(&i).unwrap()).await.unwrap();
+				}
+				sink.closed().await;
+			},
+		)
+		.unwrap();
+	let handle = server.start(module);
+
+	let mut client = WebSocketTestClient::new(addr).with_default_timeout().await.unwrap().unwrap();
+	let sub_id: JsonValue =
+		deser_call(client.send_request_text(call("subscribe_counted", Vec::<()>::new(), Id::Num(0))).await.unwrap());
+	for _ in 0..3 {
+		client.receive().with_default_timeout().await.unwrap().unwrap();
+	}
+
+	let stats = handle.subscription_stats();
+	let counters = stats.by_method()["subscribe_counted"];
+	assert_eq!(counters.active, 1);
+	assert_eq!(counters.notifications_sent, 3);
+	assert_eq!(counters.notifications_dropped, 0);
+	assert!(counters.average_send_latency().is_some());
+	assert_eq!(stats.by_connection().len(), 1);
+
+	let unsub: bool =
+		deser_call(client.send_request_text(call("unsubscribe_counted", vec![sub_id], Id::Num(1))).await.unwrap());
+	assert!(unsub);
+
+	// The subscription is closed in the background.
+	while !stats.by_connection().is_empty() {
+		tokio::time::sleep(Duration::from_millis(10)).with_default_timeout().await.unwrap();
+	}
+	let counters = stats.by_method()["subscribe_counted"];
+	assert_eq!(counters.active, 0);
+	assert_eq!(counters.notifications_sent, 3);
+}
+
+#[tokio::test]
+async fn connection_extensions_are_shared_by_calls_on_a_connection() {
+	init_logger();
+
+	#[derive(Clone)]
+	struct Calls(usize);
+
+	let server = ServerBuilder::default().build("127.0.0.1:0").with_default_timeout().await.unwrap().unwrap();
+	let addr = server.local_addr().unwrap();
+	let mut module = RpcModule::new(());
+	module
+		.register_method("count", |_, _, ext| {
+			let conn = ext.get::<ConnectionExtensions>().unwrap();
+			let calls = conn.get::<Calls>().map_or(1, |c| c.0 + 1);
+			conn.insert(Calls(calls));
+			calls
+		})
+		.unwrap();
+	let _handle = server.start(module);
+
+	let mut client1 = WebSocketTestClient::new(addr).with_default_timeout().await.unwrap().unwrap();
+	let mut client2 = WebSocketTestClient::new(addr).with_default_timeout().await.unwrap().unwrap();
+
+	let count = call("count", Vec::<()>::new(), Id::Num(0));
+	assert_eq!(deser_call::<usize>(client1.send_request_text(&count).await.unwrap()), 1);
+	assert_eq!(deser_call::<usize>(client1.send_request_text(&count).await.unwrap()), 2);
+	assert_eq!(deser_call::<usize>(client2.send_request_text(&count).await.unwrap()), 1);
+}
+
+#[tokio::test]
+async fn connection_hooks_work() {
+	init_logger();
+
+	let connected = Arc::new(AtomicUsize::new(0));
+	let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+
+	let server = ServerBuilder::default()
+		.set_on_connect({
+			let connected = connected.clone();
+			move |info: ConnectInfo| {
+				let connected = connected.clone();
+				async move {
+					assert_eq!(info.transport, TransportKind::WebSocket);
+					assert!(info.remote_addr.is_some());
+					tokio::time::sleep(Duration::from_millis(10)).await;
+					if connected.fetch_add(1, Ordering::SeqCst) == 0 {
+						Ok(())
+					} else {
+						let mut rp = HttpResponse::new(HttpBody::empty());
+						*rp.status_mut() = hyper::StatusCode::FORBIDDEN;
+						Err(rp)
+					}
+				}
+			}
+		})
+		.set_on_disconnect(move |info| tx.send(info).unwrap())
+		.build("127.0.0.1:0")
+		.with_default_timeout()
+		.await
+		.unwrap()
+		.unwrap();
+	let addr = server.local_addr().unwrap();
+	let mut module = RpcModule::new(());
+	module.register_method("say_hello", |_, _, _| "hello").unwrap();
+	let _handle = server.start(module);
+
+	let mut client = WebSocketTestClient::new(addr).with_default_timeout().await.unwrap().unwrap();
+	let rejected = WebSocketTestClient::new(addr).with_default_timeout().await.unwrap();
+	assert!(matches!(rejected, Err(WebSocketTestError::RejectedWithStatusCode(403))));
+
+	let hello = call("say_hello", Vec::<()>::new(), Id::Num(0));
+	assert_eq!(deser_call::<String>(client.send_request_text(&hello).await.unwrap()), "hello");
+	client.close().await.unwrap();
+
+	let info = rx.recv().with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(info.transport, TransportKind::WebSocket);
+	assert_eq!(info.reason, DisconnectReason::Closed);
+	assert_eq!(info.messages, 1);
+	// The rejected connection is never reported as disconnected.
+	assert!(rx.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn coalesced_ws_writes_work() {
+	init_logger();
+
+	let server = ServerBuilder::default()
+		.set_ws_flush_policy(WsFlushPolicy::Coalesce { max_messages: 4, window: Duration::from_millis(10) })
+		.build("127.0.0.1:0")
+		.await
+		.unwrap();
+	let addr = server.local_addr().unwrap();
+	let mut module = RpcModule::new(());
+	module
+		.register_subscription("subscribe_numbers", "numbers", "unsubscribe_numbers", |_, pending, _, _| async move {
+			let sink = pending.accept().await?;
+
+			for n in 0..10 {
+				sink.send(SubscriptionMessage::from_json(&n)?).await?;
+			}
+			sink.closed().await;
+
+			Ok(())
+		})
+		.unwrap();
+	module.register_method("say_hello", |_, _, _| "hello").unwrap();
+	let handle = server.start(module);
+
+	let mut client = WebSocketTestClient::new(addr).with_default_timeout().await.unwrap().unwrap();
+
+	let sub = call("subscribe_numbers", Vec::<()>::new(), Id::Num(0));
+	let sub_id: JsonValue = deser_call(client.send_request_text(&sub).await.unwrap());
+
+	for n in 0..10 {
+		let msg = client.receive().with_default_timeout().await.unwrap().unwrap();
+		let notif: JsonValue = serde_json::from_str(&msg).unwrap();
+		assert_eq!(notif["params"]["subscription"], sub_id);
+		assert_eq!(notif["params"]["result"], n);
+	}
+
+	// A single message is flushed once the window has passed.
+	let hello = call("say_hello", Vec::<()>::new(), Id::Num(1));
+	let response = client.send_request_text(&hello).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(deser_call::<String>(response), "hello");
+
+	handle.stop().unwrap();
+	handle.stopped().await;
+}
+
+#[tokio::test]
+async fn pooled_serialization_buffers_work() {
+	init_logger();
+
+	let server = ServerBuilder::default().set_buffer_pool_size(2).build("127.0.0.1:0").await.unwrap();
+	let addr = server.local_addr().unwrap();
+	let mut module = RpcModule::new(());
+	module
+		.register_subscription("subscribe_numbers", "numbers", "unsubscribe_numbers", |_, pending, _, _| async move {
+			let sink = pending.accept().await?;
+
+			for n in 0..5 {
+				sink.send(SubscriptionMessage::from_json(&n)?).await?;
+			}
+			sink.closed().await;
+
+			Ok(())
+		})
+		.unwrap();
+	module.register_method("echo", |params, _, _| params.one::<String>()).unwrap();
+	let handle = server.start(module);
+
+	let mut client = WebSocketTestClient::new(addr).with_default_timeout().await.unwrap().unwrap();
+
+	// The reused buffers don't leak the content of the previous messages.
+	for msg in ["a long message which is sent first", "short"] {
+		let req = call("echo", vec![msg], Id::Num(1));
+		let response = client.send_request_text(&req).with_default_timeout().await.unwrap().unwrap();
+		assert_eq!(response, ok_response(msg.into(), Id::Num(1)));
+	}
+
+	let sub = call("subscribe_numbers", Vec::<()>::new(), Id::Num(2));
+	let sub_id: JsonValue = deser_call(client.send_request_text(&sub).await.unwrap());
+
+	for n in 0..5 {
+		let msg = client.receive().with_default_timeout().await.unwrap().unwrap();
+		let notif: JsonValue = serde_json::from_str(&msg).unwrap();
+		assert_eq!(notif["params"]["subscription"], sub_id);
+		assert_eq!(notif["params"]["result"], n);
+	}
+
+	handle.stop().unwrap();
+	handle.stopped().await;
+}
+
+#[tokio::test]
+async fn responses_are_sent_by_priority_when_buffer_is_full() {
+	use crate::transport::ws::PrioritySink;
+	use jsonrpsee_core::server::{MethodSink, ResponsePriority};
+
+	let (tx, mut rx) = tokio::sync::mpsc::channel(1);
+	let sink = MethodSink::new(tx);
+	sink.send("full".to_string()).await.unwrap();
+
+	let responses = PrioritySink::new(sink);
+	let mut sends = Vec::new();
+	for (rp, priority) in
+		[("low", ResponsePriority::Low), ("normal", ResponsePriority::Normal), ("high", ResponsePriority::High)]
+	{
+		let responses = responses.clone();
+		sends.push(tokio::spawn(async move { responses.send(rp.to_string(), priority).await }));
+		// Wait until the response is queued.
+		tokio::time::sleep(Duration::from_millis(10)).await;
+	}
+
+	let mut received = Vec::new();
+	for _ in 0..4 {
+		received.push(rx.recv().await.unwrap().into_string());
+	}
+	assert_eq!(received, ["full", "high", "normal", "low"]);
+
+	for send in sends {
+		assert!(send.await.unwrap());
+	}
+}
+
+#[tokio::test]
+async fn unresponsive_connection_is_reported() {
+	init_logger();
+
+	let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+
+	let ping = crate::PingConfig::new()
+		.ping_interval(Duration::from_millis(50))
+		.inactive_limit(Duration::from_millis(60))
+		.max_missed_pongs(2);
+
+	let server = ServerBuilder::default()
+		.enable_ws_ping(ping)
+		.set_on_disconnect(move |info| tx.send(info).unwrap())
+		.build("127.0.0.1:0")
+		.with_default_timeout()
+		.await
+		.unwrap()
+		.unwrap();
+	let addr = server.local_addr().unwrap();
+	let mut module = RpcModule::new(());
+	module
+		.register_async_method("sleep", |_, _, _| async {
+			tokio::time::sleep(Duration::from_secs(60)).await;
+			"done"
+		})
+		.unwrap();
+	let _handle = server.start(module);
+
+	// The client never reads from the connection and thus never answers the pings.
+	let mut client = WebSocketTestClient::new(addr).with_default_timeout().await.unwrap().unwrap();
+	client.send(call("sleep", Vec::<()>::new(), Id::Num(0))).with_default_timeout().await.unwrap().unwrap();
+
+	let info = rx.recv().with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(info.reason, DisconnectReason::Unresponsive);
+	assert_eq!(info.messages, 1);
+	assert_eq!(info.pending_calls, 1);
+}
+
+#[tokio::test]
+async fn handoff_keeps_serving_accepted_connections() {
+	init_logger();
+
+	let mut old_module = RpcModule::new(());
+	old_module.register_method("version", |_, _, _| "old").unwrap();
+	let server = ServerBuilder::default().build("127.0.0.1:0").with_default_timeout().await.unwrap().unwrap();
+	let addr = server.local_addr().unwrap();
+	let old = server.start(old_module);
+
+	let mut old_client = WebSocketTestClient::new(addr).with_default_timeout().await.unwrap().unwrap();
+
+	let listener = old.handoff().with_default_timeout().await.unwrap().unwrap();
+	assert!(matches!(old.handoff().await, Err(HandoffError::Unavailable)));
+
+	let mut new_module = RpcModule::new(());
+	new_module.register_method("version", |_, _, _| "new").unwrap();
+	let new = ServerBuilder::default().build_from_tcp(listener).unwrap().start(new_module);
+
+	// The connection that was accepted by the old server is still served by it.
+	let req = r#"{"jsonrpc":"2.0","method":"version","id":1}"#;
+	let response = old_client.send_request_text(req).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response, ok_response("old".into(), Id::Num(1)));
+
+	// New connections are accepted by the new server.
+	let mut new_client = WebSocketTestClient::new(addr).with_default_timeout().await.unwrap().unwrap();
+	let response = new_client.send_request_text(req).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response, ok_response("new".into(), Id::Num(1)));
+
+	// The old server is stopped once its connections have been drained.
+	assert!(!old.is_stopped());
+	old_client.close().await.unwrap();
+	old.stopped().with_default_timeout().await.unwrap();
+
+	new.stop().unwrap();
+	new.stopped().await;
+}
+
+#[cfg(all(unix, not(any(target_os = "solaris", target_os = "illumos"))))]
+#[tokio::test]
+async fn reuse_port_works() {
+	init_logger();
+
+	let first = ServerBuilder::default().set_reuse_port(true).build("127.0.0.1:0").await.unwrap();
+	let addr = first.local_addr().unwrap();
+
+	let second = ServerBuilder::default().set_reuse_port(true).build(addr).await.unwrap();
+	assert_eq!(second.local_addr().unwrap(), addr);
+	assert!(ServerBuilder::default().build(addr).await.is_err());
+}
+
+#[tokio::test]
+async fn drain_notice_works() {
+	init_logger();
+
+	let mut module = RpcModule::new(());
+	module.register_method("say_hello", |_, _, _| "hello").unwrap();
+	let server = ServerBuilder::default()
+		.set_drain_notice(Duration::from_millis(500))
+		.build("127.0.0.1:0")
+		.with_default_timeout()
+		.await
+		.unwrap()
+		.unwrap();
+	let addr = server.local_addr().unwrap();
+	let handle = server.start(module);
+
+	let mut client = WebSocketTestClient::new(addr).with_default_timeout().await.unwrap().unwrap();
+	handle.stop().unwrap();
+
+	let notif: serde_json::Value =
+		serde_json::from_str(&client.receive().with_default_timeout().await.unwrap().unwrap()).unwrap();
+	assert_eq!(notif["method"], SHUTDOWN_NOTIFICATION);
+	assert_eq!(notif["params"]["deadlineMs"], 500);
+
+	// The connection is still served until the deadline.
+	let req = r#"{"jsonrpc":"2.0","method":"say_hello","id":1}"#;
+	let response = client.send_request_text(req).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response, ok_response("hello".into(), Id::Num(1)));
+
+	// The connection is closed once the deadline has elapsed.
+	handle.stopped().with_default_timeout().await.unwrap();
+	assert!(client.receive().with_default_timeout().await.unwrap().is_err());
+}
+
+#[tokio::test]
+async fn panics_in_handlers_are_isolated() {
+	init_logger();
+
+	let panics = Arc::new(std::sync::Mutex::new(Vec::new()));
+	let mut module = RpcModule::new(());
+	module.register_method::<(), _>("sync_panic", |_, _, _| panic!("sync")).unwrap();
+	module.register_async_method::<(), _, _>("async_panic", |_, _, _| async { panic!("async") }).unwrap();
+	module.register_blocking_method::<(), _>("blocking_panic", |_, _, _| panic!("blocking")).unwrap();
+	let pool = jsonrpsee_core::server::CpuPool::new(1, 1);
+	module.register_cpu_method::<(), _>("cpu_panic", &pool, |_, _, _| panic!("cpu")).unwrap();
+	module.register_method("say_hello", |_, _, _| "hello").unwrap();
+	module
+		.register_subscription::<(), _, _>(
+			"subscribe_panic",
+			"panic",
+			"unsubscribe_panic",
+			|_, pending, _, _| async move {
+				let _sink = pending.accept().await.unwrap();
+				panic!("subscription")
+			},
+		)
+		.unwrap();
+
+	let server = ServerBuilder::default()
+		.set_on_panic({
+			let panics = panics.clone();
+			move |panic| {
+				panics.lock().unwrap().push((panic.method.clone(), panic.message.clone(), panic.correlation_id))
+			}
+		})
+		.build("127.0.0.1:0")
+		.with_default_timeout()
+		.await
+		.unwrap()
+		.unwrap();
+	let addr = server.local_addr().unwrap();
+	let _handle = server.start(module);
+
+	let mut client = WebSocketTestClient::new(addr).with_default_timeout().await.unwrap().unwrap();
+
+	for (id, method) in ["sync_panic", "async_panic", "blocking_panic", "cpu_panic"].into_iter().enumerate() {
+		let req = format!(r#"{{"jsonrpc":"2.0","method":"{method}","id":{id}}}"#);
+		let response: serde_json::Value =
+			serde_json::from_str(&client.send_request_text(req).with_default_timeout().await.unwrap().unwrap())
+				.unwrap();
+		let (_, _, correlation_id) = panics.lock().unwrap().last().cloned().unwrap();
+		assert_eq!(response["id"], id);
+		assert_eq!(response["error"]["code"], -32603);
+		assert_eq!(response["error"]["data"]["correlationId"], correlation_id);
+	}
+
+	// The connection is kept alive.
+	let req = r#"{"jsonrpc":"2.0","method":"say_hello","id":5}"#;
+	let response = client.send_request_text(req).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response, ok_response("hello".into(), Id::Num(5)));
+
+	// A panic of a subscription task closes the subscription only.
+	let req = r#"{"jsonrpc":"2.0","method":"subscribe_panic","id":3}"#;
+	let response: serde_json::Value =
+		serde_json::from_str(&client.send_request_text(req).with_default_timeout().await.unwrap().unwrap()).unwrap();
+	assert!(response["result"].is_number());
+
+	let req = r#"{"jsonrpc":"2.0","method":"say_hello","id":4}"#;
+	let response = client.send_request_text(req).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response, ok_response("hello".into(), Id::Num(4)));
+
+	while panics.lock().unwrap().len() < 5 {
+		tokio::time::sleep(Duration::from_millis(10)).with_default_timeout().await.unwrap();
+	}
+
+	let panics: Vec<_> = panics.lock().unwrap().iter().map(|(method, msg, _)| (method.clone(), msg.clone())).collect();
+	assert_eq!(
+		panics,
+		[
+			("sync_panic".to_owned(), Some("sync".to_owned())),
+			("async_panic".to_owned(), Some("async".to_owned())),
+			("blocking_panic".to_owned(), Some("blocking".to_owned())),
+			("cpu_panic".to_owned(), Some("cpu".to_owned())),
+			("subscribe_panic".to_owned(), Some("subscription".to_owned())),
+		]
+	);
+}
+
+#[tokio::test]
+async fn panic_of_subscription_callback_keeps_the_extensions() {
+	init_logger();
+
+	#[derive(Debug, Clone)]
+	struct TraceId;
+
+	#[derive(Clone)]
+	struct Trace<S>(S, Arc<AtomicUsize>);
+
+	impl<'a, S> RpcServiceT<'a> for Trace<S>
+	where
+		S: RpcServiceT<'a> + Send + Sync,
+		S::Future: Send + 'a,
+	{
+		type Future = futures_util::future::BoxFuture<'a, MethodResponse>;
+
+		fn call(&self, mut request: jsonrpsee_types::Request<'a>) -> Self::Future {
+			request.extensions_mut().insert(TraceId);
+			let fut = self.0.call(request);
+			let traced = self.1.clone();
+
+			Box::pin(async move {
+				let rp = fut.await;
+				if rp.extensions().get::<TraceId>().is_some() {
+					traced.fetch_add(1, Ordering::SeqCst);
+				}
+				rp
+			})
+		}
+	}
+
+	let mut module = RpcModule::new(());
+	module
+		.register_subscription_raw::<(), _>("subscribe_panic", "panic", "unsubscribe_panic", |_, _, _, _| {
+			panic!("subscription callback")
+		})
+		.unwrap();
+
+	let traced = Arc::new(AtomicUsize::new(0));
+	let server = ServerBuilder::default()
+		.set_rpc_middleware(RpcServiceBuilder::new().layer_fn({
+			let traced = traced.clone();
+			move |service| Trace(service, traced.clone())
+		}))
+		.build("127.0.0.1:0")
+		.with_default_timeout()
+		.await
+		.unwrap()
+		.unwrap();
+	let addr = server.local_addr().unwrap();
+	let _handle = server.start(module);
+
+	let mut client = WebSocketTestClient::new(addr).with_default_timeout().await.unwrap().unwrap();
+	let req = r#"{"jsonrpc":"2.0","method":"subscribe_panic","id":1}"#;
+	let response: serde_json::Value =
+		serde_json::from_str(&client.send_request_text(req).with_default_timeout().await.unwrap().unwrap()).unwrap();
+	assert_eq!(response["error"]["code"], -32603);
+	assert_eq!(traced.load(Ordering::SeqCst), 1);
+}
+
+/// Performs the WebSocket handshake on a TCP stream to exchange hand-crafted frames.
+async fn raw_ws_connect(addr: std::net::SocketAddr) -> TcpStream {
+	let mut stream = TcpStream::connect(addr).await.unwrap();
+	let req = format!(
+		"GET / HTTP/1.1\r\nHost: {addr}\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+		Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\nSec-WebSocket-Version: 13\r\n\r\n"
+	);
+	stream.write_all(req.as_bytes()).await.unwrap();
+
+	let mut response = Vec::new();
+	while !response.ends_with(b"\r\n\r\n") {
+		response.push(stream.read_u8().await.unwrap());
+	}
+	assert!(response.starts_with(b"HTTP/1.1 101"));
+
+	stream
+}
+
+/// Sends a text message fragmented into one frame per chunk, the chunks must be smaller than 126 bytes.
+async fn raw_ws_send(stream: &mut TcpStream, chunks: &[&str]) {
+	let mut frames = Vec::new();
+	for (i, chunk) in chunks.iter().enumerate() {
+		let opcode = if i == 0 { 0x1 } else { 0x0 };
+		let fin = if i == chunks.len() - 1 { 0x80 } else { 0x0 };
+		// The client frames are masked, with a zero mask the payload is sent as is.
+		frames.extend_from_slice(&[fin | opcode, 0x80 | chunk.len() as u8, 0, 0, 0, 0]);
+		frames.extend_from_slice(chunk.as_bytes());
+	}
+	stream.write_all(&frames).await.unwrap();
+}
+
+/// Receives a frame and returns the first byte of its header and its payload.
+async fn raw_ws_recv(stream: &mut TcpStream) -> (u8, Vec<u8>) {
+	let first = stream.read_u8().await.unwrap();
+	let len = match stream.read_u8().await.unwrap() {
+		126 => stream.read_u16().await.unwrap() as usize,
+		127 => stream.read_u64().await.unwrap() as usize,
+		len => len as usize,
+	};
+	let mut payload = vec![0; len];
+	stream.read_exact(&mut payload).await.unwrap();
+
+	(first, payload)
+}
+
+/// Reads until the server closes the connection.
+async fn raw_ws_closed(stream: &mut TcpStream) {
+	let mut rest = Vec::new();
+	_ = stream.read_to_end(&mut rest).with_default_timeout().await.unwrap();
+}
+
+#[tokio::test]
+async fn ws_frame_limits_work() {
+	init_logger();
+
+	let mut module = RpcModule::new(());
+	module.register_method("say_hello", |_, _, _| "hello").unwrap();
+
+	let server = ServerBuilder::default()
+		.set_ws_frame_config(WsFrameConfig::new().max_frame_size(64).max_frames_per_message(3))
+		.build("127.0.0.1:0")
+		.with_default_timeout()
+		.await
+		.unwrap()
+		.unwrap();
+	let addr = server.local_addr().unwrap();
+	let _handle = server.start(module);
+
+	let mut stream = raw_ws_connect(addr).with_default_timeout().await.unwrap();
+
+	raw_ws_send(&mut stream, &[r#"{"jsonrpc":"2.0","#, r#""method":"say_hello","#, r#""id":1}"#]).await;
+	let (first, payload) = raw_ws_recv(&mut stream).with_default_timeout().await.unwrap();
+	assert_eq!(first, 0x81);
+	assert_eq!(String::from_utf8(payload).unwrap(), ok_response("hello".into(), Id::Num(1)));
+
+	// A message of more frames than allowed closes the connection with a protocol error.
+	raw_ws_send(&mut stream, &[r#"{"jsonrpc":"2.0","#, r#""method":"#, r#""say_hello","#, r#""id":2}"#]).await;
+	let (first, payload) = raw_ws_recv(&mut stream).with_default_timeout().await.unwrap();
+	assert_eq!(first, 0x88);
+	assert_eq!(payload[..2], 1002_u16.to_be_bytes());
+	raw_ws_closed(&mut stream).await;
+
+	// A frame that exceeds the max size closes the connection.
+	let mut stream = raw_ws_connect(addr).with_default_timeout().await.unwrap();
+	raw_ws_send(&mut stream, &[&format!(r#"{{"jsonrpc":"2.0","method":"say_hello","id":3}}{:30}"#, "")]).await;
+	raw_ws_closed(&mut stream).await;
+}
+
+#[tokio::test]
+async fn ws_fragmentation_threshold_works() {
+	init_logger();
+
+	let mut module = RpcModule::new(());
+	module.register_method("say_hello", |_, _, _| "hello").unwrap();
+
+	let server = ServerBuilder::default()
+		.set_ws_frame_config(WsFrameConfig::new().fragmentation_threshold(16))
+		.build("127.0.0.1:0")
+		.with_default_timeout()
+		.await
+		.unwrap()
+		.unwrap();
+	let addr = server.local_addr().unwrap();
+	let _handle = server.start(module);
+
+	let expected = ok_response("hello".into(), Id::Num(1));
+
+	let mut stream = raw_ws_connect(addr).with_default_timeout().await.unwrap();
+	raw_ws_send(&mut stream, &[r#"{"jsonrpc":"2.0","method":"say_hello","id":1}"#]).await;
+
+	let mut message = Vec::new();
+	let mut frames = Vec::new();
+	loop {
+		let (first, payload) = raw_ws_recv(&mut stream).with_default_timeout().await.unwrap();
+		assert!(payload.len() <= 16);
+		message.extend(payload);
+		frames.push(first);
+		if first & 0x80 != 0 {
+			break;
+		}
+	}
+
+	assert_eq!(String::from_utf8(message).unwrap(), expected);
+	assert_eq!(frames.len(), expected.len().div_ceil(16));
+	assert_eq!(frames[0], 0x01);
+	assert!(frames[1..frames.len() - 1].iter().all(|first| *first == 0x00));
+	assert_eq!(frames[frames.len() - 1], 0x80);
+
+	// The fragments are reassembled by the client.
+	let mut client = WebSocketTestClient::new(addr).with_default_timeout().await.unwrap().unwrap();
+	let req = r#"{"jsonrpc":"2.0","method":"say_hello","id":1}"#;
+	let response = client.send_request_text(req).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response, expected);
+}
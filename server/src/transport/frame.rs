@@ -0,0 +1,347 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any
+// person obtaining a copy of this software and associated
+// documentation files (the "Software"), to deal in the
+// Software without restriction, including without
+// limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software
+// is furnished to do so, subject to the following
+// conditions:
+//
+// The above copyright notice and this permission notice
+// shall be included in all copies or substantial portions
+// of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF
+// ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED
+// TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A
+// PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT
+// SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY
+// CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+// IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Inspection of the WebSocket frames below `soketto`, which only enforces the size of frames and messages.
+
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_util::io::{AsyncRead, AsyncWrite};
+use futures_util::ready;
+
+use crate::WsFrameConfig;
+
+const MAX_HEADER_SIZE: usize = 14;
+const FIN: u8 = 0x80;
+const OPCODE_CLOSE: u8 = 0x8;
+/// Status code of a close frame for a connection that is closed because of a protocol error.
+const PROTOCOL_ERROR: [u8; 2] = 1002_u16.to_be_bytes();
+
+/// I/O of a WebSocket connection that counts the frames of the incoming messages
+/// and fragments the outgoing messages according to [`WsFrameConfig`].
+///
+/// If an incoming message exceeds the max number of frames the read fails, which makes
+/// the connection close, and the close frame that is sent has the status code of a protocol error.
+pub(crate) struct WsFrameIo<T> {
+	inner: T,
+	counter: Option<FrameCounter>,
+	fragmenter: Option<Fragmenter>,
+}
+
+impl<T> WsFrameIo<T> {
+	pub(crate) fn new(inner: T, config: &WsFrameConfig) -> Self {
+		let counter = config.max_frames_per_message.map(FrameCounter::new);
+		// The outgoing frames are also parsed to set the status code of the close frame if messages are counted.
+		let fragmenter = match (config.fragmentation_threshold, &counter) {
+			(Some(threshold), _) => Some(Fragmenter::new(threshold as u64)),
+			(None, Some(_)) => Some(Fragmenter::new(u64::MAX)),
+			(None, None) => None,
+		};
+
+		Self { inner, counter, fragmenter }
+	}
+}
+
+impl<T: AsyncRead + Unpin> AsyncRead for WsFrameIo<T> {
+	fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+		let this = &mut *self;
+		let n = ready!(Pin::new(&mut this.inner).poll_read(cx, buf))?;
+
+		if let Some(counter) = &mut this.counter {
+			if let Err(err) = counter.inspect(&buf[..n]) {
+				if let Some(fragmenter) = &mut this.fragmenter {
+					fragmenter.protocol_error = true;
+				}
+				return Poll::Ready(Err(err));
+			}
+		}
+
+		Poll::Ready(Ok(n))
+	}
+}
+
+impl<T: AsyncWrite + Unpin> AsyncWrite for WsFrameIo<T> {
+	fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+		let this = &mut *self;
+		let Some(fragmenter) = &mut this.fragmenter else {
+			return Pin::new(&mut this.inner).poll_write(cx, buf);
+		};
+
+		ready!(fragmenter.poll_drain(&mut this.inner, cx))?;
+		fragmenter.encode(buf);
+
+		Poll::Ready(Ok(buf.len()))
+	}
+
+	fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+		let this = &mut *self;
+		if let Some(fragmenter) = &mut this.fragmenter {
+			ready!(fragmenter.poll_drain(&mut this.inner, cx))?;
+		}
+		Pin::new(&mut this.inner).poll_flush(cx)
+	}
+
+	fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+		let this = &mut *self;
+		if let Some(fragmenter) = &mut this.fragmenter {
+			ready!(fragmenter.poll_drain(&mut this.inner, cx))?;
+		}
+		Pin::new(&mut this.inner).poll_close(cx)
+	}
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Header {
+	raw: [u8; MAX_HEADER_SIZE],
+	size: usize,
+	payload_len: u64,
+}
+
+impl Header {
+	fn is_fin(&self) -> bool {
+		self.raw[0] & FIN != 0
+	}
+
+	/// Data frames, i.e. continuation, text and binary frames, have the opcodes below 0x8.
+	fn is_data(&self) -> bool {
+		self.raw[0] & 0x0f < OPCODE_CLOSE
+	}
+
+	fn is_close(&self) -> bool {
+		self.raw[0] & 0x0f == OPCODE_CLOSE
+	}
+
+	fn is_masked(&self) -> bool {
+		self.raw[1] & 0x80 != 0
+	}
+
+	fn encode(out: &mut Vec<u8>, first: u8, payload_len: u64) {
+		out.push(first);
+		if payload_len < 126 {
+			out.push(payload_len as u8);
+		} else if let Ok(len) = u16::try_from(payload_len) {
+			out.push(126);
+			out.extend_from_slice(&len.to_be_bytes());
+		} else {
+			out.push(127);
+			out.extend_from_slice(&payload_len.to_be_bytes());
+		}
+	}
+}
+
+/// Decodes the frame headers byte by byte as they may be split across reads and writes.
+#[derive(Debug, Default)]
+struct HeaderDecoder {
+	buf: [u8; MAX_HEADER_SIZE],
+	len: usize,
+}
+
+impl HeaderDecoder {
+	fn push(&mut self, byte: u8) -> Option<Header> {
+		self.buf[self.len] = byte;
+		self.len += 1;
+
+		if self.len < 2 || self.len < self.header_size() {
+			return None;
+		}
+
+		let payload_len = match self.buf[1] & 0x7f {
+			126 => u16::from_be_bytes([self.buf[2], self.buf[3]]) as u64,
+			127 => u64::from_be_bytes(self.buf[2..10].try_into().expect("8 bytes; qed")),
+			len => len as u64,
+		};
+		let header = Header { raw: self.buf, size: self.len, payload_len };
+		self.len = 0;
+
+		Some(header)
+	}
+
+	fn header_size(&self) -> usize {
+		let extended_len = match self.buf[1] & 0x7f {
+			126 => 2,
+			127 => 8,
+			_ => 0,
+		};
+		let mask = if self.buf[1] & 0x80 != 0 { 4 } else { 0 };
+
+		2 + extended_len + mask
+	}
+}
+
+/// Rejects the messages that are sent in more than `max` frames.
+struct FrameCounter {
+	max: u32,
+	decoder: HeaderDecoder,
+	payload: u64,
+	frames: u32,
+}
+
+impl FrameCounter {
+	fn new(max: u32) -> Self {
+		Self { max, decoder: HeaderDecoder::default(), payload: 0, frames: 0 }
+	}
+
+	fn inspect(&mut self, mut bytes: &[u8]) -> io::Result<()> {
+		while !bytes.is_empty() {
+			if self.payload > 0 {
+				let n = self.payload.min(bytes.len() as u64);
+				self.payload -= n;
+				bytes = &bytes[n as usize..];
+				continue;
+			}
+
+			let header = self.decoder.push(bytes[0]);
+			bytes = &bytes[1..];
+
+			let Some(header) = header else { continue };
+			self.payload = header.payload_len;
+
+			// Control frames may be interleaved with the fragments of a message.
+			if header.is_data() {
+				self.frames += 1;
+				if self.frames > self.max {
+					return Err(io::Error::new(
+						io::ErrorKind::InvalidData,
+						format!("message exceeds the max of {} frames", self.max),
+					));
+				}
+				if header.is_fin() {
+					self.frames = 0;
+				}
+			}
+		}
+
+		Ok(())
+	}
+}
+
+/// Splits the outgoing data frames whose payload exceeds `threshold` into fragments.
+///
+/// The fragmented frames are buffered until they are written to the underlying I/O.
+struct Fragmenter {
+	threshold: u64,
+	decoder: HeaderDecoder,
+	payload: u64,
+	split: Option<Split>,
+	/// Whether the connection is closed because of a protocol error.
+	protocol_error: bool,
+	/// Number of bytes of the status code of the close frame that remain to be replaced.
+	close_code: usize,
+	out: Vec<u8>,
+	written: usize,
+}
+
+/// State of the frame that is being fragmented.
+struct Split {
+	/// First byte of the header of the next fragment.
+	first: u8,
+	/// Remaining payload of the current fragment.
+	fragment: u64,
+}
+
+impl Fragmenter {
+	fn new(threshold: u64) -> Self {
+		Self {
+			threshold,
+			decoder: HeaderDecoder::default(),
+			payload: 0,
+			split: None,
+			protocol_error: false,
+			close_code: 0,
+			out: Vec::new(),
+			written: 0,
+		}
+	}
+
+	fn encode(&mut self, mut bytes: &[u8]) {
+		while !bytes.is_empty() {
+			if self.payload == 0 {
+				let header = self.decoder.push(bytes[0]);
+				bytes = &bytes[1..];
+
+				let Some(header) = header else { continue };
+				self.payload = header.payload_len;
+
+				if self.protocol_error && header.is_close() && header.payload_len >= 2 {
+					self.close_code = PROTOCOL_ERROR.len();
+				}
+
+				// Masked frames can't be re-framed without knowing the mask, which a server doesn't send anyway.
+				if header.is_data() && header.is_fin() && !header.is_masked() && header.payload_len > self.threshold {
+					self.split = Some(Split { first: header.raw[0] & !FIN, fragment: 0 });
+				} else {
+					self.split = None;
+					self.out.extend_from_slice(&header.raw[..header.size]);
+				}
+				continue;
+			}
+
+			let n = match &mut self.split {
+				Some(split) => {
+					if split.fragment == 0 {
+						split.fragment = self.threshold.min(self.payload);
+						let fin = if split.fragment == self.payload { FIN } else { 0 };
+						Header::encode(&mut self.out, split.first | fin, split.fragment);
+						// The fragments after the first one are continuation frames.
+						split.first = 0;
+					}
+					let n = split.fragment.min(bytes.len() as u64);
+					split.fragment -= n;
+					n
+				}
+				None => self.payload.min(bytes.len() as u64),
+			};
+
+			let start = self.out.len();
+			self.out.extend_from_slice(&bytes[..n as usize]);
+			for byte in &mut self.out[start..] {
+				if self.close_code == 0 {
+					break;
+				}
+				*byte = PROTOCOL_ERROR[PROTOCOL_ERROR.len() - self.close_code];
+				self.close_code -= 1;
+			}
+			self.payload -= n;
+			bytes = &bytes[n as usize..];
+		}
+	}
+
+	fn poll_drain<T: AsyncWrite + Unpin>(&mut self, inner: &mut T, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+		while self.written < self.out.len() {
+			let n = ready!(Pin::new(&mut *inner).poll_write(cx, &self.out[self.written..]))?;
+			if n == 0 {
+				return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+			}
+			self.written += n;
+		}
+
+		self.out.clear();
+		self.written = 0;
+
+		Poll::Ready(Ok(()))
+	}
+}
@@ -1,15 +1,32 @@
 use crate::{
+	hooks::MessageTap,
+	long_polling::LongPolling,
 	middleware::rpc::{RpcService, RpcServiceBuilder, RpcServiceCfg, RpcServiceT},
-	server::{handle_rpc_call, ServerConfig},
-	BatchRequestConfig, ConnectionState, HttpRequest, HttpResponse, LOG_TARGET,
+	server::{handle_rpc_call, transport_max_request_size, ServerConfig},
+	transport::Encoding,
+	BatchExecution, BatchFailFast, BatchRequestConfig, BatchResponseOrder, CallerIdentity, ConnectionState, HttpBody,
+	HttpRequest, HttpResponse, LOG_TARGET,
 };
-use http::Method;
+use futures_util::{future, stream, FutureExt, StreamExt};
+use http::{request::Parts, Extensions, HeaderMap, Method};
+use http_body::Frame;
+use http_body_util::StreamBody;
 use hyper::body::{Body, Bytes};
 use jsonrpsee_core::{
-	http_helpers::{read_body, HttpError},
-	server::Methods,
+	codec::{Codec, Codecs},
+	http_helpers::{read_body, read_deadline_header, read_encoded_body, HttpError},
+	server::{
+		CancellationGuard, Deadline, MethodResponse, Methods, ResponseExtensions, ResponseStream, SinkMessage,
+		StreamedResponseSupport,
+	},
 	BoxError,
 };
+use jsonrpsee_types::SubscriptionId;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+
+/// Max number of chunks of a streamed batch response that are buffered until backpressure kicks in.
+const BATCH_STREAM_CAPACITY: usize = 16;
 
 /// Checks that content type of received request is valid for JSON-RPC.
 pub fn content_type_is_json<T: Body>(request: &HttpRequest<T>) -> bool {
@@ -28,11 +45,35 @@ pub fn is_json(content_type: Option<&hyper::header::HeaderValue>) -> bool {
 	})
 }
 
+/// Returns the codec of the `Content-Type` of a request, see [`jsonrpsee_core::codec`].
+///
+/// The codec is looked up in the [`Codecs`] of the request extensions, which the server
+/// inserts from its configuration, or in the builtin codecs otherwise.
+pub fn content_type_codec<T: Body>(request: &HttpRequest<T>) -> Option<&'static dyn Codec> {
+	let content_type = request.headers().get(hyper::header::CONTENT_TYPE).and_then(|val| val.to_str().ok())?;
+
+	match request.extensions().get::<Codecs>() {
+		Some(codecs) => codecs.from_content_type(content_type),
+		None => jsonrpsee_core::codec::from_content_type(content_type),
+	}
+}
+
+/// Returns true if the `Accept` header of a request accepts server-sent events.
+pub(crate) fn accepts_event_stream(headers: &hyper::HeaderMap) -> bool {
+	headers
+		.get_all(hyper::header::ACCEPT)
+		.iter()
+		.filter_map(|val| val.to_str().ok())
+		.flat_map(|val| val.split(','))
+		.filter_map(|media_range| media_range.split(';').next())
+		.any(|media_type| media_type.trim().eq_ignore_ascii_case("text/event-stream"))
+}
+
 /// Make JSON-RPC HTTP call with a [`RpcServiceBuilder`]
 ///
 /// Fails if the HTTP request was a malformed JSON-RPC request.
 pub async fn call_with_service_builder<L, B>(
-	request: HttpRequest<B>,
+	mut request: HttpRequest<B>,
 	server_cfg: ServerConfig,
 	conn: ConnectionState,
 	methods: impl Into<Methods>,
@@ -46,33 +87,243 @@ where
 	<L as tower::Layer<RpcService>>::Service: Send + Sync + 'static,
 	for<'a> <L as tower::Layer<RpcService>>::Service: RpcServiceT<'a>,
 {
-	let ServerConfig { max_response_body_size, batch_requests_config, max_request_body_size, .. } = server_cfg;
+	let ServerConfig {
+		max_response_body_size,
+		batch_requests_config,
+		batch_execution,
+		batch_response_order,
+		batch_fail_fast,
+		stream_batch_responses,
+		max_request_body_size,
+		method_timeout,
+		on_response,
+		audit_log,
+		codecs,
+		..
+	} = server_cfg;
 
+	request.extensions_mut().insert(codecs);
+
+	let methods = methods.into();
 	let rpc_service = rpc_service.service(RpcService::new(
-		methods.into(),
+		methods.clone(),
 		max_response_body_size as usize,
+		None,
+		method_timeout,
+		on_response,
+		audit_log,
 		conn.conn_id.into(),
 		RpcServiceCfg::OnlyCalls,
 	));
 
-	let rp =
-		call_with_service(request, batch_requests_config, max_request_body_size, rpc_service, max_response_body_size)
+	call_with_service_and_methods(
+		request,
+		batch_requests_config,
+		batch_execution,
+		batch_response_order,
+		&batch_fail_fast,
+		stream_batch_responses,
+		max_request_body_size,
+		&methods,
+		rpc_service,
+		max_response_body_size,
+		conn,
+	)
+	.await
+}
+
+/// Make JSON-RPC HTTP call with a service [`RpcServiceT`]
+///
+/// Fails if the HTTP request was a malformed JSON-RPC request.
+pub async fn call_with_service<S, B>(
+	request: HttpRequest<B>,
+	batch_config: BatchRequestConfig,
+	max_request_size: u32,
+	rpc_service: S,
+	max_response_size: u32,
+) -> HttpResponse
+where
+	B: http_body::Body<Data = Bytes> + Send + 'static,
+	B::Data: Send,
+	B::Error: Into<BoxError>,
+	for<'a> S: RpcServiceT<'a> + Send,
+{
+	let (mut parts, body, is_single, encoding) = match read_request(request, max_request_size).await {
+		Ok(r) => r,
+		Err(rp) => return rp,
+	};
+
+	// Cancelled once the call has been answered or when this future
+	// is dropped because the client aborted the request.
+	let cancel_guard = CancellationGuard::new();
+	parts.extensions.insert(cancel_guard.token());
+
+	insert_deadline(&mut parts);
+
+	// The streamed responses are written as is, which only works for JSON.
+	let streamed = StreamedResponseSupport::new();
+	if is_single && matches!(encoding, Encoding::Json) {
+		parts.extensions.insert(streamed.clone());
+	}
+
+	let rp = handle_rpc_call(
+		&body,
+		is_single,
+		batch_config,
+		BatchExecution::default(),
+		BatchResponseOrder::default(),
+		None,
+		max_request_size,
+		max_response_size,
+		&Methods::new(),
+		&rpc_service,
+		parts.extensions,
+		None,
+	)
+	.await;
+
+	let rp_ext = take_response_extensions(rp.as_ref());
+
+	if let Some(chunks) = take_streamed_response(rp.as_ref(), &streamed) {
+		return with_response_extensions(streamed_response(chunks, cancel_guard), rp_ext);
+	}
+	drop(cancel_guard);
+
+	// If the response is empty it means that it was a notification or empty batch.
+	// For HTTP these are just ACK:ed with a empty body.
+	with_response_extensions(encoded_response(rp.map_or(String::new(), |r| r.into_result()), encoding), rp_ext)
+}
+
+/// Similar to [`call_with_service`] but respects the limits configured on the individual methods
+/// and may stream the responses of batch requests.
+///
+/// The connection state is held until the response has been sent.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn call_with_service_and_methods<S, B>(
+	request: HttpRequest<B>,
+	batch_config: BatchRequestConfig,
+	batch_execution: BatchExecution,
+	batch_response_order: BatchResponseOrder,
+	batch_fail_fast: &BatchFailFast,
+	stream_batch_responses: bool,
+	max_request_size: u32,
+	methods: &Methods,
+	rpc_service: S,
+	max_response_size: u32,
+	conn: ConnectionState,
+) -> HttpResponse
+where
+	B: http_body::Body<Data = Bytes> + Send + 'static,
+	B::Data: Send,
+	B::Error: Into<BoxError>,
+	for<'a> S: RpcServiceT<'a> + Send + Sync + 'static,
+{
+	let transport_max_request_size = transport_max_request_size(methods, max_request_size);
+
+	let (mut parts, body, is_single, encoding) = match read_request(request, transport_max_request_size).await {
+		Ok(r) => r,
+		Err(rp) => return rp,
+	};
+
+	// Cancelled once the call has been answered or when this future
+	// is dropped because the client aborted the request.
+	let cancel_guard = CancellationGuard::new();
+	parts.extensions.insert(cancel_guard.token());
+
+	insert_deadline(&mut parts);
+
+	let batch_fail_fast = batch_fail_fast.resolve(&parts.headers).cloned();
+	let is_json = matches!(encoding, Encoding::Json);
+
+	// The streamed responses are written as is, which only works for JSON.
+	let streamed = StreamedResponseSupport::new();
+	if is_single && is_json {
+		parts.extensions.insert(streamed.clone());
+	}
+
+	if !is_single && stream_batch_responses && is_json {
+		let methods = methods.clone();
+		let (tx, rx) = mpsc::channel(BATCH_STREAM_CAPACITY);
+
+		// The batch is processed while the response body is polled,
+		// which holds the connection state until the response has been sent.
+		let process_batch = async move {
+			let rp = handle_rpc_call(
+				&body,
+				is_single,
+				batch_config,
+				batch_execution,
+				batch_response_order,
+				batch_fail_fast.as_ref(),
+				max_request_size,
+				max_response_size,
+				&methods,
+				&rpc_service,
+				parts.extensions,
+				Some(tx.clone()),
+			)
 			.await;
 
+			// The batch couldn't be processed at all.
+			if let Some(rp) = rp {
+				_ = tx.send(rp.into_result()).await;
+			}
+
+			drop(cancel_guard);
+			drop(conn);
+		};
+
+		let chunks = ReceiverStream::new(rx).map(|chunk| Ok::<_, BoxError>(Frame::data(Bytes::from(chunk))));
+		let process_batch = process_batch.into_stream().filter_map(|()| future::ready(None));
+
+		return response::ok_response(HttpBody::new(StreamBody::new(stream::select(chunks, process_batch))));
+	}
+
+	let rp = handle_rpc_call(
+		&body,
+		is_single,
+		batch_config,
+		batch_execution,
+		batch_response_order,
+		batch_fail_fast.as_ref(),
+		max_request_size,
+		max_response_size,
+		methods,
+		&rpc_service,
+		parts.extensions,
+		None,
+	)
+	.await;
+
+	let rp_ext = take_response_extensions(rp.as_ref());
+
+	if let Some(chunks) = take_streamed_response(rp.as_ref(), &streamed) {
+		return with_response_extensions(streamed_response(chunks, (cancel_guard, conn)), rp_ext);
+	}
+	drop(cancel_guard);
 	drop(conn);
 
-	rp
+	// If the response is empty it means that it was a notification or empty batch.
+	// For HTTP these are just ACK:ed with a empty body.
+	with_response_extensions(encoded_response(rp.map_or(String::new(), |r| r.into_result()), encoding), rp_ext)
 }
 
-/// Make JSON-RPC HTTP call with a service [`RpcServiceT`]
+/// Make a JSON-RPC call which may be a subscription and respond with server-sent events.
 ///
-/// Fails if the HTTP request was a malformed JSON-RPC request.
-pub async fn call_with_service<S, B>(
+/// The response of the call is the first event, if it's not sent by the subscription itself, followed by
+/// the messages that the subscriptions send to `events`. The event stream ends once the subscriptions are closed.
+///
+/// The connection state is held until the event stream ends.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn call_with_event_stream<S, B>(
 	request: HttpRequest<B>,
 	batch_config: BatchRequestConfig,
 	max_request_size: u32,
+	methods: &Methods,
 	rpc_service: S,
 	max_response_size: u32,
+	events: mpsc::Receiver<SinkMessage>,
+	conn: ConnectionState,
 ) -> HttpResponse
 where
 	B: http_body::Body<Data = Bytes> + Send + 'static,
@@ -80,31 +331,239 @@ where
 	B::Error: Into<BoxError>,
 	for<'a> S: RpcServiceT<'a> + Send,
 {
-	// Only the `POST` method is allowed.
-	match *request.method() {
-		Method::POST if content_type_is_json(&request) => {
-			let (parts, body) = request.into_parts();
-
-			let (body, is_single) = match read_body(&parts.headers, body, max_request_size).await {
-				Ok(r) => r,
-				Err(HttpError::TooLarge) => return response::too_large(max_request_size),
-				Err(HttpError::Malformed) => return response::malformed(),
-				Err(HttpError::Stream(e)) => {
-					tracing::warn!(target: LOG_TARGET, "Internal error reading request body: {}", e);
-					return response::internal_error();
-				}
-			};
-
-			let rp = handle_rpc_call(&body, is_single, batch_config, max_response_size, &rpc_service, parts.extensions)
-				.await;
-
-			// If the response is empty it means that it was a notification or empty batch.
-			// For HTTP these are just ACK:ed with a empty body.
-			response::ok_response(rp.map_or(String::new(), |r| r.into_result()))
+	let transport_max_request_size = transport_max_request_size(methods, max_request_size);
+
+	// The events are always JSON.
+	let (mut parts, body, is_single, _) = match read_request(request, transport_max_request_size).await {
+		Ok(r) => r,
+		Err(rp) => return rp,
+	};
+
+	// Cancelled once the event stream has ended or when the call is dropped
+	// because the client aborted the request.
+	let cancel_guard = CancellationGuard::new();
+	parts.extensions.insert(cancel_guard.token());
+
+	let rp = handle_rpc_call(
+		&body,
+		is_single,
+		batch_config,
+		BatchExecution::default(),
+		BatchResponseOrder::default(),
+		None,
+		max_request_size,
+		max_response_size,
+		methods,
+		&rpc_service,
+		parts.extensions,
+		None,
+	)
+	.await;
+
+	// The event stream ends once the subscriptions, which hold the other senders, are closed.
+	drop(rpc_service);
+
+	let rp = rp.filter(|rp| !rp.is_subscription()).map(|rp| rp.into_result());
+	let guard = (cancel_guard, conn);
+
+	let events = stream::iter(rp).chain(ReceiverStream::new(events).map(SinkMessage::into_string)).map(move |msg| {
+		let _guard = &guard;
+		Ok::<_, BoxError>(Frame::data(Bytes::from(to_event(&msg))))
+	});
+
+	response::event_stream(HttpBody::new(StreamBody::new(events)))
+}
+
+/// Make a JSON-RPC call which may be a subscription whose notifications are long-polled.
+///
+/// If the call was an accepted subscription the messages that the subscription sends to
+/// `notifications` are buffered until they are polled, otherwise they are dropped.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn call_with_long_polling<S, B>(
+	request: HttpRequest<B>,
+	batch_config: BatchRequestConfig,
+	max_request_size: u32,
+	methods: &Methods,
+	rpc_service: S,
+	max_response_size: u32,
+	mut notifications: mpsc::Receiver<SinkMessage>,
+	long_polling: &LongPolling,
+	conn: ConnectionState,
+) -> HttpResponse
+where
+	B: http_body::Body<Data = Bytes> + Send + 'static,
+	B::Data: Send,
+	B::Error: Into<BoxError>,
+	for<'a> S: RpcServiceT<'a> + Send,
+{
+	let transport_max_request_size = transport_max_request_size(methods, max_request_size);
+
+	let (mut parts, body, is_single, encoding) = match read_request(request, transport_max_request_size).await {
+		Ok(r) => r,
+		Err(rp) => return rp,
+	};
+
+	// Cancelled once the call has been answered or when this future
+	// is dropped because the client aborted the request.
+	let cancel_guard = CancellationGuard::new();
+	parts.extensions.insert(cancel_guard.token());
+
+	insert_deadline(&mut parts);
+
+	let rp = handle_rpc_call(
+		&body,
+		is_single,
+		batch_config,
+		BatchExecution::default(),
+		BatchResponseOrder::default(),
+		None,
+		max_request_size,
+		max_response_size,
+		methods,
+		&rpc_service,
+		parts.extensions,
+		None,
+	)
+	.await;
+
+	drop(rpc_service);
+	drop(cancel_guard);
+	drop(conn);
+
+	let rp = rp.map(|rp| {
+		if let Some(sub_id) = rp.extensions().get::<SubscriptionId<'static>>().filter(|_| rp.is_subscription()) {
+			// The response to the subscription call is sent to the buffer as well.
+			_ = notifications.try_recv();
+			let caller = rp.extensions().get::<CallerIdentity>().cloned();
+			long_polling.insert(sub_id.clone(), caller, notifications);
 		}
+		rp.into_result()
+	});
+
+	// If the response is empty it means that it was a notification or empty batch.
+	// For HTTP these are just ACK:ed with a empty body.
+	encoded_response(rp.unwrap_or_default(), encoding)
+}
+
+/// Format a JSON-RPC message as a server-sent event.
+fn to_event(msg: &str) -> String {
+	let mut event = String::with_capacity(msg.len() + 8);
+
+	for line in msg.lines() {
+		event.push_str("data: ");
+		event.push_str(line);
+		event.push('\n');
+	}
+	event.push('\n');
+
+	event
+}
+
+/// Inserts the [`Deadline`] of the request into its extensions, if the client has sent one.
+fn insert_deadline(parts: &mut Parts) {
+	if let Some(deadline) = read_deadline_header(&parts.headers).and_then(Deadline::after) {
+		parts.extensions.insert(deadline);
+	}
+}
+
+/// Take the chunks of the streamed response to a single call, which replace the
+/// placeholder response unless a middleware turned it into an error.
+fn take_streamed_response(rp: Option<&MethodResponse>, streamed: &StreamedResponseSupport) -> Option<ResponseStream> {
+	let chunks = streamed.take()?;
+	rp.filter(|rp| rp.is_success()).map(|_| chunks)
+}
+
+/// Create a chunked response from a streamed method response.
+///
+/// The `guard` is held until the response has been sent or the client went away.
+fn streamed_response<G: Send + 'static>(chunks: ResponseStream, guard: G) -> HttpResponse {
+	let chunks = chunks.map(move |chunk| {
+		let _guard = &guard;
+		chunk.map(|chunk| Frame::data(Bytes::from(chunk)))
+	});
+
+	response::ok_response(HttpBody::new(StreamBody::new(chunks)))
+}
+
+/// Take the values of the [`ResponseExtensions`] of the response to a single call.
+fn take_response_extensions(rp: Option<&MethodResponse>) -> Option<Extensions> {
+	rp.and_then(|rp| rp.extensions().get::<ResponseExtensions>()).map(ResponseExtensions::take)
+}
+
+/// Move the values of the [`ResponseExtensions`] of a single call into the HTTP response,
+/// where a [`HeaderMap`] is appended to the headers.
+fn with_response_extensions(mut response: HttpResponse, extensions: Option<Extensions>) -> HttpResponse {
+	let Some(mut extensions) = extensions else {
+		return response;
+	};
+
+	if let Some(headers) = extensions.remove::<HeaderMap>() {
+		for (name, value) in &headers {
+			response.headers_mut().append(name, value.clone());
+		}
+	}
+	response.extensions_mut().extend(extensions);
+	response
+}
+
+/// Create the response of a call in the `encoding` of the request.
+fn encoded_response(body: String, encoding: Encoding) -> HttpResponse {
+	match encoding {
+		Encoding::Json => response::ok_response(body),
+		Encoding::Codec(codec) if body.is_empty() => response::ok_encoded_response(HttpBody::default(), codec),
+		Encoding::Codec(codec) => match codec.encode(body.as_bytes()) {
+			Ok(encoded) => response::ok_encoded_response(encoded, codec),
+			Err(e) => {
+				tracing::warn!(target: LOG_TARGET, "Could not encode the response: {}", e);
+				response::internal_error()
+			}
+		},
+	}
+}
+
+/// Read the JSON-RPC payload of a HTTP request, whether it's a single call and how it's encoded.
+///
+/// A payload in the encoding of a [`Codec`] is transcoded into JSON.
+async fn read_request<B>(
+	request: HttpRequest<B>,
+	max_request_size: u32,
+) -> Result<(Parts, Vec<u8>, bool, Encoding), HttpResponse>
+where
+	B: http_body::Body<Data = Bytes> + Send + 'static,
+	B::Data: Send,
+	B::Error: Into<BoxError>,
+{
+	// Only the `POST` method is allowed.
+	let encoding = match *request.method() {
+		Method::POST if content_type_is_json(&request) => Encoding::Json,
+		Method::POST => match content_type_codec(&request) {
+			Some(codec) => Encoding::Codec(codec),
+			None => return Err(response::unsupported_content_type()),
+		},
 		// Error scenarios:
-		Method::POST => response::unsupported_content_type(),
-		_ => response::method_not_allowed(),
+		_ => return Err(response::method_not_allowed()),
+	};
+
+	let (parts, body) = request.into_parts();
+
+	let body = match encoding {
+		Encoding::Json => read_body(&parts.headers, body, max_request_size).await,
+		Encoding::Codec(codec) => read_encoded_body(&parts.headers, body, max_request_size, codec).await,
+	};
+
+	match body {
+		Ok((body, is_single)) => {
+			if let Some(tap) = parts.extensions.get::<MessageTap>() {
+				tap.inbound(&body);
+			}
+			Ok((parts, body, is_single, encoding))
+		}
+		Err(HttpError::TooLarge) => Err(response::too_large(max_request_size)),
+		Err(HttpError::Malformed) => Err(response::malformed()),
+		Err(HttpError::Stream(e)) => {
+			tracing::warn!(target: LOG_TARGET, "Internal error reading request body: {}", e);
+			Err(response::internal_error())
+		}
 	}
 }
 
@@ -114,9 +573,11 @@ pub mod response {
 	use jsonrpsee_types::{ErrorObjectOwned, Id, Response, ResponsePayload};
 
 	use crate::{HttpBody, HttpResponse};
+	use jsonrpsee_core::codec::Codec;
 
 	const JSON: &str = "application/json; charset=utf-8";
 	const TEXT: &str = "text/plain";
+	const EVENT_STREAM: &str = "text/event-stream";
 
 	/// Create a response for json internal error.
 	pub fn internal_error() -> HttpResponse {
@@ -141,6 +602,11 @@ pub mod response {
 		)
 	}
 
+	/// Create a text/plain response for requests to unknown paths.
+	pub fn not_found() -> HttpResponse {
+		from_template(hyper::StatusCode::NOT_FOUND, "Not found\n", TEXT)
+	}
+
 	/// Create a json response for oversized requests (413)
 	pub fn too_large(limit: u32) -> HttpResponse {
 		let err = ResponsePayload::<()>::error(reject_too_big_request(limit));
@@ -174,6 +640,18 @@ pub mod response {
 		from_template(hyper::StatusCode::OK, body, JSON)
 	}
 
+	/// Create a response which is encoded by the `codec`.
+	pub fn ok_encoded_response(body: impl Into<HttpBody>, codec: &dyn Codec) -> HttpResponse {
+		from_template(hyper::StatusCode::OK, body, codec.content_type())
+	}
+
+	/// Create a response with a stream of server-sent events.
+	pub fn event_stream(body: impl Into<HttpBody>) -> HttpResponse {
+		let mut rp = from_template(hyper::StatusCode::OK, body, EVENT_STREAM);
+		rp.headers_mut().insert(hyper::header::CACHE_CONTROL, hyper::header::HeaderValue::from_static("no-cache"));
+		rp
+	}
+
 	/// Create a response for unsupported content type.
 	pub fn unsupported_content_type() -> HttpResponse {
 		from_template(
@@ -188,8 +666,31 @@ pub mod response {
 		from_template(hyper::StatusCode::TOO_MANY_REQUESTS, "Too many connections. Please try again later.", TEXT)
 	}
 
+	/// Create a text/plain response for HTTP requests to a server that only accepts WebSocket connections.
+	pub fn http_not_supported() -> HttpResponse {
+		from_template(
+			hyper::StatusCode::FORBIDDEN,
+			"Only WebSocket connections are accepted, HTTP requests are not supported\n",
+			TEXT,
+		)
+	}
+
+	/// Create a text/plain response for WebSocket upgrades to a server that only accepts HTTP requests.
+	pub fn ws_not_supported() -> HttpResponse {
+		from_template(
+			hyper::StatusCode::FORBIDDEN,
+			"Only HTTP requests are accepted, WebSocket connections are not supported\n",
+			TEXT,
+		)
+	}
+
 	/// Create a response for when the server denied the request.
 	pub fn denied() -> HttpResponse {
 		from_template(hyper::StatusCode::FORBIDDEN, HttpBody::default(), TEXT)
 	}
+
+	/// Create a json response for a rejected handshake.
+	pub fn rejected(status: hyper::StatusCode, body: &serde_json::Value) -> HttpResponse {
+		from_template(status, body.to_string(), JSON)
+	}
 }
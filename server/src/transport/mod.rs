@@ -1,4 +1,16 @@
+/// WebSocket frame limits and fragmentation.
+pub(crate) mod frame;
 /// HTTP related server functionality.
 pub mod http;
 /// WebSocket related server functionality.
 pub mod ws;
+
+/// Encoding of the JSON-RPC messages of a request or connection.
+#[derive(Debug, Copy, Clone, Default)]
+pub(crate) enum Encoding {
+	/// The messages are JSON.
+	#[default]
+	Json,
+	/// The messages are transcoded from and to JSON by the codec.
+	Codec(&'static dyn jsonrpsee_core::codec::Codec),
+}
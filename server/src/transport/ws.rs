@@ -1,17 +1,32 @@
-use std::sync::Arc;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::Instant;
 
 use crate::future::{IntervalStream, SessionClose};
+use crate::hooks::{DisconnectReason, MessageTap, TransportKind};
 use crate::middleware::rpc::{RpcService, RpcServiceBuilder, RpcServiceCfg, RpcServiceT};
-use crate::server::{handle_rpc_call, ConnectionState, ServerConfig};
-use crate::{HttpBody, HttpRequest, HttpResponse, PingConfig, LOG_TARGET};
+use crate::server::{
+	handle_rpc_call, shutdown_notification, transport_max_request_size, ConnectionState, ServerConfig,
+};
+use crate::transport::frame::WsFrameIo;
+use crate::transport::Encoding;
+use crate::{
+	BatchErrorClass, HttpBody, HttpRequest, HttpResponse, PingConfig, WsFlushPolicy, LOG_TARGET,
+	MAX_RESPONSE_SIZE_HEADER,
+};
 
 use futures_util::future::{self, Either};
 use futures_util::io::{BufReader, BufWriter};
-use futures_util::{Future, StreamExt, TryStreamExt};
+use futures_util::{Future, Stream, StreamExt, TryStreamExt};
 use hyper::upgrade::Upgraded;
 use hyper_util::rt::TokioIo;
-use jsonrpsee_core::server::{BoundedSubscriptions, MethodSink, Methods};
+use jsonrpsee_core::codec::Codecs;
+use jsonrpsee_core::server::{
+	BoundedSubscriptions, BufferPool, CancellationGuard, MethodSink, Methods, ResponsePriority, SinkMessage,
+};
 use jsonrpsee_types::error::{reject_too_big_request, ErrorCode};
 use jsonrpsee_types::Id;
 use soketto::connection::Error as SokettoError;
@@ -22,8 +37,8 @@ use tokio::time::{interval, interval_at};
 use tokio_stream::wrappers::ReceiverStream;
 use tokio_util::compat::{Compat, TokioAsyncReadCompatExt};
 
-pub(crate) type Sender = soketto::Sender<BufReader<BufWriter<Compat<TokioIo<Upgraded>>>>>;
-pub(crate) type Receiver = soketto::Receiver<BufReader<BufWriter<Compat<TokioIo<Upgraded>>>>>;
+pub(crate) type Sender = soketto::Sender<BufReader<BufWriter<WsFrameIo<Compat<TokioIo<Upgraded>>>>>>;
+pub(crate) type Receiver = soketto::Receiver<BufReader<BufWriter<WsFrameIo<Compat<TokioIo<Upgraded>>>>>>;
 
 pub use soketto::handshake::http::is_upgrade_request;
 
@@ -32,9 +47,111 @@ enum Incoming {
 	Pong,
 }
 
-pub(crate) async fn send_message(sender: &mut Sender, response: String) -> Result<(), SokettoError> {
-	sender.send_text_owned(response).await?;
-	sender.flush().await.map_err(Into::into)
+/// Create the sink of the messages of a connection, which are serialized into
+/// the buffers of a pool if it's enabled.
+pub(crate) fn method_sink(tx: mpsc::Sender<SinkMessage>, server_cfg: &ServerConfig) -> MethodSink {
+	let sink = MethodSink::new(tx);
+
+	match server_cfg.buffer_pool_size {
+		0 => sink,
+		size => sink.with_buffer_pool(BufferPool::new(size as usize)),
+	}
+}
+
+pub(crate) async fn send_message(
+	sender: &mut Sender,
+	response: SinkMessage,
+	pool: Option<&BufferPool>,
+	tap: Option<&MessageTap>,
+	encoding: Encoding,
+) -> Result<(), SokettoError> {
+	write_message(sender, response, pool, tap, encoding).await?;
+	sender.flush().await
+}
+
+/// Write a message to the connection without flushing it and return its buffer to the pool if there is one.
+///
+/// The result of a notification is shared with the other subscriptions it was sent to until it's
+/// written out here.
+async fn write_message(
+	sender: &mut Sender,
+	msg: SinkMessage,
+	pool: Option<&BufferPool>,
+	tap: Option<&MessageTap>,
+	encoding: Encoding,
+) -> Result<(), SokettoError> {
+	match encoding {
+		Encoding::Json => {}
+		Encoding::Codec(codec) => {
+			let msg = msg.into_string();
+			if let Some(tap) = tap {
+				tap.outbound(msg.as_bytes());
+			}
+			return match codec.encode(msg.as_bytes()) {
+				Ok(encoded) => sender.send_binary_mut(encoded).await,
+				Err(e) => {
+					tracing::warn!(target: LOG_TARGET, "Could not encode the message: {}", e);
+					Ok(())
+				}
+			};
+		}
+	}
+
+	match pool {
+		Some(pool) => {
+			let msg = msg.into_pooled_string(pool);
+			if let Some(tap) = tap {
+				tap.outbound(msg.as_bytes());
+			}
+			sender.send_text(&msg).await?;
+			pool.recycle(msg);
+			Ok(())
+		}
+		None => {
+			let msg = msg.into_string();
+			if let Some(tap) = tap {
+				tap.outbound(msg.as_bytes());
+			}
+			sender.send_text_owned(msg).await
+		}
+	}
+}
+
+/// Send `first` and, if the messages are coalesced, the messages received on `rx` within the window
+/// before flushing them at once.
+async fn send_messages<S>(
+	sender: &mut Sender,
+	first: SinkMessage,
+	rx: &mut S,
+	flush_policy: WsFlushPolicy,
+	pool: Option<&BufferPool>,
+	tap: Option<&MessageTap>,
+	encoding: Encoding,
+) -> Result<(), SokettoError>
+where
+	S: Stream<Item = SinkMessage> + Unpin,
+{
+	let WsFlushPolicy::Coalesce { max_messages, window } = flush_policy else {
+		return send_message(sender, first, pool, tap, encoding).await;
+	};
+
+	write_message(sender, first, pool, tap, encoding).await?;
+
+	let deadline = tokio::time::Instant::now() + window;
+	let mut buffered = 1;
+
+	while buffered < max_messages {
+		match tokio::time::timeout_at(deadline, rx.next()).await {
+			Ok(Some(msg)) => {
+				write_message(sender, msg, pool, tap, encoding).await?;
+				buffered += 1;
+			}
+			// The window has passed or there are no more messages.
+			_ => break,
+		}
+	}
+
+	sender.flush().await
 }
 
 pub(crate) async fn send_ping(sender: &mut Sender) -> Result<(), SokettoError> {
@@ -47,20 +164,128 @@ pub(crate) async fn send_ping(sender: &mut Sender) -> Result<(), SokettoError> {
 	sender.flush().await.map_err(Into::into)
 }
 
+/// Sends the responses of a connection to its send buffer such that, once the buffer is full,
+/// the responses are sent in the order of their [`ResponsePriority`] instead of the order they arrived in.
+#[derive(Clone)]
+pub(crate) struct PrioritySink {
+	sink: MethodSink,
+	queue: Arc<Mutex<PriorityQueue>>,
+}
+
+/// Responses that wait for space in the send buffer.
+#[derive(Default)]
+struct PriorityQueue {
+	waiting: BinaryHeap<WaitingResponse>,
+	/// Number of responses that have waited which orders the responses of the same priority.
+	count: u64,
+	/// Whether a task is forwarding the waiting responses.
+	forwarding: bool,
+}
+
+struct WaitingResponse {
+	priority: ResponsePriority,
+	seq: Reverse<u64>,
+	response: String,
+	sent: oneshot::Sender<bool>,
+}
+
+impl PartialEq for WaitingResponse {
+	fn eq(&self, other: &Self) -> bool {
+		self.cmp(other).is_eq()
+	}
+}
+
+impl Eq for WaitingResponse {}
+
+impl PartialOrd for WaitingResponse {
+	fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl Ord for WaitingResponse {
+	fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+		(self.priority, self.seq).cmp(&(other.priority, other.seq))
+	}
+}
+
+impl PrioritySink {
+	pub(crate) fn new(sink: MethodSink) -> Self {
+		Self { sink, queue: Default::default() }
+	}
+
+	/// Send a response and wait until it's in the send buffer.
+	///
+	/// Returns `false` if the connection was closed.
+	pub(crate) async fn send(&self, response: String, priority: ResponsePriority) -> bool {
+		let waiting = {
+			let mut queue = self.queue.lock().expect("poisoned lock");
+
+			// The order only matters if the responses have to wait for space in the buffer.
+			if queue.waiting.is_empty() && self.sink.capacity() > 0 {
+				Either::Left(response)
+			} else {
+				let (tx, rx) = oneshot::channel();
+				let seq = Reverse(queue.count);
+				queue.count += 1;
+				queue.waiting.push(WaitingResponse { priority, seq, response, sent: tx });
+
+				if !queue.forwarding {
+					queue.forwarding = true;
+					tokio::spawn(self.clone().forward());
+				}
+				Either::Right(rx)
+			}
+		};
+
+		match waiting {
+			Either::Left(response) => self.sink.send(response).await.is_ok(),
+			Either::Right(sent) => sent.await.unwrap_or(false),
+		}
+	}
+
+	/// Send the waiting responses with the highest priority first once there is space in the buffer.
+	async fn forward(self) {
+		loop {
+			let closed = self.sink.has_capacity().await.is_err();
+
+			let next = {
+				let mut queue = self.queue.lock().expect("poisoned lock");
+				let next = queue.waiting.pop();
+				queue.forwarding = next.is_some();
+				next
+			};
+			let Some(WaitingResponse { response, sent, .. }) = next else {
+				return;
+			};
+
+			let is_sent = !closed && self.sink.send(response).await.is_ok();
+			_ = sent.send(is_sent);
+		}
+	}
+}
+
 pub(crate) struct BackgroundTaskParams<S> {
 	pub(crate) server_cfg: ServerConfig,
 	pub(crate) conn: ConnectionState,
 	pub(crate) ws_sender: Sender,
 	pub(crate) ws_receiver: Receiver,
 	pub(crate) rpc_service: S,
+	pub(crate) methods: Methods,
 	pub(crate) sink: MethodSink,
-	pub(crate) rx: mpsc::Receiver<String>,
+	pub(crate) rx: mpsc::Receiver<SinkMessage>,
 	pub(crate) pending_calls_completed: mpsc::Receiver<()>,
 	pub(crate) on_session_close: Option<SessionClose>,
 	pub(crate) extensions: http::Extensions,
+	pub(crate) batch_fail_fast: Option<BatchErrorClass>,
+	pub(crate) encoding: Encoding,
 }
 
-pub(crate) async fn background_task<S>(params: BackgroundTaskParams<S>)
+/// Serve the WebSocket connection until it's closed.
+///
+/// Returns why the connection was closed, the number of received messages
+/// and the number of calls that were still running at that time.
+pub(crate) async fn background_task<S>(params: BackgroundTaskParams<S>) -> (DisconnectReason, u64, usize)
 where
 	for<'a> S: RpcServiceT<'a> + Send + Sync + 'static,
 {
@@ -70,25 +295,62 @@ where
 		ws_sender,
 		ws_receiver,
 		rpc_service,
+		methods,
 		sink,
 		rx,
 		pending_calls_completed,
 		mut on_session_close,
-		extensions,
+		mut extensions,
+		batch_fail_fast,
+		encoding,
 	} = params;
-	let ServerConfig { ping_config, batch_requests_config, max_request_body_size, max_response_body_size, .. } =
-		server_cfg;
+	let ServerConfig {
+		ping_config,
+		ws_flush_policy,
+		batch_requests_config,
+		batch_execution,
+		batch_response_order,
+		max_request_body_size,
+		max_response_body_size,
+		method_runtime,
+		on_message,
+		drain_notice,
+		..
+	} = server_cfg;
 
 	let (conn_tx, conn_rx) = oneshot::channel();
 
-	// Spawn another task that sends out the responses on the Websocket.
-	let send_task_handle = tokio::spawn(send_task(rx, ws_sender, ping_config, conn_rx));
+	// Cancels the calls that are still running once the connection is closed.
+	let cancel_guard = CancellationGuard::new();
+	extensions.insert(cancel_guard.token());
 
-	let stopped = conn.stop_handle.clone().shutdown();
-	let rpc_service = Arc::new(rpc_service);
-	let mut missed_pings = 0;
+	// The responses are serialized into the same pool as the notifications.
+	let buffer_pool = sink.buffer_pool().cloned();
+	if let Some(pool) = &buffer_pool {
+		extensions.insert(pool.clone());
+	}
+
+	let responses = PrioritySink::new(sink.clone());
+	let tap = on_message.map(|hook| hook.tap(conn.conn_id.into(), TransportKind::WebSocket));
 
-	tokio::pin!(stopped);
+	// Spawn another task that sends out the responses on the Websocket.
+	let send_task_handle = tokio::spawn(send_task(
+		rx,
+		ws_sender,
+		ping_config,
+		ws_flush_policy,
+		buffer_pool,
+		conn_rx,
+		tap.clone(),
+		encoding,
+	));
+
+	let mut stopped: Pin<Box<dyn Future<Output = ()> + Send>> = Box::pin(conn.stop_handle.clone().shutdown());
+	let mut draining = false;
+	let rpc_service = Arc::new(rpc_service);
+	let mut missed_pongs = MissedPongs::default();
+	let mut messages = 0;
+	let pending_calls = PendingCalls::default();
 
 	let ws_stream = futures_util::stream::unfold(ws_receiver, |mut receiver| async {
 		let mut data = Vec::new();
@@ -106,9 +368,30 @@ where
 	tokio::pin!(ws_stream);
 
 	let result = loop {
-		let data = match try_recv(&mut ws_stream, stopped, ping_config, &mut missed_pings).await {
+		let data = match try_recv(&mut ws_stream, stopped, ping_config, &mut missed_pongs).await {
 			Receive::ConnectionClosed => break Ok(Shutdown::ConnectionClosed),
-			Receive::Stopped => break Ok(Shutdown::Stopped),
+			Receive::Unresponsive => {
+				tracing::info!(
+					target: LOG_TARGET,
+					conn_id = conn.conn_id,
+					missed_pongs = missed_pongs.total,
+					pending_calls = pending_calls.count(),
+					"WS connection is unresponsive; closing connection"
+				);
+				break Ok(Shutdown::Unresponsive);
+			}
+			Receive::Stopped => match drain_notice {
+				// Notify the client and keep serving the connection until the deadline.
+				Some(deadline) if !draining => {
+					draining = true;
+					stopped = Box::pin(tokio::time::sleep(deadline));
+					if sink.send(shutdown_notification(deadline)).await.is_err() {
+						break Ok(Shutdown::ConnectionClosed);
+					}
+					continue;
+				}
+				_ => break Ok(Shutdown::Stopped),
+			},
 			Receive::Ok(data, stop) => {
 				stopped = stop;
 				data
@@ -127,7 +410,8 @@ where
 							current,
 							maximum
 						);
-						if sink.send_error(Id::Null, reject_too_big_request(max_request_body_size)).await.is_err() {
+						let max = transport_max_request_size(&methods, max_request_body_size);
+						if sink.send_error(Id::Null, reject_too_big_request(max)).await.is_err() {
 							break Ok(Shutdown::ConnectionClosed);
 						}
 
@@ -141,11 +425,28 @@ where
 			}
 		};
 
+		messages += 1;
+
+		let data = match encoding {
+			Encoding::Json => data,
+			// A message that can't be decoded is answered with a parse error as it's not valid JSON either.
+			Encoding::Codec(codec) => codec.decode(&data).unwrap_or(data),
+		};
+
+		if let Some(tap) = &tap {
+			tap.inbound(&data);
+		}
+
 		let rpc_service = rpc_service.clone();
+		let methods = methods.clone();
 		let sink = sink.clone();
+		let responses = responses.clone();
 		let extensions = extensions.clone();
+		let batch_fail_fast = batch_fail_fast.clone();
+		let pending_call = pending_calls.start();
 
-		tokio::spawn(async move {
+		let call = async move {
+			let _pending_call = pending_call;
 			let first_non_whitespace = data.iter().enumerate().take(128).find(|(_, byte)| !byte.is_ascii_whitespace());
 
 			let (idx, is_single) = match first_non_whitespace {
@@ -161,18 +462,25 @@ where
 				&data[idx..],
 				is_single,
 				batch_requests_config,
+				batch_execution,
+				batch_response_order,
+				batch_fail_fast.as_ref(),
+				max_request_body_size,
 				max_response_body_size,
+				&methods,
 				&*rpc_service,
 				extensions,
+				None,
 			)
 			.await
 			{
 				if !rp.is_subscription() {
 					let is_success = rp.is_success();
+					let priority = rp.extensions().get::<ResponsePriority>().copied().unwrap_or_default();
 					let (serialized_rp, mut on_close) = rp.into_parts();
 
 					// The connection is closed, just quit.
-					if sink.send(serialized_rp).await.is_err() {
+					if !responses.send(serialized_rp, priority).await {
 						return;
 					}
 
@@ -183,13 +491,28 @@ where
 					}
 				}
 			}
-		});
+		};
+
+		match &method_runtime {
+			Some(rt) => rt.spawn(call),
+			None => tokio::spawn(call),
+		};
 	};
 
 	// Drive all running methods to completion.
 	// **NOTE** Do not return early in this function. This `await` needs to run to guarantee
 	// proper drop behaviour.
 	drop(rpc_service);
+	let pending_calls = pending_calls.count();
+	if !matches!(result, Ok(Shutdown::Stopped)) {
+		drop(cancel_guard);
+	}
+	let reason = match result {
+		Ok(Shutdown::Stopped) => DisconnectReason::ServerStopped,
+		Ok(Shutdown::ConnectionClosed) => DisconnectReason::Closed,
+		Ok(Shutdown::Unresponsive) => DisconnectReason::Unresponsive,
+		Err(_) => DisconnectReason::Error,
+	};
 	graceful_shutdown(result, pending_calls_completed, ws_stream, conn_tx, send_task_handle).await;
 
 	drop(conn);
@@ -197,14 +520,54 @@ where
 	if let Some(c) = on_session_close.take() {
 		c.close();
 	}
+
+	(reason, messages, pending_calls)
+}
+
+/// Counts the calls of a connection that are still running.
+#[derive(Debug, Default)]
+struct PendingCalls(Arc<AtomicUsize>);
+
+impl PendingCalls {
+	/// Count a call until the returned guard is dropped.
+	fn start(&self) -> PendingCall {
+		self.0.fetch_add(1, Ordering::Relaxed);
+		PendingCall(self.0.clone())
+	}
+
+	fn count(&self) -> usize {
+		self.0.load(Ordering::Relaxed)
+	}
+}
+
+struct PendingCall(Arc<AtomicUsize>);
+
+impl Drop for PendingCall {
+	fn drop(&mut self) {
+		self.0.fetch_sub(1, Ordering::Relaxed);
+	}
+}
+
+/// The pongs that the peer missed to send in time.
+#[derive(Debug, Default)]
+struct MissedPongs {
+	/// Over the lifetime of the connection.
+	total: usize,
+	/// In a row, reset once a pong is received.
+	consecutive: usize,
 }
 
 /// A task that waits for new messages via the `rx channel` and sends them out on the `WebSocket`.
+#[allow(clippy::too_many_arguments)]
 async fn send_task(
-	rx: mpsc::Receiver<String>,
+	rx: mpsc::Receiver<SinkMessage>,
 	mut ws_sender: Sender,
 	ping_config: Option<PingConfig>,
+	flush_policy: WsFlushPolicy,
+	buffer_pool: Option<BufferPool>,
 	stop: oneshot::Receiver<()>,
+	tap: Option<MessageTap>,
+	encoding: Encoding,
 ) {
 	let ping_interval = match ping_config {
 		None => IntervalStream::pending(),
@@ -229,7 +592,17 @@ async fn send_task(
 			// Received message.
 			Either::Left((Some(response), not_ready)) => {
 				// If websocket message send fail then terminate the connection.
-				if let Err(err) = send_message(&mut ws_sender, response).await {
+				if let Err(err) = send_messages(
+					&mut ws_sender,
+					response,
+					&mut rx,
+					flush_policy,
+					buffer_pool.as_ref(),
+					tap.as_ref(),
+					encoding,
+				)
+				.await
+				{
 					tracing::debug!(target: LOG_TARGET, "WS send error: {}", err);
 					break;
 				}
@@ -268,6 +641,7 @@ async fn send_task(
 
 enum Receive<S> {
 	ConnectionClosed,
+	Unresponsive,
 	Stopped,
 	Err(SokettoError, S),
 	Ok(Vec<u8>, S),
@@ -278,7 +652,7 @@ async fn try_recv<T, S>(
 	ws_stream: &mut T,
 	mut stopped: S,
 	ping_config: Option<PingConfig>,
-	missed_pings: &mut usize,
+	missed_pongs: &mut MissedPongs,
 ) -> Receive<S>
 where
 	S: Future<Output = ()> + Unpin,
@@ -303,6 +677,7 @@ where
 			// Got a pong response, update our "last seen" timestamp.
 			Either::Left((Either::Left((Some(Ok(Incoming::Pong)), inactive)), s)) => {
 				last_active = Instant::now();
+				missed_pongs.consecutive = 0;
 				stopped = s;
 				futs = futures_util::future::select(ws_stream.next(), inactive);
 			}
@@ -312,15 +687,16 @@ where
 			Either::Left((Either::Right((_instant, rcv)), s)) => {
 				if let Some(p) = ping_config {
 					if last_active.elapsed() > p.inactive_limit {
-						*missed_pings += 1;
-
-						if *missed_pings >= p.max_failures {
-							tracing::debug!(
-								target: LOG_TARGET,
-								"WS ping/pong inactivity limit `{}` exceeded; closing connection",
-								p.max_failures,
-							);
-							break Receive::ConnectionClosed;
+						missed_pongs.total += 1;
+						missed_pongs.consecutive += 1;
+
+						let unresponsive = match p.max_missed_pongs {
+							Some(max) => missed_pongs.consecutive >= max,
+							None => missed_pongs.total >= p.max_failures,
+						};
+
+						if unresponsive {
+							break Receive::Unresponsive;
 						}
 					}
 				}
@@ -338,6 +714,7 @@ where
 pub(crate) enum Shutdown {
 	Stopped,
 	ConnectionClosed,
+	Unresponsive,
 }
 
 /// Enforce a graceful shutdown.
@@ -421,7 +798,7 @@ async fn graceful_shutdown<S>(
 /// ```
 pub async fn connect<L, B>(
 	req: HttpRequest<B>,
-	server_cfg: ServerConfig,
+	mut server_cfg: ServerConfig,
 	methods: impl Into<Methods>,
 	conn: ConnectionState,
 	rpc_middleware: RpcServiceBuilder<L>,
@@ -432,11 +809,16 @@ where
 	for<'a> <L as tower::Layer<RpcService>>::Service: RpcServiceT<'a>,
 {
 	let mut server = soketto::handshake::http::Server::new();
+	let client_max_response_size = client_max_response_size(req.headers());
+	server_cfg.max_response_body_size =
+		negotiate_max_response_size(client_max_response_size, server_cfg.max_response_body_size);
 
 	match server.receive_request(&req) {
-		Ok(response) => {
-			let (tx, rx) = mpsc::channel::<String>(server_cfg.message_buffer_capacity as usize);
-			let sink = MethodSink::new(tx);
+		Ok(mut response) => {
+			let encoding = negotiate_encoding(req.headers(), &server_cfg.codecs, &mut response);
+
+			let (tx, rx) = mpsc::channel::<SinkMessage>(server_cfg.message_buffer_capacity as usize);
+			let sink = method_sink(tx, &server_cfg);
 
 			// On each method call the `pending_calls` is cloned
 			// then when all pending_calls are dropped
@@ -446,13 +828,20 @@ where
 			let rpc_service_cfg = RpcServiceCfg::CallsAndSubscriptions {
 				bounded_subscriptions: BoundedSubscriptions::new(server_cfg.max_subscriptions_per_connection),
 				id_provider: server_cfg.id_provider.clone(),
+				on_subscribe: server_cfg.on_subscribe.clone(),
 				sink: sink.clone(),
+				subscription_conn_id: conn.conn_id.into(),
 				_pending_calls: pending_calls,
 			};
 
+			let methods = methods.into();
 			let rpc_service = RpcService::new(
-				methods.into(),
+				methods.clone(),
 				server_cfg.max_response_body_size as usize,
+				client_max_response_size,
+				server_cfg.method_timeout,
+				server_cfg.on_response.clone(),
+				server_cfg.audit_log.clone(),
 				conn.conn_id.into(),
 				rpc_service_cfg,
 			);
@@ -463,6 +852,7 @@ where
 			// is returned below, so that's why it's a separate async block
 			let fut = async move {
 				let extensions = req.extensions().clone();
+				let batch_fail_fast = server_cfg.batch_fail_fast.resolve(req.headers()).cloned();
 
 				let upgraded = match hyper::upgrade::on(req).await {
 					Ok(upgraded) => upgraded,
@@ -474,9 +864,15 @@ where
 
 				let io = TokioIo::new(upgraded);
 
-				let stream = BufReader::new(BufWriter::new(io.compat()));
+				let frame_cfg = server_cfg.ws_frame_config;
+				let stream = BufReader::new(BufWriter::new(WsFrameIo::new(io.compat(), &frame_cfg)));
 				let mut ws_builder = server.into_builder(stream);
-				ws_builder.set_max_message_size(server_cfg.max_response_body_size as usize);
+				ws_builder.set_max_message_size(
+					transport_max_request_size(&methods, server_cfg.max_request_body_size) as usize
+				);
+				if let Some(max) = frame_cfg.max_frame_size {
+					ws_builder.set_max_frame_size(max as usize);
+				}
 				let (sender, receiver) = ws_builder.finish();
 
 				let params = BackgroundTaskParams {
@@ -485,14 +881,17 @@ where
 					ws_sender: sender,
 					ws_receiver: receiver,
 					rpc_service,
+					methods,
 					sink,
 					rx,
 					pending_calls_completed,
 					on_session_close: None,
 					extensions,
+					batch_fail_fast,
+					encoding,
 				};
 
-				background_task(params).await;
+				_ = background_task(params).await;
 			};
 
 			Ok((response.map(|()| HttpBody::default()), fut))
@@ -503,3 +902,38 @@ where
 		}
 	}
 }
+
+/// The encoding of a connection which is the first of the `codecs` whose subprotocol the client offered,
+/// in which case the subprotocol is selected in the `response` to the handshake, see [`jsonrpsee_core::codec`].
+pub(crate) fn negotiate_encoding<B>(
+	headers: &http::HeaderMap,
+	codecs: &Codecs,
+	response: &mut http::Response<B>,
+) -> Encoding {
+	let protocols = headers
+		.get_all(http::header::SEC_WEBSOCKET_PROTOCOL)
+		.iter()
+		.filter_map(|val| val.to_str().ok())
+		.flat_map(|val| val.split(','));
+
+	match codecs.from_ws_subprotocols(protocols) {
+		Some(codec) => {
+			response
+				.headers_mut()
+				.insert(http::header::SEC_WEBSOCKET_PROTOCOL, http::HeaderValue::from_static(codec.ws_subprotocol()));
+			Encoding::Codec(codec)
+		}
+		None => Encoding::Json,
+	}
+}
+
+/// The max response size that the client advertised by the [`MAX_RESPONSE_SIZE_HEADER`] header.
+pub(crate) fn client_max_response_size(headers: &http::HeaderMap) -> Option<u32> {
+	headers.get(MAX_RESPONSE_SIZE_HEADER).and_then(|val| val.to_str().ok()).and_then(|val| val.parse::<u32>().ok())
+}
+
+/// The max response size of a connection which is the lower of the server limit
+/// and the limit that the client advertised.
+pub(crate) fn negotiate_max_response_size(client_max_response_size: Option<u32>, max_response_size: u32) -> u32 {
+	client_max_response_size.map_or(max_response_size, |limit| limit.min(max_response_size))
+}
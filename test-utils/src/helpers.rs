@@ -29,7 +29,7 @@ use std::net::SocketAddr;
 
 use crate::mocks::{HttpResponse, Id, Uri};
 use http_body_util::BodyExt;
-use hyper::{service::service_fn, Response};
+use hyper::{service::service_fn, HeaderMap, Response};
 use hyper_util::{
 	client::legacy::Client,
 	rt::{TokioExecutor, TokioIo},
@@ -199,22 +199,28 @@ pub fn server_notification_without_params(method: &str) -> String {
 
 pub async fn http_request(body: Body, uri: Uri) -> Result<HttpResponse, String> {
 	let client = hyper_util::client::legacy::Client::builder(TokioExecutor::new()).build_http();
-	http_post(client, body, uri).await
+	http_post(client, body, uri, HeaderMap::new()).await
+}
+
+pub async fn http_request_with_headers(body: Body, uri: Uri, headers: HeaderMap) -> Result<HttpResponse, String> {
+	let client = hyper_util::client::legacy::Client::builder(TokioExecutor::new()).build_http();
+	http_post(client, body, uri, headers).await
 }
 
 pub async fn http2_request(body: Body, uri: Uri) -> Result<HttpResponse, String> {
 	let client = hyper_util::client::legacy::Client::builder(TokioExecutor::new()).build_http();
-	http_post(client, body, uri).await
+	http_post(client, body, uri, HeaderMap::new()).await
 }
 
-async fn http_post<C>(client: Client<C, Body>, body: Body, uri: Uri) -> Result<HttpResponse, String>
+async fn http_post<C>(client: Client<C, Body>, body: Body, uri: Uri, headers: HeaderMap) -> Result<HttpResponse, String>
 where
 	C: hyper_util::client::legacy::connect::Connect + Clone + Send + Sync + 'static,
 {
-	let r = hyper::Request::post(uri)
+	let mut r = hyper::Request::post(uri)
 		.header(hyper::header::CONTENT_TYPE, hyper::header::HeaderValue::from_static("application/json"))
 		.body(body)
 		.expect("uri and request headers are valid; qed");
+	r.headers_mut().extend(headers);
 	let res = client.request(r).await.map_err(|e| format!("{e:?}"))?;
 
 	let (parts, mut body) = res.into_parts();
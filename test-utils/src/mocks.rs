@@ -95,8 +95,15 @@ impl From<io::Error> for WebSocketTestError {
 
 impl WebSocketTestClient {
 	pub async fn new(url: SocketAddr) -> Result<Self, WebSocketTestError> {
+		Self::new_with_headers(url, &[]).await
+	}
+
+	pub async fn new_with_headers(url: SocketAddr, headers: &[(&str, &str)]) -> Result<Self, WebSocketTestError> {
 		let socket = TcpStream::connect(url).await?;
 		let mut client = handshake::Client::new(BufReader::new(BufWriter::new(socket.compat())), "test-client", "/");
+		let headers: Vec<_> =
+			headers.iter().map(|(name, value)| handshake::client::Header { name, value: value.as_bytes() }).collect();
+		client.set_headers(&headers);
 		match client.handshake().await {
 			Ok(handshake::ServerResponse::Accepted { .. }) => {
 				let (tx, rx) = client.into_builder().finish();
@@ -43,14 +43,17 @@ use helpers::{
 use http_body_util::BodyExt;
 use hyper::http::HeaderValue;
 use hyper_util::rt::TokioExecutor;
+use jsonrpsee::core::cbor::Cbor;
 use jsonrpsee::core::client::SubscriptionCloseReason;
-use jsonrpsee::core::client::{ClientT, Error, IdKind, Subscription, SubscriptionClientT};
+use jsonrpsee::core::client::{ClientT, Error, IdKind, RequestIdGenerator, Subscription, SubscriptionClientT};
+use jsonrpsee::core::codec::{Codec, CodecError, Codecs};
+use jsonrpsee::core::msgpack::MessagePack;
 use jsonrpsee::core::params::{ArrayParams, BatchRequestBuilder};
-use jsonrpsee::core::server::SubscriptionMessage;
+use jsonrpsee::core::server::{Deadline, SubscriptionMessage};
 use jsonrpsee::core::{JsonValue, StringError};
 use jsonrpsee::http_client::HttpClientBuilder;
 use jsonrpsee::server::middleware::http::HostFilterLayer;
-use jsonrpsee::server::{ConnectionGuard, ServerBuilder, ServerHandle};
+use jsonrpsee::server::{BatchExecution, BatchResponseOrder, ConnectionGuard, IdPolicy, ServerBuilder, ServerHandle};
 use jsonrpsee::types::error::{ErrorObject, UNKNOWN_ERROR_CODE};
 use jsonrpsee::ws_client::WsClientBuilder;
 use jsonrpsee::{rpc_params, ResponsePayload, RpcModule};
@@ -83,6 +86,40 @@ async fn ws_subscription_works() {
 	}
 }
 
+#[tokio::test]
+async fn ws_client_scope_cancels_calls_and_subscriptions() {
+	init_logger();
+
+	let server_addr = server().await;
+	let client = WsClientBuilder::default().build(format!("ws://{}", server_addr)).await.unwrap();
+
+	let scope = client.scope();
+	let handle = scope.handle();
+	let (res, _) = tokio::join!(scope.request::<String, _>("slow_hello", rpc_params![]), async {
+		tokio::time::sleep(Duration::from_millis(100)).await;
+		handle.cancel();
+	});
+	assert!(matches!(res, Err(Error::Cancelled)));
+	assert!(matches!(scope.request::<String, _>("say_hello", rpc_params![]).await, Err(Error::Cancelled)));
+
+	// The client itself is still usable.
+	let res: String = client.request("slow_hello", rpc_params![]).await.unwrap();
+	assert_eq!(res, "hello");
+
+	let server_addr = server_with_subscription().await;
+	let client = WsClientBuilder::default().build(format!("ws://{}", server_addr)).await.unwrap();
+
+	let scope = client.scope();
+	let mut sub: Subscription<String> =
+		scope.subscribe("subscribe_hello", rpc_params![], "unsubscribe_hello").await.unwrap();
+	assert!(sub.next().await.unwrap().is_ok());
+
+	drop(scope);
+	while let Some(notif) = sub.next().with_default_timeout().await.unwrap() {
+		assert!(notif.is_ok());
+	}
+}
+
 #[tokio::test]
 async fn ws_subscription_works_over_proxy_stream() {
 	init_logger();
@@ -301,6 +338,100 @@ async fn http_method_call_str_id_works() {
 	assert_eq!(&response, "hello");
 }
 
+#[tokio::test]
+async fn ws_uuid_id_works() {
+	init_logger();
+
+	let server_addr = server_with_subscription().await;
+	let server_url = format!("ws://{}", server_addr);
+	let client = WsClientBuilder::default().id_format(IdKind::Uuid).build(&server_url).await.unwrap();
+
+	let response: String = client.request("say_hello", rpc_params![]).await.unwrap();
+	assert_eq!(&response, "hello");
+
+	let mut batch = BatchRequestBuilder::new();
+	batch.insert("say_hello", rpc_params![]).unwrap();
+	batch.insert("say_hello", rpc_params![]).unwrap();
+	let res = client.batch_request::<String>(batch).await.unwrap();
+	assert_eq!(res.num_successful_calls(), 2);
+
+	let mut sub: Subscription<JsonValue> =
+		client.subscribe("subscribe_hello", rpc_params![], "unsubscribe_hello").await.unwrap();
+	assert_eq!(sub.next().await.unwrap().unwrap(), JsonValue::String("hello from subscription".into()));
+}
+
+#[tokio::test]
+async fn http_uuid_id_works() {
+	init_logger();
+
+	let server_addr = server().await;
+	let uri = format!("http://{}", server_addr);
+	let client = HttpClientBuilder::default().id_format(IdKind::Uuid).build(&uri).unwrap();
+
+	let response: String = client.request("say_hello", rpc_params![]).await.unwrap();
+	assert_eq!(&response, "hello");
+
+	let mut batch = BatchRequestBuilder::new();
+	batch.insert("say_hello", rpc_params![]).unwrap();
+	batch.insert("say_hello", rpc_params![]).unwrap();
+	let res = client.batch_request::<String>(batch).await.unwrap();
+	assert_eq!(res.num_successful_calls(), 2);
+}
+
+#[tokio::test]
+async fn http_deadline_propagation_works() {
+	init_logger();
+
+	let server = ServerBuilder::default().build("127.0.0.1:0").with_default_timeout().await.unwrap().unwrap();
+	let mut module = RpcModule::new(());
+	module
+		.register_method("remaining_ms", |_, _, ext| {
+			Deadline::from_extensions(ext).map(|deadline| deadline.remaining().as_millis() as u64)
+		})
+		.unwrap();
+	let uri = format!("http://{}", server.local_addr().unwrap());
+	let _handle = server.start(module);
+
+	let client = HttpClientBuilder::default().build(&uri).unwrap();
+	let remaining: Option<u64> = client.request("remaining_ms", rpc_params![]).await.unwrap();
+	assert_eq!(remaining, None);
+
+	let client = HttpClientBuilder::default()
+		.request_timeout(Duration::from_secs(5))
+		.set_propagate_deadline(true)
+		.build(&uri)
+		.unwrap();
+	let remaining: Option<u64> = client.request("remaining_ms", rpc_params![]).await.unwrap();
+	assert!(matches!(remaining, Some(ms) if ms > 0 && ms <= 5000));
+
+	// The timeout of the call is propagated instead of the request timeout of the client.
+	let remaining: Option<u64> =
+		client.request_with_timeout("remaining_ms", rpc_params![], Duration::from_millis(1000)).await.unwrap();
+	assert!(matches!(remaining, Some(ms) if ms > 0 && ms <= 1000));
+}
+
+#[tokio::test]
+async fn timestamp_id_works() {
+	async fn run(client: impl ClientT) {
+		let response: String = client.request("say_hello", rpc_params![]).await.unwrap();
+		assert_eq!(&response, "hello");
+
+		let mut batch = BatchRequestBuilder::new();
+		batch.insert("say_hello", rpc_params![]).unwrap();
+		batch.insert("say_hello", rpc_params![]).unwrap();
+		let res = client.batch_request::<String>(batch).await.unwrap();
+		assert_eq!(res.num_successful_calls(), 2);
+	}
+
+	init_logger();
+
+	let server_addr = server().await;
+	run(WsClientBuilder::default().id_format(IdKind::Timestamp).build(format!("ws://{server_addr}")).await.unwrap())
+		.await;
+	run(HttpClientBuilder::default().id_format(IdKind::Timestamp).build(format!("http://{server_addr}")).unwrap())
+		.await;
+}
+
 #[tokio::test]
 async fn ws_subscription_several_clients() {
 	init_logger();
@@ -778,6 +909,54 @@ async fn ws_batch_works() {
 	assert_eq!(err_responses, vec![&ErrorObject::borrowed(UNKNOWN_ERROR_CODE, "err", None)]);
 }
 
+#[tokio::test]
+async fn batch_with_notifications_works() {
+	async fn run(client: impl ClientT) {
+		let mut batch = BatchRequestBuilder::new();
+		batch.insert_notification("say_hello", rpc_params![]).unwrap();
+		batch.insert("say_hello", rpc_params![]).unwrap();
+		batch.insert_notification("say_hello", rpc_params![]).unwrap();
+		batch.insert("err", rpc_params![]).unwrap();
+
+		let res = client.batch_request::<String>(batch).await.unwrap();
+		assert_eq!(res.len(), 2);
+		assert_eq!(res.num_successful_calls(), 1);
+		assert_eq!(res.num_failed_calls(), 1);
+
+		assert!(res.get(0).is_none());
+		assert_eq!(res.get(1).unwrap().as_ref().unwrap(), "hello");
+		assert!(res.get(2).is_none());
+		assert!(res.get(3).unwrap().is_err());
+		assert!(res.get(4).is_none());
+
+		let methods: Vec<_> = res.iter_with_methods().map(|(method, rp)| (method, rp.is_ok())).collect();
+		assert_eq!(methods, vec![("say_hello", true), ("err", false)]);
+
+		let results = res.clone().into_ordered_results();
+		assert_eq!(results.iter().map(|(idx, _)| *idx).collect::<Vec<_>>(), vec![1, 3]);
+
+		let results: Vec<_> = res.into_stream().collect().await;
+		assert_eq!(results[0].as_ref().unwrap(), "hello");
+
+		let mut batch = BatchRequestBuilder::new();
+		batch.insert_notification("say_hello", rpc_params![]).unwrap();
+		batch.insert_notification("say_hello", rpc_params![]).unwrap();
+
+		let res = client.batch_request::<String>(batch).await.unwrap();
+		assert!(res.is_empty());
+
+		// The client still works after a batch which wasn't answered.
+		let response: String = client.request("say_hello", rpc_params![]).await.unwrap();
+		assert_eq!(&response, "hello");
+	}
+
+	init_logger();
+
+	let server_addr = server().await;
+	run(WsClientBuilder::default().build(format!("ws://{server_addr}")).await.unwrap()).await;
+	run(HttpClientBuilder::default().build(format!("http://{server_addr}")).unwrap()).await;
+}
+
 #[tokio::test]
 async fn http_batch_works() {
 	init_logger();
@@ -873,6 +1052,95 @@ async fn ws_server_limit_subs_per_conn_works() {
 	);
 }
 
+#[tokio::test]
+async fn ws_server_limit_subs_per_method_works() {
+	use futures::StreamExt;
+	use jsonrpsee::types::error::TOO_MANY_SUBSCRIPTIONS_CODE;
+	use jsonrpsee::{server::ServerBuilder, RpcModule};
+
+	init_logger();
+
+	let server = ServerBuilder::default().build("127.0.0.1:0").await.unwrap();
+	let server_url = format!("ws://{}", server.local_addr().unwrap());
+
+	let mut module = RpcModule::new(());
+	for (subscribe, unsubscribe) in [("subscribe_a", "unsubscribe_a"), ("subscribe_b", "unsubscribe_b")] {
+		module
+			.register_subscription(subscribe, subscribe, unsubscribe, |_, pending, _, _| async move {
+				let interval = interval(Duration::from_millis(50));
+				let stream = IntervalStream::new(interval).map(move |_| 0_usize);
+
+				pipe_from_stream_and_drop(pending, stream).await.map_err(Into::into)
+			})
+			.unwrap();
+	}
+	module.set_max_subscriptions_per_connection("subscribe_a", 2).unwrap();
+	let _handle = server.start(module);
+
+	let c1 = WsClientBuilder::default().build(&server_url).await.unwrap();
+	let c2 = WsClientBuilder::default().build(&server_url).await.unwrap();
+
+	let mut subs = Vec::new();
+	for _ in 0..2 {
+		subs.push(c1.subscribe::<usize, ArrayParams>("subscribe_a", rpc_params![], "unsubscribe_a").await.unwrap());
+	}
+
+	let err = c1.subscribe::<usize, ArrayParams>("subscribe_a", rpc_params![], "unsubscribe_a").await;
+	assert!(matches!(err, Err(Error::Call(err)) if err.code() == TOO_MANY_SUBSCRIPTIONS_CODE));
+
+	// The limit doesn't apply to other methods nor to other connections.
+	c1.subscribe::<usize, ArrayParams>("subscribe_b", rpc_params![], "unsubscribe_b").await.unwrap();
+	c2.subscribe::<usize, ArrayParams>("subscribe_a", rpc_params![], "unsubscribe_a").await.unwrap();
+
+	// A slot is released once a subscription is closed.
+	subs.pop().unwrap().unsubscribe().await.unwrap();
+	c1.subscribe::<usize, ArrayParams>("subscribe_a", rpc_params![], "unsubscribe_a").await.unwrap();
+}
+
+#[tokio::test]
+async fn ws_server_on_subscribe_hook_works() {
+	use jsonrpsee::core::server::ConnectionExtensions;
+	use jsonrpsee::types::ErrorObject;
+	use jsonrpsee::{server::ServerBuilder, RpcModule};
+
+	init_logger();
+
+	let server = ServerBuilder::default()
+		.set_on_subscribe(|info| match info.params.one::<String>() {
+			Ok(user) if user == "admin" => Ok(()),
+			_ => Err(ErrorObject::owned(-32001, "Unauthorized", Some(info.method))),
+		})
+		.build("127.0.0.1:0")
+		.await
+		.unwrap();
+	let server_url = format!("ws://{}", server.local_addr().unwrap());
+
+	let mut module = RpcModule::new(());
+	module
+		.register_subscription("subscribe_hello", "hello", "unsubscribe_hello", |_, pending, _, ext| async move {
+			// The extensions of the connection are available before the subscription is accepted.
+			assert!(ext.get::<ConnectionExtensions>().is_some());
+			let sink = pending.accept().await?;
+			sink.send(jsonrpsee::SubscriptionMessage::from_json(&"hello")?).await?;
+			Ok(())
+		})
+		.unwrap();
+	let _handle = server.start(module);
+
+	let client = WsClientBuilder::default().build(&server_url).await.unwrap();
+
+	let err = client.subscribe::<String, ArrayParams>("subscribe_hello", rpc_params!["bob"], "unsubscribe_hello").await;
+	assert!(
+		matches!(err, Err(Error::Call(err)) if err.code() == -32001 && err.data().unwrap().get() == "\"subscribe_hello\"")
+	);
+
+	let mut sub = client
+		.subscribe::<String, ArrayParams>("subscribe_hello", rpc_params!["admin"], "unsubscribe_hello")
+		.await
+		.unwrap();
+	assert_eq!(sub.next().await.unwrap().unwrap(), "hello");
+}
+
 #[tokio::test]
 async fn ws_server_unsub_methods_should_ignore_sub_limit() {
 	use futures::StreamExt;
@@ -1105,6 +1373,151 @@ async fn http_health_api_works() {
 	assert_eq!(out.as_str(), "{\"health\":true}");
 }
 
+#[tokio::test]
+async fn http_openrpc_document_is_served() {
+	use hyper::Request;
+	use hyper_util::client::legacy::Client;
+	use jsonrpsee::server::OpenRpcInfo;
+
+	init_logger();
+
+	let server = ServerBuilder::default()
+		.serve_openrpc("/openrpc.json", OpenRpcInfo::new("test", "1.0.0"))
+		.build("127.0.0.1:0")
+		.await
+		.unwrap();
+	let mut module = RpcModule::new(());
+	module.register_method("say_hello", |_, _, _| "hello").unwrap();
+	let server_addr = server.local_addr().unwrap();
+	let _handle = server.start(module);
+
+	let http_client = Client::builder(TokioExecutor::new()).build_http();
+	let uri = format!("http://{}/openrpc.json", server_addr);
+
+	let req = Request::builder().method("GET").uri(&uri).body(HttpBody::default()).expect("request builder");
+	let res = http_client.request(req).await.unwrap();
+
+	assert!(res.status().is_success());
+
+	let bytes = res.into_body().collect().await.unwrap().to_bytes();
+	let doc: JsonValue = serde_json::from_slice(&bytes).unwrap();
+	assert_eq!(doc["openrpc"], "1.2.6");
+	assert_eq!(doc["methods"][0]["name"], "say_hello");
+}
+
+#[tokio::test]
+async fn http_server_info_is_served() {
+	use hyper::Request;
+	use hyper_util::client::legacy::Client;
+	use jsonrpsee::server::ServerInfo;
+
+	init_logger();
+
+	let server = ServerBuilder::default()
+		.max_request_body_size(1024)
+		.ws_only()
+		.serve_server_info(ServerInfo::new("test", "1.0.0"))
+		.build("127.0.0.1:0")
+		.await
+		.unwrap();
+	let server_addr = server.local_addr().unwrap();
+	let _handle = server.start(RpcModule::new(()));
+
+	let http_client = Client::builder(TokioExecutor::new()).build_http();
+	let uri = format!("http://{}", server_addr);
+
+	let req = Request::builder().method("GET").uri(&uri).body(HttpBody::default()).expect("request builder");
+	let res = http_client.request(req).await.unwrap();
+
+	assert!(res.status().is_success());
+
+	let bytes = res.into_body().collect().await.unwrap().to_bytes();
+	let doc: JsonValue = serde_json::from_slice(&bytes).unwrap();
+	assert_eq!(doc["name"], "test");
+	assert_eq!(doc["version"], "1.0.0");
+	assert_eq!(doc["transports"], serde_json::json!(["ws"]));
+	assert_eq!(doc["limits"]["maxRequestBodySize"], 1024);
+	assert_eq!(doc["limits"]["maxBatchLen"], JsonValue::Null);
+}
+
+#[tokio::test]
+async fn rpc_discover_works() {
+	use jsonrpsee::server::OpenRpcInfo;
+
+	init_logger();
+
+	let server = ServerBuilder::default()
+		.enable_rpc_discover(OpenRpcInfo::new("test", "1.0.0"))
+		.build("127.0.0.1:0")
+		.await
+		.unwrap();
+	let mut module = RpcModule::new(());
+	module.register_method("say_hello", |_, _, _| "hello").unwrap();
+	module.register_method("internal", |_, _, _| "secret").unwrap();
+	module.hide_method("internal").unwrap();
+	let server_addr = server.local_addr().unwrap();
+	let _handle = server.start(module);
+
+	let client = HttpClientBuilder::default().build(format!("http://{}", server_addr)).unwrap();
+	let doc: JsonValue = client.request("rpc.discover", rpc_params![]).await.unwrap();
+
+	assert_eq!(doc["info"]["title"], "test");
+	let names: Vec<_> = doc["methods"].as_array().unwrap().iter().map(|m| m["name"].as_str().unwrap()).collect();
+	assert_eq!(names, vec!["say_hello"]);
+
+	// Hidden methods can still be called.
+	let rp: String = client.request("internal", rpc_params![]).await.unwrap();
+	assert_eq!(rp, "secret");
+}
+
+#[tokio::test]
+async fn methods_introspection_works() {
+	init_logger();
+
+	let server = ServerBuilder::default()
+		.enable_methods_introspection()
+		.max_response_body_size(4096)
+		.build("127.0.0.1:0")
+		.await
+		.unwrap();
+	let mut module = RpcModule::new(());
+	module.register_method("say_hello", |_, _, _| "hello").unwrap();
+	module.register_alias("hello", "say_hello").unwrap();
+	module.deprecate_method("hello", "use `say_hello`").unwrap();
+	module.register_method("admin_stop", |_, _, _| ()).unwrap();
+	module.require_auth("admin_stop", "admin").unwrap();
+	module.set_method_timeout("admin_stop", Duration::from_secs(5)).unwrap();
+	module.register_subscription("sub", "notif", "unsub", |_, _, _, _| async { Ok(()) }).unwrap();
+	module.register_method("internal", |_, _, _| "secret").unwrap();
+	module.hide_method("internal").unwrap();
+	let server_addr = server.local_addr().unwrap();
+	let _handle = server.start(module);
+
+	let client = HttpClientBuilder::default().build(format!("http://{}", server_addr)).unwrap();
+	let doc: JsonValue = client.request("system_methods", rpc_params![]).await.unwrap();
+	let methods = doc["methods"].as_array().unwrap();
+
+	let names: Vec<_> = methods.iter().map(|m| m["name"].as_str().unwrap()).collect();
+	assert_eq!(names, vec!["admin_stop", "say_hello", "sub", "unsub"]);
+
+	let admin_stop = &methods[0];
+	assert_eq!(admin_stop["kind"], "method");
+	assert_eq!(admin_stop["auth"], "admin");
+	assert_eq!(admin_stop["limits"]["timeoutMs"], 5000);
+	assert_eq!(admin_stop["limits"]["maxResponseSize"], 4096);
+
+	// The alias is listed with its method and deprecating the alias doesn't deprecate the method.
+	let say_hello = &methods[1];
+	assert_eq!(say_hello["aliases"], serde_json::json!([{ "name": "hello", "deprecated": "use `say_hello`" }]));
+	assert_eq!(say_hello["deprecated"], JsonValue::Null);
+
+	let sub = &methods[2];
+	assert_eq!(sub["kind"], "subscription");
+	assert_eq!(sub["notification"], "notif");
+	assert_eq!(sub["unsubscribe"], "unsub");
+	assert_eq!(methods[3]["kind"], "unsubscription");
+}
+
 #[tokio::test]
 async fn ws_host_filtering_wildcard_works() {
 	use jsonrpsee::server::*;
@@ -1611,3 +2024,365 @@ async fn http_connection_guard_works() {
 		assert_eq!(conn_count, 1);
 	}
 }
+
+#[tokio::test]
+async fn ws_codecs_work() {
+	init_logger();
+
+	let server_addr = server_with_subscription().await;
+	let server_url = format!("ws://{}", server_addr);
+
+	for codec in [&Cbor as &dyn Codec, &MessagePack] {
+		let client = WsClientBuilder::default().set_codec(codec).build(&server_url).await.unwrap();
+
+		let response: String = client.request("say_hello", rpc_params![]).await.unwrap();
+		assert_eq!(&response, "hello");
+
+		let mut batch = BatchRequestBuilder::new();
+		batch.insert("say_hello", rpc_params![]).unwrap();
+		batch.insert("say_hello", rpc_params![]).unwrap();
+		let responses: Vec<_> = client.batch_request::<String>(batch).await.unwrap().into_ok().unwrap().collect();
+		assert_eq!(responses, vec!["hello".to_string(), "hello".to_string()]);
+
+		let mut sub: Subscription<String> =
+			client.subscribe("subscribe_hello", rpc_params![], "unsubscribe_hello").await.unwrap();
+		assert_eq!(sub.next().await.unwrap().unwrap(), "hello from subscription".to_string());
+	}
+}
+
+#[tokio::test]
+async fn http_codecs_work() {
+	use hyper::{Method, Request};
+	use hyper_util::client::legacy::Client;
+
+	init_logger();
+
+	let server_addr = server().await;
+	let uri = format!("http://{}", server_addr);
+
+	for codec in [&Cbor as &dyn Codec, &MessagePack] {
+		let client = HttpClientBuilder::default().set_codec(codec).build(&uri).unwrap();
+
+		let response: String = client.request("say_hello", rpc_params![]).await.unwrap();
+		assert_eq!(&response, "hello");
+
+		let mut batch = BatchRequestBuilder::new();
+		batch.insert("say_hello", rpc_params![]).unwrap();
+		batch.insert("say_hello", rpc_params![]).unwrap();
+		let responses: Vec<_> = client.batch_request::<String>(batch).await.unwrap().into_ok().unwrap().collect();
+		assert_eq!(responses, vec!["hello".to_string(), "hello".to_string()]);
+
+		// The response is encoded the same way as the request.
+		let body = codec.encode(br#"{"jsonrpc":"2.0","method":"say_hello","id":1}"#).unwrap();
+		let req = Request::builder()
+			.method(Method::POST)
+			.uri(&uri)
+			.header("content-type", codec.content_type())
+			.body(HttpBody::from(body))
+			.expect("request builder");
+
+		let res = Client::builder(TokioExecutor::new()).build_http().request(req).await.unwrap();
+		assert_eq!(res.headers().get("content-type").unwrap(), codec.content_type());
+
+		let body = res.into_body().collect().await.unwrap().to_bytes();
+		let response: JsonValue = serde_json::from_slice(&codec.decode(&body).unwrap()).unwrap();
+		assert_eq!(response, serde_json::json!({"jsonrpc": "2.0", "result": "hello", "id": 1}));
+	}
+}
+
+#[tokio::test]
+async fn custom_server_codecs_work() {
+	init_logger();
+
+	// JSON with the bytes in reverse order.
+	#[derive(Debug)]
+	struct Reversed;
+
+	impl Codec for Reversed {
+		fn content_type(&self) -> &'static str {
+			"application/x-reversed-json"
+		}
+
+		fn ws_subprotocol(&self) -> &'static str {
+			"reversed-json"
+		}
+
+		fn encode(&self, json: &[u8]) -> Result<Vec<u8>, CodecError> {
+			Ok(json.iter().rev().copied().collect())
+		}
+
+		fn decode(&self, data: &[u8]) -> Result<Vec<u8>, CodecError> {
+			Ok(data.iter().rev().copied().collect())
+		}
+	}
+
+	let server =
+		ServerBuilder::default().set_codecs(Codecs::new([&Reversed as &dyn Codec])).build("127.0.0.1:0").await.unwrap();
+	let mut module = RpcModule::new(());
+	module.register_method("say_hello", |_, _, _| "hello").unwrap();
+	let addr = server.local_addr().unwrap();
+	let handle = server.start(module);
+
+	let client = HttpClientBuilder::default().set_codec(&Reversed).build(format!("http://{addr}")).unwrap();
+	let response: String = client.request("say_hello", rpc_params![]).await.unwrap();
+	assert_eq!(&response, "hello");
+
+	let client = WsClientBuilder::default().set_codec(&Reversed).build(format!("ws://{addr}")).await.unwrap();
+	let response: String = client.request("say_hello", rpc_params![]).await.unwrap();
+	assert_eq!(&response, "hello");
+
+	// The builtin codecs are replaced.
+	let client = HttpClientBuilder::default().set_codec(&Cbor).build(format!("http://{addr}")).unwrap();
+	assert!(client.request::<String, _>("say_hello", rpc_params![]).await.is_err());
+
+	handle.stop().unwrap();
+	handle.stopped().await;
+}
+
+#[tokio::test]
+async fn other_ids_round_trip() {
+	use jsonrpsee::types::{Id, InvalidRequestId};
+
+	init_logger();
+
+	// Encodes the route to the downstream client in the ids.
+	#[derive(Debug)]
+	struct RoutedIds;
+
+	impl RequestIdGenerator for RoutedIds {
+		fn to_id(&self, n: u64) -> Id<'static> {
+			Id::from(serde_json::json!({ "route": "client-1", "n": n }))
+		}
+
+		fn parse_id(&self, id: &Id) -> Result<u64, InvalidRequestId> {
+			id.as_other()
+				.and_then(|id| id.parse::<JsonValue>().ok())
+				.filter(|id| id["route"] == "client-1")
+				.and_then(|id| id["n"].as_u64())
+				.ok_or_else(|| InvalidRequestId::Invalid(id.to_string()))
+		}
+	}
+
+	let server = ServerBuilder::default()
+		.set_id_policy(IdPolicy::default().accept_other(true))
+		.build("127.0.0.1:0")
+		.await
+		.unwrap();
+	let mut module = RpcModule::new(());
+	module.register_method("say_hello", |_, _, _| "hello").unwrap();
+	let addr = server.local_addr().unwrap();
+	let handle = server.start(module);
+
+	let client = HttpClientBuilder::default().id_generator(RoutedIds).build(format!("http://{addr}")).unwrap();
+	let response: String = client.request("say_hello", rpc_params![]).await.unwrap();
+	assert_eq!(&response, "hello");
+
+	let client = WsClientBuilder::default().id_generator(RoutedIds).build(format!("ws://{addr}")).await.unwrap();
+	let mut batch = BatchRequestBuilder::new();
+	batch.insert("say_hello", rpc_params![]).unwrap();
+	batch.insert("say_hello", rpc_params![]).unwrap();
+	let responses: Vec<_> = client.batch_request::<String>(batch).await.unwrap().into_ok().unwrap().collect();
+	assert_eq!(responses, vec!["hello".to_string(), "hello".to_string()]);
+
+	handle.stop().unwrap();
+	handle.stopped().await;
+}
+
+#[tokio::test]
+async fn batch_request_stream_works() {
+	init_logger();
+
+	let server = ServerBuilder::default()
+		.set_batch_execution(BatchExecution::Concurrent(4))
+		.set_batch_response_order(BatchResponseOrder::Completion)
+		.enable_batch_response_streaming()
+		.build("127.0.0.1:0")
+		.await
+		.unwrap();
+
+	// `wait` is answered once `release` has been called.
+	let mut module = RpcModule::new(tokio::sync::Notify::new());
+	module.register_async_method("wait", |_, ctx, _| async move { ctx.notified().await }).unwrap();
+	module
+		.register_method("release", |_, ctx, _| {
+			ctx.notify_one();
+			"released"
+		})
+		.unwrap();
+	module.register_method("fail", |_, _, _| Err::<(), _>(ErrorObject::owned(-1, "failed", None::<()>))).unwrap();
+	let addr = server.local_addr().unwrap();
+	let handle = server.start(module);
+
+	let mut batch = BatchRequestBuilder::new();
+	batch.insert("wait", rpc_params![]).unwrap();
+	batch.insert("fail", rpc_params![]).unwrap();
+
+	// The response of `fail` is received while `wait` is still pending.
+	let client = HttpClientBuilder::default().build(format!("http://{addr}")).unwrap();
+	let mut responses = client.batch_request_stream::<JsonValue>(batch).await.unwrap();
+	let (idx, rp) = responses.next().with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(idx, 1);
+	assert_eq!(rp.unwrap_err().message(), "failed");
+
+	client.request::<String, _>("release", rpc_params![]).await.unwrap();
+	let (idx, rp) = responses.next().with_default_timeout().await.unwrap().unwrap();
+	assert_eq!((idx, rp.unwrap()), (0, JsonValue::Null));
+	assert!(responses.next().await.is_none());
+	assert!(responses.error().is_none());
+
+	// The other clients yield the responses once the whole batch response has been received.
+	let client = WsClientBuilder::default().build(format!("ws://{addr}")).await.unwrap();
+	let mut batch = BatchRequestBuilder::new();
+	batch.insert("fail", rpc_params![]).unwrap();
+	batch.insert("wait", rpc_params![]).unwrap();
+	let (responses, released) = tokio::join!(
+		client.batch_request_stream::<JsonValue>(batch),
+		client.request::<String, _>("release", rpc_params![])
+	);
+	released.unwrap();
+	let mut responses: Vec<_> = responses.unwrap().collect().await;
+	responses.sort_by_key(|(idx, _)| *idx);
+	assert_eq!(responses.len(), 2);
+	assert_eq!(responses[0].1.as_ref().unwrap_err().message(), "failed");
+	assert_eq!(responses[1].1.as_ref().unwrap(), &JsonValue::Null);
+
+	handle.stop().unwrap();
+	handle.stopped().await;
+}
+
+#[tokio::test]
+async fn json_rpc_v1_compat_works() {
+	use hyper::{Method, Request};
+	use hyper_util::client::legacy::Client;
+
+	init_logger();
+
+	let v1_server = ServerBuilder::default().set_json_rpc_v1_compat(true).build("127.0.0.1:0").await.unwrap();
+	let addr = v1_server.local_addr().unwrap();
+	let mut module = RpcModule::new(());
+	module.register_method("getblockcount", |_, _, _| 840_000).unwrap();
+	module
+		.register_method::<Result<(), _>, _>("fail", |_, _, _| Err(ErrorObject::owned(-1, "boom", None::<()>)))
+		.unwrap();
+	let handle = v1_server.start(module);
+	tokio::spawn(handle.stopped());
+
+	let http_client = Client::builder(TokioExecutor::new()).build_http();
+	let uri = format!("http://{}", addr);
+	let call = |body: &'static str| {
+		let req = Request::builder()
+			.method(Method::POST)
+			.uri(&uri)
+			.header("content-type", "application/json")
+			.body(HttpBody::from(body))
+			.expect("request builder");
+		let rp = http_client.request(req);
+		async move {
+			let body = rp.await.unwrap().into_body().collect().await.unwrap().to_bytes();
+			if body.is_empty() {
+				JsonValue::Null
+			} else {
+				serde_json::from_slice::<JsonValue>(&body).unwrap()
+			}
+		}
+	};
+
+	assert_eq!(
+		call(r#"{"method":"getblockcount","params":[],"id":1}"#).await,
+		serde_json::json!({"result": 840_000, "error": null, "id": 1})
+	);
+	assert_eq!(
+		call(r#"{"method":"fail","params":[],"id":"a"}"#).await,
+		serde_json::json!({"result": null, "error": {"code": -1, "message": "boom"}, "id": "a"})
+	);
+	// A request whose id is `null` is a notification.
+	assert_eq!(call(r#"{"method":"getblockcount","params":[],"id":null}"#).await, JsonValue::Null);
+	// JSON-RPC 2.0 requests are answered as usual.
+	assert_eq!(
+		call(r#"{"jsonrpc":"2.0","method":"getblockcount","id":1}"#).await,
+		serde_json::json!({"jsonrpc": "2.0", "result": 840_000, "id": 1})
+	);
+
+	let client = HttpClientBuilder::default().set_json_rpc_v1(true).build(&uri).unwrap();
+	assert_eq!(client.request::<u64, _>("getblockcount", rpc_params![]).await.unwrap(), 840_000);
+	let err = client.request::<(), _>("fail", rpc_params![]).await.unwrap_err();
+	assert!(matches!(err, Error::Call(e) if e.code() == -1));
+
+	let client = WsClientBuilder::default().set_json_rpc_v1(true).build(format!("ws://{}", addr)).await.unwrap();
+	assert_eq!(client.request::<u64, _>("getblockcount", rpc_params![]).await.unwrap(), 840_000);
+
+	// The 1.0 requests are rejected by a server without the compatibility mode.
+	let client =
+		HttpClientBuilder::default().set_json_rpc_v1(true).build(format!("http://{}", server().await)).unwrap();
+	assert!(client.request::<String, _>("say_hello", rpc_params![]).await.is_err());
+}
+
+#[cfg(feature = "arbitrary-precision")]
+#[tokio::test]
+async fn arbitrary_precision_numbers_are_not_rounded() {
+	init_logger();
+
+	const U256_MAX: &str = "115792089237316195423570985008687907853269984665640564039457584007913129639935";
+
+	let server = ServerBuilder::default().build("127.0.0.1:0").await.unwrap();
+	let mut module = RpcModule::new(());
+	module.register_method("echo", |params, _, _| params.one::<JsonValue>()).unwrap();
+	let addr = server.local_addr().unwrap();
+	let handle = server.start(module);
+	tokio::spawn(handle.stopped());
+
+	let value: JsonValue = serde_json::from_str(U256_MAX).unwrap();
+
+	let client = HttpClientBuilder::default().build(format!("http://{addr}")).unwrap();
+	let res: JsonValue = client.request("echo", rpc_params![&value]).await.unwrap();
+	assert_eq!(res.to_string(), U256_MAX);
+
+	let client = WsClientBuilder::default().build(format!("ws://{addr}")).await.unwrap();
+	let res: JsonValue = client.request("echo", rpc_params![&value]).await.unwrap();
+	assert_eq!(res.to_string(), U256_MAX);
+}
+
+#[tokio::test]
+async fn response_cache_inside_identity_middleware_is_per_caller() {
+	use jsonrpsee::server::middleware::rpc::{ResponseCacheLayer, RpcServiceBuilder, RpcServiceT};
+	use jsonrpsee::server::{CallerIdentity, ConnectionId};
+	use jsonrpsee::types::Request;
+	use std::sync::atomic::{AtomicUsize, Ordering};
+
+	/// Identifies the caller by its connection.
+	#[derive(Clone)]
+	struct ConnIdentity<S>(S);
+
+	impl<'a, S: RpcServiceT<'a>> RpcServiceT<'a> for ConnIdentity<S> {
+		type Future = S::Future;
+
+		fn call(&self, mut request: Request<'a>) -> Self::Future {
+			let conn_id = request.extensions().get::<ConnectionId>().expect("the connection id is set").0;
+			request.extensions_mut().insert(CallerIdentity(format!("conn-{conn_id}")));
+			self.0.call(request)
+		}
+	}
+
+	init_logger();
+
+	let cache = ResponseCacheLayer::new(1024).cache_method("balance", Duration::from_secs(60));
+	let handle = cache.cache();
+	// The cache is inside the identity middleware such that it sees the identity of the caller.
+	let rpc_middleware = RpcServiceBuilder::new().layer_fn(ConnIdentity).layer(cache);
+	let server = ServerBuilder::default().set_rpc_middleware(rpc_middleware).build("127.0.0.1:0").await.unwrap();
+	let mut module = RpcModule::new(AtomicUsize::new(0));
+	module.register_method("balance", |_, calls, _| calls.fetch_add(1, Ordering::SeqCst)).unwrap();
+	let addr = server.local_addr().unwrap();
+	let server_handle = server.start(module);
+	tokio::spawn(server_handle.stopped());
+
+	let alice = WsClientBuilder::default().build(format!("ws://{addr}")).await.unwrap();
+	let bob = WsClientBuilder::default().build(format!("ws://{addr}")).await.unwrap();
+
+	assert_eq!(alice.request::<usize, _>("balance", rpc_params![1]).await.unwrap(), 0);
+	assert_eq!(bob.request::<usize, _>("balance", rpc_params![1]).await.unwrap(), 1);
+	// Both callers are served their own cached response.
+	assert_eq!(alice.request::<usize, _>("balance", rpc_params![1]).await.unwrap(), 0);
+	assert_eq!(bob.request::<usize, _>("balance", rpc_params![1]).await.unwrap(), 1);
+	assert_eq!(handle.len(), 2);
+}
@@ -422,3 +422,713 @@ async fn sync_sub_works() {
 
 	assert_eq!(sub.next().await.unwrap().unwrap(), "hello");
 }
+
+#[tokio::test]
+async fn trait_param_kind_is_used_by_the_client() {
+	use jsonrpsee::proc_macros::rpc;
+	use jsonrpsee::RpcModule;
+
+	#[rpc(client, param_kind = map)]
+	trait Named {
+		#[method(name = "by_name")]
+		async fn by_name(&self, block_number: u64) -> RpcResult<serde_json::Value>;
+
+		#[method(name = "by_position", param_kind = array)]
+		async fn by_position(&self, block_number: u64) -> RpcResult<serde_json::Value>;
+	}
+
+	init_logger();
+
+	let mut module = RpcModule::new(());
+	for name in ["by_name", "by_position"] {
+		module.register_method(name, |params, _, _| params.parse::<serde_json::Value>().unwrap()).unwrap();
+	}
+
+	let server = ServerBuilder::default().build("127.0.0.1:0").await.unwrap();
+	let addr = server.local_addr().unwrap();
+	let handle = server.start(module);
+	tokio::spawn(handle.stopped());
+
+	let client = HttpClientBuilder::default().build(format!("http://{addr}")).unwrap();
+
+	assert_eq!(client.by_name(7).await.unwrap(), json!({ "block_number": 7 }));
+	assert_eq!(client.by_position(7).await.unwrap(), json!([7]));
+}
+
+#[tokio::test]
+async fn macro_fills_default_params() {
+	use jsonrpsee::core::RpcResult;
+	use jsonrpsee::proc_macros::rpc;
+
+	#[rpc(server)]
+	trait Defaults {
+		#[method(name = "page")]
+		fn page(
+			&self,
+			offset: u64,
+			#[argument(default = 10)] limit: u64,
+			#[argument(default = "asc".to_string())] order: String,
+		) -> RpcResult<String>;
+	}
+
+	struct DefaultsImpl;
+
+	impl DefaultsServer for DefaultsImpl {
+		fn page(&self, offset: u64, limit: u64, order: String) -> RpcResult<String> {
+			Ok(format!("{offset} {limit} {order}"))
+		}
+	}
+
+	let module = DefaultsImpl.into_rpc();
+
+	let res: String = module.call("page", [5]).await.unwrap();
+	assert_eq!(res, "5 10 asc");
+
+	let res: String = module.call("page", [json!(5), json!(null), json!("desc")]).await.unwrap();
+	assert_eq!(res, "5 10 desc");
+
+	let mut params = ObjectParams::new();
+	params.insert("offset", 5).unwrap();
+	params.insert("limit", 20).unwrap();
+	let res: String = module.call("page", params).await.unwrap();
+	assert_eq!(res, "5 20 asc");
+
+	let params = module.method_metadata("page").unwrap().params().unwrap();
+	assert!(params[0].is_required());
+	assert!(!params[1].is_required());
+}
+
+#[tokio::test]
+async fn client_omits_trailing_none_params() {
+	use jsonrpsee::proc_macros::rpc;
+	use jsonrpsee::RpcModule;
+
+	#[rpc(client)]
+	trait Optional {
+		#[method(name = "by_position")]
+		async fn by_position(&self, a: Option<u64>, b: Option<u64>, c: Option<u64>) -> RpcResult<serde_json::Value>;
+
+		#[method(name = "by_name", param_kind = map)]
+		async fn by_name(&self, a: u64, b: Option<u64>, c: Option<u64>) -> RpcResult<serde_json::Value>;
+	}
+
+	init_logger();
+
+	let mut module = RpcModule::new(());
+	for name in ["by_position", "by_name"] {
+		module.register_method(name, |params, _, _| params.parse::<serde_json::Value>().unwrap()).unwrap();
+	}
+
+	let server = ServerBuilder::default().build("127.0.0.1:0").await.unwrap();
+	let addr = server.local_addr().unwrap();
+	let handle = server.start(module);
+	tokio::spawn(handle.stopped());
+
+	let client = HttpClientBuilder::default().build(format!("http://{addr}")).unwrap();
+
+	assert_eq!(client.by_position(Some(1), None, None).await.unwrap(), json!([1]));
+	assert_eq!(client.by_position(None, None, Some(3)).await.unwrap(), json!([null, null, 3]));
+	assert_eq!(client.by_name(1, None, Some(3)).await.unwrap(), json!({ "a": 1, "c": 3 }));
+}
+
+#[tokio::test]
+async fn method_timeout_works() {
+	use std::time::Duration;
+
+	use jsonrpsee::core::{async_trait, RpcResult};
+	use jsonrpsee::proc_macros::rpc;
+	use jsonrpsee::types::error::METHOD_TIMEOUT_CODE;
+
+	#[rpc(client, server)]
+	trait Slow {
+		#[method(name = "server_timeout", timeout_ms = 50)]
+		async fn server_timeout(&self) -> RpcResult<()>;
+
+		#[method(name = "client_timeout", timeout_ms = 50)]
+		async fn client_timeout(&self) -> RpcResult<()>;
+	}
+
+	struct SlowImpl;
+
+	#[async_trait]
+	impl SlowServer for SlowImpl {
+		async fn server_timeout(&self) -> RpcResult<()> {
+			tokio::time::sleep(Duration::from_secs(60)).await;
+			Ok(())
+		}
+
+		async fn client_timeout(&self) -> RpcResult<()> {
+			tokio::time::sleep(Duration::from_secs(60)).await;
+			Ok(())
+		}
+	}
+
+	init_logger();
+
+	let mut module = SlowImpl.into_rpc();
+	assert_eq!(module.method_config("server_timeout").unwrap().timeout(), Some(Duration::from_millis(50)));
+
+	// Only the client applies the timeout of `client_timeout`.
+	module.set_method_timeout("client_timeout", Duration::from_secs(60)).unwrap();
+
+	let server = ServerBuilder::default().build("127.0.0.1:0").await.unwrap();
+	let addr = server.local_addr().unwrap();
+	let handle = server.start(module);
+	tokio::spawn(handle.stopped());
+
+	let client = HttpClientBuilder::default().build(format!("http://{addr}")).unwrap();
+
+	// Called without the timeout of the client such that the server answers with the timeout error.
+	let call = client.request::<(), _>("server_timeout", rpc_params![]);
+	let err = tokio::time::timeout(Duration::from_secs(5), call).await.unwrap().unwrap_err();
+	assert!(matches!(err, Error::Call(e) if e.code() == METHOD_TIMEOUT_CODE));
+
+	let err = tokio::time::timeout(Duration::from_secs(5), client.client_timeout()).await.unwrap().unwrap_err();
+	assert!(matches!(err, Error::RequestTimeout));
+}
+
+#[tokio::test]
+async fn server_error_type_is_converted() {
+	use jsonrpsee::core::async_trait;
+	use jsonrpsee::core::server::MethodsError;
+	use jsonrpsee::proc_macros::rpc;
+	use jsonrpsee::types::{ErrorObject, ErrorObjectOwned};
+
+	#[derive(Debug)]
+	enum AccountError {
+		NotFound(u64),
+	}
+
+	impl From<AccountError> for ErrorObjectOwned {
+		fn from(err: AccountError) -> Self {
+			match err {
+				AccountError::NotFound(id) => ErrorObject::owned(404, format!("account {id} not found"), None::<()>),
+			}
+		}
+	}
+
+	#[rpc(client, server, error = AccountError)]
+	trait Accounts {
+		#[method(name = "balance")]
+		async fn balance(&self, id: u64) -> jsonrpsee::core::RpcResult<u64>;
+
+		#[method(name = "owner")]
+		fn owner(&self, id: u64) -> jsonrpsee::core::RpcResult<String>;
+	}
+
+	struct AccountsImpl;
+
+	#[async_trait]
+	impl AccountsServer for AccountsImpl {
+		async fn balance(&self, id: u64) -> Result<u64, AccountError> {
+			match id {
+				1 => Ok(100),
+				_ => Err(AccountError::NotFound(id)),
+			}
+		}
+
+		fn owner(&self, id: u64) -> Result<String, AccountError> {
+			Err(AccountError::NotFound(id))
+		}
+	}
+
+	let module = AccountsImpl.into_rpc();
+
+	let res: u64 = module.call("balance", [1]).await.unwrap();
+	assert_eq!(res, 100);
+
+	let err = module.call::<_, u64>("balance", [2]).await.unwrap_err();
+	assert!(matches!(err, MethodsError::JsonRpc(e) if e.code() == 404 && e.message() == "account 2 not found"));
+
+	let err = module.call::<_, String>("owner", [3]).await.unwrap_err();
+	assert!(matches!(err, MethodsError::JsonRpc(e) if e.code() == 404));
+
+	// The client still reports failures with its own error type.
+	let server = ServerBuilder::default().build("127.0.0.1:0").await.unwrap();
+	let addr = server.local_addr().unwrap();
+	let handle = server.start(module);
+	tokio::spawn(handle.stopped());
+
+	let client = HttpClientBuilder::default().build(format!("http://{addr}")).unwrap();
+	assert_eq!(client.balance(1).await.unwrap(), 100);
+	assert!(matches!(client.balance(2).await.unwrap_err(), Error::Call(e) if e.code() == 404));
+}
+
+#[tokio::test]
+async fn generics_and_associated_types_work() {
+	use jsonrpsee::core::server::OpenRpcInfo;
+	use jsonrpsee::core::{async_trait, RpcResult, SubscriptionResult};
+	use jsonrpsee::proc_macros::rpc;
+	use jsonrpsee::server::{PendingSubscriptionSink, SubscriptionMessage};
+	use jsonrpsee::RpcModule;
+
+	#[rpc(client, server, namespace = "chain", openrpc)]
+	trait Chain<Asset> {
+		type Hash: std::fmt::Debug;
+
+		#[method(name = "balance")]
+		async fn balance(&self, block: Self::Hash, asset: Asset) -> RpcResult<u64>;
+
+		#[method(name = "head")]
+		fn head(&self) -> RpcResult<Self::Hash>;
+
+		#[subscription(name = "subscribeHeads", unsubscribe = "unsubscribeHeads", item = Self::Hash)]
+		async fn subscribe_heads(&self) -> SubscriptionResult;
+	}
+
+	struct NumberChain;
+
+	#[async_trait]
+	impl ChainServer<String> for NumberChain {
+		type Hash = u64;
+
+		async fn balance(&self, block: u64, asset: String) -> RpcResult<u64> {
+			Ok(block + asset.len() as u64)
+		}
+
+		fn head(&self) -> RpcResult<u64> {
+			Ok(7)
+		}
+
+		async fn subscribe_heads(&self, pending: PendingSubscriptionSink) -> SubscriptionResult {
+			let sink = pending.accept().await?;
+			sink.send(SubscriptionMessage::from_json(&8_u64)?).await?;
+			Ok(())
+		}
+	}
+
+	struct HexChain;
+
+	#[async_trait]
+	impl ChainServer<u8> for HexChain {
+		type Hash = String;
+
+		async fn balance(&self, block: String, asset: u8) -> RpcResult<u64> {
+			Ok(u64::from_str_radix(&block, 16).unwrap() * asset as u64)
+		}
+
+		fn head(&self) -> RpcResult<String> {
+			Ok("ff".to_string())
+		}
+
+		async fn subscribe_heads(&self, pending: PendingSubscriptionSink) -> SubscriptionResult {
+			let sink = pending.accept().await?;
+			sink.send(SubscriptionMessage::from_json(&"100")?).await?;
+			Ok(())
+		}
+	}
+
+	init_logger();
+
+	async fn start(module: RpcModule<impl Send + Sync + 'static>) -> WsClient {
+		let server = ServerBuilder::default().build("127.0.0.1:0").await.unwrap();
+		let addr = server.local_addr().unwrap();
+		let handle = server.start(module);
+		tokio::spawn(handle.stopped());
+		WsClientBuilder::default().build(format!("ws://{addr}")).await.unwrap()
+	}
+
+	let client = start(NumberChain.into_rpc()).await;
+	let head = ChainClient::<String, u64>::head(&client).await.unwrap();
+	assert_eq!(head, 7);
+	assert_eq!(client.balance(head, "dot".to_string()).await.unwrap(), 10);
+	let mut heads = ChainClient::<String, u64>::subscribe_heads(&client).await.unwrap();
+	assert_eq!(heads.next().await.unwrap().unwrap(), 8);
+
+	let client = start(HexChain.into_rpc()).await;
+	let head = ChainClient::<u8, String>::head(&client).await.unwrap();
+	assert_eq!(head, "ff");
+	assert_eq!(client.balance(head, 2).await.unwrap(), 510);
+	let mut heads = ChainClient::<u8, String>::subscribe_heads(&client).await.unwrap();
+	assert_eq!(heads.next().await.unwrap().unwrap(), "100");
+
+	let doc = <HexChain as ChainServer<u8>>::openrpc(&OpenRpcInfo::new("hex", "0.1.0"));
+	let head = doc["methods"].as_array().unwrap().iter().find(|m| m["name"] == "chain_head").unwrap();
+	assert_eq!(head["result"]["schema"], json!({ "type": "string" }));
+}
+
+#[tokio::test]
+async fn mock_client_works() {
+	use jsonrpsee::core::client::MockCall;
+	use jsonrpsee::proc_macros::rpc;
+	use jsonrpsee::types::ErrorObject;
+
+	#[rpc(client, namespace = "wallet", mock)]
+	trait Wallet<Asset> {
+		#[method(name = "balance")]
+		async fn balance(&self, asset: Asset) -> jsonrpsee::core::RpcResult<u64>;
+
+		#[method(name = "transfer", param_kind = map)]
+		async fn transfer(&self, to: String, amount: u64) -> Result<String, jsonrpsee::types::ErrorObjectOwned>;
+
+		#[method(name = "log")]
+		fn log(&self, msg: String);
+
+		#[subscription(name = "subscribeBalance", unsubscribe = "unsubscribeBalance", item = u64)]
+		async fn subscribe_balance(&self, asset: Asset) -> jsonrpsee::core::SubscriptionResult;
+	}
+
+	async fn pay(client: &(impl WalletClient<String> + Sync)) -> Result<String, Error> {
+		let balance = client.balance("dot".to_string()).await?;
+		client.log(format!("balance {balance}")).await?;
+		client.transfer("bob".to_string(), balance / 2).await
+	}
+
+	let mock = MockWalletClient::<String>::new();
+	mock.expect_balance(Ok(10)).expect_transfer(Ok("0xabc".to_string())).expect_subscribe_balance([10, 5]);
+
+	assert_eq!(pay(&mock).await.unwrap(), "0xabc");
+	assert_eq!(
+		mock.calls(),
+		vec![
+			MockCall { method: "wallet_balance".into(), params: Some(json!(["dot"])) },
+			MockCall { method: "wallet_log".into(), params: Some(json!(["balance 10"])) },
+			MockCall { method: "wallet_transfer".into(), params: Some(json!({ "to": "bob", "amount": 5 })) },
+		]
+	);
+
+	let mut sub = mock.subscribe_balance("dot".to_string()).await.unwrap();
+	assert_eq!(sub.next().await.unwrap().unwrap(), 10);
+	assert_eq!(sub.next().await.unwrap().unwrap(), 5);
+	assert!(sub.next().await.is_none());
+
+	mock.expect_transfer(Err(ErrorObject::owned(1, "insufficient funds", None::<()>)));
+	assert!(matches!(pay(&mock).await.unwrap_err(), Error::Call(e) if e.message() == "insufficient funds"));
+}
+
+#[tokio::test]
+async fn stream_subscriptions_work() {
+	use futures::stream::{self, Stream, StreamExt};
+	use jsonrpsee::core::async_trait;
+	use jsonrpsee::proc_macros::rpc;
+	use jsonrpsee::server::SubscriptionStream;
+	use jsonrpsee::types::{ErrorObject, ErrorObjectOwned};
+
+	#[rpc(client, server, namespace = "counter")]
+	trait Counter {
+		#[subscription(name = "subscribeCount", unsubscribe = "unsubscribeCount")]
+		async fn count(&self, from: u64, to: u64) -> impl Stream<Item = u64>;
+
+		#[subscription(name = "subscribeLetters", unsubscribe = "unsubscribeLetters")]
+		async fn letters(&self, word: String) -> Result<impl Stream<Item = String>, ErrorObjectOwned>;
+
+		#[subscription(name = "subscribeForever", unsubscribe = "unsubscribeForever", item = u64)]
+		fn forever(&self) -> impl Stream<Item = u64>;
+	}
+
+	struct CounterImpl;
+
+	#[async_trait]
+	impl CounterServer for CounterImpl {
+		async fn count(&self, from: u64, to: u64) -> SubscriptionStream<u64> {
+			stream::iter(from..to).boxed()
+		}
+
+		async fn letters(&self, word: String) -> Result<SubscriptionStream<String>, ErrorObjectOwned> {
+			if word.is_empty() {
+				return Err(ErrorObject::owned(1, "empty word", None::<()>));
+			}
+			let letters: Vec<_> = word.chars().map(String::from).collect();
+			Ok(stream::iter(letters).boxed())
+		}
+
+		fn forever(&self) -> SubscriptionStream<u64> {
+			stream::repeat(1).boxed()
+		}
+	}
+
+	init_logger();
+
+	let server = ServerBuilder::default().build("127.0.0.1:0").await.unwrap();
+	let addr = server.local_addr().unwrap();
+	let handle = server.start(CounterImpl.into_rpc());
+	tokio::spawn(handle.stopped());
+
+	let client = WsClientBuilder::default().build(format!("ws://{addr}")).await.unwrap();
+
+	let count: Vec<_> = client.count(1, 4).await.unwrap().take(3).map(Result::unwrap).collect().await;
+	assert_eq!(count, vec![1, 2, 3]);
+
+	let letters: Vec<_> = client.letters("abc".into()).await.unwrap().take(3).map(Result::unwrap).collect().await;
+	assert_eq!(letters, vec!["a", "b", "c"]);
+
+	let err = client.letters(String::new()).await.unwrap_err();
+	assert!(matches!(err, Error::Call(e) if e.message() == "empty word"));
+
+	let mut forever = client.forever().await.unwrap();
+	assert_eq!(forever.next().await.unwrap().unwrap(), 1);
+	forever.unsubscribe().await.unwrap();
+}
+
+#[tokio::test]
+async fn versioned_namespaces_work() {
+	use futures::stream::{self, Stream, StreamExt};
+	use jsonrpsee::core::{async_trait, RpcResult};
+	use jsonrpsee::proc_macros::rpc;
+	use jsonrpsee::server::SubscriptionStream;
+
+	#[rpc(client, server, namespace = "eth", versions("1", "2"))]
+	trait Eth {
+		#[method(name = "balance")]
+		async fn balance(&self, account: String) -> RpcResult<u64>;
+
+		#[method(name = "chainId", versions("1"))]
+		fn chain_id(&self) -> RpcResult<String>;
+
+		#[method(name = "chainId", versions("2"))]
+		fn chain_id_number(&self) -> RpcResult<u64>;
+
+		#[subscription(name = "subscribeBlocks", unsubscribe = "unsubscribeBlocks")]
+		async fn blocks(&self) -> impl Stream<Item = u64>;
+	}
+
+	struct EthImpl;
+
+	#[async_trait]
+	impl EthServer for EthImpl {
+		async fn balance(&self, account: String) -> RpcResult<u64> {
+			Ok(account.len() as u64)
+		}
+
+		fn chain_id(&self) -> RpcResult<String> {
+			Ok("0x1".to_string())
+		}
+
+		fn chain_id_number(&self) -> RpcResult<u64> {
+			Ok(1)
+		}
+
+		async fn blocks(&self) -> SubscriptionStream<u64> {
+			stream::iter([1, 2]).boxed()
+		}
+	}
+
+	init_logger();
+
+	let module = EthImpl.into_rpc();
+	let mut names: Vec<_> = module.method_names().collect();
+	names.sort_unstable();
+	assert_eq!(
+		names,
+		vec![
+			"eth_v1_balance",
+			"eth_v1_chainId",
+			"eth_v1_subscribeBlocks",
+			"eth_v1_unsubscribeBlocks",
+			"eth_v2_balance",
+			"eth_v2_chainId",
+			"eth_v2_subscribeBlocks",
+			"eth_v2_unsubscribeBlocks",
+		]
+	);
+
+	let server = ServerBuilder::default().build("127.0.0.1:0").await.unwrap();
+	let addr = server.local_addr().unwrap();
+	let handle = server.start(module);
+	tokio::spawn(handle.stopped());
+
+	let client = WsClientBuilder::default().build(format!("ws://{addr}")).await.unwrap();
+
+	assert_eq!(EthV1Client::balance(&client, "alice".into()).await.unwrap(), 5);
+	assert_eq!(EthV2Client::balance(&client, "bob".into()).await.unwrap(), 3);
+	assert_eq!(EthV1Client::chain_id(&client).await.unwrap(), "0x1");
+	assert_eq!(EthV2Client::chain_id_number(&client).await.unwrap(), 1);
+
+	let blocks: Vec<_> = EthV2Client::blocks(&client).await.unwrap().take(2).map(Result::unwrap).collect().await;
+	assert_eq!(blocks, vec![1, 2]);
+}
+
+#[tokio::test]
+async fn context_arguments_are_taken_from_the_extensions() {
+	use jsonrpsee::core::{async_trait, RpcResult, SubscriptionResult};
+	use jsonrpsee::proc_macros::rpc;
+	use jsonrpsee::server::{ConnectionId, Extensions, PeerInfo, PendingSubscriptionSink, SubscriptionMessage};
+
+	#[derive(Clone)]
+	struct User(String);
+
+	#[rpc(client, server)]
+	trait Session {
+		#[method(name = "connectionId")]
+		async fn connection_id(&self, #[argument(context)] conn: ConnectionId) -> RpcResult<usize>;
+
+		#[method(name = "greet")]
+		fn greet(&self, greeting: String, #[argument(context)] ext: &Extensions) -> RpcResult<String>;
+
+		#[method(name = "user")]
+		fn user(&self, #[argument(context)] user: Option<User>) -> RpcResult<Option<String>>;
+
+		#[method(name = "requireUser")]
+		fn require_user(&self, #[argument(context)] user: User) -> RpcResult<String>;
+
+		#[subscription(name = "subscribePeer", unsubscribe = "unsubscribePeer", item = bool)]
+		async fn peer(&self, #[argument(context)] peer: PeerInfo, loopback: bool) -> SubscriptionResult;
+	}
+
+	struct SessionImpl;
+
+	#[async_trait]
+	impl SessionServer for SessionImpl {
+		async fn connection_id(&self, conn: ConnectionId) -> RpcResult<usize> {
+			Ok(conn.0)
+		}
+
+		fn greet(&self, greeting: String, ext: &Extensions) -> RpcResult<String> {
+			let peer = ext.get::<PeerInfo>().unwrap();
+			Ok(format!("{greeting} {}", peer.remote_addr.unwrap().ip()))
+		}
+
+		fn user(&self, user: Option<User>) -> RpcResult<Option<String>> {
+			Ok(user.map(|user| user.0))
+		}
+
+		fn require_user(&self, user: User) -> RpcResult<String> {
+			Ok(user.0)
+		}
+
+		async fn peer(&self, pending: PendingSubscriptionSink, peer: PeerInfo, loopback: bool) -> SubscriptionResult {
+			let sink = pending.accept().await?;
+			let is_loopback = peer.remote_addr.unwrap().ip().is_loopback();
+			sink.send(SubscriptionMessage::from_json(&(is_loopback == loopback))?).await?;
+			Ok(())
+		}
+	}
+
+	init_logger();
+
+	let server = ServerBuilder::default().build("127.0.0.1:0").await.unwrap();
+	let addr = server.local_addr().unwrap();
+	let handle = server.start(SessionImpl.into_rpc());
+	tokio::spawn(handle.stopped());
+
+	let client = WsClientBuilder::default().build(format!("ws://{addr}")).await.unwrap();
+
+	let conn_id = client.connection_id().await.unwrap();
+	assert_eq!(client.connection_id().await.unwrap(), conn_id);
+	assert_eq!(client.greet("hello".into()).await.unwrap(), "hello 127.0.0.1");
+	assert_eq!(client.user().await.unwrap(), None);
+
+	let err = client.require_user().await.unwrap_err();
+	assert!(matches!(err, Error::Call(e) if e.code() == ErrorCode::InternalError.code()));
+
+	let mut peer = client.peer(true).await.unwrap();
+	assert!(peer.next().await.unwrap().unwrap());
+}
+
+#[tokio::test]
+async fn macro_describes_methods_in_openrpc() {
+	use jsonrpsee::core::server::OpenRpcInfo;
+
+	let module = RpcServerImpl.into_rpc();
+	let doc = module.openrpc(&OpenRpcInfo::new("test", "0.1.0"));
+
+	assert_eq!(doc["info"], json!({ "title": "test", "version": "0.1.0" }));
+
+	let method = |name: &str| doc["methods"].as_array().unwrap().iter().find(|m| m["name"] == name).unwrap().clone();
+
+	assert_eq!(
+		method("foo_optional_params")["params"],
+		json!([
+			{ "name": "a", "schema": {}, "required": true },
+			{ "name": "b", "schema": {}, "required": false },
+			{ "name": "c", "schema": {}, "required": false },
+		])
+	);
+	assert_eq!(method("foo_bar")["params"], json!([]));
+
+	let sub = method("foo_echo");
+	assert_eq!(sub["params"][0]["name"], "val");
+	assert_eq!(sub["x-subscription"], json!({ "notification": "foo_echo", "unsubscribe": "foo_unsubscribe_echo" }));
+	assert_eq!(method("alias_echo")["x-subscription"], sub["x-subscription"]);
+	assert_eq!(method("foo_unsubscribe_echo")["result"]["schema"], json!({ "type": "boolean" }));
+}
+
+#[test]
+fn macro_generates_openrpc_document() {
+	use jsonrpsee::core::{async_trait, RpcResult, SubscriptionResult};
+	use jsonrpsee::proc_macros::rpc;
+	use jsonrpsee::server::{OpenRpcInfo, PendingSubscriptionSink};
+	use schemars::JsonSchema;
+	use serde::{Deserialize, Serialize};
+
+	#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+	struct Block {
+		number: u64,
+		hash: String,
+	}
+
+	#[rpc(server, namespace = "chain", openrpc)]
+	trait Chain {
+		/// Get the block with the given number.
+		///
+		/// Returns the latest block if no number is given.
+		#[method(name = "getBlock", aliases = ["chain_blockByNumber"])]
+		fn block(&self, number: Option<u64>) -> RpcResult<Block>;
+
+		#[deprecated]
+		#[method(name = "height")]
+		async fn height(&self) -> u64;
+
+		#[subscription(name = "subscribeBlocks" => "block", unsubscribe = "unsubscribeBlocks", item = Block)]
+		async fn subscribe_blocks(&self, full: bool) -> SubscriptionResult;
+	}
+
+	struct ChainImpl;
+
+	#[async_trait]
+	impl ChainServer for ChainImpl {
+		fn block(&self, _number: Option<u64>) -> RpcResult<Block> {
+			Ok(Block { number: 0, hash: "0x0".into() })
+		}
+
+		async fn height(&self) -> u64 {
+			0
+		}
+
+		async fn subscribe_blocks(&self, _pending: PendingSubscriptionSink, _full: bool) -> SubscriptionResult {
+			Ok(())
+		}
+	}
+
+	let doc = ChainImpl::openrpc(&OpenRpcInfo::new("chain", "1.0.0"));
+	let method = |name: &str| doc["methods"].as_array().unwrap().iter().find(|m| m["name"] == name).unwrap().clone();
+
+	let names: Vec<_> = doc["methods"].as_array().unwrap().iter().map(|m| m["name"].as_str().unwrap()).collect();
+	assert_eq!(
+		names,
+		["chain_blockByNumber", "chain_getBlock", "chain_height", "chain_subscribeBlocks", "chain_unsubscribeBlocks"]
+	);
+
+	let block = method("chain_getBlock");
+	assert_eq!(
+		block["description"],
+		"Get the block with the given number.\n\nReturns the latest block if no number is given."
+	);
+	assert_eq!(block["params"][0]["name"], "number");
+	assert_eq!(block["params"][0]["required"], false);
+	assert_eq!(block["result"]["schema"], json!({ "$ref": "#/components/schemas/Block" }));
+	assert_eq!(method("chain_blockByNumber")["params"], block["params"]);
+
+	assert_eq!(method("chain_height")["deprecated"], true);
+	assert_eq!(method("chain_height")["result"]["schema"]["type"], "integer");
+
+	let sub = method("chain_subscribeBlocks");
+	assert_eq!(sub["params"][0], json!({ "name": "full", "schema": { "type": "boolean" }, "required": true }));
+	assert_eq!(
+		sub["x-subscription"],
+		json!({
+			"notification": "chain_block",
+			"unsubscribe": "chain_unsubscribeBlocks",
+			"schema": { "$ref": "#/components/schemas/Block" }
+		})
+	);
+	assert_eq!(method("chain_unsubscribeBlocks")["result"]["schema"], json!({ "type": "boolean" }));
+
+	let schema = &doc["components"]["schemas"]["Block"];
+	assert_eq!(schema["required"], json!(["number", "hash"]));
+
+	// The parameters of the registered methods are described by their schemas too.
+	let doc = ChainImpl.into_rpc().openrpc(&OpenRpcInfo::new("chain", "1.0.0"));
+	let sub = doc["methods"].as_array().unwrap().iter().find(|m| m["name"] == "chain_subscribeBlocks").unwrap();
+	assert_eq!(sub["params"][0]["schema"], json!({ "type": "boolean", "title": "boolean" }));
+}
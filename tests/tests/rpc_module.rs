@@ -28,14 +28,17 @@
 
 mod helpers;
 
+use std::borrow::Cow;
 use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
 use std::time::Duration;
 
 use futures::StreamExt;
 use helpers::{init_logger, pipe_from_stream_and_drop};
 use jsonrpsee::core::EmptyServerParams;
+use jsonrpsee::core::RegisterMethodError;
 use jsonrpsee::core::{server::*, RpcResult};
-use jsonrpsee::types::error::{ErrorCode, ErrorObject, INVALID_PARAMS_MSG, PARSE_ERROR_CODE};
+use jsonrpsee::types::error::{ErrorCode, ErrorObject, INVALID_PARAMS_MSG, METHOD_TIMEOUT_CODE, PARSE_ERROR_CODE};
 use jsonrpsee::types::{ErrorObjectOwned, Response, ResponsePayload};
 use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc;
@@ -90,6 +93,72 @@ fn rpc_register_alias() {
 
 	assert!(module.method("hello_world").is_some());
 	assert!(module.method("hello_foobar").is_some());
+	assert_eq!(module.method_metadata("hello_foobar").unwrap().alias_of(), Some("hello_world"));
+}
+
+#[test]
+fn deprecate_method_and_require_auth_works() {
+	let mut module = RpcModule::new(());
+	module.register_method("hello", |_, _, _| "hello").unwrap();
+
+	module.deprecate_method("hello", "use `hello_v2`").unwrap();
+	module.require_auth("hello", "admin").unwrap();
+	assert!(module.deprecate_method("unknown", "gone").is_err());
+
+	let metadata = module.method_metadata("hello").unwrap();
+	assert_eq!(metadata.deprecation(), Some("use `hello_v2`"));
+	assert_eq!(metadata.auth(), Some("admin"));
+
+	let doc = module.openrpc(&jsonrpsee::server::OpenRpcInfo::new("test", "1.0.0"));
+	assert_eq!(doc["methods"][0]["deprecated"], true);
+}
+
+#[test]
+fn set_method_timeout_requires_registered_method() {
+	let mut module = RpcModule::new(());
+	module.register_async_method("hello", |_, _, _| async { "hello" }).unwrap();
+
+	assert!(module.set_method_timeout("hello", Duration::from_secs(1)).is_ok());
+	assert_eq!(module.method_config("hello").and_then(|c| c.timeout()), Some(Duration::from_secs(1)));
+	assert!(matches!(
+		module.set_method_timeout("not_registered", Duration::from_secs(1)),
+		Err(RegisterMethodError::MethodNotFound(_))
+	));
+}
+
+#[test]
+fn method_priority_works() {
+	let mut module = RpcModule::new(());
+	module.register_method("health", |_, _, _| "ok").unwrap();
+	module.register_method("bulk", |_, _, _| "ok").unwrap();
+	module.register_subscription("sub", "sub", "unsub", |_, _, _, _| async { Ok(()) }).unwrap();
+
+	module.set_response_priority("health", ResponsePriority::High).unwrap();
+	assert_eq!(module.response_priority("health"), ResponsePriority::High);
+	assert_eq!(module.response_priority("bulk"), ResponsePriority::Normal);
+	assert_eq!(module.response_priority("unsub"), ResponsePriority::High);
+
+	module.set_response_priority("unsub", ResponsePriority::Low).unwrap();
+	assert_eq!(module.response_priority("unsub"), ResponsePriority::Low);
+	assert!(matches!(
+		module.set_response_priority("not_registered", ResponsePriority::High),
+		Err(RegisterMethodError::MethodNotFound(_))
+	));
+}
+
+#[tokio::test]
+async fn calling_method_with_timeout_without_server() {
+	let mut module = RpcModule::new(());
+	module
+		.register_async_method("sleep", |_, _, _| async {
+			tokio::time::sleep(Duration::from_secs(60)).await;
+			"done"
+		})
+		.unwrap();
+	module.set_method_timeout("sleep", Duration::from_millis(10)).unwrap();
+
+	let err = module.call::<_, String>("sleep", EmptyServerParams::new()).await.unwrap_err();
+	assert!(matches!(err, MethodsError::JsonRpc(e) if e.code() == METHOD_TIMEOUT_CODE));
 }
 
 #[tokio::test]
@@ -136,6 +205,108 @@ async fn calling_method_without_server() {
 	assert_eq!(res, 25);
 }
 
+#[cfg(feature = "arbitrary-precision")]
+#[tokio::test]
+async fn arbitrary_precision_numbers_are_not_rounded() {
+	let mut module = RpcModule::new(());
+	module
+		.register_method("echo", |params, _, _| params.one::<serde_json::Value>().map_err(ErrorObjectOwned::from))
+		.unwrap();
+
+	let u256_max = "115792089237316195423570985008687907853269984665640564039457584007913129639935";
+	let req = format!(r#"{{"jsonrpc":"2.0","method":"echo","params":[{u256_max}],"id":1}}"#);
+	let (resp, _) = module.raw_json_request(&req, 1).await.unwrap();
+	assert_eq!(resp, format!(r#"{{"jsonrpc":"2.0","id":1,"result":{u256_max}}}"#));
+}
+
+#[tokio::test]
+async fn calling_raw_method_without_server() {
+	let mut module = RpcModule::new(());
+	module
+		.register_raw_method("echo", |params, _, _| {
+			let params = params.map(ToOwned::to_owned);
+			async move { params }
+		})
+		.unwrap();
+
+	let res: serde_json::Value = module.call("echo", (1, "two", [3])).await.unwrap();
+	assert_eq!(res, serde_json::json!([1, "two", [3]]));
+
+	let (rp, _) = module.raw_json_request(r#"{"jsonrpc":"2.0","method":"echo","id":0}"#, 1).await.unwrap();
+	assert_eq!(rp, r#"{"jsonrpc":"2.0","id":0,"result":null}"#);
+}
+
+#[tokio::test]
+async fn pre_serialized_responses_are_not_reserialized() {
+	let json = r#"{"cached": [1, 2,  3]}"#;
+	let cached = RawJson::new(json).unwrap();
+
+	let mut module = RpcModule::new(());
+	module.register_method("cached", move |_, _, _| cached.clone()).unwrap();
+	module
+		.register_method("boxed", move |_, _, _| serde_json::value::RawValue::from_string(json.to_owned()).unwrap())
+		.unwrap();
+	let borrowed: &'static serde_json::value::RawValue = serde_json::from_str(json).unwrap();
+	module.register_method("static", move |_, _, _| Cow::Borrowed(borrowed)).unwrap();
+	let shared: Arc<serde_json::value::RawValue> =
+		serde_json::value::RawValue::from_string(json.to_owned()).unwrap().into();
+	module.register_method("shared", move |_, _, _| shared.clone()).unwrap();
+
+	for method in ["cached", "boxed", "static", "shared"] {
+		let req = format!(r#"{{"jsonrpc":"2.0","method":"{method}","id":0}}"#);
+		let (rp, _) = module.raw_json_request(&req, 1).await.unwrap();
+		assert_eq!(rp, format!(r#"{{"jsonrpc":"2.0","id":0,"result":{json}}}"#));
+	}
+
+	assert!(RawJson::new("not json").is_err());
+}
+
+#[tokio::test]
+async fn shared_string_responses_work() {
+	let shared: Arc<str> = "shared".into();
+
+	let mut module = RpcModule::new(());
+	module.register_method("shared", move |_, _, _| shared.clone()).unwrap();
+	module.register_method("static", |_, _, _| Cow::<'static, str>::Borrowed("static")).unwrap();
+
+	let res: String = module.call("shared", EmptyServerParams::new()).await.unwrap();
+	assert_eq!(res, "shared");
+	let res: String = module.call("static", EmptyServerParams::new()).await.unwrap();
+	assert_eq!(res, "static");
+}
+
+#[tokio::test]
+async fn cpu_method_rejects_calls_when_queue_is_full() {
+	let (release_tx, release_rx) = std::sync::mpsc::channel::<()>();
+	let pool = CpuPool::new(1, 0);
+
+	let mut module = RpcModule::new(std::sync::Mutex::new(release_rx));
+	module
+		.register_cpu_method("compute", &pool, |_, release, _| {
+			release.lock().unwrap().recv().unwrap();
+			"done"
+		})
+		.unwrap();
+	let module = Arc::new(module);
+
+	// The only thread of the pool is busy with the first call.
+	let first = tokio::spawn({
+		let module = module.clone();
+		async move { module.call::<_, String>("compute", EmptyServerParams::new()).await }
+	});
+	tokio::time::sleep(Duration::from_millis(100)).await;
+
+	let err = module.call::<_, String>("compute", EmptyServerParams::new()).await.unwrap_err();
+	assert!(matches!(err, MethodsError::JsonRpc(e) if e.code() == ErrorCode::ServerIsBusy.code()));
+
+	release_tx.send(()).unwrap();
+	assert_eq!(first.await.unwrap().unwrap(), "done");
+
+	release_tx.send(()).unwrap();
+	let res: String = module.call("compute", EmptyServerParams::new()).await.unwrap();
+	assert_eq!(res, "done");
+}
+
 #[tokio::test]
 async fn calling_method_without_server_using_proc_macro() {
 	use jsonrpsee::{core::async_trait, proc_macros::rpc};
@@ -364,6 +535,175 @@ async fn subscribing_without_server_indicates_close() {
 	assert!(sub.next::<usize>().await.is_none());
 }
 
+#[tokio::test]
+async fn subscription_from_stream_without_server() {
+	init_logger();
+
+	let mut module = RpcModule::new(10_usize);
+	module
+		.register_subscription_from_stream("my_sub", "my_sub", "my_unsub", |params, ctx| {
+			let n: usize = params.one().unwrap_or(0);
+			futures::stream::iter(*ctx..*ctx + n)
+		})
+		.unwrap();
+
+	let mut sub = module.subscribe_unbounded("my_sub", [3]).await.unwrap();
+
+	for i in 10..13 {
+		let (val, id) = sub.next::<usize>().await.unwrap().unwrap();
+		assert_eq!(val, i);
+		assert_eq!(&id, sub.subscription_id());
+	}
+	// The subscription is closed once the stream is exhausted.
+	assert!(sub.next::<usize>().await.is_none());
+}
+
+#[tokio::test]
+async fn subscription_backpressure_policy_works() {
+	init_logger();
+
+	async fn received(policy: BackpressurePolicy) -> (Vec<usize>, bool) {
+		let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+		let subscribed = Arc::new(tokio::sync::Notify::new());
+		let mut module = RpcModule::new((tx, subscribed.clone()));
+		module
+			.register_subscription("my_sub", "my_sub", "my_unsub", |_, pending, ctx, _| async move {
+				let sink = pending.accept().await?;
+				let mut closed = false;
+
+				// Wait until the subscription response has been read.
+				ctx.1.notified().await;
+
+				for i in 0..5 {
+					if sink.send(SubscriptionMessage::from_json(&i)?).await.is_err() {
+						closed = true;
+						break;
+					}
+				}
+
+				ctx.0.send(closed).unwrap();
+				Ok(())
+			})
+			.unwrap();
+		module.set_subscription_backpressure("my_sub", policy).unwrap();
+
+		let mut sub = module.subscribe("my_sub", EmptyServerParams::new(), 1).await.unwrap();
+		subscribed.notify_one();
+		let closed = rx.recv().await.unwrap();
+
+		let mut items = Vec::new();
+		while let Ok(Some(Ok((item, _)))) = tokio::time::timeout(Duration::from_millis(100), sub.next()).await {
+			items.push(item);
+		}
+
+		(items, closed)
+	}
+
+	assert_eq!(received(BackpressurePolicy::DropNewest).await, (vec![0], false));
+	assert_eq!(received(BackpressurePolicy::DropOldest).await, (vec![0, 4], false));
+	assert_eq!(received(BackpressurePolicy::Disconnect).await, (vec![0], true));
+	assert_eq!(received(BackpressurePolicy::WaitTimeout(Duration::from_millis(1))).await, (vec![0], false));
+}
+
+#[tokio::test]
+async fn subscription_broadcast_works() {
+	init_logger();
+
+	let broadcast = SubscriptionBroadcast::new();
+	let mut module = RpcModule::new(broadcast.clone());
+	module
+		.register_subscription("my_sub", "my_sub", "my_unsub", |_, pending, broadcast, _| async move {
+			broadcast.add(pending.accept().await?);
+			Ok(())
+		})
+		.unwrap();
+
+	let mut sub1 = module.subscribe_unbounded("my_sub", EmptyServerParams::new()).await.unwrap();
+	let mut sub2 = module.subscribe_unbounded("my_sub", EmptyServerParams::new()).await.unwrap();
+	let mut sub3 = module.subscribe_unbounded("my_sub", EmptyServerParams::new()).await.unwrap();
+	sub3.close();
+
+	// Wait until the subscriptions have been added.
+	while broadcast.len() < 3 {
+		tokio::time::sleep(Duration::from_millis(10)).await;
+	}
+
+	assert_eq!(broadcast.send(SubscriptionMessage::from_json(&"hello").unwrap()).await, 2);
+	assert_eq!(broadcast.len(), 2);
+
+	for sub in [&mut sub1, &mut sub2] {
+		let (val, id) = sub.next::<String>().await.unwrap().unwrap();
+		assert_eq!(val, "hello");
+		assert_eq!(&id, sub.subscription_id());
+	}
+}
+
+#[tokio::test]
+async fn subscription_broadcast_shares_result() {
+	let broadcast = SubscriptionBroadcast::new();
+	let mut module = RpcModule::new(broadcast.clone());
+	module
+		.register_subscription("my_sub", "my_sub", "my_unsub", |_, pending, broadcast, _| async move {
+			broadcast.add(pending.accept().await?);
+			Ok(())
+		})
+		.unwrap();
+
+	let req = r#"{"jsonrpc":"2.0","method":"my_sub","id":0}"#;
+	let (rp1, mut stream1) = module.raw_json_request(req, 1).await.unwrap();
+	let (rp2, mut stream2) = module.raw_json_request(req, 1).await.unwrap();
+
+	while broadcast.len() < 2 {
+		tokio::time::sleep(Duration::from_millis(10)).await;
+	}
+	assert_eq!(broadcast.send(SubscriptionMessage::from_json(&"hello").unwrap()).await, 2);
+
+	let (n1, n2) = (stream1.recv().await.unwrap(), stream2.recv().await.unwrap());
+	let (SinkMessage::Notification { result: r1, .. }, SinkMessage::Notification { result: r2, .. }) = (&n1, &n2)
+	else {
+		panic!("Expected notifications");
+	};
+	assert!(Arc::ptr_eq(r1, r2));
+
+	for (rp, notif) in [(rp1, n1), (rp2, n2)] {
+		let sub_id = serde_json::from_str::<Response<serde_json::Value>>(&rp).unwrap().payload;
+		let ResponsePayload::Success(sub_id) = sub_id else { panic!("Expected valid response") };
+		assert_eq!(
+			notif.into_string(),
+			format!(r#"{{"jsonrpc":"2.0","method":"my_sub","params":{{"subscription":{},"result":"hello"}}}}"#, sub_id)
+		);
+	}
+}
+
+#[tokio::test]
+async fn subscription_broadcast_uses_the_id_of_each_subscription() {
+	let broadcast = SubscriptionBroadcast::new();
+	let mut module = RpcModule::new(broadcast.clone());
+	module
+		.register_subscription("my_sub", "my_sub", "my_unsub", |_, pending, broadcast, _| async move {
+			broadcast.add(pending.accept().await?);
+			Ok(())
+		})
+		.unwrap();
+
+	let mut sub1 = module.subscribe_unbounded("my_sub", EmptyServerParams::new()).await.unwrap();
+	let mut sub2 = module.subscribe_unbounded("my_sub", EmptyServerParams::new()).await.unwrap();
+
+	while broadcast.len() < 2 {
+		tokio::time::sleep(Duration::from_millis(10)).await;
+	}
+
+	// The message is built for the first subscription but must be sent to each subscription with its own ID.
+	let msg = SubscriptionMessage::new("my_sub", sub1.subscription_id().clone(), &"hello").unwrap();
+	assert_eq!(broadcast.send(msg).await, 2);
+
+	for sub in [&mut sub1, &mut sub2] {
+		let (val, id) = sub.next::<String>().await.unwrap().unwrap();
+		assert_eq!(val, "hello");
+		assert_eq!(&id, sub.subscription_id());
+	}
+}
+
 #[tokio::test]
 async fn subscribe_unsubscribe_without_server() {
 	let mut module = RpcModule::new(());
@@ -521,7 +861,7 @@ async fn serialize_sub_error_adds_extra_string_quotes() {
 
 	let (rp, mut stream) = module.raw_json_request(r#"{"jsonrpc":"2.0","method":"my_sub","id":0}"#, 1).await.unwrap();
 	let resp = serde_json::from_str::<Response<u64>>(&rp).unwrap();
-	let sub_resp = stream.recv().await.unwrap();
+	let sub_resp = stream.recv().await.unwrap().into_string();
 
 	let resp = match resp.payload {
 		ResponsePayload::Success(val) => val,
@@ -574,7 +914,7 @@ async fn subscription_close_response_works() {
 
 		assert_eq!(
 			format!(r#"{{"jsonrpc":"2.0","method":"my_sub","params":{{"subscription":{},"result":1}}}}"#, sub_id),
-			stream.recv().await.unwrap()
+			stream.recv().await.unwrap().into_string()
 		);
 	}
 
@@ -651,3 +991,104 @@ async fn conn_id_in_rpc_module_without_server() {
 		matches!(module.call::<_, usize>("get_conn_id", EmptyServerParams::new()).await, Ok(conn_id) if conn_id == 0)
 	);
 }
+
+#[tokio::test]
+async fn typed_method_works() {
+	#[derive(Deserialize)]
+	struct Transfer {
+		to: String,
+		amount: u64,
+		memo: Option<String>,
+	}
+
+	let mut module = RpcModule::new(());
+	module
+		.register_typed_method("transfer", |t: Transfer, _, _| {
+			format!("{} {} {}", t.to, t.amount, t.memo.unwrap_or_default())
+		})
+		.unwrap();
+	module.register_typed_method("optional", |p: Option<u64>, _, _| p).unwrap();
+	module
+		.register_async_typed_method("async_transfer", |t: Transfer, _, _| async move {
+			format!("{} {} {}", t.to, t.amount, t.memo.unwrap_or_default())
+		})
+		.unwrap();
+
+	// Positional and by-name params.
+	let (rp, _) = module
+		.raw_json_request(r#"{"jsonrpc":"2.0","method":"transfer","params":["alice",10,"rent"],"id":1}"#, 1)
+		.await
+		.unwrap();
+	assert_eq!(rp, r#"{"jsonrpc":"2.0","id":1,"result":"alice 10 rent"}"#);
+	let (rp, _) = module
+		.raw_json_request(r#"{"jsonrpc":"2.0","method":"transfer","params":{"amount":10,"to":"bob"},"id":1}"#, 1)
+		.await
+		.unwrap();
+	assert_eq!(rp, r#"{"jsonrpc":"2.0","id":1,"result":"bob 10 "}"#);
+	let (rp, _) = module
+		.raw_json_request(r#"{"jsonrpc":"2.0","method":"async_transfer","params":["alice",10,"rent"],"id":1}"#, 1)
+		.await
+		.unwrap();
+	assert_eq!(rp, r#"{"jsonrpc":"2.0","id":1,"result":"alice 10 rent"}"#);
+
+	// Trailing optional positional params may be left out.
+	let (rp, _) = module
+		.raw_json_request(r#"{"jsonrpc":"2.0","method":"transfer","params":["alice",10],"id":1}"#, 1)
+		.await
+		.unwrap();
+	assert_eq!(rp, r#"{"jsonrpc":"2.0","id":1,"result":"alice 10 "}"#);
+
+	// The errors name the offending field.
+	let (rp, _) = module
+		.raw_json_request(r#"{"jsonrpc":"2.0","method":"transfer","params":{"to":"bob","amount":"ten"},"id":1}"#, 1)
+		.await
+		.unwrap();
+	let rp: Response<()> = serde_json::from_str(&rp).unwrap();
+	let ResponsePayload::Error(err) = rp.payload else { panic!("expected error") };
+	assert_eq!(err.code(), ErrorCode::InvalidParams.code());
+	assert_eq!(err.message(), INVALID_PARAMS_MSG);
+	assert!(err.data().unwrap().get().contains("invalid value for field `amount`"));
+
+	let (rp, _) = module
+		.raw_json_request(r#"{"jsonrpc":"2.0","method":"transfer","params":["bob",true],"id":1}"#, 1)
+		.await
+		.unwrap();
+	let rp: Response<()> = serde_json::from_str(&rp).unwrap();
+	let ResponsePayload::Error(err) = rp.payload else { panic!("expected error") };
+	assert!(err.data().unwrap().get().contains("invalid value for field `amount`"));
+
+	let (rp, _) =
+		module.raw_json_request(r#"{"jsonrpc":"2.0","method":"transfer","params":["bob"],"id":1}"#, 1).await.unwrap();
+	let rp: Response<()> = serde_json::from_str(&rp).unwrap();
+	let ResponsePayload::Error(err) = rp.payload else { panic!("expected error") };
+	assert!(err.data().unwrap().get().contains("invalid value for field `amount`: invalid type: null"));
+
+	let (rp, _) = module
+		.raw_json_request(r#"{"jsonrpc":"2.0","method":"transfer","params":{"to":"bob"},"id":1}"#, 1)
+		.await
+		.unwrap();
+	let rp: Response<()> = serde_json::from_str(&rp).unwrap();
+	let ResponsePayload::Error(err) = rp.payload else { panic!("expected error") };
+	assert!(err.data().unwrap().get().contains("missing field `amount`"));
+
+	// Calls without params.
+	let (rp, _) = module.raw_json_request(r#"{"jsonrpc":"2.0","method":"optional","id":1}"#, 1).await.unwrap();
+	assert_eq!(rp, r#"{"jsonrpc":"2.0","id":1,"result":null}"#);
+}
+
+#[tokio::test]
+async fn swappable_context_works() {
+	let mut module = RpcModule::new(SwappableContext::new(1_u64));
+	module.register_method("value", |_, ctx, _| *ctx.load()).unwrap();
+	module.register_async_method("async_value", |_, ctx, _| async move { *ctx.load() }).unwrap();
+
+	let handle = module.context_handle();
+	assert_eq!(module.call::<_, u64>("value", EmptyServerParams::new()).await.unwrap(), 1);
+
+	assert_eq!(*handle.store(2), 1);
+	assert_eq!(module.call::<_, u64>("value", EmptyServerParams::new()).await.unwrap(), 2);
+	assert_eq!(module.call::<_, u64>("async_value", EmptyServerParams::new()).await.unwrap(), 2);
+
+	handle.update(|v| v * 10);
+	assert_eq!(module.call::<_, u64>("value", EmptyServerParams::new()).await.unwrap(), 20);
+}
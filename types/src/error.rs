@@ -25,6 +25,8 @@
 // DEALINGS IN THE SOFTWARE.
 
 use std::fmt;
+use std::ops::RangeInclusive;
+use std::time::Duration;
 
 use serde::de::Deserializer;
 use serde::ser::Serializer;
@@ -66,12 +68,107 @@ impl<'a> ErrorObject<'a> {
 		self.data.as_ref().map(|d| d.borrow())
 	}
 
+	/// Deserialize the data associated with this error, if any.
+	///
+	/// ```
+	/// use jsonrpsee_types::ErrorObject;
+	///
+	/// let err = ErrorObject::try_owned(3, "execution reverted", Some(vec!["insufficient balance"])).unwrap();
+	/// assert_eq!(err.data_as::<Vec<&str>>().unwrap(), Some(vec!["insufficient balance"]));
+	/// ```
+	pub fn data_as<'b, T: Deserialize<'b>>(&'b self) -> Result<Option<T>, serde_json::Error> {
+		self.data().map(|d| serde_json::from_str(d.get())).transpose()
+	}
+
 	/// Create a new `ErrorObjectOwned` with optional data.
+	///
+	/// The data is dropped if it can't be serialized, see [`ErrorObject::try_owned`] to handle the error.
 	pub fn owned<S: Serialize>(code: i32, message: impl Into<String>, data: Option<S>) -> ErrorObject<'static> {
 		let data = data.and_then(|d| serde_json::value::to_raw_value(&d).ok());
 		ErrorObject { code: code.into(), message: message.into().into(), data: data.map(StdCow::Owned) }
 	}
 
+	/// Create a new `ErrorObjectOwned` with optional data, which fails if the data can't be serialized.
+	pub fn try_owned<S: Serialize>(
+		code: i32,
+		message: impl Into<String>,
+		data: Option<S>,
+	) -> Result<ErrorObject<'static>, serde_json::Error> {
+		let data = data.map(|d| serde_json::value::to_raw_value(&d)).transpose()?;
+		Ok(ErrorObject { code: code.into(), message: message.into().into(), data: data.map(StdCow::Owned) })
+	}
+
+	/// Replace the data associated with this error, which fails if the data can't be serialized.
+	pub fn with_data<S: Serialize>(self, data: S) -> Result<ErrorObject<'a>, serde_json::Error> {
+		let data = serde_json::value::to_raw_value(&data)?;
+		Ok(ErrorObject { code: self.code, message: self.message, data: Some(StdCow::Owned(data)) })
+	}
+
+	/// Create a new [`ErrorObjectBuilder`] for the given code.
+	///
+	/// The codes in [`RESERVED_CODES`] are only accepted if they're defined by the JSON-RPC specification
+	/// or by jsonrpsee, such that the application errors don't collide with them.
+	///
+	/// ```
+	/// use jsonrpsee_types::{ErrorObject, error::INVALID_PARAMS_CODE};
+	///
+	/// let err = ErrorObject::build(1001).message("insufficient balance").data(42).finish().unwrap();
+	/// assert_eq!(err.data_as::<u32>().unwrap(), Some(42));
+	///
+	/// let err = ErrorObject::build(INVALID_PARAMS_CODE).data("expected an address").finish().unwrap();
+	/// assert_eq!(err.message(), "Invalid params");
+	///
+	/// assert!(ErrorObject::build(-32050).message("collides").finish().is_err());
+	/// ```
+	pub fn build(code: i32) -> ErrorObjectBuilder {
+		ErrorObjectBuilder { code, message: None, data: None }
+	}
+
+	/// Create a new `ErrorObjectOwned` for [`ErrorCode::InvalidParams`].
+	pub fn invalid_params<S: Serialize>(data: Option<S>) -> ErrorObject<'static> {
+		ErrorObject::owned(INVALID_PARAMS_CODE, INVALID_PARAMS_MSG, data)
+	}
+
+	/// Create a new `ErrorObjectOwned` for [`ErrorCode::InternalError`].
+	pub fn internal_error<S: Serialize>(data: Option<S>) -> ErrorObject<'static> {
+		ErrorObject::owned(INTERNAL_ERROR_CODE, INTERNAL_ERROR_MSG, data)
+	}
+
+	/// Create a new `ErrorObjectOwned` with the code and message of a well-known application error.
+	pub fn app_error<S: Serialize>(code: AppErrorCode, data: Option<S>) -> ErrorObject<'static> {
+		ErrorObject::owned(code.code(), code.message(), data)
+	}
+
+	/// Create a new `ErrorObjectOwned` for [`AppErrorCode::RateLimited`].
+	pub fn rate_limited<S: Serialize>(data: Option<S>) -> ErrorObject<'static> {
+		ErrorObject::app_error(AppErrorCode::RateLimited, data)
+	}
+
+	/// Create a new `ErrorObjectOwned` for [`AppErrorCode::Unauthorized`].
+	pub fn unauthorized<S: Serialize>(data: Option<S>) -> ErrorObject<'static> {
+		ErrorObject::app_error(AppErrorCode::Unauthorized, data)
+	}
+
+	/// Create a new `ErrorObjectOwned` for [`AppErrorCode::MethodDeprecated`].
+	pub fn method_deprecated<S: Serialize>(data: Option<S>) -> ErrorObject<'static> {
+		ErrorObject::app_error(AppErrorCode::MethodDeprecated, data)
+	}
+
+	/// Create a new `ErrorObjectOwned` for [`AppErrorCode::ResourceExhausted`].
+	pub fn resource_exhausted<S: Serialize>(data: Option<S>) -> ErrorObject<'static> {
+		ErrorObject::app_error(AppErrorCode::ResourceExhausted, data)
+	}
+
+	/// Create a new `ErrorObjectOwned` for [`AppErrorCode::NodeSyncing`].
+	pub fn node_syncing<S: Serialize>(data: Option<S>) -> ErrorObject<'static> {
+		ErrorObject::app_error(AppErrorCode::NodeSyncing, data)
+	}
+
+	/// Returns the well-known application error of this error object, if any.
+	pub fn app_code(&self) -> Option<AppErrorCode> {
+		AppErrorCode::from_code(self.code())
+	}
+
 	/// Create a new [`ErrorObject`] with optional data.
 	pub fn borrowed(code: i32, message: &'a str, data: Option<&'a RawValue>) -> ErrorObject<'a> {
 		ErrorObject { code: code.into(), message: StdCow::Borrowed(message), data: data.map(StdCow::Borrowed) }
@@ -96,6 +193,90 @@ impl<'a> ErrorObject<'a> {
 	}
 }
 
+/// Builder for [`ErrorObject`], see [`ErrorObject::build`].
+#[derive(Debug)]
+pub struct ErrorObjectBuilder {
+	code: i32,
+	message: Option<String>,
+	data: Option<Result<Box<RawValue>, serde_json::Error>>,
+}
+
+impl ErrorObjectBuilder {
+	/// Set the message of the error.
+	///
+	/// Default: the message of the code if it's defined by the JSON-RPC specification or by jsonrpsee,
+	/// otherwise the message is required.
+	pub fn message(mut self, message: impl Into<String>) -> Self {
+		self.message = Some(message.into());
+		self
+	}
+
+	/// Set the data of the error.
+	pub fn data<S: Serialize>(mut self, data: S) -> Self {
+		self.data = Some(serde_json::value::to_raw_value(&data));
+		self
+	}
+
+	/// Finish the error object.
+	pub fn finish(self) -> Result<ErrorObjectOwned, ErrorObjectBuildError> {
+		let known_message = known_message(self.code);
+
+		if RESERVED_CODES.contains(&self.code) && known_message.is_none() {
+			return Err(ErrorObjectBuildError::ReservedCode(self.code));
+		}
+
+		let message = match (self.message, known_message) {
+			(Some(message), _) => message,
+			(None, Some(message)) => message.to_owned(),
+			(None, None) => return Err(ErrorObjectBuildError::MissingMessage),
+		};
+		let data = self.data.transpose().map_err(ErrorObjectBuildError::Data)?;
+
+		Ok(ErrorObject { code: self.code.into(), message: message.into(), data: data.map(StdCow::Owned) })
+	}
+}
+
+/// Error when finishing an [`ErrorObjectBuilder`].
+#[derive(Debug, Error)]
+pub enum ErrorObjectBuildError {
+	/// The code is in [`RESERVED_CODES`] but isn't defined by the JSON-RPC specification or by jsonrpsee.
+	#[error("Error code {0} is reserved by the JSON-RPC specification")]
+	ReservedCode(i32),
+	/// The code has no default message and no message was set.
+	#[error("Error message is required for application error codes")]
+	MissingMessage,
+	/// The data couldn't be serialized.
+	#[error("Error data couldn't be serialized: {0}")]
+	Data(serde_json::Error),
+}
+
+/// Returns the message of a code which is defined by the JSON-RPC specification or by jsonrpsee.
+const fn known_message(code: i32) -> Option<&'static str> {
+	let message = match code {
+		PARSE_ERROR_CODE => PARSE_ERROR_MSG,
+		INVALID_REQUEST_CODE => INVALID_REQUEST_MSG,
+		METHOD_NOT_FOUND_CODE => METHOD_NOT_FOUND_MSG,
+		INVALID_PARAMS_CODE => INVALID_PARAMS_MSG,
+		INTERNAL_ERROR_CODE => INTERNAL_ERROR_MSG,
+		CALL_EXECUTION_FAILED_CODE | UNKNOWN_ERROR_CODE => SERVER_ERROR_MSG,
+		BATCHES_NOT_SUPPORTED_CODE => BATCHES_NOT_SUPPORTED_MSG,
+		TOO_MANY_SUBSCRIPTIONS_CODE => TOO_MANY_SUBSCRIPTIONS_MSG,
+		OVERSIZED_REQUEST_CODE => OVERSIZED_REQUEST_MSG,
+		OVERSIZED_RESPONSE_CODE => OVERSIZED_RESPONSE_MSG,
+		SERVER_IS_BUSY_CODE => SERVER_IS_BUSY_MSG,
+		TOO_BIG_BATCH_REQUEST_CODE => TOO_BIG_BATCH_REQUEST_MSG,
+		TOO_BIG_BATCH_RESPONSE_CODE => TOO_BIG_BATCH_RESPONSE_MSG,
+		METHOD_TIMEOUT_CODE => METHOD_TIMEOUT_MSG,
+		BATCH_CALL_NOT_PROCESSED_CODE => BATCH_CALL_NOT_PROCESSED_MSG,
+		code => match AppErrorCode::from_code(code) {
+			Some(code) => code.message(),
+			None => return None,
+		},
+	};
+
+	Some(message)
+}
+
 impl<'a> PartialEq for ErrorObject<'a> {
 	fn eq(&self, other: &Self) -> bool {
 		let this_raw = self.data.as_ref().map(|r| r.get());
@@ -110,6 +291,11 @@ impl<'a> From<ErrorCode> for ErrorObject<'a> {
 	}
 }
 
+/// Codes which are reserved by the JSON-RPC specification and must not be used by applications.
+pub const RESERVED_CODES: RangeInclusive<i32> = -32768..=-32000;
+/// Codes which are reserved for implementation-defined server errors, a subset of [`RESERVED_CODES`].
+pub const SERVER_ERROR_CODES: RangeInclusive<i32> = -32099..=-32000;
+
 /// Parse error code.
 pub const PARSE_ERROR_CODE: i32 = -32700;
 /// Invalid request error code.
@@ -138,6 +324,20 @@ pub const SERVER_IS_BUSY_CODE: i32 = -32009;
 pub const TOO_BIG_BATCH_REQUEST_CODE: i32 = -32010;
 /// Batch request limit was exceed.
 pub const TOO_BIG_BATCH_RESPONSE_CODE: i32 = -32011;
+/// Method call exceeded its execution timeout.
+pub const METHOD_TIMEOUT_CODE: i32 = -32012;
+/// Batch call was not processed because a previous call of the batch failed.
+pub const BATCH_CALL_NOT_PROCESSED_CODE: i32 = -32013;
+/// The rate limit of the client was exceeded.
+pub const RATE_LIMITED_CODE: i32 = -32014;
+/// The client is not authorized to call the method.
+pub const UNAUTHORIZED_CODE: i32 = -32015;
+/// The method is deprecated and no longer served.
+pub const METHOD_DEPRECATED_CODE: i32 = -32016;
+/// A resource that is required to answer the call is exhausted.
+pub const RESOURCE_EXHAUSTED_CODE: i32 = -32017;
+/// The node is syncing and can't answer the call yet.
+pub const NODE_SYNCING_CODE: i32 = -32018;
 
 /// Parse error message
 pub const PARSE_ERROR_MSG: &str = "Parse error";
@@ -165,6 +365,20 @@ pub const TOO_MANY_SUBSCRIPTIONS_MSG: &str = "Too many subscriptions on the conn
 pub const TOO_BIG_BATCH_REQUEST_MSG: &str = "The batch request was too large";
 /// Batch request response limit was exceed.
 pub const TOO_BIG_BATCH_RESPONSE_MSG: &str = "The batch response was too large";
+/// Method call exceeded its execution timeout.
+pub const METHOD_TIMEOUT_MSG: &str = "Method call timed out";
+/// Batch call was not processed because a previous call of the batch failed.
+pub const BATCH_CALL_NOT_PROCESSED_MSG: &str = "The call was not processed because a previous call of the batch failed";
+/// Rate limited error message.
+pub const RATE_LIMITED_MSG: &str = "Rate limit exceeded, try again later";
+/// Unauthorized error message.
+pub const UNAUTHORIZED_MSG: &str = "Unauthorized";
+/// Method deprecated error message.
+pub const METHOD_DEPRECATED_MSG: &str = "Method is deprecated";
+/// Resource exhausted error message.
+pub const RESOURCE_EXHAUSTED_MSG: &str = "Resource exhausted";
+/// Node syncing error message.
+pub const NODE_SYNCING_MSG: &str = "Node is syncing";
 
 /// JSONRPC error code
 #[derive(Error, Debug, PartialEq, Eq, Copy, Clone)]
@@ -204,6 +418,18 @@ impl ErrorCode {
 		}
 	}
 
+	/// Returns whether the code is reserved by the JSON-RPC specification, see [`RESERVED_CODES`].
+	pub const fn is_reserved(&self) -> bool {
+		let code = self.code();
+		code >= *RESERVED_CODES.start() && code <= *RESERVED_CODES.end()
+	}
+
+	/// Returns whether the code is an implementation-defined server error, see [`SERVER_ERROR_CODES`].
+	pub const fn is_server_error(&self) -> bool {
+		let code = self.code();
+		code >= *SERVER_ERROR_CODES.start() && code <= *SERVER_ERROR_CODES.end()
+	}
+
 	/// Returns the message for the given error code.
 	pub const fn message(&self) -> &'static str {
 		use ErrorCode::*;
@@ -260,6 +486,81 @@ impl serde::Serialize for ErrorCode {
 	}
 }
 
+/// Well-known application error codes in the range reserved for implementation-defined server errors.
+///
+/// They are returned by the methods rather than by the server itself, see the constructors
+/// such as [`ErrorObject::rate_limited`] to create the corresponding error objects.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
+pub enum AppErrorCode {
+	/// The rate limit of the client was exceeded.
+	RateLimited,
+	/// The client is not authorized to call the method.
+	Unauthorized,
+	/// The method is deprecated and no longer served.
+	MethodDeprecated,
+	/// A resource that is required to answer the call is exhausted, such as a quota or storage.
+	ResourceExhausted,
+	/// The node is syncing and can't answer the call yet.
+	NodeSyncing,
+}
+
+impl AppErrorCode {
+	/// Returns integer code value
+	pub const fn code(&self) -> i32 {
+		use AppErrorCode::*;
+		match *self {
+			RateLimited => RATE_LIMITED_CODE,
+			Unauthorized => UNAUTHORIZED_CODE,
+			MethodDeprecated => METHOD_DEPRECATED_CODE,
+			ResourceExhausted => RESOURCE_EXHAUSTED_CODE,
+			NodeSyncing => NODE_SYNCING_CODE,
+		}
+	}
+
+	/// Returns the message for the given error code.
+	pub const fn message(&self) -> &'static str {
+		use AppErrorCode::*;
+		match *self {
+			RateLimited => RATE_LIMITED_MSG,
+			Unauthorized => UNAUTHORIZED_MSG,
+			MethodDeprecated => METHOD_DEPRECATED_MSG,
+			ResourceExhausted => RESOURCE_EXHAUSTED_MSG,
+			NodeSyncing => NODE_SYNCING_MSG,
+		}
+	}
+
+	/// Returns the application error code of the integer code value, if it's a well-known one.
+	pub const fn from_code(code: i32) -> Option<Self> {
+		use AppErrorCode::*;
+		match code {
+			RATE_LIMITED_CODE => Some(RateLimited),
+			UNAUTHORIZED_CODE => Some(Unauthorized),
+			METHOD_DEPRECATED_CODE => Some(MethodDeprecated),
+			RESOURCE_EXHAUSTED_CODE => Some(ResourceExhausted),
+			NODE_SYNCING_CODE => Some(NodeSyncing),
+			_ => None,
+		}
+	}
+}
+
+impl fmt::Display for AppErrorCode {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{}: {}", self.code(), self.message())
+	}
+}
+
+impl From<AppErrorCode> for ErrorCode {
+	fn from(code: AppErrorCode) -> Self {
+		ErrorCode::ServerError(code.code())
+	}
+}
+
+impl<'a> From<AppErrorCode> for ErrorObject<'a> {
+	fn from(code: AppErrorCode) -> Self {
+		Self { code: code.into(), message: code.message().into(), data: None }
+	}
+}
+
 /// Helper to get a `JSON-RPC` error object when the maximum number of subscriptions have been exceeded.
 pub fn reject_too_many_subscriptions(limit: u32) -> ErrorObjectOwned {
 	ErrorObjectOwned::owned(
@@ -296,9 +597,19 @@ pub fn reject_too_big_batch_response(limit: usize) -> ErrorObjectOwned {
 	)
 }
 
+/// Helper to get a `JSON-RPC` error object when a method call exceeded its execution timeout.
+pub fn reject_method_timeout(timeout: Duration) -> ErrorObjectOwned {
+	ErrorObjectOwned::owned(
+		METHOD_TIMEOUT_CODE,
+		METHOD_TIMEOUT_MSG,
+		Some(format!("Exceeded max execution time of {}ms", timeout.as_millis())),
+	)
+}
+
 #[cfg(test)]
 mod tests {
-	use super::{ErrorCode, ErrorObject};
+	use super::{AppErrorCode, ErrorCode, ErrorObject, ErrorObjectBuildError, INVALID_PARAMS_CODE, RATE_LIMITED_CODE};
+	use std::collections::BTreeMap;
 
 	#[test]
 	fn deserialize_works() {
@@ -308,6 +619,67 @@ mod tests {
 		assert_eq!(exp, err);
 	}
 
+	#[test]
+	fn builder_works() {
+		let err = ErrorObject::build(1001).message("insufficient balance").data(["a"]).finish().unwrap();
+		assert_eq!(err, ErrorObject::owned(1001, "insufficient balance", Some(["a"])));
+
+		let err = ErrorObject::build(INVALID_PARAMS_CODE).finish().unwrap();
+		assert_eq!(err, ErrorObject::invalid_params(None::<()>));
+
+		let err = ErrorObject::build(RATE_LIMITED_CODE).message("slow down").finish().unwrap();
+		assert_eq!(err.app_code(), Some(AppErrorCode::RateLimited));
+		assert_eq!(err.message(), "slow down");
+
+		assert!(matches!(ErrorObject::build(-32050).finish(), Err(ErrorObjectBuildError::ReservedCode(-32050))));
+		assert!(matches!(ErrorObject::build(-32768).finish(), Err(ErrorObjectBuildError::ReservedCode(-32768))));
+		assert!(matches!(ErrorObject::build(-31999).finish(), Err(ErrorObjectBuildError::MissingMessage)));
+
+		let map = BTreeMap::from([((1, 2), 3)]);
+		assert!(matches!(
+			ErrorObject::build(1).message("invalid data").data(map).finish(),
+			Err(ErrorObjectBuildError::Data(_))
+		));
+	}
+
+	#[test]
+	fn reserved_codes_works() {
+		assert!(ErrorCode::ParseError.is_reserved());
+		assert!(!ErrorCode::ParseError.is_server_error());
+		assert!(ErrorCode::from(AppErrorCode::NodeSyncing).is_server_error());
+		assert!(ErrorCode::ServerError(-32000).is_server_error());
+		assert!(!ErrorCode::ServerError(-32100).is_server_error());
+		assert!(ErrorCode::ServerError(-32100).is_reserved());
+		assert!(!ErrorCode::ServerError(-31999).is_reserved());
+		assert!(!ErrorCode::ServerError(1).is_reserved());
+	}
+
+	#[test]
+	fn typed_data_works() {
+		#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+		struct Revert {
+			reason: String,
+		}
+
+		let revert = Revert { reason: "out of gas".into() };
+		let err = ErrorObject::try_owned(3, "execution reverted", Some(&revert)).unwrap();
+		assert_eq!(err.data().unwrap().get(), r#"{"reason":"out of gas"}"#);
+		assert_eq!(err.data_as::<Revert>().unwrap(), Some(revert));
+		assert!(err.data_as::<u32>().is_err());
+
+		let err = ErrorObject::from(ErrorCode::InvalidParams).with_data(["a", "b"]).unwrap();
+		assert_eq!(err.code(), ErrorCode::InvalidParams.code());
+		assert_eq!(err.data_as::<Vec<&str>>().unwrap(), Some(vec!["a", "b"]));
+
+		let err = ErrorObject::try_owned(3, "no data", None::<()>).unwrap();
+		assert_eq!(err.data_as::<Revert>().unwrap(), None);
+
+		// Maps with non-string keys can't be serialized to JSON.
+		let map = BTreeMap::from([((1, 2), 3)]);
+		assert!(ErrorObject::try_owned(3, "invalid data", Some(&map)).is_err());
+		assert!(ErrorObject::owned(3, "invalid data", Some(&map)).data().is_none());
+	}
+
 	#[test]
 	fn deserialize_with_optional_data() {
 		let ser = r#"{"code":-32700,"message":"Parse error", "data":"vegan"}"#;
@@ -351,4 +723,22 @@ mod tests {
 		let ser = serde_json::to_string(&ErrorObject::owned(-32699, "food", Some(data))).unwrap();
 		assert_eq!(exp, ser);
 	}
+
+	#[test]
+	fn app_errors_work() {
+		#[derive(serde::Serialize)]
+		struct RetryAfter {
+			retry_after_ms: u64,
+		}
+
+		let err = ErrorObject::rate_limited(Some(RetryAfter { retry_after_ms: 500 }));
+		let exp = r#"{"code":-32014,"message":"Rate limit exceeded, try again later","data":{"retry_after_ms":500}}"#;
+		assert_eq!(serde_json::to_string(&err).unwrap(), exp);
+
+		let err: ErrorObject = serde_json::from_str(exp).unwrap();
+		assert_eq!(err.app_code(), Some(AppErrorCode::RateLimited));
+		assert_eq!(ErrorObject::from(ErrorCode::InternalError).app_code(), None);
+
+		assert_eq!(ErrorObject::node_syncing(None::<()>), AppErrorCode::NodeSyncing.into());
+	}
 }
@@ -44,7 +44,16 @@ pub mod response;
 /// JSON-RPC response error object related types.
 pub mod error;
 
-pub use error::{ErrorCode, ErrorObject, ErrorObjectOwned};
-pub use params::{Id, InvalidRequestId, Params, ParamsSequence, SubscriptionId, TwoPointZero};
+/// JSON-RPC 1.0 compatibility.
+pub mod v1;
+
+pub use error::{AppErrorCode, ErrorCode, ErrorObject, ErrorObjectBuildError, ErrorObjectBuilder, ErrorObjectOwned};
+pub use params::{
+	Id, InvalidParam, InvalidRequestId, MaybeParam, Params, ParamsArguments, ParamsMap, ParamsSequence, RawId,
+	SubscriptionId, TwoPointZero,
+};
 pub use request::{InvalidRequest, Notification, NotificationSer, Request, RequestSer};
-pub use response::{Response, ResponsePayload, SubscriptionPayload, SubscriptionResponse, Success as ResponseSuccess};
+pub use response::{
+	Response, ResponsePayload, SubscriptionNotification, SubscriptionPayload, SubscriptionResponse,
+	Success as ResponseSuccess,
+};
@@ -28,11 +28,13 @@
 //! Some types come with a "*Ser" variant that implements [`serde::Serialize`]; these are used in the client.
 
 use std::borrow::Cow;
+use std::collections::BTreeMap;
 use std::fmt;
 
-use serde::de::{self, Deserializer, Unexpected, Visitor};
+use serde::de::{self, DeserializeOwned, Deserializer, Unexpected, Visitor};
 use serde::ser::Serializer;
 use serde::{Deserialize, Serialize};
+use serde_json::value::RawValue;
 use serde_json::Value as JsonValue;
 
 use crate::error::{ErrorCode, INVALID_PARAMS_MSG};
@@ -114,7 +116,43 @@ impl<'a> Params<'a> {
 			Some(json) => json,
 			None => "",
 		};
-		ParamsSequence(json)
+		ParamsSequence(json, 0)
+	}
+
+	/// Obtain a parser of the params by name, [`ParamsMap`].
+	///
+	/// Absent params are regarded as an empty object.
+	pub fn map(&self) -> Result<ParamsMap<'_>, ErrorObjectOwned> {
+		match self.0.as_ref() {
+			Some(json) => serde_json::from_str(json).map(ParamsMap).map_err(invalid_params),
+			None => Ok(ParamsMap(BTreeMap::new())),
+		}
+	}
+
+	/// Obtain a parser which maps the params onto the arguments of a method, [`ParamsArguments`].
+	///
+	/// The params are parsed by name if they are a JSON object and by position otherwise, such
+	/// that a method accepts both kinds of params.
+	///
+	/// ```
+	/// # use jsonrpsee_types::params::Params;
+	/// for params in [r#"[10, true]"#, r#"{"verbose": true, "height": 10}"#] {
+	///     let params = Params::new(Some(params));
+	///     let mut args = params.arguments().unwrap();
+	///
+	///     let height: u64 = args.next("height").unwrap();
+	///     let verbose: Option<bool> = args.optional_next("verbose").unwrap();
+	///
+	///     assert_eq!(height, 10);
+	///     assert_eq!(verbose, Some(true));
+	/// }
+	/// ```
+	pub fn arguments(&self) -> Result<ParamsArguments<'_>, ErrorObjectOwned> {
+		if self.is_object() {
+			self.map().map(ParamsArguments::Map)
+		} else {
+			Ok(ParamsArguments::Sequence(self.sequence()))
+		}
 	}
 
 	/// Attempt to parse all parameters as an array or map into type `T`.
@@ -124,7 +162,54 @@ impl<'a> Params<'a> {
 	{
 		// NOTE(niklasad1): Option::None is serialized as `null` so we provide that here.
 		let params = self.0.as_ref().map(AsRef::as_ref).unwrap_or("null");
-		serde_json::from_str(params).map_err(invalid_params)
+		serde_json::from_str(params).map_err(|e| InvalidParam::from_json_error(params, e).into_error())
+	}
+
+	/// Attempt to parse all parameters as an array or map into type `T`, where the absent parameters
+	/// are taken from `defaults`.
+	///
+	/// For params given by position the trailing parameters which are absent are taken from `defaults`
+	/// if it serializes to an array, and for params given by name the absent fields are taken from `defaults`
+	/// if it serializes to an object. A parameter that is `null` is not regarded as absent.
+	///
+	/// ```
+	/// # use jsonrpsee_types::params::Params;
+	/// let defaults = (0, Some(true));
+	///
+	/// let params = Params::new(Some("[10]"));
+	/// assert_eq!(params.parse_with_defaults::<(u64, Option<bool>)>(defaults).unwrap(), (10, Some(true)));
+	///
+	/// let params = Params::new(Some("[10, null]"));
+	/// assert_eq!(params.parse_with_defaults::<(u64, Option<bool>)>(defaults).unwrap(), (10, None));
+	///
+	/// let params = Params::new(None);
+	/// assert_eq!(params.parse_with_defaults::<(u64, Option<bool>)>(defaults).unwrap(), (0, Some(true)));
+	/// ```
+	pub fn parse_with_defaults<T>(&self, defaults: T) -> Result<T, ErrorObjectOwned>
+	where
+		T: Serialize + DeserializeOwned,
+	{
+		let defaults = serde_json::to_value(defaults).map_err(invalid_params)?;
+		let Some(json) = self.0.as_ref() else {
+			return serde_json::from_value(defaults).map_err(invalid_params);
+		};
+
+		let params = match (serde_json::from_str(json).map_err(invalid_params)?, defaults) {
+			(JsonValue::Array(mut params), JsonValue::Array(defaults)) => {
+				let given = params.len();
+				params.extend(defaults.into_iter().skip(given));
+				JsonValue::Array(params)
+			}
+			(JsonValue::Object(mut params), JsonValue::Object(defaults)) => {
+				for (name, value) in defaults {
+					params.entry(name).or_insert(value);
+				}
+				JsonValue::Object(params)
+			}
+			(params, _) => params,
+		};
+
+		serde_json::from_value(params).map_err(invalid_params)
 	}
 
 	/// Attempt to parse parameters as an array of a single value of type `T`, and returns that value.
@@ -167,7 +252,7 @@ impl<'a> Params<'a> {
 ///
 /// Regards empty array `[]` as no parameters provided.
 #[derive(Debug, Copy, Clone)]
-pub struct ParamsSequence<'a>(&'a str);
+pub struct ParamsSequence<'a>(&'a str, usize);
 
 impl<'a> ParamsSequence<'a> {
 	fn next_inner<T>(&mut self) -> Option<Result<T, ErrorObjectOwned>>
@@ -183,7 +268,7 @@ impl<'a> ParamsSequence<'a> {
 			b'[' | b',' => json = &json[1..],
 			_ => {
 				let errmsg = format!("Invalid params. Expected one of '[', ']' or ',' but found {json:?}");
-				return Some(Err(invalid_params(errmsg)));
+				return Some(Err(InvalidParam::new(errmsg).with_position(self.1).with_expected::<T>().into_error()));
 			}
 		}
 
@@ -192,12 +277,13 @@ impl<'a> ParamsSequence<'a> {
 		match iter.next()? {
 			Ok(value) => {
 				self.0 = json[iter.byte_offset()..].trim_start();
+				self.1 += 1;
 
 				Some(Ok(value))
 			}
 			Err(e) => {
 				self.0 = "";
-				Some(Err(invalid_params(e)))
+				Some(Err(InvalidParam::new(e).with_position(self.1).with_expected::<T>().into_error()))
 			}
 		}
 	}
@@ -224,7 +310,7 @@ impl<'a> ParamsSequence<'a> {
 	{
 		match self.next_inner() {
 			Some(result) => result,
-			None => Err(invalid_params("No more params")),
+			None => Err(InvalidParam::new("No more params").with_position(self.1).with_expected::<T>().into_error()),
 		}
 	}
 
@@ -255,11 +341,329 @@ impl<'a> ParamsSequence<'a> {
 			None => Ok(None),
 		}
 	}
+
+	/// Parse the next parameter to type `T` and distinguish between a missing parameter and `null`.
+	///
+	/// ```
+	/// # use jsonrpsee_types::params::{MaybeParam, Params};
+	/// let params = Params::new(Some(r#"[1, null]"#));
+	/// let mut seq = params.sequence();
+	///
+	/// assert_eq!(seq.maybe_next::<u32>().unwrap(), MaybeParam::Value(1));
+	/// assert_eq!(seq.maybe_next::<u32>().unwrap(), MaybeParam::Null);
+	/// assert_eq!(seq.maybe_next::<u32>().unwrap(), MaybeParam::Absent);
+	/// ```
+	pub fn maybe_next<T>(&mut self) -> Result<MaybeParam<T>, ErrorObjectOwned>
+	where
+		T: Deserialize<'a>,
+	{
+		match self.next_inner::<Option<T>>() {
+			Some(result) => result.map(MaybeParam::from),
+			None => Ok(MaybeParam::Absent),
+		}
+	}
+
+	/// Parse the next parameter to type `T`, or return `default` if the parameter is missing.
+	///
+	/// ```
+	/// # use jsonrpsee_types::params::Params;
+	/// let params = Params::new(Some(r#"[1]"#));
+	/// let mut seq = params.sequence();
+	///
+	/// assert_eq!(seq.next_or(10_u32).unwrap(), 1);
+	/// assert_eq!(seq.next_or(10_u32).unwrap(), 10);
+	/// ```
+	pub fn next_or<T>(&mut self, default: T) -> Result<T, ErrorObjectOwned>
+	where
+		T: Deserialize<'a>,
+	{
+		match self.next_inner() {
+			Some(result) => result,
+			None => Ok(default),
+		}
+	}
+}
+
+/// A parameter which may be missing or `null`, see [`ParamsSequence::maybe_next`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MaybeParam<T> {
+	/// The parameter is missing.
+	Absent,
+	/// The parameter is `null`.
+	Null,
+	/// The value of the parameter.
+	Value(T),
+}
+
+impl<T> MaybeParam<T> {
+	/// Returns whether the parameter is missing.
+	pub fn is_absent(&self) -> bool {
+		matches!(self, Self::Absent)
+	}
+
+	/// Returns whether the parameter is `null`.
+	pub fn is_null(&self) -> bool {
+		matches!(self, Self::Null)
+	}
+
+	/// Convert into an `Option`, where both a missing parameter and `null` are `None`.
+	pub fn into_option(self) -> Option<T> {
+		match self {
+			Self::Value(value) => Some(value),
+			Self::Absent | Self::Null => None,
+		}
+	}
+
+	/// Convert into an `Option`, where a missing parameter is `Some(default)` and `null` is `None`.
+	pub fn absent_or(self, default: T) -> Option<T> {
+		match self {
+			Self::Value(value) => Some(value),
+			Self::Absent => Some(default),
+			Self::Null => None,
+		}
+	}
+}
+
+impl<T> From<Option<T>> for MaybeParam<T> {
+	fn from(value: Option<T>) -> Self {
+		match value {
+			Some(value) => Self::Value(value),
+			None => Self::Null,
+		}
+	}
+}
+
+/// Parser for [`Params`] by name, given as a JSON object.
+///
+/// The fields are looked up by name and the fields which are not looked up are ignored.
+#[derive(Debug, Clone)]
+pub struct ParamsMap<'a>(BTreeMap<String, &'a RawValue>);
+
+impl<'a> ParamsMap<'a> {
+	/// Parse the field `name` to type `T`.
+	///
+	/// ```
+	/// # use jsonrpsee_types::params::Params;
+	/// let params = Params::new(Some(r#"{"name": "foo", "count": 3}"#));
+	/// let map = params.map().unwrap();
+	///
+	/// assert_eq!(map.get::<&str>("name").unwrap(), "foo");
+	/// assert_eq!(map.get::<u32>("count").unwrap(), 3);
+	/// assert!(map.get::<u32>("missing").is_err());
+	/// ```
+	pub fn get<T>(&self, name: &str) -> Result<T, ErrorObjectOwned>
+	where
+		T: Deserialize<'a>,
+	{
+		match self.0.get(name) {
+			Some(value) => parse_field(name, value),
+			None => Err(InvalidParam::new(format!("missing field `{name}`"))
+				.with_name(name)
+				.with_expected::<T>()
+				.into_error()),
+		}
+	}
+
+	/// Parse the optional field `name` to type `Option<T>`.
+	///
+	/// The result will be `None` for `null` and for missing fields.
+	pub fn optional<T>(&self, name: &str) -> Result<Option<T>, ErrorObjectOwned>
+	where
+		T: Deserialize<'a>,
+	{
+		match self.0.get(name) {
+			Some(value) => parse_field(name, value),
+			None => Ok(None),
+		}
+	}
+
+	/// Parse the field `name` to type `T` and distinguish between a missing field and `null`.
+	pub fn maybe<T>(&self, name: &str) -> Result<MaybeParam<T>, ErrorObjectOwned>
+	where
+		T: Deserialize<'a>,
+	{
+		match self.0.get(name) {
+			Some(value) => parse_field::<Option<T>>(name, value).map(MaybeParam::from),
+			None => Ok(MaybeParam::Absent),
+		}
+	}
+
+	/// Returns whether the field `name` is present.
+	pub fn contains(&self, name: &str) -> bool {
+		self.0.contains_key(name)
+	}
+}
+
+fn parse_field<'a, T: Deserialize<'a>>(name: &str, value: &'a RawValue) -> Result<T, ErrorObjectOwned> {
+	serde_json::from_str(value.get()).map_err(|e| {
+		InvalidParam::new(format!("invalid value for field `{name}`: {e}"))
+			.with_name(name)
+			.with_expected::<T>()
+			.into_error()
+	})
+}
+
+/// Parser which maps [`Params`] onto the arguments of a method, either by position or by name.
+///
+/// See [`Params::arguments`].
+#[derive(Debug, Clone)]
+pub enum ParamsArguments<'a> {
+	/// The params are given by position.
+	Sequence(ParamsSequence<'a>),
+	/// The params are given by name.
+	Map(ParamsMap<'a>),
+}
+
+impl<'a> ParamsArguments<'a> {
+	/// Parse the next argument `name` to type `T`.
+	#[allow(clippy::should_implement_trait)]
+	pub fn next<T>(&mut self, name: &str) -> Result<T, ErrorObjectOwned>
+	where
+		T: Deserialize<'a>,
+	{
+		match self {
+			Self::Sequence(seq) => seq.next().map_err(|e| name_invalid_param(e, name)),
+			Self::Map(map) => map.get(name),
+		}
+	}
+
+	/// Parse the next optional argument `name` to type `Option<T>`.
+	///
+	/// The result will be `None` for `null` and for missing arguments.
+	pub fn optional_next<T>(&mut self, name: &str) -> Result<Option<T>, ErrorObjectOwned>
+	where
+		T: Deserialize<'a>,
+	{
+		match self {
+			Self::Sequence(seq) => seq.optional_next().map_err(|e| name_invalid_param(e, name)),
+			Self::Map(map) => map.optional(name),
+		}
+	}
+
+	/// Parse the next argument `name` to type `T` and distinguish between a missing argument and `null`.
+	pub fn maybe_next<T>(&mut self, name: &str) -> Result<MaybeParam<T>, ErrorObjectOwned>
+	where
+		T: Deserialize<'a>,
+	{
+		match self {
+			Self::Sequence(seq) => seq.maybe_next().map_err(|e| name_invalid_param(e, name)),
+			Self::Map(map) => map.maybe(name),
+		}
+	}
+
+	/// Parse the next argument `name` to type `T`, or return `default` if the argument is missing.
+	pub fn next_or<T>(&mut self, name: &str, default: T) -> Result<T, ErrorObjectOwned>
+	where
+		T: Deserialize<'a>,
+	{
+		match self {
+			Self::Sequence(seq) => seq.next_or(default).map_err(|e| name_invalid_param(e, name)),
+			Self::Map(map) if map.contains(name) => map.get(name),
+			Self::Map(_) => Ok(default),
+		}
+	}
+}
+
+/// Add the argument `name` to the [`InvalidParam`] data of an error from a sequence of params.
+fn name_invalid_param(err: ErrorObjectOwned, name: &str) -> ErrorObjectOwned {
+	match err.data_as::<InvalidParam>() {
+		Ok(Some(param)) => param.with_name(name).into_error(),
+		_ => err,
+	}
+}
+
+/// Details of a parameter which couldn't be parsed, given as the `data` of an `Invalid params` error
+/// by the [`Params`] parsers.
+///
+/// A client may obtain it from the error with [`ErrorObject::data_as`].
+///
+/// ```
+/// # use jsonrpsee_types::params::{InvalidParam, Params};
+/// let params = Params::new(Some(r#"[1, "two"]"#));
+/// let mut seq = params.sequence();
+///
+/// assert_eq!(seq.next::<u32>().unwrap(), 1);
+/// let err = seq.next::<u32>().unwrap_err();
+/// let param: InvalidParam = err.data_as().unwrap().unwrap();
+///
+/// assert_eq!(param.position, Some(1));
+/// assert_eq!(param.expected.as_deref(), Some("u32"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InvalidParam {
+	/// Position of the parameter, if known.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub position: Option<usize>,
+	/// Name of the parameter, if known.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub name: Option<String>,
+	/// Name of the expected Rust type, if the parameter is parsed on its own.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub expected: Option<String>,
+	/// Description of the error.
+	pub message: String,
+}
+
+impl InvalidParam {
+	/// Create a new [`InvalidParam`] from the error message.
+	pub fn new(e: impl ToString) -> Self {
+		Self { position: None, name: None, expected: None, message: e.to_string() }
+	}
+
+	/// Locate the parameter which `e` refers to in the params `json`, given either by position or by name.
+	fn from_json_error(json: &str, e: serde_json::Error) -> Self {
+		let mut param = Self::new(&e);
+		if !e.is_data() || e.line() == 0 {
+			return param;
+		}
+
+		let offset =
+			json.split_inclusive('\n').take(e.line() - 1).map(str::len).sum::<usize>() + e.column().saturating_sub(1);
+		let contains = |value: &RawValue| {
+			let start = value.get().as_ptr() as usize - json.as_ptr() as usize;
+			(start..=start + value.get().len()).contains(&offset)
+		};
+
+		if let Ok(seq) = serde_json::from_str::<Vec<&RawValue>>(json) {
+			param.position = seq.into_iter().position(contains);
+		} else if let Ok(map) = serde_json::from_str::<BTreeMap<String, &RawValue>>(json) {
+			param.name = map.into_iter().find(|(_, value)| contains(value)).map(|(name, _)| name);
+		}
+		param
+	}
+
+	/// Set the position of the parameter.
+	pub fn with_position(mut self, position: usize) -> Self {
+		self.position = Some(position);
+		self
+	}
+
+	/// Set the name of the parameter.
+	pub fn with_name(mut self, name: &str) -> Self {
+		self.name = Some(name.to_owned());
+		self
+	}
+
+	/// Set the expected type of the parameter to the name of `T` without the paths of the types,
+	/// for instance `Vec<u64>`.
+	pub fn with_expected<T: ?Sized>(mut self) -> Self {
+		let name = std::any::type_name::<T>();
+		let mut expected = String::with_capacity(name.len());
+		for part in name.split_inclusive(|c: char| !(c.is_alphanumeric() || c == '_' || c == ':')) {
+			expected.push_str(part.rsplit("::").next().unwrap_or(part));
+		}
+		self.expected = Some(expected);
+		self
+	}
+
+	/// Convert into an `Invalid params` error with `self` as data.
+	pub fn into_error(self) -> ErrorObjectOwned {
+		ErrorObject::owned(ErrorCode::InvalidParams.code(), INVALID_PARAMS_MSG, Some(self))
+	}
 }
 
 /// Id of a subscription, communicated by the server.
-#[derive(Debug, PartialEq, Clone, Hash, Eq, Deserialize, Serialize)]
-#[serde(deny_unknown_fields)]
+#[derive(Debug, PartialEq, Clone, Hash, Eq, Serialize)]
 #[serde(untagged)]
 pub enum SubscriptionId<'a> {
 	/// Numeric id
@@ -269,6 +673,20 @@ pub enum SubscriptionId<'a> {
 	Str(Cow<'a, str>),
 }
 
+impl<'de: 'a, 'a> Deserialize<'de> for SubscriptionId<'a> {
+	fn deserialize<D>(deserializer: D) -> Result<SubscriptionId<'a>, D::Error>
+	where
+		D: Deserializer<'de>,
+	{
+		match deserializer.deserialize_any(IdVisitor { other: false })? {
+			Id::Number(n) => Ok(SubscriptionId::Num(n)),
+			Id::Str(s) => Ok(SubscriptionId::Str(s)),
+			Id::Null => Err(de::Error::invalid_type(Unexpected::Unit, &"a number or a string")),
+			Id::Other(_) => Err(de::Error::invalid_type(Unexpected::Other("JSON value"), &"a number or a string")),
+		}
+	}
+}
+
 impl<'a> From<SubscriptionId<'a>> for JsonValue {
 	fn from(sub_id: SubscriptionId) -> Self {
 		match sub_id {
@@ -337,17 +755,224 @@ pub enum InvalidRequestId {
 }
 
 /// Request Id
-#[derive(Debug, PartialEq, Clone, Hash, Eq, Deserialize, Serialize, PartialOrd, Ord)]
-#[serde(deny_unknown_fields)]
+#[derive(Debug, PartialEq, Clone, Hash, Eq, Serialize, PartialOrd, Ord)]
 #[serde(untagged)]
+#[non_exhaustive]
 pub enum Id<'a> {
 	/// Null
 	Null,
 	/// Numeric id
 	Number(u64),
 	/// String id
-	#[serde(borrow)]
 	Str(Cow<'a, str>),
+	/// Any other JSON value, such as an object which carries routing data or a negative number.
+	///
+	/// These ids are not allowed by the JSON-RPC specification, such that they are only understood by
+	/// the servers which accept them with `IdPolicy::accept_other` and are rejected when an [`Id`] is
+	/// deserialized, except as the id of a [`Response`](crate::Response).
+	/// See [`Id::from`] or [`RawId::new`] to create one.
+	Other(RawId<'a>),
+}
+
+/// The JSON of an [`Id::Other`].
+///
+/// It's serialized exactly as it was created, including its whitespace and the order of the keys
+/// of its objects, which is why the ids are also compared by their JSON text.
+#[derive(Debug, Clone)]
+pub struct RawId<'a>(Cow<'a, RawValue>);
+
+impl<'a> RawId<'a> {
+	/// Create an id from its JSON.
+	pub fn new(json: Cow<'a, RawValue>) -> Self {
+		Self(json)
+	}
+
+	/// Get the JSON of the id.
+	pub fn get(&self) -> &str {
+		self.0.get()
+	}
+
+	/// Parse the JSON of the id into `T`.
+	pub fn parse<T: DeserializeOwned>(&self) -> Result<T, serde_json::Error> {
+		serde_json::from_str(self.get())
+	}
+
+	/// Convert `RawId<'a>` to `RawId<'static>`.
+	pub fn into_owned(self) -> RawId<'static> {
+		RawId(Cow::Owned(self.0.into_owned()))
+	}
+}
+
+impl PartialEq for RawId<'_> {
+	fn eq(&self, other: &Self) -> bool {
+		self.get() == other.get()
+	}
+}
+
+impl Eq for RawId<'_> {}
+
+impl std::hash::Hash for RawId<'_> {
+	fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+		self.get().hash(state)
+	}
+}
+
+impl PartialOrd for RawId<'_> {
+	fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl Ord for RawId<'_> {
+	fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+		self.get().cmp(other.get())
+	}
+}
+
+impl Serialize for RawId<'_> {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		self.0.serialize(serializer)
+	}
+}
+
+/// Create an id from a JSON value, which is an [`Id::Other`] unless the value is `null`,
+/// an unsigned integer or a string.
+///
+/// The JSON of an object is written in the order of its keys in the [`JsonValue`], which is
+/// only the order in which they were inserted if the `preserve_order` feature of `serde_json` is enabled.
+///
+/// # Examples
+///
+/// ```
+/// use jsonrpsee_types::Id;
+///
+/// let id = Id::from(serde_json::json!({ "route": 3 }));
+/// assert_eq!(serde_json::to_string(&id).unwrap(), r#"{"route":3}"#);
+/// assert_eq!(id.as_other().unwrap().parse::<serde_json::Value>().unwrap()["route"], 3);
+///
+/// assert_eq!(Id::from(serde_json::json!(1)), Id::Number(1));
+/// ```
+impl From<JsonValue> for Id<'static> {
+	fn from(value: JsonValue) -> Self {
+		match value {
+			JsonValue::Null => Id::Null,
+			JsonValue::String(s) => Id::Str(Cow::Owned(s)),
+			JsonValue::Number(n) if n.as_u64().is_some() => Id::Number(n.as_u64().expect("checked above; qed")),
+			other => {
+				let raw = serde_json::value::to_raw_value(&other).expect("a JSON value is valid JSON; qed");
+				Id::Other(RawId(Cow::Owned(raw)))
+			}
+		}
+	}
+}
+
+/// Visitor of [`Id`] and [`SubscriptionId`] which are not deserialized as untagged enums
+/// such that numbers are also understood if `serde_json/arbitrary_precision` is enabled.
+///
+/// This replaces the `#[serde(deny_unknown_fields)]` of the derived implementations, which
+/// didn't have any effect on the newtype variants: objects are rejected by the visitor unless
+/// they're arbitrary precision numbers or other ids are accepted.
+struct IdVisitor {
+	/// Whether other JSON values are accepted as [`Id::Other`].
+	other: bool,
+}
+
+impl IdVisitor {
+	fn other<E: de::Error>(&self, value: JsonValue, unexpected: Unexpected) -> Result<Id<'static>, E> {
+		if self.other {
+			Ok(Id::from(value))
+		} else {
+			Err(de::Error::invalid_type(unexpected, self))
+		}
+	}
+}
+
+impl<'de> Visitor<'de> for IdVisitor {
+	type Value = Id<'de>;
+
+	fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+		if self.other {
+			formatter.write_str("a JSON value")
+		} else {
+			formatter.write_str("null, an unsigned integer or a string")
+		}
+	}
+
+	fn visit_bool<E: de::Error>(self, b: bool) -> Result<Self::Value, E> {
+		self.other(JsonValue::from(b), Unexpected::Bool(b))
+	}
+
+	fn visit_f64<E: de::Error>(self, n: f64) -> Result<Self::Value, E> {
+		self.other(JsonValue::from(n), Unexpected::Float(n))
+	}
+
+	fn visit_seq<A: de::SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+		let value = JsonValue::deserialize(de::value::SeqAccessDeserializer::new(seq))?;
+		self.other(value, Unexpected::Seq)
+	}
+
+	fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+		Ok(Id::Null)
+	}
+
+	fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+		Ok(Id::Null)
+	}
+
+	fn visit_u64<E: de::Error>(self, n: u64) -> Result<Self::Value, E> {
+		Ok(Id::Number(n))
+	}
+
+	fn visit_i64<E: de::Error>(self, n: i64) -> Result<Self::Value, E> {
+		match u64::try_from(n) {
+			Ok(n) => Ok(Id::Number(n)),
+			Err(_) if self.other => Ok(Id::from(JsonValue::from(n))),
+			Err(_) => Err(de::Error::invalid_value(Unexpected::Signed(n), &self)),
+		}
+	}
+
+	fn visit_borrowed_str<E: de::Error>(self, s: &'de str) -> Result<Self::Value, E> {
+		Ok(Id::Str(Cow::Borrowed(s)))
+	}
+
+	fn visit_str<E: de::Error>(self, s: &str) -> Result<Self::Value, E> {
+		Ok(Id::Str(Cow::Owned(s.to_owned())))
+	}
+
+	fn visit_string<E: de::Error>(self, s: String) -> Result<Self::Value, E> {
+		Ok(Id::Str(Cow::Owned(s)))
+	}
+
+	// A number is deserialized as a map with a private key if `arbitrary_precision` is enabled.
+	fn visit_map<A: de::MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+		if self.other {
+			return JsonValue::deserialize(de::value::MapAccessDeserializer::new(map)).map(Id::from);
+		}
+
+		let n = serde_json::Number::deserialize(de::value::MapAccessDeserializer::new(map))?;
+		n.as_u64().map(Id::Number).ok_or_else(|| de::Error::invalid_value(Unexpected::Other("number"), &self))
+	}
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for Id<'a> {
+	fn deserialize<D>(deserializer: D) -> Result<Id<'a>, D::Error>
+	where
+		D: Deserializer<'de>,
+	{
+		deserializer.deserialize_any(IdVisitor { other: false })
+	}
+}
+
+/// Deserializes the id of a response, which may also be an [`Id::Other`] because the id of a
+/// response is only matched against the ids that the client generated itself.
+pub(crate) struct ResponseId;
+
+impl<'de> de::DeserializeSeed<'de> for ResponseId {
+	type Value = Id<'de>;
+
+	fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+		deserializer.deserialize_any(IdVisitor { other: true })
+	}
 }
 
 impl<'a> Id<'a> {
@@ -375,6 +1000,14 @@ impl<'a> Id<'a> {
 		}
 	}
 
+	/// If the ID is another JSON value, returns its JSON. Returns None otherwise.
+	pub fn as_other(&self) -> Option<&RawId<'a>> {
+		match self {
+			Self::Other(id) => Some(id),
+			_ => None,
+		}
+	}
+
 	/// Convert `Id<'a>` to `Id<'static>` so that it can be moved across threads.
 	///
 	/// This can cause an allocation if the id is a string.
@@ -383,6 +1016,7 @@ impl<'a> Id<'a> {
 			Id::Null => Id::Null,
 			Id::Number(num) => Id::Number(num),
 			Id::Str(s) => Id::Str(Cow::Owned(s.into_owned())),
+			Id::Other(id) => Id::Other(id.into_owned()),
 		}
 	}
 
@@ -392,6 +1026,7 @@ impl<'a> Id<'a> {
 			Id::Null => Err(InvalidRequestId::Invalid("null".to_string())),
 			Id::Number(num) => Ok(*num),
 			Id::Str(s) => s.parse().map_err(|_| InvalidRequestId::Invalid(s.as_ref().to_owned())),
+			Id::Other(id) => Err(InvalidRequestId::Invalid(id.get().to_owned())),
 		}
 	}
 }
@@ -402,17 +1037,21 @@ impl<'a> std::fmt::Display for Id<'a> {
 			Id::Null => f.write_str("null"),
 			Id::Number(n) => f.write_str(&n.to_string()),
 			Id::Str(s) => f.write_str(s),
+			Id::Other(id) => f.write_str(id.get()),
 		}
 	}
 }
 
 fn invalid_params(e: impl ToString) -> ErrorObjectOwned {
-	ErrorObject::owned(ErrorCode::InvalidParams.code(), INVALID_PARAMS_MSG, Some(e.to_string()))
+	InvalidParam::new(e).into_error()
 }
 
 #[cfg(test)]
 mod test {
-	use super::{Cow, Id, JsonValue, Params, SubscriptionId, TwoPointZero};
+	use super::{
+		Cow, Id, InvalidParam, JsonValue, MaybeParam, Params, ParamsArguments, RawId, RawValue, SubscriptionId,
+		TwoPointZero,
+	};
 	use crate::response::SubscriptionPayload;
 
 	#[test]
@@ -441,6 +1080,12 @@ mod test {
 		let s = r#"[null, 0, 2, "\"3"]"#;
 		let deserialized: Vec<Id> = serde_json::from_str(s).unwrap();
 		assert_eq!(deserialized, vec![Id::Null, Id::Number(0), Id::Number(2), Id::Str("\"3".into())]);
+
+		assert!(serde_json::from_str::<Id>("-1").is_err());
+		assert!(serde_json::from_str::<Id>("1.5").is_err());
+		assert!(serde_json::from_str::<Id>(r#"{"id":1}"#).is_err());
+		assert!(serde_json::from_str::<SubscriptionId>("-1").is_err());
+		assert!(serde_json::from_str::<SubscriptionId>("null").is_err());
 	}
 
 	#[test]
@@ -451,6 +1096,26 @@ mod test {
 		assert_eq!(serialized, r#"[null,0,2,3,"\"3","test"]"#);
 	}
 
+	#[test]
+	fn other_id_round_trip() {
+		let json = [r#"{"b": 1, "a": {"c": null}}"#, "true", r#"[1,"a"]"#];
+		for json in json {
+			let raw: &RawValue = serde_json::from_str(json).unwrap();
+			let id = Id::Other(RawId::new(Cow::Borrowed(raw)));
+			assert_eq!(serde_json::to_string(&id).unwrap(), json);
+			assert_eq!(id.clone().into_owned(), id);
+		}
+
+		assert_eq!(Id::from(serde_json::json!("a")), Id::Str("a".into()));
+		assert!(Id::from(serde_json::json!(true)).as_other().unwrap().parse::<bool>().unwrap());
+
+		let rp: crate::Response<u8> = serde_json::from_str(r#"{"jsonrpc":"2.0","result":1,"id":{"n":1}}"#).unwrap();
+		assert_eq!(rp.id, Id::from(serde_json::json!({ "n": 1 })));
+		let rp: crate::Response<u8> = serde_json::from_str(r#"{"jsonrpc":"2.0","result":1,"id":-1}"#).unwrap();
+		assert_eq!(rp.id.as_other().unwrap().get(), "-1");
+		assert!(serde_json::from_str::<SubscriptionId>("[1]").is_err());
+	}
+
 	#[test]
 	fn params_parse() {
 		let none = Params::new(None);
@@ -585,4 +1250,127 @@ mod test {
 		assert_eq!(seq.optional_next::<Vec<Vec<u32>>>().unwrap(), Some(vec![vec![5], vec![6, 7], vec![]]));
 		assert_eq!(seq.optional_next::<serde_json::Value>().unwrap(), Some(serde_json::json!({"named":7})));
 	}
+
+	#[test]
+	fn params_map_works() {
+		let params = Params::new(Some(r#"{"name": "foo", "count": 3, "flag": null}"#));
+		let map = params.map().unwrap();
+
+		assert_eq!(map.get::<&str>("name").unwrap(), "foo");
+		assert_eq!(map.optional::<u32>("count").unwrap(), Some(3));
+		assert_eq!(map.optional::<bool>("flag").unwrap(), None);
+		assert_eq!(map.optional::<bool>("missing").unwrap(), None);
+		assert!(map.contains("flag"));
+		assert!(!map.contains("missing"));
+
+		let err = map.get::<u32>("name").unwrap_err();
+		assert!(err.data().unwrap().get().contains("invalid value for field `name`"));
+		let err = map.get::<u32>("missing").unwrap_err();
+		assert!(err.data().unwrap().get().contains("missing field `missing`"));
+
+		assert!(Params::new(None).map().unwrap().optional::<u32>("count").unwrap().is_none());
+		assert!(Params::new(Some("[1, 2]")).map().is_err());
+	}
+
+	#[test]
+	fn params_arguments_by_position_or_name() {
+		let by_position = Params::new(Some(r#"["foo", 1]"#));
+		let by_name = Params::new(Some(r#"{"count": 1, "name": "foo"}"#));
+
+		for params in [&by_position, &by_name] {
+			let mut args = params.arguments().unwrap();
+			assert_eq!(args.next::<&str>("name").unwrap(), "foo");
+			assert_eq!(args.next::<u64>("count").unwrap(), 1);
+			assert_eq!(args.optional_next::<bool>("flag").unwrap(), None);
+		}
+
+		assert!(matches!(by_position.arguments().unwrap(), ParamsArguments::Sequence(_)));
+		assert!(matches!(by_name.arguments().unwrap(), ParamsArguments::Map(_)));
+		assert!(matches!(Params::new(None).arguments().unwrap(), ParamsArguments::Sequence(_)));
+	}
+
+	#[test]
+	fn params_parse_with_defaults() {
+		#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+		struct Filter {
+			from: u64,
+			to: Option<u64>,
+		}
+
+		let defaults = || Filter { from: 0, to: Some(100) };
+
+		let params = Params::new(Some(r#"{"from": 5}"#));
+		assert_eq!(params.parse_with_defaults(defaults()).unwrap(), Filter { from: 5, to: Some(100) });
+
+		let params = Params::new(Some(r#"{"from": 5, "to": null}"#));
+		assert_eq!(params.parse_with_defaults(defaults()).unwrap(), Filter { from: 5, to: None });
+
+		let params = Params::new(Some(r#"[5, 10]"#));
+		assert_eq!(params.parse_with_defaults(defaults()).unwrap(), Filter { from: 5, to: Some(10) });
+
+		let params = Params::new(Some(r#"[]"#));
+		assert_eq!(params.parse_with_defaults::<(u64, bool)>((1, true)).unwrap(), (1, true));
+
+		let params = Params::new(Some(r#"["5"]"#));
+		assert!(params.parse_with_defaults::<(u64, bool)>((1, true)).is_err());
+	}
+
+	#[test]
+	fn params_distinguish_absent_from_null() {
+		let params = Params::new(Some(r#"[1, null]"#));
+		let mut args = params.arguments().unwrap();
+		assert_eq!(args.maybe_next::<u32>("a").unwrap(), MaybeParam::Value(1));
+		assert_eq!(args.maybe_next::<u32>("b").unwrap(), MaybeParam::Null);
+		assert_eq!(args.maybe_next::<u32>("c").unwrap(), MaybeParam::Absent);
+
+		let params = Params::new(Some(r#"{"a": 1, "b": null}"#));
+		let mut args = params.arguments().unwrap();
+		assert_eq!(args.maybe_next::<u32>("a").unwrap(), MaybeParam::Value(1));
+		assert_eq!(args.maybe_next::<u32>("b").unwrap(), MaybeParam::Null);
+		assert_eq!(args.maybe_next::<u32>("c").unwrap(), MaybeParam::Absent);
+		assert_eq!(args.next_or("c", 7_u32).unwrap(), 7);
+		assert!(args.next_or("b", 7_u32).is_err());
+
+		assert_eq!(MaybeParam::Absent.absent_or(1), Some(1));
+		assert_eq!(MaybeParam::Null.absent_or(1), None);
+		assert_eq!(MaybeParam::Value(2).absent_or(1), Some(2));
+		assert_eq!(MaybeParam::<u32>::Null.into_option(), None);
+	}
+
+	#[test]
+	fn params_invalid_param_data() {
+		let invalid = |err: crate::ErrorObjectOwned| err.data_as::<InvalidParam>().unwrap().unwrap();
+
+		let params = Params::new(Some(r#"[1, "two", 3]"#));
+		let param = invalid(params.parse::<Vec<u32>>().unwrap_err());
+		assert_eq!((param.position, param.name, param.expected), (Some(1), None, None));
+
+		let mut args = params.arguments().unwrap();
+		args.next::<u32>("one").unwrap();
+		let param = invalid(args.next::<u32>("two").unwrap_err());
+		assert_eq!(
+			(param.position, param.name.as_deref(), param.expected.as_deref()),
+			(Some(1), Some("two"), Some("u32"))
+		);
+
+		let mut seq = params.sequence();
+		let param = invalid(seq.next::<Vec<String>>().unwrap_err());
+		assert_eq!(param.expected.as_deref(), Some("Vec<String>"));
+
+		let params = Params::new(Some(r#"[1]"#));
+		let mut seq = params.sequence();
+		seq.next::<u32>().unwrap();
+		let param = invalid(seq.next::<u32>().unwrap_err());
+		assert_eq!((param.position, param.message.as_str()), (Some(1), "No more params"));
+
+		let params = Params::new(Some(r#"{"a": 1, "b": true}"#));
+		let param = invalid(params.parse::<std::collections::BTreeMap<String, u32>>().unwrap_err());
+		assert_eq!((param.position, param.name.as_deref(), param.expected), (None, Some("b"), None));
+
+		let map = params.map().unwrap();
+		let param = invalid(map.get::<u32>("b").unwrap_err());
+		assert_eq!((param.name.as_deref(), param.expected.as_deref()), (Some("b"), Some("u32")));
+		let param = invalid(map.get::<u32>("c").unwrap_err());
+		assert_eq!((param.name.as_deref(), param.message.as_str()), (Some("c"), "missing field `c`"));
+	}
 }
@@ -240,6 +240,12 @@ mod test {
 		assert!(serde_json::from_str::<Request>(ser).is_err());
 	}
 
+	#[test]
+	fn deserialize_call_truncated_id_should_fail() {
+		let ser = r#"{"jsonrpc":"2.0","method":"say_hello","params":[],"id":"#;
+		assert!(serde_json::from_str::<Request>(ser).is_err());
+	}
+
 	#[test]
 	fn deserialize_invalid_request() {
 		let s = r#"{"id":120,"method":"my_method","params":["foo", "bar"],"extra_field":[]}"#;
@@ -31,11 +31,12 @@ use std::fmt;
 use std::marker::PhantomData;
 
 use crate::error::ErrorCode;
-use crate::params::{Id, SubscriptionId, TwoPointZero};
+use crate::params::{Id, ResponseId, SubscriptionId, TwoPointZero};
 use crate::request::Notification;
 use crate::{ErrorObject, ErrorObjectOwned};
 use serde::ser::SerializeStruct;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::value::RawValue;
 
 /// JSON-RPC response object as defined in the [spec](https://www.jsonrpc.org/specification#response_object).
 pub struct Response<'a, T: Clone> {
@@ -125,6 +126,95 @@ pub struct SubscriptionPayloadError<'a, T> {
 	pub error: T,
 }
 
+/// Subscription notification with the raw JSON of its result, as sent by servers to the subscribers.
+///
+/// It's the flattened form of a [`SubscriptionResponse`], for custom clients and proxies which route the
+/// notifications by their subscription ID and parse the result later or forward it as is.
+///
+/// # Examples
+///
+/// ```
+/// use jsonrpsee_types::{SubscriptionId, SubscriptionNotification};
+///
+/// let json = r#"{"jsonrpc":"2.0","method":"blocks","params":{"subscription":7,"result":{"height":1}}}"#;
+///
+/// let notif = SubscriptionNotification::parse(json).unwrap();
+/// assert_eq!(notif.method, "blocks");
+/// assert_eq!(notif.subscription_id, SubscriptionId::Num(7));
+/// assert_eq!(notif.result.get(), r#"{"height":1}"#);
+/// assert_eq!(notif.to_string(), json);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SubscriptionNotification<'a> {
+	/// Name of the method of the notification.
+	pub method: StdCow<'a, str>,
+	/// Subscription ID.
+	pub subscription_id: SubscriptionId<'a>,
+	/// Result.
+	pub result: &'a RawValue,
+}
+
+impl<'a> SubscriptionNotification<'a> {
+	/// Create a new [`SubscriptionNotification`].
+	pub fn new(method: impl Into<StdCow<'a, str>>, subscription_id: SubscriptionId<'a>, result: &'a RawValue) -> Self {
+		Self { method: method.into(), subscription_id, result }
+	}
+
+	/// Parse a subscription notification, borrowing its method, subscription ID and result from `json`.
+	pub fn parse(json: &'a str) -> Result<Self, serde_json::Error> {
+		serde_json::from_str(json)
+	}
+}
+
+impl<'a> From<SubscriptionResponse<'a, &'a RawValue>> for SubscriptionNotification<'a> {
+	fn from(notif: SubscriptionResponse<'a, &'a RawValue>) -> Self {
+		Self { method: notif.method, subscription_id: notif.params.subscription, result: notif.params.result }
+	}
+}
+
+impl<'a> From<SubscriptionNotification<'a>> for SubscriptionResponse<'a, &'a RawValue> {
+	fn from(notif: SubscriptionNotification<'a>) -> Self {
+		Notification::new(
+			notif.method,
+			SubscriptionPayload { subscription: notif.subscription_id, result: notif.result },
+		)
+	}
+}
+
+impl<'a> Serialize for SubscriptionNotification<'a> {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		S: Serializer,
+	{
+		#[derive(Serialize)]
+		struct Params<'b> {
+			subscription: &'b SubscriptionId<'b>,
+			result: &'b RawValue,
+		}
+
+		let mut s = serializer.serialize_struct("SubscriptionNotification", 3)?;
+		s.serialize_field("jsonrpc", &TwoPointZero)?;
+		s.serialize_field("method", &self.method)?;
+		s.serialize_field("params", &Params { subscription: &self.subscription_id, result: self.result })?;
+		s.end()
+	}
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for SubscriptionNotification<'a> {
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: Deserializer<'de>,
+	{
+		SubscriptionResponse::<&'a RawValue>::deserialize(deserializer).map(Into::into)
+	}
+}
+
+impl<'a> fmt::Display for SubscriptionNotification<'a> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(&serde_json::to_string(&self).expect("valid JSON; qed"))
+	}
+}
+
 /// Represent the payload of the JSON-RPC response object
 ///
 /// It can be:
@@ -273,7 +363,7 @@ where
 							if id.is_some() {
 								return Err(serde::de::Error::duplicate_field("id"));
 							}
-							id = Some(map.next_value()?);
+							id = Some(map.next_value_seed(ResponseId)?);
 						}
 						Field::Jsonrpc => {
 							if jsonrpc.is_some() {
@@ -340,7 +430,7 @@ where
 
 #[cfg(test)]
 mod tests {
-	use super::{Id, Response, TwoPointZero};
+	use super::{Id, Response, SubscriptionNotification, SubscriptionResponse, TwoPointZero};
 	use crate::{response::ResponsePayload, ErrorObjectOwned};
 
 	#[test]
@@ -421,4 +511,37 @@ mod tests {
 		assert_eq!(dsr.payload, exp.payload);
 		assert_eq!(dsr.id, exp.id);
 	}
+
+	#[test]
+	fn deserialize_borrowed_subscription_response() {
+		let raw =
+			r#"{"jsonrpc":"2.0","method":"sub","params":{"subscription":"D3wwzU6vvoUUYehv","result":{"a":[1,2]}}}"#;
+		let dsr: SubscriptionResponse<&serde_json::value::RawValue> = serde_json::from_str(raw).unwrap();
+		assert!(matches!(dsr.method, std::borrow::Cow::Borrowed("sub")));
+		assert!(matches!(
+			dsr.params.subscription,
+			crate::SubscriptionId::Str(std::borrow::Cow::Borrowed("D3wwzU6vvoUUYehv"))
+		));
+		assert_eq!(dsr.params.result.get(), r#"{"a":[1,2]}"#);
+	}
+
+	#[test]
+	fn subscription_notification_round_trip() {
+		let raw = r#"{"jsonrpc":"2.0","method":"sub","params":{"subscription":"D3wwzU6vvoUUYehv","result":[1,2]}}"#;
+		let notif = SubscriptionNotification::parse(raw).unwrap();
+		assert!(matches!(notif.method, std::borrow::Cow::Borrowed("sub")));
+		assert_eq!(notif.subscription_id, crate::SubscriptionId::Str("D3wwzU6vvoUUYehv".into()));
+		assert_eq!(notif.result.get(), "[1,2]");
+		assert_eq!(serde_json::to_string(&notif).unwrap(), raw);
+
+		let result = serde_json::value::to_raw_value(&"hello").unwrap();
+		let notif = SubscriptionNotification::new("sub", crate::SubscriptionId::Num(1), &result);
+		let exp = r#"{"jsonrpc":"2.0","method":"sub","params":{"subscription":1,"result":"hello"}}"#;
+		assert_eq!(notif.to_string(), exp);
+
+		let dsr: SubscriptionResponse<&serde_json::value::RawValue> = serde_json::from_str(exp).unwrap();
+		assert_eq!(SubscriptionNotification::from(dsr).to_string(), exp);
+
+		assert!(SubscriptionNotification::parse(r#"{"jsonrpc":"2.0","method":"sub","params":[1]}"#).is_err());
+	}
 }
@@ -0,0 +1,159 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any
+// person obtaining a copy of this software and associated
+// documentation files (the "Software"), to deal in the
+// Software without restriction, including without
+// limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software
+// is furnished to do so, subject to the following
+// conditions:
+//
+// The above copyright notice and this permission notice
+// shall be included in all copies or substantial portions
+// of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF
+// ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED
+// TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A
+// PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT
+// SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY
+// CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+// IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Conversions between [JSON-RPC 1.0](https://www.jsonrpc.org/specification_v1) and JSON-RPC 2.0 messages.
+//!
+//! A 1.0 request has no `jsonrpc` member and it's a notification if its `id` is `null`, while a 1.0 response
+//! has both the `result` and `error` members, of which the one that doesn't apply is `null`.
+//!
+//! JSON-RPC 1.0 has no batches, but apart from [`request_to_v2`](crate::v1::request_to_v2) the conversions
+//! are applied to the members of a batch one by one. Messages which aren't JSON objects are left as they are.
+
+use serde_json::{Map, Value};
+
+const JSONRPC: &str = "jsonrpc";
+const ID: &str = "id";
+const RESULT: &str = "result";
+const ERROR: &str = "error";
+
+/// Convert a 1.0 request into a 2.0 request.
+///
+/// Returns `None` if the message isn't a 1.0 request.
+pub fn request_to_v2(request: &[u8]) -> Option<Vec<u8>> {
+	let mut request: Map<String, Value> = serde_json::from_slice(request).ok()?;
+
+	if request.contains_key(JSONRPC) || !request.contains_key("method") {
+		return None;
+	}
+
+	request.insert(JSONRPC.into(), Value::from("2.0"));
+	if request.get(ID).is_some_and(Value::is_null) {
+		request.remove(ID);
+	}
+
+	serde_json::to_vec(&request).ok()
+}
+
+/// Convert a 2.0 request into a 1.0 request.
+pub fn request_to_v1(request: &str) -> String {
+	convert(request, |request| {
+		request.remove(JSONRPC);
+		request.entry(ID).or_insert(Value::Null);
+	})
+}
+
+/// Convert a 2.0 response into a 1.0 response.
+pub fn response_to_v1(response: &str) -> String {
+	convert(response, |response| {
+		response.remove(JSONRPC);
+		response.entry(RESULT).or_insert(Value::Null);
+		response.entry(ERROR).or_insert(Value::Null);
+	})
+}
+
+/// Convert a 1.0 response into a 2.0 response.
+pub fn response_to_v2(response: &str) -> String {
+	convert(response, |response| {
+		response.insert(JSONRPC.into(), Value::from("2.0"));
+		if response.get(ERROR).is_some_and(Value::is_null) {
+			response.remove(ERROR);
+		} else if response.get(RESULT).is_some_and(Value::is_null) {
+			response.remove(RESULT);
+		}
+	})
+}
+
+fn convert(msg: &str, f: impl Fn(&mut Map<String, Value>)) -> String {
+	let Ok(mut value) = serde_json::from_str::<Value>(msg) else {
+		return msg.to_owned();
+	};
+
+	match &mut value {
+		Value::Object(obj) => f(obj),
+		Value::Array(batch) => batch.iter_mut().filter_map(Value::as_object_mut).for_each(&f),
+		_ => return msg.to_owned(),
+	}
+
+	serde_json::to_string(&value).expect("JSON serialization infallible; qed")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn json(s: &str) -> Value {
+		serde_json::from_str(s).unwrap()
+	}
+
+	#[test]
+	fn request_to_v2_works() {
+		let req = request_to_v2(br#"{"method":"getblockcount","params":[],"id":1}"#).unwrap();
+		assert_eq!(
+			serde_json::from_slice::<Value>(&req).unwrap(),
+			json(r#"{"jsonrpc":"2.0","method":"getblockcount","params":[],"id":1}"#)
+		);
+
+		// A `null` id is a notification.
+		let req = request_to_v2(br#"{"method":"ping","params":[],"id":null}"#).unwrap();
+		assert_eq!(
+			serde_json::from_slice::<Value>(&req).unwrap(),
+			json(r#"{"jsonrpc":"2.0","method":"ping","params":[]}"#)
+		);
+
+		assert!(request_to_v2(br#"{"jsonrpc":"2.0","method":"ping","id":1}"#).is_none());
+		assert!(request_to_v2(br#"[{"method":"ping","id":1}]"#).is_none());
+		assert!(request_to_v2(br#"{"result":1,"id":1}"#).is_none());
+	}
+
+	#[test]
+	fn request_to_v1_works() {
+		let req = request_to_v1(r#"{"jsonrpc":"2.0","method":"ping","params":[1],"id":1}"#);
+		assert_eq!(json(&req), json(r#"{"method":"ping","params":[1],"id":1}"#));
+
+		let notif = request_to_v1(r#"{"jsonrpc":"2.0","method":"ping"}"#);
+		assert_eq!(json(&notif), json(r#"{"method":"ping","id":null}"#));
+	}
+
+	#[test]
+	fn response_roundtrip_works() {
+		let success = r#"{"jsonrpc":"2.0","result":1,"id":1}"#;
+		let v1 = response_to_v1(success);
+		assert_eq!(json(&v1), json(r#"{"result":1,"error":null,"id":1}"#));
+		assert_eq!(json(&response_to_v2(&v1)), json(success));
+
+		let failure = r#"{"jsonrpc":"2.0","error":{"code":-1,"message":"a"},"id":1}"#;
+		let v1 = response_to_v1(failure);
+		assert_eq!(json(&v1), json(r#"{"result":null,"error":{"code":-1,"message":"a"},"id":1}"#));
+		assert_eq!(json(&response_to_v2(&v1)), json(failure));
+
+		// A successful call may return `null`.
+		let null = r#"{"jsonrpc":"2.0","result":null,"id":1}"#;
+		assert_eq!(json(&response_to_v2(&response_to_v1(null))), json(null));
+
+		let batch = response_to_v2(r#"[{"result":1,"error":null,"id":1},"x"]"#);
+		assert_eq!(json(&batch), json(r#"[{"jsonrpc":"2.0","result":1,"id":1},"x"]"#));
+	}
+}